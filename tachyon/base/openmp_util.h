@@ -1,6 +1,8 @@
 #ifndef TACHYON_BASE_OPENMP_UTIL_H_
 #define TACHYON_BASE_OPENMP_UTIL_H_
 
+#include <stddef.h>
+
 #include <algorithm>
 #include <optional>
 
@@ -14,11 +16,16 @@
 #define OPENMP_PARALLEL_NESTED_FOR(expr) \
   _Pragma("omp parallel for collapse(2)") for (expr)
 #define OPENMP_FOR(expr) _Pragma("omp for") for (expr)
+#define OPENMP_PRAGMA(x) _Pragma(#x)
+// Like |OPENMP_PARALLEL_FOR|, but uses at most |n| threads.
+#define OPENMP_PARALLEL_FOR_NUM_THREADS(n, expr) \
+  OPENMP_PRAGMA(omp parallel for num_threads(n)) for (expr)
 #else
 #define OPENMP_CONSTEXPR constexpr
 #define OPENMP_PARALLEL_FOR(expr) for (expr)
 #define OPENMP_PARALLEL_NESTED_FOR(expr) for (expr)
 #define OPENMP_FOR(expr) for (expr)
+#define OPENMP_PARALLEL_FOR_NUM_THREADS(n, expr) for (expr)
 #endif  // defined(TACHYON_HAS_OPENMP)
 
 namespace tachyon::base {
@@ -39,6 +46,30 @@ size_t GetNumElementsPerThread(const Container& container,
              : size;
 }
 
+// Makes the parallel regions the calling thread starts run on |num_threads|
+// threads until it goes out of scope, and then restores the previous number.
+// OpenMP keeps this number per thread. 0 leaves it as is.
+class ScopedNumThreads {
+ public:
+  explicit ScopedNumThreads(size_t num_threads) {
+#if defined(TACHYON_HAS_OPENMP)
+    if (num_threads == 0) return;
+    previous_ = omp_get_max_threads();
+    omp_set_num_threads(static_cast<int>(num_threads));
+#endif  // defined(TACHYON_HAS_OPENMP)
+  }
+  ScopedNumThreads(const ScopedNumThreads& other) = delete;
+  ScopedNumThreads& operator=(const ScopedNumThreads& other) = delete;
+  ~ScopedNumThreads() {
+#if defined(TACHYON_HAS_OPENMP)
+    if (previous_ != 0) omp_set_num_threads(previous_);
+#endif  // defined(TACHYON_HAS_OPENMP)
+  }
+
+ private:
+  int previous_ = 0;
+};
+
 }  // namespace tachyon::base
 
 #endif  // TACHYON_BASE_OPENMP_UTIL_H_
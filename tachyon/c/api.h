@@ -1,6 +1,8 @@
 #ifndef TACHYON_C_API_H_
 #define TACHYON_C_API_H_
 
+#include "tachyon/c/device_memory.h"
+#include "tachyon/c/logging.h"
 #include "tachyon/c/math/elliptic_curves/bls12/bls12_381/fq.h"
 #include "tachyon/c/math/elliptic_curves/bls12/bls12_381/fr.h"
 #include "tachyon/c/math/elliptic_curves/bls12/bls12_381/g1.h"
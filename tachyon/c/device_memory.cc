@@ -0,0 +1,20 @@
+#include "tachyon/c/device_memory.h"
+
+#if TACHYON_CUDA
+#include "tachyon/device/gpu/gpu_enums.h"
+#include "tachyon/device/gpu/gpu_memory.h"
+#endif
+
+bool tachyon_get_device_memory_in_use(size_t* bytes) {
+#if TACHYON_CUDA
+  size_t free = 0;
+  size_t total = 0;
+  if (tachyon::device::gpu::GpuMemGetInfo(&free, &total) != gpuSuccess) {
+    return false;
+  }
+  *bytes = total - free;
+  return true;
+#else
+  return false;
+#endif
+}
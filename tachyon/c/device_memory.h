@@ -0,0 +1,34 @@
+#ifndef TACHYON_C_DEVICE_MEMORY_H_
+#define TACHYON_C_DEVICE_MEMORY_H_
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#include "tachyon/c/export.h"
+
+/**
+ * @file device_memory.h
+ * @brief How much memory is in use on the GPU.
+ *
+ * Bindings sample this while proving to report the device memory a proof
+ * takes, which tachyon doesn't track per allocation.
+ */
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * @brief Gets the bytes of memory in use on the current GPU, by any process.
+ *
+ * @param bytes Where the bytes in use are stored.
+ * @return False if tachyon was built without CUDA or the device can't be
+ * queried, in which case |bytes| is left as is.
+ */
+TACHYON_C_EXPORT bool tachyon_get_device_memory_in_use(size_t* bytes);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif
+
+#endif  // TACHYON_C_DEVICE_MEMORY_H_
@@ -0,0 +1,82 @@
+#include "tachyon/c/logging.h"
+
+#include <mutex>
+
+#include "tachyon/base/logging.h"
+#include "tachyon/base/no_destructor.h"
+
+namespace tachyon::c {
+namespace {
+
+// glog calls a sink from the thread that logs, and the threads of a parallel
+// loop log at once. The callback is called under |mutex_|, so that it sees one
+// whole message at a time.
+class CallbackLogSink : public google::LogSink {
+ public:
+  void Set(tachyon_log_callback callback, void* data) {
+    std::lock_guard<std::mutex> lock(mutex_);
+    callback_ = callback;
+    data_ = data;
+  }
+
+  // google::LogSink methods
+  void send(google::LogSeverity severity, const char* full_filename,
+            const char* base_filename, int line, const struct ::tm* tm_time,
+            const char* message, size_t message_len) override {
+    std::lock_guard<std::mutex> lock(mutex_);
+    if (callback_ != nullptr) {
+      callback_(data_, static_cast<tachyon_log_severity>(severity), message,
+                message_len);
+    }
+  }
+
+ private:
+  std::mutex mutex_;
+  tachyon_log_callback callback_ = nullptr;
+  void* data_ = nullptr;
+};
+
+CallbackLogSink& GetCallbackLogSink() {
+  static base::NoDestructor<CallbackLogSink> sink;
+  return *sink;
+}
+
+}  // namespace
+}  // namespace tachyon::c
+
+void tachyon_set_log_destination(tachyon_log_destination destination,
+                                 tachyon_log_callback callback, void* data) {
+  using namespace tachyon::c;
+
+  static std::once_flag once;
+  std::call_once(once, []() {
+    // Before glog is initialized, it writes every message to stderr whatever
+    // the flags say.
+    if (!google::IsGoogleLoggingInitialized()) {
+      google::InitGoogleLogging("tachyon");
+    }
+    for (int severity = 0; severity < google::NUM_SEVERITIES; ++severity) {
+      google::SetLogDestination(severity, "");
+    }
+    google::AddLogSink(&GetCallbackLogSink());
+  });
+
+  FLAGS_logtostderr = destination == TACHYON_LOG_DESTINATION_STDERR;
+  FLAGS_alsologtostderr = false;
+  google::SetStderrLogging(destination == TACHYON_LOG_DESTINATION_STDERR
+                               ? google::GLOG_INFO
+                               : google::NUM_SEVERITIES);
+  if (destination == TACHYON_LOG_DESTINATION_CALLBACK) {
+    GetCallbackLogSink().Set(callback, data);
+  } else {
+    GetCallbackLogSink().Set(nullptr, nullptr);
+  }
+}
+
+int tachyon_get_log_verbosity() { return FLAGS_v; }
+
+void tachyon_set_log_verbosity(int verbosity) { FLAGS_v = verbosity; }
+
+void tachyon_log_fatal_for_testing(const char* message) {
+  LOG(FATAL) << message;
+}
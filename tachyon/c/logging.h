@@ -0,0 +1,101 @@
+#ifndef TACHYON_C_LOGGING_H_
+#define TACHYON_C_LOGGING_H_
+
+#include <stddef.h>
+
+#include "tachyon/c/export.h"
+
+/**
+ * @file logging.h
+ * @brief Control over where the messages tachyon logs go.
+ *
+ * By default, tachyon writes its messages to stderr. This header lets a host
+ * with its own logging, e.g, structured service logs, silence them or receive
+ * them through a callback instead.
+ */
+
+/**
+ * @brief Where the messages tachyon logs go.
+ */
+typedef enum {
+  /// Nowhere.
+  TACHYON_LOG_DESTINATION_SILENT,
+  /// To stderr, as by default.
+  TACHYON_LOG_DESTINATION_STDERR,
+  /// To the callback passed to tachyon_set_log_destination().
+  TACHYON_LOG_DESTINATION_CALLBACK,
+} tachyon_log_destination;
+
+/**
+ * @brief The severity of a message. Verbose messages are logged as
+ * TACHYON_LOG_SEVERITY_INFO.
+ */
+typedef enum {
+  TACHYON_LOG_SEVERITY_INFO,
+  TACHYON_LOG_SEVERITY_WARNING,
+  TACHYON_LOG_SEVERITY_ERROR,
+  TACHYON_LOG_SEVERITY_FATAL,
+} tachyon_log_severity;
+
+/**
+ * @brief Receives a message tachyon logged.
+ *
+ * It is called from the thread that logged the message, but never from two
+ * threads at once, so messages of concurrent threads don't interleave.
+ *
+ * @param data The data passed to tachyon_set_log_destination().
+ * @param severity The severity of the message.
+ * @param message The message, not null-terminated and without a trailing
+ * newline.
+ * @param message_len The length of |message|.
+ */
+typedef void (*tachyon_log_callback)(void* data, tachyon_log_severity severity,
+                                     const char* message, size_t message_len);
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * @brief Sends the messages tachyon logs from now on to |destination|.
+ *
+ * Messages are no longer written to log files either. Call it before anything
+ * else of tachyon, since messages logged before go to stderr.
+ *
+ * @param destination Where messages go.
+ * @param callback The callback messages are passed to if |destination| is
+ * TACHYON_LOG_DESTINATION_CALLBACK. Ignored otherwise.
+ * @param data Passed to every call of |callback|.
+ */
+TACHYON_C_EXPORT void tachyon_set_log_destination(
+    tachyon_log_destination destination, tachyon_log_callback callback,
+    void* data);
+
+/**
+ * @brief Returns the level up to which verbose messages are logged.
+ * @return The level, 0 by default.
+ */
+TACHYON_C_EXPORT int tachyon_get_log_verbosity();
+
+/**
+ * @brief Logs verbose messages up to |verbosity| from now on, in every
+ * thread.
+ *
+ * @param verbosity The highest level of verbose messages to log.
+ */
+TACHYON_C_EXPORT void tachyon_set_log_verbosity(int verbosity);
+
+/**
+ * @brief Logs |message| as TACHYON_LOG_SEVERITY_FATAL, which aborts the
+ * process the way a failed CHECK inside tachyon does. It lets bindings test
+ * what they leave behind when tachyon crashes.
+ *
+ * @param message The message, null-terminated.
+ */
+TACHYON_C_EXPORT void tachyon_log_fatal_for_testing(const char* message);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif
+
+#endif  // TACHYON_C_LOGGING_H_
@@ -0,0 +1,54 @@
+#include "tachyon/c/logging.h"
+
+#include <string>
+#include <utility>
+#include <vector>
+
+#include "gtest/gtest.h"
+
+#include "tachyon/base/logging.h"
+
+namespace tachyon::c {
+
+namespace {
+
+using Messages = std::vector<std::pair<tachyon_log_severity, std::string>>;
+
+void Collect(void* data, tachyon_log_severity severity, const char* message,
+             size_t message_len) {
+  reinterpret_cast<Messages*>(data)->emplace_back(
+      severity, std::string(message, message_len));
+}
+
+}  // namespace
+
+TEST(LoggingTest, Callback) {
+  Messages messages;
+  int verbosity = tachyon_get_log_verbosity();
+  tachyon_set_log_destination(TACHYON_LOG_DESTINATION_CALLBACK, Collect,
+                              &messages);
+  tachyon_set_log_verbosity(1);
+
+  testing::internal::CaptureStderr();
+  LOG(WARNING) << "warning";
+  VLOG(1) << "verbose";
+  VLOG(2) << "too verbose";
+  EXPECT_EQ(testing::internal::GetCapturedStderr(), "");
+
+  tachyon_set_log_verbosity(verbosity);
+  tachyon_set_log_destination(TACHYON_LOG_DESTINATION_SILENT, nullptr,
+                              nullptr);
+  LOG(INFO) << "silenced";
+
+  Messages expected = {
+      {TACHYON_LOG_SEVERITY_WARNING, "warning"},
+      {TACHYON_LOG_SEVERITY_INFO, "verbose"},
+  };
+  EXPECT_EQ(messages, expected);
+}
+
+TEST(LoggingDeathTest, LogFatalForTesting) {
+  EXPECT_DEATH(tachyon_log_fatal_for_testing("injected"), "injected");
+}
+
+}  // namespace tachyon::c
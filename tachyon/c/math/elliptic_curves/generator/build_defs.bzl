@@ -193,8 +193,10 @@ def generate_ec_points(
         ],
         srcs = ["fq.cc"],
         deps = g1_deps + [
+            "//tachyon/base:logging",
             "//tachyon/c:export",
             "//tachyon/c/base:type_traits_forward",
+            "@com_google_absl//absl/types:span",
         ],
     )
 
@@ -206,8 +208,10 @@ def generate_ec_points(
         ],
         srcs = ["fr.cc"],
         deps = g1_deps + [
+            "//tachyon/base:logging",
             "//tachyon/c:export",
             "//tachyon/c/base:type_traits_forward",
+            "@com_google_absl//absl/types:span",
         ],
     )
 
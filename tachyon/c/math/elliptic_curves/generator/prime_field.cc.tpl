@@ -1,4 +1,7 @@
 // clang-format off
+#include "absl/types/span.h"
+
+#include "tachyon/base/logging.h"
 #include "tachyon/c/base/type_traits_forward.h"
 #include "tachyon/c/math/elliptic_curves/%{header_dir_name}/%{suffix}_traits.h"
 #include "tachyon/math/elliptic_curves/%{header_dir_name}/%{suffix}.h"
@@ -77,6 +80,14 @@ tachyon_%{type}_%{suffix} tachyon_%{type}_%{suffix}_inv(const tachyon_%{type}_%{
   return c_cast(native_a.InverseInPlace());
 }
 
+void tachyon_%{type}_%{suffix}_batch_inverse(const tachyon_%{type}_%{suffix}* inputs, size_t len, tachyon_%{type}_%{suffix}* outputs) {
+  using namespace tachyon::c::base;
+  using NativeType = typename TypeTraits<tachyon_%{type}_%{suffix}>::NativeType;
+  absl::Span<const NativeType> native_inputs(native_cast(inputs), len);
+  absl::Span<NativeType> native_outputs(native_cast(outputs), len);
+  CHECK(NativeType::BatchInverse(native_inputs, &native_outputs));
+}
+
 bool tachyon_%{type}_%{suffix}_eq(const tachyon_%{type}_%{suffix}* a, const tachyon_%{type}_%{suffix}* b) {
     using namespace tachyon::c::base;
     return native_cast(*a) == native_cast(*b);
@@ -9,6 +9,7 @@
  */
 
 // clang-format off
+#include <stddef.h>
 #include <stdint.h>
 
 #include "tachyon/c/export.h"
@@ -104,6 +105,15 @@ TACHYON_C_EXPORT tachyon_%{type}_%{suffix} tachyon_%{type}_%{suffix}_sqr(const t
  */
 TACHYON_C_EXPORT tachyon_%{type}_%{suffix} tachyon_%{type}_%{suffix}_inv(const tachyon_%{type}_%{suffix}* a);
 
+/**
+ * @brief Calculates the multiplicative inverses of @p len elements in the finite field %{suffix}
+ * with Montgomery's trick, in parallel for large inputs. Zeros are mapped to zero.
+ * @param inputs Pointer to the elements to invert.
+ * @param len The number of elements.
+ * @param outputs Pointer to where the @p len inverses are written. It may be equal to @p inputs.
+ */
+TACHYON_C_EXPORT void tachyon_%{type}_%{suffix}_batch_inverse(const tachyon_%{type}_%{suffix}* inputs, size_t len, tachyon_%{type}_%{suffix}* outputs);
+
 /**
  * @brief Checks if two elements in the finite field %{suffix} are equal.
  * @param a Pointer to the first element.
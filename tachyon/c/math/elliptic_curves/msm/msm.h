@@ -3,7 +3,7 @@
 
 #include <tuple>
 
-#include "tachyon/base/console/console_stream.h"
+#include "tachyon/base/logging.h"
 #include "tachyon/c/math/elliptic_curves/msm/msm_input_provider.h"
 #include "tachyon/c/math/elliptic_curves/point_conversions.h"
 #include "tachyon/math/elliptic_curves/msm/variable_base_msm.h"
@@ -22,13 +22,7 @@ struct MSMApi {
     // argument for algorithm selection even though it only supports pippenger
     // in a same manner.
     std::ignore = degree;
-    {
-      // NOTE(chokobole): This should be replaced with VLOG().
-      // Currently, there's no way to delegate VLOG flags from rust side.
-      tachyon::base::ConsoleStream cs;
-      cs.Green();
-      std::cout << "CreateMSMApi()" << std::endl;
-    }
+    VLOG(1) << "CreateMSMApi()";
   }
 };
 
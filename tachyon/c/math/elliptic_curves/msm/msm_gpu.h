@@ -3,12 +3,13 @@
 
 #include <limits>
 #include <memory>
+#include <mutex>
 #include <string>
 
 #include "tachyon/base/buffer/vector_buffer.h"
-#include "tachyon/base/console/console_stream.h"
 #include "tachyon/base/environment.h"
 #include "tachyon/base/files/file_util.h"
+#include "tachyon/base/logging.h"
 #include "tachyon/c/math/elliptic_curves/msm/algorithm.h"
 #include "tachyon/c/math/elliptic_curves/msm/msm_input_provider.h"
 #include "tachyon/c/math/elliptic_curves/point_conversions.h"
@@ -20,6 +21,18 @@
 
 namespace tachyon::c::math {
 
+// Resets the device the first time it's called in this process. Resetting it
+// again would destroy the streams and memory pools of every |MSMGpuApi| that
+// is still alive.
+inline void ResetGpuDeviceOnce() {
+  static std::once_flag flag;
+  std::call_once(flag, []() {
+    GPU_MUST_SUCCESS(gpuDeviceReset(), "Failed to gpuDeviceReset()");
+  });
+}
+
+// Every |MSMGpuApi| owns its stream, memory pool and device buffers, so that
+// several of them can run on the same device at once.
 template <typename GpuCurve>
 struct MSMGpuApi {
   using GpuAffinePoint = tachyon::math::AffinePoint<GpuCurve>;
@@ -51,15 +64,9 @@ struct MSMGpuApi {
         NOTREACHED() << "Not supported algorithm";
     }
 
-    GPU_MUST_SUCCESS(gpuDeviceReset(), "Failed to gpuDeviceReset()");
+    ResetGpuDeviceOnce();
 
-    {
-      // NOTE(chokobole): This should be replaced with VLOG().
-      // Currently, there's no way to delegate VLOG flags from rust side.
-      tachyon::base::ConsoleStream cs;
-      cs.Green();
-      std::cout << "CreateMSMGpuApi()" << std::endl;
-    }
+    VLOG(1) << "CreateMSMGpuApi()";
 
     std::string_view msm_gpu_input_dir_str;
     if (tachyon::base::Environment::Get("TACHYON_MSM_GPU_INPUT_DIR",
@@ -115,12 +122,7 @@ CRetPoint* DoMSMGpu(MSMGpuApi<GpuCurve>& msm_api, const CPoint* bases,
   ToCPoint3(ret, cret);
 
   if (msm_api.log_msm) {
-    // NOTE(chokobole): This should be replaced with VLOG().
-    // Currently, there's no way to delegate VLOG flags from rust side.
-    tachyon::base::ConsoleStream cs;
-    cs.Yellow();
-    std::cout << "DoMSMGpu()" << msm_api.idx++ << std::endl;
-    std::cout << ret.ToHexString() << std::endl;
+    LOG(INFO) << "DoMSMGpu()" << msm_api.idx++ << ": " << ret.ToHexString();
   }
 
   if (!msm_api.msm_gpu_input_dir.empty()) {
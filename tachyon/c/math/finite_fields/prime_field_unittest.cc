@@ -1,3 +1,5 @@
+#include <vector>
+
 #include "gtest/gtest.h"
 
 #include "tachyon/c/math/elliptic_curves/bn/bn254/fr.h"
@@ -82,6 +84,27 @@ TEST_F(PrimeFieldTest, Inv) {
   EXPECT_EQ(c::base::native_cast(c_ret), a_.Inverse());
 }
 
+TEST_F(PrimeFieldTest, BatchInverse) {
+  tachyon_bn254_fr c_zero = tachyon_bn254_fr_zero();
+  std::vector<tachyon_bn254_fr> c_values = {c_a_, c_zero, c_b_};
+  std::vector<math::bn254::Fr> expected = {
+      a_.Inverse(), math::bn254::Fr::Zero(), b_.Inverse()};
+
+  std::vector<tachyon_bn254_fr> c_inverses(c_values.size());
+  tachyon_bn254_fr_batch_inverse(c_values.data(), c_values.size(),
+                                 c_inverses.data());
+  for (size_t i = 0; i < expected.size(); ++i) {
+    EXPECT_EQ(c::base::native_cast(c_inverses[i]), expected[i]);
+  }
+
+  // In place.
+  tachyon_bn254_fr_batch_inverse(c_values.data(), c_values.size(),
+                                 c_values.data());
+  for (size_t i = 0; i < expected.size(); ++i) {
+    EXPECT_EQ(c::base::native_cast(c_values[i]), expected[i]);
+  }
+}
+
 TEST_F(PrimeFieldTest, Eq) {
   EXPECT_EQ(tachyon_bn254_fr_eq(&c_a_, &c_b_), a_ == b_);
 }
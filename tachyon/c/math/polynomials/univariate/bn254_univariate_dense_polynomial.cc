@@ -1,5 +1,8 @@
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_dense_polynomial.h"
 
+#include <algorithm>
+#include <vector>
+
 #include "tachyon/c/math/polynomials/constants.h"
 #include "tachyon/math/elliptic_curves/bn/bn254/fr.h"
 #include "tachyon/math/polynomials/univariate/univariate_polynomial.h"
@@ -25,3 +28,25 @@ void tachyon_bn254_univariate_dense_polynomial_destroy(
     tachyon_bn254_univariate_dense_polynomial* poly) {
   delete reinterpret_cast<Poly*>(poly);
 }
+
+size_t tachyon_bn254_univariate_dense_polynomial_len(
+    const tachyon_bn254_univariate_dense_polynomial* poly) {
+  return reinterpret_cast<const Poly*>(poly)->NumElements();
+}
+
+void tachyon_bn254_univariate_dense_polynomial_get_coeffs(
+    const tachyon_bn254_univariate_dense_polynomial* poly,
+    tachyon_bn254_fr* coeffs) {
+  const std::vector<bn254::Fr>& coefficients =
+      reinterpret_cast<const Poly&>(*poly).coefficients().coefficients();
+  std::copy(coefficients.begin(), coefficients.end(),
+            reinterpret_cast<bn254::Fr*>(coeffs));
+}
+
+void tachyon_bn254_univariate_dense_polynomial_set_coeffs(
+    tachyon_bn254_univariate_dense_polynomial* poly,
+    const tachyon_bn254_fr* coeffs, size_t len) {
+  const bn254::Fr* begin = reinterpret_cast<const bn254::Fr*>(coeffs);
+  reinterpret_cast<Poly&>(*poly).coefficients().coefficients().assign(
+      begin, begin + len);
+}
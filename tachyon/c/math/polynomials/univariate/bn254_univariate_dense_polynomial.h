@@ -11,7 +11,10 @@
 #ifndef TACHYON_C_MATH_POLYNOMIALS_UNIVARIATE_BN254_UNIVARIATE_DENSE_POLYNOMIAL_H_
 #define TACHYON_C_MATH_POLYNOMIALS_UNIVARIATE_BN254_UNIVARIATE_DENSE_POLYNOMIAL_H_
 
+#include <stddef.h>
+
 #include "tachyon/c/export.h"
+#include "tachyon/c/math/elliptic_curves/bn/bn254/fr.h"
 
 /**
  * @struct tachyon_bn254_univariate_dense_polynomial
@@ -60,6 +63,41 @@ tachyon_bn254_univariate_dense_polynomial_clone(
 TACHYON_C_EXPORT void tachyon_bn254_univariate_dense_polynomial_destroy(
     tachyon_bn254_univariate_dense_polynomial* evals);
 
+/**
+ * @brief Retrieves the number of coefficients of a univariate dense
+ * polynomial.
+ *
+ * @param poly Pointer to the polynomial.
+ * @return The number of coefficients stored in the polynomial.
+ */
+TACHYON_C_EXPORT size_t tachyon_bn254_univariate_dense_polynomial_len(
+    const tachyon_bn254_univariate_dense_polynomial* poly);
+
+/**
+ * @brief Copies every coefficient out of a univariate dense polynomial.
+ *
+ * @param poly Pointer to the polynomial.
+ * @param coeffs Pointer to an array with room for
+ * tachyon_bn254_univariate_dense_polynomial_len() coefficients.
+ */
+TACHYON_C_EXPORT void tachyon_bn254_univariate_dense_polynomial_get_coeffs(
+    const tachyon_bn254_univariate_dense_polynomial* poly,
+    tachyon_bn254_fr* coeffs);
+
+/**
+ * @brief Replaces the coefficients of a univariate dense polynomial.
+ *
+ * The coefficients are stored as given, even if the trailing ones are zero.
+ *
+ * @param poly Pointer to the polynomial.
+ * @param coeffs Pointer to an array of |len| coefficients, lowest degree
+ * first.
+ * @param len The number of coefficients.
+ */
+TACHYON_C_EXPORT void tachyon_bn254_univariate_dense_polynomial_set_coeffs(
+    tachyon_bn254_univariate_dense_polynomial* poly,
+    const tachyon_bn254_fr* coeffs, size_t len);
+
 #ifdef __cplusplus
 }  // extern "C"
 #endif
@@ -1,7 +1,11 @@
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_dense_polynomial.h"
 
+#include <vector>
+
 #include "gtest/gtest.h"
 
+#include "tachyon/base/containers/container_util.h"
+#include "tachyon/c/math/elliptic_curves/bn/bn254/fr_traits.h"
 #include "tachyon/c/math/polynomials/constants.h"
 #include "tachyon/math/elliptic_curves/bn/bn254/fr.h"
 #include "tachyon/math/finite_fields/test/finite_field_test.h"
@@ -49,4 +53,33 @@ TEST_F(UnivariateDensePolynomialTest, Clone) {
   }
 }
 
+TEST_F(UnivariateDensePolynomialTest, Len) {
+  EXPECT_EQ(tachyon_bn254_univariate_dense_polynomial_len(poly_),
+            reinterpret_cast<Poly&>(*poly_).NumElements());
+}
+
+TEST_F(UnivariateDensePolynomialTest, GetCoeffs) {
+  std::vector<tachyon_bn254_fr> coeffs(
+      tachyon_bn254_univariate_dense_polynomial_len(poly_));
+  tachyon_bn254_univariate_dense_polynomial_get_coeffs(poly_, coeffs.data());
+  for (size_t i = 0; i < coeffs.size(); ++i) {
+    EXPECT_EQ(c::base::native_cast(coeffs[i]),
+              reinterpret_cast<Poly&>(*poly_)[i]);
+  }
+}
+
+TEST_F(UnivariateDensePolynomialTest, SetCoeffs) {
+  std::vector<bn254::Fr> cpp_coeffs =
+      base::CreateVector(kDegree + 3, []() { return bn254::Fr::Random(); });
+  cpp_coeffs.back() = bn254::Fr::Zero();
+  tachyon_bn254_univariate_dense_polynomial_set_coeffs(
+      poly_, reinterpret_cast<const tachyon_bn254_fr*>(cpp_coeffs.data()),
+      cpp_coeffs.size());
+  // The trailing zero is kept.
+  EXPECT_EQ(tachyon_bn254_univariate_dense_polynomial_len(poly_),
+            cpp_coeffs.size());
+  EXPECT_EQ(reinterpret_cast<Poly&>(*poly_).coefficients().coefficients(),
+            cpp_coeffs);
+}
+
 }  // namespace tachyon::math
@@ -1,7 +1,11 @@
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluation_domain.h"
 
+#include <cmath>
+#include <memory>
 #include <utility>
 
+#include "absl/container/btree_map.h"
+
 #include "tachyon/c/math/polynomials/constants.h"
 #include "tachyon/math/base/rational_field.h"
 #include "tachyon/math/elliptic_curves/bn/bn254/fr.h"
@@ -89,3 +93,58 @@ tachyon_bn254_univariate_evaluation_domain_ifft_inplace(
           reinterpret_cast<Domain::Evals&&>(std::move(*evals))));
   return reinterpret_cast<tachyon_bn254_univariate_dense_polynomial*>(poly);
 }
+
+tachyon_bn254_univariate_evaluations*
+tachyon_bn254_univariate_evaluation_domain_coset_fft(
+    const tachyon_bn254_univariate_evaluation_domain* domain,
+    const tachyon_bn254_univariate_dense_polynomial* poly,
+    const tachyon_bn254_fr* offset) {
+  std::unique_ptr<Domain> coset =
+      reinterpret_cast<const Domain*>(domain)->GetCoset(
+          reinterpret_cast<const bn254::Fr&>(*offset));
+  Domain::Evals* evals = new Domain::Evals(
+      coset->FFT(reinterpret_cast<const Domain::DensePoly&>(*poly)));
+  return reinterpret_cast<tachyon_bn254_univariate_evaluations*>(evals);
+}
+
+tachyon_bn254_univariate_dense_polynomial*
+tachyon_bn254_univariate_evaluation_domain_coset_ifft(
+    const tachyon_bn254_univariate_evaluation_domain* domain,
+    const tachyon_bn254_univariate_evaluations* evals,
+    const tachyon_bn254_fr* offset) {
+  std::unique_ptr<Domain> coset =
+      reinterpret_cast<const Domain*>(domain)->GetCoset(
+          reinterpret_cast<const bn254::Fr&>(*offset));
+  Domain::DensePoly* poly = new Domain::DensePoly(
+      coset->IFFT(reinterpret_cast<const Domain::Evals&>(*evals)));
+  return reinterpret_cast<tachyon_bn254_univariate_dense_polynomial*>(poly);
+}
+
+void tachyon_bn254_univariate_evaluation_domain_column_stats(
+    const tachyon_bn254_univariate_evaluation_domain* domain,
+    const tachyon_bn254_univariate_evaluations* evals,
+    struct tachyon_bn254_univariate_column_stats* stats) {
+  const Domain::Evals& cpp_evals =
+      reinterpret_cast<const Domain::Evals&>(*evals);
+
+  size_t num_nonzero_rows = 0;
+  absl::btree_map<bn254::Fr, size_t> counts;
+  for (const bn254::Fr& value : cpp_evals.evaluations()) {
+    if (!value.IsZero()) ++num_nonzero_rows;
+    ++counts[value];
+  }
+  double entropy = 0;
+  double num_rows = static_cast<double>(cpp_evals.NumElements());
+  for (const auto& [value, count] : counts) {
+    double p = count / num_rows;
+    entropy -= p * std::log2(p);
+  }
+
+  // |IFFT()| removes the high degree zeros, so the number of
+  // coefficients left is the one of the highest nonzero one.
+  Domain::DensePoly poly =
+      reinterpret_cast<const Domain*>(domain)->IFFT(cpp_evals);
+  stats->num_nonzero_rows = num_nonzero_rows;
+  stats->num_coeffs = poly.IsZero() ? 0 : poly.Degree() + 1;
+  stats->entropy = entropy;
+}
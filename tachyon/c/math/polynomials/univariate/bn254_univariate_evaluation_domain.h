@@ -25,6 +25,20 @@
  */
 struct tachyon_bn254_univariate_evaluation_domain {};
 
+/**
+ * @struct tachyon_bn254_univariate_column_stats
+ * @brief Summarizes how much of the domain the values of a column use.
+ */
+struct tachyon_bn254_univariate_column_stats {
+  // The number of rows whose value isn't zero.
+  size_t num_nonzero_rows;
+  // One more than the index of the highest nonzero coefficient of the
+  // interpolated polynomial, or 0 if it is the zero polynomial.
+  size_t num_coeffs;
+  // The Shannon entropy of the values over all the rows, in bits.
+  double entropy;
+};
+
 #ifdef __cplusplus
 extern "C" {
 #endif
@@ -133,6 +147,50 @@ tachyon_bn254_univariate_evaluation_domain_ifft_inplace(
     const tachyon_bn254_univariate_evaluation_domain* domain,
     tachyon_bn254_univariate_evaluations* evals);
 
+/**
+ * @brief Performs the Fast Fourier Transform (FFT) on a given polynomial over
+ * the coset of the domain by |offset|, i.e, evaluates it at |offset| * ωⁱ.
+ *
+ * @param domain Pointer to the evaluation domain.
+ * @param poly Pointer to the polynomial to transform.
+ * @param offset Pointer to the offset of the coset.
+ * @return Pointer to the evaluations resulting from the FFT.
+ */
+TACHYON_C_EXPORT tachyon_bn254_univariate_evaluations*
+tachyon_bn254_univariate_evaluation_domain_coset_fft(
+    const tachyon_bn254_univariate_evaluation_domain* domain,
+    const tachyon_bn254_univariate_dense_polynomial* poly,
+    const tachyon_bn254_fr* offset);
+
+/**
+ * @brief Performs the inverse Fast Fourier Transform (IFFT) on given
+ * evaluations over the coset of the domain by |offset|. This is the inverse of
+ * tachyon_bn254_univariate_evaluation_domain_coset_fft().
+ *
+ * @param domain Pointer to the evaluation domain.
+ * @param evals Pointer to the evaluations to transform back into a polynomial.
+ * @param offset Pointer to the offset of the coset.
+ * @return Pointer to the dense polynomial resulting from the IFFT.
+ */
+TACHYON_C_EXPORT tachyon_bn254_univariate_dense_polynomial*
+tachyon_bn254_univariate_evaluation_domain_coset_ifft(
+    const tachyon_bn254_univariate_evaluation_domain* domain,
+    const tachyon_bn254_univariate_evaluations* evals,
+    const tachyon_bn254_fr* offset);
+
+/**
+ * @brief Computes the statistics of |evals| over the domain. The polynomial is
+ * interpolated internally and isn't returned.
+ *
+ * @param domain Pointer to the evaluation domain.
+ * @param evals Pointer to the evaluations of the column.
+ * @param stats Pointer to store the statistics.
+ */
+TACHYON_C_EXPORT void tachyon_bn254_univariate_evaluation_domain_column_stats(
+    const tachyon_bn254_univariate_evaluation_domain* domain,
+    const tachyon_bn254_univariate_evaluations* evals,
+    struct tachyon_bn254_univariate_column_stats* stats);
+
 #ifdef __cplusplus
 }  // extern "C"
 #endif
@@ -1,5 +1,7 @@
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluation_domain.h"
 
+#include <cmath>
+#include <memory>
 #include <utility>
 
 #include "gtest/gtest.h"
@@ -88,4 +90,63 @@ TEST_F(UnivariateEvaluationDomainTest, IFFT) {
   tachyon_bn254_univariate_dense_polynomial_destroy(poly);
 }
 
+TEST_F(UnivariateEvaluationDomainTest, CosetFFT) {
+  Domain::DensePoly poly = Domain::DensePoly::Random(kDegree);
+  bn254::Fr offset = bn254::Fr::Random();
+  tachyon_bn254_univariate_evaluations* evals =
+      tachyon_bn254_univariate_evaluation_domain_coset_fft(
+          domain_,
+          reinterpret_cast<const tachyon_bn254_univariate_dense_polynomial*>(
+              &poly),
+          reinterpret_cast<const tachyon_bn254_fr*>(&offset));
+  const Domain::Evals& cpp_evals = reinterpret_cast<Domain::Evals&>(*evals);
+  std::unique_ptr<Domain> domain = Domain::Create(kDegree + 1);
+  for (size_t i = 0; i < cpp_evals.NumElements(); ++i) {
+    EXPECT_EQ(poly.Evaluate(offset * domain->GetElement(i)), cpp_evals[i]);
+  }
+
+  tachyon_bn254_univariate_dense_polynomial* coset_poly =
+      tachyon_bn254_univariate_evaluation_domain_coset_ifft(
+          domain_, evals, reinterpret_cast<const tachyon_bn254_fr*>(&offset));
+  EXPECT_EQ(poly, reinterpret_cast<Domain::DensePoly&>(*coset_poly));
+  tachyon_bn254_univariate_dense_polynomial_destroy(coset_poly);
+  tachyon_bn254_univariate_evaluations_destroy(evals);
+}
+
+TEST_F(UnivariateEvaluationDomainTest, ColumnStats) {
+  const Domain* domain = reinterpret_cast<const Domain*>(domain_);
+  Domain::Evals evals = domain->Zero<Domain::Evals>();
+  tachyon_bn254_univariate_column_stats stats;
+  tachyon_bn254_univariate_evaluation_domain_column_stats(
+      domain_,
+      reinterpret_cast<const tachyon_bn254_univariate_evaluations*>(&evals),
+      &stats);
+  EXPECT_EQ(stats.num_nonzero_rows, 0);
+  EXPECT_EQ(stats.num_coeffs, 0);
+  EXPECT_EQ(stats.entropy, 0);
+
+  // A constant interpolates to a polynomial of degree 0.
+  for (bn254::Fr& value : evals.evaluations()) {
+    value = bn254::Fr(3);
+  }
+  tachyon_bn254_univariate_evaluation_domain_column_stats(
+      domain_,
+      reinterpret_cast<const tachyon_bn254_univariate_evaluations*>(&evals),
+      &stats);
+  EXPECT_EQ(stats.num_nonzero_rows, evals.NumElements());
+  EXPECT_EQ(stats.num_coeffs, 1);
+  EXPECT_EQ(stats.entropy, 0);
+
+  for (size_t i = 0; i < evals.NumElements(); ++i) {
+    evals.evaluations()[i] = bn254::Fr(i);
+  }
+  tachyon_bn254_univariate_evaluation_domain_column_stats(
+      domain_,
+      reinterpret_cast<const tachyon_bn254_univariate_evaluations*>(&evals),
+      &stats);
+  EXPECT_EQ(stats.num_nonzero_rows, evals.NumElements() - 1);
+  EXPECT_EQ(stats.num_coeffs, domain->IFFT(evals).Degree() + 1);
+  EXPECT_DOUBLE_EQ(stats.entropy, std::log2(evals.NumElements()));
+}
+
 }  // namespace tachyon::math
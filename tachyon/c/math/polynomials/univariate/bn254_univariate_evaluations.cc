@@ -1,5 +1,9 @@
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluations.h"
 
+#include <algorithm>
+#include <vector>
+
+#include "tachyon/c/math/elliptic_curves/bn/bn254/fr_traits.h"
 #include "tachyon/c/math/polynomials/constants.h"
 #include "tachyon/math/elliptic_curves/bn/bn254/fr.h"
 #include "tachyon/math/polynomials/univariate/univariate_evaluations.h"
@@ -37,3 +41,32 @@ void tachyon_bn254_univariate_evaluations_set_value(
   reinterpret_cast<Evals&>(*evals).at(i) =
       reinterpret_cast<const bn254::Fr&>(*value);
 }
+
+tachyon_bn254_fr tachyon_bn254_univariate_evaluations_get_value(
+    const tachyon_bn254_univariate_evaluations* evals, size_t i) {
+  // NOTE(chokobole): Boundary check is the responsibility of API callers.
+  return tachyon::c::base::c_cast(reinterpret_cast<const Evals&>(*evals)[i]);
+}
+
+void tachyon_bn254_univariate_evaluations_get_values(
+    const tachyon_bn254_univariate_evaluations* evals,
+    tachyon_bn254_fr* values) {
+  const std::vector<bn254::Fr>& evaluations =
+      reinterpret_cast<const Evals&>(*evals).evaluations();
+  std::copy(evaluations.begin(), evaluations.end(),
+            reinterpret_cast<bn254::Fr*>(values));
+}
+
+void tachyon_bn254_univariate_evaluations_set_values(
+    tachyon_bn254_univariate_evaluations* evals,
+    const tachyon_bn254_fr* values, size_t len) {
+  const bn254::Fr* begin = reinterpret_cast<const bn254::Fr*>(values);
+  reinterpret_cast<Evals&>(*evals).evaluations().assign(begin, begin + len);
+}
+
+void tachyon_bn254_univariate_evaluations_reset(
+    tachyon_bn254_univariate_evaluations* evals) {
+  for (bn254::Fr& value : reinterpret_cast<Evals&>(*evals).evaluations()) {
+    value = bn254::Fr::Zero();
+  }
+}
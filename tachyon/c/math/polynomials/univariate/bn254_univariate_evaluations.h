@@ -78,6 +78,52 @@ TACHYON_C_EXPORT void tachyon_bn254_univariate_evaluations_set_value(
     tachyon_bn254_univariate_evaluations* evals, size_t i,
     const tachyon_bn254_fr* value);
 
+/**
+ * @brief Retrieves a value from the univariate evaluations structure.
+ *
+ * @param evals Pointer to the evaluations structure.
+ * @param i Index of the value to retrieve.
+ * @return The value at index i.
+ */
+TACHYON_C_EXPORT tachyon_bn254_fr
+tachyon_bn254_univariate_evaluations_get_value(
+    const tachyon_bn254_univariate_evaluations* evals, size_t i);
+
+/**
+ * @brief Copies every value out of the univariate evaluations structure.
+ *
+ * @param evals Pointer to the evaluations structure.
+ * @param values Pointer to an array with room for
+ * tachyon_bn254_univariate_evaluations_len() values.
+ */
+TACHYON_C_EXPORT void tachyon_bn254_univariate_evaluations_get_values(
+    const tachyon_bn254_univariate_evaluations* evals,
+    tachyon_bn254_fr* values);
+
+/**
+ * @brief Replaces the values of the univariate evaluations structure.
+ *
+ * The structure is resized to |len| values.
+ *
+ * @param evals Pointer to the evaluations structure.
+ * @param values Pointer to an array of |len| values.
+ * @param len The number of values.
+ */
+TACHYON_C_EXPORT void tachyon_bn254_univariate_evaluations_set_values(
+    tachyon_bn254_univariate_evaluations* evals,
+    const tachyon_bn254_fr* values, size_t len);
+
+/**
+ * @brief Resets every value in the univariate evaluations structure to zero.
+ *
+ * The length of the structure is preserved, so it can be reused without
+ * reallocating.
+ *
+ * @param evals Pointer to the evaluations structure.
+ */
+TACHYON_C_EXPORT void tachyon_bn254_univariate_evaluations_reset(
+    tachyon_bn254_univariate_evaluations* evals);
+
 #ifdef __cplusplus
 }  // extern "C"
 #endif
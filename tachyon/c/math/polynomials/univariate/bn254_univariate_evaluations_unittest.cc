@@ -1,7 +1,10 @@
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluations.h"
 
+#include <vector>
+
 #include "gtest/gtest.h"
 
+#include "tachyon/base/containers/container_util.h"
 #include "tachyon/c/math/elliptic_curves/bn/bn254/fr.h"
 #include "tachyon/c/math/elliptic_curves/bn/bn254/fr_traits.h"
 #include "tachyon/c/math/polynomials/constants.h"
@@ -56,4 +59,39 @@ TEST_F(UnivariateEvaluationsTest, SetValue) {
   EXPECT_EQ(reinterpret_cast<Evals&>(*evals_)[0], cpp_value);
 }
 
+TEST_F(UnivariateEvaluationsTest, GetValue) {
+  tachyon_bn254_fr value =
+      tachyon_bn254_univariate_evaluations_get_value(evals_, 0);
+  // NOTE(chokobole): It's safe to access since we created |kDegree| |evals_|.
+  EXPECT_EQ(c::base::native_cast(value), reinterpret_cast<Evals&>(*evals_)[0]);
+}
+
+TEST_F(UnivariateEvaluationsTest, GetValues) {
+  std::vector<tachyon_bn254_fr> values(
+      tachyon_bn254_univariate_evaluations_len(evals_));
+  tachyon_bn254_univariate_evaluations_get_values(evals_, values.data());
+  for (size_t i = 0; i < values.size(); ++i) {
+    EXPECT_EQ(c::base::native_cast(values[i]),
+              reinterpret_cast<Evals&>(*evals_)[i]);
+  }
+}
+
+TEST_F(UnivariateEvaluationsTest, SetValues) {
+  std::vector<bn254::Fr> cpp_values =
+      base::CreateVector(kDegree + 3, []() { return bn254::Fr::Random(); });
+  tachyon_bn254_univariate_evaluations_set_values(
+      evals_, reinterpret_cast<const tachyon_bn254_fr*>(cpp_values.data()),
+      cpp_values.size());
+  EXPECT_EQ(reinterpret_cast<Evals&>(*evals_).evaluations(), cpp_values);
+}
+
+TEST_F(UnivariateEvaluationsTest, Reset) {
+  tachyon_bn254_univariate_evaluations_reset(evals_);
+  EXPECT_EQ(tachyon_bn254_univariate_evaluations_len(evals_), kDegree + 1);
+  for (const bn254::Fr& value :
+       reinterpret_cast<Evals&>(*evals_).evaluations()) {
+    EXPECT_TRUE(value.IsZero());
+  }
+}
+
 }  // namespace tachyon::math
@@ -47,6 +47,14 @@ void tachyon_bn254_univariate_rational_evaluations_set_zero(
       RationalField<bn254::Fr>::Zero();
 }
 
+void tachyon_bn254_univariate_rational_evaluations_reset(
+    tachyon_bn254_univariate_rational_evaluations* evals) {
+  for (RationalField<bn254::Fr>& value :
+       reinterpret_cast<RationalEvals&>(*evals).evaluations()) {
+    value = RationalField<bn254::Fr>::Zero();
+  }
+}
+
 void tachyon_bn254_univariate_rational_evaluations_set_trivial(
     tachyon_bn254_univariate_rational_evaluations* evals, size_t i,
     const tachyon_bn254_fr* numerator) {
@@ -79,6 +79,17 @@ TACHYON_C_EXPORT size_t tachyon_bn254_univariate_rational_evaluations_len(
 TACHYON_C_EXPORT void tachyon_bn254_univariate_rational_evaluations_set_zero(
     tachyon_bn254_univariate_rational_evaluations* evals, size_t i);
 
+/**
+ * @brief Resets every value in the rational evaluations structure to zero.
+ *
+ * The length of the structure is preserved, so it can be reused without
+ * reallocating.
+ *
+ * @param evals Pointer to the rational evaluations structure.
+ */
+TACHYON_C_EXPORT void tachyon_bn254_univariate_rational_evaluations_reset(
+    tachyon_bn254_univariate_rational_evaluations* evals);
+
 /**
  * @brief Sets a specific index in the rational evaluations structure to a
  * trivial value (numerator only).
@@ -63,6 +63,16 @@ TEST_F(UnivariateRationalEvaluationsTest, SetZero) {
   EXPECT_TRUE(reinterpret_cast<RationalEvals&>(*evals_)[0].IsZero());
 }
 
+TEST_F(UnivariateRationalEvaluationsTest, Reset) {
+  tachyon_bn254_univariate_rational_evaluations_reset(evals_);
+  EXPECT_EQ(tachyon_bn254_univariate_rational_evaluations_len(evals_),
+            kDegree + 1);
+  for (const RationalField<bn254::Fr>& value :
+       reinterpret_cast<RationalEvals&>(*evals_).evaluations()) {
+    EXPECT_TRUE(value.IsZero());
+  }
+}
+
 TEST_F(UnivariateRationalEvaluationsTest, SetTrivial) {
   RationalField<bn254::Fr> expected =
       RationalField<bn254::Fr>(bn254::Fr::Random());
@@ -7,3 +7,5 @@ const char* tachyon_get_runtime_version_str() { return TACHYON_C_VERSION_STR; }
 const char* tachyon_get_runtime_full_version_str() {
   return TACHYON_C_VERSION_FULL_STR;
 }
+
+uint32_t tachyon_get_abi_version() { return TACHYON_C_ABI_VERSION; }
@@ -6,6 +6,11 @@
 #include "tachyon/c/export.h"
 #include "tachyon/c/version_generated.h"
 
+// The version of the layouts and enum values exposed through the C API. Bump
+// this whenever one of them changes, so that bindings built against an older
+// layout can refuse to run.
+#define TACHYON_C_ABI_VERSION 1
+
 /**
  * @file version.h
  * @brief Version information for tachyon.
@@ -38,6 +43,14 @@ TACHYON_C_EXPORT const char* tachyon_get_runtime_version_str();
  */
 TACHYON_C_EXPORT const char* tachyon_get_runtime_full_version_str();
 
+/**
+ * @brief Returns the ABI version of tachyon.
+ * @return ABI version number as a uint32_t. This is TACHYON_C_ABI_VERSION of
+ * the library that is linked, which may differ from the one of the headers a
+ * binding was built against.
+ */
+TACHYON_C_EXPORT uint32_t tachyon_get_abi_version();
+
 #ifdef __cplusplus
 }  // extern "C"
 #endif
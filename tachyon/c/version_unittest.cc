@@ -18,4 +18,5 @@ TEST(VersionTest, RunTimeVersionTest) {
             std::string_view(tachyon_get_runtime_version_str()));
   EXPECT_EQ(TACHYON_C_VERSION_FULL_STR,
             std::string_view(tachyon_get_runtime_full_version_str()));
+  EXPECT_EQ(TACHYON_C_ABI_VERSION, tachyon_get_abi_version());
 }
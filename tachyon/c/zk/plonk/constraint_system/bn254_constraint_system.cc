@@ -9,11 +9,36 @@ using namespace tachyon;
 
 using CS = zk::plonk::ConstraintSystem<math::bn254::Fr>;
 
+namespace {
+
+template <zk::plonk::ColumnType C>
+void GetQueries(const std::vector<zk::plonk::QueryData<C>>& cpp_queries,
+                tachyon_plonk_column_query* queries, size_t* queries_len) {
+  *queries_len = cpp_queries.size();
+  if (queries == nullptr) return;
+  for (size_t i = 0; i < cpp_queries.size(); ++i) {
+    queries[i].index = cpp_queries[i].column().index();
+    queries[i].rotation = cpp_queries[i].rotation().value();
+  }
+}
+
+}  // namespace
+
 uint32_t tachyon_bn254_plonk_constraint_system_compute_blinding_factors(
     const tachyon_bn254_plonk_constraint_system* cs) {
   return reinterpret_cast<const CS*>(cs)->ComputeBlindingFactors();
 }
 
+size_t tachyon_bn254_plonk_constraint_system_compute_degree(
+    const tachyon_bn254_plonk_constraint_system* cs) {
+  return reinterpret_cast<const CS*>(cs)->ComputeDegree();
+}
+
+uint32_t tachyon_bn254_plonk_constraint_system_compute_extended_k(
+    const tachyon_bn254_plonk_constraint_system* cs, uint32_t k) {
+  return reinterpret_cast<const CS*>(cs)->ComputeExtendedK(k);
+}
+
 void tachyon_bn254_plonk_constraint_system_get_advice_column_phases(
     const tachyon_bn254_plonk_constraint_system* cs, tachyon_phase* phases,
     size_t* phases_len) {
@@ -55,6 +80,16 @@ size_t tachyon_bn254_plonk_constraint_system_get_num_fixed_columns(
   return reinterpret_cast<const CS*>(cs)->num_fixed_columns();
 }
 
+size_t tachyon_bn254_plonk_constraint_system_get_num_lookups(
+    const tachyon_bn254_plonk_constraint_system* cs) {
+  return reinterpret_cast<const CS*>(cs)->lookups().size();
+}
+
+size_t tachyon_bn254_plonk_constraint_system_get_num_permutation_columns(
+    const tachyon_bn254_plonk_constraint_system* cs) {
+  return reinterpret_cast<const CS*>(cs)->permutation().columns().size();
+}
+
 size_t tachyon_bn254_plonk_constraint_system_get_num_instance_columns(
     const tachyon_bn254_plonk_constraint_system* cs) {
   return reinterpret_cast<const CS*>(cs)->num_instance_columns();
@@ -81,3 +116,48 @@ void tachyon_bn254_plonk_constraint_system_get_constants(
     constants[i].index = cpp_constants[i].index();
   }
 }
+
+void tachyon_bn254_plonk_constraint_system_get_selector_map(
+    const tachyon_bn254_plonk_constraint_system* cs,
+    tachyon_fixed_column_key* selector_map, size_t* selector_map_len) {
+  const std::vector<zk::plonk::FixedColumnKey>& cpp_selector_map =
+      reinterpret_cast<const CS*>(cs)->selector_map();
+  *selector_map_len = cpp_selector_map.size();
+  if (selector_map == nullptr) return;
+  for (size_t i = 0; i < cpp_selector_map.size(); ++i) {
+    selector_map[i].index = cpp_selector_map[i].index();
+  }
+}
+
+void tachyon_bn254_plonk_constraint_system_get_fixed_query_columns(
+    const tachyon_bn254_plonk_constraint_system* cs,
+    tachyon_fixed_column_key* columns, size_t* columns_len) {
+  const std::vector<zk::plonk::FixedQueryData>& cpp_fixed_queries =
+      reinterpret_cast<const CS*>(cs)->fixed_queries();
+  *columns_len = cpp_fixed_queries.size();
+  if (columns == nullptr) return;
+  for (size_t i = 0; i < cpp_fixed_queries.size(); ++i) {
+    columns[i].index = cpp_fixed_queries[i].column().index();
+  }
+}
+
+void tachyon_bn254_plonk_constraint_system_get_advice_queries(
+    const tachyon_bn254_plonk_constraint_system* cs,
+    tachyon_plonk_column_query* queries, size_t* queries_len) {
+  GetQueries(reinterpret_cast<const CS*>(cs)->advice_queries(), queries,
+             queries_len);
+}
+
+void tachyon_bn254_plonk_constraint_system_get_instance_queries(
+    const tachyon_bn254_plonk_constraint_system* cs,
+    tachyon_plonk_column_query* queries, size_t* queries_len) {
+  GetQueries(reinterpret_cast<const CS*>(cs)->instance_queries(), queries,
+             queries_len);
+}
+
+void tachyon_bn254_plonk_constraint_system_get_fixed_queries(
+    const tachyon_bn254_plonk_constraint_system* cs,
+    tachyon_plonk_column_query* queries, size_t* queries_len) {
+  GetQueries(reinterpret_cast<const CS*>(cs)->fixed_queries(), queries,
+             queries_len);
+}
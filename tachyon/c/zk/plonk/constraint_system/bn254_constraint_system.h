@@ -16,6 +16,7 @@
 #include "tachyon/c/export.h"
 #include "tachyon/c/zk/plonk/constraint_system/column_key.h"
 #include "tachyon/c/zk/plonk/constraint_system/phase.h"
+#include "tachyon/c/zk/plonk/constraint_system/query.h"
 
 /**
  * @struct tachyon_bn254_plonk_constraint_system
@@ -37,6 +38,28 @@ TACHYON_C_EXPORT uint32_t
 tachyon_bn254_plonk_constraint_system_compute_blinding_factors(
     const tachyon_bn254_plonk_constraint_system* cs);
 
+/**
+ * @brief Computes the degree of the constraint system, which is the maximum
+ * degree of all gates, lookups and the permutation argument.
+ *
+ * @param cs Pointer to the constraint system structure.
+ * @return The degree of the constraint system.
+ */
+TACHYON_C_EXPORT size_t tachyon_bn254_plonk_constraint_system_compute_degree(
+    const tachyon_bn254_plonk_constraint_system* cs);
+
+/**
+ * @brief Computes the log size of the extended domain used for the quotient
+ * polynomial.
+ *
+ * @param cs Pointer to the constraint system structure.
+ * @param k The log size of the domain.
+ * @return The log size of the extended domain.
+ */
+TACHYON_C_EXPORT uint32_t
+tachyon_bn254_plonk_constraint_system_compute_extended_k(
+    const tachyon_bn254_plonk_constraint_system* cs, uint32_t k);
+
 /**
  * @brief Retrieves the phases for advice columns within the constraint system.
  *
@@ -89,6 +112,26 @@ TACHYON_C_EXPORT size_t
 tachyon_bn254_plonk_constraint_system_get_num_fixed_columns(
     const tachyon_bn254_plonk_constraint_system* cs);
 
+/**
+ * @brief Retrieves the number of lookup arguments in the constraint system.
+ *
+ * @param cs Pointer to the constraint system structure.
+ * @return The number of lookup arguments.
+ */
+TACHYON_C_EXPORT size_t tachyon_bn254_plonk_constraint_system_get_num_lookups(
+    const tachyon_bn254_plonk_constraint_system* cs);
+
+/**
+ * @brief Retrieves the number of columns participating in the permutation
+ * argument.
+ *
+ * @param cs Pointer to the constraint system structure.
+ * @return The number of permutation columns.
+ */
+TACHYON_C_EXPORT size_t
+tachyon_bn254_plonk_constraint_system_get_num_permutation_columns(
+    const tachyon_bn254_plonk_constraint_system* cs);
+
 /**
  * @brief Retrieves the number of instance columns in the constraint system.
  *
@@ -147,6 +190,85 @@ TACHYON_C_EXPORT void tachyon_bn254_plonk_constraint_system_get_constants(
     const tachyon_bn254_plonk_constraint_system* cs,
     tachyon_fixed_column_key* constants, size_t* constants_len);
 
+/**
+ * @brief Retrieves the fixed column every selector was folded into by keygen,
+ * indexed by selector. It is used the same way as
+ * tachyon_bn254_plonk_constraint_system_get_constants(): with `selector_map`
+ * NULL, only `selector_map_len` is populated.
+ *
+ * @param cs Pointer to the constraint system structure.
+ * @param selector_map Pointer to the array where the fixed columns will be
+ * stored, or NULL.
+ * @param selector_map_len Pointer to a size_t variable where the function will
+ * store the number of selectors.
+ */
+TACHYON_C_EXPORT void tachyon_bn254_plonk_constraint_system_get_selector_map(
+    const tachyon_bn254_plonk_constraint_system* cs,
+    tachyon_fixed_column_key* selector_map, size_t* selector_map_len);
+
+/**
+ * @brief Retrieves the column of every fixed query of the constraint system,
+ * i.e, the fixed columns its gates and lookups refer to. A column queried at
+ * several rotations is listed once for each. It is used the same way as
+ * tachyon_bn254_plonk_constraint_system_get_constants().
+ *
+ * @param cs Pointer to the constraint system structure.
+ * @param columns Pointer to the array where the columns will be stored, or
+ * NULL.
+ * @param columns_len Pointer to a size_t variable where the function will
+ * store the number of fixed queries.
+ */
+TACHYON_C_EXPORT void
+tachyon_bn254_plonk_constraint_system_get_fixed_query_columns(
+    const tachyon_bn254_plonk_constraint_system* cs,
+    tachyon_fixed_column_key* columns, size_t* columns_len);
+
+/**
+ * @brief Retrieves every advice query of the constraint system, in the order
+ * the proof evaluates them. It is used the same way as
+ * tachyon_bn254_plonk_constraint_system_get_constants().
+ *
+ * @param cs Pointer to the constraint system structure.
+ * @param queries Pointer to the array where the queries will be stored, or
+ * NULL.
+ * @param queries_len Pointer to a size_t variable where the function will
+ * store the number of advice queries.
+ */
+TACHYON_C_EXPORT void tachyon_bn254_plonk_constraint_system_get_advice_queries(
+    const tachyon_bn254_plonk_constraint_system* cs,
+    tachyon_plonk_column_query* queries, size_t* queries_len);
+
+/**
+ * @brief Retrieves every instance query of the constraint system, in the order
+ * the proof evaluates them. It is used the same way as
+ * tachyon_bn254_plonk_constraint_system_get_constants().
+ *
+ * @param cs Pointer to the constraint system structure.
+ * @param queries Pointer to the array where the queries will be stored, or
+ * NULL.
+ * @param queries_len Pointer to a size_t variable where the function will
+ * store the number of instance queries.
+ */
+TACHYON_C_EXPORT void
+tachyon_bn254_plonk_constraint_system_get_instance_queries(
+    const tachyon_bn254_plonk_constraint_system* cs,
+    tachyon_plonk_column_query* queries, size_t* queries_len);
+
+/**
+ * @brief Retrieves every fixed query of the constraint system, in the order
+ * the proof evaluates them. It is used the same way as
+ * tachyon_bn254_plonk_constraint_system_get_constants().
+ *
+ * @param cs Pointer to the constraint system structure.
+ * @param queries Pointer to the array where the queries will be stored, or
+ * NULL.
+ * @param queries_len Pointer to a size_t variable where the function will
+ * store the number of fixed queries.
+ */
+TACHYON_C_EXPORT void tachyon_bn254_plonk_constraint_system_get_fixed_queries(
+    const tachyon_bn254_plonk_constraint_system* cs,
+    tachyon_plonk_column_query* queries, size_t* queries_len);
+
 #ifdef __cplusplus
 }  // extern "C"
 #endif
@@ -28,6 +28,16 @@ TEST_F(ConstraintSystemTest, ComputeBlindingFactors) {
             5);
 }
 
+TEST_F(ConstraintSystemTest, ComputeDegree) {
+  // The permutation argument alone requires degree 3.
+  EXPECT_EQ(tachyon_bn254_plonk_constraint_system_compute_degree(cs_), 3);
+}
+
+TEST_F(ConstraintSystemTest, ComputeExtendedK) {
+  EXPECT_EQ(tachyon_bn254_plonk_constraint_system_compute_extended_k(cs_, 4),
+            5);
+}
+
 TEST_F(ConstraintSystemTest, GetAdviceColumnPhases) {
   size_t phases_len;
   for (uint8_t i = 0; i < 3; ++i) {
@@ -96,6 +106,19 @@ TEST_F(ConstraintSystemTest, GetNumFixedColumns) {
   }
 }
 
+TEST_F(ConstraintSystemTest, GetNumLookups) {
+  EXPECT_EQ(tachyon_bn254_plonk_constraint_system_get_num_lookups(cs_), 0);
+}
+
+TEST_F(ConstraintSystemTest, GetNumPermutationColumns) {
+  for (uint8_t i = 0; i < 3; ++i) {
+    EXPECT_EQ(
+        tachyon_bn254_plonk_constraint_system_get_num_permutation_columns(cs_),
+        i);
+    cpp_cs_.EnableEquality(AnyColumnKey(cpp_cs_.CreateAdviceColumn()));
+  }
+}
+
 TEST_F(ConstraintSystemTest, GetNumInstanceColumns) {
   for (uint8_t i = 0; i < 3; ++i) {
     EXPECT_EQ(
@@ -146,4 +169,70 @@ TEST_F(ConstraintSystemTest, GetConstants) {
   }
 }
 
+TEST_F(ConstraintSystemTest, GetSelectorMap) {
+  cpp_cs_.CreateFixedColumn();
+  cpp_cs_.CreateComplexSelector();
+  cpp_cs_.CreateComplexSelector();
+
+  size_t selector_map_len;
+  tachyon_bn254_plonk_constraint_system_get_selector_map(cs_, nullptr,
+                                                         &selector_map_len);
+  ASSERT_EQ(selector_map_len, 0);
+  size_t columns_len;
+  tachyon_bn254_plonk_constraint_system_get_fixed_query_columns(cs_, nullptr,
+                                                                &columns_len);
+  ASSERT_EQ(columns_len, 0);
+
+  // Complex selectors are never combined, so each gets a column of its own
+  // after the one created above.
+  cpp_cs_.CompressSelectors({{true, false}, {false, true}});
+
+  tachyon_fixed_column_key selector_map[2];
+  tachyon_bn254_plonk_constraint_system_get_selector_map(cs_, selector_map,
+                                                         &selector_map_len);
+  ASSERT_EQ(selector_map_len, 2);
+  tachyon_fixed_column_key columns[2];
+  tachyon_bn254_plonk_constraint_system_get_fixed_query_columns(cs_, columns,
+                                                                &columns_len);
+  ASSERT_EQ(columns_len, 2);
+  for (size_t i = 0; i < 2; ++i) {
+    EXPECT_EQ(selector_map[i].index, i + 1);
+    EXPECT_EQ(columns[i].index, i + 1);
+  }
+}
+
+TEST_F(ConstraintSystemTest, GetQueries) {
+  AdviceColumnKey advice = cpp_cs_.CreateAdviceColumn();
+  InstanceColumnKey instance = cpp_cs_.CreateInstanceColumn();
+  FixedColumnKey fixed = cpp_cs_.CreateFixedColumn();
+  cpp_cs_.QueryAdviceIndex(advice, Rotation::Cur());
+  cpp_cs_.QueryAdviceIndex(advice, Rotation::Next());
+  cpp_cs_.QueryInstanceIndex(instance, Rotation::Prev());
+  cpp_cs_.QueryFixedIndex(fixed, Rotation(3));
+
+  size_t queries_len;
+  tachyon_bn254_plonk_constraint_system_get_advice_queries(cs_, nullptr,
+                                                           &queries_len);
+  ASSERT_EQ(queries_len, 2);
+  tachyon_plonk_column_query queries[2];
+  tachyon_bn254_plonk_constraint_system_get_advice_queries(cs_, queries,
+                                                           &queries_len);
+  EXPECT_EQ(queries[0].index, 0);
+  EXPECT_EQ(queries[0].rotation, 0);
+  EXPECT_EQ(queries[1].index, 0);
+  EXPECT_EQ(queries[1].rotation, 1);
+
+  tachyon_bn254_plonk_constraint_system_get_instance_queries(cs_, queries,
+                                                             &queries_len);
+  ASSERT_EQ(queries_len, 1);
+  EXPECT_EQ(queries[0].index, 0);
+  EXPECT_EQ(queries[0].rotation, -1);
+
+  tachyon_bn254_plonk_constraint_system_get_fixed_queries(cs_, queries,
+                                                          &queries_len);
+  ASSERT_EQ(queries_len, 1);
+  EXPECT_EQ(queries[0].index, 0);
+  EXPECT_EQ(queries[0].rotation, 3);
+}
+
 }  // namespace tachyon::zk::plonk
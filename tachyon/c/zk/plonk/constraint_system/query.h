@@ -0,0 +1,28 @@
+/**
+ * @file
+ * @brief Column Queries for PLONK Constraint System.
+ *
+ * This header file defines the tachyon_plonk_column_query structure, which
+ * represents a query of a column at a rotation within the PLONK constraint
+ * system.
+ */
+#ifndef TACHYON_C_ZK_PLONK_CONSTRAINT_SYSTEM_QUERY_H_
+#define TACHYON_C_ZK_PLONK_CONSTRAINT_SYSTEM_QUERY_H_
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * @struct tachyon_plonk_column_query
+ * @brief A query of a column at a rotation.
+ *
+ * The gates and lookups of a constraint system refer to the value of a column
+ * at a row relative to the current one. The proof evaluates the column's
+ * polynomial at x·ω^rotation for each such query.
+ */
+struct tachyon_plonk_column_query {
+  size_t index;
+  int32_t rotation;
+};
+
+#endif  // TACHYON_C_ZK_PLONK_CONSTRAINT_SYSTEM_QUERY_H_
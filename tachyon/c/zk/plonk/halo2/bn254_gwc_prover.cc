@@ -6,6 +6,7 @@
 #include <utility>
 #include <vector>
 
+#include "tachyon/base/buffer/buffer.h"
 #include "tachyon/base/logging.h"
 #include "tachyon/c/math/elliptic_curves/bn/bn254/g1_point_traits.h"
 #include "tachyon/c/zk/plonk/halo2/bn254_gwc_pcs.h"
@@ -15,6 +16,7 @@
 #include "tachyon/c/zk/plonk/keys/proving_key_impl_base.h"
 #include "tachyon/math/polynomials/univariate/univariate_evaluation_domain_factory.h"
 #include "tachyon/zk/plonk/halo2/blake2b_transcript.h"
+#include "tachyon/zk/plonk/halo2/callback_transcript.h"
 #include "tachyon/zk/plonk/halo2/poseidon_transcript.h"
 #include "tachyon/zk/plonk/halo2/prover.h"
 #include "tachyon/zk/plonk/halo2/sha256_transcript.h"
@@ -65,6 +67,12 @@ tachyon_halo2_bn254_gwc_prover_create_from_unsafe_setup(
                 std::move(write_buf));
             break;
           }
+          case zk::plonk::halo2::TranscriptType::kCustom: {
+            writer = std::make_unique<
+                zk::plonk::halo2::CallbackWriter<math::bn254::G1AffinePoint>>(
+                std::move(write_buf));
+            break;
+          }
         }
         CHECK(writer);
         zk::plonk::halo2::Prover<PCS, LS> prover =
@@ -115,6 +123,12 @@ tachyon_halo2_bn254_gwc_prover_create_from_params(uint8_t transcript_type,
                 std::move(write_buf));
             break;
           }
+          case zk::plonk::halo2::TranscriptType::kCustom: {
+            writer = std::make_unique<
+                zk::plonk::halo2::CallbackWriter<math::bn254::G1AffinePoint>>(
+                std::move(write_buf));
+            break;
+          }
         }
         CHECK(writer);
         zk::plonk::halo2::Prover<PCS, LS> prover =
@@ -226,10 +240,43 @@ void tachyon_halo2_bn254_gwc_prover_set_transcript_state(
       prover_impl->SetTranscript(state_span, std::move(writer));
       return;
     }
+    case zk::plonk::halo2::TranscriptType::kCustom:
+      // The state of a custom transcript is kept by its hasher, see
+      // |tachyon_halo2_bn254_gwc_prover_set_transcript_callbacks()|.
+      break;
   }
   NOTREACHED();
 }
 
+void tachyon_halo2_bn254_gwc_prover_set_transcript_callbacks(
+    tachyon_halo2_bn254_gwc_prover* prover,
+    const tachyon_halo2_bn254_transcript_callbacks* callbacks) {
+  using CallbackWriter =
+      zk::plonk::halo2::CallbackWriter<math::bn254::G1AffinePoint>;
+
+  ProverImpl* prover_impl = reinterpret_cast<ProverImpl*>(prover);
+  CHECK_EQ(prover_impl->transcript_type(), TACHYON_HALO2_CUSTOM_TRANSCRIPT);
+  tachyon_halo2_bn254_transcript_callbacks c = *callbacks;
+  std::unique_ptr<CallbackWriter> writer =
+      std::make_unique<CallbackWriter>(base::Uint8VectorBuffer());
+  writer->SetCallbacks(
+      [c](const math::bn254::Fr& scalar) {
+        c.absorb_scalar(c.ctx,
+                        reinterpret_cast<const tachyon_bn254_fr*>(&scalar));
+      },
+      [c](const math::bn254::G1AffinePoint& point) {
+        math::Point2<math::bn254::Fq> point2(point.x(), point.y());
+        c.absorb_point(
+            c.ctx, reinterpret_cast<const tachyon_bn254_g1_point2*>(&point2));
+      },
+      [c]() {
+        tachyon_bn254_fr challenge;
+        c.squeeze(c.ctx, &challenge);
+        return reinterpret_cast<const math::bn254::Fr&>(challenge);
+      });
+  prover_impl->SetTranscript(absl::Span<const uint8_t>(), std::move(writer));
+}
+
 void tachyon_halo2_bn254_gwc_prover_set_extended_domain(
     tachyon_halo2_bn254_gwc_prover* prover,
     const tachyon_bn254_plonk_proving_key* pk) {
@@ -243,6 +290,38 @@ void tachyon_halo2_bn254_gwc_prover_set_extended_domain(
       PCS::ExtendedDomain::Create(size_t{1} << extended_k));
 }
 
+void tachyon_halo2_bn254_gwc_prover_set_intra_proof_parallelism(
+    tachyon_halo2_bn254_gwc_prover* prover, size_t intra_proof_parallelism) {
+  reinterpret_cast<ProverImpl*>(prover)->set_intra_proof_parallelism(
+      intra_proof_parallelism);
+}
+
+void tachyon_halo2_bn254_gwc_prover_set_gpu_stages(
+    tachyon_halo2_bn254_gwc_prover* prover, uint8_t gpu_stages) {
+  reinterpret_cast<ProverImpl*>(prover)->set_gpu_stages(gpu_stages);
+}
+
+void tachyon_halo2_bn254_gwc_prover_set_num_threads(
+    tachyon_halo2_bn254_gwc_prover* prover, size_t num_threads) {
+  reinterpret_cast<ProverImpl*>(prover)->set_num_threads(num_threads);
+}
+
+void tachyon_halo2_bn254_gwc_prover_set_fft_chunk_log2(
+    tachyon_halo2_bn254_gwc_prover* prover, uint32_t fft_chunk_log2) {
+  reinterpret_cast<ProverImpl*>(prover)->set_fft_chunk_log2(fft_chunk_log2);
+}
+
+bool tachyon_halo2_bn254_gwc_prover_set_lagrange_basis_policy(
+    tachyon_halo2_bn254_gwc_prover* prover, uint8_t policy) {
+  return reinterpret_cast<ProverImpl*>(prover)->SetLagrangeBasisPolicy(policy);
+}
+
+void tachyon_halo2_bn254_gwc_prover_set_record_gate_costs(
+    tachyon_halo2_bn254_gwc_prover* prover, bool record_gate_costs) {
+  reinterpret_cast<ProverImpl*>(prover)->set_record_gate_costs(
+      record_gate_costs);
+}
+
 void tachyon_halo2_bn254_gwc_prover_create_proof(
     tachyon_halo2_bn254_gwc_prover* prover, tachyon_bn254_plonk_proving_key* pk,
     tachyon_halo2_bn254_argument_data* data) {
@@ -261,6 +340,39 @@ void tachyon_halo2_bn254_gwc_prover_get_proof(
   memcpy(proof, buffer.data(), buffer.size());
 }
 
+void tachyon_halo2_bn254_gwc_prover_get_transcript_state(
+    const tachyon_halo2_bn254_gwc_prover* prover, uint8_t* state,
+    size_t* state_len) {
+  std::vector<uint8_t> transcript_state =
+      reinterpret_cast<const ProverImpl*>(prover)->GetTranscriptState();
+  *state_len = transcript_state.size();
+  if (state == nullptr) return;
+  memcpy(state, transcript_state.data(), transcript_state.size());
+}
+
+void tachyon_halo2_bn254_gwc_prover_get_pcs_params(
+    const tachyon_halo2_bn254_gwc_prover* prover, uint8_t* params,
+    size_t* params_len) {
+  const PCS& pcs = reinterpret_cast<const ProverImpl*>(prover)->pcs();
+  *params_len = base::EstimateSize(pcs);
+  if (params == nullptr) return;
+  base::Buffer buffer(params, *params_len);
+  CHECK(buffer.Write(pcs));
+}
+
+void tachyon_halo2_bn254_gwc_prover_get_gate_costs(
+    const tachyon_halo2_bn254_gwc_prover* prover,
+    tachyon_halo2_gate_cost* gate_costs, size_t* gate_costs_len) {
+  const std::vector<zk::plonk::GateCost>& costs =
+      reinterpret_cast<const ProverImpl*>(prover)->gate_costs();
+  *gate_costs_len = costs.size();
+  if (gate_costs == nullptr) return;
+  for (size_t i = 0; i < costs.size(); ++i) {
+    gate_costs[i].nanos = costs[i].nanos;
+    gate_costs[i].num_ops = costs[i].num_ops;
+  }
+}
+
 void tachyon_halo2_bn254_gwc_prover_set_transcript_repr(
     const tachyon_halo2_bn254_gwc_prover* prover,
     tachyon_bn254_plonk_proving_key* pk) {
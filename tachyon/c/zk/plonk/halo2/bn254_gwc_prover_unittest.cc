@@ -13,6 +13,7 @@
 #include "tachyon/c/math/elliptic_curves/point_conversions.h"
 #include "tachyon/c/math/polynomials/constants.h"
 #include "tachyon/c/zk/plonk/halo2/bn254_transcript.h"
+#include "tachyon/c/zk/plonk/halo2/kzg_family_prover_impl.h"
 #include "tachyon/c/zk/plonk/halo2/test/bn254_halo2_params_data.h"
 #include "tachyon/math/elliptic_curves/bn/bn254/bn254.h"
 #include "tachyon/zk/base/commitments/gwc_extension.h"
@@ -137,6 +138,47 @@ TEST_P(GWCProverTest, SetRng) {
   tachyon_rng_destroy(rng);
 }
 
+TEST_P(GWCProverTest, SetIntraProofParallelism) {
+  EXPECT_EQ(
+      (reinterpret_cast<Prover<PCS, LS>*>(prover_)->intra_proof_parallelism()),
+      1);
+  tachyon_halo2_bn254_gwc_prover_set_intra_proof_parallelism(prover_, 4);
+  EXPECT_EQ(
+      (reinterpret_cast<Prover<PCS, LS>*>(prover_)->intra_proof_parallelism()),
+      4);
+}
+
+TEST_P(GWCProverTest, SetGpuStages) {
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->gpu_stages()), 0);
+  uint8_t gpu_stages = TACHYON_HALO2_STAGE_MSM | TACHYON_HALO2_STAGE_QUOTIENT;
+  tachyon_halo2_bn254_gwc_prover_set_gpu_stages(prover_, gpu_stages);
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->gpu_stages()),
+            gpu_stages);
+}
+
+TEST_P(GWCProverTest, SetFftChunkLog2) {
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->fft_chunk_log2()), 0);
+  tachyon_halo2_bn254_gwc_prover_set_fft_chunk_log2(prover_, 12);
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->fft_chunk_log2()),
+            12);
+}
+
+TEST_P(GWCProverTest, SetNumThreads) {
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->num_threads()), 0);
+  tachyon_halo2_bn254_gwc_prover_set_num_threads(prover_, 1);
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->num_threads()), 1);
+}
+
+TEST_P(GWCProverTest, SetLagrangeBasisPolicy) {
+  EXPECT_TRUE(tachyon_halo2_bn254_gwc_prover_set_lagrange_basis_policy(
+      prover_, TACHYON_HALO2_LAGRANGE_BASES_RESIDENT));
+  EXPECT_FALSE(tachyon_halo2_bn254_gwc_prover_set_lagrange_basis_policy(
+      prover_, TACHYON_HALO2_LAGRANGE_BASES_VIA_MONOMIAL));
+  using ProverImpl = c::zk::plonk::halo2::KZGFamilyProverImpl<PCS, LS>;
+  EXPECT_EQ(reinterpret_cast<ProverImpl*>(prover_)->lagrange_basis_policy(),
+            TACHYON_HALO2_LAGRANGE_BASES_RESIDENT);
+}
+
 TEST_P(GWCProverTest, SetTranscript) {
   uint8_t transcript_type = GetParam();
 
@@ -170,6 +212,8 @@ TEST_P(GWCProverTest, SetTranscript) {
       state_len = sha256->GetStateLen();
       break;
     }
+    case TranscriptType::kCustom:
+      GTEST_SKIP() << "A custom transcript has no writer in tachyon";
   }
 
   std::vector<uint8_t> data = base::CreateVector(
@@ -191,6 +235,14 @@ TEST_P(GWCProverTest, SetTranscript) {
   tachyon_halo2_bn254_gwc_prover_set_transcript_state(prover_, state.data(),
                                                       state_len);
 
+  size_t transcript_state_len;
+  tachyon_halo2_bn254_gwc_prover_get_transcript_state(
+      prover_, nullptr, &transcript_state_len);
+  std::vector<uint8_t> transcript_state(transcript_state_len);
+  tachyon_halo2_bn254_gwc_prover_get_transcript_state(
+      prover_, transcript_state.data(), &transcript_state_len);
+  EXPECT_EQ(transcript_state, state);
+
   EXPECT_EQ(
       (reinterpret_cast<Prover<PCS, LS>*>(prover_)
            ->transcript()
@@ -202,4 +254,58 @@ TEST_P(GWCProverTest, SetTranscript) {
   tachyon_halo2_bn254_transcript_writer_destroy(transcript);
 }
 
+
+namespace {
+
+// Sums everything absorbed, and squeezes the sum.
+struct SumHasher {
+  math::bn254::Fr sum = math::bn254::Fr::Zero();
+  size_t num_points = 0;
+};
+
+void AbsorbScalar(void* ctx, const tachyon_bn254_fr* scalar) {
+  SumHasher* hasher = reinterpret_cast<SumHasher*>(ctx);
+  hasher->sum += c::base::native_cast(*scalar);
+}
+
+void AbsorbPoint(void* ctx, const tachyon_bn254_g1_point2* point) {
+  SumHasher* hasher = reinterpret_cast<SumHasher*>(ctx);
+  ++hasher->num_points;
+}
+
+void Squeeze(void* ctx, tachyon_bn254_fr* challenge) {
+  SumHasher* hasher = reinterpret_cast<SumHasher*>(ctx);
+  *challenge = c::base::c_cast(hasher->sum);
+}
+
+}  // namespace
+
+TEST(GWCCustomTranscriptTest, SetTranscriptCallbacks) {
+  using PCS = GWCProverTest::PCS;
+  using LS = GWCProverTest::LS;
+
+  math::bn254::Fr s(2);
+  const tachyon_bn254_fr& c_s = c::base::c_cast(s);
+  tachyon_halo2_bn254_gwc_prover* prover =
+      tachyon_halo2_bn254_gwc_prover_create_from_unsafe_setup(
+          TACHYON_HALO2_CUSTOM_TRANSCRIPT, 5, &c_s);
+
+  SumHasher hasher;
+  tachyon_halo2_bn254_transcript_callbacks callbacks = {
+      &hasher, AbsorbScalar, AbsorbPoint, Squeeze};
+  tachyon_halo2_bn254_gwc_prover_set_transcript_callbacks(prover, &callbacks);
+
+  crypto::TranscriptWriter<math::bn254::G1AffinePoint>* writer =
+      reinterpret_cast<Prover<PCS, LS>*>(prover)->GetWriter();
+  ASSERT_TRUE(writer->WriteToTranscript(math::bn254::Fr(3)));
+  ASSERT_TRUE(writer->WriteToProof(math::bn254::Fr(4)));
+  ASSERT_TRUE(
+      writer->WriteToTranscript(math::bn254::G1AffinePoint::Generator()));
+  EXPECT_FALSE(writer->WriteToTranscript(math::bn254::G1AffinePoint::Zero()));
+  EXPECT_EQ(writer->SqueezeChallenge(), math::bn254::Fr(7));
+  EXPECT_EQ(hasher.num_points, size_t{1});
+
+  tachyon_halo2_bn254_gwc_prover_destroy(prover);
+}
+
 }  // namespace tachyon::zk::plonk::halo2::bn254
@@ -8,6 +8,7 @@
 #include "tachyon/c/zk/plonk/halo2/bn254_ls.h"
 #include "tachyon/c/zk/plonk/halo2/bn254_transcript.h"
 #include "tachyon/c/zk/plonk/halo2/verifier_impl.h"
+#include "tachyon/math/geometry/point2.h"
 #include "tachyon/math/polynomials/univariate/univariate_evaluation_domain_factory.h"
 #include "tachyon/zk/plonk/halo2/blake2b_transcript.h"
 #include "tachyon/zk/plonk/halo2/poseidon_transcript.h"
@@ -17,6 +18,25 @@
 
 using namespace tachyon;
 
+namespace {
+
+using Point2 = math::Point2<math::bn254::Fq>;
+
+// A halo2 point has no flag for the identity, which it writes as (0, 0).
+math::bn254::G1AffinePoint ToAffinePoint(const Point2& point) {
+  if (point.x.IsZero() && point.y.IsZero()) {
+    return math::bn254::G1AffinePoint::Zero();
+  }
+  return math::bn254::G1AffinePoint(point.x, point.y);
+}
+
+Point2 ToPoint2(const math::bn254::G1AffinePoint& point) {
+  if (point.IsZero()) return Point2();
+  return Point2(point.x(), point.y());
+}
+
+}  // namespace
+
 using PCS = c::zk::plonk::halo2::bn254::GWCPCS;
 using LS = c::zk::plonk::halo2::bn254::LS;
 using Verifier = c::zk::plonk::halo2::VerifierImpl<PCS, LS>;
@@ -58,6 +78,10 @@ tachyon_halo2_bn254_gwc_verifier_create_from_params(
                 std::move(read_buf));
             break;
           }
+          case zk::plonk::halo2::TranscriptType::kCustom:
+            // A proof with a custom transcript is verified on the side of
+            // its hasher.
+            break;
         }
         CHECK(reader);
         zk::plonk::halo2::Verifier<PCS, LS> verifier(std::move(pcs),
@@ -84,3 +108,48 @@ bool tachyon_halo2_bn254_gwc_verifier_verify_proof(
   tachyon_halo2_bn254_instance_columns_vec_destroy(instance_columns_vec);
   return ret;
 }
+
+bool tachyon_halo2_bn254_gwc_verifier_commit_instance_columns(
+    tachyon_halo2_bn254_gwc_verifier* verifier,
+    const tachyon_bn254_plonk_verifying_key* vkey,
+    const tachyon_bn254_fr* values, const size_t* column_lens,
+    size_t num_columns, tachyon_bn254_g1_point2* commitments) {
+  const math::bn254::Fr* value =
+      reinterpret_cast<const math::bn254::Fr*>(values);
+  std::vector<PCS::Evals> columns;
+  columns.reserve(num_columns);
+  for (size_t i = 0; i < num_columns; ++i) {
+    columns.emplace_back(
+        std::vector<math::bn254::Fr>(value, value + column_lens[i]));
+    value += column_lens[i];
+  }
+  std::vector<math::bn254::G1AffinePoint> ret;
+  if (!reinterpret_cast<Verifier*>(verifier)->CommitInstanceColumns(
+          reinterpret_cast<const VKey&>(*vkey), columns, &ret)) {
+    return false;
+  }
+  for (size_t i = 0; i < ret.size(); ++i) {
+    reinterpret_cast<Point2&>(commitments[i]) = ToPoint2(ret[i]);
+  }
+  return true;
+}
+
+bool tachyon_halo2_bn254_gwc_verifier_verify_proof_with_instance_commitments(
+    tachyon_halo2_bn254_gwc_verifier* verifier,
+    const tachyon_bn254_plonk_verifying_key* vkey,
+    const tachyon_bn254_g1_point2* commitments, size_t num_circuits,
+    size_t num_columns) {
+  const Point2* point = reinterpret_cast<const Point2*>(commitments);
+  std::vector<std::vector<math::bn254::G1AffinePoint>> instance_commitments_vec(
+      num_circuits);
+  for (std::vector<math::bn254::G1AffinePoint>& instance_commitments :
+       instance_commitments_vec) {
+    instance_commitments.reserve(num_columns);
+    for (size_t i = 0; i < num_columns; ++i) {
+      instance_commitments.push_back(ToAffinePoint(*point++));
+    }
+  }
+  return reinterpret_cast<Verifier*>(verifier)
+      ->VerifyProofWithInstanceCommitments(reinterpret_cast<const VKey&>(*vkey),
+                                           instance_commitments_vec);
+}
@@ -14,6 +14,8 @@
 #include <stdint.h>
 
 #include "tachyon/c/export.h"
+#include "tachyon/c/math/elliptic_curves/bn/bn254/fr.h"
+#include "tachyon/c/math/elliptic_curves/bn/bn254/g1.h"
 #include "tachyon/c/zk/plonk/halo2/bn254_instance_columns_vec.h"
 #include "tachyon/c/zk/plonk/keys/bn254_plonk_verifying_key.h"
 
@@ -78,6 +80,49 @@ TACHYON_C_EXPORT bool tachyon_halo2_bn254_gwc_verifier_verify_proof(
     const tachyon_bn254_plonk_verifying_key* vkey,
     tachyon_halo2_bn254_instance_columns_vec* instance_columns_vec);
 
+/**
+ * @brief Commits to instance columns the way
+ * @ref tachyon_halo2_bn254_gwc_verifier_verify_proof() does, so that they can
+ * be committed to once for many proofs and passed to
+ * @ref tachyon_halo2_bn254_gwc_verifier_verify_proof_with_instance_commitments().
+ * The commitments don't depend on the proof of @p verifier.
+ *
+ * @param verifier Pointer to the GWC verifier.
+ * @param vkey Pointer to the verifying key of the circuit of the columns.
+ * @param values The values of every column, one column after another.
+ * @param column_lens The number of values of every column.
+ * @param num_columns The number of columns.
+ * @param commitments Buffer to store the commitment to every column.
+ * @return False if a column has more values than usable rows.
+ */
+TACHYON_C_EXPORT bool tachyon_halo2_bn254_gwc_verifier_commit_instance_columns(
+    tachyon_halo2_bn254_gwc_verifier* verifier,
+    const tachyon_bn254_plonk_verifying_key* vkey,
+    const tachyon_bn254_fr* values, const size_t* column_lens,
+    size_t num_columns, tachyon_bn254_g1_point2* commitments);
+
+/**
+ * @brief Verifies a proof against a given verifying key and the commitments
+ * to the instance columns, which is all the verifier reads of them. A
+ * commitment to other values than the ones the proof was created with makes
+ * the proof fail.
+ *
+ * @param verifier Pointer to the GWC verifier.
+ * @param vkey Pointer to the verifying key against which the proof will be
+ * checked.
+ * @param commitments The commitment to every instance column of every circuit,
+ * circuit by circuit.
+ * @param num_circuits The number of circuits.
+ * @param num_columns The number of instance columns of every circuit.
+ * @return True if the proof is valid, false otherwise.
+ */
+TACHYON_C_EXPORT bool
+tachyon_halo2_bn254_gwc_verifier_verify_proof_with_instance_commitments(
+    tachyon_halo2_bn254_gwc_verifier* verifier,
+    const tachyon_bn254_plonk_verifying_key* vkey,
+    const tachyon_bn254_g1_point2* commitments, size_t num_circuits,
+    size_t num_columns);
+
 #ifdef __cplusplus
 }  // extern "C"
 #endif
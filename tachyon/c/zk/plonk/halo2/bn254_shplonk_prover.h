@@ -20,6 +20,9 @@
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluation_domain.h"
 #include "tachyon/c/zk/base/bn254_blinder.h"
 #include "tachyon/c/zk/plonk/halo2/bn254_argument_data.h"
+#include "tachyon/c/zk/plonk/halo2/bn254_transcript.h"
+#include "tachyon/c/zk/plonk/halo2/constants.h"
+#include "tachyon/c/zk/plonk/halo2/gate_cost.h"
 #include "tachyon/c/zk/plonk/keys/bn254_plonk_proving_key.h"
 
 /**
@@ -31,6 +34,30 @@
  */
 struct tachyon_halo2_bn254_shplonk_prover {};
 
+/**
+ * @struct tachyon_halo2_bn254_shplonk_prover_allocation_stats
+ * @brief The bytes the prover keeps allocated between proofs.
+ */
+struct tachyon_halo2_bn254_shplonk_prover_allocation_stats {
+  /// The powers of tau, in both the monomial and the Lagrange basis.
+  size_t srs_bytes;
+  /// The buffer the proof is written to.
+  size_t transcript_bytes;
+};
+
+/**
+ * @struct tachyon_halo2_bn254_shplonk_prover_scratch_stats
+ * @brief How the commitments of the prover reuse their scratch buffers.
+ */
+struct tachyon_halo2_bn254_shplonk_prover_scratch_stats {
+  /// The bytes held by the scratch buffers between commitments.
+  size_t bytes;
+  /// The commitments that had to allocate scratch buffers.
+  size_t num_allocations;
+  /// The commitments that ran in scratch buffers kept from earlier ones.
+  size_t num_reuses;
+};
+
 #ifdef __cplusplus
 extern "C" {
 #endif
@@ -169,6 +196,20 @@ TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_set_transcript_state(
     tachyon_halo2_bn254_shplonk_prover* prover, const uint8_t* state,
     size_t state_len);
 
+/**
+ * @brief Starts an empty transcript that absorbs and squeezes through
+ * |callbacks|, for a prover created with TACHYON_HALO2_CUSTOM_TRANSCRIPT. It
+ * takes the place of tachyon_halo2_bn254_shplonk_prover_set_transcript_state(),
+ * since the state is kept by the hasher behind |callbacks|, which has to
+ * outlive the proof.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param callbacks The hasher of the transcript.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_set_transcript_callbacks(
+    tachyon_halo2_bn254_shplonk_prover* prover,
+    const tachyon_halo2_bn254_transcript_callbacks* callbacks);
+
 /**
  * @brief Sets the extended evaluation domain based on the provided proving key.
  *
@@ -179,6 +220,76 @@ TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_set_extended_domain(
     tachyon_halo2_bn254_shplonk_prover* prover,
     const tachyon_bn254_plonk_proving_key* pk);
 
+/**
+ * @brief Sets how many independent lookup and permutation polynomials are
+ * computed at once. The proof doesn't depend on it. Defaults to 1.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param intra_proof_parallelism The number of threads to use.
+ */
+TACHYON_C_EXPORT void
+tachyon_halo2_bn254_shplonk_prover_set_intra_proof_parallelism(
+    tachyon_halo2_bn254_shplonk_prover* prover,
+    size_t intra_proof_parallelism);
+
+/**
+ * @brief Sets the stages to run on the GPU, as a mask of
+ * TACHYON_HALO2_STAGE_* bits. A stage without a GPU path runs on the CPU.
+ * The proof doesn't depend on it. Defaults to 0.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param gpu_stages The mask of stages.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_set_gpu_stages(
+    tachyon_halo2_bn254_shplonk_prover* prover, uint8_t gpu_stages);
+
+/**
+ * @brief Sets how many threads the parallel loops of |create_proof()| run on.
+ * 0 leaves it to OpenMP. The proof doesn't depend on it. Defaults to 0.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param num_threads The number of threads, or 0.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_set_num_threads(
+    tachyon_halo2_bn254_shplonk_prover* prover, size_t num_threads);
+
+/**
+ * @brief Sets the log₂ of the size of the IFFTs the IFFT of the extended
+ * domain is split into, which bounds the memory it works in at the cost of
+ * extra passes. 0 runs it in one piece. The proof doesn't depend on it.
+ * Defaults to 0.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param fft_chunk_log2 The log₂ of the chunk size, or 0.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_set_fft_chunk_log2(
+    tachyon_halo2_bn254_shplonk_prover* prover, uint32_t fft_chunk_log2);
+
+/**
+ * @brief Sets how commit_lagrange() gets the Lagrange bases, one of
+ * TACHYON_HALO2_LAGRANGE_BASES_*. The commitments don't depend on it.
+ * Defaults to TACHYON_HALO2_LAGRANGE_BASES_RESIDENT.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param policy The policy.
+ * @return True if the prover follows |policy|. Otherwise the policy is left
+ * as it was, and the caller has to commit another way to follow it.
+ */
+TACHYON_C_EXPORT bool
+tachyon_halo2_bn254_shplonk_prover_set_lagrange_basis_policy(
+    tachyon_halo2_bn254_shplonk_prover* prover, uint8_t policy);
+
+/**
+ * @brief Sets whether |create_proof()| records the cost of evaluating every
+ * custom gate, see @ref tachyon_halo2_bn254_shplonk_prover_get_gate_costs().
+ * Only every 64th row is timed. Defaults to false.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param record_gate_costs Whether to record the costs.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_set_record_gate_costs(
+    tachyon_halo2_bn254_shplonk_prover* prover, bool record_gate_costs);
+
 /**
  * @brief Generates a SHPLONK proof for the provided argument data.
  *
@@ -191,6 +302,21 @@ TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_create_proof(
     tachyon_bn254_plonk_proving_key* pk,
     tachyon_halo2_bn254_argument_data* data);
 
+/**
+ * @brief Creates a SHPLONK opening proof that the i-th polynomial evaluates
+ * to its value at the i-th point, without the rest of the PLONK protocol. The
+ * same polynomial may be given more than once to open it at several points.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param polys Array of pointers to the polynomials to open.
+ * @param points Array of points to open the polynomials at.
+ * @param num_openings The number of elements in |polys| and |points|.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_create_opening_proof(
+    tachyon_halo2_bn254_shplonk_prover* prover,
+    const tachyon_bn254_univariate_dense_polynomial* const* polys,
+    const tachyon_bn254_fr* points, size_t num_openings);
+
 /**
  * @brief Retrieves the generated SHPLONK proof.
  *
@@ -202,6 +328,77 @@ TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_get_proof(
     const tachyon_halo2_bn254_shplonk_prover* prover, uint8_t* proof,
     size_t* proof_len);
 
+/**
+ * @brief Retrieves the state of the transcript of the SHPLONK prover, which
+ * continues from the state set with
+ * @ref tachyon_halo2_bn254_shplonk_prover_set_transcript_state() through the
+ * proof. Call it with @p state set to NULL to get the length first.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param state Buffer to store the state.
+ * @param state_len Pointer to store the length of the state.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_get_transcript_state(
+    const tachyon_halo2_bn254_shplonk_prover* prover, uint8_t* state,
+    size_t* state_len);
+
+/**
+ * @brief Serializes the PCS params of the SHPLONK prover in the layout
+ * @ref tachyon_halo2_bn254_shplonk_verifier_create_from_params() reads. Call
+ * it with @p params set to NULL to get the length first.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param params Buffer to store the params.
+ * @param params_len Pointer to store the length of the params.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_get_pcs_params(
+    const tachyon_halo2_bn254_shplonk_prover* prover, uint8_t* params,
+    size_t* params_len);
+
+/**
+ * @brief Retrieves the cost of every custom gate in the last proof, in the
+ * order of the gates of the constraint system, or none if it wasn't recorded.
+ * Call it with @p gate_costs set to NULL to get the length first.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param gate_costs Buffer to store the costs.
+ * @param gate_costs_len Pointer to store the number of costs.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_get_gate_costs(
+    const tachyon_halo2_bn254_shplonk_prover* prover,
+    tachyon_halo2_gate_cost* gate_costs, size_t* gate_costs_len);
+
+/**
+ * @brief Reports the bytes the SHPLONK prover keeps allocated, to find what
+ * grows over many proofs.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param stats Pointer to store the allocation statistics.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_get_allocation_stats(
+    const tachyon_halo2_bn254_shplonk_prover* prover,
+    tachyon_halo2_bn254_shplonk_prover_allocation_stats* stats);
+
+/**
+ * @brief Reports how the commitments of the SHPLONK prover reuse the scratch
+ * buffers it keeps between them.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ * @param stats Pointer to store the scratch statistics.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_get_scratch_stats(
+    const tachyon_halo2_bn254_shplonk_prover* prover,
+    tachyon_halo2_bn254_shplonk_prover_scratch_stats* stats);
+
+/**
+ * @brief Frees the scratch buffers the SHPLONK prover keeps between
+ * commitments. The next commitment allocates them again.
+ *
+ * @param prover Pointer to the SHPLONK prover instance.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_prover_release_scratch(
+    tachyon_halo2_bn254_shplonk_prover* prover);
+
 /**
  * @brief Sets the representation of the transcript according to the proving
  * key. This is used for encoding the transcript in a specific way as defined by
@@ -13,7 +13,9 @@
 #include "tachyon/c/math/elliptic_curves/point_conversions.h"
 #include "tachyon/c/math/polynomials/constants.h"
 #include "tachyon/c/zk/plonk/halo2/bn254_transcript.h"
+#include "tachyon/c/zk/plonk/halo2/kzg_family_prover_impl.h"
 #include "tachyon/c/zk/plonk/halo2/test/bn254_halo2_params_data.h"
+#include "tachyon/crypto/commitments/polynomial_openings.h"
 #include "tachyon/math/elliptic_curves/bn/bn254/bn254.h"
 #include "tachyon/zk/base/commitments/shplonk_extension.h"
 #include "tachyon/zk/lookup/halo2/scheme.h"
@@ -139,6 +141,71 @@ TEST_P(SHPlonkProverTest, SetRng) {
   tachyon_rng_destroy(rng);
 }
 
+TEST_P(SHPlonkProverTest, SetIntraProofParallelism) {
+  EXPECT_EQ(
+      (reinterpret_cast<Prover<PCS, LS>*>(prover_)->intra_proof_parallelism()),
+      1);
+  tachyon_halo2_bn254_shplonk_prover_set_intra_proof_parallelism(prover_, 4);
+  EXPECT_EQ(
+      (reinterpret_cast<Prover<PCS, LS>*>(prover_)->intra_proof_parallelism()),
+      4);
+}
+
+TEST_P(SHPlonkProverTest, SetGpuStages) {
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->gpu_stages()), 0);
+  uint8_t gpu_stages = TACHYON_HALO2_STAGE_MSM | TACHYON_HALO2_STAGE_QUOTIENT;
+  tachyon_halo2_bn254_shplonk_prover_set_gpu_stages(prover_, gpu_stages);
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->gpu_stages()),
+            gpu_stages);
+}
+
+TEST_P(SHPlonkProverTest, SetFftChunkLog2) {
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->fft_chunk_log2()), 0);
+  tachyon_halo2_bn254_shplonk_prover_set_fft_chunk_log2(prover_, 12);
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->fft_chunk_log2()),
+            12);
+}
+
+TEST_P(SHPlonkProverTest, SetNumThreads) {
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->num_threads()), 0);
+  tachyon_halo2_bn254_shplonk_prover_set_num_threads(prover_, 1);
+  EXPECT_EQ((reinterpret_cast<Prover<PCS, LS>*>(prover_)->num_threads()), 1);
+}
+
+TEST_P(SHPlonkProverTest, SetLagrangeBasisPolicy) {
+  EXPECT_TRUE(tachyon_halo2_bn254_shplonk_prover_set_lagrange_basis_policy(
+      prover_, TACHYON_HALO2_LAGRANGE_BASES_RESIDENT));
+  EXPECT_FALSE(tachyon_halo2_bn254_shplonk_prover_set_lagrange_basis_policy(
+      prover_, TACHYON_HALO2_LAGRANGE_BASES_VIA_MONOMIAL));
+  using ProverImpl = c::zk::plonk::halo2::KZGFamilyProverImpl<PCS, LS>;
+  EXPECT_EQ(reinterpret_cast<ProverImpl*>(prover_)->lagrange_basis_policy(),
+            TACHYON_HALO2_LAGRANGE_BASES_RESIDENT);
+}
+
+TEST_P(SHPlonkProverTest, GetPcsParams) {
+  size_t params_len;
+  tachyon_halo2_bn254_shplonk_prover_get_pcs_params(prover_, nullptr,
+                                                    &params_len);
+  std::vector<uint8_t> params(params_len);
+  tachyon_halo2_bn254_shplonk_prover_get_pcs_params(prover_, params.data(),
+                                                    &params_len);
+  ASSERT_EQ(params.size(), params_len);
+
+  PCS pcs;
+  base::ReadOnlyBuffer read_buf(params.data(), params.size());
+  ASSERT_TRUE(read_buf.Read(&pcs));
+  EXPECT_EQ(pcs.N(), size_t{1} << k_);
+  EXPECT_EQ(pcs.SG2(),
+            (reinterpret_cast<Prover<PCS, LS>*>(prover_)->pcs().SG2()));
+}
+
+TEST_P(SHPlonkProverTest, GetAllocationStats) {
+  tachyon_halo2_bn254_shplonk_prover_allocation_stats stats;
+  tachyon_halo2_bn254_shplonk_prover_get_allocation_stats(prover_, &stats);
+  size_t n = size_t{1} << k_;
+  EXPECT_GE(stats.srs_bytes, 2 * n * sizeof(math::bn254::G1AffinePoint));
+}
+
 TEST_P(SHPlonkProverTest, SetTranscript) {
   uint8_t transcript_type = GetParam();
 
@@ -174,6 +241,8 @@ TEST_P(SHPlonkProverTest, SetTranscript) {
       state_len = sha256->GetStateLen();
       break;
     }
+    case TranscriptType::kCustom:
+      GTEST_SKIP() << "A custom transcript has no writer in tachyon";
   }
 
   std::vector<uint8_t> data = base::CreateVector(
@@ -195,6 +264,14 @@ TEST_P(SHPlonkProverTest, SetTranscript) {
   tachyon_halo2_bn254_shplonk_prover_set_transcript_state(prover_, state.data(),
                                                           state_len);
 
+  size_t transcript_state_len;
+  tachyon_halo2_bn254_shplonk_prover_get_transcript_state(
+      prover_, nullptr, &transcript_state_len);
+  std::vector<uint8_t> transcript_state(transcript_state_len);
+  tachyon_halo2_bn254_shplonk_prover_get_transcript_state(
+      prover_, transcript_state.data(), &transcript_state_len);
+  EXPECT_EQ(transcript_state, state);
+
   EXPECT_EQ(
       (reinterpret_cast<Prover<PCS, LS>*>(prover_)
            ->transcript()
@@ -206,4 +283,105 @@ TEST_P(SHPlonkProverTest, SetTranscript) {
   tachyon_halo2_bn254_transcript_writer_destroy(transcript);
 }
 
+TEST_P(SHPlonkProverTest, CreateOpeningProof) {
+  const tachyon_bn254_fr& c_s = c::base::c_cast(s_);
+  tachyon_halo2_bn254_shplonk_prover* expected_prover =
+      tachyon_halo2_bn254_shplonk_prover_create_from_unsafe_setup(GetParam(),
+                                                                  k_, &c_s);
+  Prover<PCS, LS>* expected_prover_impl =
+      reinterpret_cast<Prover<PCS, LS>*>(expected_prover);
+
+  std::vector<PCS::Domain::DensePoly> polys = base::CreateVector(
+      3, []() { return PCS::Domain::DensePoly::Random(5); });
+  std::vector<math::bn254::Fr> points = {math::bn254::Fr::Random(),
+                                         math::bn254::Fr::Random()};
+  // |polys[0]| is opened at both points.
+  std::vector<size_t> poly_indices = {0, 1, 0, 2};
+  std::vector<size_t> point_indices = {0, 0, 1, 1};
+
+  std::vector<const tachyon_bn254_univariate_dense_polynomial*> c_polys;
+  std::vector<tachyon_bn254_fr> c_points;
+  std::vector<crypto::PolynomialOpening<PCS::Domain::DensePoly>> openings;
+  for (size_t i = 0; i < poly_indices.size(); ++i) {
+    const PCS::Domain::DensePoly& poly = polys[poly_indices[i]];
+    const math::bn254::Fr& point = points[point_indices[i]];
+    c_polys.push_back(
+        reinterpret_cast<const tachyon_bn254_univariate_dense_polynomial*>(
+            &poly));
+    c_points.push_back(c::base::c_cast(point));
+    openings.emplace_back(base::Ref<const PCS::Domain::DensePoly>(&poly),
+                          point, poly.Evaluate(point));
+  }
+
+  tachyon_halo2_bn254_shplonk_prover_create_opening_proof(
+      prover_, c_polys.data(), c_points.data(), c_polys.size());
+  ASSERT_TRUE(expected_prover_impl->pcs().CreateOpeningProof(
+      openings, expected_prover_impl->GetWriter()));
+
+  size_t proof_len;
+  tachyon_halo2_bn254_shplonk_prover_get_proof(prover_, nullptr, &proof_len);
+  std::vector<uint8_t> proof(proof_len);
+  tachyon_halo2_bn254_shplonk_prover_get_proof(prover_, proof.data(),
+                                               &proof_len);
+  EXPECT_EQ(proof,
+            expected_prover_impl->GetWriter()->buffer().owned_buffer());
+
+  tachyon_halo2_bn254_shplonk_prover_destroy(expected_prover);
+}
+
+
+namespace {
+
+// Sums everything absorbed, and squeezes the sum.
+struct SumHasher {
+  math::bn254::Fr sum = math::bn254::Fr::Zero();
+  size_t num_points = 0;
+};
+
+void AbsorbScalar(void* ctx, const tachyon_bn254_fr* scalar) {
+  SumHasher* hasher = reinterpret_cast<SumHasher*>(ctx);
+  hasher->sum += c::base::native_cast(*scalar);
+}
+
+void AbsorbPoint(void* ctx, const tachyon_bn254_g1_point2* point) {
+  SumHasher* hasher = reinterpret_cast<SumHasher*>(ctx);
+  ++hasher->num_points;
+}
+
+void Squeeze(void* ctx, tachyon_bn254_fr* challenge) {
+  SumHasher* hasher = reinterpret_cast<SumHasher*>(ctx);
+  *challenge = c::base::c_cast(hasher->sum);
+}
+
+}  // namespace
+
+TEST(SHPlonkCustomTranscriptTest, SetTranscriptCallbacks) {
+  using PCS = SHPlonkProverTest::PCS;
+  using LS = SHPlonkProverTest::LS;
+
+  math::bn254::Fr s(2);
+  const tachyon_bn254_fr& c_s = c::base::c_cast(s);
+  tachyon_halo2_bn254_shplonk_prover* prover =
+      tachyon_halo2_bn254_shplonk_prover_create_from_unsafe_setup(
+          TACHYON_HALO2_CUSTOM_TRANSCRIPT, 5, &c_s);
+
+  SumHasher hasher;
+  tachyon_halo2_bn254_transcript_callbacks callbacks = {
+      &hasher, AbsorbScalar, AbsorbPoint, Squeeze};
+  tachyon_halo2_bn254_shplonk_prover_set_transcript_callbacks(prover,
+                                                              &callbacks);
+
+  crypto::TranscriptWriter<math::bn254::G1AffinePoint>* writer =
+      reinterpret_cast<Prover<PCS, LS>*>(prover)->GetWriter();
+  ASSERT_TRUE(writer->WriteToTranscript(math::bn254::Fr(3)));
+  ASSERT_TRUE(writer->WriteToProof(math::bn254::Fr(4)));
+  ASSERT_TRUE(
+      writer->WriteToTranscript(math::bn254::G1AffinePoint::Generator()));
+  EXPECT_FALSE(writer->WriteToTranscript(math::bn254::G1AffinePoint::Zero()));
+  EXPECT_EQ(writer->SqueezeChallenge(), math::bn254::Fr(7));
+  EXPECT_EQ(hasher.num_points, size_t{1});
+
+  tachyon_halo2_bn254_shplonk_prover_destroy(prover);
+}
+
 }  // namespace tachyon::zk::plonk::halo2::bn254
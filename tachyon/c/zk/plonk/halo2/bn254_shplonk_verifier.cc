@@ -1,5 +1,7 @@
 #include "tachyon/c/zk/plonk/halo2/bn254_shplonk_verifier.h"
 
+#include <string.h>
+
 #include <memory>
 #include <utility>
 #include <vector>
@@ -58,6 +60,10 @@ tachyon_halo2_bn254_shplonk_verifier_create_from_params(
                 std::move(read_buf));
             break;
           }
+          case zk::plonk::halo2::TranscriptType::kCustom:
+            // A proof with a custom transcript is verified on the side of
+            // its hasher.
+            break;
         }
         CHECK(reader);
         zk::plonk::halo2::Verifier<PCS, LS> verifier(std::move(pcs),
@@ -73,6 +79,23 @@ void tachyon_halo2_bn254_shplonk_verifier_destroy(
   delete reinterpret_cast<Verifier*>(verifier);
 }
 
+void tachyon_halo2_bn254_shplonk_verifier_set_transcript_state(
+    tachyon_halo2_bn254_shplonk_verifier* verifier, const uint8_t* state,
+    size_t state_len) {
+  reinterpret_cast<Verifier*>(verifier)->SetTranscriptState(
+      absl::Span<const uint8_t>(state, state_len));
+}
+
+void tachyon_halo2_bn254_shplonk_verifier_get_transcript_state(
+    const tachyon_halo2_bn254_shplonk_verifier* verifier, uint8_t* state,
+    size_t* state_len) {
+  std::vector<uint8_t> transcript_state =
+      reinterpret_cast<const Verifier*>(verifier)->GetTranscriptState();
+  *state_len = transcript_state.size();
+  if (state == nullptr) return;
+  memcpy(state, transcript_state.data(), transcript_state.size());
+}
+
 bool tachyon_halo2_bn254_shplonk_verifier_verify_proof(
     tachyon_halo2_bn254_shplonk_verifier* verifier,
     const tachyon_bn254_plonk_verifying_key* vkey,
@@ -49,6 +49,34 @@ tachyon_halo2_bn254_shplonk_verifier_create_from_params(
 TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_verifier_destroy(
     tachyon_halo2_bn254_shplonk_verifier* verifier);
 
+/**
+ * @brief Sets the state of the transcript the SHPLONK verifier reads the proof
+ * with, for a proof whose transcript continued from another, e.g, from the
+ * proof before it. It is in the layout
+ * tachyon_halo2_bn254_transcript_writer_get_state() writes.
+ *
+ * @param verifier Pointer to the SHPLONK verifier instance.
+ * @param state The transcript state to set.
+ * @param state_len The length of the transcript state.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_verifier_set_transcript_state(
+    tachyon_halo2_bn254_shplonk_verifier* verifier, const uint8_t* state,
+    size_t state_len);
+
+/**
+ * @brief Retrieves the state of the transcript of the SHPLONK verifier, which
+ * after tachyon_halo2_bn254_shplonk_verifier_verify_proof() is the state the
+ * prover's transcript ended in. Call it with @p state set to NULL to get the
+ * length first.
+ *
+ * @param verifier Pointer to the SHPLONK verifier instance.
+ * @param state Buffer to store the state.
+ * @param state_len Pointer to store the length of the state.
+ */
+TACHYON_C_EXPORT void tachyon_halo2_bn254_shplonk_verifier_get_transcript_state(
+    const tachyon_halo2_bn254_shplonk_verifier* verifier, uint8_t* state,
+    size_t* state_len);
+
 /**
  * @brief Verifies a SHPLONK proof using the provided verifying key and instance
  * columns vector. The instance columns vector provides the public inputs
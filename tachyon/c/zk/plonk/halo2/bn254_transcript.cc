@@ -38,6 +38,9 @@ tachyon_halo2_bn254_transcript_writer_create(uint8_t type) {
       writer->extra = new Sha256Writer(base::Uint8VectorBuffer());
       return writer;
     }
+    case zk::plonk::halo2::TranscriptType::kCustom:
+      // The writers of a custom transcript are on the side of its hasher.
+      break;
   }
   NOTREACHED();
   return nullptr;
@@ -69,6 +72,8 @@ tachyon_halo2_bn254_transcript_writer_create_from_state(uint8_t type,
       writer->extra = sha256;
       return writer;
     }
+    case zk::plonk::halo2::TranscriptType::kCustom:
+      break;
   }
   NOTREACHED();
   return nullptr;
@@ -92,6 +97,8 @@ void tachyon_halo2_bn254_transcript_writer_destroy(
       delete writer;
       return;
     }
+    case zk::plonk::halo2::TranscriptType::kCustom:
+      break;
   }
   NOTREACHED();
 }
@@ -114,6 +121,8 @@ void tachyon_halo2_bn254_transcript_writer_update(
       reinterpret_cast<Sha256Writer*>(writer->extra)->Update(data, data_len);
       return;
     }
+    case zk::plonk::halo2::TranscriptType::kCustom:
+      break;
   }
   NOTREACHED();
 }
@@ -131,6 +140,7 @@ void tachyon_halo2_bn254_transcript_writer_finalize(
       return;
     }
     case zk::plonk::halo2::TranscriptType::kPoseidon:
+    case zk::plonk::halo2::TranscriptType::kCustom:
       break;
     case zk::plonk::halo2::TranscriptType::kSha256: {
       *data_len = SHA256_DIGEST_LENGTH;
@@ -157,6 +167,7 @@ tachyon_bn254_fr tachyon_halo2_bn254_transcript_writer_squeeze(
     }
     case zk::plonk::halo2::TranscriptType::kBlake2b:
     case zk::plonk::halo2::TranscriptType::kSha256:
+    case zk::plonk::halo2::TranscriptType::kCustom:
       break;
   }
   NOTREACHED();
@@ -192,6 +203,8 @@ void tachyon_halo2_bn254_transcript_writer_get_state(
       memcpy(state, state_tmp.data(), *state_len);
       return;
     }
+    case zk::plonk::halo2::TranscriptType::kCustom:
+      break;
   }
   NOTREACHED();
 }
@@ -12,6 +12,7 @@
 
 #include "tachyon/c/export.h"
 #include "tachyon/c/math/elliptic_curves/bn/bn254/fr.h"
+#include "tachyon/c/math/elliptic_curves/bn/bn254/g1.h"
 #include "tachyon/c/zk/plonk/halo2/constants.h"
 
 struct tachyon_halo2_bn254_transcript_writer {
@@ -19,6 +20,19 @@ struct tachyon_halo2_bn254_transcript_writer {
   void* extra;
 };
 
+/**
+ * @struct tachyon_halo2_bn254_transcript_callbacks
+ * @brief The hasher of a transcript of type TACHYON_HALO2_CUSTOM_TRANSCRIPT,
+ * which lives outside of tachyon. Every callback is called with |ctx|.
+ */
+struct tachyon_halo2_bn254_transcript_callbacks {
+  void* ctx;
+  void (*absorb_scalar)(void* ctx, const tachyon_bn254_fr* scalar);
+  // Never called with the point at infinity.
+  void (*absorb_point)(void* ctx, const tachyon_bn254_g1_point2* point);
+  void (*squeeze)(void* ctx, tachyon_bn254_fr* challenge);
+};
+
 #ifdef __cplusplus
 extern "C" {
 #endif
@@ -4,5 +4,21 @@
 #define TACHYON_HALO2_BLAKE2B_TRANSCRIPT 0
 #define TACHYON_HALO2_POSEIDON_TRANSCRIPT 1
 #define TACHYON_HALO2_SHA256_TRANSCRIPT 2
+// The hasher is outside of tachyon and is driven through the callbacks set
+// with |tachyon_halo2_bn254_*_prover_set_transcript_callbacks()|.
+#define TACHYON_HALO2_CUSTOM_TRANSCRIPT 3
+
+// The stages of a proof, as bits of the mask passed to
+// |tachyon_halo2_bn254_*_prover_set_gpu_stages()|.
+#define TACHYON_HALO2_STAGE_FFT (1 << 0)
+#define TACHYON_HALO2_STAGE_MSM (1 << 1)
+#define TACHYON_HALO2_STAGE_LOOKUP_PRODUCTS (1 << 2)
+#define TACHYON_HALO2_STAGE_QUOTIENT (1 << 3)
+
+// How |tachyon_halo2_bn254_*_prover_commit_lagrange()| gets the Lagrange
+// bases, see |tachyon_halo2_bn254_*_prover_set_lagrange_basis_policy()|.
+#define TACHYON_HALO2_LAGRANGE_BASES_RESIDENT 0
+#define TACHYON_HALO2_LAGRANGE_BASES_RECOMPUTE_PER_BATCH 1
+#define TACHYON_HALO2_LAGRANGE_BASES_VIA_MONOMIAL 2
 
 #endif  // TACHYON_C_ZK_PLONK_HALO2_CONSTANTS_H_
@@ -0,0 +1,18 @@
+#ifndef TACHYON_C_ZK_PLONK_HALO2_GATE_COST_H_
+#define TACHYON_C_ZK_PLONK_HALO2_GATE_COST_H_
+
+#include <stdint.h>
+
+/**
+ * @struct tachyon_halo2_gate_cost
+ * @brief What evaluating a custom gate over the extended domain cost.
+ */
+struct tachyon_halo2_gate_cost {
+  /// The time spent in the calculations of the gate, extrapolated from the
+  /// rows that were timed.
+  uint64_t nanos;
+  /// The calculations of the gate times the rows they were evaluated at.
+  uint64_t num_ops;
+};
+
+#endif  // TACHYON_C_ZK_PLONK_HALO2_GATE_COST_H_
@@ -2,6 +2,8 @@
 #define TACHYON_C_ZK_PLONK_HALO2_KZG_FAMILY_PROVER_IMPL_H_
 
 #include <algorithm>
+#include <memory>
+#include <mutex>
 #include <utility>
 #include <vector>
 
@@ -9,6 +11,7 @@
 
 #include "tachyon/base/logging.h"
 #include "tachyon/c/math/elliptic_curves/point_traits_forward.h"
+#include "tachyon/c/zk/plonk/halo2/constants.h"
 #include "tachyon/c/zk/plonk/halo2/prover_impl_base.h"
 #include "tachyon/math/elliptic_curves/msm/variable_base_msm.h"
 
@@ -25,9 +28,26 @@ class KZGFamilyProverImpl : public ProverImplBase<PCS, LS> {
   using CAffinePoint = typename math::PointTraits<AffinePoint>::CCurvePoint;
   using CJacobianPoint = typename math::PointTraits<JacobianPoint>::CCurvePoint;
   using CScalarField = typename math::PointTraits<AffinePoint>::CScalarField;
+  using MSM = tachyon::math::VariableBaseMSM<AffinePoint>;
+  using MSMScratch = typename MSM::Scratch;
+
+  struct ScratchStats {
+    // The bytes held by the scratch buffers between MSMs.
+    size_t bytes = 0;
+    // The MSMs that had to allocate scratch buffers.
+    size_t num_allocations = 0;
+    // The MSMs that ran in scratch buffers kept from earlier ones.
+    size_t num_reuses = 0;
+  };
 
   using ProverImplBase<PCS, LS>::ProverImplBase;
 
+  // Every |Commit()| and |CommitLagrange()| runs its MSM in scratch buffers
+  // kept by the prover. They are created by the first MSM and grow to the
+  // largest one, at most 2ᵏ scalars, so the dozens of MSMs of a proof don't
+  // allocate and free the same buffers again and again. |CommitLagrange()| may
+  // be called from several threads at once, each of which takes a scratch of
+  // its own.
   CJacobianPoint* Commit(const std::vector<ScalarField>& scalars) const {
     return DoMSM(this->pcs_.GetG1PowersOfTau(), scalars);
   }
@@ -37,18 +57,92 @@ class KZGFamilyProverImpl : public ProverImplBase<PCS, LS> {
     return DoMSM(this->pcs_.GetG1PowersOfTauLagrange(), scalars);
   }
 
+  uint8_t lagrange_basis_policy() const { return lagrange_basis_policy_; }
+
+  // Returns whether |CommitLagrange()| follows |policy|, one of
+  // TACHYON_HALO2_LAGRANGE_BASES_*, in which case it is kept. The proving
+  // pipeline commits with the resident Lagrange bases itself, so they can't
+  // be released and that is the only policy followed here. The caller
+  // commits another way otherwise.
+  bool SetLagrangeBasisPolicy(uint8_t policy) {
+    if (policy != TACHYON_HALO2_LAGRANGE_BASES_RESIDENT) return false;
+    lagrange_basis_policy_ = policy;
+    return true;
+  }
+
+  // Returns the bytes reserved for the powers of tau, in both the monomial and
+  // the Lagrange basis.
+  size_t GetSRSBytes() const {
+    return (this->pcs_.GetG1PowersOfTau().capacity() +
+            this->pcs_.GetG1PowersOfTauLagrange().capacity()) *
+           sizeof(AffinePoint);
+  }
+
+  ScratchStats GetScratchStats() const {
+    std::lock_guard<std::mutex> lock(scratch_mutex_);
+    ScratchStats ret = scratch_stats_;
+    for (const std::unique_ptr<MSMScratch>& scratch : idle_scratches_) {
+      ret.bytes += GetBytes(*scratch);
+    }
+    return ret;
+  }
+
+  // Frees the scratch buffers. The next MSM creates them again. Buffers of
+  // MSMs running meanwhile are kept.
+  void ReleaseScratch() {
+    std::lock_guard<std::mutex> lock(scratch_mutex_);
+    idle_scratches_.clear();
+  }
+
  private:
-  static CJacobianPoint* DoMSM(const std::vector<AffinePoint>& bases,
-                               const std::vector<ScalarField>& scalars) {
-    using MSM = tachyon::math::VariableBaseMSM<AffinePoint>;
+  static size_t GetBytes(const MSMScratch& scratch) {
+    size_t ret = 0;
+    for (const auto& chunk : scratch) {
+      ret += chunk.GetBytes();
+    }
+    return ret;
+  }
+
+  std::unique_ptr<MSMScratch> TakeScratch() const {
+    std::lock_guard<std::mutex> lock(scratch_mutex_);
+    if (idle_scratches_.empty()) return std::make_unique<MSMScratch>();
+    std::unique_ptr<MSMScratch> ret = std::move(idle_scratches_.back());
+    idle_scratches_.pop_back();
+    return ret;
+  }
+
+  void ReturnScratch(std::unique_ptr<MSMScratch> scratch, bool reused) const {
+    std::lock_guard<std::mutex> lock(scratch_mutex_);
+    if (reused) {
+      ++scratch_stats_.num_reuses;
+    } else {
+      ++scratch_stats_.num_allocations;
+    }
+    idle_scratches_.push_back(std::move(scratch));
+  }
+
+  CJacobianPoint* DoMSM(const std::vector<AffinePoint>& bases,
+                        const std::vector<ScalarField>& scalars) const {
+    std::unique_ptr<MSMScratch> scratch = TakeScratch();
+    // The buffers only ever grow, so the MSM allocated iff they did.
+    size_t bytes = GetBytes(*scratch);
     MSM msm;
+    msm.SetScratch(scratch.get());
     typename MSM::Bucket bucket;
     absl::Span<const AffinePoint> bases_span(
         bases.data(), std::min(bases.size(), scalars.size()));
     CHECK(msm.Run(bases_span, scalars, &bucket));
+    bool reused = bytes != 0 && GetBytes(*scratch) == bytes;
+    ReturnScratch(std::move(scratch), reused);
     JacobianPoint* ret = new JacobianPoint(bucket.ToJacobian());
     return reinterpret_cast<CJacobianPoint*>(ret);
   }
+
+  uint8_t lagrange_basis_policy_ = TACHYON_HALO2_LAGRANGE_BASES_RESIDENT;
+  mutable std::mutex scratch_mutex_;
+  // The scratches no MSM runs in at the moment.
+  mutable std::vector<std::unique_ptr<MSMScratch>> idle_scratches_;
+  mutable ScratchStats scratch_stats_;
 };
 
 }  // namespace tachyon::c::zk::plonk::halo2
@@ -5,12 +5,18 @@
 
 #include <memory>
 #include <utility>
+#include <vector>
 
 #include "tachyon/base/environment.h"
 #include "tachyon/base/files/file_util.h"
 #include "tachyon/base/functional/callback.h"
 #include "tachyon/base/logging.h"
+#include "tachyon/base/openmp_util.h"
+#include "tachyon/zk/plonk/halo2/blake2b_transcript.h"
+#include "tachyon/zk/plonk/halo2/poseidon_transcript.h"
 #include "tachyon/zk/plonk/halo2/prover.h"
+#include "tachyon/zk/plonk/halo2/sha256_transcript.h"
+#include "tachyon/zk/plonk/halo2/transcript_type.h"
 
 namespace tachyon::c::zk::plonk::halo2 {
 
@@ -69,6 +75,32 @@ class ProverImplBase : public tachyon::zk::plonk::halo2::Prover<PCS, LS> {
     this->transcript_ = std::move(writer);
   }
 
+  // Returns the state of the transcript, which is the state given to
+  // |SetTranscript()| followed by everything |CreateProof()| wrote.
+  std::vector<uint8_t> GetTranscriptState() const {
+    const crypto::TranscriptWriter<Commitment>* writer = this->GetWriter();
+    switch (static_cast<tachyon::zk::plonk::halo2::TranscriptType>(
+        transcript_type_)) {
+      case tachyon::zk::plonk::halo2::TranscriptType::kBlake2b:
+        return static_cast<const tachyon::zk::plonk::halo2::Blake2bWriter<
+            Commitment>*>(writer)
+            ->GetState();
+      case tachyon::zk::plonk::halo2::TranscriptType::kPoseidon:
+        return static_cast<const tachyon::zk::plonk::halo2::PoseidonWriter<
+            Commitment>*>(writer)
+            ->GetState();
+      case tachyon::zk::plonk::halo2::TranscriptType::kSha256:
+        return static_cast<const tachyon::zk::plonk::halo2::Sha256Writer<
+            Commitment>*>(writer)
+            ->GetState();
+      case tachyon::zk::plonk::halo2::TranscriptType::kCustom:
+        // The state is kept by the hasher behind the callbacks.
+        return {};
+    }
+    NOTREACHED();
+    return {};
+  }
+
   void CreateProof(
       tachyon::zk::plonk::ProvingKey<LS>& proving_key,
       tachyon::zk::plonk::halo2::ArgumentData<Poly, Evals>* argument_data) {
@@ -83,6 +115,7 @@ class ProverImplBase : public tachyon::zk::plonk::halo2::Prover<PCS, LS> {
                                           buffer.owned_buffer()));
     }
 
+    tachyon::base::ScopedNumThreads scoped_num_threads(this->num_threads());
     Base::CreateProof(proving_key, argument_data);
   }
 
@@ -6,8 +6,15 @@
 #include <utility>
 #include <vector>
 
+#include "absl/types/span.h"
+
 #include "tachyon/base/containers/container_util.h"
 #include "tachyon/base/functional/callback.h"
+#include "tachyon/base/logging.h"
+#include "tachyon/zk/plonk/halo2/blake2b_transcript.h"
+#include "tachyon/zk/plonk/halo2/poseidon_transcript.h"
+#include "tachyon/zk/plonk/halo2/sha256_transcript.h"
+#include "tachyon/zk/plonk/halo2/transcript_type.h"
 #include "tachyon/zk/plonk/halo2/verifier.h"
 
 namespace tachyon::c::zk::plonk::halo2 {
@@ -26,6 +33,54 @@ class VerifierImpl : public tachyon::zk::plonk::halo2::Verifier<PCS, LS> {
 
   uint8_t transcript_type() const { return transcript_type_; }
 
+  void SetTranscriptState(absl::Span<const uint8_t> state) {
+    crypto::TranscriptReader<Commitment>* reader = this->GetReader();
+    switch (static_cast<tachyon::zk::plonk::halo2::TranscriptType>(
+        transcript_type_)) {
+      case tachyon::zk::plonk::halo2::TranscriptType::kBlake2b:
+        static_cast<tachyon::zk::plonk::halo2::Blake2bReader<Commitment>*>(
+            reader)
+            ->SetState(state);
+        return;
+      case tachyon::zk::plonk::halo2::TranscriptType::kPoseidon:
+        static_cast<tachyon::zk::plonk::halo2::PoseidonReader<Commitment>*>(
+            reader)
+            ->SetState(state);
+        return;
+      case tachyon::zk::plonk::halo2::TranscriptType::kSha256:
+        static_cast<tachyon::zk::plonk::halo2::Sha256Reader<Commitment>*>(
+            reader)
+            ->SetState(state);
+        return;
+      case tachyon::zk::plonk::halo2::TranscriptType::kCustom:
+        break;
+    }
+    NOTREACHED();
+  }
+
+  std::vector<uint8_t> GetTranscriptState() const {
+    const crypto::TranscriptReader<Commitment>* reader = this->GetReader();
+    switch (static_cast<tachyon::zk::plonk::halo2::TranscriptType>(
+        transcript_type_)) {
+      case tachyon::zk::plonk::halo2::TranscriptType::kBlake2b:
+        return static_cast<const tachyon::zk::plonk::halo2::Blake2bReader<
+            Commitment>*>(reader)
+            ->GetState();
+      case tachyon::zk::plonk::halo2::TranscriptType::kPoseidon:
+        return static_cast<const tachyon::zk::plonk::halo2::PoseidonReader<
+            Commitment>*>(reader)
+            ->GetState();
+      case tachyon::zk::plonk::halo2::TranscriptType::kSha256:
+        return static_cast<const tachyon::zk::plonk::halo2::Sha256Reader<
+            Commitment>*>(reader)
+            ->GetState();
+      case tachyon::zk::plonk::halo2::TranscriptType::kCustom:
+        break;
+    }
+    NOTREACHED();
+    return {};
+  }
+
   using Base::VerifyProof;
 
   [[nodiscard]] bool VerifyProof(
@@ -12,6 +12,8 @@
 #include <utility>
 #include <vector>
 
+#include "absl/types/span.h"
+
 #include "tachyon/base/openmp_util.h"
 #include "tachyon/math/base/big_int.h"
 #include "tachyon/math/elliptic_curves/msm/algorithms/pippenger/pippenger_base.h"
@@ -25,12 +27,12 @@ namespace tachyon::math {
 // https://github.com/arkworks-rs/gemini/blob/main/src/kzg/msm/variable_base.rs#L20
 template <size_t N>
 void FillDigits(const BigInt<N>& scalar, size_t window_bits,
-                std::vector<int64_t>* digits) {
+                absl::Span<int64_t> digits) {
   uint64_t radix = 1 << window_bits;
 
   uint64_t carry = 0;
   size_t bit_offset = 0;
-  for (size_t i = 0; i < digits->size(); ++i) {
+  for (size_t i = 0; i < digits.size(); ++i) {
     // Construct a buffer of bits of the |scalar|, starting at
     // `bit_offset`.
     uint64_t bits = scalar.ExtractBits64(bit_offset, window_bits);
@@ -41,14 +43,29 @@ void FillDigits(const BigInt<N>& scalar, size_t window_bits,
     // Recenter coefficients from [0,2^|window_bits|) to
     // [-2^|window_bits|/2, 2^|window_bits|/2)
     carry = (coeff + radix / 2) >> window_bits;
-    (*digits)[i] = static_cast<int64_t>(coeff) -
-                   static_cast<int64_t>(carry << window_bits);
+    digits[i] = static_cast<int64_t>(coeff) -
+                static_cast<int64_t>(carry << window_bits);
     bit_offset += window_bits;
   }
 
-  digits->back() += static_cast<int64_t>(carry << window_bits);
+  digits.back() += static_cast<int64_t>(carry << window_bits);
 }
 
+// The buffers of |Pippenger::Run()| that grow with the number of scalars. A
+// caller running many MSMs can keep them between runs, see
+// |Pippenger::SetScratch()|. They only ever grow.
+template <size_t N>
+struct PippengerScratch {
+  std::vector<BigInt<N>> scalars;
+  // The signed digits of every scalar, |window_count| per scalar.
+  std::vector<int64_t> digits;
+
+  size_t GetBytes() const {
+    return scalars.capacity() * sizeof(BigInt<N>) +
+           digits.capacity() * sizeof(int64_t);
+  }
+};
+
 template <typename Point>
 class Pippenger : public PippengerBase<Point> {
  public:
@@ -71,6 +88,10 @@ class Pippenger : public PippengerBase<Point> {
     use_msm_window_naf_ = use_msm_window_naf;
   }
 
+  // Runs in the buffers of |scratch|, which must outlive the runs, instead of
+  // allocating them on every run.
+  void SetScratch(PippengerScratch<N>* scratch) { scratch_ = scratch; }
+
   template <typename BaseInputIterator, typename ScalarInputIterator,
             std::enable_if_t<IsAbleToMSM<BaseInputIterator, ScalarInputIterator,
                                          Point, ScalarField>>* = nullptr>
@@ -86,8 +107,13 @@ class Pippenger : public PippengerBase<Point> {
     }
     ctx_ = MSMCtx::CreateDefault<ScalarField>(scalars_size);
 
-    std::vector<BigInt<N>> scalars;
-    scalars.resize(scalars_size);
+    PippengerScratch<N> local_scratch;
+    PippengerScratch<N>* scratch = scratch_ ? scratch_ : &local_scratch;
+    if (scratch->scalars.size() < scalars_size) {
+      scratch->scalars.resize(scalars_size);
+    }
+    absl::Span<BigInt<N>> scalars =
+        absl::MakeSpan(scratch->scalars).subspan(0, scalars_size);
     auto scalars_it = scalars_first;
     for (size_t i = 0; i < scalars_size; ++i, ++scalars_it) {
       scalars[i] = scalars_it->ToBigInt();
@@ -96,7 +122,8 @@ class Pippenger : public PippengerBase<Point> {
     std::vector<Bucket> window_sums(ctx_.window_count);
 
     if (use_msm_window_naf_) {
-      AccumulateWindowNAFSums(std::move(bases_first), scalars, &window_sums);
+      AccumulateWindowNAFSums(std::move(bases_first), scalars,
+                              &scratch->digits, &window_sums);
     } else {
       AccumulateWindowSums(std::move(bases_first), scalars, &window_sums);
     }
@@ -108,10 +135,10 @@ class Pippenger : public PippengerBase<Point> {
 
  private:
   template <typename BaseInputIterator>
-  void AccumulateSingleWindowNAFSum(
-      BaseInputIterator bases_it,
-      const std::vector<std::vector<int64_t>>& scalar_digits, size_t i,
-      Bucket* window_sum, bool is_last_window) {
+  void AccumulateSingleWindowNAFSum(BaseInputIterator bases_it,
+                                    absl::Span<const int64_t> scalar_digits,
+                                    size_t i, Bucket* window_sum,
+                                    bool is_last_window) {
     size_t bucket_size;
     if (is_last_window) {
       bucket_size = 1 << ctx_.window_bits;
@@ -119,9 +146,10 @@ class Pippenger : public PippengerBase<Point> {
       bucket_size = 1 << (ctx_.window_bits - 1);
     }
     std::vector<Bucket> buckets(bucket_size);
-    for (size_t j = 0; j < scalar_digits.size(); ++j, ++bases_it) {
+    size_t num_scalars = scalar_digits.size() / ctx_.window_count;
+    for (size_t j = 0; j < num_scalars; ++j, ++bases_it) {
       const Point& base = *bases_it;
-      int64_t scalar = scalar_digits[j][i];
+      int64_t scalar = scalar_digits[j * ctx_.window_count + i];
       if (0 < scalar) {
         buckets[static_cast<uint64_t>(scalar - 1)] += base;
       } else if (0 > scalar) {
@@ -134,14 +162,18 @@ class Pippenger : public PippengerBase<Point> {
   template <typename BaseInputIterator>
   void AccumulateWindowNAFSums(BaseInputIterator bases_first,
                                absl::Span<const BigInt<N>> scalars,
+                               std::vector<int64_t>* digits,
                                std::vector<Bucket>* window_sums) {
-    std::vector<std::vector<int64_t>> scalar_digits;
-    scalar_digits.resize(scalars.size());
-    for (std::vector<int64_t>& scalar_digit : scalar_digits) {
-      scalar_digit.resize(ctx_.window_count);
+    size_t num_digits = scalars.size() * ctx_.window_count;
+    if (digits->size() < num_digits) {
+      digits->resize(num_digits);
     }
+    absl::Span<int64_t> scalar_digits =
+        absl::MakeSpan(*digits).subspan(0, num_digits);
     for (size_t i = 0; i < scalars.size(); ++i) {
-      FillDigits(scalars[i], ctx_.window_bits, &scalar_digits[i]);
+      FillDigits(scalars[i], ctx_.window_bits,
+                 scalar_digits.subspan(i * ctx_.window_count,
+                                       ctx_.window_count));
     }
     if (parallel_windows_) {
       OPENMP_PARALLEL_FOR(size_t i = 0; i < ctx_.window_count; ++i) {
@@ -217,6 +249,8 @@ class Pippenger : public PippengerBase<Point> {
 
   bool use_msm_window_naf_ = false;
   bool parallel_windows_ = false;
+  // not owned
+  PippengerScratch<N>* scratch_ = nullptr;
   MSMCtx ctx_;
 };
 
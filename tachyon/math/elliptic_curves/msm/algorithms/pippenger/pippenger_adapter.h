@@ -5,6 +5,7 @@
 #include <utility>
 #include <vector>
 
+#include "tachyon/base/openmp_util.h"
 #include "tachyon/math/elliptic_curves/msm/algorithms/pippenger/pippenger.h"
 
 namespace tachyon::math {
@@ -21,6 +22,12 @@ class PippengerAdapter {
  public:
   using ScalarField = typename Point::ScalarField;
   using Bucket = typename Pippenger<Point>::Bucket;
+  // The buffers of one |Pippenger| per chunk of the scalars.
+  using Scratch = std::vector<PippengerScratch<Pippenger<Point>::N>>;
+
+  // Runs in the buffers of |scratch|, which must outlive the runs, instead of
+  // allocating them on every run. Runs sharing one must not overlap.
+  void SetScratch(Scratch* scratch) { scratch_ = scratch; }
 
   template <typename BaseInputIterator, typename ScalarInputIterator>
   [[nodiscard]] bool Run(BaseInputIterator bases_first,
@@ -44,6 +51,10 @@ class PippengerAdapter {
       Pippenger<Point> pippenger;
       pippenger.SetParallelWindows(strategy ==
                                    PippengerParallelStrategy::kParallelWindow);
+      if (scratch_) {
+        if (scratch_->empty()) scratch_->resize(1);
+        pippenger.SetScratch(&(*scratch_)[0]);
+      }
       return pippenger.Run(std::move(bases_first), std::move(bases_last),
                            std::move(scalars_first), std::move(scalars_last),
                            ret);
@@ -75,19 +86,23 @@ class PippengerAdapter {
         bool valid;
       };
 
-#if defined(TACHYON_HAS_OPENMP)
-      omp_set_num_threads(thread_nums);
-#endif
+      // The thread count is restored after the MSM, since OpenMP would keep
+      // it for every later parallel region of the calling thread.
+      base::ScopedNumThreads scoped_num_threads(thread_nums);
       size_t chunk_size = (scalars_size + thread_nums - 1) / thread_nums;
       size_t num_chunks = (scalars_size + chunk_size - 1) / chunk_size;
       std::vector<Result> results;
       results.resize(num_chunks);
+      if (scratch_ && scratch_->size() < num_chunks) {
+        scratch_->resize(num_chunks);
+      }
       OPENMP_PARALLEL_FOR(size_t i = 0; i < num_chunks; ++i) {
         size_t start = i * chunk_size;
         size_t len = i == num_chunks - 1 ? scalars_size - start : chunk_size;
         Pippenger<Point> pippenger;
         pippenger.SetParallelWindows(
             strategy == PippengerParallelStrategy::kParallelWindowAndTerm);
+        if (scratch_) pippenger.SetScratch(&(*scratch_)[i]);
         auto bases_start = bases_first + start;
         auto bases_end = bases_start + len;
         auto scalars_start = scalars_first + start;
@@ -108,6 +123,10 @@ class PippengerAdapter {
       return true;
     }
   }
+
+ private:
+  // not owned
+  Scratch* scratch_ = nullptr;
 };
 
 }  // namespace tachyon::math
@@ -47,4 +47,32 @@ TEST_F(PippengerAdapterTest, RunWithStrategy) {
   }
 }
 
+TEST_F(PippengerAdapterTest, RunWithScratch) {
+  using Scratch = PippengerAdapter<bn254::G1AffinePoint>::Scratch;
+
+  VariableBaseMSMTestSet<bn254::G1AffinePoint> small_test_set =
+      VariableBaseMSMTestSet<bn254::G1AffinePoint>::Random(
+          kSize / 4, VariableBaseMSMMethod::kMSM);
+  for (PippengerParallelStrategy strategy :
+       {PippengerParallelStrategy::kNone,
+        PippengerParallelStrategy::kParallelTerm}) {
+    SCOPED_TRACE(absl::Substitute("strategy: $0", static_cast<int>(strategy)));
+    Scratch scratch;
+    // The second run fits in the buffers of the first, which must not leak
+    // into its result.
+    for (const VariableBaseMSMTestSet<bn254::G1AffinePoint>* test_set :
+         {&this->test_set_, &small_test_set}) {
+      PippengerAdapter<bn254::G1AffinePoint> pippenger;
+      pippenger.SetScratch(&scratch);
+      bn254::G1PointXYZZ ret;
+      EXPECT_TRUE(pippenger.RunWithStrategy(
+          test_set->bases.begin(), test_set->bases.end(),
+          test_set->scalars.begin(), test_set->scalars.end(), strategy, &ret));
+      EXPECT_EQ(ret, test_set->answer);
+    }
+    EXPECT_FALSE(scratch.empty());
+    EXPECT_GE(scratch[0].scalars.size(), kSize / scratch.size());
+  }
+}
+
 }  // namespace tachyon::math
@@ -17,6 +17,11 @@ class VariableBaseMSM {
  public:
   using ScalarField = typename Point::ScalarField;
   using Bucket = typename Pippenger<Point>::Bucket;
+  using Scratch = typename PippengerAdapter<Point>::Scratch;
+
+  // Keeps the buffers of the runs in |scratch|, see
+  // |PippengerAdapter::SetScratch()|.
+  void SetScratch(Scratch* scratch) { scratch_ = scratch; }
 
   template <typename BaseInputIterator, typename ScalarInputIterator>
   [[nodiscard]] bool Run(BaseInputIterator bases_first,
@@ -24,6 +29,7 @@ class VariableBaseMSM {
                          ScalarInputIterator scalars_first,
                          ScalarInputIterator scalars_last, Bucket* ret) {
     PippengerAdapter<Point> pippenger;
+    pippenger.SetScratch(scratch_);
     return pippenger.Run(std::move(bases_first), std::move(bases_last),
                          std::move(scalars_first), std::move(scalars_last),
                          ret);
@@ -35,6 +41,10 @@ class VariableBaseMSM {
     return Run(std::begin(bases), std::end(bases), std::begin(scalars),
                std::end(scalars), ret);
   }
+
+ private:
+  // not owned
+  Scratch* scratch_ = nullptr;
 };
 
 }  // namespace tachyon::math
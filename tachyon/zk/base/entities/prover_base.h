@@ -8,6 +8,7 @@
 #define TACHYON_ZK_BASE_ENTITIES_PROVER_BASE_H_
 
 #include <stddef.h>
+#include <stdint.h>
 
 #include <memory>
 #include <utility>
@@ -36,6 +37,34 @@ class ProverBase : public Entity<PCS> {
 
   Blinder<F>& blinder() { return blinder_; }
 
+  // The number of independent lookup and permutation polynomials computed at
+  // once. The blinding factors are still drawn in the same order, so the
+  // proof doesn't depend on it.
+  size_t intra_proof_parallelism() const { return intra_proof_parallelism_; }
+  void set_intra_proof_parallelism(size_t intra_proof_parallelism) {
+    intra_proof_parallelism_ = intra_proof_parallelism;
+  }
+
+  // A mask of the stages to run on the GPU, whose bits are set by the caller.
+  // A stage without a GPU path runs on the CPU, and either computes the same
+  // values, so the proof doesn't depend on it.
+  uint8_t gpu_stages() const { return gpu_stages_; }
+  void set_gpu_stages(uint8_t gpu_stages) { gpu_stages_ = gpu_stages; }
+
+  // The log₂ of the size of the IFFTs the IFFT of the extended domain is split
+  // into, to bound the memory it works in. 0 runs it in one piece. The
+  // coefficients, and so the proof, don't depend on it.
+  uint32_t fft_chunk_log2() const { return fft_chunk_log2_; }
+  void set_fft_chunk_log2(uint32_t fft_chunk_log2) {
+    fft_chunk_log2_ = fft_chunk_log2;
+  }
+
+  // The number of threads the parallel loops of a proof run on. 0 leaves it
+  // to OpenMP. Every value is computed exactly and the blinding factors are
+  // drawn in the same order, so the proof doesn't depend on it.
+  size_t num_threads() const { return num_threads_; }
+  void set_num_threads(size_t num_threads) { num_threads_ = num_threads; }
+
   crypto::TranscriptWriter<Commitment>* GetWriter() {
     return this->transcript()->ToWriter();
   }
@@ -110,6 +139,10 @@ class ProverBase : public Entity<PCS> {
 
  protected:
   Blinder<F> blinder_;
+  size_t intra_proof_parallelism_ = 1;
+  uint8_t gpu_stages_ = 0;
+  uint32_t fft_chunk_log2_ = 0;
+  size_t num_threads_ = 0;
 };
 
 }  // namespace tachyon::zk
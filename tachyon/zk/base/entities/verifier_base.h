@@ -26,6 +26,9 @@ class VerifierBase : public Entity<PCS> {
   crypto::TranscriptReader<Commitment>* GetReader() {
     return this->transcript()->ToReader();
   }
+  const crypto::TranscriptReader<Commitment>* GetReader() const {
+    return this->transcript()->ToReader();
+  }
 };
 
 }  // namespace tachyon::zk
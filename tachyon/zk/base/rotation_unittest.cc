@@ -18,6 +18,16 @@ TEST(RotationTest, GetRotationIdx) {
       {4, Rotation(2), 5, 3, 2},
       // (6 + 3 * 4) % 6 = 0
       {6, Rotation(3), 4, 6, 0},
+      // (3 - 5 * 1) % 16 = 14
+      {3, Rotation(-5), 1, 16, 14},
+      // (5 + 32 * 1) % 64 = 37
+      {5, Rotation(32), 1, 64, 37},
+      // (5 + 32 * 1) % 16 = 5
+      {5, Rotation(32), 1, 16, 5},
+      // (2 - 5 * 4) % 16 = 14
+      {2, Rotation(-5), 4, 16, 14},
+      // (1 - 32 * 2) % 16 = 1
+      {1, Rotation(-32), 2, 16, 1},
   };
   for (const auto& test : tests) {
     EXPECT_EQ(test.expected,
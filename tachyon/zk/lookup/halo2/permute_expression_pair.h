@@ -24,11 +24,12 @@ namespace tachyon::zk::lookup::halo2 {
 // - like values in A' are vertically adjacent to each other; and
 // - the first row in a sequence of like values in A' is the row
 //   that has the corresponding value in S'.
-// This method returns (A', S') if no errors are encountered.
+// This method returns (A', S') if no errors are encountered. They aren't
+// blinded, see |PermuteExpressionPair()|. It doesn't touch the blinder, so it
+// can run for independent lookups at once.
 template <typename PCS, typename Evals, typename F = typename Evals::Field>
-[[nodiscard]] bool PermuteExpressionPair(ProverBase<PCS>* prover,
-                                         const Pair<Evals>& in,
-                                         Pair<Evals>* out) {
+[[nodiscard]] bool PermuteExpressionPairWithoutBlinding(
+    const ProverBase<PCS>* prover, const Pair<Evals>& in, Pair<Evals>* out) {
   size_t domain_size = prover->domain()->size();
   RowIndex usable_rows = prover->GetUsableRows();
 
@@ -131,13 +132,26 @@ template <typename PCS, typename Evals, typename F = typename Evals::Field>
 
   CHECK(repeated_input_rows.empty());
 
-  Evals input(std::move(permuted_input_expressions));
-  Evals table(std::move(permuted_table_expressions));
+  *out = {Evals(std::move(permuted_input_expressions)),
+          Evals(std::move(permuted_table_expressions))};
+  return true;
+}
 
-  prover->blinder().Blind(input, /*include_last_row=*/true);
-  prover->blinder().Blind(table, /*include_last_row=*/true);
+// Blinds the rows of (A', S') that |PermuteExpressionPairWithoutBlinding()|
+// leaves for it.
+template <typename PCS, typename Evals>
+void BlindPermutedExpressionPair(ProverBase<PCS>* prover, Pair<Evals>* pair) {
+  prover->blinder().Blind(pair->input(), /*include_last_row=*/true);
+  prover->blinder().Blind(pair->table(), /*include_last_row=*/true);
+}
 
-  *out = {std::move(input), std::move(table)};
+// Same as |PermuteExpressionPairWithoutBlinding()|, but blinds the result.
+template <typename PCS, typename Evals>
+[[nodiscard]] bool PermuteExpressionPair(ProverBase<PCS>* prover,
+                                         const Pair<Evals>& in,
+                                         Pair<Evals>* out) {
+  if (!PermuteExpressionPairWithoutBlinding(prover, in, out)) return false;
+  BlindPermutedExpressionPair(prover, out);
   return true;
 }
 
@@ -49,11 +49,7 @@ class Prover {
 
   template <typename PCS>
   static void BatchPermutePairs(std::vector<Prover>& lookup_provers,
-                                ProverBase<PCS>* prover) {
-    for (Prover& lookup_prover : lookup_provers) {
-      lookup_prover.PermutePairs(prover);
-    }
-  }
+                                ProverBase<PCS>* prover);
 
   constexpr static size_t GetNumPermutedPairsCommitments(
       const std::vector<Prover>& lookup_provers) {
@@ -69,11 +65,7 @@ class Prover {
   template <typename PCS>
   static void BatchCreateGrandProductPolys(std::vector<Prover>& lookup_provers,
                                            ProverBase<PCS>* prover,
-                                           const F& beta, const F& gamma) {
-    for (Prover& lookup_prover : lookup_provers) {
-      lookup_prover.CreateGrandProductPolys(prover, beta, gamma);
-    }
-  }
+                                           const F& beta, const F& gamma);
 
   constexpr static size_t GetNumGrandProductPolysCommitments(
       const std::vector<Prover>& lookup_provers) {
@@ -118,12 +110,22 @@ class Prover {
   static Pair<BlindedPolynomial<Poly, Evals>> PermutePair(
       ProverBase<PCS>* prover, const Pair<Evals>& compressed_pair);
 
+  // Blinds a pair from |PermuteExpressionPairWithoutBlinding()| and draws the
+  // blinding factors of its polynomials.
+  template <typename PCS>
+  static Pair<BlindedPolynomial<Poly, Evals>> BlindPermutedPair(
+      ProverBase<PCS>* prover, Pair<Evals>&& permuted_pair);
+
   template <typename PCS>
   static BlindedPolynomial<Poly, Evals> CreateGrandProductPoly(
       ProverBase<PCS>* prover, const Pair<Evals>& compressed_pair,
       const Pair<BlindedPolynomial<Poly, Evals>>& permuted_pair, const F& beta,
       const F& gamma);
 
+  template <typename PCS>
+  static BlindedPolynomial<Poly, Evals> CreateGrandProductPolyFromRatios(
+      ProverBase<PCS>* prover, std::vector<F>&& ratios);
+
   template <typename Domain>
   void CompressPairs(const Domain* domain,
                      const std::vector<Argument<F>>& arguments, const F& theta,
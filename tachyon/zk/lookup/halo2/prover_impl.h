@@ -11,6 +11,7 @@
 #include <vector>
 
 #include "tachyon/base/containers/container_util.h"
+#include "tachyon/base/openmp_util.h"
 #include "tachyon/base/ref.h"
 #include "tachyon/zk/lookup/halo2/compress_expression.h"
 #include "tachyon/zk/lookup/halo2/permute_expression_pair.h"
@@ -71,14 +72,59 @@ Pair<BlindedPolynomial<Poly, Evals>> Prover<Poly, Evals>::PermutePair(
     ProverBase<PCS>* prover, const Pair<Evals>& compressed_pair) {
   // A'ᵢ(X), S'ᵢ(X)
   Pair<Evals> permuted_pair;
-  CHECK(PermuteExpressionPair(prover, compressed_pair, &permuted_pair));
+  CHECK(PermuteExpressionPairWithoutBlinding(prover, compressed_pair,
+                                             &permuted_pair));
+  return BlindPermutedPair(prover, std::move(permuted_pair));
+}
 
+// static
+template <typename Poly, typename Evals>
+template <typename PCS>
+Pair<BlindedPolynomial<Poly, Evals>> Prover<Poly, Evals>::BlindPermutedPair(
+    ProverBase<PCS>* prover, Pair<Evals>&& permuted_pair) {
+  BlindPermutedExpressionPair(prover, &permuted_pair);
   F input_blind = prover->blinder().Generate();
   F table_blind = prover->blinder().Generate();
   return {{std::move(permuted_pair).TakeInput(), std::move(input_blind)},
           {std::move(permuted_pair).TakeTable(), std::move(table_blind)}};
 }
 
+// static
+template <typename Poly, typename Evals>
+template <typename PCS>
+void Prover<Poly, Evals>::BatchPermutePairs(std::vector<Prover>& lookup_provers,
+                                            ProverBase<PCS>* prover) {
+  size_t parallelism = prover->intra_proof_parallelism();
+  if (parallelism <= 1 || lookup_provers.empty()) {
+    for (Prover& lookup_prover : lookup_provers) {
+      lookup_prover.PermutePairs(prover);
+    }
+    return;
+  }
+
+  // Sorting is independent per lookup, but blinding draws from the prover's
+  // RNG, so it runs in the same order as above.
+  size_t num_lookups = lookup_provers[0].compressed_pairs_.size();
+  std::vector<Pair<Evals>> permuted_pairs(lookup_provers.size() * num_lookups);
+  OPENMP_PARALLEL_FOR_NUM_THREADS(parallelism,
+                                  size_t i = 0; i < permuted_pairs.size();
+                                  ++i) {
+    const Prover& lookup_prover = lookup_provers[i / num_lookups];
+    CHECK(PermuteExpressionPairWithoutBlinding(
+        prover, lookup_prover.compressed_pairs_[i % num_lookups],
+        &permuted_pairs[i]));
+  }
+  for (size_t i = 0; i < lookup_provers.size(); ++i) {
+    Prover& lookup_prover = lookup_provers[i];
+    lookup_prover.permuted_pairs_.clear();
+    lookup_prover.permuted_pairs_.reserve(num_lookups);
+    for (size_t j = 0; j < num_lookups; ++j) {
+      lookup_prover.permuted_pairs_.push_back(BlindPermutedPair(
+          prover, std::move(permuted_pairs[i * num_lookups + j])));
+    }
+  }
+}
+
 template <typename Poly, typename Evals>
 template <typename PCS>
 void Prover<Poly, Evals>::PermutePairs(ProverBase<PCS>* prover) {
@@ -122,10 +168,23 @@ BlindedPolynomial<Poly, Evals> Prover<Poly, Evals>::CreateGrandProductPoly(
     ProverBase<PCS>* prover, const Pair<Evals>& compressed_pair,
     const Pair<BlindedPolynomial<Poly, Evals>>& permuted_pair, const F& beta,
     const F& gamma) {
-  return {plonk::GrandProductArgument::CreatePolySerial(
-              prover, CreateNumeratorCallback(compressed_pair, beta, gamma),
-              CreateDenominatorCallback(permuted_pair, beta, gamma)),
-          prover->blinder().Generate()};
+  return CreateGrandProductPolyFromRatios(
+      prover, plonk::GrandProductArgument::ComputeRatiosSerial(
+                  prover, CreateNumeratorCallback(compressed_pair, beta, gamma),
+                  CreateDenominatorCallback(permuted_pair, beta, gamma)));
+}
+
+// static
+template <typename Poly, typename Evals>
+template <typename PCS>
+BlindedPolynomial<Poly, Evals>
+Prover<Poly, Evals>::CreateGrandProductPolyFromRatios(ProverBase<PCS>* prover,
+                                                      std::vector<F>&& ratios) {
+  F last_z = F::One();
+  Evals grand_product_poly = plonk::GrandProductArgument::CreatePolyFromRatios(
+      prover, last_z, std::move(ratios));
+  F blind = prover->blinder().Generate();
+  return {std::move(grand_product_poly), std::move(blind)};
 }
 
 template <typename Poly, typename Evals>
@@ -147,6 +206,47 @@ void Prover<Poly, Evals>::CreateGrandProductPolys(ProverBase<PCS>* prover,
   compressed_pairs_.clear();
 }
 
+// static
+template <typename Poly, typename Evals>
+template <typename PCS>
+void Prover<Poly, Evals>::BatchCreateGrandProductPolys(
+    std::vector<Prover>& lookup_provers, ProverBase<PCS>* prover, const F& beta,
+    const F& gamma) {
+  size_t parallelism = prover->intra_proof_parallelism();
+  if (parallelism <= 1 || lookup_provers.empty()) {
+    for (Prover& lookup_prover : lookup_provers) {
+      lookup_prover.CreateGrandProductPolys(prover, beta, gamma);
+    }
+    return;
+  }
+
+  // The ratios are independent per lookup, but blinding draws from the
+  // prover's RNG, so it runs in the same order as above.
+  size_t num_lookups = lookup_provers[0].compressed_pairs_.size();
+  std::vector<std::vector<F>> ratios(lookup_provers.size() * num_lookups);
+  OPENMP_PARALLEL_FOR_NUM_THREADS(parallelism,
+                                  size_t i = 0; i < ratios.size(); ++i) {
+    const Prover& lookup_prover = lookup_provers[i / num_lookups];
+    size_t j = i % num_lookups;
+    ratios[i] = plonk::GrandProductArgument::ComputeRatiosSerial(
+        prover, CreateNumeratorCallback(lookup_prover.compressed_pairs_[j], beta,
+                                        gamma),
+        CreateDenominatorCallback(lookup_prover.permuted_pairs_[j], beta,
+                                  gamma));
+  }
+  for (size_t i = 0; i < lookup_provers.size(); ++i) {
+    Prover& lookup_prover = lookup_provers[i];
+    lookup_prover.grand_product_polys_.clear();
+    lookup_prover.grand_product_polys_.reserve(num_lookups);
+    for (size_t j = 0; j < num_lookups; ++j) {
+      lookup_prover.grand_product_polys_.push_back(
+          CreateGrandProductPolyFromRatios(
+              prover, std::move(ratios[i * num_lookups + j])));
+    }
+    lookup_prover.compressed_pairs_.clear();
+  }
+}
+
 // static
 template <typename Poly, typename Evals>
 template <typename PCS>
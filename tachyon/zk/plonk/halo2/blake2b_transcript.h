@@ -111,6 +111,12 @@ class Blake2bReader : public crypto::TranscriptReader<AffinePoint>,
   explicit Blake2bReader(base::ReadOnlyBuffer read_buf)
       : crypto::TranscriptReader<AffinePoint>(std::move(read_buf)) {}
 
+  // NOTE: |GetState()| and |SetState()| are called from the C API, to verify
+  // proofs that continue the transcript of the proof before them.
+  std::vector<uint8_t> GetState() const { return this->DoGetState(); }
+
+  void SetState(absl::Span<const uint8_t> state) { this->DoSetState(state); }
+
   // crypto::TranscriptReader methods
   ScalarField SqueezeChallenge() override { return this->DoSqueezeChallenge(); }
 
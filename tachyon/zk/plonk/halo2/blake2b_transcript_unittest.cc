@@ -71,4 +71,17 @@ TEST_F(Blake2bTranscriptTest, SqueezeChallenge) {
   EXPECT_EQ(expected, actual);
 }
 
+TEST_F(Blake2bTranscriptTest, ReaderState) {
+  base::Uint8VectorBuffer write_buf;
+  Blake2bWriter<G1AffinePoint> writer(std::move(write_buf));
+  ASSERT_TRUE(writer.WriteToTranscript(G1AffinePoint::Generator()));
+
+  // A reader continues from the state of the writer, whatever it has read.
+  base::Buffer read_buf;
+  Blake2bReader<G1AffinePoint> reader(std::move(read_buf));
+  reader.SetState(writer.GetState());
+  EXPECT_EQ(reader.GetState(), writer.GetState());
+  EXPECT_EQ(reader.SqueezeChallenge(), writer.SqueezeChallenge());
+}
+
 }  // namespace tachyon::zk::plonk::halo2
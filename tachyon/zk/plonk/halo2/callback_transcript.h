@@ -0,0 +1,78 @@
+#ifndef TACHYON_ZK_PLONK_HALO2_CALLBACK_TRANSCRIPT_H_
+#define TACHYON_ZK_PLONK_HALO2_CALLBACK_TRANSCRIPT_H_
+
+#include <utility>
+
+#include "tachyon/base/functional/callback.h"
+#include "tachyon/base/logging.h"
+#include "tachyon/crypto/transcripts/transcript.h"
+#include "tachyon/zk/plonk/halo2/proof_serializer.h"
+
+namespace tachyon::zk::plonk::halo2 {
+
+// A transcript whose hasher lives outside of tachyon. Absorbing and squeezing
+// are handed to callbacks, which is slower than the native transcripts, since
+// every value crosses them, but takes hashers tachyon doesn't ship. Its state
+// is kept by whoever owns the hasher. Points and scalars are written to the
+// proof like |Blake2bWriter| writes them.
+template <typename AffinePoint>
+class CallbackWriter : public crypto::TranscriptWriter<AffinePoint> {
+ public:
+  using ScalarField = typename AffinePoint::ScalarField;
+  using AbsorbScalarCallback =
+      base::RepeatingCallback<void(const ScalarField&)>;
+  using AbsorbPointCallback = base::RepeatingCallback<void(const AffinePoint&)>;
+  using SqueezeCallback = base::RepeatingCallback<ScalarField()>;
+
+  // Initialize a transcript given an output buffer. It can't be used before
+  // |SetCallbacks()|.
+  explicit CallbackWriter(base::Uint8VectorBuffer write_buf)
+      : crypto::TranscriptWriter<AffinePoint>(std::move(write_buf)) {}
+
+  bool HasCallbacks() const { return static_cast<bool>(squeeze_); }
+
+  void SetCallbacks(AbsorbScalarCallback absorb_scalar,
+                    AbsorbPointCallback absorb_point,
+                    SqueezeCallback squeeze) {
+    absorb_scalar_ = std::move(absorb_scalar);
+    absorb_point_ = std::move(absorb_point);
+    squeeze_ = std::move(squeeze);
+  }
+
+  // crypto::TranscriptWriter methods
+  ScalarField SqueezeChallenge() override {
+    CHECK(HasCallbacks()) << "the callbacks of the transcript aren't set";
+    return squeeze_.Run();
+  }
+
+  bool WriteToTranscript(const AffinePoint& point) override {
+    CHECK(HasCallbacks()) << "the callbacks of the transcript aren't set";
+    // Like halo2, the point at infinity can't be absorbed.
+    if (point.infinity()) return false;
+    absorb_point_.Run(point);
+    return true;
+  }
+
+  bool WriteToTranscript(const ScalarField& scalar) override {
+    CHECK(HasCallbacks()) << "the callbacks of the transcript aren't set";
+    absorb_scalar_.Run(scalar);
+    return true;
+  }
+
+ private:
+  bool DoWriteToProof(const AffinePoint& point) override {
+    return ProofSerializer<AffinePoint>::WriteToProof(point, this->buffer_);
+  }
+
+  bool DoWriteToProof(const ScalarField& scalar) override {
+    return ProofSerializer<ScalarField>::WriteToProof(scalar, this->buffer_);
+  }
+
+  AbsorbScalarCallback absorb_scalar_;
+  AbsorbPointCallback absorb_point_;
+  SqueezeCallback squeeze_;
+};
+
+}  // namespace tachyon::zk::plonk::halo2
+
+#endif  // TACHYON_ZK_PLONK_HALO2_CALLBACK_TRANSCRIPT_H_
@@ -0,0 +1,55 @@
+#include "tachyon/zk/plonk/halo2/callback_transcript.h"
+
+#include <utility>
+#include <vector>
+
+#include "gtest/gtest.h"
+
+#include "tachyon/math/elliptic_curves/bn/bn254/g1.h"
+#include "tachyon/zk/plonk/halo2/blake2b_transcript.h"
+
+namespace tachyon::zk::plonk::halo2 {
+
+namespace {
+
+using namespace math::bn254;
+
+class CallbackTranscriptTest : public testing::Test {
+ public:
+  static void SetUpTestSuite() { G1Curve::Init(); }
+};
+
+}  // namespace
+
+TEST_F(CallbackTranscriptTest, Callbacks) {
+  CallbackWriter<G1AffinePoint> writer((base::Uint8VectorBuffer()));
+  EXPECT_FALSE(writer.HasCallbacks());
+
+  // A toy hasher that sums what it absorbs.
+  Fr sum = Fr::Zero();
+  std::vector<G1AffinePoint> points;
+  writer.SetCallbacks([&sum](const Fr& scalar) { sum += scalar; },
+                      [&points](const G1AffinePoint& point) {
+                        points.push_back(point);
+                      },
+                      [&sum]() { return sum.Double(); });
+  EXPECT_TRUE(writer.HasCallbacks());
+
+  Fr scalar = Fr::Random();
+  G1AffinePoint point = G1AffinePoint::Random();
+  ASSERT_TRUE(writer.WriteToTranscript(scalar));
+  ASSERT_TRUE(writer.WriteToProof(point));
+  ASSERT_TRUE(writer.WriteToProof(scalar));
+  EXPECT_EQ(sum, scalar.Double());
+  EXPECT_EQ(points, std::vector<G1AffinePoint>{point});
+  EXPECT_EQ(writer.SqueezeChallenge(), scalar.Double().Double());
+  EXPECT_FALSE(writer.WriteToTranscript(G1AffinePoint::Zero()));
+
+  // Only what is written to the proof ends up in it, as Blake2b writes it.
+  Blake2bWriter<G1AffinePoint> blake2b((base::Uint8VectorBuffer()));
+  ASSERT_TRUE(blake2b.WriteToProof(point));
+  ASSERT_TRUE(blake2b.WriteToProof(scalar));
+  EXPECT_EQ(writer.buffer().owned_buffer(), blake2b.buffer().owned_buffer());
+}
+
+}  // namespace tachyon::zk::plonk::halo2
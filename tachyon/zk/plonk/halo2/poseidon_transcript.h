@@ -161,6 +161,12 @@ class PoseidonReader : public crypto::TranscriptReader<AffinePoint>,
   explicit PoseidonReader(base::ReadOnlyBuffer buffer)
       : crypto::TranscriptReader<AffinePoint>(std::move(buffer)) {}
 
+  // NOTE: |GetState()| and |SetState()| are called from the C API, to verify
+  // proofs that continue the transcript of the proof before them.
+  std::vector<uint8_t> GetState() const { return this->DoGetState(); }
+
+  void SetState(absl::Span<const uint8_t> state) { this->DoSetState(state); }
+
   // crypto::TranscriptReader methods
   ScalarField SqueezeChallenge() override { return this->DoSqueezeChallenge(); }
 
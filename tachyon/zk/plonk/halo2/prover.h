@@ -18,6 +18,7 @@
 #include "tachyon/zk/plonk/halo2/random_field_generator.h"
 #include "tachyon/zk/plonk/halo2/verifier.h"
 #include "tachyon/zk/plonk/permutation/permutation_prover.h"
+#include "tachyon/zk/plonk/vanishing/gate_cost.h"
 #include "tachyon/zk/plonk/vanishing/vanishing_prover.h"
 
 namespace tachyon::zk::plonk::halo2 {
@@ -65,6 +66,16 @@ class Prover : public ProverBase<PCS> {
   crypto::XORShiftRNG* rng() { return rng_.get(); }
   RandomFieldGenerator<F>* generator() { return generator_.get(); }
 
+  // Whether |CreateProof()| records the cost of evaluating every custom gate
+  // into |gate_costs()|. Otherwise it times nothing. Defaults to false.
+  bool record_gate_costs() const { return record_gate_costs_; }
+  void set_record_gate_costs(bool record_gate_costs) {
+    record_gate_costs_ = record_gate_costs;
+  }
+  // The cost of every gate of the constraint system in the last proof, in
+  // the order of its gates, or empty if it wasn't recorded.
+  const std::vector<GateCost>& gate_costs() const { return gate_costs_; }
+
   Verifier<PCS, LS> ToVerifier(
       std::unique_ptr<crypto::TranscriptReader<Commitment>> reader) {
     Verifier<PCS, LS> ret(std::move(this->pcs_), std::move(reader));
@@ -222,9 +233,11 @@ class Prover : public ProverBase<PCS> {
     std::vector<MultiPhaseRefTable<Poly>> poly_tables =
         argument_data->ExportPolyTables(proving_key.fixed_polys());
 
-    vanishing_prover.CreateHEvals(this, proving_key, poly_tables, theta, beta,
-                                  gamma, y, permutation_provers,
-                                  lookup_provers);
+    gate_costs_.clear();
+    vanishing_prover.CreateHEvals(
+        this, proving_key, poly_tables, theta, beta, gamma, y,
+        permutation_provers, lookup_provers,
+        record_gate_costs_ ? &gate_costs_ : nullptr);
     vanishing_prover.CreateFinalHPoly(this, cs);
 
     if constexpr (PCS::kSupportsBatchMode) {
@@ -331,6 +344,8 @@ class Prover : public ProverBase<PCS> {
 
   std::unique_ptr<crypto::XORShiftRNG> rng_;
   std::unique_ptr<RandomFieldGenerator<F>> generator_;
+  bool record_gate_costs_ = false;
+  std::vector<GateCost> gate_costs_;
 };
 
 }  // namespace tachyon::zk::plonk::halo2
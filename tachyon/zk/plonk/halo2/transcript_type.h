@@ -19,6 +19,8 @@ enum class TranscriptType : uint8_t {
   kBlake2b,
   kPoseidon,
   kSha256,
+  // The hasher is outside of tachyon, see |CallbackWriter|.
+  kCustom,
 };
 
 }  // namespace zk::plonk::halo2
@@ -52,6 +52,51 @@ class Verifier : public VerifierBase<PCS> {
     return VerifyProofForTesting(vkey, instance_columns_vec, nullptr, nullptr);
   }
 
+  // Commits to instance |columns| the way |VerifyProof()| does, so that
+  // the commitments can be computed once for many proofs and passed to
+  // |VerifyProofWithInstanceCommitments()|. Returns false if a column has
+  // more values than usable rows.
+  [[nodiscard]] bool CommitInstanceColumns(
+      const VerifyingKey<F, Commitment>& vkey,
+      const std::vector<Evals>& columns,
+      std::vector<Commitment>* commitments) {
+    static_assert(PCS::kQueryInstance,
+                  "Only a PCS that queries instances commits to them");
+    RowIndex usable_rows =
+        this->GetUsableRows(vkey.constraint_system().ComputeBlindingFactors());
+    for (const Evals& column : columns) {
+      if (column.NumElements() > size_t{usable_rows}) {
+        LOG(ERROR) << "Too many number of elements in instance column";
+        return false;
+      }
+    }
+    *commitments = CommitColumns(columns);
+    return true;
+  }
+
+  // Same as |VerifyProof()|, but given the commitments to the instance
+  // columns instead of their values, which is all the verifier reads of them
+  // when the PCS queries instances. A commitment to other values than the
+  // proof's makes the proof fail.
+  [[nodiscard]] bool VerifyProofWithInstanceCommitments(
+      const VerifyingKey<F, Commitment>& vkey,
+      const std::vector<std::vector<Commitment>>& instance_commitments_vec) {
+    static_assert(PCS::kQueryInstance,
+                  "Only a PCS that queries instances commits to them");
+    size_t num_instance_columns =
+        vkey.constraint_system().num_instance_columns();
+    for (const std::vector<Commitment>& commitments :
+         instance_commitments_vec) {
+      if (commitments.size() != num_instance_columns) {
+        LOG(ERROR) << "The size of instance commitments doesn't match with "
+                      "constraint system";
+        return false;
+      }
+    }
+    return ReadAndVerifyProof(vkey, {}, instance_commitments_vec, nullptr,
+                              nullptr);
+  }
+
  private:
   template <typename TestArguments, typename TestData>
   friend class plonk::CircuitTest;
@@ -68,7 +113,17 @@ class Verifier : public VerifierBase<PCS> {
     } else {
       instance_commitments_vec.resize(instance_columns_vec.size());
     }
+    return ReadAndVerifyProof(vkey, instance_columns_vec,
+                              instance_commitments_vec, proof_out,
+                              expected_h_eval_out);
+  }
 
+  // |instance_columns_vec| is only read if the PCS doesn't query instances.
+  bool ReadAndVerifyProof(
+      const VerifyingKey<F, Commitment>& vkey,
+      const std::vector<std::vector<Evals>>& instance_columns_vec,
+      const std::vector<std::vector<Commitment>>& instance_commitments_vec,
+      Proof<F, Commitment>* proof_out, F* expected_h_eval_out) {
     crypto::TranscriptReader<Commitment>* transcript = this->GetReader();
     CHECK(transcript->WriteToTranscript(vkey.transcript_repr()));
 
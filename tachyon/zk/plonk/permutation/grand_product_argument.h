@@ -32,7 +32,7 @@ class GrandProductArgument {
     base::Parallelize(grand_product, std::move(numerator_callback));
 
     F last_z = F::One();
-    return DoCreatePoly(prover, last_z, std::move(z));
+    return CreatePolyFromRatios(prover, last_z, std::move(z));
   }
 
   template <typename PCS, typename Callable,
@@ -42,6 +42,21 @@ class GrandProductArgument {
                                 Callable denominator_callback) {
     using F = typename Evals::Field;
 
+    F last_z = F::One();
+    return CreatePolyFromRatios(
+        prover, last_z,
+        ComputeRatiosSerial(prover, std::move(numerator_callback),
+                            std::move(denominator_callback)));
+  }
+
+  // Computes the ratios |CreatePolySerial()| multiplies up, without blinding
+  // them. It doesn't touch the blinder, so it can run for independent
+  // polynomials at once. See |CreatePolyFromRatios()|.
+  template <typename PCS, typename Callable,
+            typename F = typename PCS::Evals::Field>
+  static std::vector<F> ComputeRatiosSerial(const ProverBase<PCS>* prover,
+                                            Callable numerator_callback,
+                                            Callable denominator_callback) {
     // NOTE(chokobole): It's safe to downcast because domain is already checked.
     RowIndex size = static_cast<RowIndex>(prover->pcs().N());
     std::vector<F> z(size + 1);
@@ -56,9 +71,7 @@ class GrandProductArgument {
     for (RowIndex i = 0; i < size; ++i) {
       grand_product[i] *= numerator_callback(i);
     }
-
-    F last_z = F::One();
-    return DoCreatePoly(prover, last_z, std::move(z));
+    return z;
   }
 
   // If the number of rows is out of the supported size of polynomial
@@ -72,6 +85,20 @@ class GrandProductArgument {
                                    Callable numerator_callback,
                                    Callable denominator_callback,
                                    size_t num_cols, F& last_z) {
+    return CreatePolyFromRatios(
+        prover, last_z,
+        ComputeExcessiveRatios(prover, std::move(numerator_callback),
+                               std::move(denominator_callback), num_cols));
+  }
+
+  // Computes the ratios |CreateExcessivePoly()| multiplies up, without
+  // blinding them. See |ComputeRatiosSerial()|.
+  template <typename PCS, typename Callable,
+            typename F = typename PCS::Evals::Field>
+  static std::vector<F> ComputeExcessiveRatios(const ProverBase<PCS>* prover,
+                                               Callable numerator_callback,
+                                               Callable denominator_callback,
+                                               size_t num_cols) {
     // NOTE(chokobole): It's safe to downcast because domain is already checked.
     RowIndex size = static_cast<RowIndex>(prover->pcs().N());
     std::vector<F> z(size + 1, F::One());
@@ -98,14 +125,14 @@ class GrandProductArgument {
         }
       }
     }
-
-    return DoCreatePoly(prover, last_z, std::move(z));
+    return z;
   }
 
- private:
+  // Multiplies up the ratios from |last_z| and blinds the result. Blinding
+  // draws from the prover's RNG, so this must be called in proof order.
   template <typename PCS, typename F, typename Evals = typename PCS::Evals>
-  static Evals DoCreatePoly(ProverBase<PCS>* prover, F& last_z,
-                            std::vector<F>&& grand_product) {
+  static Evals CreatePolyFromRatios(ProverBase<PCS>* prover, F& last_z,
+                                    std::vector<F>&& grand_product) {
     RowIndex usable_rows = prover->GetUsableRows();
 
     absl::Span<F> z(grand_product);
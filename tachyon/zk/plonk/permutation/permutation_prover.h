@@ -93,11 +93,23 @@ class PermutationProver {
       std::vector<crypto::PolynomialOpening<Poly>>& openings);
 
  private:
+  template <typename PCS>
+  static std::vector<F> ComputeChunkRatios(
+      const ProverBase<PCS>* prover,
+      const PermutationTableStore<Evals>& table_store, size_t chunk_idx,
+      const F& beta, const F& gamma);
+
   template <typename PCS>
   void CreateGrandProductPolys(ProverBase<PCS>* prover,
                                const PermutationTableStore<Evals>& table_store,
                                size_t chunk_num, const F& beta, const F& gamma);
 
+  // Appends the grand product polynomial of the next chunk, which continues
+  // from |last_z| and updates it.
+  template <typename PCS>
+  void AddGrandProductPoly(ProverBase<PCS>* prover, std::vector<F>&& ratios,
+                           F& last_z);
+
   template <typename Domain>
   void TransformEvalsToPoly(const Domain* domain);
 
@@ -11,6 +11,7 @@
 #include <vector>
 
 #include "tachyon/base/logging.h"
+#include "tachyon/base/openmp_util.h"
 #include "tachyon/base/ref.h"
 #include "tachyon/zk/plonk/permutation/grand_product_argument.h"
 #include "tachyon/zk/plonk/permutation/permutation_prover.h"
@@ -18,6 +19,29 @@
 
 namespace tachyon::zk::plonk {
 
+// static
+template <typename Poly, typename Evals>
+template <typename PCS>
+std::vector<typename Poly::Field>
+PermutationProver<Poly, Evals>::ComputeChunkRatios(
+    const ProverBase<PCS>* prover,
+    const PermutationTableStore<Evals>& table_store, size_t chunk_idx,
+    const F& beta, const F& gamma) {
+  std::vector<base::Ref<const Evals>> permuted_columns =
+      table_store.GetPermutedColumns(chunk_idx);
+  std::vector<base::Ref<const Evals>> unpermuted_columns =
+      table_store.GetUnpermutedColumns(chunk_idx);
+  std::vector<base::Ref<const Evals>> value_columns =
+      table_store.GetValueColumns(chunk_idx);
+
+  size_t chunk_size = table_store.GetChunkSize(chunk_idx);
+  return GrandProductArgument::ComputeExcessiveRatios(
+      prover,
+      CreateNumeratorCallback(unpermuted_columns, value_columns, beta, gamma),
+      CreateDenominatorCallback(permuted_columns, value_columns, beta, gamma),
+      chunk_size);
+}
+
 template <typename Poly, typename Evals>
 template <typename PCS>
 void PermutationProver<Poly, Evals>::CreateGrandProductPolys(
@@ -29,25 +53,23 @@ void PermutationProver<Poly, Evals>::CreateGrandProductPolys(
   F last_z = F::One();
 
   for (size_t i = 0; i < chunk_num; ++i) {
-    std::vector<base::Ref<const Evals>> permuted_columns =
-        table_store.GetPermutedColumns(i);
-    std::vector<base::Ref<const Evals>> unpermuted_columns =
-        table_store.GetUnpermutedColumns(i);
-    std::vector<base::Ref<const Evals>> value_columns =
-        table_store.GetValueColumns(i);
-
-    size_t chunk_size = table_store.GetChunkSize(i);
-    Evals grand_product_poly = GrandProductArgument::CreateExcessivePoly(
-        prover,
-        CreateNumeratorCallback(unpermuted_columns, value_columns, beta, gamma),
-        CreateDenominatorCallback(permuted_columns, value_columns, beta, gamma),
-        chunk_size, last_z);
-
-    grand_product_polys_.emplace_back(std::move(grand_product_poly),
-                                      prover->blinder().Generate());
+    AddGrandProductPoly(
+        prover, ComputeChunkRatios(prover, table_store, i, beta, gamma),
+        last_z);
   }
 }
 
+template <typename Poly, typename Evals>
+template <typename PCS>
+void PermutationProver<Poly, Evals>::AddGrandProductPoly(
+    ProverBase<PCS>* prover, std::vector<F>&& ratios, F& last_z) {
+  Evals grand_product_poly = GrandProductArgument::CreatePolyFromRatios(
+      prover, last_z, std::move(ratios));
+  F blind = prover->blinder().Generate();
+  grand_product_polys_.emplace_back(std::move(grand_product_poly),
+                                    std::move(blind));
+}
+
 // static
 template <typename Poly, typename Evals>
 template <typename PCS>
@@ -71,12 +93,39 @@ void PermutationProver<Poly, Evals>::BatchCreateGrandProductPolys(
   UnpermutedTable<Evals> unpermuted_table = UnpermutedTable<Evals>::Construct(
       argument.columns().size(), prover->pcs().N(), prover->domain());
   PermutedTable<Evals> permuted_table(&permutation_proving_key.permutations());
-  for (size_t i = 0; i < tables.size(); ++i) {
-    PermutationTableStore<Evals> table_store(argument.columns(), tables[i],
+  size_t parallelism = prover->intra_proof_parallelism();
+  if (parallelism <= 1) {
+    for (size_t i = 0; i < tables.size(); ++i) {
+      PermutationTableStore<Evals> table_store(argument.columns(), tables[i],
+                                               permuted_table, unpermuted_table,
+                                               chunk_len);
+      permutation_provers[i].CreateGrandProductPolys(prover, table_store,
+                                                     chunk_num, beta, gamma);
+    }
+    return;
+  }
+
+  // The ratios of every chunk of every circuit are independent, but each
+  // chunk continues from the last value of the previous one, and blinding
+  // draws from the prover's RNG, so the rest runs in order.
+  std::vector<std::vector<F>> ratios(tables.size() * chunk_num);
+  OPENMP_PARALLEL_FOR_NUM_THREADS(parallelism,
+                                  size_t i = 0; i < ratios.size(); ++i) {
+    PermutationTableStore<Evals> table_store(argument.columns(),
+                                             tables[i / chunk_num],
                                              permuted_table, unpermuted_table,
                                              chunk_len);
-    permutation_provers[i].CreateGrandProductPolys(prover, table_store,
-                                                   chunk_num, beta, gamma);
+    ratios[i] =
+        ComputeChunkRatios(prover, table_store, i % chunk_num, beta, gamma);
+  }
+  for (size_t i = 0; i < tables.size(); ++i) {
+    PermutationProver& permutation_prover = permutation_provers[i];
+    permutation_prover.grand_product_polys_.reserve(chunk_num);
+    F last_z = F::One();
+    for (size_t j = 0; j < chunk_num; ++j) {
+      permutation_prover.AddGrandProductPoly(
+          prover, std::move(ratios[i * chunk_num + j]), last_z);
+    }
   }
 }
 
@@ -7,7 +7,10 @@
 #ifndef TACHYON_ZK_PLONK_VANISHING_CIRCUIT_POLYNOMIAL_BUILDER_H_
 #define TACHYON_ZK_PLONK_VANISHING_CIRCUIT_POLYNOMIAL_BUILDER_H_
 
+#include <stdint.h>
+
 #include <memory>
+#include <mutex>
 #include <utility>
 #include <vector>
 
@@ -24,6 +27,7 @@
 #include "tachyon/zk/plonk/keys/proving_key_forward.h"
 #include "tachyon/zk/plonk/permutation/permutation_prover.h"
 #include "tachyon/zk/plonk/vanishing/evaluation_input.h"
+#include "tachyon/zk/plonk/vanishing/gate_cost.h"
 #include "tachyon/zk/plonk/vanishing/graph_evaluator.h"
 #include "tachyon/zk/plonk/vanishing/vanishing_utils.h"
 
@@ -36,6 +40,14 @@ namespace tachyon::zk::plonk {
 template <typename PCS, typename LS>
 class CircuitPolynomialBuilder {
  public:
+  // The time spent in the calculations of every gate at the sampled rows,
+  // which the chunks add to as they are evaluated.
+  struct GateTimes {
+    std::mutex mutex;
+    std::vector<uint64_t> nanos;
+    uint64_t num_sampled_rows = 0;
+  };
+
   using F = typename PCS::Field;
   using C = typename PCS::Commitment;
   using Poly = typename PCS::Poly;
@@ -95,9 +107,15 @@ class CircuitPolynomialBuilder {
 
   // Returns an evaluation-formed polynomial as below.
   // - gate₀(X) + y * gate₁(X) + ... + yⁱ * gateᵢ(X) + ...
+  // If |gate_costs| is given, it is filled with the cost of every gate, whose
+  // calculations end before the matching one of |gate_calculation_ends|.
   ExtendedEvals BuildExtendedCircuitColumn(
       const GraphEvaluator<F>& custom_gate_evaluator,
-      LookupEvaluator& lookup_evaluator) {
+      LookupEvaluator& lookup_evaluator,
+      absl::Span<const size_t> gate_calculation_ends = {},
+      std::vector<GateCost>* gate_costs = nullptr) {
+    GateTimes gate_times;
+    gate_times.nanos.resize(gate_calculation_ends.size());
     std::vector<std::vector<F>> value_parts;
     value_parts.reserve(num_parts_);
     // Calculate the quotient polynomial for each part
@@ -123,10 +141,17 @@ class CircuitPolynomialBuilder {
           lookup_evaluator.UpdateLookupCosets(*this, j);
         base::Parallelize(
             value_part,
-            [this, &custom_gate_evaluator, &lookup_evaluator](
+            [this, &custom_gate_evaluator, &lookup_evaluator,
+             gate_calculation_ends, gate_costs, &gate_times](
                 absl::Span<F> chunk, size_t chunk_offset, size_t chunk_size) {
-              UpdateChunkByCustomGates(custom_gate_evaluator, chunk,
-                                       chunk_offset, chunk_size);
+              if (gate_costs) {
+                UpdateChunkByCustomGatesAndTime(
+                    custom_gate_evaluator, gate_calculation_ends, chunk,
+                    chunk_offset, chunk_size, gate_times);
+              } else {
+                UpdateChunkByCustomGates(custom_gate_evaluator, chunk,
+                                         chunk_offset, chunk_size);
+              }
               UpdateChunkByPermutation(chunk, chunk_offset, chunk_size);
               lookup_evaluator.UpdateChunkByLookups(*this, chunk, chunk_offset,
                                                     chunk_size);
@@ -136,6 +161,12 @@ class CircuitPolynomialBuilder {
       value_parts.push_back(std::move(value_part));
       UpdateCurrentExtendedOmega();
     }
+    if (gate_costs) {
+      uint64_t num_rows = static_cast<uint64_t>(num_parts_) *
+                          poly_tables_.size() * static_cast<uint64_t>(n_);
+      *gate_costs = ComputeGateCosts(gate_calculation_ends, gate_times,
+                                     num_rows);
+    }
     std::vector<F> extended = BuildExtendedColumnWithColumns(value_parts);
     return ExtendedEvals(std::move(extended));
   }
@@ -246,6 +277,37 @@ class CircuitPolynomialBuilder {
     }
   }
 
+  // Same as |UpdateChunkByCustomGates()|, but times the calculations of every
+  // gate at every |GateCost::kSampleInterval|-th row into |gate_times|.
+  void UpdateChunkByCustomGatesAndTime(
+      const GraphEvaluator<F>& custom_gate_evaluator,
+      absl::Span<const size_t> gate_calculation_ends, absl::Span<F> chunk,
+      size_t chunk_offset, size_t chunk_size, GateTimes& gate_times) {
+    EvaluationInput<Evals> evaluation_input = ExtractEvaluationInput(
+        custom_gate_evaluator.CreateInitialIntermediates(),
+        custom_gate_evaluator.CreateEmptyRotations());
+    std::vector<uint64_t> nanos(gate_calculation_ends.size());
+    uint64_t num_sampled_rows = 0;
+    size_t start = chunk_offset * chunk_size;
+    for (size_t i = 0; i < chunk.size(); ++i) {
+      if ((start + i) % GateCost::kSampleInterval == 0) {
+        chunk[i] = custom_gate_evaluator.EvaluateAndTime(
+            evaluation_input, start + i, /*scale=*/1, chunk[i],
+            gate_calculation_ends, absl::MakeSpan(nanos));
+        ++num_sampled_rows;
+      } else {
+        chunk[i] = custom_gate_evaluator.Evaluate(evaluation_input, start + i,
+                                                  /*scale=*/1, chunk[i]);
+      }
+    }
+
+    std::lock_guard<std::mutex> lock(gate_times.mutex);
+    for (size_t i = 0; i < nanos.size(); ++i) {
+      gate_times.nanos[i] += nanos[i];
+    }
+    gate_times.num_sampled_rows += num_sampled_rows;
+  }
+
   void UpdateLPolys() {
     l_first_ = coset_domain_->FFT(proving_key_.l_first());
     l_last_ = coset_domain_->FFT(proving_key_.l_last());
@@ -269,6 +331,23 @@ class CircuitPolynomialBuilder {
     }
   }
 
+  static std::vector<GateCost> ComputeGateCosts(
+      absl::Span<const size_t> gate_calculation_ends,
+      const GateTimes& gate_times, uint64_t num_rows) {
+    std::vector<GateCost> gate_costs(gate_calculation_ends.size());
+    size_t begin = 0;
+    for (size_t i = 0; i < gate_costs.size(); ++i) {
+      if (gate_times.num_sampled_rows > 0) {
+        gate_costs[i].nanos = static_cast<uint64_t>(
+            static_cast<double>(gate_times.nanos[i]) * num_rows /
+            gate_times.num_sampled_rows);
+      }
+      gate_costs[i].num_ops = (gate_calculation_ends[i] - begin) * num_rows;
+      begin = gate_calculation_ends[i];
+    }
+    return gate_costs;
+  }
+
   void UpdateTable(size_t circuit_idx) {
     absl::Span<const Poly> new_fixed_columns =
         poly_tables_[circuit_idx].GetFixedColumns();
@@ -0,0 +1,25 @@
+#ifndef TACHYON_ZK_PLONK_VANISHING_GATE_COST_H_
+#define TACHYON_ZK_PLONK_VANISHING_GATE_COST_H_
+
+#include <stddef.h>
+#include <stdint.h>
+
+namespace tachyon::zk::plonk {
+
+// What evaluating a custom gate over the extended domain cost, to find the
+// gates that dominate the quotient.
+struct GateCost {
+  // The time spent in the calculations of the gate, extrapolated from every
+  // |kSampleInterval|-th row, which is the only one that is timed.
+  uint64_t nanos = 0;
+  // The calculations of the gate times the rows they were evaluated at. A
+  // calculation shared with an earlier gate is only counted for that one.
+  uint64_t num_ops = 0;
+
+  // Timing every row would cost more than many of the calculations it times.
+  constexpr static size_t kSampleInterval = 64;
+};
+
+}  // namespace tachyon::zk::plonk
+
+#endif  // TACHYON_ZK_PLONK_VANISHING_GATE_COST_H_
@@ -7,10 +7,12 @@
 #ifndef TACHYON_ZK_PLONK_VANISHING_GRAPH_EVALUATOR_H_
 #define TACHYON_ZK_PLONK_VANISHING_GRAPH_EVALUATOR_H_
 
+#include <chrono>
 #include <string>
 #include <vector>
 
 #include "absl/strings/substitute.h"
+#include "absl/types/span.h"
 
 #include "tachyon/zk/expressions/advice_expression.h"
 #include "tachyon/zk/expressions/challenge_expression.h"
@@ -61,18 +63,30 @@ class GraphEvaluator : public Evaluator<F, ValueSource> {
   template <typename Evals>
   F Evaluate(EvaluationInput<Evals>& data, size_t idx, int32_t scale,
              const F& previous_value) const {
-    for (size_t i = 0; i < rotations_.size(); ++i) {
-      data.rotations()[i] =
-          Rotation(rotations_[i]).GetIndex(idx, scale, data.n());
-    }
+    UpdateRotations(data, idx, scale);
+    EvaluateCalculations(data, 0, calculations_.size(), previous_value);
+    return GetResult(data);
+  }
 
-    for (const CalculationInfo& calculation : calculations_) {
-      data.intermediates()[calculation.target] =
-          calculation.calculation.Evaluate(data, constants_, previous_value);
+  // Same as |Evaluate()|, but adds the nanoseconds spent in the i-th range
+  // of calculations, the ones before |ends[i]| and from |ends[i - 1]|, to
+  // |nanos[i]|. The calculations after the last range aren't timed.
+  template <typename Evals>
+  F EvaluateAndTime(EvaluationInput<Evals>& data, size_t idx, int32_t scale,
+                    const F& previous_value, absl::Span<const size_t> ends,
+                    absl::Span<uint64_t> nanos) const {
+    UpdateRotations(data, idx, scale);
+    size_t begin = 0;
+    for (size_t i = 0; i < ends.size(); ++i) {
+      auto start = std::chrono::steady_clock::now();
+      EvaluateCalculations(data, begin, ends[i], previous_value);
+      nanos[i] += std::chrono::duration_cast<std::chrono::nanoseconds>(
+                      std::chrono::steady_clock::now() - start)
+                      .count();
+      begin = ends[i];
     }
-
-    if (calculations_.empty()) return F::Zero();
-    return data.intermediates()[calculations_.back().target];
+    EvaluateCalculations(data, begin, calculations_.size(), previous_value);
+    return GetResult(data);
   }
 
   // Evaluator methods
@@ -235,13 +249,36 @@ class GraphEvaluator : public Evaluator<F, ValueSource> {
   }
 
  private:
+  template <typename Evals>
+  void UpdateRotations(EvaluationInput<Evals>& data, size_t idx,
+                       int32_t scale) const {
+    for (size_t i = 0; i < rotations_.size(); ++i) {
+      data.rotations()[i] =
+          Rotation(rotations_[i]).GetIndex(idx, scale, data.n());
+    }
+  }
+
+  template <typename Evals>
+  void EvaluateCalculations(EvaluationInput<Evals>& data, size_t begin,
+                            size_t end, const F& previous_value) const {
+    for (size_t i = begin; i < end; ++i) {
+      const CalculationInfo& calculation = calculations_[i];
+      data.intermediates()[calculation.target] =
+          calculation.calculation.Evaluate(data, constants_, previous_value);
+    }
+  }
+
+  template <typename Evals>
+  F GetResult(EvaluationInput<Evals>& data) const {
+    if (calculations_.empty()) return F::Zero();
+    return data.intermediates()[calculations_.back().target];
+  }
+
   size_t AddRotation(const Rotation& rotation) {
-    size_t rotation_index = rotation.value();
-    std::optional<size_t> position = base::FindIndexIf(
-        rotations_,
-        [rotation_index](size_t value) { return rotation_index == value; });
+    std::optional<size_t> position =
+        base::FindIndex(rotations_, rotation.value());
     if (position.has_value()) return position.value();
-    rotations_.push_back(rotation_index);
+    rotations_.push_back(rotation.value());
     return rotations_.size() - 1;
   }
 
@@ -19,6 +19,7 @@
 #include "tachyon/zk/plonk/constraint_system/constraint_system.h"
 #include "tachyon/zk/plonk/keys/proving_key_forward.h"
 #include "tachyon/zk/plonk/vanishing/circuit_polynomial_builder.h"
+#include "tachyon/zk/plonk/vanishing/gate_cost.h"
 #include "tachyon/zk/plonk/vanishing/graph_evaluator.h"
 
 namespace tachyon::zk::plonk {
@@ -46,6 +47,8 @@ class VanishingArgument {
           });
       parts.insert(parts.end(), std::make_move_iterator(tmp.begin()),
                    std::make_move_iterator(tmp.end()));
+      evaluator.gate_calculation_ends_.push_back(
+          evaluator.custom_gates_.calculations().size());
     }
     evaluator.custom_gates_.AddCalculation(Calculation::Horner(
         ValueSource::PreviousValue(), std::move(parts), ValueSource::Y()));
@@ -57,6 +60,11 @@ class VanishingArgument {
 
   const GraphEvaluator<F>& custom_gates() const { return custom_gates_; }
   const LookupEvaluator& lookup_evaluator() const { return lookup_evaluator_; }
+  // The calculations of the i-th gate of |custom_gates()| are the ones before
+  // the i-th end and from the one before it.
+  const std::vector<size_t>& gate_calculation_ends() const {
+    return gate_calculation_ends_;
+  }
 
   template <typename PCS, typename Poly,
             typename ExtendedEvals = typename PCS::ExtendedEvals>
@@ -65,7 +73,8 @@ class VanishingArgument {
       const std::vector<MultiPhaseRefTable<Poly>>& poly_tables, const F& theta,
       const F& beta, const F& gamma, const F& y, const F& zeta,
       const std::vector<PermutationProver<Poly, Evals>>& permutation_provers,
-      const std::vector<LookupProver>& lookup_provers) {
+      const std::vector<LookupProver>& lookup_provers,
+      std::vector<GateCost>* gate_costs = nullptr) {
     size_t cs_degree =
         proving_key.verifying_key().constraint_system().ComputeDegree();
 
@@ -75,12 +84,14 @@ class VanishingArgument {
             prover->GetLastRow(), cs_degree, poly_tables, theta, beta, gamma, y,
             zeta, proving_key, permutation_provers, lookup_provers);
 
-    return builder.BuildExtendedCircuitColumn(custom_gates_, lookup_evaluator_);
+    return builder.BuildExtendedCircuitColumn(
+        custom_gates_, lookup_evaluator_, gate_calculation_ends_, gate_costs);
   }
 
  private:
   GraphEvaluator<F> custom_gates_;
   LookupEvaluator lookup_evaluator_;
+  std::vector<size_t> gate_calculation_ends_;
 };
 
 }  // namespace tachyon::zk::plonk
@@ -18,6 +18,7 @@
 #include "tachyon/zk/plonk/base/multi_phase_ref_table.h"
 #include "tachyon/zk/plonk/keys/proving_key.h"
 #include "tachyon/zk/plonk/permutation/permutation_prover.h"
+#include "tachyon/zk/plonk/vanishing/gate_cost.h"
 
 namespace tachyon::zk::plonk {
 
@@ -42,7 +43,8 @@ class VanishingProver {
       const std::vector<MultiPhaseRefTable<Poly>>& tables, const F& theta,
       const F& beta, const F& gamma, const F& y,
       const std::vector<PermutationProver<Poly, Evals>>& permutation_provers,
-      const std::vector<LookupProver>& lookup_provers);
+      const std::vector<LookupProver>& lookup_provers,
+      std::vector<GateCost>* gate_costs = nullptr);
 
   template <typename PCS>
   void CreateFinalHPoly(ProverBase<PCS>* prover,
@@ -57,13 +57,14 @@ void VanishingProver<Poly, Evals, ExtendedPoly, ExtendedEvals>::CreateHEvals(
     const std::vector<MultiPhaseRefTable<Poly>>& tables, const F& theta,
     const F& beta, const F& gamma, const F& y,
     const std::vector<PermutationProver<Poly, Evals>>& permutation_provers,
-    const std::vector<LookupProver>& lookup_provers) {
+    const std::vector<LookupProver>& lookup_provers,
+    std::vector<GateCost>* gate_costs) {
   VanishingArgument<LS> vanishing_argument = VanishingArgument<LS>::Create(
       proving_key.verifying_key().constraint_system());
   F zeta = GetHalo2Zeta<F>();
   h_evals_ = vanishing_argument.BuildExtendedCircuitColumn(
       prover, proving_key, tables, theta, beta, gamma, y, zeta,
-      permutation_provers, lookup_provers);
+      permutation_provers, lookup_provers, gate_costs);
 }
 
 template <typename Poly, typename Evals, typename ExtendedPoly,
@@ -78,8 +79,9 @@ void VanishingProver<Poly, Evals, ExtendedPoly,
                                   prover->domain());
 
   // Obtain final h(X) polynomial
-  h_poly_ = ExtendedToCoeff<F, ExtendedPoly>(std::move(h_evals_),
-                                             prover->extended_domain());
+  h_poly_ = ExtendedToCoeffChunked<F, ExtendedPoly>(std::move(h_evals_),
+                                                    prover->extended_domain(),
+                                                    prover->fft_chunk_log2());
 
   // FIXME(TomTaehoonKim): Remove this if possible.
   const size_t quotient_poly_degree = constraint_system.ComputeDegree() - 1;
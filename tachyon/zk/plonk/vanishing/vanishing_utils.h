@@ -7,11 +7,13 @@
 #ifndef TACHYON_ZK_PLONK_VANISHING_VANISHING_UTILS_H_
 #define TACHYON_ZK_PLONK_VANISHING_VANISHING_UTILS_H_
 
+#include <memory>
 #include <utility>
 #include <vector>
 
 #include "absl/types/span.h"
 
+#include "tachyon/base/openmp_util.h"
 #include "tachyon/base/parallelize.h"
 #include "tachyon/zk/base/blinded_polynomial.h"
 #include "tachyon/zk/base/entities/prover_base.h"
@@ -127,6 +129,96 @@ ExtendedPoly ExtendedToCoeff(ExtendedEvals&& evals,
   return poly;
 }
 
+// Transposes the |rows| x |cols| matrix stored row-major in |values| in place,
+// following the cycles of the permutation, so that only a bit per element is
+// needed on top of |values|.
+template <typename F>
+void TransposeInPlace(std::vector<F>& values, size_t rows, size_t cols) {
+  size_t size = values.size();
+  CHECK_EQ(size, rows * cols);
+  if (rows == 1 || cols == 1) return;
+  // The element at i = r * |cols| + c moves to c * |rows| + r, which is
+  // i * |rows| mod (|size| - 1). The first and the last element stay.
+  std::vector<bool> moved(size, false);
+  for (size_t start = 1; start < size - 1; ++start) {
+    if (moved[start]) continue;
+    size_t i = start;
+    F value = std::move(values[i]);
+    do {
+      size_t next = (i * rows) % (size - 1);
+      std::swap(value, values[next]);
+      moved[next] = true;
+      i = next;
+    } while (i != start);
+  }
+}
+
+// Same as |ExtendedToCoeff()|, but splits the IFFT of the extended domain into
+// IFFTs of 2^|chunk_log2| and N / 2^|chunk_log2| elements, where N is the size
+// of the extended domain (the four-step algorithm). It makes extra passes over
+// |evals|, but needs a chunk per thread on top of it instead of the buffers of
+// an IFFT of N elements. The field arithmetic is exact, so the coefficients are
+// the same as those of |ExtendedToCoeff()|.
+//
+// A |chunk_log2| of 0 or at least log₂(N) runs the IFFT of N elements.
+template <typename F, typename ExtendedPoly, typename ExtendedEvals,
+          typename ExtendedDomain>
+ExtendedPoly ExtendedToCoeffChunked(ExtendedEvals&& evals,
+                                    const ExtendedDomain* extended_domain,
+                                    uint32_t chunk_log2) {
+  uint32_t log_n = extended_domain->log_size_of_group();
+  if (chunk_log2 == 0 || chunk_log2 >= log_n) {
+    return ExtendedToCoeff<F, ExtendedPoly>(std::move(evals), extended_domain);
+  }
+  CHECK_EQ(evals.NumElements(), extended_domain->size());
+
+  // The evaluation at ωʲ for j = j₁ + n₁ * j₂ is the (j₂, j₁)-th entry of an
+  // n₂ x n₁ matrix, and the coefficient of k = k₁ * n₂ + k₂ ends up as the
+  // (k₂, k₁)-th one:
+  //   a_k = 1/N Σ_{j₁} ω^{-n₂j₁k₁} ω^{-j₁k₂} Σ_{j₂} ω^{-n₁j₂k₂} e_j
+  size_t n2 = size_t{1} << chunk_log2;
+  size_t n1 = extended_domain->size() >> chunk_log2;
+  std::unique_ptr<ExtendedDomain> inner_domain = ExtendedDomain::Create(n2);
+  std::unique_ptr<ExtendedDomain> outer_domain = ExtendedDomain::Create(n1);
+  std::vector<F>& values = evals.evaluations();
+
+  // The IFFTs of the columns, each twisted by ω^{-j₁k₂}.
+  OPENMP_PARALLEL_FOR(size_t j1 = 0; j1 < n1; ++j1) {
+    std::vector<F> column(n2);
+    for (size_t j2 = 0; j2 < n2; ++j2) {
+      column[j2] = values[j1 + n1 * j2];
+    }
+    ExtendedPoly poly = inner_domain->IFFT(ExtendedEvals(std::move(column)));
+    const std::vector<F>& coeffs = poly.coefficients().coefficients();
+    F twiddle = extended_domain->group_gen_inv().Pow(j1);
+    F twiddle_pow = F::One();
+    for (size_t k2 = 0; k2 < n2; ++k2) {
+      // |IFFT()| drops the zeros of the highest degrees.
+      values[j1 + n1 * k2] =
+          k2 < coeffs.size() ? coeffs[k2] * twiddle_pow : F::Zero();
+      twiddle_pow *= twiddle;
+    }
+  }
+
+  // The IFFTs of the rows, which are contiguous.
+  OPENMP_PARALLEL_FOR(size_t k2 = 0; k2 < n2; ++k2) {
+    auto row = values.begin() + n1 * k2;
+    std::vector<F> row_values(row, row + n1);
+    ExtendedPoly poly =
+        outer_domain->IFFT(ExtendedEvals(std::move(row_values)));
+    const std::vector<F>& coeffs = poly.coefficients().coefficients();
+    for (size_t k1 = 0; k1 < n1; ++k1) {
+      row[k1] = k1 < coeffs.size() ? coeffs[k1] : F::Zero();
+    }
+  }
+
+  TransposeInPlace(values, n2, n1);
+  ExtendedPoly poly(
+      typename ExtendedPoly::Coefficients(std::move(evals.evaluations())));
+  DistributePowersZeta<F>(poly, false);
+  return poly;
+}
+
 template <typename F>
 std::vector<F> BuildExtendedColumnWithColumns(
     const std::vector<std::vector<F>>& columns) {
@@ -10,14 +10,16 @@
 
 #include "gtest/gtest.h"
 
+#include "tachyon/base/containers/container_util.h"
 #include "tachyon/math/elliptic_curves/bn/bn254/g1.h"
+#include "tachyon/math/finite_fields/test/finite_field_test.h"
 #include "tachyon/math/polynomials/univariate/univariate_evaluation_domain_factory.h"
 
 namespace tachyon::zk::plonk {
 
 namespace {
 
-class VanishingUtilsTest : public testing::Test {
+class VanishingUtilsTest : public math::FiniteFieldTest<math::bn254::Fr> {
  public:
   constexpr static size_t N = size_t{1} << 4;
   constexpr static size_t kMaxDegree = N - 1;
@@ -50,4 +52,34 @@ TEST_F(VanishingUtilsTest, BuildExtendedColumnWithColumns) {
   }
 }
 
+TEST_F(VanishingUtilsTest, TransposeInPlace) {
+  // A 2 x 3 matrix.
+  std::vector<F> values = base::CreateVector(6, [](size_t i) { return F(i); });
+  TransposeInPlace(values, 2, 3);
+  EXPECT_EQ(values, std::vector<F>({F(0), F(3), F(1), F(4), F(2), F(5)}));
+}
+
+TEST_F(VanishingUtilsTest, ExtendedToCoeffChunked) {
+  constexpr uint32_t kLogN = 16;
+  using ExtendedDomain =
+      math::UnivariateEvaluationDomain<F, (size_t{1} << kLogN) - 1>;
+  using ExtendedPoly = typename ExtendedDomain::DensePoly;
+  using ExtendedEvals = typename ExtendedDomain::Evals;
+
+  std::unique_ptr<ExtendedDomain> domain =
+      ExtendedDomain::Create(size_t{1} << kLogN);
+  std::vector<F> values =
+      base::CreateVector(domain->size(), []() { return F::Random(); });
+
+  ExtendedPoly expected =
+      ExtendedToCoeff<F, ExtendedPoly>(ExtendedEvals(values), domain.get());
+  for (uint32_t chunk_log2 : {0, 1, 5, 8, 11, 15, 16}) {
+    SCOPED_TRACE(chunk_log2);
+    ExtendedPoly chunked = ExtendedToCoeffChunked<F, ExtendedPoly>(
+        ExtendedEvals(values), domain.get(), chunk_log2);
+    EXPECT_EQ(chunked.coefficients().coefficients(),
+              expected.coefficients().coefficients());
+  }
+}
+
 }  // namespace tachyon::zk::plonk
@@ -0,0 +1,184 @@
+// Captures which halo2_proofs this crate declares and which one Cargo resolved
+// it to, for `compat::halo2_versions()` and `compat::linked_halo2()`. Both are
+// passed on as `key=value` pairs separated by ';', one entry per dependency,
+// separated by '|'. Builds that don't run this script, e.g, with Bazel, fall
+// back to `version::HALO2_VERSION`.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+const HALO2_PROOFS: &str = "halo2_proofs";
+
+// The `key = "value"` fields of a package of a Cargo.lock, and its
+// dependencies.
+type LockPackage = (Vec<(String, String)>, Vec<String>);
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let manifest = manifest_dir.join("Cargo.toml");
+    println!("cargo:rerun-if-changed={}", manifest.display());
+    if let Ok(manifest) = fs::read_to_string(&manifest) {
+        let declared = declared_halo2(&manifest);
+        if !declared.is_empty() {
+            println!(
+                "cargo:rustc-env=TACHYON_HALO2_EXPECTED={}",
+                declared.join("|")
+            );
+        }
+    }
+
+    if let Some(lock) = find_lock(&manifest_dir) {
+        println!("cargo:rerun-if-changed={}", lock.display());
+        if let Ok(lock) = fs::read_to_string(&lock) {
+            let package = env::var("CARGO_PKG_NAME").unwrap();
+            let linked = linked_halo2(&lock, &package);
+            if !linked.is_empty() {
+                println!("cargo:rustc-env=TACHYON_HALO2_LINKED={}", linked.join("|"));
+            }
+        }
+    }
+}
+
+// Returns the `key = "value"` pairs of `s`, e.g, of an inline table.
+fn fields(s: &str) -> Vec<(String, String)> {
+    let mut fields = vec![];
+    let mut rest = s;
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().trim_start_matches(['{', ',']).trim();
+        let value = rest[eq + 1..].trim_start();
+        if !value.starts_with('"') {
+            rest = &rest[eq + 1..];
+            continue;
+        }
+        let end = match value[1..].find('"') {
+            Some(end) => end + 1,
+            None => break,
+        };
+        fields.push((key.to_owned(), value[1..end].to_owned()));
+        rest = &value[end + 1..];
+    }
+    fields
+}
+
+fn join(fields: &[(String, String)]) -> String {
+    fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+// Every dependency of the manifest that is halo2_proofs, under its own name or
+// renamed with `package`, e.g, one per feature-selected fork.
+fn declared_halo2(manifest: &str) -> Vec<String> {
+    let mut declared = vec![];
+    let mut in_dependencies = false;
+    let mut lines = manifest.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_dependencies = line.ends_with("dependencies]") && !line.contains("dev-");
+            continue;
+        }
+        let eq = match line.find('=') {
+            Some(eq) if in_dependencies && !line.starts_with('#') => eq,
+            _ => continue,
+        };
+        let name = line[..eq].trim();
+        let mut spec = line[eq + 1..].trim().to_owned();
+        if spec.starts_with('{') {
+            while !spec.contains('}') {
+                match lines.next() {
+                    Some(next) => spec.push_str(next.trim()),
+                    None => break,
+                }
+            }
+        }
+        let mut fields = if spec.starts_with('"') {
+            vec![("version".to_owned(), spec.trim_matches('"').to_owned())]
+        } else {
+            fields(&spec)
+        };
+        let package = fields
+            .iter()
+            .find(|(key, _)| key == "package")
+            .map(|(_, value)| value.clone())
+            .unwrap_or_else(|| name.to_owned());
+        if package == HALO2_PROOFS {
+            fields.retain(|(key, _)| key == "version" || key == "git" || key == "rev");
+            fields.insert(0, ("name".to_owned(), package));
+            declared.push(join(&fields));
+        }
+    }
+    declared
+}
+
+fn find_lock(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock| lock.exists())
+}
+
+// The halo2_proofs packages `package` depends on in `lock`.
+fn linked_halo2(lock: &str, package: &str) -> Vec<String> {
+    // Each package is a list of `key = "value"` lines, followed by its
+    // dependencies, one per line, as "name", "name version" or
+    // "name version (source)" when the name alone is ambiguous.
+    let packages: Vec<LockPackage> = lock
+        .split("[[package]]")
+        .skip(1)
+        .map(|block| {
+            let fields = block.lines().flat_map(fields).collect();
+            let dependencies = block
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with('"') && line.ends_with("\","))
+                .map(|line| line.trim_end_matches(',').trim_matches('"').to_owned())
+                .collect();
+            (fields, dependencies)
+        })
+        .collect();
+    let field = |fields: &[(String, String)], key: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.clone())
+    };
+
+    let dependencies = match packages
+        .iter()
+        .find(|(fields, _)| field(fields, "name").as_deref() == Some(package))
+    {
+        Some((_, dependencies)) => dependencies,
+        None => return vec![],
+    };
+    dependencies
+        .iter()
+        .filter(|dependency| dependency.split(' ').next() == Some(HALO2_PROOFS))
+        .filter_map(|dependency| {
+            let mut parts = dependency.splitn(3, ' ');
+            parts.next();
+            let version = parts.next();
+            let source = parts.next().map(|source| source.trim_matches(['(', ')']));
+            packages.iter().map(|(fields, _)| fields).find(|fields| {
+                field(fields, "name").as_deref() == Some(HALO2_PROOFS)
+                    && version
+                        .iter()
+                        .all(|v| field(fields, "version").as_deref() == Some(v))
+                    && source
+                        .iter()
+                        .all(|s| field(fields, "source").as_deref() == Some(s))
+            })
+        })
+        .map(|fields| {
+            let fields: Vec<_> = fields
+                .iter()
+                .filter(|(key, _)| key == "name" || key == "version" || key == "source")
+                .cloned()
+                .collect();
+            join(&fields)
+        })
+        .collect()
+}
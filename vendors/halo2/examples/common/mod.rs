@@ -0,0 +1,73 @@
+// The flags every example takes: `--k`, `--params` and `--out`.
+//
+// Without `--params`, fresh params of size 2^k are generated. `--k` is ignored
+// with `--params`, which carries its own.
+
+use std::{env, fs::File, io::BufReader, process};
+
+use halo2_proofs::poly::{commitment::Params, kzg::commitment::ParamsKZG};
+use halo2curves::bn256::Bn256;
+use rand_core::OsRng;
+
+pub struct Args {
+    pub k: u32,
+    pub params: Option<String>,
+    pub out: Option<String>,
+}
+
+fn parse_args(default_k: u32) -> Result<Args, String> {
+    let mut args = Args {
+        k: default_k,
+        params: None,
+        out: None,
+    };
+    let mut iter = env::args().skip(1);
+    while let Some(flag) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--k" => args.k = value.parse().map_err(|e| format!("--k: {}", e))?,
+            "--params" => args.params = Some(value),
+            "--out" => args.out = Some(value),
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+    Ok(args)
+}
+
+/// Parses the flags of the example `name`, or exits with its usage.
+pub fn args(name: &str, default_k: u32) -> Args {
+    parse_args(default_k).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!("usage: {} [--k K] [--params PATH] [--out PATH]", name);
+        process::exit(2);
+    })
+}
+
+/// Reads the params at `--params`, or generates them for `--k`.
+pub fn params(args: &Args) -> ParamsKZG<Bn256> {
+    match &args.params {
+        Some(path) => {
+            let file = File::open(path).unwrap_or_else(|e| {
+                eprintln!("failed to open {}: {}", path, e);
+                process::exit(1);
+            });
+            ParamsKZG::<Bn256>::read(&mut BufReader::new(file)).unwrap_or_else(|e| {
+                eprintln!("failed to read {}: {}", path, e);
+                process::exit(1);
+            })
+        }
+        None => ParamsKZG::<Bn256>::setup(args.k, OsRng),
+    }
+}
+
+/// Writes `bytes` to `--out`, if it was given.
+pub fn write_out(args: &Args, bytes: &[u8]) {
+    if let Some(path) = &args.out {
+        std::fs::write(path, bytes).unwrap_or_else(|e| {
+            eprintln!("failed to write {}: {}", path, e);
+            process::exit(1);
+        });
+    }
+}
@@ -0,0 +1,30 @@
+// Proves a multiplication circuit for an EVM verifier, with the SHA-256
+// transcript and uncompressed points, and writes its calldata: the public
+// input as a 32-byte big-endian word, followed by the proof.
+//
+//   cargo run --example prove_and_encode_evm --features examples -- \
+//       [--k 8] [--params params.bin] [--out calldata.bin]
+
+mod common;
+
+use std::process;
+
+use halo2_proofs::poly::commitment::Params;
+
+fn main() {
+    let args = common::args("prove_and_encode_evm", 8);
+    let params = common::params(&args);
+
+    let (proof, calldata) =
+        tachyon_halo2::prove_and_encode_evm(&params, 2, 3).unwrap_or_else(|e| {
+            eprintln!("failed to prove: {:?}", e);
+            process::exit(1);
+        });
+    println!(
+        "k = {}: proved in {} bytes, matching halo2, and encoded {} bytes of calldata",
+        params.k(),
+        proof.len(),
+        calldata.len()
+    );
+    common::write_out(&args, &calldata);
+}
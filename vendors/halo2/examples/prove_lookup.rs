@@ -0,0 +1,29 @@
+// Proves a circuit with a lookup argument end to end and checks the proof
+// with halo2's verifier:
+//
+//   cargo run --example prove_lookup --features examples -- \
+//       [--k 8] [--params params.bin] [--out proof.bin]
+//
+// The circuit looks 16 values up in a table of 8, so k has to be at least 5.
+
+mod common;
+
+use std::process;
+
+use halo2_proofs::poly::commitment::Params;
+
+fn main() {
+    let args = common::args("prove_lookup", 8);
+    let params = common::params(&args);
+
+    let proof = tachyon_halo2::prove_lookup(&params).unwrap_or_else(|e| {
+        eprintln!("failed to prove: {:?}", e);
+        process::exit(1);
+    });
+    println!(
+        "k = {}: proved and verified the lookup in {} bytes",
+        params.k(),
+        proof.len()
+    );
+    common::write_out(&args, &proof);
+}
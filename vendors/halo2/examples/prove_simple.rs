@@ -0,0 +1,28 @@
+// Proves a multiplication circuit end to end and checks the proof with
+// halo2's verifier:
+//
+//   cargo run --example prove_simple --features examples -- \
+//       [--k 8] [--params params.bin] [--out proof.bin]
+
+mod common;
+
+use std::process;
+
+use halo2_proofs::poly::commitment::Params;
+
+fn main() {
+    let args = common::args("prove_simple", 8);
+    let params = common::params(&args);
+
+    let (proof, public_input) = tachyon_halo2::prove_simple(&params, 2, 3).unwrap_or_else(|e| {
+        eprintln!("failed to prove: {:?}", e);
+        process::exit(1);
+    });
+    println!(
+        "k = {}: proved and verified {:?} in {} bytes",
+        params.k(),
+        public_input,
+        proof.len()
+    );
+    common::write_out(&args, &proof);
+}
@@ -0,0 +1,10 @@
+// Run with `cargo +nightly fuzz run parse_all` from `vendors/halo2`. The first
+// byte of an input picks the parser, see `src/fuzz.rs`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    tachyon_halo2::parse_all(data);
+});
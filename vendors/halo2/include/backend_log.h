@@ -0,0 +1,21 @@
+#ifndef VENDORS_HALO2_INCLUDE_BACKEND_LOG_H_
+#define VENDORS_HALO2_INCLUDE_BACKEND_LOG_H_
+
+#include <stddef.h>
+#include <stdint.h>
+
+#include "rust/cxx.h"
+
+namespace tachyon::halo2_api {
+
+void set_backend_log(uint8_t destination, size_t callback);
+
+int32_t backend_log_verbosity();
+
+void set_backend_log_verbosity(int32_t verbosity);
+
+void log_fatal_for_testing(rust::Str message);
+
+}  // namespace tachyon::halo2_api
+
+#endif  // VENDORS_HALO2_INCLUDE_BACKEND_LOG_H_
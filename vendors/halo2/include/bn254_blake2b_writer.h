@@ -16,6 +16,8 @@ namespace tachyon::halo2_api::bn254 {
 class Blake2bWriter {
  public:
   Blake2bWriter();
+  explicit Blake2bWriter(tachyon_halo2_bn254_transcript_writer* writer)
+      : writer_(writer) {}
   Blake2bWriter(const Blake2bWriter& other) = delete;
   Blake2bWriter& operator=(const Blake2bWriter& other) = delete;
   ~Blake2bWriter();
@@ -23,12 +25,15 @@ class Blake2bWriter {
   void update(rust::Slice<const uint8_t> data);
   void finalize(std::array<uint8_t, BLAKE2B512_DIGEST_LENGTH>& result);
   rust::Vec<uint8_t> state() const;
+  std::unique_ptr<Blake2bWriter> clone() const;
 
  private:
   tachyon_halo2_bn254_transcript_writer* writer_;
 };
 
 std::unique_ptr<Blake2bWriter> new_blake2b_writer();
+std::unique_ptr<Blake2bWriter> new_blake2b_writer_from_state(
+    rust::Slice<const uint8_t> state);
 
 }  // namespace tachyon::halo2_api::bn254
 
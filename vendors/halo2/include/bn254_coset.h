@@ -0,0 +1,27 @@
+#ifndef VENDORS_HALO2_INCLUDE_BN254_COSET_H_
+#define VENDORS_HALO2_INCLUDE_BN254_COSET_H_
+
+#include <stdint.h>
+
+#include <memory>
+
+namespace tachyon::halo2_api::bn254 {
+
+struct Fr;
+class Evals;
+class Poly;
+
+// Returns zero evaluations over the domain of size 2ᵏ.
+std::unique_ptr<Evals> empty_coset_evals(uint32_t k);
+
+// Evaluates |poly| at |offset| * ωⁱ, where ω generates the domain of size 2ᵏ.
+std::unique_ptr<Evals> coset_fft(const Poly& poly, uint32_t k,
+                                 const Fr& offset);
+
+// Inverse of |coset_fft()|.
+std::unique_ptr<Poly> coset_ifft(const Evals& evals, uint32_t k,
+                                 const Fr& offset);
+
+}  // namespace tachyon::halo2_api::bn254
+
+#endif  // VENDORS_HALO2_INCLUDE_BN254_COSET_H_
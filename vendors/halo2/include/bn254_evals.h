@@ -6,6 +6,8 @@
 #include <memory>
 #include <utility>
 
+#include "rust/cxx.h"
+
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluations.h"
 
 namespace tachyon::halo2_api::bn254 {
@@ -28,7 +30,11 @@ class Evals {
   }
 
   size_t len() const;
+  rust::Box<Fr> get_value(size_t idx) const;
   void set_value(size_t idx, const Fr& value);
+  void get_values(rust::Slice<Fr> values) const;
+  void set_values(rust::Slice<const Fr> values);
+  void reset();
   std::unique_ptr<Evals> clone() const;
 
  private:
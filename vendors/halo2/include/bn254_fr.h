@@ -0,0 +1,16 @@
+#ifndef VENDORS_HALO2_INCLUDE_BN254_FR_H_
+#define VENDORS_HALO2_INCLUDE_BN254_FR_H_
+
+#include "rust/cxx.h"
+
+namespace tachyon::halo2_api::bn254 {
+
+struct Fr;
+
+void batch_inverse(rust::Slice<const Fr> values, rust::Slice<Fr> inverses);
+
+void batch_inverse_in_place(rust::Slice<Fr> values);
+
+}  // namespace tachyon::halo2_api::bn254
+
+#endif  // VENDORS_HALO2_INCLUDE_BN254_FR_H_
@@ -0,0 +1,38 @@
+#ifndef VENDORS_HALO2_INCLUDE_BN254_GWC_VERIFIER_H_
+#define VENDORS_HALO2_INCLUDE_BN254_GWC_VERIFIER_H_
+
+#include <stddef.h>
+#include <stdint.h>
+
+#include "rust/cxx.h"
+
+#include "tachyon/c/zk/plonk/halo2/bn254_gwc_verifier.h"
+
+namespace tachyon::halo2_api::bn254 {
+
+struct Fr;
+struct G1Point2;
+class ProvingKey;
+
+// Sets |commitments| to the commitment to every instance column of a circuit
+// over |pk|. |column_lens| has the length of every column and |values| the
+// values of all of them in that order. Returns false if a column has more
+// values than usable rows.
+bool commit_gwc_instance_columns(uint8_t transcript_type, uint32_t k,
+                                 rust::Slice<const uint8_t> pcs_params,
+                                 const ProvingKey& pk,
+                                 rust::Slice<const size_t> column_lens,
+                                 rust::Slice<const Fr> values,
+                                 rust::Slice<G1Point2> commitments);
+
+// |commitments| has the commitment to every instance column of every circuit,
+// circuit by circuit, e.g, from |commit_gwc_instance_columns()|.
+bool verify_gwc_proof_with_instance_commitments(
+    uint8_t transcript_type, uint32_t k,
+    rust::Slice<const uint8_t> pcs_params, rust::Slice<const uint8_t> proof,
+    const ProvingKey& pk, size_t num_circuits,
+    rust::Slice<const G1Point2> commitments);
+
+}  // namespace tachyon::halo2_api::bn254
+
+#endif  // VENDORS_HALO2_INCLUDE_BN254_GWC_VERIFIER_H_
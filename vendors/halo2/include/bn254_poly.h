@@ -1,12 +1,19 @@
 #ifndef VENDORS_HALO2_INCLUDE_BN254_POLY_H_
 #define VENDORS_HALO2_INCLUDE_BN254_POLY_H_
 
+#include <stddef.h>
+
+#include <memory>
 #include <utility>
 
+#include "rust/cxx.h"
+
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_dense_polynomial.h"
 
 namespace tachyon::halo2_api::bn254 {
 
+struct Fr;
+
 class Poly {
  public:
   Poly();
@@ -25,10 +32,17 @@ class Poly {
     return std::exchange(poly_, nullptr);
   }
 
+  size_t len() const;
+  void get_coeffs(rust::Slice<Fr> coeffs) const;
+  void set_coeffs(rust::Slice<const Fr> coeffs);
+  std::unique_ptr<Poly> clone() const;
+
  private:
   tachyon_bn254_univariate_dense_polynomial* poly_;
 };
 
+std::unique_ptr<Poly> zero_poly();
+
 }  // namespace tachyon::halo2_api::bn254
 
 #endif  // VENDORS_HALO2_INCLUDE_BN254_POLY_H_
@@ -16,6 +16,8 @@ struct Fr;
 class PoseidonWriter {
  public:
   PoseidonWriter();
+  explicit PoseidonWriter(tachyon_halo2_bn254_transcript_writer* writer)
+      : writer_(writer) {}
   PoseidonWriter(const PoseidonWriter& other) = delete;
   PoseidonWriter& operator=(const PoseidonWriter& other) = delete;
   ~PoseidonWriter();
@@ -23,12 +25,15 @@ class PoseidonWriter {
   void update(rust::Slice<const uint8_t> data);
   rust::Box<Fr> squeeze();
   rust::Vec<uint8_t> state() const;
+  std::unique_ptr<PoseidonWriter> clone() const;
 
  private:
   tachyon_halo2_bn254_transcript_writer* writer_;
 };
 
 std::unique_ptr<PoseidonWriter> new_poseidon_writer();
+std::unique_ptr<PoseidonWriter> new_poseidon_writer_from_state(
+    rust::Slice<const uint8_t> state);
 
 }  // namespace tachyon::halo2_api::bn254
 
@@ -12,6 +12,7 @@
 
 namespace tachyon::halo2_api::bn254 {
 
+struct ColumnQuery;
 struct Fr;
 class GWCProver;
 class SHPlonkProver;
@@ -30,10 +31,20 @@ class ProvingKey {
   uint32_t blinding_factors() const;
   rust::Vec<uint8_t> challenge_phases() const;
   rust::Vec<size_t> constants() const;
+  size_t degree() const;
+  uint32_t extended_k(uint32_t k) const;
   size_t num_advice_columns() const;
   size_t num_challenges() const;
+  size_t num_fixed_columns() const;
   size_t num_instance_columns() const;
+  size_t num_lookups() const;
+  size_t num_permutation_columns() const;
   rust::Vec<uint8_t> phases() const;
+  rust::Vec<size_t> selector_map() const;
+  rust::Vec<size_t> fixed_query_columns() const;
+  rust::Vec<ColumnQuery> advice_queries() const;
+  rust::Vec<ColumnQuery> instance_queries() const;
+  rust::Vec<ColumnQuery> fixed_queries() const;
   rust::Box<Fr> transcript_repr_gwc(const GWCProver& prover);
   rust::Box<Fr> transcript_repr_shplonk(const SHPlonkProver& prover);
 
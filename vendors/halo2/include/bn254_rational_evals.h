@@ -32,6 +32,7 @@ class RationalEvals {
 
   size_t len() const;
   void set_zero(size_t idx);
+  void reset();
   void set_trivial(size_t idx, const Fr& numerator);
   void set_rational(size_t idx, const Fr& numerator, const Fr& denominator);
   std::unique_ptr<RationalEvals> clone() const;
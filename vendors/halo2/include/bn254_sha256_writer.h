@@ -16,6 +16,8 @@ namespace tachyon::halo2_api::bn254 {
 class Sha256Writer {
  public:
   Sha256Writer();
+  explicit Sha256Writer(tachyon_halo2_bn254_transcript_writer* writer)
+      : writer_(writer) {}
   Sha256Writer(const Sha256Writer& other) = delete;
   Sha256Writer& operator=(const Sha256Writer& other) = delete;
   ~Sha256Writer();
@@ -23,12 +25,15 @@ class Sha256Writer {
   void update(rust::Slice<const uint8_t> data);
   void finalize(std::array<uint8_t, SHA256_DIGEST_LENGTH>& result);
   rust::Vec<uint8_t> state() const;
+  std::unique_ptr<Sha256Writer> clone() const;
 
  private:
   tachyon_halo2_bn254_transcript_writer* writer_;
 };
 
 std::unique_ptr<Sha256Writer> new_sha256_writer();
+std::unique_ptr<Sha256Writer> new_sha256_writer_from_state(
+    rust::Slice<const uint8_t> state);
 
 }  // namespace tachyon::halo2_api::bn254
 
@@ -4,6 +4,7 @@
 #include <stdint.h>
 
 #include <memory>
+#include <vector>
 
 #include "rust/cxx.h"
 
@@ -16,6 +17,11 @@ struct G1JacobianPoint;
 struct G2AffinePoint;
 struct InstanceSingle;
 struct AdviceSingle;
+struct ColumnStats;
+struct GateCost;
+struct TranscriptCallbacks;
+struct AllocationStats;
+struct ScratchStats;
 class ProvingKey;
 class Evals;
 class RationalEvals;
@@ -40,20 +46,40 @@ class SHPlonkProver {
   std::unique_ptr<Evals> empty_evals() const;
   std::unique_ptr<RationalEvals> empty_rational_evals() const;
   std::unique_ptr<Poly> ifft(const Evals& evals) const;
+  ColumnStats column_stats(const Evals& evals) const;
   void batch_evaluate(
       rust::Slice<const std::unique_ptr<RationalEvals>> rational_evals,
       rust::Slice<std::unique_ptr<Evals>> evals) const;
   void set_rng(rust::Slice<const uint8_t> state);
   void set_transcript(rust::Slice<const uint8_t> state);
+  void set_transcript_callbacks(TranscriptCallbacks* callbacks);
   void set_extended_domain(const ProvingKey& pk);
+  void set_intra_proof_parallelism(size_t intra_proof_parallelism);
+  void set_gpu_stages(uint8_t gpu_stages);
+  void set_fft_chunk_log2(uint32_t fft_chunk_log2);
+  void set_num_threads(size_t num_threads);
+  bool set_lagrange_basis_policy(uint8_t policy);
+  void set_record_gate_costs(bool record_gate_costs);
   void create_proof(ProvingKey& key,
                     rust::Slice<InstanceSingle> instance_singles,
                     rust::Slice<AdviceSingle> advice_singles,
                     rust::Slice<const Fr> challenges);
+  void add_opening(const Poly& poly, const Fr& point);
+  void create_opening_proof();
   rust::Vec<uint8_t> get_proof() const;
+  rust::Vec<uint8_t> get_transcript_state() const;
+  rust::Vec<uint8_t> pcs_params() const;
+  rust::Vec<GateCost> gate_costs() const;
+  AllocationStats allocation_stats() const;
+  ScratchStats scratch_stats() const;
+  void release_scratch();
 
  private:
   tachyon_halo2_bn254_shplonk_prover* prover_;
+  // Openings added by |add_opening()| that are consumed by the next
+  // |create_opening_proof()|.
+  std::vector<const tachyon_bn254_univariate_dense_polynomial*> opening_polys_;
+  std::vector<tachyon_bn254_fr> opening_points_;
 };
 
 std::unique_ptr<SHPlonkProver> new_shplonk_prover(uint8_t transcript_type,
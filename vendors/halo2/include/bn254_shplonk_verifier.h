@@ -0,0 +1,37 @@
+#ifndef VENDORS_HALO2_INCLUDE_BN254_SHPLONK_VERIFIER_H_
+#define VENDORS_HALO2_INCLUDE_BN254_SHPLONK_VERIFIER_H_
+
+#include <stddef.h>
+#include <stdint.h>
+
+#include "rust/cxx.h"
+
+#include "tachyon/c/zk/plonk/halo2/bn254_shplonk_verifier.h"
+
+namespace tachyon::halo2_api::bn254 {
+
+struct Fr;
+class ProvingKey;
+
+// |column_lens| has the length of every instance column of every circuit,
+// circuit by circuit, and |values| the values of all of them in that order.
+bool verify_shplonk_proof(uint8_t transcript_type, uint32_t k,
+                          rust::Slice<const uint8_t> pcs_params,
+                          rust::Slice<const uint8_t> proof,
+                          const ProvingKey& pk, size_t num_circuits,
+                          rust::Slice<const size_t> column_lens,
+                          rust::Slice<const Fr> values);
+
+// Verifies |proof| with the transcript started from |state|, in the layout
+// |state()| of a writer returns, and on success sets |next_state| to the
+// state the transcript ended in.
+bool verify_shplonk_proof_from_state(
+    uint8_t transcript_type, uint32_t k,
+    rust::Slice<const uint8_t> pcs_params, rust::Slice<const uint8_t> proof,
+    const ProvingKey& pk, size_t num_circuits,
+    rust::Slice<const size_t> column_lens, rust::Slice<const Fr> values,
+    rust::Slice<const uint8_t> state, rust::Vec<uint8_t>& next_state);
+
+}  // namespace tachyon::halo2_api::bn254
+
+#endif  // VENDORS_HALO2_INCLUDE_BN254_SHPLONK_VERIFIER_H_
@@ -0,0 +1,17 @@
+#ifndef VENDORS_HALO2_INCLUDE_BN254_TRANSCRIPT_CALLBACKS_H_
+#define VENDORS_HALO2_INCLUDE_BN254_TRANSCRIPT_CALLBACKS_H_
+
+#include "tachyon/c/zk/plonk/halo2/bn254_transcript.h"
+
+namespace tachyon::halo2_api::bn254 {
+
+struct TranscriptCallbacks;
+
+// Returns the callbacks through which the backend absorbs into and squeezes
+// from the hasher behind |callbacks|, which has to outlive the proof.
+tachyon_halo2_bn254_transcript_callbacks to_c_transcript_callbacks(
+    TranscriptCallbacks* callbacks);
+
+}  // namespace tachyon::halo2_api::bn254
+
+#endif  // VENDORS_HALO2_INCLUDE_BN254_TRANSCRIPT_CALLBACKS_H_
@@ -0,0 +1,567 @@
+#ifndef VENDORS_HALO2_INCLUDE_DLOPEN_H_
+#define VENDORS_HALO2_INCLUDE_DLOPEN_H_
+
+// With TACHYON_HALO2_DLOPEN defined, i.e, with the "dlopen" feature, the
+// wrappers aren't linked against libtachyon. Every function of its C API they
+// call is redirected to the address `src/dlopen.rs` resolved for it after
+// loading the library at runtime, so the call sites are written the same way
+// in both builds. Every wrapper that calls libtachyon includes this header.
+
+#include "tachyon/c/crypto/random/rng.h"
+#include "tachyon/c/device_memory.h"
+#include "tachyon/c/logging.h"
+#include "tachyon/c/math/elliptic_curves/bn/bn254/fr.h"
+#include "tachyon/c/math/elliptic_curves/bn/bn254/msm.h"
+#include "tachyon/c/math/polynomials/univariate/bn254_univariate_dense_polynomial.h"
+#include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluation_domain.h"
+#include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluations.h"
+#include "tachyon/c/math/polynomials/univariate/bn254_univariate_rational_evaluations.h"
+#include "tachyon/c/version.h"
+#include "tachyon/c/zk/base/bn254_blinder.h"
+#include "tachyon/c/zk/plonk/constraint_system/bn254_constraint_system.h"
+#include "tachyon/c/zk/plonk/halo2/bn254_argument_data.h"
+#include "tachyon/c/zk/plonk/halo2/bn254_gwc_prover.h"
+#include "tachyon/c/zk/plonk/halo2/bn254_gwc_verifier.h"
+#include "tachyon/c/zk/plonk/halo2/bn254_instance_columns_vec.h"
+#include "tachyon/c/zk/plonk/halo2/bn254_shplonk_prover.h"
+#include "tachyon/c/zk/plonk/halo2/bn254_shplonk_verifier.h"
+#include "tachyon/c/zk/plonk/halo2/bn254_transcript.h"
+#include "tachyon/c/zk/plonk/keys/bn254_plonk_proving_key.h"
+#include "tachyon/c/zk/plonk/keys/bn254_plonk_verifying_key.h"
+
+#if defined(TACHYON_HALO2_DLOPEN)
+
+#include <stddef.h>
+
+#include "rust/cxx.h"
+
+// The functions of the C API the wrappers call. Loading fails if one of them is
+// missing.
+#define TACHYON_HALO2_REQUIRED_SYMBOLS(X)                                    \
+  X(tachyon_bn254_fr_batch_inverse)                                          \
+  X(tachyon_bn254_g1_create_msm)                                             \
+  X(tachyon_bn254_g1_destroy_msm)                                            \
+  X(tachyon_bn254_g1_point2_msm)                                             \
+  X(tachyon_bn254_plonk_constraint_system_compute_blinding_factors)          \
+  X(tachyon_bn254_plonk_constraint_system_compute_degree)                    \
+  X(tachyon_bn254_plonk_constraint_system_compute_extended_k)                \
+  X(tachyon_bn254_plonk_constraint_system_get_advice_column_phases)          \
+  X(tachyon_bn254_plonk_constraint_system_get_advice_queries)                \
+  X(tachyon_bn254_plonk_constraint_system_get_challenge_phases)              \
+  X(tachyon_bn254_plonk_constraint_system_get_constants)                     \
+  X(tachyon_bn254_plonk_constraint_system_get_fixed_queries)                 \
+  X(tachyon_bn254_plonk_constraint_system_get_fixed_query_columns)           \
+  X(tachyon_bn254_plonk_constraint_system_get_instance_queries)              \
+  X(tachyon_bn254_plonk_constraint_system_get_num_advice_columns)            \
+  X(tachyon_bn254_plonk_constraint_system_get_num_challenges)                \
+  X(tachyon_bn254_plonk_constraint_system_get_num_fixed_columns)             \
+  X(tachyon_bn254_plonk_constraint_system_get_num_instance_columns)          \
+  X(tachyon_bn254_plonk_constraint_system_get_num_lookups)                   \
+  X(tachyon_bn254_plonk_constraint_system_get_num_permutation_columns)       \
+  X(tachyon_bn254_plonk_constraint_system_get_phases)                        \
+  X(tachyon_bn254_plonk_constraint_system_get_selector_map)                  \
+  X(tachyon_bn254_plonk_proving_key_create_from_state)                       \
+  X(tachyon_bn254_plonk_proving_key_destroy)                                 \
+  X(tachyon_bn254_plonk_proving_key_get_verifying_key)                       \
+  X(tachyon_bn254_plonk_verifying_key_get_constraint_system)                 \
+  X(tachyon_bn254_plonk_verifying_key_get_transcript_repr)                   \
+  X(tachyon_bn254_univariate_dense_polynomial_clone)                         \
+  X(tachyon_bn254_univariate_dense_polynomial_create)                        \
+  X(tachyon_bn254_univariate_dense_polynomial_destroy)                       \
+  X(tachyon_bn254_univariate_dense_polynomial_get_coeffs)                    \
+  X(tachyon_bn254_univariate_dense_polynomial_len)                           \
+  X(tachyon_bn254_univariate_dense_polynomial_set_coeffs)                    \
+  X(tachyon_bn254_univariate_evaluation_domain_column_stats)                 \
+  X(tachyon_bn254_univariate_evaluation_domain_coset_fft)                    \
+  X(tachyon_bn254_univariate_evaluation_domain_coset_ifft)                   \
+  X(tachyon_bn254_univariate_evaluation_domain_create)                       \
+  X(tachyon_bn254_univariate_evaluation_domain_destroy)                      \
+  X(tachyon_bn254_univariate_evaluation_domain_empty_evals)                  \
+  X(tachyon_bn254_univariate_evaluation_domain_empty_rational_evals)         \
+  X(tachyon_bn254_univariate_evaluation_domain_ifft)                         \
+  X(tachyon_bn254_univariate_evaluations_clone)                              \
+  X(tachyon_bn254_univariate_evaluations_create)                             \
+  X(tachyon_bn254_univariate_evaluations_destroy)                            \
+  X(tachyon_bn254_univariate_evaluations_get_value)                          \
+  X(tachyon_bn254_univariate_evaluations_get_values)                         \
+  X(tachyon_bn254_univariate_evaluations_len)                                \
+  X(tachyon_bn254_univariate_evaluations_reset)                              \
+  X(tachyon_bn254_univariate_evaluations_set_value)                          \
+  X(tachyon_bn254_univariate_evaluations_set_values)                         \
+  X(tachyon_bn254_univariate_rational_evaluations_batch_evaluate)            \
+  X(tachyon_bn254_univariate_rational_evaluations_clone)                     \
+  X(tachyon_bn254_univariate_rational_evaluations_create)                    \
+  X(tachyon_bn254_univariate_rational_evaluations_destroy)                   \
+  X(tachyon_bn254_univariate_rational_evaluations_len)                       \
+  X(tachyon_bn254_univariate_rational_evaluations_reset)                     \
+  X(tachyon_bn254_univariate_rational_evaluations_set_rational)              \
+  X(tachyon_bn254_univariate_rational_evaluations_set_trivial)               \
+  X(tachyon_bn254_univariate_rational_evaluations_set_zero)                  \
+  X(tachyon_get_abi_version)                                                 \
+  X(tachyon_get_device_memory_in_use)                                        \
+  X(tachyon_get_log_verbosity)                                               \
+  X(tachyon_get_runtime_full_version_str)                                    \
+  X(tachyon_halo2_bn254_argument_data_add_advice_blind)                      \
+  X(tachyon_halo2_bn254_argument_data_add_advice_column)                     \
+  X(tachyon_halo2_bn254_argument_data_add_challenge)                         \
+  X(tachyon_halo2_bn254_argument_data_add_instance_column)                   \
+  X(tachyon_halo2_bn254_argument_data_add_instance_poly)                     \
+  X(tachyon_halo2_bn254_argument_data_create)                                \
+  X(tachyon_halo2_bn254_argument_data_destroy)                               \
+  X(tachyon_halo2_bn254_argument_data_reserve_advice_blinds)                 \
+  X(tachyon_halo2_bn254_argument_data_reserve_advice_columns)                \
+  X(tachyon_halo2_bn254_argument_data_reserve_challenges)                    \
+  X(tachyon_halo2_bn254_argument_data_reserve_instance_columns)              \
+  X(tachyon_halo2_bn254_argument_data_reserve_instance_polys)                \
+  X(tachyon_halo2_bn254_blinder_set_blinding_factors)                        \
+  X(tachyon_halo2_bn254_gwc_prover_commit)                                   \
+  X(tachyon_halo2_bn254_gwc_prover_commit_lagrange)                          \
+  X(tachyon_halo2_bn254_gwc_prover_create_from_params)                       \
+  X(tachyon_halo2_bn254_gwc_prover_create_from_unsafe_setup)                 \
+  X(tachyon_halo2_bn254_gwc_prover_create_proof)                             \
+  X(tachyon_halo2_bn254_gwc_prover_destroy)                                  \
+  X(tachyon_halo2_bn254_gwc_prover_get_blinder)                              \
+  X(tachyon_halo2_bn254_gwc_prover_get_domain)                               \
+  X(tachyon_halo2_bn254_gwc_prover_get_gate_costs)                           \
+  X(tachyon_halo2_bn254_gwc_prover_get_k)                                    \
+  X(tachyon_halo2_bn254_gwc_prover_get_n)                                    \
+  X(tachyon_halo2_bn254_gwc_prover_get_pcs_params)                           \
+  X(tachyon_halo2_bn254_gwc_prover_get_proof)                                \
+  X(tachyon_halo2_bn254_gwc_prover_get_s_g2)                                 \
+  X(tachyon_halo2_bn254_gwc_prover_get_transcript_state)                     \
+  X(tachyon_halo2_bn254_gwc_prover_set_extended_domain)                      \
+  X(tachyon_halo2_bn254_gwc_prover_set_fft_chunk_log2)                       \
+  X(tachyon_halo2_bn254_gwc_prover_set_gpu_stages)                           \
+  X(tachyon_halo2_bn254_gwc_prover_set_intra_proof_parallelism)              \
+  X(tachyon_halo2_bn254_gwc_prover_set_lagrange_basis_policy)                \
+  X(tachyon_halo2_bn254_gwc_prover_set_num_threads)                          \
+  X(tachyon_halo2_bn254_gwc_prover_set_record_gate_costs)                    \
+  X(tachyon_halo2_bn254_gwc_prover_set_rng_state)                            \
+  X(tachyon_halo2_bn254_gwc_prover_set_transcript_callbacks)                 \
+  X(tachyon_halo2_bn254_gwc_prover_set_transcript_repr)                      \
+  X(tachyon_halo2_bn254_gwc_prover_set_transcript_state)                     \
+  X(tachyon_halo2_bn254_gwc_verifier_commit_instance_columns)                \
+  X(tachyon_halo2_bn254_gwc_verifier_create_from_params)                     \
+  X(tachyon_halo2_bn254_gwc_verifier_destroy)                                \
+  X(tachyon_halo2_bn254_gwc_verifier_verify_proof)                           \
+  X(tachyon_halo2_bn254_gwc_verifier_verify_proof_with_instance_commitments) \
+  X(tachyon_halo2_bn254_instance_columns_vec_add_values)                     \
+  X(tachyon_halo2_bn254_instance_columns_vec_create)                         \
+  X(tachyon_halo2_bn254_instance_columns_vec_reserve_values)                 \
+  X(tachyon_halo2_bn254_instance_columns_vec_resize_columns)                 \
+  X(tachyon_halo2_bn254_shplonk_prover_commit)                               \
+  X(tachyon_halo2_bn254_shplonk_prover_commit_lagrange)                      \
+  X(tachyon_halo2_bn254_shplonk_prover_create_from_params)                   \
+  X(tachyon_halo2_bn254_shplonk_prover_create_from_unsafe_setup)             \
+  X(tachyon_halo2_bn254_shplonk_prover_create_opening_proof)                 \
+  X(tachyon_halo2_bn254_shplonk_prover_create_proof)                         \
+  X(tachyon_halo2_bn254_shplonk_prover_destroy)                              \
+  X(tachyon_halo2_bn254_shplonk_prover_get_allocation_stats)                 \
+  X(tachyon_halo2_bn254_shplonk_prover_get_blinder)                          \
+  X(tachyon_halo2_bn254_shplonk_prover_get_domain)                           \
+  X(tachyon_halo2_bn254_shplonk_prover_get_gate_costs)                       \
+  X(tachyon_halo2_bn254_shplonk_prover_get_k)                                \
+  X(tachyon_halo2_bn254_shplonk_prover_get_n)                                \
+  X(tachyon_halo2_bn254_shplonk_prover_get_pcs_params)                       \
+  X(tachyon_halo2_bn254_shplonk_prover_get_proof)                            \
+  X(tachyon_halo2_bn254_shplonk_prover_get_s_g2)                             \
+  X(tachyon_halo2_bn254_shplonk_prover_get_scratch_stats)                    \
+  X(tachyon_halo2_bn254_shplonk_prover_get_transcript_state)                 \
+  X(tachyon_halo2_bn254_shplonk_prover_release_scratch)                      \
+  X(tachyon_halo2_bn254_shplonk_prover_set_extended_domain)                  \
+  X(tachyon_halo2_bn254_shplonk_prover_set_fft_chunk_log2)                   \
+  X(tachyon_halo2_bn254_shplonk_prover_set_gpu_stages)                       \
+  X(tachyon_halo2_bn254_shplonk_prover_set_intra_proof_parallelism)          \
+  X(tachyon_halo2_bn254_shplonk_prover_set_lagrange_basis_policy)            \
+  X(tachyon_halo2_bn254_shplonk_prover_set_num_threads)                      \
+  X(tachyon_halo2_bn254_shplonk_prover_set_record_gate_costs)                \
+  X(tachyon_halo2_bn254_shplonk_prover_set_rng_state)                        \
+  X(tachyon_halo2_bn254_shplonk_prover_set_transcript_callbacks)             \
+  X(tachyon_halo2_bn254_shplonk_prover_set_transcript_repr)                  \
+  X(tachyon_halo2_bn254_shplonk_prover_set_transcript_state)                 \
+  X(tachyon_halo2_bn254_shplonk_verifier_create_from_params)                 \
+  X(tachyon_halo2_bn254_shplonk_verifier_destroy)                            \
+  X(tachyon_halo2_bn254_shplonk_verifier_get_transcript_state)               \
+  X(tachyon_halo2_bn254_shplonk_verifier_set_transcript_state)               \
+  X(tachyon_halo2_bn254_shplonk_verifier_verify_proof)                       \
+  X(tachyon_halo2_bn254_transcript_writer_create)                            \
+  X(tachyon_halo2_bn254_transcript_writer_create_from_state)                 \
+  X(tachyon_halo2_bn254_transcript_writer_destroy)                           \
+  X(tachyon_halo2_bn254_transcript_writer_finalize)                          \
+  X(tachyon_halo2_bn254_transcript_writer_get_state)                         \
+  X(tachyon_halo2_bn254_transcript_writer_squeeze)                           \
+  X(tachyon_halo2_bn254_transcript_writer_update)                            \
+  X(tachyon_log_fatal_for_testing)                                           \
+  X(tachyon_rng_create_from_seed)                                            \
+  X(tachyon_rng_create_from_state)                                           \
+  X(tachyon_rng_destroy)                                                     \
+  X(tachyon_rng_get_next_u32)                                                \
+  X(tachyon_rng_get_state)                                                   \
+  X(tachyon_set_log_destination)                                             \
+  X(tachyon_set_log_verbosity)
+
+// The functions that only a libtachyon built with GPU support has. Calling one
+// that is missing aborts. Their header is only generated for GPU builds, so
+// `src/bn254_msm_gpu.cc` includes it itself, before this one.
+#define TACHYON_HALO2_OPTIONAL_SYMBOLS(X) \
+  X(tachyon_bn254_g1_create_msm_gpu)      \
+  X(tachyon_bn254_g1_destroy_msm_gpu)     \
+  X(tachyon_bn254_g1_point2_msm_gpu)
+
+#define TACHYON_HALO2_SYMBOLS(X) \
+  TACHYON_HALO2_REQUIRED_SYMBOLS(X) TACHYON_HALO2_OPTIONAL_SYMBOLS(X)
+
+namespace tachyon::halo2_api::dlopen {
+
+enum Symbol : size_t {
+#define TACHYON_HALO2_SYMBOL_ENUM(name) k_##name,
+  TACHYON_HALO2_SYMBOLS(TACHYON_HALO2_SYMBOL_ENUM)
+#undef TACHYON_HALO2_SYMBOL_ENUM
+  kNumSymbols,
+};
+
+// Returns the address resolved for |symbol|. If libtachyon isn't loaded yet, it
+// is loaded from TACHYON_LIB_PATH first, and this aborts if that fails.
+const void* GetSymbol(Symbol symbol);
+
+rust::Vec<rust::String> symbol_names();
+
+size_t num_required_symbols();
+
+void set_symbols(rust::Slice<const size_t> addresses);
+
+}  // namespace tachyon::halo2_api::dlopen
+
+// A macro isn't expanded again inside its own expansion, so |name| below still
+// names the declaration of the C API.
+#define TACHYON_HALO2_DLSYM(name)              \
+  (reinterpret_cast<decltype(&::name)>(        \
+      ::tachyon::halo2_api::dlopen::GetSymbol( \
+          ::tachyon::halo2_api::dlopen::k_##name)))
+
+#define tachyon_bn254_fr_batch_inverse \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_fr_batch_inverse)
+#define tachyon_bn254_g1_create_msm \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_g1_create_msm)
+#define tachyon_bn254_g1_create_msm_gpu \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_g1_create_msm_gpu)
+#define tachyon_bn254_g1_destroy_msm \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_g1_destroy_msm)
+#define tachyon_bn254_g1_destroy_msm_gpu \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_g1_destroy_msm_gpu)
+#define tachyon_bn254_g1_point2_msm \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_g1_point2_msm)
+#define tachyon_bn254_g1_point2_msm_gpu \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_g1_point2_msm_gpu)
+#define tachyon_bn254_plonk_constraint_system_compute_blinding_factors \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_compute_blinding_factors)
+#define tachyon_bn254_plonk_constraint_system_compute_degree \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_compute_degree)
+#define tachyon_bn254_plonk_constraint_system_compute_extended_k \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_compute_extended_k)
+#define tachyon_bn254_plonk_constraint_system_get_advice_column_phases \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_advice_column_phases)
+#define tachyon_bn254_plonk_constraint_system_get_advice_queries \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_advice_queries)
+#define tachyon_bn254_plonk_constraint_system_get_challenge_phases \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_challenge_phases)
+#define tachyon_bn254_plonk_constraint_system_get_constants \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_constants)
+#define tachyon_bn254_plonk_constraint_system_get_fixed_queries \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_fixed_queries)
+#define tachyon_bn254_plonk_constraint_system_get_fixed_query_columns \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_fixed_query_columns)
+#define tachyon_bn254_plonk_constraint_system_get_instance_queries \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_instance_queries)
+#define tachyon_bn254_plonk_constraint_system_get_num_advice_columns \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_num_advice_columns)
+#define tachyon_bn254_plonk_constraint_system_get_num_challenges \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_num_challenges)
+#define tachyon_bn254_plonk_constraint_system_get_num_fixed_columns \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_num_fixed_columns)
+#define tachyon_bn254_plonk_constraint_system_get_num_instance_columns \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_num_instance_columns)
+#define tachyon_bn254_plonk_constraint_system_get_num_lookups \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_num_lookups)
+#define tachyon_bn254_plonk_constraint_system_get_num_permutation_columns \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_num_permutation_columns)
+#define tachyon_bn254_plonk_constraint_system_get_phases \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_phases)
+#define tachyon_bn254_plonk_constraint_system_get_selector_map \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_constraint_system_get_selector_map)
+#define tachyon_bn254_plonk_proving_key_create_from_state \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_proving_key_create_from_state)
+#define tachyon_bn254_plonk_proving_key_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_proving_key_destroy)
+#define tachyon_bn254_plonk_proving_key_get_verifying_key \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_proving_key_get_verifying_key)
+#define tachyon_bn254_plonk_verifying_key_get_constraint_system \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_verifying_key_get_constraint_system)
+#define tachyon_bn254_plonk_verifying_key_get_transcript_repr \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_plonk_verifying_key_get_transcript_repr)
+#define tachyon_bn254_univariate_dense_polynomial_clone \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_dense_polynomial_clone)
+#define tachyon_bn254_univariate_dense_polynomial_create \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_dense_polynomial_create)
+#define tachyon_bn254_univariate_dense_polynomial_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_dense_polynomial_destroy)
+#define tachyon_bn254_univariate_dense_polynomial_get_coeffs \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_dense_polynomial_get_coeffs)
+#define tachyon_bn254_univariate_dense_polynomial_len \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_dense_polynomial_len)
+#define tachyon_bn254_univariate_dense_polynomial_set_coeffs \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_dense_polynomial_set_coeffs)
+#define tachyon_bn254_univariate_evaluation_domain_column_stats \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluation_domain_column_stats)
+#define tachyon_bn254_univariate_evaluation_domain_coset_fft \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluation_domain_coset_fft)
+#define tachyon_bn254_univariate_evaluation_domain_coset_ifft \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluation_domain_coset_ifft)
+#define tachyon_bn254_univariate_evaluation_domain_create \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluation_domain_create)
+#define tachyon_bn254_univariate_evaluation_domain_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluation_domain_destroy)
+#define tachyon_bn254_univariate_evaluation_domain_empty_evals \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluation_domain_empty_evals)
+#define tachyon_bn254_univariate_evaluation_domain_empty_rational_evals \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluation_domain_empty_rational_evals)
+#define tachyon_bn254_univariate_evaluation_domain_ifft \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluation_domain_ifft)
+#define tachyon_bn254_univariate_evaluations_clone \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluations_clone)
+#define tachyon_bn254_univariate_evaluations_create \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluations_create)
+#define tachyon_bn254_univariate_evaluations_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluations_destroy)
+#define tachyon_bn254_univariate_evaluations_get_value \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluations_get_value)
+#define tachyon_bn254_univariate_evaluations_get_values \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluations_get_values)
+#define tachyon_bn254_univariate_evaluations_len \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluations_len)
+#define tachyon_bn254_univariate_evaluations_reset \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluations_reset)
+#define tachyon_bn254_univariate_evaluations_set_value \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluations_set_value)
+#define tachyon_bn254_univariate_evaluations_set_values \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_evaluations_set_values)
+#define tachyon_bn254_univariate_rational_evaluations_batch_evaluate \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_rational_evaluations_batch_evaluate)
+#define tachyon_bn254_univariate_rational_evaluations_clone \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_rational_evaluations_clone)
+#define tachyon_bn254_univariate_rational_evaluations_create \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_rational_evaluations_create)
+#define tachyon_bn254_univariate_rational_evaluations_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_rational_evaluations_destroy)
+#define tachyon_bn254_univariate_rational_evaluations_len \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_rational_evaluations_len)
+#define tachyon_bn254_univariate_rational_evaluations_reset \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_rational_evaluations_reset)
+#define tachyon_bn254_univariate_rational_evaluations_set_rational \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_rational_evaluations_set_rational)
+#define tachyon_bn254_univariate_rational_evaluations_set_trivial \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_rational_evaluations_set_trivial)
+#define tachyon_bn254_univariate_rational_evaluations_set_zero \
+  TACHYON_HALO2_DLSYM(tachyon_bn254_univariate_rational_evaluations_set_zero)
+#define tachyon_get_abi_version TACHYON_HALO2_DLSYM(tachyon_get_abi_version)
+#define tachyon_get_device_memory_in_use \
+  TACHYON_HALO2_DLSYM(tachyon_get_device_memory_in_use)
+#define tachyon_get_log_verbosity TACHYON_HALO2_DLSYM(tachyon_get_log_verbosity)
+#define tachyon_get_runtime_full_version_str \
+  TACHYON_HALO2_DLSYM(tachyon_get_runtime_full_version_str)
+#define tachyon_halo2_bn254_argument_data_add_advice_blind \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_add_advice_blind)
+#define tachyon_halo2_bn254_argument_data_add_advice_column \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_add_advice_column)
+#define tachyon_halo2_bn254_argument_data_add_challenge \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_add_challenge)
+#define tachyon_halo2_bn254_argument_data_add_instance_column \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_add_instance_column)
+#define tachyon_halo2_bn254_argument_data_add_instance_poly \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_add_instance_poly)
+#define tachyon_halo2_bn254_argument_data_create \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_create)
+#define tachyon_halo2_bn254_argument_data_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_destroy)
+#define tachyon_halo2_bn254_argument_data_reserve_advice_blinds \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_reserve_advice_blinds)
+#define tachyon_halo2_bn254_argument_data_reserve_advice_columns \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_reserve_advice_columns)
+#define tachyon_halo2_bn254_argument_data_reserve_challenges \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_reserve_challenges)
+#define tachyon_halo2_bn254_argument_data_reserve_instance_columns \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_reserve_instance_columns)
+#define tachyon_halo2_bn254_argument_data_reserve_instance_polys \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_argument_data_reserve_instance_polys)
+#define tachyon_halo2_bn254_blinder_set_blinding_factors \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_blinder_set_blinding_factors)
+#define tachyon_halo2_bn254_gwc_prover_commit \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_commit)
+#define tachyon_halo2_bn254_gwc_prover_commit_lagrange \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_commit_lagrange)
+#define tachyon_halo2_bn254_gwc_prover_create_from_params \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_create_from_params)
+#define tachyon_halo2_bn254_gwc_prover_create_from_unsafe_setup \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_create_from_unsafe_setup)
+#define tachyon_halo2_bn254_gwc_prover_create_proof \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_create_proof)
+#define tachyon_halo2_bn254_gwc_prover_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_destroy)
+#define tachyon_halo2_bn254_gwc_prover_get_blinder \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_get_blinder)
+#define tachyon_halo2_bn254_gwc_prover_get_domain \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_get_domain)
+#define tachyon_halo2_bn254_gwc_prover_get_gate_costs \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_get_gate_costs)
+#define tachyon_halo2_bn254_gwc_prover_get_k \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_get_k)
+#define tachyon_halo2_bn254_gwc_prover_get_n \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_get_n)
+#define tachyon_halo2_bn254_gwc_prover_get_pcs_params \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_get_pcs_params)
+#define tachyon_halo2_bn254_gwc_prover_get_proof \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_get_proof)
+#define tachyon_halo2_bn254_gwc_prover_get_s_g2 \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_get_s_g2)
+#define tachyon_halo2_bn254_gwc_prover_get_transcript_state \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_get_transcript_state)
+#define tachyon_halo2_bn254_gwc_prover_set_extended_domain \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_extended_domain)
+#define tachyon_halo2_bn254_gwc_prover_set_fft_chunk_log2 \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_fft_chunk_log2)
+#define tachyon_halo2_bn254_gwc_prover_set_gpu_stages \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_gpu_stages)
+#define tachyon_halo2_bn254_gwc_prover_set_intra_proof_parallelism \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_intra_proof_parallelism)
+#define tachyon_halo2_bn254_gwc_prover_set_lagrange_basis_policy \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_lagrange_basis_policy)
+#define tachyon_halo2_bn254_gwc_prover_set_num_threads \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_num_threads)
+#define tachyon_halo2_bn254_gwc_prover_set_record_gate_costs \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_record_gate_costs)
+#define tachyon_halo2_bn254_gwc_prover_set_rng_state \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_rng_state)
+#define tachyon_halo2_bn254_gwc_prover_set_transcript_callbacks \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_transcript_callbacks)
+#define tachyon_halo2_bn254_gwc_prover_set_transcript_repr \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_transcript_repr)
+#define tachyon_halo2_bn254_gwc_prover_set_transcript_state \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_prover_set_transcript_state)
+#define tachyon_halo2_bn254_gwc_verifier_commit_instance_columns \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_verifier_commit_instance_columns)
+#define tachyon_halo2_bn254_gwc_verifier_create_from_params \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_verifier_create_from_params)
+#define tachyon_halo2_bn254_gwc_verifier_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_verifier_destroy)
+#define tachyon_halo2_bn254_gwc_verifier_verify_proof \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_verifier_verify_proof)
+#define tachyon_halo2_bn254_gwc_verifier_verify_proof_with_instance_commitments \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_gwc_verifier_verify_proof_with_instance_commitments)
+#define tachyon_halo2_bn254_instance_columns_vec_add_values \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_instance_columns_vec_add_values)
+#define tachyon_halo2_bn254_instance_columns_vec_create \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_instance_columns_vec_create)
+#define tachyon_halo2_bn254_instance_columns_vec_reserve_values \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_instance_columns_vec_reserve_values)
+#define tachyon_halo2_bn254_instance_columns_vec_resize_columns \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_instance_columns_vec_resize_columns)
+#define tachyon_halo2_bn254_shplonk_prover_commit \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_commit)
+#define tachyon_halo2_bn254_shplonk_prover_commit_lagrange \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_commit_lagrange)
+#define tachyon_halo2_bn254_shplonk_prover_create_from_params \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_create_from_params)
+#define tachyon_halo2_bn254_shplonk_prover_create_from_unsafe_setup \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_create_from_unsafe_setup)
+#define tachyon_halo2_bn254_shplonk_prover_create_opening_proof \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_create_opening_proof)
+#define tachyon_halo2_bn254_shplonk_prover_create_proof \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_create_proof)
+#define tachyon_halo2_bn254_shplonk_prover_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_destroy)
+#define tachyon_halo2_bn254_shplonk_prover_get_allocation_stats \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_allocation_stats)
+#define tachyon_halo2_bn254_shplonk_prover_get_blinder \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_blinder)
+#define tachyon_halo2_bn254_shplonk_prover_get_domain \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_domain)
+#define tachyon_halo2_bn254_shplonk_prover_get_gate_costs \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_gate_costs)
+#define tachyon_halo2_bn254_shplonk_prover_get_k \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_k)
+#define tachyon_halo2_bn254_shplonk_prover_get_n \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_n)
+#define tachyon_halo2_bn254_shplonk_prover_get_pcs_params \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_pcs_params)
+#define tachyon_halo2_bn254_shplonk_prover_get_proof \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_proof)
+#define tachyon_halo2_bn254_shplonk_prover_get_s_g2 \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_s_g2)
+#define tachyon_halo2_bn254_shplonk_prover_get_scratch_stats \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_scratch_stats)
+#define tachyon_halo2_bn254_shplonk_prover_get_transcript_state \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_get_transcript_state)
+#define tachyon_halo2_bn254_shplonk_prover_release_scratch \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_release_scratch)
+#define tachyon_halo2_bn254_shplonk_prover_set_extended_domain \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_extended_domain)
+#define tachyon_halo2_bn254_shplonk_prover_set_fft_chunk_log2 \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_fft_chunk_log2)
+#define tachyon_halo2_bn254_shplonk_prover_set_gpu_stages \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_gpu_stages)
+#define tachyon_halo2_bn254_shplonk_prover_set_intra_proof_parallelism \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_intra_proof_parallelism)
+#define tachyon_halo2_bn254_shplonk_prover_set_lagrange_basis_policy \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_lagrange_basis_policy)
+#define tachyon_halo2_bn254_shplonk_prover_set_num_threads \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_num_threads)
+#define tachyon_halo2_bn254_shplonk_prover_set_record_gate_costs \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_record_gate_costs)
+#define tachyon_halo2_bn254_shplonk_prover_set_rng_state \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_rng_state)
+#define tachyon_halo2_bn254_shplonk_prover_set_transcript_callbacks \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_transcript_callbacks)
+#define tachyon_halo2_bn254_shplonk_prover_set_transcript_repr \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_transcript_repr)
+#define tachyon_halo2_bn254_shplonk_prover_set_transcript_state \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_prover_set_transcript_state)
+#define tachyon_halo2_bn254_shplonk_verifier_create_from_params \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_verifier_create_from_params)
+#define tachyon_halo2_bn254_shplonk_verifier_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_verifier_destroy)
+#define tachyon_halo2_bn254_shplonk_verifier_get_transcript_state \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_verifier_get_transcript_state)
+#define tachyon_halo2_bn254_shplonk_verifier_set_transcript_state \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_verifier_set_transcript_state)
+#define tachyon_halo2_bn254_shplonk_verifier_verify_proof \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_shplonk_verifier_verify_proof)
+#define tachyon_halo2_bn254_transcript_writer_create \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_transcript_writer_create)
+#define tachyon_halo2_bn254_transcript_writer_create_from_state \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_transcript_writer_create_from_state)
+#define tachyon_halo2_bn254_transcript_writer_destroy \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_transcript_writer_destroy)
+#define tachyon_halo2_bn254_transcript_writer_finalize \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_transcript_writer_finalize)
+#define tachyon_halo2_bn254_transcript_writer_get_state \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_transcript_writer_get_state)
+#define tachyon_halo2_bn254_transcript_writer_squeeze \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_transcript_writer_squeeze)
+#define tachyon_halo2_bn254_transcript_writer_update \
+  TACHYON_HALO2_DLSYM(tachyon_halo2_bn254_transcript_writer_update)
+#define tachyon_log_fatal_for_testing \
+  TACHYON_HALO2_DLSYM(tachyon_log_fatal_for_testing)
+#define tachyon_rng_create_from_seed \
+  TACHYON_HALO2_DLSYM(tachyon_rng_create_from_seed)
+#define tachyon_rng_create_from_state \
+  TACHYON_HALO2_DLSYM(tachyon_rng_create_from_state)
+#define tachyon_rng_destroy TACHYON_HALO2_DLSYM(tachyon_rng_destroy)
+#define tachyon_rng_get_next_u32 TACHYON_HALO2_DLSYM(tachyon_rng_get_next_u32)
+#define tachyon_rng_get_state TACHYON_HALO2_DLSYM(tachyon_rng_get_state)
+#define tachyon_set_log_destination \
+  TACHYON_HALO2_DLSYM(tachyon_set_log_destination)
+#define tachyon_set_log_verbosity TACHYON_HALO2_DLSYM(tachyon_set_log_verbosity)
+
+#endif  // defined(TACHYON_HALO2_DLOPEN)
+
+#endif  // VENDORS_HALO2_INCLUDE_DLOPEN_H_
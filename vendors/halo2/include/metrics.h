@@ -0,0 +1,12 @@
+#ifndef VENDORS_HALO2_INCLUDE_METRICS_H_
+#define VENDORS_HALO2_INCLUDE_METRICS_H_
+
+#include <stddef.h>
+
+namespace tachyon::halo2_api {
+
+bool device_memory_in_use(size_t& bytes);
+
+}  // namespace tachyon::halo2_api
+
+#endif  // VENDORS_HALO2_INCLUDE_METRICS_H_
@@ -0,0 +1,16 @@
+#ifndef VENDORS_HALO2_INCLUDE_VERSION_H_
+#define VENDORS_HALO2_INCLUDE_VERSION_H_
+
+#include <stdint.h>
+
+#include "rust/cxx.h"
+
+namespace tachyon::halo2_api {
+
+uint32_t abi_version();
+
+rust::String runtime_version();
+
+}  // namespace tachyon::halo2_api
+
+#endif  // VENDORS_HALO2_INCLUDE_VERSION_H_
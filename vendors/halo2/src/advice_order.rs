@@ -0,0 +1,286 @@
+use std::{collections::VecDeque, fmt, io};
+
+use halo2_proofs::{
+    plonk::VerifyingKey,
+    transcript::{Challenge255, Transcript, TranscriptRead},
+};
+use halo2curves::{
+    bn256::{Fr, G1Affine},
+    group::prime::PrimeCurveAffine,
+};
+
+use crate::consts::AbsorbOrder;
+
+/// Wraps the transcript of a verifier so that it reads the advice commitments
+/// of a proof in the [`AbsorbOrder`] it was created with. halo2's verifier
+/// reads those of a phase circuit by circuit; with
+/// [`AbsorbOrder::GlobalColumn`] this reads the whole phase column by column
+/// on its first read, and hands the commitments out again in halo2's order
+/// without absorbing them twice. The first points of the proof have to be the
+/// advice commitments, which they are unless the instances are committed to.
+pub struct AdviceOrderRead<T> {
+    inner: T,
+    num_circuits: usize,
+    /// The number of advice columns of each phase still to be read, in order.
+    /// Empty with [`AbsorbOrder::PhaseThenColumn`].
+    phases: VecDeque<usize>,
+    /// The commitments of the phase being read, in halo2's order.
+    buffered: VecDeque<G1Affine>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for AdviceOrderRead<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdviceOrderRead")
+            .field("inner", &self.inner)
+            .field("num_circuits", &self.num_circuits)
+            .field("pending_phases", &self.phases.len())
+            .field("buffered", &self.buffered.len())
+            .finish()
+    }
+}
+
+impl<T: TranscriptRead<G1Affine, Challenge255<G1Affine>>> AdviceOrderRead<T> {
+    /// Reads a proof of `num_circuits` circuits with `vk` in `order`.
+    pub fn new(
+        inner: T,
+        vk: &VerifyingKey<G1Affine>,
+        num_circuits: usize,
+        order: AbsorbOrder,
+    ) -> Self {
+        let cs = vk.cs();
+        let phases = match order {
+            AbsorbOrder::PhaseThenColumn => VecDeque::new(),
+            AbsorbOrder::GlobalColumn => cs
+                .phases()
+                .map(|phase| {
+                    cs.advice_column_phase
+                        .iter()
+                        .filter(|column_phase| **column_phase == phase)
+                        .count()
+                })
+                .filter(|num_columns| *num_columns > 0)
+                .collect(),
+        };
+        AdviceOrderRead {
+            inner,
+            num_circuits,
+            phases,
+            buffered: VecDeque::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn read_phase(&mut self, num_columns: usize) -> io::Result<()> {
+        let mut commitments = vec![G1Affine::identity(); num_columns * self.num_circuits];
+        for column in 0..num_columns {
+            for circuit in 0..self.num_circuits {
+                commitments[circuit * num_columns + column] = self.inner.read_point()?;
+            }
+        }
+        self.buffered.extend(commitments);
+        Ok(())
+    }
+}
+
+impl<T: TranscriptRead<G1Affine, Challenge255<G1Affine>>>
+    Transcript<G1Affine, Challenge255<G1Affine>> for AdviceOrderRead<T>
+{
+    fn squeeze_challenge(&mut self) -> Challenge255<G1Affine> {
+        self.inner.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: G1Affine) -> io::Result<()> {
+        self.inner.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.inner.common_scalar(scalar)
+    }
+}
+
+impl<T: TranscriptRead<G1Affine, Challenge255<G1Affine>>>
+    TranscriptRead<G1Affine, Challenge255<G1Affine>> for AdviceOrderRead<T>
+{
+    fn read_point(&mut self) -> io::Result<G1Affine> {
+        if self.buffered.is_empty() {
+            if let Some(num_columns) = self.phases.pop_front() {
+                self.read_phase(num_columns)?;
+            }
+        }
+        match self.buffered.pop_front() {
+            Some(point) => Ok(point),
+            None => self.inner.read_point(),
+        }
+    }
+
+    fn read_scalar(&mut self) -> io::Result<Fr> {
+        self.inner.read_scalar()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::{Blake2bRead, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::interleaved_phase_circuit::InterleavedPhaseCircuit,
+        consts::{AbsorbOrder, InstanceAbsorption, TranscriptType},
+        error::TachyonError,
+        instance_hash::verify_proof,
+        prover::{create_proof_with_options, ProofOptions},
+        proving_key::{advice_absorb_order_to_metadata, Metadata},
+        rng::proof_context,
+    };
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_advice_absorb_order() {
+        let k = 4;
+        let s = Fr::from(2);
+        // Two circuits with two columns in each of two phases, so that the
+        // orders differ in every phase.
+        let circuits = [1, 5].map(|start| InterleavedPhaseCircuit {
+            values: (start..start + 3)
+                .map(|i| [Value::known(Fr::from(i)), Value::known(Fr::from(i + 10))])
+                .collect(),
+        });
+        let instances: [&[&[Fr]]; 2] = [&[], &[]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuits[0]).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let metadata = |order| {
+            let mut metadata = Metadata::new();
+            advice_absorb_order_to_metadata(order, &mut metadata);
+            metadata
+        };
+        let prove = |metadata: &Metadata, order| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            tachyon_pk.set_metadata(metadata.clone());
+            let options = ProofOptions {
+                rng_context: proof_context(b"interleaved", 0),
+                advice_absorb_order: order,
+                ..Default::default()
+            };
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &circuits,
+                &instances,
+                &options,
+                &mut transcript,
+            )?;
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            Ok::<_, TachyonError>(proof)
+        };
+        let verify = |metadata: &Metadata, order, proof: &[u8]| {
+            verify_proof::<Blake2bRead<_, _, _>>(
+                &params,
+                pk.get_vk(),
+                metadata,
+                InstanceAbsorption::PerValue,
+                order,
+                &instances,
+                proof,
+            )
+        };
+
+        // A key without an order absorbs like halo2.
+        let default_proof = prove(&Metadata::new(), AbsorbOrder::default()).unwrap();
+        let phase_then_column = metadata(AbsorbOrder::PhaseThenColumn);
+        let global_column = metadata(AbsorbOrder::GlobalColumn);
+        let phase_then_column_proof =
+            prove(&phase_then_column, AbsorbOrder::PhaseThenColumn).unwrap();
+        let global_column_proof = prove(&global_column, AbsorbOrder::GlobalColumn).unwrap();
+        assert_eq!(default_proof, phase_then_column_proof);
+        assert_ne!(phase_then_column_proof, global_column_proof);
+
+        assert!(verify(
+            &Metadata::new(),
+            AbsorbOrder::PhaseThenColumn,
+            &default_proof
+        )
+        .is_ok());
+        assert!(verify(
+            &phase_then_column,
+            AbsorbOrder::PhaseThenColumn,
+            &phase_then_column_proof
+        )
+        .is_ok());
+        assert!(verify(
+            &global_column,
+            AbsorbOrder::GlobalColumn,
+            &global_column_proof
+        )
+        .is_ok());
+
+        // A prover or verifier configured for the other order refuses the key
+        // instead of diverging from the transcript.
+        assert!(matches!(
+            prove(&global_column, AbsorbOrder::PhaseThenColumn),
+            Err(TachyonError::AdviceAbsorbOrderMismatch {
+                key: AbsorbOrder::GlobalColumn,
+                configured: AbsorbOrder::PhaseThenColumn,
+            })
+        ));
+        let e = verify(
+            &global_column,
+            AbsorbOrder::PhaseThenColumn,
+            &global_column_proof,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            e,
+            TachyonError::AdviceAbsorbOrderMismatch {
+                key: AbsorbOrder::GlobalColumn,
+                configured: AbsorbOrder::PhaseThenColumn,
+            }
+        ));
+        assert!(e.to_string().contains("diverge"));
+        assert!(matches!(
+            verify(
+                &phase_then_column,
+                AbsorbOrder::GlobalColumn,
+                &phase_then_column_proof
+            ),
+            Err(TachyonError::AdviceAbsorbOrderMismatch {
+                key: AbsorbOrder::PhaseThenColumn,
+                configured: AbsorbOrder::GlobalColumn,
+            })
+        ));
+        // A proof checked against a key that lies about its order still
+        // fails, just not as clearly.
+        assert!(matches!(
+            verify(
+                &phase_then_column,
+                AbsorbOrder::PhaseThenColumn,
+                &global_column_proof
+            ),
+            Err(TachyonError::Verification(_))
+        ));
+    }
+}
@@ -0,0 +1,502 @@
+use std::{fmt, io::Read};
+
+use halo2_proofs::{
+    plonk::{verify_proof, Error, VerifyingKey},
+    poly::{
+        commitment::Params,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::AccumulatorStrategy,
+        },
+        VerificationStrategy,
+    },
+    transcript::{Blake2bRead, Challenge255, EncodedChallenge, PoseidonRead, TranscriptReadBuffer},
+};
+use halo2curves::{
+    bn256::{Bn256, Fr, G1Affine},
+    CurveAffine,
+};
+
+use crate::{
+    analyze::usable_rows,
+    consts::{MultiOpenScheme, TranscriptType},
+    proof::{parse, ParseError, PointEncoding, ProofValue},
+    transcript::transcript_layout,
+};
+
+#[derive(Debug)]
+pub enum AggregateError {
+    /// Proof `proof` couldn't be read, e.g, because it is truncated. A proof
+    /// that reads fine but is wrong is only caught by
+    /// [`Accumulator::check()`].
+    Invalid { proof: usize, error: Error },
+}
+
+impl fmt::Display for AggregateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregateError::Invalid { proof, error } => {
+                write!(f, "failed to accumulate proof {}: {}", proof, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AggregateError {}
+
+/// A halo2 transcript reader, by the type of the transcript it reads, so that
+/// [`verify_proof_shallow()`] parses the points of a proof the way the reader
+/// does.
+pub trait TranscriptReadType {
+    const TRANSCRIPT_TYPE: TranscriptType;
+}
+
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C>> TranscriptReadType for Blake2bRead<R, C, E> {
+    const TRANSCRIPT_TYPE: TranscriptType = TranscriptType::Blake2b;
+}
+
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C>> TranscriptReadType for PoseidonRead<R, C, E> {
+    const TRANSCRIPT_TYPE: TranscriptType = TranscriptType::Poseidon;
+}
+
+/// Why a proof failed [`verify_proof_shallow()`].
+#[derive(Debug)]
+pub enum ShallowError {
+    /// The proof isn't made of the points and scalars a proof for the key
+    /// has, e.g, it is truncated or has a point that isn't on the curve.
+    Malformed(ParseError),
+    /// The instances don't have the shape of the key, or halo2's verifier
+    /// couldn't replay the transcript.
+    Rejected(Error),
+}
+
+impl fmt::Display for ShallowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShallowError::Malformed(e) => write!(f, "malformed proof: {}", e),
+            ShallowError::Rejected(e) => write!(f, "proof rejected: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ShallowError {}
+
+/// What [`verify_proof_shallow()`] checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShallowReport {
+    /// The points that were found on the curve.
+    pub num_points: usize,
+    /// The scalars that were found below the modulus.
+    pub num_scalars: usize,
+}
+
+/// The KZG openings of a batch of SHPlonk proofs, folded into one pairing
+/// check with random coefficients.
+#[derive(Clone, Debug)]
+pub struct Accumulator<'params> {
+    params: &'params ParamsKZG<Bn256>,
+    strategy: AccumulatorStrategy<'params, Bn256>,
+    num_proofs: usize,
+}
+
+impl<'params> Accumulator<'params> {
+    /// An accumulator without proofs, to [`Accumulator::add()`] them one by
+    /// one.
+    pub fn new(params: &'params ParamsKZG<Bn256>) -> Self {
+        Accumulator {
+            params,
+            strategy: AccumulatorStrategy::new(params),
+            num_proofs: 0,
+        }
+    }
+
+    pub fn num_proofs(&self) -> usize {
+        self.num_proofs
+    }
+
+    /// Runs [`verify_proof_shallow()`] on `proof` and, if it passes, folds
+    /// its pairing check into the batch, e.g, to screen proofs as they come
+    /// in and spend the pairings on those that pass only once. A proof that
+    /// fails isn't added, and the batch stays as it was.
+    pub fn add<
+        'a,
+        T: TranscriptReadBuffer<&'a [u8], G1Affine, Challenge255<G1Affine>> + TranscriptReadType,
+    >(
+        &mut self,
+        vk: &VerifyingKey<G1Affine>,
+        instances: &[&[&[Fr]]],
+        proof: &'a [u8],
+    ) -> Result<ShallowReport, ShallowError> {
+        let report = check_shape::<T>(self.params, vk, instances, proof)?;
+        // halo2 takes the accumulated openings by value and drops them if it
+        // fails, so a copy of them is folded into.
+        self.strategy = fold::<T>(self.params, self.strategy.clone(), vk, instances, proof)
+            .map_err(ShallowError::Rejected)?;
+        self.num_proofs += 1;
+        Ok(report)
+    }
+
+    /// Runs the single pairing check. It passes only if every accumulated
+    /// proof is valid, except with negligible probability.
+    pub fn check(self) -> bool {
+        VerificationStrategy::<_, VerifierSHPLONK<'params, Bn256>>::finalize(self.strategy)
+    }
+}
+
+/// Runs the verifier on every `(vk, instances, proof)` of `proofs`, but
+/// instead of a pairing check per proof, accumulates them so that
+/// [`Accumulator::check()`] checks the whole batch with one. `T` is the
+/// transcript the proofs were created with, e.g, `Blake2bRead<_, _, _>`.
+///
+/// The accumulator is built from halo2's `AccumulatorStrategy`, which keeps
+/// the two sides of the pairing as unevaluated MSMs and doesn't expose them,
+/// so it is checked against the `s_g2` of `params` and can't be serialized.
+pub fn accumulate<
+    'params,
+    'a,
+    T: TranscriptReadBuffer<&'a [u8], G1Affine, Challenge255<G1Affine>>,
+>(
+    params: &'params ParamsKZG<Bn256>,
+    proofs: &[(&VerifyingKey<G1Affine>, &[&[Fr]], &'a [u8])],
+) -> Result<Accumulator<'params>, AggregateError> {
+    let mut strategy = AccumulatorStrategy::new(params);
+    for (i, (vk, instances, proof)) in proofs.iter().enumerate() {
+        strategy = fold::<T>(params, strategy, vk, &[*instances], proof)
+            .map_err(|error| AggregateError::Invalid { proof: i, error })?;
+    }
+    Ok(Accumulator {
+        params,
+        strategy,
+        num_proofs: proofs.len(),
+    })
+}
+
+/// Checks what of a SHPlonk proof can be checked without a pairing, as a
+/// cheap filter before verifying it, e.g, with [`Accumulator::add()`]:
+///
+/// - `proof` has exactly the points and scalars that a proof of
+///   `instances.len()` circuits over `vk` writes, see
+///   [`crate::transcript::transcript_layout()`], and nothing after them.
+/// - Every point is encoded canonically for the transcript `T` reads and is
+///   on the curve, and every scalar is below the modulus.
+/// - `instances` has every instance column of `vk` for every circuit, and no
+///   column has more values than usable rows.
+/// - halo2's verifier replays the transcript: it derives every challenge,
+///   the value the quotient has to open to from the claimed evaluations of
+///   the gates, lookups and permutation, and the MSMs of the multi-open
+///   argument.
+///
+/// It doesn't check that the proof is valid, not even in part. halo2 checks
+/// the gates, lookups and permutation only through the opening of the
+/// quotient, which is part of the pairing check, so there is no identity
+/// without a pairing that a wrong witness, instance or evaluation fails. A
+/// well-formed proof with any value changed passes.
+pub fn verify_proof_shallow<
+    'a,
+    T: TranscriptReadBuffer<&'a [u8], G1Affine, Challenge255<G1Affine>> + TranscriptReadType,
+>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[&[&[Fr]]],
+    proof: &'a [u8],
+) -> Result<ShallowReport, ShallowError> {
+    let report = check_shape::<T>(params, vk, instances, proof)?;
+    fold::<T>(
+        params,
+        AccumulatorStrategy::new(params),
+        vk,
+        instances,
+        proof,
+    )
+    .map_err(ShallowError::Rejected)?;
+    Ok(report)
+}
+
+// Everything `verify_proof_shallow()` checks before it runs halo2's verifier,
+// which drops the accumulated openings when it fails.
+fn check_shape<T: TranscriptReadType>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[&[&[Fr]]],
+    proof: &[u8],
+) -> Result<ShallowReport, ShallowError> {
+    let cs = vk.cs();
+    let usable_rows = usable_rows(params.k(), cs.blinding_factors());
+    for columns in instances {
+        if columns.len() != cs.num_instance_columns() {
+            return Err(ShallowError::Rejected(Error::InvalidInstances));
+        }
+        if columns.iter().any(|column| column.len() > usable_rows) {
+            return Err(ShallowError::Rejected(Error::InstanceTooLarge));
+        }
+    }
+
+    let layout = transcript_layout(vk, instances.len(), MultiOpenScheme::SHPlonk);
+    let parsed = parse(
+        proof,
+        &layout,
+        PointEncoding::for_transcript(&T::TRANSCRIPT_TYPE),
+    )
+    .map_err(ShallowError::Malformed)?;
+    let num_points = parsed
+        .values()
+        .iter()
+        .filter(|(_, value)| matches!(value, ProofValue::Point(_)))
+        .count();
+    Ok(ShallowReport {
+        num_points,
+        num_scalars: parsed.values().len() - num_points,
+    })
+}
+
+fn fold<'params, 'a, T: TranscriptReadBuffer<&'a [u8], G1Affine, Challenge255<G1Affine>>>(
+    params: &'params ParamsKZG<Bn256>,
+    strategy: AccumulatorStrategy<'params, Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[&[&[Fr]]],
+    proof: &'a [u8],
+) -> Result<AccumulatorStrategy<'params, Bn256>, Error> {
+    let mut transcript = T::init(proof);
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'params, Bn256>, _, _, _>(
+        params,
+        vk,
+        strategy,
+        instances,
+        &mut transcript,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, ProvingKey},
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::{Blake2bRead, TranscriptWriterBuffer},
+    };
+    use halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        group::GroupEncoding,
+    };
+    use rand_core::SeedableRng;
+
+    use super::{accumulate, verify_proof_shallow, Accumulator, AggregateError, ShallowError};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{MultiOpenScheme, TranscriptType, SEED},
+        proof::ParseError,
+        prover::create_proof,
+        transcript::transcript_layout,
+        xor_shift_rng::XORShiftRng,
+    };
+
+    const K: u32 = 4;
+
+    // Returns the params and key of `SimpleCircuit`, and a public input and a
+    // proof of it for every `(a, b)` of `inputs`.
+    fn prove(
+        inputs: &[(u64, u64)],
+    ) -> (
+        ParamsKZG<Bn256>,
+        ProvingKey<G1Affine>,
+        Vec<Vec<Fr>>,
+        Vec<Vec<u8>>,
+    ) {
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(K, s);
+        let empty_circuit = SimpleCircuit {
+            constant,
+            a: Value::unknown(),
+            b: Value::unknown(),
+        };
+        let pk = keygen_pk2(&params, &empty_circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let public_inputs = inputs
+            .iter()
+            .map(|(a, b)| vec![constant * Fr::from(*a).square() * Fr::from(*b).square()])
+            .collect::<Vec<_>>();
+        let proofs = inputs
+            .iter()
+            .zip(&public_inputs)
+            .map(|((a, b), public_inputs)| {
+                let circuit = SimpleCircuit {
+                    constant,
+                    a: Value::known(Fr::from(*a)),
+                    b: Value::known(Fr::from(*b)),
+                };
+                let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+                let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                    TranscriptType::Blake2b as u8,
+                    K,
+                    &s,
+                );
+                let mut transcript = TachyonBlake2bWrite::init(vec![]);
+                create_proof(
+                    &mut prover,
+                    &mut tachyon_pk,
+                    &[circuit],
+                    &[&[&public_inputs[..]]],
+                    XORShiftRng::from_seed(SEED),
+                    &mut transcript,
+                )
+                .expect("proof generation should not fail");
+                let mut proof = transcript.finalize();
+                proof.extend_from_slice(&prover.get_proof());
+                proof
+            })
+            .collect::<Vec<_>>();
+        (params, pk, public_inputs, proofs)
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_accumulate() {
+        let (params, pk, public_inputs, proofs) = prove(&[(2, 3), (4, 5), (6, 7)]);
+        let proofs = proofs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let check = |public_inputs: &[Vec<Fr>], proofs: &[&[u8]]| {
+            let instances = public_inputs
+                .iter()
+                .map(|public_inputs| vec![&public_inputs[..]])
+                .collect::<Vec<_>>();
+            let batch = instances
+                .iter()
+                .zip(proofs)
+                .map(|(instances, proof)| (pk.get_vk(), &instances[..], *proof))
+                .collect::<Vec<_>>();
+            accumulate::<Blake2bRead<_, _, _>>(&params, &batch).map(|acc| acc.check())
+        };
+
+        assert!(check(&public_inputs, &proofs).unwrap());
+
+        // The second proof, checked against a wrong public input, reads fine
+        // but fails the pairing check of the whole batch.
+        let mut wrong_inputs = public_inputs.clone();
+        wrong_inputs[1][0] += Fr::one();
+        assert!(!check(&wrong_inputs, &proofs).unwrap());
+
+        // A truncated proof can't be read at all.
+        let mut truncated = proofs.clone();
+        truncated[2] = &proofs[2][..32];
+        assert!(matches!(
+            check(&public_inputs, &truncated),
+            Err(AggregateError::Invalid { proof: 2, .. })
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_verify_proof_shallow() {
+        let (params, pk, public_inputs, proofs) = prove(&[(2, 3), (4, 5)]);
+        let vk = pk.get_vk();
+        let columns = public_inputs
+            .iter()
+            .map(|public_inputs| vec![&public_inputs[..]])
+            .collect::<Vec<_>>();
+        let instances = |i: usize| [&columns[i][..]];
+        let shallow = |instances: &[&[&[Fr]]], proof: &[u8]| {
+            verify_proof_shallow::<Blake2bRead<_, _, _>>(&params, vk, instances, proof)
+        };
+        let layout = transcript_layout(vk, 1, MultiOpenScheme::SHPlonk);
+
+        // A valid proof passes both.
+        let report = shallow(&instances(0), &proofs[0]).unwrap();
+        assert_eq!(report.num_points + report.num_scalars, layout.len());
+        let mut batch = Accumulator::new(&params);
+        batch
+            .add::<Blake2bRead<_, _, _>>(vk, &instances(0), &proofs[0])
+            .unwrap();
+        assert!(batch.clone().check());
+
+        // A point that isn't on the curve is caught shallowly, and the batch
+        // stays as it was. Compressed points and scalars are both 32 bytes.
+        let index = layout
+            .iter()
+            .position(|item| item.component.is_point())
+            .unwrap();
+        let (offset, item) = (index * 32, layout[index]);
+        let mut bad_point = proofs[1].clone();
+        bad_point[offset..offset + 32].copy_from_slice(&[0xff; 32]);
+        let expected = ParseError::NonCanonicalPoint { offset, item };
+        assert!(matches!(
+            shallow(&instances(1), &bad_point),
+            Err(ShallowError::Malformed(e)) if e == expected
+        ));
+        assert!(matches!(
+            batch.add::<Blake2bRead<_, _, _>>(vk, &instances(1), &bad_point),
+            Err(ShallowError::Malformed(e)) if e == expected
+        ));
+        assert_eq!(batch.num_proofs(), 1);
+        assert!(batch.clone().check());
+
+        // A changed evaluation is still a well-formed proof, so it passes
+        // shallowly but fails the pairing check.
+        let index = layout
+            .iter()
+            .position(|item| !item.component.is_point())
+            .unwrap();
+        let offset = index * 32;
+        let mut bad_eval = proofs[1].clone();
+        bad_eval[offset] ^= 1;
+        shallow(&instances(1), &bad_eval).unwrap();
+        batch
+            .add::<Blake2bRead<_, _, _>>(vk, &instances(1), &bad_eval)
+            .unwrap();
+        assert_eq!(batch.num_proofs(), 2);
+        assert!(!batch.check());
+
+        let mut batch = Accumulator::new(&params);
+        for (i, proof) in proofs.iter().enumerate() {
+            batch
+                .add::<Blake2bRead<_, _, _>>(vk, &instances(i), proof)
+                .unwrap();
+        }
+        assert!(batch.check());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_accumulator_keeps_batch_after_rejected_proof() {
+        let (params, pk, public_inputs, proofs) = prove(&[(2, 3), (4, 5)]);
+        let vk = pk.get_vk();
+        let columns = public_inputs
+            .iter()
+            .map(|public_inputs| vec![&public_inputs[..]])
+            .collect::<Vec<_>>();
+        let instances = |i: usize| [&columns[i][..]];
+        let layout = transcript_layout(vk, 1, MultiOpenScheme::SHPlonk);
+
+        // The identity is a point on the curve, so it passes the shallow
+        // checks, but halo2's verifier refuses to read it.
+        let index = layout
+            .iter()
+            .position(|item| item.component.is_point())
+            .unwrap();
+        let offset = index * 32;
+        let mut identity = proofs[1].clone();
+        identity[offset..offset + 32].copy_from_slice(G1Affine::default().to_bytes().as_ref());
+
+        let mut batch = Accumulator::new(&params);
+        batch
+            .add::<Blake2bRead<_, _, _>>(vk, &instances(0), &proofs[0])
+            .unwrap();
+        assert!(matches!(
+            batch.add::<Blake2bRead<_, _, _>>(vk, &instances(1), &identity),
+            Err(ShallowError::Rejected(_))
+        ));
+        batch
+            .add::<Blake2bRead<_, _, _>>(vk, &instances(1), &proofs[1])
+            .unwrap();
+        assert_eq!(batch.num_proofs(), 2);
+        assert!(batch.check());
+    }
+}
@@ -0,0 +1,866 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use ff::Field;
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{
+        keygen_vk, Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem,
+        Error, Expression, Fixed, FloorPlanner, Instance, Selector, VerifyingKey,
+    },
+    poly::commitment::Params,
+};
+use halo2curves::{
+    bn256::{Fr, G1Affine},
+    CurveAffine,
+};
+
+use crate::{
+    annotation::ColumnAnnotations, bn254::ProvingKey as TachyonProvingKey,
+    circuit_id::encode_expressions,
+};
+
+/// Returns the number of rows a circuit with `2ᵏ` rows can use, i.e, every
+/// row but the blinding rows and the one in front of them.
+pub(crate) fn usable_rows(k: u32, blinding_factors: usize) -> usize {
+    (1usize << k) - (blinding_factors + 1)
+}
+
+/// Returns the largest number of rows a lookup table can have in a circuit
+/// with `2ᵏ` rows that is keyed by `pk`. A table may fill every usable row,
+/// but not the rows reserved for blinding.
+pub fn max_table_rows<C: CurveAffine>(k: u32, pk: &TachyonProvingKey<C>) -> usize {
+    usable_rows(k, pk.blinding_factors() as usize)
+}
+
+/// Records the highest row assigned in every fixed column and the column
+/// annotations. Unlike keygen, it doesn't stop at the usable rows, so that
+/// overflowing tables can be measured.
+struct FixedRows {
+    rows: BTreeMap<usize, usize>,
+    annotations: ColumnAnnotations,
+}
+
+impl<F: Field> Assignment<F> for FixedRows {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn annotate_column<A, AR>(&mut self, annotation: A, column: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.annotations.insert(column, annotation().into());
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let rows = self.rows.entry(column.index()).or_default();
+        *rows = (*rows).max(row + 1);
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        // This pads the rest of a table, which doesn't add to its size.
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// Synthesizes `circuit` and returns the number of rows assigned to each
+/// lookup table column, keyed by the index of its fixed column. Only tables
+/// that appear as plain column queries in a lookup are found.
+pub fn table_rows<F: Field, ConcreteCircuit: Circuit<F>>(
+    circuit: &ConcreteCircuit,
+) -> Result<BTreeMap<usize, usize>, Error> {
+    let (cs, recorder) = record_fixed(circuit)?;
+    let mut tables = BTreeMap::new();
+    for lookup in cs.lookups() {
+        for expression in lookup.table_expressions() {
+            if let Expression::Fixed(query) = expression {
+                let column = query.column_index();
+                tables.insert(column, recorder.rows.get(&column).copied().unwrap_or(0));
+            }
+        }
+    }
+    Ok(tables)
+}
+
+/// Synthesizes `circuit` and returns the names it gives its columns with
+/// `annotate_column()`, e.g, to be stored with
+/// [`crate::annotation::annotations_to_metadata()`].
+pub fn column_annotations<F: Field, ConcreteCircuit: Circuit<F>>(
+    circuit: &ConcreteCircuit,
+) -> Result<ColumnAnnotations, Error> {
+    Ok(record_fixed(circuit)?.1.annotations)
+}
+
+fn record_fixed<F: Field, ConcreteCircuit: Circuit<F>>(
+    circuit: &ConcreteCircuit,
+) -> Result<(ConstraintSystem<F>, FixedRows), Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = ConcreteCircuit::configure(&mut cs);
+
+    let mut recorder = FixedRows {
+        rows: BTreeMap::new(),
+        annotations: ColumnAnnotations::new(),
+    };
+    ConcreteCircuit::FloorPlanner::synthesize(
+        &mut recorder,
+        circuit,
+        config,
+        cs.constants().clone(),
+    )?;
+    Ok((cs, recorder))
+}
+
+/// The structure of a circuit, along with, if it is keyed, the commitments
+/// to its fixed values and copy constraints, to tell whether a new version of
+/// a circuit can keep the keys of the old one. See [`compatibility()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintSystemSummary<C: CurveAffine> {
+    num_fixed_columns: usize,
+    num_advice_columns: usize,
+    num_instance_columns: usize,
+    num_selectors: usize,
+    num_challenges: usize,
+    /// The phases of the advice columns followed by those of the challenges.
+    phases: Vec<u8>,
+    /// The polynomials of every gate, encoded as for the circuit id.
+    gates: Vec<Vec<u8>>,
+    /// The input and table expressions of every lookup, encoded as for the
+    /// circuit id.
+    lookups: Vec<Vec<u8>>,
+    lookup_arities: Vec<usize>,
+    max_degree: usize,
+    rotations: BTreeSet<i32>,
+    permutation_columns: Vec<Column<Any>>,
+    constants: Vec<Column<Fixed>>,
+    commitments: Option<KeyCommitments<C>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct KeyCommitments<C: CurveAffine> {
+    fixed: Vec<C>,
+    permutation: Vec<C>,
+}
+
+/// How many advice columns and challenges a phase has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PhaseUsage {
+    pub phase: u8,
+    pub advice_columns: usize,
+    pub challenges: usize,
+}
+
+impl<C: CurveAffine> ConstraintSystemSummary<C> {
+    fn from_cs(cs: &ConstraintSystem<C::Scalar>, commitments: Option<KeyCommitments<C>>) -> Self {
+        let encode = |expressions: &[&[Expression<C::Scalar>]]| {
+            let mut out = vec![];
+            for expressions in expressions {
+                encode_expressions(expressions, &mut out);
+            }
+            out
+        };
+        let rotations = cs
+            .advice_queries()
+            .iter()
+            .map(|(_, rotation)| rotation.0)
+            .chain(cs.fixed_queries().iter().map(|(_, rotation)| rotation.0))
+            .chain(cs.instance_queries().iter().map(|(_, rotation)| rotation.0))
+            .collect();
+        ConstraintSystemSummary {
+            num_fixed_columns: cs.num_fixed_columns(),
+            num_advice_columns: cs.num_advice_columns(),
+            num_instance_columns: cs.num_instance_columns(),
+            num_selectors: cs.num_selectors(),
+            num_challenges: cs.num_challenges(),
+            phases: cs
+                .advice_column_phase
+                .iter()
+                .chain(cs.challenge_phase.iter())
+                .map(|phase| phase.0)
+                .collect(),
+            gates: cs
+                .gates()
+                .iter()
+                .map(|gate| encode(&[gate.polynomials()]))
+                .collect(),
+            lookups: cs
+                .lookups()
+                .iter()
+                .map(|lookup| encode(&[lookup.input_expressions(), lookup.table_expressions()]))
+                .collect(),
+            lookup_arities: cs
+                .lookups()
+                .iter()
+                .map(|lookup| lookup.input_expressions().len())
+                .collect(),
+            max_degree: cs.degree(),
+            rotations,
+            permutation_columns: cs.permutation().get_columns(),
+            constants: cs.constants().clone(),
+            commitments,
+        }
+    }
+
+    pub fn from_vk(vk: &VerifyingKey<C>) -> Self {
+        Self::from_cs(
+            vk.cs(),
+            Some(KeyCommitments {
+                fixed: vk.fixed_commitments().clone(),
+                permutation: vk.permutation().commitments().clone(),
+            }),
+        )
+    }
+
+    /// Summarizes the verifying key `circuit` would get with `params`. Keygen
+    /// folds selectors into fixed columns depending on the rows they are
+    /// enabled in, so `configure()` alone doesn't give the constraint system
+    /// of the key, and the circuit is synthesized without witnesses as well.
+    pub fn from_circuit<'params, P, ConcreteCircuit>(
+        params: &P,
+        circuit: &ConcreteCircuit,
+    ) -> Result<Self, Error>
+    where
+        P: Params<'params, C>,
+        ConcreteCircuit: Circuit<C::Scalar>,
+    {
+        Ok(Self::from_vk(&keygen_vk(params, circuit)?))
+    }
+
+    /// Summarizes what `configure()` of `ConcreteCircuit` declares, without
+    /// params or keys. Selectors aren't folded into fixed columns, so they are
+    /// counted on their own, and the summary can't be compared to one of a
+    /// key, only to another unkeyed one.
+    pub fn from_configure<ConcreteCircuit: Circuit<C::Scalar>>() -> Self {
+        let mut cs = ConstraintSystem::default();
+        ConcreteCircuit::configure(&mut cs);
+        Self::from_cs(&cs, None)
+    }
+
+    pub fn num_fixed_columns(&self) -> usize {
+        self.num_fixed_columns
+    }
+
+    pub fn num_advice_columns(&self) -> usize {
+        self.num_advice_columns
+    }
+
+    pub fn num_instance_columns(&self) -> usize {
+        self.num_instance_columns
+    }
+
+    /// In a keyed summary, the selectors are counted in the fixed columns as
+    /// well.
+    pub fn num_selectors(&self) -> usize {
+        self.num_selectors
+    }
+
+    pub fn num_challenges(&self) -> usize {
+        self.num_challenges
+    }
+
+    pub fn num_gates(&self) -> usize {
+        self.gates.len()
+    }
+
+    pub fn num_lookups(&self) -> usize {
+        self.lookups.len()
+    }
+
+    /// The number of input expressions of every lookup.
+    pub fn lookup_arities(&self) -> &[usize] {
+        &self.lookup_arities
+    }
+
+    /// The degree of the constraint system, which sets the size of the
+    /// extended domain the quotient is computed on.
+    pub fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+
+    /// Every rotation a column is queried at.
+    pub fn rotations(&self) -> &BTreeSet<i32> {
+        &self.rotations
+    }
+
+    /// The advice columns and challenges of every phase up to the last one
+    /// that is used.
+    pub fn phase_usage(&self) -> Vec<PhaseUsage> {
+        let (advice, challenges) = self.phases.split_at(self.num_advice_columns);
+        let num_phases = self
+            .phases
+            .iter()
+            .max()
+            .map_or(0, |phase| *phase as usize + 1);
+        (0..num_phases)
+            .map(|phase| {
+                let count = |phases: &[u8]| phases.iter().filter(|p| **p as usize == phase).count();
+                PhaseUsage {
+                    phase: phase as u8,
+                    advice_columns: count(advice),
+                    challenges: count(challenges),
+                }
+            })
+            .collect()
+    }
+
+    pub fn is_keyed(&self) -> bool {
+        self.commitments.is_some()
+    }
+
+    /// Writes the summary, without the gates and the commitments, as a JSON
+    /// object.
+    pub fn to_json(&self) -> String {
+        let phases = self
+            .phase_usage()
+            .into_iter()
+            .map(|usage| {
+                format!(
+                    "{{\"phase\":{},\"advice_columns\":{},\"challenges\":{}}}",
+                    usage.phase, usage.advice_columns, usage.challenges
+                )
+            })
+            .collect::<Vec<_>>();
+        let lookup_arities = self
+            .lookup_arities
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>();
+        let rotations = self
+            .rotations
+            .iter()
+            .map(i32::to_string)
+            .collect::<Vec<_>>();
+        format!(
+            "{{\"num_fixed_columns\":{},\"num_advice_columns\":{},\"num_instance_columns\":{},\"num_selectors\":{},\"num_challenges\":{},\"num_gates\":{},\"num_lookups\":{},\"lookup_arities\":[{}],\"max_degree\":{},\"rotations\":[{}],\"phases\":[{}],\"keyed\":{}}}",
+            self.num_fixed_columns,
+            self.num_advice_columns,
+            self.num_instance_columns,
+            self.num_selectors,
+            self.num_challenges,
+            self.gates.len(),
+            self.lookups.len(),
+            lookup_arities.join(","),
+            self.max_degree,
+            rotations.join(","),
+            phases.join(","),
+            self.is_keyed()
+        )
+    }
+}
+
+/// Summarizes what `configure()` of `ConcreteCircuit` declares, see
+/// [`ConstraintSystemSummary::from_configure()`].
+pub fn summarize<ConcreteCircuit: Circuit<Fr>>() -> ConstraintSystemSummary<G1Affine> {
+    ConstraintSystemSummary::from_configure::<ConcreteCircuit>()
+}
+
+impl<C: CurveAffine> fmt::Display for ConstraintSystemSummary<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |values: Vec<String>| {
+            if values.is_empty() {
+                "-".to_string()
+            } else {
+                values.join(", ")
+            }
+        };
+        let rows = [
+            ("fixed columns", self.num_fixed_columns.to_string()),
+            ("advice columns", self.num_advice_columns.to_string()),
+            ("instance columns", self.num_instance_columns.to_string()),
+            ("selectors", self.num_selectors.to_string()),
+            ("challenges", self.num_challenges.to_string()),
+            ("gates", self.gates.len().to_string()),
+            ("lookups", self.lookups.len().to_string()),
+            (
+                "lookup arities",
+                list(self.lookup_arities.iter().map(usize::to_string).collect()),
+            ),
+            ("max degree", self.max_degree.to_string()),
+            (
+                "rotations",
+                list(self.rotations.iter().map(i32::to_string).collect()),
+            ),
+        ];
+        for (name, value) in rows {
+            writeln!(f, "{:<18}{}", name, value)?;
+        }
+        for usage in self.phase_usage() {
+            writeln!(
+                f,
+                "{:<18}{} advice, {} challenges",
+                format!("phase {}", usage.phase),
+                usage.advice_columns,
+                usage.challenges
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Difference {
+    /// The number of `kind` columns, which is "fixed", "advice", "instance"
+    /// or "selector", changed. Since selectors are folded into fixed columns, a
+    /// change of the selectors may show up as a change of the fixed columns.
+    ColumnCount {
+        kind: &'static str,
+        old: usize,
+        new: usize,
+    },
+    ChallengeCount {
+        old: usize,
+        new: usize,
+    },
+    /// As many columns and challenges, but in other phases.
+    Phases,
+    AddedGate(usize),
+    RemovedGate(usize),
+    ChangedGate(usize),
+    AddedLookup(usize),
+    RemovedLookup(usize),
+    ChangedLookup(usize),
+    /// Equality is enabled on other columns.
+    PermutationColumns,
+    /// Constants are loaded into other fixed columns.
+    Constants,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difference::ColumnCount { kind, old, new } => {
+                write!(f, "{} {} columns instead of {}", new, kind, old)
+            }
+            Difference::ChallengeCount { old, new } => {
+                write!(f, "{} challenges instead of {}", new, old)
+            }
+            Difference::Phases => write!(f, "columns or challenges in other phases"),
+            Difference::AddedGate(i) => write!(f, "added gate {}", i),
+            Difference::RemovedGate(i) => write!(f, "removed gate {}", i),
+            Difference::ChangedGate(i) => write!(f, "changed gate {}", i),
+            Difference::AddedLookup(i) => write!(f, "added lookup {}", i),
+            Difference::RemovedLookup(i) => write!(f, "removed lookup {}", i),
+            Difference::ChangedLookup(i) => write!(f, "changed lookup {}", i),
+            Difference::PermutationColumns => write!(f, "equality enabled on other columns"),
+            Difference::Constants => write!(f, "constants in other columns"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Compatibility {
+    /// The new circuit gets the same verifying key.
+    Identical,
+    /// The constraint system and so the circuit id are the same, but fixed
+    /// values or copy constraints differ. The keys have to be regenerated, but
+    /// keep their shape.
+    FixedOnly,
+    /// The constraint system changed, so the keys have to be regenerated and
+    /// verifiers of the old circuit don't accept the new proofs.
+    Breaking(Vec<Difference>),
+}
+
+/// Pushes a difference for every index at which `old` and `new` differ.
+fn diff_items(
+    old: &[Vec<u8>],
+    new: &[Vec<u8>],
+    added: fn(usize) -> Difference,
+    removed: fn(usize) -> Difference,
+    changed: fn(usize) -> Difference,
+    differences: &mut Vec<Difference>,
+) {
+    for i in 0..old.len().max(new.len()) {
+        match (old.get(i), new.get(i)) {
+            (Some(old), Some(new)) if old != new => differences.push(changed(i)),
+            (Some(_), None) => differences.push(removed(i)),
+            (None, Some(_)) => differences.push(added(i)),
+            _ => {}
+        }
+    }
+}
+
+/// Classifies how the circuit summarized by `new` differs from the one `old`
+/// was generated for. Gates and lookups are compared by index, so inserting
+/// one in front of others reports those as changed.
+pub fn compatibility<C: CurveAffine>(
+    old: &VerifyingKey<C>,
+    new: &ConstraintSystemSummary<C>,
+) -> Compatibility {
+    compare_summaries(&ConstraintSystemSummary::from_vk(old), new)
+}
+
+/// Like [`compatibility()`] for two summaries. If either isn't keyed, the
+/// fixed values can't be compared, so an unchanged constraint system is
+/// [`Compatibility::FixedOnly`].
+pub fn compare_summaries<C: CurveAffine>(
+    old: &ConstraintSystemSummary<C>,
+    new: &ConstraintSystemSummary<C>,
+) -> Compatibility {
+    let mut differences = vec![];
+    for (kind, old, new) in [
+        ("fixed", old.num_fixed_columns, new.num_fixed_columns),
+        ("advice", old.num_advice_columns, new.num_advice_columns),
+        (
+            "instance",
+            old.num_instance_columns,
+            new.num_instance_columns,
+        ),
+        ("selector", old.num_selectors, new.num_selectors),
+    ] {
+        if old != new {
+            differences.push(Difference::ColumnCount { kind, old, new });
+        }
+    }
+    if old.num_challenges != new.num_challenges {
+        differences.push(Difference::ChallengeCount {
+            old: old.num_challenges,
+            new: new.num_challenges,
+        });
+    }
+    if old.phases.len() == new.phases.len() && old.phases != new.phases {
+        differences.push(Difference::Phases);
+    }
+    diff_items(
+        &old.gates,
+        &new.gates,
+        Difference::AddedGate,
+        Difference::RemovedGate,
+        Difference::ChangedGate,
+        &mut differences,
+    );
+    diff_items(
+        &old.lookups,
+        &new.lookups,
+        Difference::AddedLookup,
+        Difference::RemovedLookup,
+        Difference::ChangedLookup,
+        &mut differences,
+    );
+    if old.permutation_columns != new.permutation_columns {
+        differences.push(Difference::PermutationColumns);
+    }
+    if old.constants != new.constants {
+        differences.push(Difference::Constants);
+    }
+
+    if !differences.is_empty() {
+        Compatibility::Breaking(differences)
+    } else if old.commitments.is_none() || old.commitments != new.commitments {
+        Compatibility::FixedOnly
+    } else {
+        Compatibility::Identical
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{keygen_vk, Advice, Circuit, Column, ConstraintSystem, Error, TableColumn},
+        poly::{kzg::commitment::ParamsKZG, Rotation},
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+    use super::{
+        compare_summaries, compatibility, max_table_rows, summarize, table_rows, usable_rows,
+        Compatibility, ConstraintSystemSummary, Difference, PhaseUsage,
+    };
+    use crate::{
+        bn254::ProvingKey as TachyonProvingKey,
+        circuits::simple_circuit::SimpleCircuit,
+        keygen::{keygen_pk2, KeygenError},
+    };
+
+    #[derive(Clone)]
+    struct TableCircuit {
+        rows: usize,
+    }
+
+    impl Circuit<Fr> for TableCircuit {
+        type Config = (Column<Advice>, TableColumn);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let advice = meta.advice_column();
+            let table = meta.lookup_table_column();
+            meta.lookup("table", |meta| {
+                vec![(meta.query_advice(advice, Rotation::cur()), table)]
+            });
+            (advice, table)
+        }
+
+        fn synthesize(
+            &self,
+            (_, table): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "table",
+                |mut t| {
+                    for row in 0..self.rows {
+                        t.assign_cell(
+                            || format!("row {}", row),
+                            table,
+                            row,
+                            || Value::known(Fr::from_u128(row as u128)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_table_rows() {
+        let k = 4;
+        let mut cs = ConstraintSystem::<Fr>::default();
+        TableCircuit::configure(&mut cs);
+        let usable = usable_rows(k, cs.blinding_factors());
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::from(2));
+
+        let fits = TableCircuit { rows: usable };
+        assert_eq!(
+            table_rows(&fits).unwrap().into_values().collect::<Vec<_>>(),
+            vec![usable]
+        );
+        let pk = keygen_pk2(&params, &fits).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let tachyon_pk = TachyonProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+        assert_eq!(max_table_rows(k, &tachyon_pk), usable);
+
+        match keygen_pk2(&params, &TableCircuit { rows: usable + 1 }) {
+            Err(KeygenError::TableTooLarge {
+                table_rows,
+                usable_rows,
+            }) => {
+                assert_eq!(table_rows, usable + 1);
+                assert_eq!(usable_rows, usable);
+            }
+            _ => panic!("expected TableTooLarge"),
+        }
+    }
+
+    /// `SimpleCircuit` with another advice column, which an unselected gate
+    /// constrains to zero.
+    #[derive(Clone, Default)]
+    struct ExtraGateCircuit(SimpleCircuit<Fr>);
+
+    impl Circuit<Fr> for ExtraGateCircuit {
+        type Config = <SimpleCircuit<Fr> as Circuit<Fr>>::Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let config = SimpleCircuit::configure(meta);
+            let extra = meta.advice_column();
+            meta.create_gate("extra", |meta| {
+                vec![meta.query_advice(extra, Rotation::cur())]
+            });
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            self.0.synthesize(config, layouter)
+        }
+    }
+
+    #[test]
+    fn test_compatibility() {
+        let k = 4;
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::from(2));
+        let circuit = |constant: u64| SimpleCircuit {
+            constant: Fr::from(constant),
+            a: Value::unknown(),
+            b: Value::unknown(),
+        };
+        let vk = keygen_vk(&params, &circuit(7)).expect("vk should not fail");
+        let extra_vk =
+            keygen_vk(&params, &ExtraGateCircuit(circuit(7))).expect("vk should not fail");
+
+        let summary = ConstraintSystemSummary::from_circuit(&params, &circuit(7)).unwrap();
+        assert_eq!(compatibility(&vk, &summary), Compatibility::Identical);
+
+        // The constant is loaded into a fixed column.
+        let summary = ConstraintSystemSummary::from_circuit(&params, &circuit(8)).unwrap();
+        assert_eq!(compatibility(&vk, &summary), Compatibility::FixedOnly);
+
+        let summary =
+            ConstraintSystemSummary::from_circuit(&params, &ExtraGateCircuit(circuit(7))).unwrap();
+        assert_eq!(
+            compatibility(&vk, &summary),
+            Compatibility::Breaking(vec![
+                Difference::ColumnCount {
+                    kind: "advice",
+                    old: 2,
+                    new: 3,
+                },
+                Difference::AddedGate(1),
+            ])
+        );
+
+        let summary = ConstraintSystemSummary::from_circuit(&params, &circuit(7)).unwrap();
+        assert_eq!(
+            compatibility(&extra_vk, &summary),
+            Compatibility::Breaking(vec![
+                Difference::ColumnCount {
+                    kind: "advice",
+                    old: 3,
+                    new: 2,
+                },
+                Difference::RemovedGate(1),
+            ])
+        );
+    }
+    #[test]
+    fn test_summarize() {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+
+        let summary = summarize::<SimpleCircuit<Fr>>();
+        assert_eq!(summary.num_fixed_columns(), 1);
+        assert_eq!(summary.num_advice_columns(), 2);
+        assert_eq!(summary.num_instance_columns(), 1);
+        assert_eq!(summary.num_selectors(), 1);
+        assert_eq!(summary.num_challenges(), 0);
+        assert_eq!(summary.num_gates(), 1);
+        assert_eq!(summary.num_lookups(), 0);
+        assert_eq!(
+            summary.rotations().iter().copied().collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        assert_eq!(
+            summary.phase_usage(),
+            vec![PhaseUsage {
+                phase: 0,
+                advice_columns: 2,
+                challenges: 0,
+            }]
+        );
+        assert!(!summary.is_keyed());
+        let vk = keygen_vk(&params, &SimpleCircuit::<Fr>::default()).expect("vk should not fail");
+        assert_eq!(summary.max_degree(), vk.cs().degree());
+        assert_eq!(
+            summary.to_json(),
+            "{\"num_fixed_columns\":1,\"num_advice_columns\":2,\"num_instance_columns\":1,\"num_selectors\":1,\"num_challenges\":0,\"num_gates\":1,\"num_lookups\":0,\"lookup_arities\":[],\"max_degree\":3,\"rotations\":[0,1],\"phases\":[{\"phase\":0,\"advice_columns\":2,\"challenges\":0}],\"keyed\":false}"
+        );
+
+        let summary = summarize::<TableCircuit>();
+        assert_eq!(summary.num_lookups(), 1);
+        assert_eq!(summary.lookup_arities(), &[1]);
+        assert_eq!(
+            summary.rotations().iter().copied().collect::<Vec<_>>(),
+            vec![0]
+        );
+        let vk = keygen_vk(&params, &TableCircuit { rows: 1 }).expect("vk should not fail");
+        assert_eq!(summary.max_degree(), vk.cs().degree());
+
+        // Unkeyed summaries can't tell whether the fixed values changed.
+        assert_eq!(
+            compare_summaries(
+                &summarize::<SimpleCircuit<Fr>>(),
+                &summarize::<SimpleCircuit<Fr>>()
+            ),
+            Compatibility::FixedOnly
+        );
+        assert_eq!(
+            compare_summaries(
+                &summarize::<SimpleCircuit<Fr>>(),
+                &summarize::<ExtraGateCircuit>()
+            ),
+            Compatibility::Breaking(vec![
+                Difference::ColumnCount {
+                    kind: "advice",
+                    old: 2,
+                    new: 3,
+                },
+                Difference::AddedGate(1),
+            ])
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use std::{collections::HashMap, fmt};
+
+use halo2_proofs::plonk::{Any, Column};
+
+use crate::proving_key::Metadata;
+
+/// The names given to columns with `annotate_column()`.
+pub type ColumnAnnotations = HashMap<Column<Any>, String>;
+
+const METADATA_PREFIX: &str = "annotation.";
+
+fn kind_name(column: &Column<Any>) -> &'static str {
+    match column.column_type() {
+        Any::Advice(_) => "advice",
+        Any::Fixed => "fixed",
+        Any::Instance => "instance",
+    }
+}
+
+/// The proving key metadata key the annotation of `column` is stored under,
+/// e.g, `annotation.advice.17`.
+pub(crate) fn metadata_key(column: &Column<Any>) -> String {
+    format!(
+        "{}{}.{}",
+        METADATA_PREFIX,
+        kind_name(column),
+        column.index()
+    )
+}
+
+/// Adds every annotation to `metadata`, so that it is written in front of the
+/// proving key with [`crate::proving_key::write_with_metadata()`] and can be
+/// looked up with [`crate::bn254::ProvingKey::column_annotation()`].
+pub fn annotations_to_metadata(annotations: &ColumnAnnotations, metadata: &mut Metadata) {
+    for (column, annotation) in annotations {
+        metadata.insert(metadata_key(column), annotation.clone());
+    }
+}
+
+/// Returns e.g, "column 'byte_lookup_input' (advice 17)", or "advice 17" for a
+/// column that isn't annotated.
+pub fn describe_column(column: &Column<Any>, annotation: Option<&str>) -> String {
+    match annotation {
+        Some(annotation) => format!(
+            "column '{}' ({} {})",
+            annotation,
+            kind_name(column),
+            column.index()
+        ),
+        None => format!("{} {}", kind_name(column), column.index()),
+    }
+}
+
+/// A cell was assigned in a row that is reserved for blinding or beyond the
+/// end of the circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowOverflow {
+    /// The column, as returned by [`describe_column()`].
+    pub column: String,
+    pub row: usize,
+    pub usable_rows: usize,
+}
+
+impl fmt::Display for RowOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is assigned in row {}, but only {} rows are usable",
+            self.column, self.row, self.usable_rows
+        )
+    }
+}
+
+impl std::error::Error for RowOverflow {}
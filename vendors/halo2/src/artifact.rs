@@ -0,0 +1,267 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, BufReader, Cursor, Read},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// Where large artifacts, e.g, proving keys and params, are kept, so that
+/// they can be read from object storage instead of a local disk. Implement it
+/// over the client of a store, e.g, S3 or GCS, to load from there.
+///
+/// Keys are `/`-separated paths relative to the root of the store, e.g,
+/// `"keys/transfer.pk"`.
+pub trait ArtifactStore: Send + Sync {
+    /// Streams the artifact `key`. Fails with [`io::ErrorKind::NotFound`] if
+    /// there is none.
+    fn get(&self, key: &str) -> io::Result<Box<dyn Read + Send + '_>>;
+
+    /// Stores `data` as `key`, replacing what was stored before. A reader of
+    /// `key` sees either the old or the new artifact, never part of one.
+    fn put(&self, key: &str, data: &[u8]) -> io::Result<()>;
+
+    fn exists(&self, key: &str) -> io::Result<bool>;
+}
+
+/// Keeps every artifact in its own file under a directory.
+#[derive(Debug)]
+pub struct FsArtifactStore {
+    root: PathBuf,
+}
+
+impl FsArtifactStore {
+    pub fn new(root: impl Into<PathBuf>) -> io::Result<FsArtifactStore> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        Ok(FsArtifactStore { root })
+    }
+
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        // A key can't leave the root, e.g, with `..` or an absolute path.
+        let relative = Path::new(key);
+        if key.is_empty()
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid artifact key: {:?}", key),
+            ));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+impl ArtifactStore for FsArtifactStore {
+    fn get(&self, key: &str) -> io::Result<Box<dyn Read + Send + '_>> {
+        let file = fs::File::open(self.path(key)?)?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first, so that a crash never leaves a
+        // partial file under the final name.
+        let mut tmp_name = path.file_name().unwrap().to_os_string();
+        tmp_name.push(format!(".tmp{}", std::process::id()));
+        let tmp_path = path.with_file_name(tmp_name);
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, &path)
+    }
+
+    fn exists(&self, key: &str) -> io::Result<bool> {
+        Ok(self.path(key)?.is_file())
+    }
+}
+
+/// Keeps every artifact in memory, e.g, for tests. Clones share the
+/// artifacts.
+#[derive(Clone, Default)]
+pub struct MemoryArtifactStore {
+    artifacts: Arc<Mutex<HashMap<String, Arc<Vec<u8>>>>>,
+}
+
+impl fmt::Debug for MemoryArtifactStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryArtifactStore")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl MemoryArtifactStore {
+    pub fn new() -> MemoryArtifactStore {
+        MemoryArtifactStore::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<Vec<u8>>>> {
+        self.artifacts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// Reads an artifact without copying it, while a `put()` of the same key
+// replaces it for later readers only.
+struct SharedBytes(Arc<Vec<u8>>);
+
+impl AsRef<[u8]> for SharedBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl ArtifactStore for MemoryArtifactStore {
+    fn get(&self, key: &str) -> io::Result<Box<dyn Read + Send + '_>> {
+        match self.lock().get(key) {
+            Some(data) => Ok(Box::new(Cursor::new(SharedBytes(data.clone())))),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no artifact under {:?}", key),
+            )),
+        }
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> io::Result<()> {
+        self.lock().insert(key.to_owned(), Arc::new(data.to_vec()));
+        Ok(())
+    }
+
+    fn exists(&self, key: &str) -> io::Result<bool> {
+        Ok(self.lock().contains_key(key))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io::Read};
+
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::{Challenge255, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::SeedableRng;
+
+    use super::{ArtifactStore, FsArtifactStore, MemoryArtifactStore};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{MultiOpenScheme, TranscriptType, SEED},
+        prover::create_proof,
+        proving_key::{multi_open_scheme_to_metadata, write_with_metadata, Metadata},
+        xor_shift_rng::XORShiftRng,
+    };
+
+    fn read(store: &dyn ArtifactStore, key: &str) -> Vec<u8> {
+        let mut data = vec![];
+        store.get(key).unwrap().read_to_end(&mut data).unwrap();
+        data
+    }
+
+    fn check_store(store: &dyn ArtifactStore) {
+        assert!(!store.exists("keys/a.pk").unwrap());
+        assert_eq!(
+            store.get("keys/a.pk").err().unwrap().kind(),
+            std::io::ErrorKind::NotFound
+        );
+
+        store.put("keys/a.pk", b"first").unwrap();
+        assert!(store.exists("keys/a.pk").unwrap());
+        let mut reader = store.get("keys/a.pk").unwrap();
+        store.put("keys/a.pk", b"second").unwrap();
+        assert_eq!(read(store, "keys/a.pk"), b"second");
+        // A reader opened before the replacement reads the old artifact.
+        let mut data = vec![];
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"first");
+    }
+
+    #[test]
+    fn test_memory_store() {
+        let store = MemoryArtifactStore::new();
+        check_store(&store);
+        assert_eq!(store.clone().len(), 1);
+    }
+
+    #[test]
+    fn test_fs_store() {
+        let dir =
+            std::env::temp_dir().join(format!("tachyon_halo2_artifacts_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = FsArtifactStore::new(&dir).unwrap();
+        check_store(&store);
+        assert_eq!(fs::read(dir.join("keys/a.pk")).unwrap(), b"second");
+        for key in ["", "../a.pk", "/tmp/a.pk", "keys/../../a.pk"] {
+            assert_eq!(
+                store.put(key, b"").unwrap_err().kind(),
+                std::io::ErrorKind::InvalidInput
+            );
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_read_proving_key() {
+        let k = 4;
+        let s = Fr::from(2);
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let instances = vec![Fr::from(7 * 2 * 2 * 3 * 3)];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut metadata = Metadata::new();
+        multi_open_scheme_to_metadata(MultiOpenScheme::SHPlonk, &mut metadata);
+        let mut stored = vec![];
+        write_with_metadata(&mut stored, &metadata, &pk_bytes).unwrap();
+        let store = MemoryArtifactStore::new();
+        store.put("keys/simple.pk", &stored).unwrap();
+
+        assert_eq!(
+            TachyonProvingKey::<G1Affine>::read(&store, "keys/missing.pk")
+                .err()
+                .unwrap()
+                .kind(),
+            std::io::ErrorKind::NotFound
+        );
+        let mut tachyon_pk = TachyonProvingKey::<G1Affine>::read(&store, "keys/simple.pk").unwrap();
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        let mut transcript = TachyonBlake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &[&[&instances[..]]],
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+        assert!(prover.verify_proof(&mut tachyon_pk, &[&[&instances[..]]], &proof));
+    }
+}
@@ -0,0 +1,481 @@
+// A witness calculator written in another language, e.g, a C++ or Go one,
+// already knows the value of every advice cell, and wrapping it in a
+// `Circuit` only to hand the values back through `assign_advice()` is
+// artificial. `create_proof_from_assignments()` commits to the values as they
+// are given, the way a replayed witness is, see
+// `crate::prover::ProofSteps::replay_witness()`, so the floor planner never
+// runs. The key still holds the constraint system, so only the values are
+// needed.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use halo2_proofs::{
+    plonk::sealed, poly::commitment::CommitmentScheme, transcript::EncodedChallenge,
+};
+use halo2curves::bn256::Fr;
+
+use crate::{
+    bn254::{ProvingKey as TachyonProvingKey, TachyonProver, TranscriptWriteState},
+    column::AdviceColumnIndex,
+    error::TachyonError,
+    phase::ColumnIndex,
+    prover::{check_transcript_type, prepare_instances, ProofSteps, WitnessOnly},
+    xor_shift_rng::XORShiftRng as TachyonXORShiftRng,
+};
+
+/// The values of an advice column, already evaluated. The rows that aren't
+/// given are zero.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssignedColumn {
+    /// The values of the rows from the first on.
+    Dense(Vec<Fr>),
+    /// The values of the given rows, each given once.
+    Sparse(Vec<(usize, Fr)>),
+}
+
+/// The advice columns of one phase, of every circuit of the proof. A column
+/// that isn't assigned is all zeros.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PhaseAssignments {
+    columns: BTreeMap<(usize, ColumnIndex), AssignedColumn>,
+}
+
+impl PhaseAssignments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns `values` to `column` of the circuit at `circuit`, replacing
+    /// what it was assigned before.
+    pub fn assign(&mut self, circuit: usize, column: AdviceColumnIndex, values: AssignedColumn) {
+        self.columns.insert((circuit, column.index()), values);
+    }
+
+    /// Checks every column against the key, see [`AssignmentError`].
+    fn check(
+        &self,
+        phase: usize,
+        num_circuits: usize,
+        column_phases: &[sealed::Phase],
+        current_phase: sealed::Phase,
+        usable_rows: usize,
+    ) -> Result<(), AssignmentError> {
+        for ((circuit, column), values) in &self.columns {
+            let invalid_column = |reason| AssignmentError::InvalidColumn {
+                phase,
+                circuit: *circuit,
+                column: *column,
+                reason,
+            };
+            if *circuit >= num_circuits {
+                return Err(invalid_column("the proof has no such circuit"));
+            }
+            match column_phases.get(*column) {
+                None => return Err(invalid_column("the key has no such advice column")),
+                Some(column_phase) if *column_phase != current_phase => {
+                    return Err(invalid_column("the column belongs to another phase"))
+                }
+                Some(_) => {}
+            }
+            match values {
+                AssignedColumn::Dense(values) => {
+                    if values.len() > usable_rows {
+                        return Err(invalid_column("more values than usable rows are given"));
+                    }
+                }
+                AssignedColumn::Sparse(values) => {
+                    let mut assigned = vec![false; usable_rows];
+                    for (row, _) in values {
+                        let reason = match assigned.get_mut(*row) {
+                            None => "the row is past the usable rows",
+                            Some(true) => "the row is assigned twice",
+                            Some(assigned) => {
+                                *assigned = true;
+                                continue;
+                            }
+                        };
+                        return Err(AssignmentError::InvalidRow {
+                            phase,
+                            circuit: *circuit,
+                            column: *column,
+                            row: *row,
+                            reason,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The advice columns a proof is created from, see
+/// [`create_proof_from_assignments()`].
+pub enum AdviceAssignments<'a> {
+    /// The columns of each phase of the key, in order. Only the first phase
+    /// can be given this way if the circuit has challenges, since the values
+    /// of later phases usually depend on them.
+    Phases(Vec<PhaseAssignments>),
+    /// Called before each phase with the index of the phase among the key's
+    /// phases and the challenges squeezed so far, indexed by challenge. The
+    /// challenges of this phase and later ones are still zero.
+    Callback(Box<dyn FnMut(usize, &[Fr]) -> PhaseAssignments + 'a>),
+}
+
+impl<'a> fmt::Debug for AdviceAssignments<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdviceAssignments::Phases(phases) => f.debug_tuple("Phases").field(phases).finish(),
+            AdviceAssignments::Callback(_) => f.debug_tuple("Callback").finish(),
+        }
+    }
+}
+
+/// Advice assignments that don't fit the key or the prover's domain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssignmentError {
+    /// [`AdviceAssignments::Phases`] holds another number of phases than the
+    /// key has.
+    PhaseCount { expected: usize, found: usize },
+    /// A column can't be assigned in the phase it is given in.
+    InvalidColumn {
+        phase: usize,
+        circuit: usize,
+        column: usize,
+        reason: &'static str,
+    },
+    /// A row of an [`AssignedColumn::Sparse`] can't be assigned.
+    InvalidRow {
+        phase: usize,
+        circuit: usize,
+        column: usize,
+        row: usize,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for AssignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssignmentError::PhaseCount { expected, found } => write!(
+                f,
+                "assignments of {} phases are given, but the key has {}",
+                found, expected
+            ),
+            AssignmentError::InvalidColumn {
+                phase,
+                circuit,
+                column,
+                reason,
+            } => write!(
+                f,
+                "advice column {} of circuit {} in phase {}: {}",
+                column, circuit, phase, reason
+            ),
+            AssignmentError::InvalidRow {
+                phase,
+                circuit,
+                column,
+                row,
+                reason,
+            } => write!(
+                f,
+                "row {} of advice column {} of circuit {} in phase {}: {}",
+                row, column, circuit, phase, reason
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AssignmentError {}
+
+impl From<AssignmentError> for TachyonError {
+    fn from(e: AssignmentError) -> Self {
+        TachyonError::Assignment(e)
+    }
+}
+
+/// Same as [`crate::prover::create_proof()`], but commits to `assignments`
+/// instead of synthesizing circuits, one for each entry of `instances`. The
+/// assignments of each phase are checked against `pk` and the usable rows of
+/// the prover's domain before anything of the phase is committed to, and
+/// fail the proof with [`TachyonError::Assignment`]. The proof is the same as
+/// the one of a circuit that assigns the same values.
+pub fn create_proof_from_assignments<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E>,
+>(
+    prover: &mut P,
+    pk: &mut TachyonProvingKey<Scheme::Curve>,
+    mut assignments: AdviceAssignments<'_>,
+    instances: &[&[&[Scheme::Scalar]]],
+    rng: TachyonXORShiftRng,
+    transcript: &mut T,
+) -> Result<(), TachyonError> {
+    check_transcript_type(prover, transcript)?;
+    let phases = pk.phases();
+    if let AdviceAssignments::Phases(given) = &assignments {
+        if given.len() != phases.len() {
+            return Err(AssignmentError::PhaseCount {
+                expected: phases.len(),
+                found: given.len(),
+            }
+            .into());
+        }
+    }
+    let column_phases = pk.advice_column_phases();
+    let usable_rows = prover.n() as usize - (pk.blinding_factors() as usize + 1);
+    let prepared = prepare_instances(prover, pk, instances)?;
+
+    let circuits = vec![WitnessOnly; instances.len()];
+    let mut steps = ProofSteps::<Scheme, WitnessOnly>::new(pk, circuits.len(), rng, false);
+    steps.replay_witness(vec![]);
+    let mut overflow = None;
+    while !steps.is_finished() {
+        if let Some((phase, 0)) = steps.next_synthesis() {
+            let columns = match &mut assignments {
+                AdviceAssignments::Phases(given) => std::mem::take(&mut given[phase]),
+                AdviceAssignments::Callback(callback) => callback(phase, &steps.challenges()),
+            };
+            columns.check(
+                phase,
+                circuits.len(),
+                &column_phases,
+                phases[phase],
+                usable_rows,
+            )?;
+            steps.assign(columns.columns.into_iter().collect::<HashMap<_, _>>());
+        }
+        steps
+            .run_next(prover, pk, &circuits, &prepared, transcript, &mut overflow)
+            .map_err(|e| steps.error(e, overflow))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, Circuit},
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::TranscriptWriterBuffer,
+        SerdeFormat,
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::SeedableRng;
+
+    use super::{
+        create_proof_from_assignments, AdviceAssignments, AssignedColumn, AssignmentError,
+        PhaseAssignments,
+    };
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::{
+            interleaved_phase_circuit::InterleavedPhaseCircuit, simple_circuit::SimpleCircuit,
+        },
+        consts::{TranscriptType, SEED},
+        error::TachyonError,
+        prover::{prepare_instances, ProofSteps},
+        xor_shift_rng::XORShiftRng,
+    };
+
+    type Prover = SHPlonkProver<KZGCommitmentScheme<Bn256>>;
+
+    fn setup<C: Circuit<Fr>>(k: u32, circuit: &C) -> (Prover, TachyonProvingKey<G1Affine>) {
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, circuit).expect("pk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        (
+            Prover::new(TranscriptType::Blake2b as u8, k, &s),
+            TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap(),
+        )
+    }
+
+    /// Proves `circuit`, and returns the proof and the assignments of each
+    /// phase it made, every other column given sparsely.
+    fn prove_circuit<C: Circuit<Fr>>(
+        prover: &mut Prover,
+        pk: &mut TachyonProvingKey<G1Affine>,
+        circuit: &C,
+        instances: &[&[Fr]],
+    ) -> (Vec<u8>, Vec<PhaseAssignments>) {
+        let circuits = std::slice::from_ref(circuit);
+        let prepared = prepare_instances(prover, pk, &[instances]).unwrap();
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        let mut steps = ProofSteps::<KZGCommitmentScheme<Bn256>, C>::new(
+            pk,
+            circuits.len(),
+            XORShiftRng::from_seed(SEED),
+            false,
+        );
+        steps.capture_witness();
+        steps
+            .run_all(prover, pk, circuits, &prepared, &mut transcript, &mut None)
+            .expect("proof generation should not fail");
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+
+        let usable_rows = prover.n() as usize - (pk.blinding_factors() as usize + 1);
+        let phases = pk.phases();
+        let column_phases = pk.advice_column_phases();
+        let mut assignments = vec![PhaseAssignments::new(); phases.len()];
+        for (i, column) in steps.take_captured_witness().into_iter().enumerate() {
+            let phase = phases
+                .iter()
+                .position(|phase| *phase == column_phases[column.column])
+                .unwrap();
+            let values = if i % 2 == 0 {
+                AssignedColumn::Dense(column.values[..usable_rows].to_vec())
+            } else {
+                AssignedColumn::Sparse(
+                    column
+                        .values
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, value)| **value != Fr::zero())
+                        .map(|(row, value)| (row, *value))
+                        .collect(),
+                )
+            };
+            assignments[phase].assign(
+                column.circuit,
+                pk.advice_index(column.column).unwrap(),
+                values,
+            );
+        }
+        (proof, assignments)
+    }
+
+    fn prove_assignments(
+        prover: &mut Prover,
+        pk: &mut TachyonProvingKey<G1Affine>,
+        assignments: AdviceAssignments<'_>,
+        instances: &[&[Fr]],
+    ) -> Result<Vec<u8>, TachyonError> {
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        create_proof_from_assignments(
+            prover,
+            pk,
+            assignments,
+            &[instances],
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )?;
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+        Ok(proof)
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_assignments_match_circuit() {
+        let k = 4;
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let instance = [Fr::from(7 * 2 * 2 * 3 * 3)];
+        let (mut prover, mut pk) = setup(k, &circuit);
+        let (proof, phases) = prove_circuit(&mut prover, &mut pk, &circuit, &[&instance]);
+        let assignments = AdviceAssignments::Phases(phases);
+        let proof_from_assignments =
+            prove_assignments(&mut prover, &mut pk, assignments, &[&instance]).unwrap();
+        assert_eq!(proof_from_assignments, proof);
+
+        let usable_rows = prover.n() as usize - (pk.blinding_factors() as usize + 1);
+        let mut prove_phase = |column: usize, circuit: usize, values: AssignedColumn| {
+            let mut phase = PhaseAssignments::new();
+            phase.assign(circuit, pk.advice_index(column).unwrap(), values);
+            let assignments = AdviceAssignments::Phases(vec![phase]);
+            match prove_assignments(&mut prover, &mut pk, assignments, &[&instance]) {
+                Err(TachyonError::Assignment(e)) => e,
+                result => panic!("unexpected {:?}", result.map(|_| ())),
+            }
+        };
+        let past_usable_rows = AssignedColumn::Sparse(vec![(usable_rows, Fr::one())]);
+        assert!(matches!(
+            prove_phase(0, 0, past_usable_rows),
+            AssignmentError::InvalidRow { row, .. } if row == usable_rows
+        ));
+        let twice = AssignedColumn::Sparse(vec![(1, Fr::one()), (1, Fr::one())]);
+        assert!(matches!(
+            prove_phase(0, 0, twice),
+            AssignmentError::InvalidRow { row: 1, .. }
+        ));
+        let too_long = AssignedColumn::Dense(vec![Fr::one(); usable_rows + 1]);
+        assert!(matches!(
+            prove_phase(0, 0, too_long),
+            AssignmentError::InvalidColumn { column: 0, .. }
+        ));
+        assert!(matches!(
+            prove_phase(0, 1, AssignedColumn::Dense(vec![])),
+            AssignmentError::InvalidColumn { circuit: 1, .. }
+        ));
+        assert!(matches!(
+            prove_assignments(
+                &mut prover,
+                &mut pk,
+                AdviceAssignments::Phases(vec![]),
+                &[&instance]
+            ),
+            Err(TachyonError::Assignment(AssignmentError::PhaseCount {
+                expected: 1,
+                found: 0
+            }))
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_assignments_with_challenges() {
+        let k = 4;
+        let circuit = InterleavedPhaseCircuit {
+            values: (1..4)
+                .map(|i| [Value::known(Fr::from(i)), Value::known(Fr::from(i + 10))])
+                .collect(),
+        };
+        let (mut prover, mut pk) = setup(k, &circuit);
+        let (proof, mut phases) = prove_circuit(&mut prover, &mut pk, &circuit, &[]);
+        assert_eq!(phases.len(), 2);
+        let second_phase = phases[1].clone();
+
+        // The columns of the second phase are handed over only once the
+        // challenge of the first is squeezed.
+        let mut calls = vec![];
+        let callback = |phase: usize, challenges: &[Fr]| {
+            calls.push((phase, challenges.to_vec()));
+            std::mem::take(&mut phases[phase])
+        };
+        let assignments = AdviceAssignments::Callback(Box::new(callback));
+        let proof_from_assignments =
+            prove_assignments(&mut prover, &mut pk, assignments, &[]).unwrap();
+        assert_eq!(proof_from_assignments, proof);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0], (0, vec![Fr::zero()]));
+        assert_eq!(calls[1].0, 1);
+        assert_ne!(calls[1].1, vec![Fr::zero()]);
+
+        // The columns of the second phase can't be given in the first.
+        let assignments = AdviceAssignments::Phases(vec![second_phase, PhaseAssignments::new()]);
+        assert!(matches!(
+            prove_assignments(&mut prover, &mut pk, assignments, &[]),
+            Err(TachyonError::Assignment(AssignmentError::InvalidColumn {
+                phase: 0,
+                ..
+            }))
+        ));
+    }
+}
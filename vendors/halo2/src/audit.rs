@@ -0,0 +1,310 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use ff::PrimeField;
+use halo2_proofs::{
+    plonk::VerifyingKey,
+    transcript::{EncodedChallenge, Transcript, TranscriptWrite},
+};
+use halo2curves::{
+    bn256::{G1Affine, G2Affine},
+    group::GroupEncoding,
+    CurveAffine,
+};
+
+use crate::{
+    bn254::{ProvingKey as TachyonProvingKey, TranscriptWriteState},
+    consts::{MultiOpenScheme, TranscriptType},
+    proof::{parse, PointEncoding, ProofValue},
+    serde_fr::fr_to_le_bytes,
+    transcript::{transcript_layout, Component, TranscriptEntry, TranscriptItem, TranscriptLog},
+};
+
+/// Which side of the prover touched the transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditSource {
+    Rust,
+    Backend,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditOp {
+    CommonPoint,
+    CommonScalar,
+    /// A G2 point absorbed with
+    /// [`crate::bn254::TranscriptWriteState::common_g2()`].
+    CommonG2,
+    WritePoint,
+    WriteScalar,
+    Squeeze,
+    /// Backend bytes that weren't split, see [`AuditLayout`].
+    WriteBytes,
+}
+
+impl AuditOp {
+    fn name(&self) -> &'static str {
+        match self {
+            AuditOp::CommonPoint => "common_point",
+            AuditOp::CommonScalar => "common_scalar",
+            AuditOp::CommonG2 => "common_g2",
+            AuditOp::WritePoint => "write_point",
+            AuditOp::WriteScalar => "write_scalar",
+            AuditOp::Squeeze => "squeeze",
+            AuditOp::WriteBytes => "write_bytes",
+        }
+    }
+}
+
+/// One line of an audit log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub source: AuditSource,
+    pub op: AuditOp,
+    /// E.g, `vk_repr`, `instance[0][1][2]` or `advice_commitment[0][3]`.
+    pub label: String,
+    /// Points are compressed and scalars are little-endian, whatever the
+    /// transcript writes.
+    pub value: Vec<u8>,
+}
+
+impl AuditEntry {
+    /// Formats the entry as one JSON object. Labels are generated here and
+    /// never need escaping.
+    pub fn to_json_line(&self, seq: usize) -> String {
+        format!(
+            "{{\"seq\":{},\"source\":\"{}\",\"op\":\"{}\",\"label\":\"{}\",\"value\":\"{}\"}}",
+            seq,
+            match self.source {
+                AuditSource::Rust => "rust",
+                AuditSource::Backend => "backend",
+            },
+            self.op.name(),
+            self.label,
+            to_hex(&self.value),
+        )
+    }
+}
+
+/// Lets the audit log label the values the backend writes. Without it, they
+/// are logged as a single `backend_proof` entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditLayout {
+    pub items: Vec<TranscriptItem>,
+    pub encoding: PointEncoding,
+}
+
+impl AuditLayout {
+    pub fn new<C: CurveAffine>(
+        vk: &VerifyingKey<C>,
+        num_circuits: usize,
+        scheme: MultiOpenScheme,
+        transcript_type: &TranscriptType,
+    ) -> Self {
+        Self {
+            items: transcript_layout(vk, num_circuits, scheme),
+            encoding: PointEncoding::for_transcript(transcript_type),
+        }
+    }
+}
+
+/// Forwards to a borrowed transcript, so that it can be wrapped in a
+/// [`crate::transcript::RecordingTranscript`].
+pub(crate) struct Forward<'a, T>(pub &'a mut T);
+
+impl<'a, C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E>
+    for Forward<'a, T>
+{
+    fn squeeze_challenge(&mut self) -> E {
+        self.0.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.0.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.0.common_scalar(scalar)
+    }
+}
+
+impl<'a, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWrite<C, E>> TranscriptWrite<C, E>
+    for Forward<'a, T>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.0.write_point(point)
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.0.write_scalar(scalar)
+    }
+}
+
+impl<'a, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWriteState<C, E>>
+    TranscriptWriteState<C, E> for Forward<'a, T>
+{
+    const TRANSCRIPT_TYPE: TranscriptType = T::TRANSCRIPT_TYPE;
+
+    fn state(&self) -> Vec<u8> {
+        self.0.state()
+    }
+
+    fn common_g2(&mut self, point: G2Affine) -> io::Result<()> {
+        self.0.common_g2(point)
+    }
+}
+
+/// Labels the operations `create_proof()` does on the Rust side, in order:
+/// the vk repr, the instances, then the advice commitments and challenges of
+/// each phase.
+pub(crate) fn rust_entries<C: CurveAffine>(
+    pk: &TachyonProvingKey<C>,
+    instances: &[&[&[C::Scalar]]],
+    query_instance: bool,
+    log: &TranscriptLog<C>,
+) -> Vec<AuditEntry> {
+    let mut labels = vec!["vk_repr".to_string()];
+    for (circuit, instance) in instances.iter().enumerate() {
+        for (column, values) in instance.iter().enumerate() {
+            if query_instance {
+                labels.push(format!("instance_commitment[{}][{}]", circuit, column));
+            } else {
+                labels.extend(
+                    (0..values.len())
+                        .map(|row| format!("instance[{}][{}][{}]", circuit, column, row)),
+                );
+            }
+        }
+    }
+    let advice_column_phases = pk.advice_column_phases();
+    let challenge_phases = pk.challenge_phases();
+    for phase in pk.phases() {
+        for circuit in 0..instances.len() {
+            for (column, column_phase) in advice_column_phases.iter().enumerate() {
+                if *column_phase == phase {
+                    labels.push(format!("advice_commitment[{}][{}]", circuit, column));
+                }
+            }
+        }
+        for (index, challenge_phase) in challenge_phases.iter().enumerate() {
+            if *challenge_phase == phase {
+                labels.push(format!("challenge[{}]", index));
+            }
+        }
+    }
+    assert_eq!(
+        labels.len(),
+        log.len(),
+        "the audit labels are out of sync with create_proof()"
+    );
+
+    log.iter()
+        .zip(labels)
+        .map(|(entry, label)| {
+            let (op, value) = match entry {
+                TranscriptEntry::CommonPoint(point) => (AuditOp::CommonPoint, point_bytes(point)),
+                TranscriptEntry::CommonScalar(scalar) => {
+                    (AuditOp::CommonScalar, scalar.to_repr().as_ref().to_vec())
+                }
+                TranscriptEntry::CommonG2(point) => (AuditOp::CommonG2, point_bytes(point)),
+                TranscriptEntry::WritePoint(point) => (AuditOp::WritePoint, point_bytes(point)),
+                TranscriptEntry::WriteScalar(scalar) => {
+                    (AuditOp::WriteScalar, scalar.to_repr().as_ref().to_vec())
+                }
+                TranscriptEntry::Challenge(scalar) => {
+                    (AuditOp::Squeeze, scalar.to_repr().as_ref().to_vec())
+                }
+            };
+            AuditEntry {
+                source: AuditSource::Rust,
+                op,
+                label,
+                value,
+            }
+        })
+        .collect()
+}
+
+/// Labels the bytes the backend wrote, i.e, everything in `layout` after the
+/// advice commitments. The backend squeezes its challenges internally, so
+/// only its writes show up.
+pub(crate) fn backend_entries(
+    proof: &[u8],
+    layout: Option<&AuditLayout>,
+) -> io::Result<Vec<AuditEntry>> {
+    let layout = match layout {
+        Some(layout) => layout,
+        None => {
+            return Ok(vec![AuditEntry {
+                source: AuditSource::Backend,
+                op: AuditOp::WriteBytes,
+                label: "backend_proof".to_string(),
+                value: proof.to_vec(),
+            }])
+        }
+    };
+    let items = layout
+        .items
+        .iter()
+        .filter(|item| item.component != Component::AdviceCommitment)
+        .copied()
+        .collect::<Vec<_>>();
+    let parsed = parse(proof, &items, layout.encoding)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(parsed
+        .values()
+        .iter()
+        .map(|(item, value)| {
+            let (op, value) = match value {
+                ProofValue::Point(point) => (AuditOp::WritePoint, point_bytes::<G1Affine>(point)),
+                ProofValue::Scalar(scalar) => {
+                    (AuditOp::WriteScalar, fr_to_le_bytes(scalar).to_vec())
+                }
+            };
+            AuditEntry {
+                source: AuditSource::Backend,
+                op,
+                label: item_label(item),
+                value,
+            }
+        })
+        .collect())
+}
+
+/// Writes one JSON object per line, numbered from 0. The file is replaced if
+/// it exists.
+pub fn write_audit_log(path: &Path, entries: &[AuditEntry]) -> io::Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    for (seq, entry) in entries.iter().enumerate() {
+        writeln!(file, "{}", entry.to_json_line(seq))?;
+    }
+    file.flush()
+}
+
+pub(crate) fn item_label(item: &TranscriptItem) -> String {
+    let name = item.component.name();
+    match item.circuit {
+        Some(circuit) => format!("{}[{}][{}]", name, circuit, item.index),
+        None => format!("{}[{}]", name, item.index),
+    }
+}
+
+fn point_bytes<C: CurveAffine>(point: &C) -> Vec<u8> {
+    point.to_bytes().as_ref().to_vec()
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub(crate) fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|chunk| u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok())
+        .collect()
+}
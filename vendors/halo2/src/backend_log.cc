@@ -0,0 +1,40 @@
+#include "vendors/halo2/include/backend_log.h"
+
+#include <string>
+
+#include "tachyon/c/logging.h"
+#include "vendors/halo2/include/dlopen.h"
+#include "vendors/halo2/src/backend_log.rs.h"
+
+namespace tachyon::halo2_api {
+
+namespace {
+
+// |data| is the callback of `BackendLog::Callback`.
+void ForwardLog(void* data, tachyon_log_severity severity, const char* message,
+                size_t message_len) {
+  forward_backend_log(
+      reinterpret_cast<size_t>(data), static_cast<uint8_t>(severity),
+      rust::Slice<const uint8_t>(reinterpret_cast<const uint8_t*>(message),
+                                 message_len));
+}
+
+}  // namespace
+
+void set_backend_log(uint8_t destination, size_t callback) {
+  tachyon_set_log_destination(
+      static_cast<tachyon_log_destination>(destination), ForwardLog,
+      reinterpret_cast<void*>(callback));
+}
+
+int32_t backend_log_verbosity() { return tachyon_get_log_verbosity(); }
+
+void set_backend_log_verbosity(int32_t verbosity) {
+  tachyon_set_log_verbosity(verbosity);
+}
+
+void log_fatal_for_testing(rust::Str message) {
+  tachyon_log_fatal_for_testing(std::string(message).c_str());
+}
+
+}  // namespace tachyon::halo2_api
@@ -0,0 +1,150 @@
+use std::fmt;
+
+use log::Level;
+
+#[cfg(feature = "stub-backend")]
+use crate::stub::backend_log as ffi;
+
+#[cfg(not(feature = "stub-backend"))]
+#[cxx::bridge(namespace = "tachyon::halo2_api")]
+mod ffi {
+    extern "Rust" {
+        fn forward_backend_log(callback: usize, severity: u8, message: &[u8]);
+    }
+
+    unsafe extern "C++" {
+        include!("vendors/halo2/include/backend_log.h");
+
+        fn set_backend_log(destination: u8, callback: usize);
+        fn backend_log_verbosity() -> i32;
+        fn set_backend_log_verbosity(verbosity: i32);
+        fn log_fatal_for_testing(message: &str);
+    }
+}
+
+/// Where the messages libtachyon logs go, see
+/// [`crate::version::InitOptions::backend_log`]. They are never written to
+/// log files.
+#[derive(Clone, Copy)]
+pub enum BackendLog {
+    Silent,
+    /// To stderr, as before [`crate::version::init_with()`] is called.
+    Stderr,
+    /// To a function, with one whole message per call. The backend calls it
+    /// from the thread that logged the message, possibly one of its own, but
+    /// never from two threads at once. Pass [`forward_to_log()`] to log the
+    /// messages with the `log` crate.
+    Callback(fn(Level, &str)),
+}
+
+impl Default for BackendLog {
+    fn default() -> Self {
+        BackendLog::Stderr
+    }
+}
+
+// A function pointer with a reference argument has no `Debug` in the Rust this
+// crate supports.
+impl fmt::Debug for BackendLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendLog::Silent => f.write_str("Silent"),
+            BackendLog::Stderr => f.write_str("Stderr"),
+            BackendLog::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+/// Logs a message of libtachyon with the `log` crate under the target
+/// "tachyon", e.g, for [`BackendLog::Callback`].
+pub fn forward_to_log(level: Level, message: &str) {
+    log::log!(target: "tachyon", level, "{}", message);
+}
+
+pub(crate) fn set_backend_log(backend_log: &BackendLog) {
+    // Mirrors `tachyon_log_destination`.
+    let (destination, callback) = match backend_log {
+        BackendLog::Silent => (0, 0),
+        BackendLog::Stderr => (1, 0),
+        BackendLog::Callback(callback) => (2, *callback as usize),
+    };
+    ffi::set_backend_log(destination, callback);
+}
+
+/// Aborts the process from inside libtachyon the way a failed `CHECK` does,
+/// for tests of what a crash leaves behind, e.g, the trace of
+/// `src/ffi_trace.rs`.
+#[cfg(test)]
+pub(crate) fn abort_in_backend(message: &str) -> ! {
+    ffi_trace!("log_fatal_for_testing", "message={}", message);
+    ffi::log_fatal_for_testing(message);
+    unreachable!("libtachyon returned from a fatal log")
+}
+
+#[cfg(not(feature = "stub-backend"))]
+fn forward_backend_log(callback: usize, severity: u8, message: &[u8]) {
+    // `callback` is the function of `BackendLog::Callback` that
+    // `set_backend_log()` passed.
+    let callback = unsafe { std::mem::transmute::<usize, fn(Level, &str)>(callback) };
+    // Mirrors `tachyon_log_severity`.
+    let level = match severity {
+        0 => Level::Info,
+        1 => Level::Warn,
+        _ => Level::Error,
+    };
+    callback(level, &String::from_utf8_lossy(message));
+}
+
+/// Logs the verbose messages of libtachyon up to a level while it is alive,
+/// see [`crate::prover::ProofOptions::backend_log_verbosity`].
+pub(crate) struct VerbosityOverride {
+    previous: i32,
+}
+
+impl VerbosityOverride {
+    pub(crate) fn new(verbosity: i32) -> VerbosityOverride {
+        let previous = ffi::backend_log_verbosity();
+        ffi::set_backend_log_verbosity(verbosity);
+        VerbosityOverride { previous }
+    }
+}
+
+impl Drop for VerbosityOverride {
+    fn drop(&mut self) {
+        ffi::set_backend_log_verbosity(self.previous);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use log::Level;
+
+    use super::{ffi::backend_log_verbosity, set_backend_log, BackendLog, VerbosityOverride};
+    use crate::bn254::ffi;
+
+    static MSM_MESSAGES: AtomicUsize = AtomicUsize::new(0);
+
+    fn count_msm_messages(level: Level, message: &str) {
+        if level == Level::Info && message == "CreateMSMApi()" {
+            MSM_MESSAGES.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_callback() {
+        let previous = backend_log_verbosity();
+        set_backend_log(&BackendLog::Callback(count_msm_messages));
+        {
+            // Creating an MSM logs at level 1.
+            let _verbosity = VerbosityOverride::new(1);
+            let msm = ffi::create_g1_msm(2);
+            unsafe { ffi::destroy_g1_msm(msm) };
+        }
+        set_backend_log(&BackendLog::Stderr);
+        assert!(MSM_MESSAGES.load(Ordering::SeqCst) > 0);
+        assert_eq!(backend_log_verbosity(), previous);
+    }
+}
@@ -1,17 +1,30 @@
+#[cfg(feature = "stats")]
+use std::sync::Arc;
+#[cfg(feature = "halo2")]
 use std::{
-    io::{self, Write},
-    marker::PhantomData,
+    cell::{RefCell, RefMut},
+    collections::BTreeSet,
+    io::{self, Read, Write},
+    time::Duration,
 };
+use std::{fmt, marker::PhantomData};
 
 use ff::{Field, PrimeField};
+#[cfg(feature = "halo2")]
 use halo2_proofs::{
-    plonk::{sealed, Column, Fixed},
-    poly::commitment::{Blind, CommitmentScheme},
+    plonk::{sealed, Any, Circuit, Column, Error, Fixed},
+    poly::{
+        commitment::{Blind, CommitmentScheme},
+        Coeff, EvaluationDomain, LagrangeCoeff, Polynomial, Rotation,
+    },
     transcript::{
         Challenge255, EncodedChallenge, Transcript, TranscriptWrite, TranscriptWriterBuffer,
     },
 };
-use halo2curves::{bn256::G2Affine, Coordinates, CurveAffine, FieldExt};
+use halo2curves::FieldExt;
+#[cfg(feature = "halo2")]
+use halo2curves::{bn256::G2Affine, group::prime::PrimeCurveAffine, Coordinates, CurveAffine};
+#[cfg(feature = "halo2")]
 use num_bigint::BigUint;
 
 use tachyon_rs::math::elliptic_curves::bn::bn254::{
@@ -19,24 +32,249 @@ use tachyon_rs::math::elliptic_curves::bn::bn254::{
     G2AffinePoint as G2AffinePointImpl,
 };
 
+#[cfg(feature = "stats")]
+use crate::stats::{ProverStats, StatsSnapshot};
+#[cfg(feature = "halo2")]
+use crate::{
+    annotation::metadata_key,
+    artifact::ArtifactStore,
+    circuit_id::circuit_id_from_metadata,
+    column::{
+        check_index, AdviceColumnIndex, ColumnIndexError, FixedColumnIndex, InstanceColumnIndex,
+    },
+    consts::{
+        AbsorbOrder, InstanceAbsorption, LagrangeBasisPolicy, MultiOpenScheme, TranscriptType,
+        VkHashMode,
+    },
+    debug::ColumnKind,
+    error::TachyonError,
+    healthcheck::{warm_up, WarmupReport},
+    metrics::{AllocationStats, ColumnStats, ProofMetrics, ScratchStats},
+    opening::{opening_queries, OpeningQuery},
+    par::{self, AssertSync},
+    phase::{ChallengeId, ColumnIndex, PhaseId},
+    pool::{Pool, Pooled},
+    proving_key::{
+        advice_absorb_order_from_metadata, instance_absorption_from_metadata,
+        multi_open_scheme_from_metadata, split_metadata, Metadata,
+    },
+    redact::Redacted,
+    selector_map::{check_selector_mapping, SelectorCompression},
+    srs::{IntegrityError, IntegrityPolicy, SrsIntegrity},
+    state::{state_body, transcript_state_body, versioned, StateError, StateKind},
+    transcript::{custom_hasher_from_body, custom_state, TranscriptHasher},
+    version::ensure_abi_version,
+    vk_hash::{vk_hash_mode_from_metadata, vk_transcript_repr_from_metadata},
+};
+use crate::{
+    pool::Poolable,
+    redact::{DebugFull, Values},
+};
+
+#[derive(Debug)]
 pub struct G1MSM;
-pub struct G1MSMGpu;
+/// The GPU MSM allocates its buffers on the CUDA device that is current on
+/// the thread that creates it, which is a per-thread setting, so it is kept
+/// on that thread.
+#[derive(Debug)]
+pub struct G1MSMGpu(PhantomData<*const ()>);
+#[derive(Debug)]
 pub struct G1JacobianPoint(pub G1JacobianPointImpl);
+#[derive(Debug)]
 pub struct G1Point2(pub G1Point2Impl);
+#[derive(Debug)]
 pub struct G2AffinePoint(pub G2AffinePointImpl);
+#[derive(Debug)]
 pub struct Fr(pub FrImpl);
+#[derive(Clone, Debug)]
 pub struct InstanceSingle {
     pub instance_values: Vec<Evals>,
     pub instance_polys: Vec<Poly>,
 }
+#[cfg(feature = "halo2")]
+impl InstanceSingle {
+    /// Sets the instance cells in `changes`, given as `(column, row, value)`,
+    /// and recomputes the polynomials of only the columns whose values
+    /// changed. Returns those columns in ascending order.
+    pub fn update<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+        &mut self,
+        changes: &[(usize, usize, halo2curves::bn256::Fr)],
+        prover: &P,
+        pk: &ProvingKey<Scheme::Curve>,
+    ) -> Result<Vec<usize>, Error> {
+        let usable_rows = prover.n() as usize - (pk.blinding_factors() as usize + 1);
+        let mut columns = BTreeSet::new();
+        for (column, row, value) in changes {
+            let values = self
+                .instance_values
+                .get_mut(*column)
+                .ok_or(Error::InvalidInstances)?;
+            if *row >= usable_rows {
+                return Err(Error::InstanceTooLarge);
+            }
+            if values.get_value(*row) != *value {
+                values.set_value(*row, value);
+                columns.insert(*column);
+            }
+        }
+        for column in &columns {
+            self.instance_polys[*column] = prover.ifft(&self.instance_values[*column]);
+        }
+        Ok(columns.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "halo2")]
 #[derive(Clone)]
 pub struct AdviceSingle {
     pub advice_polys: Vec<Evals>,
     pub advice_blinds: Vec<Blind<halo2curves::bn256::Fr>>,
 }
 
+/// Without the "halo2" feature nothing creates advice, but the bridge still
+/// names the type.
+#[cfg(not(feature = "halo2"))]
+#[derive(Debug)]
+pub struct AdviceSingle(());
+
+// The blinds are what keeps the advice columns secret, so they are redacted.
+#[cfg(feature = "halo2")]
+impl fmt::Debug for AdviceSingle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdviceSingle")
+            .field("advice_polys", &self.advice_polys)
+            .field("advice_blinds", &Redacted(()))
+            .finish()
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl DebugFull for AdviceSingle {
+    fn fmt_full(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdviceSingle")
+            .field(
+                "advice_polys",
+                &self
+                    .advice_polys
+                    .iter()
+                    .map(|evals| evals.debug_full())
+                    .collect::<Vec<_>>(),
+            )
+            .field("advice_blinds", &self.advice_blinds)
+            .finish()
+    }
+}
+
+/// The hasher of a custom transcript, which the backend calls back into for
+/// everything it absorbs and squeezes, see
+/// [`crate::transcript::register_custom()`].
+#[cfg(feature = "halo2")]
+pub struct TranscriptCallbacks {
+    id: u8,
+    hasher: Box<dyn TranscriptHasher>,
+}
+
+#[cfg(feature = "halo2")]
+impl TranscriptCallbacks {
+    /// Continues the registered hasher from `body`, a custom transcript state
+    /// without its version byte.
+    fn from_body(body: &[u8]) -> Result<Self, StateError> {
+        let (id, hasher) = custom_hasher_from_body(body)?;
+        Ok(TranscriptCallbacks { id, hasher })
+    }
+
+    fn state(&self) -> Vec<u8> {
+        custom_state(self.id, self.hasher.as_ref())
+    }
+
+    fn absorb_scalar(&mut self, scalar: &Fr) {
+        let scalar = unsafe { std::mem::transmute::<_, &halo2curves::bn256::Fr>(scalar) };
+        self.hasher.absorb_scalar(scalar);
+    }
+
+    fn absorb_point(&mut self, point: &G1Point2) {
+        let point = unsafe { std::mem::transmute::<_, &halo2curves::bn256::G1Affine>(point) };
+        self.hasher.absorb_point(point);
+    }
+
+    fn squeeze(&mut self) -> Box<Fr> {
+        let challenge = Box::new(self.hasher.squeeze());
+        unsafe { std::mem::transmute::<_, Box<Fr>>(challenge) }
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl fmt::Debug for TranscriptCallbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranscriptCallbacks")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Without the "halo2" feature there are no transcripts, but the bridge still
+/// names the type.
+#[cfg(not(feature = "halo2"))]
+#[derive(Debug)]
+pub enum TranscriptCallbacks {}
+
+#[cfg(not(feature = "halo2"))]
+impl TranscriptCallbacks {
+    fn absorb_scalar(&mut self, _scalar: &Fr) {
+        match *self {}
+    }
+
+    fn absorb_point(&mut self, _point: &G1Point2) {
+        match *self {}
+    }
+
+    fn squeeze(&mut self) -> Box<Fr> {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "stub-backend")]
+pub use crate::stub::bn254 as ffi;
+#[cfg(feature = "stub-backend")]
+use crate::stub::UniquePtr;
+#[cfg(not(feature = "stub-backend"))]
+use cxx::UniquePtr;
+
+#[cfg(not(feature = "stub-backend"))]
 #[cxx::bridge(namespace = "tachyon::halo2_api::bn254")]
 pub mod ffi {
+    /// Mirrors `tachyon_bn254_univariate_column_stats`.
+    struct ColumnStats {
+        num_nonzero_rows: usize,
+        num_coeffs: usize,
+        entropy: f64,
+    }
+
+    /// Mirrors `tachyon_plonk_column_query`.
+    struct ColumnQuery {
+        index: usize,
+        rotation: i32,
+    }
+
+    /// Mirrors `tachyon_halo2_bn254_shplonk_prover_allocation_stats`.
+    struct AllocationStats {
+        srs_bytes: usize,
+        transcript_bytes: usize,
+    }
+
+    /// Mirrors `tachyon_halo2_bn254_shplonk_prover_scratch_stats`.
+    struct ScratchStats {
+        bytes: usize,
+        num_allocations: usize,
+        num_reuses: usize,
+    }
+
+    /// Mirrors `tachyon_halo2_gate_cost`.
+    struct GateCost {
+        nanos: u64,
+        num_ops: u64,
+    }
+
     extern "Rust" {
         type G1MSM;
         type G1MSMGpu;
@@ -46,6 +284,11 @@ pub mod ffi {
         type Fr;
         type InstanceSingle;
         type AdviceSingle;
+        type TranscriptCallbacks;
+
+        fn absorb_scalar(self: &mut TranscriptCallbacks, scalar: &Fr);
+        fn absorb_point(self: &mut TranscriptCallbacks, point: &G1Point2);
+        fn squeeze(self: &mut TranscriptCallbacks) -> Box<Fr>;
     }
 
     unsafe extern "C++" {
@@ -72,15 +315,24 @@ pub mod ffi {
         ) -> Box<G1JacobianPoint>;
     }
 
+    unsafe extern "C++" {
+        include!("vendors/halo2/include/bn254_fr.h");
+
+        fn batch_inverse(values: &[Fr], inverses: &mut [Fr]);
+        fn batch_inverse_in_place(values: &mut [Fr]);
+    }
+
     unsafe extern "C++" {
         include!("vendors/halo2/include/bn254_blake2b_writer.h");
 
         type Blake2bWriter;
 
         fn new_blake2b_writer() -> UniquePtr<Blake2bWriter>;
+        fn new_blake2b_writer_from_state(state: &[u8]) -> UniquePtr<Blake2bWriter>;
         fn update(self: Pin<&mut Blake2bWriter>, data: &[u8]);
         fn finalize(self: Pin<&mut Blake2bWriter>, result: &mut [u8; 64]);
         fn state(&self) -> Vec<u8>;
+        fn clone(&self) -> UniquePtr<Blake2bWriter>;
     }
 
     unsafe extern "C++" {
@@ -89,9 +341,11 @@ pub mod ffi {
         type PoseidonWriter;
 
         fn new_poseidon_writer() -> UniquePtr<PoseidonWriter>;
+        fn new_poseidon_writer_from_state(state: &[u8]) -> UniquePtr<PoseidonWriter>;
         fn update(self: Pin<&mut PoseidonWriter>, data: &[u8]);
         fn squeeze(self: Pin<&mut PoseidonWriter>) -> Box<Fr>;
         fn state(&self) -> Vec<u8>;
+        fn clone(&self) -> UniquePtr<PoseidonWriter>;
     }
 
     unsafe extern "C++" {
@@ -100,9 +354,11 @@ pub mod ffi {
         type Sha256Writer;
 
         fn new_sha256_writer() -> UniquePtr<Sha256Writer>;
+        fn new_sha256_writer_from_state(state: &[u8]) -> UniquePtr<Sha256Writer>;
         fn update(self: Pin<&mut Sha256Writer>, data: &[u8]);
         fn finalize(self: Pin<&mut Sha256Writer>, result: &mut [u8; 32]);
         fn state(&self) -> Vec<u8>;
+        fn clone(&self) -> UniquePtr<Sha256Writer>;
     }
 
     unsafe extern "C++" {
@@ -115,10 +371,20 @@ pub mod ffi {
         fn blinding_factors(&self) -> u32;
         fn challenge_phases(&self) -> Vec<u8>;
         fn constants(&self) -> Vec<usize>;
+        fn degree(&self) -> usize;
+        fn extended_k(&self, k: u32) -> u32;
         fn num_advice_columns(&self) -> usize;
         fn num_challenges(&self) -> usize;
+        fn num_fixed_columns(&self) -> usize;
         fn num_instance_columns(&self) -> usize;
+        fn num_lookups(&self) -> usize;
+        fn num_permutation_columns(&self) -> usize;
         fn phases(&self) -> Vec<u8>;
+        fn selector_map(&self) -> Vec<usize>;
+        fn fixed_query_columns(&self) -> Vec<usize>;
+        fn advice_queries(&self) -> Vec<ColumnQuery>;
+        fn instance_queries(&self) -> Vec<ColumnQuery>;
+        fn fixed_queries(&self) -> Vec<ColumnQuery>;
         // TODO(chokobole): In this way, we need to add `transcript_repr_xxx` as more prover comes.
         // We need to figure out how to make them a single method.
         fn transcript_repr_gwc(self: Pin<&mut ProvingKey>, prover: &GWCProver) -> Box<Fr>;
@@ -132,7 +398,11 @@ pub mod ffi {
 
         fn zero_evals() -> UniquePtr<Evals>;
         fn len(&self) -> usize;
+        fn get_value(&self, idx: usize) -> Box<Fr>;
         fn set_value(self: Pin<&mut Evals>, idx: usize, value: &Fr);
+        fn get_values(&self, values: &mut [Fr]);
+        fn set_values(self: Pin<&mut Evals>, values: &[Fr]);
+        fn reset(self: Pin<&mut Evals>);
         fn clone(&self) -> UniquePtr<Evals>;
     }
 
@@ -141,6 +411,7 @@ pub mod ffi {
 
         type RationalEvals;
 
+        fn len(&self) -> usize;
         fn set_zero(self: Pin<&mut RationalEvals>, idx: usize);
         fn set_trivial(self: Pin<&mut RationalEvals>, idx: usize, numerator: &Fr);
         fn set_rational(
@@ -149,6 +420,7 @@ pub mod ffi {
             numerator: &Fr,
             denominator: &Fr,
         );
+        fn reset(self: Pin<&mut RationalEvals>);
         fn clone(&self) -> UniquePtr<RationalEvals>;
     }
 
@@ -156,6 +428,20 @@ pub mod ffi {
         include!("vendors/halo2/include/bn254_poly.h");
 
         type Poly;
+
+        fn zero_poly() -> UniquePtr<Poly>;
+        fn len(&self) -> usize;
+        fn get_coeffs(&self, coeffs: &mut [Fr]);
+        fn set_coeffs(self: Pin<&mut Poly>, coeffs: &[Fr]);
+        fn clone(&self) -> UniquePtr<Poly>;
+    }
+
+    unsafe extern "C++" {
+        include!("vendors/halo2/include/bn254_coset.h");
+
+        fn empty_coset_evals(k: u32) -> UniquePtr<Evals>;
+        fn coset_fft(poly: &Poly, k: u32, offset: &Fr) -> UniquePtr<Evals>;
+        fn coset_ifft(evals: &Evals, k: u32, offset: &Fr) -> UniquePtr<Poly>;
     }
 
     unsafe extern "C++" {
@@ -177,6 +463,7 @@ pub mod ffi {
         fn empty_evals(&self) -> UniquePtr<Evals>;
         fn empty_rational_evals(&self) -> UniquePtr<RationalEvals>;
         fn ifft(&self, evals: &Evals) -> UniquePtr<Poly>;
+        fn column_stats(&self, evals: &Evals) -> ColumnStats;
         fn batch_evaluate(
             &self,
             rational_evals: &[UniquePtr<RationalEvals>],
@@ -184,7 +471,17 @@ pub mod ffi {
         );
         fn set_rng(self: Pin<&mut GWCProver>, state: &[u8]);
         fn set_transcript(self: Pin<&mut GWCProver>, state: &[u8]);
+        unsafe fn set_transcript_callbacks(
+            self: Pin<&mut GWCProver>,
+            callbacks: *mut TranscriptCallbacks,
+        );
         fn set_extended_domain(self: Pin<&mut GWCProver>, pk: &ProvingKey);
+        fn set_intra_proof_parallelism(self: Pin<&mut GWCProver>, intra_proof_parallelism: usize);
+        fn set_gpu_stages(self: Pin<&mut GWCProver>, gpu_stages: u8);
+        fn set_fft_chunk_log2(self: Pin<&mut GWCProver>, fft_chunk_log2: u32);
+        fn set_num_threads(self: Pin<&mut GWCProver>, num_threads: usize);
+        fn set_lagrange_basis_policy(self: Pin<&mut GWCProver>, policy: u8) -> bool;
+        fn set_record_gate_costs(self: Pin<&mut GWCProver>, record_gate_costs: bool);
         fn create_proof(
             self: Pin<&mut GWCProver>,
             key: Pin<&mut ProvingKey>,
@@ -193,6 +490,32 @@ pub mod ffi {
             challenges: &[Fr],
         );
         fn get_proof(self: &GWCProver) -> Vec<u8>;
+        fn get_transcript_state(self: &GWCProver) -> Vec<u8>;
+        fn pcs_params(&self) -> Vec<u8>;
+        fn gate_costs(&self) -> Vec<GateCost>;
+    }
+
+    unsafe extern "C++" {
+        include!("vendors/halo2/include/bn254_gwc_verifier.h");
+
+        fn commit_gwc_instance_columns(
+            transcript_type: u8,
+            k: u32,
+            pcs_params: &[u8],
+            pk: &ProvingKey,
+            column_lens: &[usize],
+            values: &[Fr],
+            commitments: &mut [G1Point2],
+        ) -> bool;
+        fn verify_gwc_proof_with_instance_commitments(
+            transcript_type: u8,
+            k: u32,
+            pcs_params: &[u8],
+            proof: &[u8],
+            pk: &ProvingKey,
+            num_circuits: usize,
+            commitments: &[G1Point2],
+        ) -> bool;
     }
 
     unsafe extern "C++" {
@@ -214,6 +537,7 @@ pub mod ffi {
         fn empty_evals(&self) -> UniquePtr<Evals>;
         fn empty_rational_evals(&self) -> UniquePtr<RationalEvals>;
         fn ifft(&self, evals: &Evals) -> UniquePtr<Poly>;
+        fn column_stats(&self, evals: &Evals) -> ColumnStats;
         fn batch_evaluate(
             &self,
             rational_evals: &[UniquePtr<RationalEvals>],
@@ -221,7 +545,20 @@ pub mod ffi {
         );
         fn set_rng(self: Pin<&mut SHPlonkProver>, state: &[u8]);
         fn set_transcript(self: Pin<&mut SHPlonkProver>, state: &[u8]);
+        unsafe fn set_transcript_callbacks(
+            self: Pin<&mut SHPlonkProver>,
+            callbacks: *mut TranscriptCallbacks,
+        );
         fn set_extended_domain(self: Pin<&mut SHPlonkProver>, pk: &ProvingKey);
+        fn set_intra_proof_parallelism(
+            self: Pin<&mut SHPlonkProver>,
+            intra_proof_parallelism: usize,
+        );
+        fn set_gpu_stages(self: Pin<&mut SHPlonkProver>, gpu_stages: u8);
+        fn set_fft_chunk_log2(self: Pin<&mut SHPlonkProver>, fft_chunk_log2: u32);
+        fn set_num_threads(self: Pin<&mut SHPlonkProver>, num_threads: usize);
+        fn set_lagrange_basis_policy(self: Pin<&mut SHPlonkProver>, policy: u8) -> bool;
+        fn set_record_gate_costs(self: Pin<&mut SHPlonkProver>, record_gate_costs: bool);
         fn create_proof(
             self: Pin<&mut SHPlonkProver>,
             key: Pin<&mut ProvingKey>,
@@ -229,28 +566,212 @@ pub mod ffi {
             advice_singles: &mut [AdviceSingle],
             challenges: &[Fr],
         );
+        fn add_opening(self: Pin<&mut SHPlonkProver>, poly: &Poly, point: &Fr);
+        fn create_opening_proof(self: Pin<&mut SHPlonkProver>);
         fn get_proof(self: &SHPlonkProver) -> Vec<u8>;
+        fn get_transcript_state(self: &SHPlonkProver) -> Vec<u8>;
+        fn pcs_params(&self) -> Vec<u8>;
+        fn gate_costs(&self) -> Vec<GateCost>;
+        fn allocation_stats(&self) -> AllocationStats;
+        fn scratch_stats(&self) -> ScratchStats;
+        fn release_scratch(self: Pin<&mut SHPlonkProver>);
+    }
+
+    unsafe extern "C++" {
+        include!("vendors/halo2/include/bn254_shplonk_verifier.h");
+
+        fn verify_shplonk_proof(
+            transcript_type: u8,
+            k: u32,
+            pcs_params: &[u8],
+            proof: &[u8],
+            pk: &ProvingKey,
+            num_circuits: usize,
+            column_lens: &[usize],
+            values: &[Fr],
+        ) -> bool;
+        fn verify_shplonk_proof_from_state(
+            transcript_type: u8,
+            k: u32,
+            pcs_params: &[u8],
+            proof: &[u8],
+            pk: &ProvingKey,
+            num_circuits: usize,
+            column_lens: &[usize],
+            values: &[Fr],
+            state: &[u8],
+            next_state: &mut Vec<u8>,
+        ) -> bool;
     }
 }
 
+// cxx makes opaque C++ types neither `Send` nor `Sync`, and so every wrapper
+// around them. These are the ones that are safe to move or share.
+//
+// `Evals`, `RationalEvals` and `Poly` own a vector of field elements and
+// nothing else, and their `&self` methods only read it.
+unsafe impl Send for ffi::Evals {}
+unsafe impl Sync for ffi::Evals {}
+unsafe impl Send for ffi::RationalEvals {}
+unsafe impl Sync for ffi::RationalEvals {}
+unsafe impl Send for ffi::Poly {}
+unsafe impl Sync for ffi::Poly {}
+// The proving key only changes in `transcript_repr_*()`, which take it
+// mutably.
+unsafe impl Send for ffi::ProvingKey {}
+unsafe impl Sync for ffi::ProvingKey {}
+// The transcript writers own their hasher state.
+unsafe impl Send for ffi::Blake2bWriter {}
+unsafe impl Sync for ffi::Blake2bWriter {}
+unsafe impl Send for ffi::PoseidonWriter {}
+unsafe impl Sync for ffi::PoseidonWriter {}
+unsafe impl Send for ffi::Sha256Writer {}
+unsafe impl Sync for ffi::Sha256Writer {}
+// A prover owns its SRS, domains, RNG and transcript, and the backend keeps no
+// global state, so a prover can move to another thread between proofs or
+// while a proof is paused. Its `&self` methods weren't audited for concurrent
+// calls, and the Rust side keeps its pools and metrics in cells anyway, so
+// provers aren't `Sync`; see [`crate::prover::ProofGuard`].
+unsafe impl Send for ffi::GWCProver {}
+unsafe impl Send for ffi::SHPlonkProver {}
+
+/// A transcript whose hasher state can be handed to the backend, which
+/// continues the transcript from there. The state is versioned, see
+/// [`crate::state::STATE_VERSION`]. Wrappers like
+/// [`crate::transcript::RecordingTranscript`] forward `state()` to the
+/// transcript they wrap.
+#[cfg(feature = "halo2")]
 pub trait TranscriptWriteState<C: CurveAffine, E: EncodedChallenge<C>>:
     TranscriptWrite<C, E>
 {
+    /// The type of the transcript, which has to be the one the prover was
+    /// created with, see [`TachyonProver::transcript_type()`].
+    const TRANSCRIPT_TYPE: TranscriptType;
+
     fn state(&self) -> Vec<u8>;
+
+    /// Writes `points` like `write_point()` on each in turn. The transcripts
+    /// of the backend absorb them in one call, which hashes the same input,
+    /// so the challenges are the same either way. A point at infinity fails
+    /// the whole batch before any of it is written, but the writer failing
+    /// partway may leave the points before it written.
+    fn write_points(&mut self, points: &[C]) -> io::Result<()> {
+        for point in points {
+            transcript_coordinates(point)?;
+        }
+        points.iter().try_for_each(|point| self.write_point(*point))
+    }
+
+    /// Absorbs `scalars` like `common_scalar()` on each in turn, see
+    /// [`TranscriptWriteState::write_points()`].
+    fn common_scalars(&mut self, scalars: &[C::Scalar]) -> io::Result<()> {
+        scalars
+            .iter()
+            .try_for_each(|scalar| self.common_scalar(*scalar))
+    }
+
+    /// Absorbs a G2 point without writing it to the proof, e.g, for a KZG
+    /// degree check. Each transcript documents its encoding. Those without
+    /// one fail, as does the point at infinity.
+    fn common_g2(&mut self, point: G2Affine) -> io::Result<()> {
+        let _ = point;
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{:?} transcripts can't absorb G2 points",
+                Self::TRANSCRIPT_TYPE
+            ),
+        ))
+    }
+}
+
+/// The coordinates of `point`, which fails for the point at infinity, since
+/// it can't be absorbed.
+#[cfg(feature = "halo2")]
+fn transcript_coordinates<C: CurveAffine>(point: &C) -> io::Result<Coordinates<C>> {
+    Option::from(point.coordinates()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            "cannot write points at infinity to the transcript",
+        )
+    })
+}
+
+/// A transcript that can be copied, sponge and written bytes alike, so that
+/// two proofs can continue from the same point independently, e.g, in
+/// [`crate::race::race_prove()`]. It isn't part of
+/// [`TranscriptWriteState`], which is also implemented by wrappers that only
+/// borrow their transcript.
+#[cfg(feature = "halo2")]
+pub trait TranscriptFork<C: CurveAffine, E: EncodedChallenge<C>>:
+    TranscriptWriteState<C, E> + Sized
+{
+    fn fork(&self) -> Self;
+}
+
+/// A transcript that can be started from a state, as written by
+/// [`TranscriptWriteState::state()`] or
+/// [`TachyonProver::transcript_state()`], into an output buffer of its own, so
+/// that a proof can continue the transcript of the one before it, see
+/// [`crate::shared::create_proofs_shared_transcript()`].
+#[cfg(feature = "halo2")]
+pub trait TranscriptResume<W: Write, C: CurveAffine, E: EncodedChallenge<C>>:
+    TranscriptWriteState<C, E> + Sized
+{
+    /// Fails if `state` isn't a state of a transcript of this type.
+    fn resume(writer: W, state: &[u8]) -> Result<Self, StateError>;
+
+    /// Returns the output buffer, like `finalize()`.
+    fn into_writer(self) -> W;
 }
 
+#[cfg(feature = "halo2")]
 pub struct Blake2bWrite<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
-    state: cxx::UniquePtr<ffi::Blake2bWriter>,
+    state: UniquePtr<ffi::Blake2bWriter>,
     writer: W,
     _marker: PhantomData<(W, C, E)>,
 }
 
+#[cfg(feature = "halo2")]
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> fmt::Debug for Blake2bWrite<W, C, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blake2bWrite").finish_non_exhaustive()
+    }
+}
+
+// Prefix to a prover's message soliciting a challenge
+#[cfg(feature = "halo2")]
+const BLAKE2B_PREFIX_CHALLENGE: u8 = 0;
+// Prefix to a prover's message containing a curve point
+#[cfg(feature = "halo2")]
+const BLAKE2B_PREFIX_POINT: u8 = 1;
+// Prefix to a prover's message containing a scalar
+#[cfg(feature = "halo2")]
+const BLAKE2B_PREFIX_SCALAR: u8 = 2;
+
+#[cfg(feature = "halo2")]
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> Blake2bWrite<W, C, E> {
+    /// Appends the bytes `point` is absorbed as to `input`.
+    fn point_input(input: &mut Vec<u8>, point: &C) -> io::Result<()> {
+        let coords = transcript_coordinates(point)?;
+        input.push(BLAKE2B_PREFIX_POINT);
+        input.extend_from_slice(coords.x().to_repr().as_ref());
+        input.extend_from_slice(coords.y().to_repr().as_ref());
+        Ok(())
+    }
+
+    /// Appends the bytes `scalar` is absorbed as to `input`.
+    fn scalar_input(input: &mut Vec<u8>, scalar: &C::Scalar) {
+        input.push(BLAKE2B_PREFIX_SCALAR);
+        input.extend_from_slice(scalar.to_repr().as_ref());
+    }
+}
+
+#[cfg(feature = "halo2")]
 impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
     for Blake2bWrite<W, C, Challenge255<C>>
 {
     fn squeeze_challenge(&mut self) -> Challenge255<C> {
-        // Prefix to a prover's message soliciting a challenge
-        const BLAKE2B_PREFIX_CHALLENGE: u8 = 0;
         self.state.pin_mut().update(&[BLAKE2B_PREFIX_CHALLENGE]);
         let mut result: [u8; 64] = [0; 64];
         self.state.pin_mut().finalize(&mut result);
@@ -258,30 +779,21 @@ impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
-        // Prefix to a prover's message containing a curve point
-        const BLAKE2B_PREFIX_POINT: u8 = 1;
-        self.state.pin_mut().update(&[BLAKE2B_PREFIX_POINT]);
-        let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "cannot write points at infinity to the transcript",
-            )
-        })?;
-        self.state.pin_mut().update(coords.x().to_repr().as_ref());
-        self.state.pin_mut().update(coords.y().to_repr().as_ref());
-
+        let mut input = vec![];
+        Self::point_input(&mut input, &point)?;
+        self.state.pin_mut().update(&input);
         Ok(())
     }
 
     fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
-        // Prefix to a prover's message containing a scalar
-        const BLAKE2B_PREFIX_SCALAR: u8 = 2;
-        self.state.pin_mut().update(&[BLAKE2B_PREFIX_SCALAR]);
-        self.state.pin_mut().update(scalar.to_repr().as_ref());
+        let mut input = vec![];
+        Self::scalar_input(&mut input, &scalar);
+        self.state.pin_mut().update(&input);
         Ok(())
     }
 }
 
+#[cfg(feature = "halo2")]
 impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
     for Blake2bWrite<W, C, Challenge255<C>>
 {
@@ -298,14 +810,81 @@ impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
     }
 }
 
+#[cfg(feature = "halo2")]
 impl<W: Write, C: CurveAffine> TranscriptWriteState<C, Challenge255<C>>
     for Blake2bWrite<W, C, Challenge255<C>>
 {
+    const TRANSCRIPT_TYPE: TranscriptType = TranscriptType::Blake2b;
+
     fn state(&self) -> Vec<u8> {
-        self.state.state()
+        versioned(self.state.state())
+    }
+
+    fn write_points(&mut self, points: &[C]) -> io::Result<()> {
+        let mut input = vec![];
+        let mut proof = vec![];
+        for point in points {
+            Self::point_input(&mut input, point)?;
+            proof.extend_from_slice(point.to_bytes().as_ref());
+        }
+        self.state.pin_mut().update(&input);
+        self.writer.write_all(&proof)
+    }
+
+    fn common_scalars(&mut self, scalars: &[C::Scalar]) -> io::Result<()> {
+        let mut input = vec![];
+        for scalar in scalars {
+            Self::scalar_input(&mut input, scalar);
+        }
+        self.state.pin_mut().update(&input);
+        Ok(())
+    }
+
+    /// Absorbs `point` like halo2's `Blake2bWrite<_, G2Affine, _>` absorbs
+    /// any point: the point prefix, then x and y as `Fq2::to_repr()` writes
+    /// them, c0 then c1, each 32 bytes little-endian.
+    fn common_g2(&mut self, point: G2Affine) -> io::Result<()> {
+        let coords = transcript_coordinates(&point)?;
+        let mut input = vec![BLAKE2B_PREFIX_POINT];
+        input.extend_from_slice(coords.x().to_repr().as_ref());
+        input.extend_from_slice(coords.y().to_repr().as_ref());
+        self.state.pin_mut().update(&input);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<W: Write + Clone, C: CurveAffine> TranscriptFork<C, Challenge255<C>>
+    for Blake2bWrite<W, C, Challenge255<C>>
+{
+    fn fork(&self) -> Self {
+        Blake2bWrite {
+            state: self.state.clone(),
+            writer: self.writer.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<W: Write, C: CurveAffine> TranscriptResume<W, C, Challenge255<C>>
+    for Blake2bWrite<W, C, Challenge255<C>>
+{
+    fn resume(writer: W, state: &[u8]) -> Result<Self, StateError> {
+        let body = state_body(StateKind::Transcript(TranscriptType::Blake2b), state)?;
+        Ok(Blake2bWrite {
+            state: ffi::new_blake2b_writer_from_state(body),
+            writer,
+            _marker: PhantomData,
+        })
+    }
+
+    fn into_writer(self) -> W {
+        self.writer
     }
 }
 
+#[cfg(feature = "halo2")]
 impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, Challenge255<C>>
     for Blake2bWrite<W, C, Challenge255<C>>
 {
@@ -325,18 +904,28 @@ impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, Challenge255<C>>
     }
 }
 
+#[cfg(feature = "halo2")]
 pub struct PoseidonWrite<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
-    state: cxx::UniquePtr<ffi::PoseidonWriter>,
+    state: UniquePtr<ffi::PoseidonWriter>,
     writer: W,
     _marker: PhantomData<(W, C, E)>,
 }
 
+#[cfg(feature = "halo2")]
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> fmt::Debug for PoseidonWrite<W, C, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoseidonWrite").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "halo2")]
 fn field_to_bn<F: FieldExt>(f: &F) -> BigUint {
     BigUint::from_bytes_le(f.to_repr().as_ref())
 }
 
 /// Input a big integer `bn`, compute a field element `f`
 /// such that `f == bn % F::MODULUS`.
+#[cfg(feature = "halo2")]
 fn bn_to_field<F: FieldExt>(bn: &BigUint) -> F {
     let mut buf = bn.to_bytes_le();
     buf.resize(64, 0u8);
@@ -348,12 +937,14 @@ fn bn_to_field<F: FieldExt>(bn: &BigUint) -> F {
 
 /// Input a base field element `b`, output a scalar field
 /// element `s` s.t. `s == b % ScalarField::MODULUS`
+#[cfg(feature = "halo2")]
 fn base_to_scalar<C: CurveAffine>(base: &C::Base) -> C::Scalar {
     let bn = field_to_bn(base);
     // bn_to_field will perform a mod reduction
     bn_to_field(&bn)
 }
 
+#[cfg(feature = "halo2")]
 impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
     for PoseidonWrite<W, C, Challenge255<C>>
 {
@@ -367,40 +958,19 @@ impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
-        let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "cannot write points at infinity to the transcript",
-            )
-        })?;
-        let x = coords.x();
-        let y = coords.y();
-        let slice = &[base_to_scalar::<C>(x), base_to_scalar::<C>(y)];
-        let bytes = std::mem::size_of::<C::Scalar>() * 2;
-        unsafe {
-            self.state.pin_mut().update(std::slice::from_raw_parts(
-                slice.as_ptr() as *const u8,
-                bytes,
-            ));
-        }
-
+        let mut input = vec![];
+        Self::point_input(&mut input, &point)?;
+        self.absorb(&input);
         Ok(())
     }
 
     fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
-        let slice = &[scalar];
-        let bytes = std::mem::size_of::<C::Scalar>();
-        unsafe {
-            self.state.pin_mut().update(std::slice::from_raw_parts(
-                slice.as_ptr() as *const u8,
-                bytes,
-            ));
-        }
-
+        self.absorb(&[scalar]);
         Ok(())
     }
 }
 
+#[cfg(feature = "halo2")]
 impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
     for PoseidonWrite<W, C, Challenge255<C>>
 {
@@ -417,7 +987,26 @@ impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
     }
 }
 
+#[cfg(feature = "halo2")]
 impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> PoseidonWrite<W, C, E> {
+    /// Appends the scalars `point` is absorbed as to `input`.
+    fn point_input(input: &mut Vec<C::Scalar>, point: &C) -> io::Result<()> {
+        let coords = transcript_coordinates(point)?;
+        input.push(base_to_scalar::<C>(coords.x()));
+        input.push(base_to_scalar::<C>(coords.y()));
+        Ok(())
+    }
+
+    fn absorb(&mut self, scalars: &[C::Scalar]) {
+        let bytes = std::mem::size_of::<C::Scalar>() * scalars.len();
+        unsafe {
+            self.state.pin_mut().update(std::slice::from_raw_parts(
+                scalars.as_ptr() as *const u8,
+                bytes,
+            ));
+        }
+    }
+
     /// Initialize a transcript given an output buffer.
     pub fn init(writer: W) -> Self {
         PoseidonWrite {
@@ -435,20 +1024,92 @@ impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> PoseidonWrite<W, C, E> {
     }
 }
 
+#[cfg(feature = "halo2")]
 impl<W: Write, C: CurveAffine> TranscriptWriteState<C, Challenge255<C>>
     for PoseidonWrite<W, C, Challenge255<C>>
 {
+    const TRANSCRIPT_TYPE: TranscriptType = TranscriptType::Poseidon;
+
     fn state(&self) -> Vec<u8> {
-        self.state.state()
+        versioned(self.state.state())
+    }
+
+    fn write_points(&mut self, points: &[C]) -> io::Result<()> {
+        let mut input = vec![];
+        let mut proof = vec![];
+        for point in points {
+            Self::point_input(&mut input, point)?;
+            proof.extend_from_slice(point.to_bytes().as_ref());
+        }
+        self.absorb(&input);
+        self.writer.write_all(&proof)
+    }
+
+    fn common_scalars(&mut self, scalars: &[C::Scalar]) -> io::Result<()> {
+        self.absorb(scalars);
+        Ok(())
+    }
+
+    /// Absorbs `point` as four scalars, x.c0, x.c1, y.c0 and y.c1, each
+    /// reduced into the scalar field like the coordinates of a G1 point.
+    fn common_g2(&mut self, point: G2Affine) -> io::Result<()> {
+        let coords = transcript_coordinates(&point)?;
+        let (x, y) = (coords.x(), coords.y());
+        let input = [&x.c0, &x.c1, &y.c0, &y.c1]
+            .iter()
+            .map(|base| bn_to_field::<C::Scalar>(&field_to_bn(*base)))
+            .collect::<Vec<_>>();
+        self.absorb(&input);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<W: Write + Clone, C: CurveAffine> TranscriptFork<C, Challenge255<C>>
+    for PoseidonWrite<W, C, Challenge255<C>>
+{
+    fn fork(&self) -> Self {
+        PoseidonWrite {
+            state: self.state.clone(),
+            writer: self.writer.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<W: Write, C: CurveAffine> TranscriptResume<W, C, Challenge255<C>>
+    for PoseidonWrite<W, C, Challenge255<C>>
+{
+    fn resume(writer: W, state: &[u8]) -> Result<Self, StateError> {
+        let body = state_body(StateKind::Transcript(TranscriptType::Poseidon), state)?;
+        Ok(PoseidonWrite {
+            state: ffi::new_poseidon_writer_from_state(body),
+            writer,
+            _marker: PhantomData,
+        })
+    }
+
+    fn into_writer(self) -> W {
+        self.writer
     }
 }
 
+#[cfg(feature = "halo2")]
 pub struct Sha256Write<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
-    state: cxx::UniquePtr<ffi::Sha256Writer>,
+    state: UniquePtr<ffi::Sha256Writer>,
     writer: W,
     _marker: PhantomData<(W, C, E)>,
 }
 
+#[cfg(feature = "halo2")]
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> fmt::Debug for Sha256Write<W, C, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sha256Write").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "halo2")]
 impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
     for Sha256Write<W, C, Challenge255<C>>
 {
@@ -467,48 +1128,55 @@ impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
-        const SHA256_PREFIX_POINT: u8 = 1;
-        self.state.pin_mut().update(&[0u8; 31]);
-        self.state.pin_mut().update(&[SHA256_PREFIX_POINT]);
-        let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "cannot write points at infinity to the transcript",
-            )
-        })?;
-
-        for base in &[coords.x(), coords.y()] {
-            let mut buf = base.to_repr().as_ref().to_vec();
-            buf.resize(32, 0u8);
-            buf.reverse();
-            self.state.pin_mut().update(buf.as_slice());
-        }
-
+        let mut input = vec![];
+        Self::point_input(&mut input, &point)?;
+        self.state.pin_mut().update(&input);
         Ok(())
     }
 
     fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
-        const SHA256_PREFIX_SCALAR: u8 = 2;
-        self.state.pin_mut().update(&[0u8; 31]);
-        self.state.pin_mut().update(&[SHA256_PREFIX_SCALAR]);
+        let mut input = vec![];
+        Self::scalar_input(&mut input, &scalar);
+        self.state.pin_mut().update(&input);
+        Ok(())
+    }
+}
 
-        {
-            let mut buf = scalar.to_repr().as_ref().to_vec();
-            buf.resize(32, 0u8);
-            buf.reverse();
-            self.state.pin_mut().update(buf.as_slice());
-        }
+#[cfg(feature = "halo2")]
+const SHA256_PREFIX_POINT: u8 = 1;
+#[cfg(feature = "halo2")]
+const SHA256_PREFIX_SCALAR: u8 = 2;
 
+#[cfg(feature = "halo2")]
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> Sha256Write<W, C, E> {
+    /// Appends `repr` as a big-endian 32-byte word to `input`.
+    fn word_input(input: &mut Vec<u8>, repr: &[u8]) {
+        let mut word = repr.to_vec();
+        word.resize(32, 0u8);
+        word.reverse();
+        input.extend_from_slice(&word);
+    }
+
+    /// Appends the bytes `point` is absorbed as to `input`: its prefix as a
+    /// word followed by its coordinates.
+    fn point_input(input: &mut Vec<u8>, point: &C) -> io::Result<()> {
+        let coords = transcript_coordinates(point)?;
+        input.extend_from_slice(&[0u8; 31]);
+        input.push(SHA256_PREFIX_POINT);
+        Self::word_input(input, coords.x().to_repr().as_ref());
+        Self::word_input(input, coords.y().to_repr().as_ref());
         Ok(())
     }
-}
 
-impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
-    for Sha256Write<W, C, Challenge255<C>>
-{
-    fn write_point(&mut self, point: C) -> io::Result<()> {
-        self.common_point(point)?;
+    /// Appends the bytes `scalar` is absorbed as to `input`.
+    fn scalar_input(input: &mut Vec<u8>, scalar: &C::Scalar) {
+        input.extend_from_slice(&[0u8; 31]);
+        input.push(SHA256_PREFIX_SCALAR);
+        Self::word_input(input, scalar.to_repr().as_ref());
+    }
 
+    /// Appends the bytes `point` is written to the proof as to `proof`.
+    fn point_proof(proof: &mut Vec<u8>, point: &C) {
         let coords = point.coordinates();
         let x = coords
             .map(|v| *v.x())
@@ -516,12 +1184,20 @@ impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
         let y = coords
             .map(|v| *v.y())
             .unwrap_or(<C as CurveAffine>::Base::zero());
+        proof.extend_from_slice(x.to_repr().as_ref());
+        proof.extend_from_slice(y.to_repr().as_ref());
+    }
+}
 
-        for base in &[&x, &y] {
-            self.writer.write_all(base.to_repr().as_ref())?;
-        }
-
-        Ok(())
+#[cfg(feature = "halo2")]
+impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
+    for Sha256Write<W, C, Challenge255<C>>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        let mut proof = vec![];
+        Self::point_proof(&mut proof, &point);
+        self.writer.write_all(&proof)
     }
 
     fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
@@ -532,45 +1208,211 @@ impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
     }
 }
 
+#[cfg(feature = "halo2")]
 impl<W: Write, C: CurveAffine> TranscriptWriteState<C, Challenge255<C>>
     for Sha256Write<W, C, Challenge255<C>>
 {
+    const TRANSCRIPT_TYPE: TranscriptType = TranscriptType::Sha256;
+
     fn state(&self) -> Vec<u8> {
-        self.state.state()
+        versioned(self.state.state())
     }
-}
 
-impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> Sha256Write<W, C, E> {
-    /// Initialize a transcript given an output buffer.
-    pub fn init(writer: W) -> Self {
-        Sha256Write {
-            state: ffi::new_sha256_writer(),
-            writer,
-            _marker: PhantomData,
+    fn write_points(&mut self, points: &[C]) -> io::Result<()> {
+        let mut input = vec![];
+        let mut proof = vec![];
+        for point in points {
+            Self::point_input(&mut input, point)?;
+            Self::point_proof(&mut proof, point);
         }
+        self.state.pin_mut().update(&input);
+        self.writer.write_all(&proof)
     }
 
-    /// Conclude the interaction and return the output buffer (writer).
-    pub fn finalize(self) -> W {
-        // TODO: handle outstanding scalars?
-        // See https://github.com/zcash/halo2/issues/138.
-        self.writer
+    fn common_scalars(&mut self, scalars: &[C::Scalar]) -> io::Result<()> {
+        let mut input = vec![];
+        for scalar in scalars {
+            Self::scalar_input(&mut input, scalar);
+        }
+        self.state.pin_mut().update(&input);
+        Ok(())
     }
-}
 
-pub struct ProvingKey<C: CurveAffine> {
-    inner: cxx::UniquePtr<ffi::ProvingKey>,
-    _marker: PhantomData<C>,
+    /// Absorbs `point` as the point prefix as a word, followed by x.c1, x.c0,
+    /// y.c1 and y.c0 as big-endian words. That is the order the EVM's pairing
+    /// precompile (EIP-197) takes G2 points in.
+    fn common_g2(&mut self, point: G2Affine) -> io::Result<()> {
+        let coords = transcript_coordinates(&point)?;
+        let (x, y) = (coords.x(), coords.y());
+        let mut input = vec![0u8; 31];
+        input.push(SHA256_PREFIX_POINT);
+        for base in [&x.c1, &x.c0, &y.c1, &y.c0] {
+            Self::word_input(&mut input, base.to_repr().as_ref());
+        }
+        self.state.pin_mut().update(&input);
+        Ok(())
+    }
 }
 
-impl<C: CurveAffine> ProvingKey<C> {
-    pub fn from(data: &[u8]) -> ProvingKey<C> {
-        ProvingKey {
-            inner: ffi::new_proving_key(data),
+#[cfg(feature = "halo2")]
+impl<W: Write + Clone, C: CurveAffine> TranscriptFork<C, Challenge255<C>>
+    for Sha256Write<W, C, Challenge255<C>>
+{
+    fn fork(&self) -> Self {
+        Sha256Write {
+            state: self.state.clone(),
+            writer: self.writer.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<W: Write, C: CurveAffine> TranscriptResume<W, C, Challenge255<C>>
+    for Sha256Write<W, C, Challenge255<C>>
+{
+    fn resume(writer: W, state: &[u8]) -> Result<Self, StateError> {
+        let body = state_body(StateKind::Transcript(TranscriptType::Sha256), state)?;
+        Ok(Sha256Write {
+            state: ffi::new_sha256_writer_from_state(body),
+            writer,
+            _marker: PhantomData,
+        })
+    }
+
+    fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> Sha256Write<W, C, E> {
+    /// Initialize a transcript given an output buffer.
+    pub fn init(writer: W) -> Self {
+        Sha256Write {
+            state: ffi::new_sha256_writer(),
+            writer,
             _marker: PhantomData,
         }
     }
 
+    /// Conclude the interaction and return the output buffer (writer).
+    pub fn finalize(self) -> W {
+        // TODO: handle outstanding scalars?
+        // See https://github.com/zcash/halo2/issues/138.
+        self.writer
+    }
+}
+
+#[cfg(feature = "halo2")]
+pub struct ProvingKey<C: CurveAffine> {
+    inner: UniquePtr<ffi::ProvingKey>,
+    metadata: Metadata,
+    _marker: PhantomData<C>,
+}
+
+#[cfg(feature = "halo2")]
+impl<C: CurveAffine> fmt::Debug for ProvingKey<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProvingKey")
+            .field("num_advice_columns", &self.num_advice_columns())
+            .field("num_fixed_columns", &self.num_fixed_columns())
+            .field("num_instance_columns", &self.num_instance_columns())
+            .field("num_challenges", &self.num_challenges())
+            .field("num_lookups", &self.num_lookups())
+            .field("degree", &self.degree())
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<C: CurveAffine> ProvingKey<C> {
+    /// Loads a key written by halo2's `ProvingKey::write()`, optionally
+    /// preceded by a metadata section from `write_with_metadata()`. Fails if
+    /// the metadata section is malformed. The key behind it is still read by
+    /// the backend, which aborts on a malformed key.
+    pub fn try_from(data: &[u8]) -> io::Result<ProvingKey<C>> {
+        ffi_trace!("new_proving_key", "len={}", data.len());
+        let (metadata, data) = split_metadata(data)?;
+        Ok(ProvingKey {
+            inner: ffi::new_proving_key(data),
+            metadata,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Same as [`ProvingKey::try_from()`] with the key stored as `key` in
+    /// `store`. The backend parses a key from one buffer, so the key is read
+    /// into one, but only once.
+    pub fn read(store: &dyn ArtifactStore, key: &str) -> io::Result<ProvingKey<C>> {
+        let mut data = vec![];
+        store.get(key)?.read_to_end(&mut data)?;
+        Self::try_from(&data)
+    }
+
+    /// Same as [`ProvingKey::try_from()`], but also fails with
+    /// [`TachyonError::SelectorCompressionMismatch`] unless keygen folded the
+    /// selectors of `ConcreteCircuit` into fixed columns as `compression`
+    /// says, see [`check_selector_mapping()`].
+    pub fn from_checked<ConcreteCircuit: Circuit<C::Scalar>>(
+        data: &[u8],
+        compression: SelectorCompression,
+    ) -> Result<ProvingKey<C>, TachyonError> {
+        let pk = Self::try_from(data).map_err(TachyonError::MalformedKey)?;
+        check_selector_mapping::<_, ConcreteCircuit>(&pk, compression)?;
+        Ok(pk)
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Replaces the metadata that `write_with_metadata(writer, pk.metadata(), ..)`
+    /// puts in front of the key.
+    pub fn set_metadata(&mut self, metadata: Metadata) {
+        self.metadata = metadata;
+    }
+
+    /// Returns the circuit id stored with
+    /// [`crate::circuit_id::circuit_id_to_metadata()`], if any.
+    pub fn circuit_id(&self) -> Option<[u8; 32]> {
+        circuit_id_from_metadata(&self.metadata)
+    }
+
+    /// Returns the multi-open scheme stored with
+    /// [`crate::proving_key::multi_open_scheme_to_metadata()`], if any.
+    pub fn multi_open_scheme(&self) -> Option<MultiOpenScheme> {
+        multi_open_scheme_from_metadata(&self.metadata)
+    }
+
+    /// Returns the instance absorption stored with
+    /// [`crate::proving_key::instance_absorption_to_metadata()`], or
+    /// [`InstanceAbsorption::PerValue`].
+    pub fn instance_absorption(&self) -> InstanceAbsorption {
+        instance_absorption_from_metadata(&self.metadata)
+    }
+
+    /// Returns the advice absorb order stored with
+    /// [`crate::proving_key::advice_absorb_order_to_metadata()`], or
+    /// [`AbsorbOrder::PhaseThenColumn`].
+    pub fn advice_absorb_order(&self) -> AbsorbOrder {
+        advice_absorb_order_from_metadata(&self.metadata)
+    }
+
+    /// Returns the vk hash mode stored with
+    /// [`crate::vk_hash::vk_hash_mode_to_metadata()`], or
+    /// [`VkHashMode::Blake2bField`].
+    pub fn vk_hash_mode(&self) -> VkHashMode {
+        vk_hash_mode_from_metadata(&self.metadata)
+    }
+
+    /// Returns the name `column` was annotated with, if the key was written
+    /// with [`crate::annotation::annotations_to_metadata()`].
+    pub fn column_annotation(&self, column: &Column<Any>) -> Option<&str> {
+        self.metadata.get(&metadata_key(column)).map(String::as_str)
+    }
+
     // NOTE(chokobole): We name this as plural since it contains multi phases.
     // pk.vk.cs.advice_column_phase
     pub fn advice_column_phases(&self) -> Vec<sealed::Phase> {
@@ -608,6 +1450,16 @@ impl<C: CurveAffine> ProvingKey<C> {
         constants
     }
 
+    // pk.vk.cs.degree()
+    pub fn degree(&self) -> usize {
+        self.inner.degree()
+    }
+
+    // pk.vk.domain.extended_k()
+    pub fn extended_k(&self, k: u32) -> u32 {
+        self.inner.extended_k(k)
+    }
+
     // pk.vk.cs.num_advice_columns
     pub fn num_advice_columns(&self) -> usize {
         self.inner.num_advice_columns()
@@ -618,11 +1470,42 @@ impl<C: CurveAffine> ProvingKey<C> {
         self.inner.num_challenges()
     }
 
+    // pk.vk.cs.num_fixed_columns
+    pub fn num_fixed_columns(&self) -> usize {
+        self.inner.num_fixed_columns()
+    }
+
     // pk.vk.cs.num_instance_columns
     pub fn num_instance_columns(&self) -> usize {
         self.inner.num_instance_columns()
     }
 
+    /// Fails if the key has no advice column `index`.
+    pub fn advice_index(&self, index: usize) -> Result<AdviceColumnIndex, ColumnIndexError> {
+        check_index(ColumnKind::Advice, index, self.num_advice_columns()).map(AdviceColumnIndex)
+    }
+
+    /// Fails if the key has no fixed column `index`.
+    pub fn fixed_index(&self, index: usize) -> Result<FixedColumnIndex, ColumnIndexError> {
+        check_index(ColumnKind::Fixed, index, self.num_fixed_columns()).map(FixedColumnIndex)
+    }
+
+    /// Fails if the key has no instance column `index`.
+    pub fn instance_index(&self, index: usize) -> Result<InstanceColumnIndex, ColumnIndexError> {
+        check_index(ColumnKind::Instance, index, self.num_instance_columns())
+            .map(InstanceColumnIndex)
+    }
+
+    // pk.vk.cs.lookups.len()
+    pub fn num_lookups(&self) -> usize {
+        self.inner.num_lookups()
+    }
+
+    // pk.vk.cs.permutation.columns.len()
+    pub fn num_permutation_columns(&self) -> usize {
+        self.inner.num_permutation_columns()
+    }
+
     // pk.vk.cs.phases()
     pub fn phases(&self) -> Vec<sealed::Phase> {
         unsafe {
@@ -631,11 +1514,107 @@ impl<C: CurveAffine> ProvingKey<C> {
         }
     }
 
-    // pk.vk.transcript_repr
+    /// Returns the fixed column keygen folded every selector into, indexed by
+    /// selector.
+    // pk.vk.cs.selector_map
+    pub fn selector_mapping(&self) -> Vec<Column<Fixed>> {
+        self.inner
+            .selector_map()
+            .iter()
+            .map(|index| Column {
+                index: *index,
+                column_type: Fixed,
+            })
+            .collect()
+    }
+
+    /// Returns the column of every fixed query, i.e, the fixed columns the
+    /// gates and lookups of the key refer to.
+    // pk.vk.cs.fixed_queries
+    pub fn fixed_query_columns(&self) -> Vec<Column<Fixed>> {
+        self.inner
+            .fixed_query_columns()
+            .iter()
+            .map(|index| Column {
+                index: *index,
+                column_type: Fixed,
+            })
+            .collect()
+    }
+
+    /// Returns the column and rotation of every advice query, in the order the
+    /// proof evaluates them.
+    // pk.vk.cs.advice_queries
+    pub fn advice_queries(&self) -> Vec<(AdviceColumnIndex, Rotation)> {
+        self.inner
+            .advice_queries()
+            .iter()
+            .map(|query| (AdviceColumnIndex(query.index), Rotation(query.rotation)))
+            .collect()
+    }
+
+    /// Returns the column and rotation of every instance query, in the order
+    /// the proof evaluates them.
+    // pk.vk.cs.instance_queries
+    pub fn instance_queries(&self) -> Vec<(InstanceColumnIndex, Rotation)> {
+        self.inner
+            .instance_queries()
+            .iter()
+            .map(|query| (InstanceColumnIndex(query.index), Rotation(query.rotation)))
+            .collect()
+    }
+
+    /// Returns the column and rotation of every fixed query, in the order the
+    /// proof evaluates them.
+    // pk.vk.cs.fixed_queries
+    pub fn fixed_queries(&self) -> Vec<(FixedColumnIndex, Rotation)> {
+        self.inner
+            .fixed_queries()
+            .iter()
+            .map(|query| (FixedColumnIndex(query.index), Rotation(query.rotation)))
+            .collect()
+    }
+
+    /// Returns every query the multi-open argument of a proof of one circuit
+    /// with this key opens with `scheme`, in the order it opens them. See
+    /// [`crate::opening::opening_queries()`].
+    pub fn opening_queries(&self, scheme: MultiOpenScheme) -> Vec<OpeningQuery> {
+        opening_queries(self, 1, scheme)
+    }
+
+    /// Returns every phase together with the advice columns assigned and the
+    /// challenges squeezed in it.
+    pub fn iter_phases(
+        &self,
+    ) -> impl Iterator<Item = (PhaseId, Vec<ColumnIndex>, Vec<ChallengeId>)> {
+        let advice_column_phases = self.advice_column_phases();
+        let challenge_phases = self.challenge_phases();
+        self.phases().into_iter().map(move |phase| {
+            let columns = advice_column_phases
+                .iter()
+                .enumerate()
+                .filter(|(_, column_phase)| **column_phase == phase)
+                .map(|(index, _)| index)
+                .collect();
+            let challenges = challenge_phases
+                .iter()
+                .enumerate()
+                .filter(|(_, challenge_phase)| **challenge_phase == phase)
+                .map(|(index, _)| ChallengeId(index))
+                .collect();
+            (PhaseId::from(phase), columns, challenges)
+        })
+    }
+
+    // pk.vk.transcript_repr, or the digest stored with
+    // `vk_hash_mode_to_metadata()` if the key isn't hashed like halo2.
     pub fn transcript_repr_gwc<Scheme: CommitmentScheme>(
         &mut self,
         prover: &GWCProver<Scheme>,
     ) -> C::Scalar {
+        if let Some(repr) = vk_transcript_repr_from_metadata(&self.metadata) {
+            return repr;
+        }
         *unsafe {
             std::mem::transmute::<_, Box<C::Scalar>>(
                 self.inner.pin_mut().transcript_repr_gwc(&prover.inner),
@@ -647,6 +1626,9 @@ impl<C: CurveAffine> ProvingKey<C> {
         &mut self,
         prover: &SHPlonkProver<Scheme>,
     ) -> C::Scalar {
+        if let Some(repr) = vk_transcript_repr_from_metadata(&self.metadata) {
+            return repr;
+        }
         *unsafe {
             std::mem::transmute::<_, Box<C::Scalar>>(
                 self.inner.pin_mut().transcript_repr_shplonk(&prover.inner),
@@ -655,8 +1637,19 @@ impl<C: CurveAffine> ProvingKey<C> {
     }
 }
 
+#[cfg(feature = "halo2")]
+impl From<ffi::ColumnStats> for ColumnStats {
+    fn from(stats: ffi::ColumnStats) -> Self {
+        ColumnStats {
+            num_nonzero_rows: stats.num_nonzero_rows,
+            highest_nonzero_coeff: stats.num_coeffs.checked_sub(1),
+            entropy: stats.entropy,
+        }
+    }
+}
+
 pub struct Evals {
-    inner: cxx::UniquePtr<ffi::Evals>,
+    inner: UniquePtr<ffi::Evals>,
 }
 
 impl Evals {
@@ -664,7 +1657,7 @@ impl Evals {
         Self::new(ffi::zero_evals())
     }
 
-    pub fn new(inner: cxx::UniquePtr<ffi::Evals>) -> Evals {
+    pub fn new(inner: UniquePtr<ffi::Evals>) -> Evals {
         Evals { inner }
     }
 
@@ -672,10 +1665,67 @@ impl Evals {
         self.inner.len()
     }
 
+    pub fn get_value(&self, idx: usize) -> halo2curves::bn256::Fr {
+        *unsafe { std::mem::transmute::<_, Box<halo2curves::bn256::Fr>>(self.inner.get_value(idx)) }
+    }
+
     pub fn set_value(&mut self, idx: usize, fr: &halo2curves::bn256::Fr) {
         let cpp_fr = unsafe { std::mem::transmute::<_, &Fr>(fr) };
         self.inner.pin_mut().set_value(idx, cpp_fr)
     }
+
+    /// Copies the values into a halo2 polynomial over the domain of the same
+    /// size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values isn't a power of two, which it is for
+    /// the evals of a prover.
+    #[cfg(feature = "halo2")]
+    pub fn to_lagrange_polynomial(&self) -> Polynomial<halo2curves::bn256::Fr, LagrangeCoeff> {
+        let n = self.len();
+        assert!(n.is_power_of_two(), "{} values aren't a domain", n);
+        let mut values = vec![halo2curves::bn256::Fr::zero(); n];
+        let cpp_values: &mut [Fr] = unsafe { std::mem::transmute(values.as_mut_slice()) };
+        self.inner.get_values(cpp_values);
+        EvaluationDomain::new(1, n.trailing_zeros()).lagrange_from_vec(values)
+    }
+
+    /// Copies `poly` into evals of `prover`. Fails with
+    /// [`TachyonError::LengthMismatch`] unless it has `prover.n()` values.
+    #[cfg(feature = "halo2")]
+    pub fn from_lagrange<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+        prover: &P,
+        poly: &Polynomial<halo2curves::bn256::Fr, LagrangeCoeff>,
+    ) -> Result<Evals, TachyonError> {
+        check_domain_len(prover, poly.len())?;
+        let mut evals = prover.empty_evals();
+        let cpp_values: &[Fr] = unsafe { std::mem::transmute(&poly[..]) };
+        evals.inner.pin_mut().set_values(cpp_values);
+        Ok(evals)
+    }
+}
+
+#[cfg(feature = "halo2")]
+fn check_domain_len<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    prover: &P,
+    found: usize,
+) -> Result<(), TachyonError> {
+    let expected = prover.n() as usize;
+    if found != expected {
+        return Err(TachyonError::LengthMismatch { expected, found });
+    }
+    Ok(())
+}
+
+impl Poolable for Evals {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn reset(&mut self) {
+        self.inner.pin_mut().reset()
+    }
 }
 
 impl Clone for Evals {
@@ -686,15 +1736,41 @@ impl Clone for Evals {
     }
 }
 
+impl fmt::Debug for Evals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Evals");
+        s.field("len", &self.len());
+        #[cfg(feature = "debug-values")]
+        s.field(
+            "values",
+            &Values::summary(self.len(), |i| self.get_value(i)),
+        );
+        s.finish()
+    }
+}
+
+impl DebugFull for Evals {
+    fn fmt_full(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Evals")
+            .field("len", &self.len())
+            .field("values", &Values::full(self.len(), |i| self.get_value(i)))
+            .finish()
+    }
+}
+
 pub struct RationalEvals {
-    inner: cxx::UniquePtr<ffi::RationalEvals>,
+    inner: UniquePtr<ffi::RationalEvals>,
 }
 
 impl RationalEvals {
-    pub fn new(inner: cxx::UniquePtr<ffi::RationalEvals>) -> RationalEvals {
+    pub fn new(inner: UniquePtr<ffi::RationalEvals>) -> RationalEvals {
         RationalEvals { inner }
     }
 
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
     pub fn set_zero(&mut self, idx: usize) {
         self.inner.pin_mut().set_zero(idx)
     }
@@ -704,6 +1780,9 @@ impl RationalEvals {
         self.inner.pin_mut().set_trivial(idx, cpp_numerator)
     }
 
+    /// Sets the value at `idx` to `numerator / denominator`. Following
+    /// upstream halo2, a zero `denominator` makes the value zero rather than
+    /// failing.
     pub fn set_rational(
         &mut self,
         idx: usize,
@@ -718,6 +1797,25 @@ impl RationalEvals {
     }
 }
 
+impl Poolable for RationalEvals {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn reset(&mut self) {
+        self.inner.pin_mut().reset()
+    }
+}
+
+// The backend has no way to read the values back.
+impl fmt::Debug for RationalEvals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RationalEvals")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
 impl Clone for RationalEvals {
     fn clone(&self) -> Self {
         Self {
@@ -727,141 +1825,911 @@ impl Clone for RationalEvals {
 }
 
 pub struct Poly {
-    inner: cxx::UniquePtr<ffi::Poly>,
+    inner: UniquePtr<ffi::Poly>,
 }
 
 impl Poly {
-    pub fn new(inner: cxx::UniquePtr<ffi::Poly>) -> Poly {
-        Poly { inner }
+    pub fn zero() -> Poly {
+        Self::new(ffi::zero_poly())
     }
-}
-
-pub trait TachyonProver<Scheme: CommitmentScheme> {
-    const QUERY_INSTANCE: bool;
-
-    fn k(&self) -> u32;
-
-    fn n(&self) -> u64;
-
-    fn s_g2(&self) -> &G2Affine;
-
-    fn commit(&self, poly: &Poly) -> <Scheme::Curve as CurveAffine>::CurveExt;
-
-    fn commit_lagrange(&self, evals: &Evals) -> <Scheme::Curve as CurveAffine>::CurveExt;
-
-    fn empty_evals(&self) -> Evals;
-
-    fn empty_rational_evals(&self) -> RationalEvals;
 
-    fn batch_evaluate(&self, rational_evals: &[RationalEvals], evals: &mut [Evals]);
+    pub fn new(inner: UniquePtr<ffi::Poly>) -> Poly {
+        Poly { inner }
+    }
 
-    fn ifft(&self, evals: &Evals) -> Poly;
+    /// The coefficients the backend keeps, without the zeros of the highest
+    /// degrees it drops.
+    fn coeffs(&self) -> Vec<halo2curves::bn256::Fr> {
+        let mut coeffs = vec![halo2curves::bn256::Fr::zero(); self.inner.len()];
+        let cpp_coeffs: &mut [Fr] = unsafe { std::mem::transmute(coeffs.as_mut_slice()) };
+        self.inner.get_coeffs(cpp_coeffs);
+        coeffs
+    }
 
-    fn set_rng(&mut self, state: &[u8]);
+    /// Copies the coefficients into a halo2 polynomial over the domain of
+    /// `prover`. The backend drops the zero coefficients of the highest
+    /// degrees, so they are put back. Fails with
+    /// [`TachyonError::LengthMismatch`] if there are more than `prover.n()`.
+    #[cfg(feature = "halo2")]
+    pub fn to_coeff_polynomial<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+        &self,
+        prover: &P,
+    ) -> Result<Polynomial<halo2curves::bn256::Fr, Coeff>, TachyonError> {
+        let len = self.inner.len();
+        let n = prover.n() as usize;
+        if len > n {
+            return Err(TachyonError::LengthMismatch {
+                expected: n,
+                found: len,
+            });
+        }
+        let mut coeffs = vec![halo2curves::bn256::Fr::zero(); n];
+        let cpp_coeffs: &mut [Fr] = unsafe { std::mem::transmute(&mut coeffs[..len]) };
+        self.inner.get_coeffs(cpp_coeffs);
+        Ok(EvaluationDomain::new(1, prover.k()).coeff_from_vec(coeffs))
+    }
+
+    /// Copies `poly` into a polynomial for `prover`. Fails with
+    /// [`TachyonError::LengthMismatch`] unless it has `prover.n()`
+    /// coefficients.
+    #[cfg(feature = "halo2")]
+    pub fn from_coeff<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+        prover: &P,
+        poly: &Polynomial<halo2curves::bn256::Fr, Coeff>,
+    ) -> Result<Poly, TachyonError> {
+        check_domain_len(prover, poly.len())?;
+        let mut ret = Poly::zero();
+        let cpp_coeffs: &[Fr] = unsafe { std::mem::transmute(&poly[..]) };
+        ret.inner.pin_mut().set_coeffs(cpp_coeffs);
+        Ok(ret)
+    }
+}
 
-    fn set_transcript(&mut self, state: &[u8]);
+impl Clone for Poly {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
 
-    fn set_extended_domain(&mut self, pk: &ProvingKey<Scheme::Curve>);
+impl fmt::Debug for Poly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Poly");
+        s.field("len", &self.inner.len());
+        #[cfg(feature = "debug-values")]
+        {
+            let coeffs = self.coeffs();
+            s.field("coeffs", &Values::summary(coeffs.len(), |i| coeffs[i]));
+        }
+        s.finish()
+    }
+}
 
-    fn create_proof(
-        &mut self,
-        key: &mut ProvingKey<Scheme::Curve>,
-        instance_singles: &mut [InstanceSingle],
-        advice_singles: &mut [AdviceSingle],
-        challenges: &[Fr],
-    );
+impl DebugFull for Poly {
+    fn fmt_full(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let coeffs = self.coeffs();
+        f.debug_struct("Poly")
+            .field("len", &coeffs.len())
+            .field("coeffs", &coeffs)
+            .finish()
+    }
+}
 
-    fn get_proof(&self) -> Vec<u8>;
+/// Evaluations over the extended domain of size `2^extended_k`, shifted by `ζ`
+/// so that they never hit a root of the vanishing polynomial. This is halo2's
+/// `Polynomial<Fr, ExtendedLagrangeCoeff>`, which the quotient is computed
+/// over.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ExtendedEvals {
+    k: u32,
+    extended_k: u32,
+    values: Vec<halo2curves::bn256::Fr>,
+}
 
-    fn transcript_repr(&self, pk: &mut ProvingKey<Scheme::Curve>) -> Scheme::Scalar;
+impl fmt::Debug for ExtendedEvals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("ExtendedEvals");
+        s.field("k", &self.k)
+            .field("extended_k", &self.extended_k)
+            .field("len", &self.values.len());
+        #[cfg(feature = "debug-values")]
+        s.field(
+            "values",
+            &Values::summary(self.values.len(), |i| self.values[i]),
+        );
+        s.finish()
+    }
 }
 
-pub struct GWCProver<Scheme: CommitmentScheme> {
-    inner: cxx::UniquePtr<ffi::GWCProver>,
-    _marker: PhantomData<Scheme>,
+impl DebugFull for ExtendedEvals {
+    fn fmt_full(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtendedEvals")
+            .field("k", &self.k)
+            .field("extended_k", &self.extended_k)
+            .field("len", &self.values.len())
+            .field("values", &self.values)
+            .finish()
+    }
 }
 
-impl<Scheme: CommitmentScheme> GWCProver<Scheme> {
-    pub fn new(transcript_type: u8, k: u32, s: &halo2curves::bn256::Fr) -> GWCProver<Scheme> {
-        let cpp_s = unsafe { std::mem::transmute::<_, &Fr>(s) };
-        GWCProver {
-            inner: ffi::new_gwc_prover(transcript_type, k, cpp_s),
-            _marker: PhantomData,
+impl ExtendedEvals {
+    /// Evaluates `poly` at `ζωₑⁱ`, where `ωₑ` generates the domain of size
+    /// `2^extended_k`. This matches `EvaluationDomain::coeff_to_extended()`.
+    pub fn coset_fft(poly: &Poly, k: u32, extended_k: u32) -> ExtendedEvals {
+        let zeta = halo2curves::bn256::Fr::ZETA;
+        let cpp_zeta = unsafe { std::mem::transmute::<_, &Fr>(&zeta) };
+        let evals = Evals::new(ffi::coset_fft(&poly.inner, extended_k, cpp_zeta));
+        ExtendedEvals {
+            k,
+            extended_k,
+            values: (0..evals.len()).map(|i| evals.get_value(i)).collect(),
         }
     }
 
-    pub fn from_params(transcript_type: u8, k: u32, params: &[u8]) -> GWCProver<Scheme> {
-        GWCProver {
-            inner: ffi::new_gwc_prover_from_params(transcript_type, k, params),
-            _marker: PhantomData,
+    /// Inverse of [`ExtendedEvals::coset_fft()`].
+    pub fn coset_ifft(&self) -> Poly {
+        let mut evals = Evals::new(ffi::empty_coset_evals(self.extended_k));
+        for (i, value) in self.values.iter().enumerate() {
+            evals.set_value(i, value);
         }
+        let zeta = halo2curves::bn256::Fr::ZETA;
+        let cpp_zeta = unsafe { std::mem::transmute::<_, &Fr>(&zeta) };
+        Poly::new(ffi::coset_ifft(&evals.inner, self.extended_k, cpp_zeta))
     }
-}
 
-impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for GWCProver<Scheme> {
-    const QUERY_INSTANCE: bool = true;
+    pub fn k(&self) -> u32 {
+        self.k
+    }
 
-    fn k(&self) -> u32 {
-        self.inner.k()
+    pub fn extended_k(&self) -> u32 {
+        self.extended_k
     }
 
-    fn n(&self) -> u64 {
-        self.inner.n()
+    pub fn values(&self) -> &[halo2curves::bn256::Fr] {
+        &self.values
     }
 
-    fn s_g2(&self) -> &G2Affine {
-        unsafe { std::mem::transmute::<_, &G2Affine>(self.inner.s_g2()) }
+    pub fn len(&self) -> usize {
+        self.values.len()
     }
 
-    fn commit(&self, poly: &Poly) -> <Scheme::Curve as CurveAffine>::CurveExt {
-        *unsafe {
-            std::mem::transmute::<_, Box<<Scheme::Curve as CurveAffine>::CurveExt>>(
-                self.inner.commit(&poly.inner),
-            )
-        }
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
     }
 
-    fn commit_lagrange(&self, evals: &Evals) -> <Scheme::Curve as CurveAffine>::CurveExt {
-        *unsafe {
-            std::mem::transmute::<_, Box<<Scheme::Curve as CurveAffine>::CurveExt>>(
-                self.inner.commit_lagrange(&evals.inner),
-            )
+    /// Divides by `Xⁿ - 1`, where `n = 2ᵏ`. This matches
+    /// `EvaluationDomain::divide_by_vanishing_poly()`.
+    pub fn divide_by_vanishing_poly(&mut self) {
+        use halo2curves::bn256::Fr;
+
+        let n = 1u64 << self.k;
+        // `Xⁿ - 1` only takes `2^(extended_k - k)` distinct values over the
+        // coset, since `ωₑⁿ` has that order.
+        let extended_omega = Fr::root_of_unity().pow_vartime([1u64 << (Fr::S - self.extended_k)]);
+        let extended_omega_pow_n = extended_omega.pow_vartime([n]);
+        let mut x_pow_n = Fr::ZETA.pow_vartime([n]);
+        let t_inverses = (0..1usize << (self.extended_k - self.k))
+            .map(|_| {
+                let t_inverse = (x_pow_n - Fr::one()).invert().unwrap();
+                x_pow_n *= extended_omega_pow_n;
+                t_inverse
+            })
+            .collect::<Vec<_>>();
+        for (i, value) in self.values.iter_mut().enumerate() {
+            *value *= t_inverses[i % t_inverses.len()];
         }
     }
 
-    fn empty_evals(&self) -> Evals {
-        Evals::new(self.inner.empty_evals())
+    fn check_same_domain(&self, other: &ExtendedEvals) {
+        assert_eq!(
+            (self.k, self.extended_k),
+            (other.k, other.extended_k),
+            "evaluations are over different domains"
+        );
     }
+}
 
-    fn empty_rational_evals(&self) -> RationalEvals {
-        RationalEvals::new(self.inner.empty_rational_evals())
+impl std::ops::AddAssign<&ExtendedEvals> for ExtendedEvals {
+    fn add_assign(&mut self, other: &ExtendedEvals) {
+        self.check_same_domain(other);
+        for (value, other) in self.values.iter_mut().zip(&other.values) {
+            *value += other;
+        }
     }
+}
 
-    fn batch_evaluate(&self, rational_evals: &[RationalEvals], evals: &mut [Evals]) {
-        unsafe {
-            let rational_evals: &[cxx::UniquePtr<ffi::RationalEvals>] =
-                std::mem::transmute(rational_evals);
-            let evals: &mut [cxx::UniquePtr<ffi::Evals>] = std::mem::transmute(evals);
-            self.inner.batch_evaluate(rational_evals, evals)
+impl std::ops::MulAssign<&ExtendedEvals> for ExtendedEvals {
+    fn mul_assign(&mut self, other: &ExtendedEvals) {
+        self.check_same_domain(other);
+        for (value, other) in self.values.iter_mut().zip(&other.values) {
+            *value *= other;
         }
     }
+}
 
-    fn ifft(&self, evals: &Evals) -> Poly {
-        Poly::new(self.inner.ifft(&evals.inner))
+/// Scales every value.
+impl std::ops::MulAssign<halo2curves::bn256::Fr> for ExtendedEvals {
+    fn mul_assign(&mut self, scalar: halo2curves::bn256::Fr) {
+        for value in &mut self.values {
+            *value *= scalar;
+        }
     }
+}
 
-    fn set_rng(&mut self, state: &[u8]) {
-        self.inner.pin_mut().set_rng(state)
-    }
+#[cfg(feature = "halo2")]
+pub trait TachyonProver<Scheme: CommitmentScheme> {
+    /// The multi-open argument of the proofs this creates, which decides the
+    /// verifier they have to be checked with.
+    fn multi_open_scheme(&self) -> MultiOpenScheme;
 
-    fn set_transcript(&mut self, state: &[u8]) {
-        self.inner.pin_mut().set_transcript(state)
-    }
+    /// Whether instance columns are committed to and opened like the other
+    /// columns rather than absorbed into the transcript directly.
+    fn query_instance(&self) -> bool;
+
+    fn k(&self) -> u32;
+
+    /// The transcript type the prover was created with, as the backend takes
+    /// it.
+    fn transcript_type(&self) -> u8;
+
+    fn n(&self) -> u64;
+
+    fn s_g2(&self) -> &G2Affine;
+
+    fn commit(&self, poly: &Poly) -> <Scheme::Curve as CurveAffine>::CurveExt;
+
+    fn commit_lagrange(&self, evals: &Evals) -> <Scheme::Curve as CurveAffine>::CurveExt;
+
+    /// Commits to every column of `columns`, in order. With the "rayon"
+    /// feature, the provers of this crate commit to them in parallel.
+    fn commit_lagrange_columns(
+        &self,
+        columns: &[&Evals],
+    ) -> Vec<<Scheme::Curve as CurveAffine>::CurveExt> {
+        columns
+            .iter()
+            .map(|evals| self.commit_lagrange(evals))
+            .collect()
+    }
+
+    /// Returns a zeroed `Evals` of the domain size, reusing a pooled one if
+    /// there is one. It is a plain `Evals` rather than a [`Pooled`] one,
+    /// since most of them are handed to the backend, which owns them from
+    /// then on; use [`TachyonProver::pooled_evals()`] for scratch buffers
+    /// that should go back to the pool when dropped.
+    fn empty_evals(&self) -> Evals;
+
+    /// Returns a zeroed `RationalEvals` of the domain size, reusing a pooled
+    /// one if there is one. See [`TachyonProver::empty_evals()`] for why it
+    /// isn't [`Pooled`].
+    fn empty_rational_evals(&self) -> RationalEvals;
+
+    fn evals_pool(&self) -> &Pool<Evals>;
+
+    fn rational_evals_pool(&self) -> &Pool<RationalEvals>;
+
+    /// Returns an empty `Evals` that goes back to the pool when dropped.
+    fn pooled_evals(&self) -> Pooled<'_, Evals> {
+        Pooled::new(self.evals_pool(), self.empty_evals())
+    }
+
+    /// Returns an empty `RationalEvals` that goes back to the pool when dropped.
+    fn pooled_rational_evals(&self) -> Pooled<'_, RationalEvals> {
+        Pooled::new(self.rational_evals_pool(), self.empty_rational_evals())
+    }
+
+    /// Hands `evals` back so that a later `empty_evals()` can reuse it. The
+    /// proof paths only recycle the buffers they free themselves, see
+    /// `src/pool.rs`.
+    fn recycle_evals(&self, evals: Evals) {
+        self.evals_pool().put(evals)
+    }
+
+    /// Hands `rational_evals` back so that a later `empty_rational_evals()`
+    /// can reuse it.
+    fn recycle_rational_evals(&self, rational_evals: RationalEvals) {
+        self.rational_evals_pool().put(rational_evals)
+    }
+
+    /// Sets the maximum number of buffers each pool keeps around.
+    fn set_pool_capacity(&mut self, capacity: usize) {
+        self.evals_pool().set_capacity(capacity);
+        self.rational_evals_pool().set_capacity(capacity);
+    }
+
+    /// Releases every pooled buffer back to the backend.
+    fn trim_pool(&mut self) {
+        self.evals_pool().trim();
+        self.rational_evals_pool().trim();
+    }
+
+    fn metrics_mut(&self) -> RefMut<'_, ProofMetrics>;
+
+    /// Returns the key that `create_proof_with_options()` derives RNG seeds
+    /// from.
+    fn master_key(&self) -> Option<&[u8; 32]>;
+
+    fn set_master_key(&mut self, master_key: [u8; 32]);
+
+    /// Returns the metrics collected since the prover was created or since the
+    /// last `reset_metrics()`.
+    fn metrics(&self) -> ProofMetrics {
+        self.metrics_mut().clone()
+    }
+
+    fn reset_metrics(&mut self) {
+        *self.metrics_mut() = ProofMetrics::default();
+    }
+
+    /// Returns the histograms the prover records its proofs into, which can
+    /// be read from another thread while a proof runs.
+    #[cfg(feature = "stats")]
+    fn stats(&self) -> Arc<ProverStats>;
+
+    /// Returns the statistics of the proofs since the prover was created or
+    /// since the last `reset_stats()`.
+    #[cfg(feature = "stats")]
+    fn stats_snapshot(&self) -> StatsSnapshot {
+        self.stats().snapshot()
+    }
+
+    #[cfg(feature = "stats")]
+    fn reset_stats(&mut self) {
+        self.stats().reset();
+    }
+
+    fn batch_evaluate(&self, rational_evals: &[RationalEvals], evals: &mut [Evals]);
+
+    fn ifft(&self, evals: &Evals) -> Poly;
+
+    /// Computes how much of the domain `evals` uses. The backend interpolates
+    /// it without handing the polynomial back.
+    fn column_stats(&self, evals: &Evals) -> ColumnStats;
+
+    /// Evaluates `poly` over the extended coset domain of `pk`, the same one
+    /// the backend computes the quotient over.
+    fn coset_evaluate(&self, pk: &ProvingKey<Scheme::Curve>, poly: &Poly) -> ExtendedEvals {
+        let k = self.k();
+        ExtendedEvals::coset_fft(poly, k, pk.extended_k(k))
+    }
+
+    /// Inverse of [`TachyonProver::coset_evaluate()`].
+    fn coset_ifft(&self, evals: &ExtendedEvals) -> Poly {
+        evals.coset_ifft()
+    }
+
+    /// Continues the backend's RNG from `state`, as written by
+    /// [`crate::xor_shift_rng::XORShiftRng::state()`]. Fails with
+    /// [`TachyonError::State`] if it doesn't have the current version and
+    /// length.
+    fn set_rng(&mut self, state: &[u8]) -> Result<(), TachyonError>;
+
+    /// Continues the backend's transcript from `state`, as written by
+    /// [`TranscriptWriteState::state()`] for a transcript of the prover's
+    /// type. Fails like [`TachyonProver::set_rng()`]. With
+    /// [`TranscriptType::Custom`], the backend calls back into a clone of the
+    /// registered hasher that `state` names, which also fails if there is
+    /// none.
+    fn set_transcript(&mut self, state: &[u8]) -> Result<(), TachyonError>;
+
+    fn set_extended_domain(&mut self, pk: &ProvingKey<Scheme::Curve>);
+
+    /// Sets how many lookup and permutation polynomials the backend computes
+    /// at once. The proof is the same for any value.
+    fn set_intra_proof_parallelism(&mut self, intra_proof_parallelism: usize);
+
+    /// Sets the stages the backend runs on the GPU, as a mask of
+    /// [`crate::placement::StageCategory::bit()`]. The proof is the same for
+    /// any value.
+    fn set_gpu_stages(&mut self, gpu_stages: u8);
+
+    /// Sets the log₂ of the size of the IFFTs the backend splits the IFFT of
+    /// the extended domain into, or 0 to run it in one piece. The proof is
+    /// the same for any value.
+    fn set_fft_chunk_log2(&mut self, fft_chunk_log2: u32);
+
+    /// Sets how many threads the backend's parallel loops run on while it
+    /// creates a proof, or 0 to leave it to OpenMP. The proof is the same for
+    /// any value.
+    fn set_num_threads(&mut self, num_threads: usize);
+
+    /// Sets how [`TachyonProver::commit_lagrange()`] gets the Lagrange bases.
+    /// A policy the backend doesn't follow is followed here if it can be:
+    /// [`LagrangeBasisPolicy::ViaMonomial`] commits to
+    /// [`TachyonProver::ifft()`] with [`TachyonProver::commit()`]. Otherwise
+    /// this fails with [`TachyonError::BackendUnavailable`] and keeps the
+    /// policy it had.
+    fn set_lagrange_basis_policy(
+        &mut self,
+        policy: LagrangeBasisPolicy,
+    ) -> Result<(), TachyonError>;
+
+    fn lagrange_basis_policy(&self) -> LagrangeBasisPolicy;
+
+    /// Sets whether [`TachyonProver::create_proof()`] records what evaluating
+    /// every custom gate costs, see [`TachyonProver::gate_costs()`]. Nothing
+    /// is timed otherwise.
+    fn set_record_gate_costs(&mut self, record_gate_costs: bool);
+
+    /// The time the backend spent evaluating every custom gate in the last
+    /// proof, and its calculations times the rows they were evaluated at, in
+    /// the order of the gates. It is empty if they weren't recorded. Only
+    /// every 64th row is timed, and the time is extrapolated from them.
+    fn gate_costs(&self) -> Vec<(Duration, u64)>;
+
+    fn create_proof(
+        &mut self,
+        key: &mut ProvingKey<Scheme::Curve>,
+        instance_singles: &mut [InstanceSingle],
+        advice_singles: &mut [AdviceSingle],
+        challenges: &[Fr],
+    );
+
+    fn get_proof(&self) -> Vec<u8>;
+
+    /// The state the backend's transcript is in, e.g, at the end of a proof,
+    /// in the layout of [`TranscriptWriteState::state()`].
+    fn transcript_state(&self) -> Vec<u8>;
+
+    fn transcript_repr(&self, pk: &mut ProvingKey<Scheme::Curve>) -> Scheme::Scalar;
+}
+
+#[cfg(feature = "halo2")]
+pub struct GWCProver<Scheme: CommitmentScheme> {
+    inner: UniquePtr<ffi::GWCProver>,
+    evals_pool: Pool<Evals>,
+    rational_evals_pool: Pool<RationalEvals>,
+    metrics: RefCell<ProofMetrics>,
+    #[cfg(feature = "stats")]
+    stats: Arc<ProverStats>,
+    master_key: Option<[u8; 32]>,
+    /// What [`TachyonProver::set_transcript()`] expects the state of.
+    transcript_type: u8,
+    /// The hasher the backend calls back into with a custom transcript. It is
+    /// boxed so that the pointer the backend holds stays put.
+    transcript_callbacks: Option<Box<TranscriptCallbacks>>,
+    lagrange_basis_policy: LagrangeBasisPolicy,
+    /// Whether `commit_lagrange()` follows `lagrange_basis_policy` here
+    /// because the backend doesn't.
+    commit_via_monomial: bool,
+    _marker: PhantomData<Scheme>,
+}
+
+#[cfg(feature = "halo2")]
+impl<Scheme: CommitmentScheme> GWCProver<Scheme> {
+    pub fn new(transcript_type: u8, k: u32, s: &halo2curves::bn256::Fr) -> GWCProver<Scheme> {
+        Self::try_new(transcript_type, k, s).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as `new()`, but fails instead of panicking if libtachyon is
+    /// missing, has another ABI or fails the self-test.
+    pub fn try_new(
+        transcript_type: u8,
+        k: u32,
+        s: &halo2curves::bn256::Fr,
+    ) -> Result<GWCProver<Scheme>, TachyonError> {
+        ffi_trace!("new_gwc_prover", "k={}", k);
+        ensure_abi_version()?;
+        let cpp_s = unsafe { std::mem::transmute::<_, &Fr>(s) };
+        Ok(GWCProver {
+            inner: ffi::new_gwc_prover(transcript_type, k, cpp_s),
+            evals_pool: Pool::default(),
+            rational_evals_pool: Pool::default(),
+            metrics: RefCell::default(),
+            #[cfg(feature = "stats")]
+            stats: Arc::default(),
+            master_key: None,
+            transcript_type,
+            transcript_callbacks: None,
+            lagrange_basis_policy: LagrangeBasisPolicy::Resident,
+            commit_via_monomial: false,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn from_params(transcript_type: u8, k: u32, params: &[u8]) -> GWCProver<Scheme> {
+        Self::try_from_params(transcript_type, k, params).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as `from_params()`, but fails instead of panicking if libtachyon
+    /// is missing, has another ABI or fails the self-test.
+    pub fn try_from_params(
+        transcript_type: u8,
+        k: u32,
+        params: &[u8],
+    ) -> Result<GWCProver<Scheme>, TachyonError> {
+        ffi_trace!(
+            "new_gwc_prover_from_params",
+            "k={} params_len={}",
+            k,
+            params.len()
+        );
+        ensure_abi_version()?;
+        Ok(GWCProver {
+            inner: ffi::new_gwc_prover_from_params(transcript_type, k, params),
+            evals_pool: Pool::default(),
+            rational_evals_pool: Pool::default(),
+            metrics: RefCell::default(),
+            #[cfg(feature = "stats")]
+            stats: Arc::default(),
+            master_key: None,
+            transcript_type,
+            transcript_callbacks: None,
+            lagrange_basis_policy: LagrangeBasisPolicy::Resident,
+            commit_via_monomial: false,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Same as `from_params()`, but verifies `params` against `integrity` first.
+    pub fn from_params_with_integrity(
+        transcript_type: u8,
+        k: u32,
+        params: &[u8],
+        integrity: IntegrityPolicy,
+    ) -> Result<GWCProver<Scheme>, IntegrityError> {
+        SrsIntegrity::verify(params, &integrity)?;
+        Ok(Self::from_params(transcript_type, k, params))
+    }
+
+    /// The params of the prover in the layout the backend's verifier reads,
+    /// which isn't the layout of `ParamsKZG::write()`.
+    pub fn pcs_params(&self) -> Vec<u8> {
+        ffi_trace!("GWCProver::pcs_params");
+        self.inner.pcs_params()
+    }
+
+    /// Commits to the instance columns of a circuit over `pk` the way the
+    /// backend's verifier does, e.g, to commit to instances shared by many
+    /// proofs once. Returns `None` if a column has more values than usable
+    /// rows.
+    pub fn commit_instance_columns(
+        &self,
+        pk: &ProvingKey<Scheme::Curve>,
+        columns: &[&[Scheme::Scalar]],
+    ) -> Option<Vec<Scheme::Curve>> {
+        ffi_trace!(
+            "GWCProver::commit_instance_columns",
+            "num_columns={}",
+            columns.len()
+        );
+        let column_lens: Vec<usize> = columns.iter().map(|column| column.len()).collect();
+        let values: Vec<Scheme::Scalar> = columns
+            .iter()
+            .flat_map(|column| column.iter().copied())
+            .collect();
+        let values = unsafe { std::mem::transmute::<_, &[Fr]>(values.as_slice()) };
+        let mut commitments = vec![Scheme::Curve::identity(); columns.len()];
+        let cpp_commitments =
+            unsafe { std::mem::transmute::<_, &mut [G1Point2]>(commitments.as_mut_slice()) };
+        ffi::commit_gwc_instance_columns(
+            self.transcript_type,
+            self.inner.k(),
+            &self.pcs_params(),
+            &pk.inner,
+            &column_lens,
+            values,
+            cpp_commitments,
+        )
+        .then(|| commitments)
+    }
+
+    /// Verifies `proof`, a proof of `instances` over `pk` that was written
+    /// with the transcript of this prover, with the backend's verifier and
+    /// the params of this prover.
+    /// A proof with a custom transcript never verifies here.
+    pub fn verify_proof(
+        &self,
+        pk: &mut ProvingKey<Scheme::Curve>,
+        instances: &[&[&[Scheme::Scalar]]],
+        proof: &[u8],
+    ) -> bool {
+        ffi_trace!(
+            "GWCProver::verify_proof",
+            "num_circuits={} proof_len={}",
+            instances.len(),
+            proof.len()
+        );
+        // GWC opens the instance columns, so its verifier reads no more of
+        // them than their commitments.
+        let commitments: Option<Vec<Vec<Scheme::Curve>>> = instances
+            .iter()
+            .map(|columns| self.commit_instance_columns(pk, columns))
+            .collect();
+        let commitments = match commitments {
+            Some(commitments) => commitments,
+            None => return false,
+        };
+        let commitments: Vec<&[Scheme::Curve]> = commitments
+            .iter()
+            .map(|columns| columns.as_slice())
+            .collect();
+        self.verify_proof_with_instance_commitments(pk, &commitments, proof)
+    }
+
+    /// Like [`GWCProver::verify_proof()`] with the commitments to the
+    /// instance columns of every circuit instead of their values, e.g, from
+    /// [`GWCProver::commit_instance_columns()`]. A commitment to other values
+    /// than the ones `proof` was created with makes it fail.
+    pub fn verify_proof_with_instance_commitments(
+        &self,
+        pk: &mut ProvingKey<Scheme::Curve>,
+        commitments: &[&[Scheme::Curve]],
+        proof: &[u8],
+    ) -> bool {
+        ffi_trace!(
+            "GWCProver::verify_proof_with_instance_commitments",
+            "num_circuits={} proof_len={}",
+            commitments.len(),
+            proof.len()
+        );
+        // The backend's verifier has no hasher to call back into, a proof
+        // with a custom transcript is verified with `CustomRead` instead.
+        if self.transcript_type == TranscriptType::Custom as u8
+            || commitments.is_empty()
+            || commitments
+                .iter()
+                .any(|columns| columns.len() != pk.num_instance_columns())
+        {
+            return false;
+        }
+        // The verifier reads the transcript repr from the verifying key, which
+        // the backend only sets when asked for it.
+        pk.inner.pin_mut().transcript_repr_gwc(&self.inner);
+        let flattened: Vec<Scheme::Curve> = commitments
+            .iter()
+            .flat_map(|columns| columns.iter().copied())
+            .collect();
+        let cpp_commitments =
+            unsafe { std::mem::transmute::<_, &[G1Point2]>(flattened.as_slice()) };
+        ffi::verify_gwc_proof_with_instance_commitments(
+            self.transcript_type,
+            self.inner.k(),
+            &self.pcs_params(),
+            proof,
+            &pk.inner,
+            commitments.len(),
+            cpp_commitments,
+        )
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for GWCProver<Scheme> {
+    fn multi_open_scheme(&self) -> MultiOpenScheme {
+        MultiOpenScheme::Gwc
+    }
+
+    fn query_instance(&self) -> bool {
+        true
+    }
+
+    fn k(&self) -> u32 {
+        self.inner.k()
+    }
+
+    fn transcript_type(&self) -> u8 {
+        self.transcript_type
+    }
+
+    fn n(&self) -> u64 {
+        self.inner.n()
+    }
+
+    fn s_g2(&self) -> &G2Affine {
+        unsafe { std::mem::transmute::<_, &G2Affine>(self.inner.s_g2()) }
+    }
+
+    fn commit(&self, poly: &Poly) -> <Scheme::Curve as CurveAffine>::CurveExt {
+        ffi_trace!(
+            "GWCProver::commit",
+            "poly={:p}",
+            crate::ffi_trace::ptr(&poly.inner)
+        );
+        *unsafe {
+            std::mem::transmute::<_, Box<<Scheme::Curve as CurveAffine>::CurveExt>>(
+                self.inner.commit(&poly.inner),
+            )
+        }
+    }
+
+    fn commit_lagrange(&self, evals: &Evals) -> <Scheme::Curve as CurveAffine>::CurveExt {
+        ffi_trace!(
+            "GWCProver::commit_lagrange",
+            "evals={:p}",
+            crate::ffi_trace::ptr(&evals.inner)
+        );
+        if self.commit_via_monomial {
+            return self.commit(&self.ifft(evals));
+        }
+        *unsafe {
+            std::mem::transmute::<_, Box<<Scheme::Curve as CurveAffine>::CurveExt>>(
+                self.inner.commit_lagrange(&evals.inner),
+            )
+        }
+    }
+
+    fn commit_lagrange_columns(
+        &self,
+        columns: &[&Evals],
+    ) -> Vec<<Scheme::Curve as CurveAffine>::CurveExt> {
+        // Only `CommitLagrange()` of the backend is known to be safe to call
+        // from several threads at once, see below.
+        if self.commit_via_monomial {
+            return columns
+                .iter()
+                .map(|evals| self.commit_lagrange(evals))
+                .collect();
+        }
+        // SAFETY: `commit_lagrange()` keeps the invariants listed on
+        // `AssertSync`: it touches no cell of `self`, and `CommitLagrange()`
+        // of the backend takes a scratch of its own under a lock.
+        let commit = unsafe { AssertSync::new(|evals: &Evals| self.commit_lagrange(evals)) };
+        par::map(columns, |evals| commit.call(*evals))
+    }
+
+    fn empty_evals(&self) -> Evals {
+        self.evals_pool.take(self.n() as usize).unwrap_or_else(|| {
+            self.metrics.borrow_mut().num_evals_allocations += 1;
+            Evals::new(self.inner.empty_evals())
+        })
+    }
+
+    fn empty_rational_evals(&self) -> RationalEvals {
+        self.rational_evals_pool
+            .take(self.n() as usize)
+            .unwrap_or_else(|| {
+                self.metrics.borrow_mut().num_rational_evals_allocations += 1;
+                RationalEvals::new(self.inner.empty_rational_evals())
+            })
+    }
+
+    fn evals_pool(&self) -> &Pool<Evals> {
+        &self.evals_pool
+    }
+
+    fn rational_evals_pool(&self) -> &Pool<RationalEvals> {
+        &self.rational_evals_pool
+    }
+
+    fn metrics_mut(&self) -> RefMut<'_, ProofMetrics> {
+        self.metrics.borrow_mut()
+    }
+
+    #[cfg(feature = "stats")]
+    fn stats(&self) -> Arc<ProverStats> {
+        Arc::clone(&self.stats)
+    }
+
+    fn master_key(&self) -> Option<&[u8; 32]> {
+        self.master_key.as_ref()
+    }
+
+    fn set_master_key(&mut self, master_key: [u8; 32]) {
+        self.master_key = Some(master_key);
+    }
+
+    fn batch_evaluate(&self, rational_evals: &[RationalEvals], evals: &mut [Evals]) {
+        ffi_trace!("GWCProver::batch_evaluate", "len={}", rational_evals.len());
+        unsafe {
+            let rational_evals: &[UniquePtr<ffi::RationalEvals>] =
+                std::mem::transmute(rational_evals);
+            let evals: &mut [UniquePtr<ffi::Evals>] = std::mem::transmute(evals);
+            self.inner.batch_evaluate(rational_evals, evals)
+        }
+    }
+
+    fn ifft(&self, evals: &Evals) -> Poly {
+        ffi_trace!(
+            "GWCProver::ifft",
+            "evals={:p}",
+            crate::ffi_trace::ptr(&evals.inner)
+        );
+        Poly::new(self.inner.ifft(&evals.inner))
+    }
+
+    fn column_stats(&self, evals: &Evals) -> ColumnStats {
+        ffi_trace!(
+            "GWCProver::column_stats",
+            "evals={:p}",
+            crate::ffi_trace::ptr(&evals.inner)
+        );
+        self.inner.column_stats(&evals.inner).into()
+    }
+
+    fn set_rng(&mut self, state: &[u8]) -> Result<(), TachyonError> {
+        let body = state_body(StateKind::Rng, state)?;
+        self.inner.pin_mut().set_rng(body);
+        Ok(())
+    }
+
+    fn set_transcript(&mut self, state: &[u8]) -> Result<(), TachyonError> {
+        let body = transcript_state_body(self.transcript_type, state)?;
+        if self.transcript_type == TranscriptType::Custom as u8 {
+            let mut callbacks = Box::new(TranscriptCallbacks::from_body(body)?);
+            unsafe {
+                self.inner
+                    .pin_mut()
+                    .set_transcript_callbacks(callbacks.as_mut());
+            }
+            self.transcript_callbacks = Some(callbacks);
+        } else {
+            self.inner.pin_mut().set_transcript(body);
+        }
+        Ok(())
+    }
 
     fn set_extended_domain(&mut self, pk: &ProvingKey<Scheme::Curve>) {
+        ffi_trace!(
+            "GWCProver::set_extended_domain",
+            "pk={:p}",
+            crate::ffi_trace::ptr(&pk.inner)
+        );
         self.inner.pin_mut().set_extended_domain(&pk.inner)
     }
 
+    fn set_intra_proof_parallelism(&mut self, intra_proof_parallelism: usize) {
+        self.inner
+            .pin_mut()
+            .set_intra_proof_parallelism(intra_proof_parallelism)
+    }
+
+    fn set_gpu_stages(&mut self, gpu_stages: u8) {
+        self.inner.pin_mut().set_gpu_stages(gpu_stages)
+    }
+
+    fn set_fft_chunk_log2(&mut self, fft_chunk_log2: u32) {
+        self.inner.pin_mut().set_fft_chunk_log2(fft_chunk_log2)
+    }
+
+    fn set_num_threads(&mut self, num_threads: usize) {
+        self.inner.pin_mut().set_num_threads(num_threads)
+    }
+
+    fn set_lagrange_basis_policy(
+        &mut self,
+        policy: LagrangeBasisPolicy,
+    ) -> Result<(), TachyonError> {
+        if self
+            .inner
+            .pin_mut()
+            .set_lagrange_basis_policy(policy.code())
+        {
+            self.commit_via_monomial = false;
+        } else if policy == LagrangeBasisPolicy::ViaMonomial {
+            self.commit_via_monomial = true;
+        } else {
+            return Err(TachyonError::BackendUnavailable {
+                reason: format!(
+                    "the backend doesn't support the {:?} Lagrange basis policy",
+                    policy
+                ),
+            });
+        }
+        self.lagrange_basis_policy = policy;
+        Ok(())
+    }
+
+    fn lagrange_basis_policy(&self) -> LagrangeBasisPolicy {
+        self.lagrange_basis_policy
+    }
+
+    fn set_record_gate_costs(&mut self, record_gate_costs: bool) {
+        self.inner
+            .pin_mut()
+            .set_record_gate_costs(record_gate_costs)
+    }
+
+    fn gate_costs(&self) -> Vec<(Duration, u64)> {
+        ffi_trace!("GWCProver::gate_costs");
+        self.inner
+            .gate_costs()
+            .into_iter()
+            .map(|cost| (Duration::from_nanos(cost.nanos), cost.num_ops))
+            .collect()
+    }
+
     fn create_proof(
         &mut self,
         key: &mut ProvingKey<Scheme::Curve>,
@@ -869,6 +2737,12 @@ impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for GWCProver<Scheme> {
         advice_singles: &mut [AdviceSingle],
         challenges: &[Fr],
     ) {
+        ffi_trace!(
+            "GWCProver::create_proof",
+            "num_circuits={} num_challenges={}",
+            advice_singles.len(),
+            challenges.len()
+        );
         self.inner.pin_mut().create_proof(
             key.inner.pin_mut(),
             instance_singles,
@@ -878,9 +2752,18 @@ impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for GWCProver<Scheme> {
     }
 
     fn get_proof(&self) -> Vec<u8> {
+        ffi_trace!("GWCProver::get_proof");
         self.inner.get_proof()
     }
 
+    fn transcript_state(&self) -> Vec<u8> {
+        ffi_trace!("GWCProver::transcript_state");
+        match &self.transcript_callbacks {
+            Some(callbacks) => callbacks.state(),
+            None => versioned(self.inner.get_transcript_state()),
+        }
+    }
+
     fn transcript_repr(
         &self,
         pk: &mut ProvingKey<<Scheme as CommitmentScheme>::Curve>,
@@ -889,35 +2772,307 @@ impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for GWCProver<Scheme> {
     }
 }
 
+#[cfg(feature = "halo2")]
 pub struct SHPlonkProver<Scheme: CommitmentScheme> {
-    inner: cxx::UniquePtr<ffi::SHPlonkProver>,
+    inner: UniquePtr<ffi::SHPlonkProver>,
+    evals_pool: Pool<Evals>,
+    rational_evals_pool: Pool<RationalEvals>,
+    metrics: RefCell<ProofMetrics>,
+    #[cfg(feature = "stats")]
+    stats: Arc<ProverStats>,
+    master_key: Option<[u8; 32]>,
+    /// What [`TachyonProver::set_transcript()`] expects the state of.
+    transcript_type: u8,
+    /// The hasher the backend calls back into with a custom transcript. It is
+    /// boxed so that the pointer the backend holds stays put.
+    transcript_callbacks: Option<Box<TranscriptCallbacks>>,
+    lagrange_basis_policy: LagrangeBasisPolicy,
+    /// Whether `commit_lagrange()` follows `lagrange_basis_policy` here
+    /// because the backend doesn't.
+    commit_via_monomial: bool,
     _marker: PhantomData<Scheme>,
 }
 
+#[cfg(feature = "halo2")]
 impl<Scheme: CommitmentScheme> SHPlonkProver<Scheme> {
     pub fn new(transcript_type: u8, k: u32, s: &halo2curves::bn256::Fr) -> SHPlonkProver<Scheme> {
+        Self::try_new(transcript_type, k, s).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as `new()`, but fails instead of panicking if libtachyon is
+    /// missing, has another ABI or fails the self-test.
+    pub fn try_new(
+        transcript_type: u8,
+        k: u32,
+        s: &halo2curves::bn256::Fr,
+    ) -> Result<SHPlonkProver<Scheme>, TachyonError> {
+        ffi_trace!("new_shplonk_prover", "k={}", k);
+        ensure_abi_version()?;
         let cpp_s = unsafe { std::mem::transmute::<_, &Fr>(s) };
-        SHPlonkProver {
+        Ok(SHPlonkProver {
             inner: ffi::new_shplonk_prover(transcript_type, k, cpp_s),
+            evals_pool: Pool::default(),
+            rational_evals_pool: Pool::default(),
+            metrics: RefCell::default(),
+            #[cfg(feature = "stats")]
+            stats: Arc::default(),
+            master_key: None,
+            transcript_type,
+            transcript_callbacks: None,
+            lagrange_basis_policy: LagrangeBasisPolicy::Resident,
+            commit_via_monomial: false,
             _marker: PhantomData,
-        }
+        })
     }
 
     pub fn from_params(transcript_type: u8, k: u32, params: &[u8]) -> SHPlonkProver<Scheme> {
-        SHPlonkProver {
+        Self::try_from_params(transcript_type, k, params).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as `from_params()`, but fails instead of panicking if libtachyon
+    /// is missing, has another ABI or fails the self-test.
+    pub fn try_from_params(
+        transcript_type: u8,
+        k: u32,
+        params: &[u8],
+    ) -> Result<SHPlonkProver<Scheme>, TachyonError> {
+        ffi_trace!(
+            "new_shplonk_prover_from_params",
+            "k={} params_len={}",
+            k,
+            params.len()
+        );
+        ensure_abi_version()?;
+        Ok(SHPlonkProver {
             inner: ffi::new_shplonk_prover_from_params(transcript_type, k, params),
+            evals_pool: Pool::default(),
+            rational_evals_pool: Pool::default(),
+            metrics: RefCell::default(),
+            #[cfg(feature = "stats")]
+            stats: Arc::default(),
+            master_key: None,
+            transcript_type,
+            transcript_callbacks: None,
+            lagrange_basis_policy: LagrangeBasisPolicy::Resident,
+            commit_via_monomial: false,
             _marker: PhantomData,
+        })
+    }
+
+    /// Same as `from_params()`, but verifies `params` against `integrity` first.
+    pub fn from_params_with_integrity(
+        transcript_type: u8,
+        k: u32,
+        params: &[u8],
+        integrity: IntegrityPolicy,
+    ) -> Result<SHPlonkProver<Scheme>, IntegrityError> {
+        SrsIntegrity::verify(params, &integrity)?;
+        Ok(Self::from_params(transcript_type, k, params))
+    }
+
+    /// Writes an opening proof of every `(poly, point)` in `queries` to the
+    /// transcript of the prover, without the rest of the PLONK protocol.
+    pub fn create_opening_proof(&mut self, queries: &[(&Poly, halo2curves::bn256::Fr)]) {
+        ffi_trace!(
+            "SHPlonkProver::create_opening_proof",
+            "len={}",
+            queries.len()
+        );
+        for (poly, point) in queries {
+            let cpp_point = unsafe { std::mem::transmute::<_, &Fr>(point) };
+            self.inner.pin_mut().add_opening(&poly.inner, cpp_point);
         }
+        self.inner.pin_mut().create_opening_proof();
+    }
+
+    /// Compiles the GPU kernels and initializes the device context with small
+    /// workloads sized for `2^k_hint` rows, so that the first proof doesn't
+    /// pay for them. Services should call it once at startup. On the CPU
+    /// backend there is nothing to warm and it returns an empty report.
+    pub fn warm_up(&mut self, k_hint: u32) -> Result<WarmupReport, TachyonError> {
+        ffi_trace!("SHPlonkProver::warm_up", "k_hint={}", k_hint);
+        warm_up(k_hint)
+    }
+
+    /// Reports what the prover keeps allocated, both in the backend and in
+    /// its pools of backend buffers, to localize memory that grows over many
+    /// proofs. An `Evals` value is an `Fr` and a `RationalEvals` value is a
+    /// numerator and a denominator.
+    pub fn debug_allocation_stats(&self) -> AllocationStats {
+        ffi_trace!("SHPlonkProver::debug_allocation_stats");
+        let stats = self.inner.allocation_stats();
+        let fr_bytes = std::mem::size_of::<halo2curves::bn256::Fr>() as u64;
+        AllocationStats {
+            categories: vec![
+                ("srs", stats.srs_bytes as u64),
+                ("transcript", stats.transcript_bytes as u64),
+                ("msm_scratch", self.inner.scratch_stats().bytes as u64),
+                (
+                    "evals_pool",
+                    self.evals_pool.num_elements() as u64 * fr_bytes,
+                ),
+                (
+                    "rational_evals_pool",
+                    self.rational_evals_pool.num_elements() as u64 * 2 * fr_bytes,
+                ),
+            ],
+        }
+    }
+
+    /// Reports how the commitments of the prover reuse the scratch buffers
+    /// of their MSMs. The backend creates them on the first commitment and
+    /// grows them up to `2^k` scalars, so every later commitment of a size
+    /// seen before runs without allocating.
+    pub fn scratch_stats(&self) -> ScratchStats {
+        ffi_trace!("SHPlonkProver::scratch_stats");
+        let stats = self.inner.scratch_stats();
+        ScratchStats {
+            bytes: stats.bytes as u64,
+            num_allocations: stats.num_allocations as u64,
+            num_reuses: stats.num_reuses as u64,
+        }
+    }
+
+    /// Frees the scratch buffers of the commitments, e.g, before the prover
+    /// is kept idle. The next commitment allocates them again.
+    pub fn release_scratch(&mut self) {
+        ffi_trace!("SHPlonkProver::release_scratch");
+        self.inner.pin_mut().release_scratch();
+    }
+
+    /// The params of the prover in the layout the backend's verifier reads,
+    /// which isn't the layout of `ParamsKZG::write()`.
+    pub fn pcs_params(&self) -> Vec<u8> {
+        ffi_trace!("SHPlonkProver::pcs_params");
+        self.inner.pcs_params()
+    }
+
+    /// Verifies `proof`, a proof of `instances` over `pk` that was written
+    /// with the transcript of this prover, with the backend's verifier and
+    /// the params of this prover.
+    /// A proof with a custom transcript never verifies here.
+    pub fn verify_proof(
+        &self,
+        pk: &mut ProvingKey<Scheme::Curve>,
+        instances: &[&[&[Scheme::Scalar]]],
+        proof: &[u8],
+    ) -> bool {
+        ffi_trace!(
+            "SHPlonkProver::verify_proof",
+            "num_circuits={} proof_len={}",
+            instances.len(),
+            proof.len()
+        );
+        self.verify(pk, instances, proof, None).is_some()
+    }
+
+    /// Like [`SHPlonkProver::verify_proof()`] for a proof whose transcript
+    /// started from `state` instead of an empty one, e.g, from
+    /// [`TachyonProver::transcript_state()`] after the proof before it.
+    /// Returns the state the transcript ended in if `proof` verifies.
+    pub fn verify_proof_from_state(
+        &self,
+        pk: &mut ProvingKey<Scheme::Curve>,
+        instances: &[&[&[Scheme::Scalar]]],
+        proof: &[u8],
+        state: &[u8],
+    ) -> Option<Vec<u8>> {
+        ffi_trace!(
+            "SHPlonkProver::verify_proof_from_state",
+            "num_circuits={} proof_len={}",
+            instances.len(),
+            proof.len()
+        );
+        let body = transcript_state_body(self.transcript_type, state).ok()?;
+        self.verify(pk, instances, proof, Some(body))
+    }
+
+    // Returns the state the transcript ended in if `proof` verifies. It is
+    // only read back from the backend for a transcript that started from
+    // `state`, and is empty otherwise.
+    fn verify(
+        &self,
+        pk: &mut ProvingKey<Scheme::Curve>,
+        instances: &[&[&[Scheme::Scalar]]],
+        proof: &[u8],
+        state: Option<&[u8]>,
+    ) -> Option<Vec<u8>> {
+        // The backend's verifier has no hasher to call back into, a proof
+        // with a custom transcript is verified with `CustomRead` instead.
+        if self.transcript_type == TranscriptType::Custom as u8
+            || instances.is_empty()
+            || instances
+                .iter()
+                .any(|columns| columns.len() != pk.num_instance_columns())
+        {
+            return None;
+        }
+        // The verifier reads the transcript repr from the verifying key, which
+        // the backend only sets when asked for it. Unlike
+        // `ProvingKey::transcript_repr_shplonk()`, this doesn't stop at the
+        // repr in the metadata.
+        pk.inner.pin_mut().transcript_repr_shplonk(&self.inner);
+        let column_lens: Vec<usize> = instances
+            .iter()
+            .flat_map(|columns| columns.iter().map(|column| column.len()))
+            .collect();
+        let values: Vec<Scheme::Scalar> = instances
+            .iter()
+            .flat_map(|columns| columns.iter().flat_map(|column| column.iter().copied()))
+            .collect();
+        let values = unsafe { std::mem::transmute::<_, &[Fr]>(values.as_slice()) };
+        let pcs_params = self.pcs_params();
+        let state = match state {
+            Some(state) => state,
+            None => {
+                return ffi::verify_shplonk_proof(
+                    self.transcript_type,
+                    self.inner.k(),
+                    &pcs_params,
+                    proof,
+                    &pk.inner,
+                    instances.len(),
+                    &column_lens,
+                    values,
+                )
+                .then(Vec::new);
+            }
+        };
+        let mut next_state = vec![];
+        ffi::verify_shplonk_proof_from_state(
+            self.transcript_type,
+            self.inner.k(),
+            &pcs_params,
+            proof,
+            &pk.inner,
+            instances.len(),
+            &column_lens,
+            values,
+            state,
+            &mut next_state,
+        )
+        .then(|| versioned(next_state))
     }
 }
 
+#[cfg(feature = "halo2")]
 impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for SHPlonkProver<Scheme> {
-    const QUERY_INSTANCE: bool = false;
+    fn multi_open_scheme(&self) -> MultiOpenScheme {
+        MultiOpenScheme::SHPlonk
+    }
+
+    fn query_instance(&self) -> bool {
+        false
+    }
 
     fn k(&self) -> u32 {
         self.inner.k()
     }
 
+    fn transcript_type(&self) -> u8 {
+        self.transcript_type
+    }
+
     fn n(&self) -> u64 {
         self.inner.n()
     }
@@ -927,6 +3082,11 @@ impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for SHPlonkProver<Scheme> {
     }
 
     fn commit(&self, poly: &Poly) -> <Scheme::Curve as CurveAffine>::CurveExt {
+        ffi_trace!(
+            "SHPlonkProver::commit",
+            "poly={:p}",
+            crate::ffi_trace::ptr(&poly.inner)
+        );
         *unsafe {
             std::mem::transmute::<_, Box<<Scheme::Curve as CurveAffine>::CurveExt>>(
                 self.inner.commit(&poly.inner),
@@ -935,6 +3095,14 @@ impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for SHPlonkProver<Scheme> {
     }
 
     fn commit_lagrange(&self, evals: &Evals) -> <Scheme::Curve as CurveAffine>::CurveExt {
+        ffi_trace!(
+            "SHPlonkProver::commit_lagrange",
+            "evals={:p}",
+            crate::ffi_trace::ptr(&evals.inner)
+        );
+        if self.commit_via_monomial {
+            return self.commit(&self.ifft(evals));
+        }
         *unsafe {
             std::mem::transmute::<_, Box<<Scheme::Curve as CurveAffine>::CurveExt>>(
                 self.inner.commit_lagrange(&evals.inner),
@@ -942,39 +3110,190 @@ impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for SHPlonkProver<Scheme> {
         }
     }
 
+    fn commit_lagrange_columns(
+        &self,
+        columns: &[&Evals],
+    ) -> Vec<<Scheme::Curve as CurveAffine>::CurveExt> {
+        // Only `CommitLagrange()` of the backend is known to be safe to call
+        // from several threads at once, see below.
+        if self.commit_via_monomial {
+            return columns
+                .iter()
+                .map(|evals| self.commit_lagrange(evals))
+                .collect();
+        }
+        // SAFETY: `commit_lagrange()` keeps the invariants listed on
+        // `AssertSync`: it touches no cell of `self`, and `CommitLagrange()`
+        // of the backend takes a scratch of its own under a lock.
+        let commit = unsafe { AssertSync::new(|evals: &Evals| self.commit_lagrange(evals)) };
+        par::map(columns, |evals| commit.call(*evals))
+    }
+
     fn empty_evals(&self) -> Evals {
-        Evals::new(self.inner.empty_evals())
+        self.evals_pool.take(self.n() as usize).unwrap_or_else(|| {
+            self.metrics.borrow_mut().num_evals_allocations += 1;
+            Evals::new(self.inner.empty_evals())
+        })
     }
 
     fn empty_rational_evals(&self) -> RationalEvals {
-        RationalEvals::new(self.inner.empty_rational_evals())
+        self.rational_evals_pool
+            .take(self.n() as usize)
+            .unwrap_or_else(|| {
+                self.metrics.borrow_mut().num_rational_evals_allocations += 1;
+                RationalEvals::new(self.inner.empty_rational_evals())
+            })
+    }
+
+    fn evals_pool(&self) -> &Pool<Evals> {
+        &self.evals_pool
+    }
+
+    fn rational_evals_pool(&self) -> &Pool<RationalEvals> {
+        &self.rational_evals_pool
+    }
+
+    fn metrics_mut(&self) -> RefMut<'_, ProofMetrics> {
+        self.metrics.borrow_mut()
+    }
+
+    #[cfg(feature = "stats")]
+    fn stats(&self) -> Arc<ProverStats> {
+        Arc::clone(&self.stats)
+    }
+
+    fn master_key(&self) -> Option<&[u8; 32]> {
+        self.master_key.as_ref()
+    }
+
+    fn set_master_key(&mut self, master_key: [u8; 32]) {
+        self.master_key = Some(master_key);
     }
 
     fn batch_evaluate(&self, rational_evals: &[RationalEvals], evals: &mut [Evals]) {
+        ffi_trace!(
+            "SHPlonkProver::batch_evaluate",
+            "len={}",
+            rational_evals.len()
+        );
         unsafe {
-            let rational_evals: &[cxx::UniquePtr<ffi::RationalEvals>] =
+            let rational_evals: &[UniquePtr<ffi::RationalEvals>] =
                 std::mem::transmute(rational_evals);
-            let evals: &mut [cxx::UniquePtr<ffi::Evals>] = std::mem::transmute(evals);
+            let evals: &mut [UniquePtr<ffi::Evals>] = std::mem::transmute(evals);
             self.inner.batch_evaluate(rational_evals, evals)
         }
     }
 
     fn ifft(&self, evals: &Evals) -> Poly {
+        ffi_trace!(
+            "SHPlonkProver::ifft",
+            "evals={:p}",
+            crate::ffi_trace::ptr(&evals.inner)
+        );
         Poly::new(self.inner.ifft(&evals.inner))
     }
 
-    fn set_rng(&mut self, state: &[u8]) {
-        self.inner.pin_mut().set_rng(state)
+    fn column_stats(&self, evals: &Evals) -> ColumnStats {
+        ffi_trace!(
+            "SHPlonkProver::column_stats",
+            "evals={:p}",
+            crate::ffi_trace::ptr(&evals.inner)
+        );
+        self.inner.column_stats(&evals.inner).into()
+    }
+
+    fn set_rng(&mut self, state: &[u8]) -> Result<(), TachyonError> {
+        let body = state_body(StateKind::Rng, state)?;
+        self.inner.pin_mut().set_rng(body);
+        Ok(())
     }
 
-    fn set_transcript(&mut self, state: &[u8]) {
-        self.inner.pin_mut().set_transcript(state)
+    fn set_transcript(&mut self, state: &[u8]) -> Result<(), TachyonError> {
+        let body = transcript_state_body(self.transcript_type, state)?;
+        if self.transcript_type == TranscriptType::Custom as u8 {
+            let mut callbacks = Box::new(TranscriptCallbacks::from_body(body)?);
+            unsafe {
+                self.inner
+                    .pin_mut()
+                    .set_transcript_callbacks(callbacks.as_mut());
+            }
+            self.transcript_callbacks = Some(callbacks);
+        } else {
+            self.inner.pin_mut().set_transcript(body);
+        }
+        Ok(())
     }
 
     fn set_extended_domain(&mut self, pk: &ProvingKey<Scheme::Curve>) {
+        ffi_trace!(
+            "SHPlonkProver::set_extended_domain",
+            "pk={:p}",
+            crate::ffi_trace::ptr(&pk.inner)
+        );
         self.inner.pin_mut().set_extended_domain(&pk.inner)
     }
 
+    fn set_intra_proof_parallelism(&mut self, intra_proof_parallelism: usize) {
+        self.inner
+            .pin_mut()
+            .set_intra_proof_parallelism(intra_proof_parallelism)
+    }
+
+    fn set_gpu_stages(&mut self, gpu_stages: u8) {
+        self.inner.pin_mut().set_gpu_stages(gpu_stages)
+    }
+
+    fn set_fft_chunk_log2(&mut self, fft_chunk_log2: u32) {
+        self.inner.pin_mut().set_fft_chunk_log2(fft_chunk_log2)
+    }
+
+    fn set_num_threads(&mut self, num_threads: usize) {
+        self.inner.pin_mut().set_num_threads(num_threads)
+    }
+
+    fn set_lagrange_basis_policy(
+        &mut self,
+        policy: LagrangeBasisPolicy,
+    ) -> Result<(), TachyonError> {
+        if self
+            .inner
+            .pin_mut()
+            .set_lagrange_basis_policy(policy.code())
+        {
+            self.commit_via_monomial = false;
+        } else if policy == LagrangeBasisPolicy::ViaMonomial {
+            self.commit_via_monomial = true;
+        } else {
+            return Err(TachyonError::BackendUnavailable {
+                reason: format!(
+                    "the backend doesn't support the {:?} Lagrange basis policy",
+                    policy
+                ),
+            });
+        }
+        self.lagrange_basis_policy = policy;
+        Ok(())
+    }
+
+    fn lagrange_basis_policy(&self) -> LagrangeBasisPolicy {
+        self.lagrange_basis_policy
+    }
+
+    fn set_record_gate_costs(&mut self, record_gate_costs: bool) {
+        self.inner
+            .pin_mut()
+            .set_record_gate_costs(record_gate_costs)
+    }
+
+    fn gate_costs(&self) -> Vec<(Duration, u64)> {
+        ffi_trace!("SHPlonkProver::gate_costs");
+        self.inner
+            .gate_costs()
+            .into_iter()
+            .map(|cost| (Duration::from_nanos(cost.nanos), cost.num_ops))
+            .collect()
+    }
+
     fn create_proof(
         &mut self,
         key: &mut ProvingKey<Scheme::Curve>,
@@ -982,6 +3301,12 @@ impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for SHPlonkProver<Scheme> {
         advice_singles: &mut [AdviceSingle],
         challenges: &[Fr],
     ) {
+        ffi_trace!(
+            "SHPlonkProver::create_proof",
+            "num_circuits={} num_challenges={}",
+            advice_singles.len(),
+            challenges.len()
+        );
         self.inner.pin_mut().create_proof(
             key.inner.pin_mut(),
             instance_singles,
@@ -991,9 +3316,18 @@ impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for SHPlonkProver<Scheme> {
     }
 
     fn get_proof(&self) -> Vec<u8> {
+        ffi_trace!("SHPlonkProver::get_proof");
         self.inner.get_proof()
     }
 
+    fn transcript_state(&self) -> Vec<u8> {
+        ffi_trace!("SHPlonkProver::transcript_state");
+        match &self.transcript_callbacks {
+            Some(callbacks) => callbacks.state(),
+            None => versioned(self.inner.get_transcript_state()),
+        }
+    }
+
     fn transcript_repr(
         &self,
         pk: &mut ProvingKey<<Scheme as CommitmentScheme>::Curve>,
@@ -1001,3 +3335,461 @@ impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for SHPlonkProver<Scheme> {
         pk.transcript_repr_shplonk(self)
     }
 }
+
+/// Prints what a prover was set up with. The master key is redacted, and the
+/// toxic waste a prover may have been created from isn't kept at all.
+#[cfg(feature = "halo2")]
+macro_rules! impl_prover_debug {
+    ($prover:ident) => {
+        impl<Scheme: CommitmentScheme> $prover<Scheme> {
+            fn fmt_with_master_key(
+                &self,
+                f: &mut fmt::Formatter<'_>,
+                master_key: &dyn fmt::Debug,
+            ) -> fmt::Result {
+                let transcript_type = TranscriptType::from_u8(self.transcript_type);
+                let transcript_type: &dyn fmt::Debug = match &transcript_type {
+                    Some(transcript_type) => transcript_type,
+                    None => &self.transcript_type,
+                };
+                f.debug_struct(stringify!($prover))
+                    .field("k", &self.inner.k())
+                    .field("transcript_type", transcript_type)
+                    .field("lagrange_basis_policy", &self.lagrange_basis_policy)
+                    .field("master_key", master_key)
+                    .field("pooled_evals", &self.evals_pool.len())
+                    .field("pooled_rational_evals", &self.rational_evals_pool.len())
+                    .finish()
+            }
+        }
+
+        impl<Scheme: CommitmentScheme> fmt::Debug for $prover<Scheme> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.fmt_with_master_key(f, &self.master_key.map(Redacted))
+            }
+        }
+
+        impl<Scheme: CommitmentScheme> DebugFull for $prover<Scheme> {
+            fn fmt_full(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.fmt_with_master_key(f, &self.master_key)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "halo2")]
+impl_prover_debug!(GWCProver);
+#[cfg(feature = "halo2")]
+impl_prover_debug!(SHPlonkProver);
+
+/// A prover whose multi-open scheme is picked at runtime. Every call is
+/// forwarded to the prover of that scheme.
+#[cfg(feature = "halo2")]
+pub enum AnyProver<Scheme: CommitmentScheme> {
+    Gwc(GWCProver<Scheme>),
+    SHPlonk(SHPlonkProver<Scheme>),
+}
+
+#[cfg(feature = "halo2")]
+macro_rules! dispatch {
+    ($self:expr, $prover:ident => $body:expr) => {
+        match $self {
+            AnyProver::Gwc($prover) => $body,
+            AnyProver::SHPlonk($prover) => $body,
+        }
+    };
+}
+
+#[cfg(feature = "halo2")]
+impl<Scheme: CommitmentScheme> AnyProver<Scheme> {
+    pub fn new(
+        scheme: MultiOpenScheme,
+        transcript_type: u8,
+        k: u32,
+        s: &halo2curves::bn256::Fr,
+    ) -> AnyProver<Scheme> {
+        match scheme {
+            MultiOpenScheme::Gwc => AnyProver::Gwc(GWCProver::new(transcript_type, k, s)),
+            MultiOpenScheme::SHPlonk => {
+                AnyProver::SHPlonk(SHPlonkProver::new(transcript_type, k, s))
+            }
+        }
+    }
+
+    pub fn from_params(
+        scheme: MultiOpenScheme,
+        transcript_type: u8,
+        k: u32,
+        params: &[u8],
+    ) -> AnyProver<Scheme> {
+        match scheme {
+            MultiOpenScheme::Gwc => {
+                AnyProver::Gwc(GWCProver::from_params(transcript_type, k, params))
+            }
+            MultiOpenScheme::SHPlonk => {
+                AnyProver::SHPlonk(SHPlonkProver::from_params(transcript_type, k, params))
+            }
+        }
+    }
+
+    /// Same as `new()`, but fails instead of panicking, see
+    /// [`SHPlonkProver::try_new()`].
+    pub fn try_new(
+        scheme: MultiOpenScheme,
+        transcript_type: u8,
+        k: u32,
+        s: &halo2curves::bn256::Fr,
+    ) -> Result<AnyProver<Scheme>, TachyonError> {
+        Ok(match scheme {
+            MultiOpenScheme::Gwc => AnyProver::Gwc(GWCProver::try_new(transcript_type, k, s)?),
+            MultiOpenScheme::SHPlonk => {
+                AnyProver::SHPlonk(SHPlonkProver::try_new(transcript_type, k, s)?)
+            }
+        })
+    }
+
+    /// Same as `from_params()`, but fails instead of panicking, see
+    /// [`SHPlonkProver::try_from_params()`].
+    pub fn try_from_params(
+        scheme: MultiOpenScheme,
+        transcript_type: u8,
+        k: u32,
+        params: &[u8],
+    ) -> Result<AnyProver<Scheme>, TachyonError> {
+        Ok(match scheme {
+            MultiOpenScheme::Gwc => {
+                AnyProver::Gwc(GWCProver::try_from_params(transcript_type, k, params)?)
+            }
+            MultiOpenScheme::SHPlonk => {
+                AnyProver::SHPlonk(SHPlonkProver::try_from_params(transcript_type, k, params)?)
+            }
+        })
+    }
+
+    pub fn scheme(&self) -> MultiOpenScheme {
+        match self {
+            AnyProver::Gwc(_) => MultiOpenScheme::Gwc,
+            AnyProver::SHPlonk(_) => MultiOpenScheme::SHPlonk,
+        }
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<Scheme: CommitmentScheme> fmt::Debug for AnyProver<Scheme> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dispatch!(self, prover => fmt::Debug::fmt(prover, f))
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<Scheme: CommitmentScheme> DebugFull for AnyProver<Scheme> {
+    fn fmt_full(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dispatch!(self, prover => prover.fmt_full(f))
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl<Scheme: CommitmentScheme> TachyonProver<Scheme> for AnyProver<Scheme> {
+    fn multi_open_scheme(&self) -> MultiOpenScheme {
+        self.scheme()
+    }
+
+    fn query_instance(&self) -> bool {
+        dispatch!(self, prover => prover.query_instance())
+    }
+
+    fn k(&self) -> u32 {
+        dispatch!(self, prover => prover.k())
+    }
+
+    fn transcript_type(&self) -> u8 {
+        dispatch!(self, prover => prover.transcript_type())
+    }
+
+    fn n(&self) -> u64 {
+        dispatch!(self, prover => prover.n())
+    }
+
+    fn s_g2(&self) -> &G2Affine {
+        dispatch!(self, prover => prover.s_g2())
+    }
+
+    fn commit(&self, poly: &Poly) -> <Scheme::Curve as CurveAffine>::CurveExt {
+        dispatch!(self, prover => prover.commit(poly))
+    }
+
+    fn commit_lagrange(&self, evals: &Evals) -> <Scheme::Curve as CurveAffine>::CurveExt {
+        dispatch!(self, prover => prover.commit_lagrange(evals))
+    }
+
+    fn commit_lagrange_columns(
+        &self,
+        columns: &[&Evals],
+    ) -> Vec<<Scheme::Curve as CurveAffine>::CurveExt> {
+        dispatch!(self, prover => prover.commit_lagrange_columns(columns))
+    }
+
+    fn empty_evals(&self) -> Evals {
+        dispatch!(self, prover => prover.empty_evals())
+    }
+
+    fn empty_rational_evals(&self) -> RationalEvals {
+        dispatch!(self, prover => prover.empty_rational_evals())
+    }
+
+    fn evals_pool(&self) -> &Pool<Evals> {
+        dispatch!(self, prover => prover.evals_pool())
+    }
+
+    fn rational_evals_pool(&self) -> &Pool<RationalEvals> {
+        dispatch!(self, prover => prover.rational_evals_pool())
+    }
+
+    fn metrics_mut(&self) -> RefMut<'_, ProofMetrics> {
+        dispatch!(self, prover => prover.metrics_mut())
+    }
+
+    #[cfg(feature = "stats")]
+    fn stats(&self) -> Arc<ProverStats> {
+        dispatch!(self, prover => prover.stats())
+    }
+
+    fn master_key(&self) -> Option<&[u8; 32]> {
+        dispatch!(self, prover => prover.master_key())
+    }
+
+    fn set_master_key(&mut self, master_key: [u8; 32]) {
+        dispatch!(self, prover => prover.set_master_key(master_key))
+    }
+
+    fn batch_evaluate(&self, rational_evals: &[RationalEvals], evals: &mut [Evals]) {
+        dispatch!(self, prover => prover.batch_evaluate(rational_evals, evals))
+    }
+
+    fn ifft(&self, evals: &Evals) -> Poly {
+        dispatch!(self, prover => prover.ifft(evals))
+    }
+
+    fn column_stats(&self, evals: &Evals) -> ColumnStats {
+        dispatch!(self, prover => prover.column_stats(evals))
+    }
+
+    fn set_rng(&mut self, state: &[u8]) -> Result<(), TachyonError> {
+        dispatch!(self, prover => prover.set_rng(state))
+    }
+
+    fn set_transcript(&mut self, state: &[u8]) -> Result<(), TachyonError> {
+        dispatch!(self, prover => prover.set_transcript(state))
+    }
+
+    fn set_extended_domain(&mut self, pk: &ProvingKey<Scheme::Curve>) {
+        dispatch!(self, prover => prover.set_extended_domain(pk))
+    }
+
+    fn set_intra_proof_parallelism(&mut self, intra_proof_parallelism: usize) {
+        dispatch!(self, prover => prover.set_intra_proof_parallelism(intra_proof_parallelism))
+    }
+
+    fn set_gpu_stages(&mut self, gpu_stages: u8) {
+        dispatch!(self, prover => prover.set_gpu_stages(gpu_stages))
+    }
+
+    fn set_fft_chunk_log2(&mut self, fft_chunk_log2: u32) {
+        dispatch!(self, prover => prover.set_fft_chunk_log2(fft_chunk_log2))
+    }
+
+    fn set_num_threads(&mut self, num_threads: usize) {
+        dispatch!(self, prover => prover.set_num_threads(num_threads))
+    }
+
+    fn set_lagrange_basis_policy(
+        &mut self,
+        policy: LagrangeBasisPolicy,
+    ) -> Result<(), TachyonError> {
+        dispatch!(self, prover => prover.set_lagrange_basis_policy(policy))
+    }
+
+    fn lagrange_basis_policy(&self) -> LagrangeBasisPolicy {
+        dispatch!(self, prover => prover.lagrange_basis_policy())
+    }
+
+    fn set_record_gate_costs(&mut self, record_gate_costs: bool) {
+        dispatch!(self, prover => prover.set_record_gate_costs(record_gate_costs))
+    }
+
+    fn gate_costs(&self) -> Vec<(Duration, u64)> {
+        dispatch!(self, prover => prover.gate_costs())
+    }
+
+    fn create_proof(
+        &mut self,
+        key: &mut ProvingKey<Scheme::Curve>,
+        instance_singles: &mut [InstanceSingle],
+        advice_singles: &mut [AdviceSingle],
+        challenges: &[Fr],
+    ) {
+        dispatch!(self, prover => {
+            prover.create_proof(key, instance_singles, advice_singles, challenges)
+        })
+    }
+
+    fn get_proof(&self) -> Vec<u8> {
+        dispatch!(self, prover => prover.get_proof())
+    }
+
+    fn transcript_state(&self) -> Vec<u8> {
+        dispatch!(self, prover => prover.transcript_state())
+    }
+
+    fn transcript_repr(&self, pk: &mut ProvingKey<Scheme::Curve>) -> Scheme::Scalar {
+        dispatch!(self, prover => prover.transcript_repr(pk))
+    }
+}
+
+#[cfg(all(test, feature = "halo2"))]
+mod test {
+    use std::{fmt::Debug, time::Instant};
+
+    use halo2_proofs::{poly::kzg::commitment::KZGCommitmentScheme, transcript::Challenge255};
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use static_assertions::{assert_impl_all, assert_not_impl_any};
+
+    use super::{
+        AnyProver, Blake2bWrite, Evals, ExtendedEvals, G1MSMGpu, GWCProver, Poly, PoseidonWrite,
+        ProvingKey, RationalEvals, SHPlonkProver, Sha256Write, TachyonProver,
+    };
+    use crate::{
+        consts::{MultiOpenScheme, TranscriptType},
+        prover::ProofGuard,
+        redact::DebugFull,
+    };
+
+    type Scheme = KZGCommitmentScheme<Bn256>;
+
+    assert_impl_all!(Evals: Send, Sync);
+    assert_impl_all!(RationalEvals: Send, Sync);
+    assert_impl_all!(Poly: Send, Sync);
+    assert_impl_all!(ExtendedEvals: Send, Sync);
+    assert_impl_all!(ProvingKey<G1Affine>: Send, Sync);
+    assert_impl_all!(Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>: Send, Sync);
+    assert_impl_all!(PoseidonWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>: Send, Sync);
+    assert_impl_all!(Sha256Write<Vec<u8>, G1Affine, Challenge255<G1Affine>>: Send, Sync);
+
+    assert_impl_all!(GWCProver<Scheme>: Send);
+    assert_impl_all!(SHPlonkProver<Scheme>: Send);
+    assert_impl_all!(AnyProver<Scheme>: Send);
+    assert_not_impl_any!(GWCProver<Scheme>: Sync);
+    assert_not_impl_any!(SHPlonkProver<Scheme>: Sync);
+    assert_not_impl_any!(AnyProver<Scheme>: Sync);
+    assert_impl_all!(ProofGuard<'static, SHPlonkProver<Scheme>>: Send);
+    assert_not_impl_any!(ProofGuard<'static, SHPlonkProver<Scheme>>: Sync);
+
+    assert_not_impl_any!(G1MSMGpu: Send, Sync);
+
+    assert_impl_all!(Evals: Debug);
+    assert_impl_all!(ProvingKey<G1Affine>: Debug);
+    assert_impl_all!(AnyProver<Scheme>: Debug);
+    assert_impl_all!(Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>: Debug);
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_debug_evals() {
+        let prover = SHPlonkProver::<Scheme>::new(TranscriptType::Blake2b as u8, 3, &Fr::from(2));
+        let mut evals = prover.empty_evals();
+        for i in 0..evals.len() {
+            evals.set_value(i, &Fr::from(i as u64));
+        }
+        let values = |range: std::ops::Range<u64>| {
+            range
+                .map(|i| format!("{:?}", Fr::from(i)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        #[cfg(not(feature = "debug-values"))]
+        assert_eq!(format!("{:?}", evals), "Evals { len: 8 }");
+        #[cfg(feature = "debug-values")]
+        assert_eq!(
+            format!("{:?}", evals),
+            format!(
+                "Evals {{ len: 8, values: [{}, .., {}] }}",
+                values(0..3),
+                values(5..8)
+            )
+        );
+        assert_eq!(
+            format!("{:?}", evals.debug_full()),
+            format!("Evals {{ len: 8, values: [{}] }}", values(0..8))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_debug_prover() {
+        let s = Fr::from(0x5eed);
+        let mut prover = AnyProver::<Scheme>::new(
+            MultiOpenScheme::SHPlonk,
+            TranscriptType::Blake2b as u8,
+            4,
+            &s,
+        );
+        assert_eq!(
+            format!("{:?}", prover),
+            "SHPlonkProver { k: 4, transcript_type: Blake2b, lagrange_basis_policy: Resident, \
+             master_key: None, pooled_evals: 0, pooled_rational_evals: 0 }"
+        );
+
+        prover.set_master_key([0xab; 32]);
+        let debug = format!("{:?}", prover);
+        assert_eq!(
+            debug,
+            "SHPlonkProver { k: 4, transcript_type: Blake2b, lagrange_basis_policy: Resident, \
+             master_key: Some(<redacted>), pooled_evals: 0, pooled_rational_evals: 0 }"
+        );
+        // Neither the master key nor the toxic waste shows up.
+        assert!(!debug.contains("171"));
+        assert!(!debug.contains(&format!("{:?}", s)));
+        assert!(format!("{:?}", prover.debug_full()).contains(&format!("{:?}", [0xabu8; 32])));
+    }
+
+    // Commits 200 columns with the scratch buffers of the MSMs kept between
+    // the commitments and with them freed before every commitment, which is
+    // what every commitment did before they were kept. Run with:
+    //   cargo test --release -- --ignored bench_scratch_reuse --nocapture
+    #[test]
+    #[ignore]
+    fn bench_scratch_reuse() {
+        let k = 14;
+        let mut prover =
+            SHPlonkProver::<Scheme>::new(TranscriptType::Blake2b as u8, k, &Fr::from(2));
+        let columns: Vec<_> = (0..200u64)
+            .map(|i| {
+                let mut evals = prover.empty_evals();
+                for j in 0..evals.len() {
+                    evals.set_value(j, &Fr::from(i * (1 << k) + j as u64 + 1));
+                }
+                evals
+            })
+            .collect();
+        for _ in 0..3 {
+            let start = Instant::now();
+            let reused: Vec<_> = columns
+                .iter()
+                .map(|evals| prover.commit_lagrange(evals))
+                .collect();
+            println!("reused:    {:?}", start.elapsed());
+
+            let start = Instant::now();
+            let released: Vec<_> = columns
+                .iter()
+                .map(|evals| {
+                    prover.release_scratch();
+                    prover.commit_lagrange(evals)
+                })
+                .collect();
+            println!("per call:  {:?}", start.elapsed());
+            assert_eq!(reused, released);
+        }
+        println!("{:?}", prover.scratch_stats());
+    }
+}
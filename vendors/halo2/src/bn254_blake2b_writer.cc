@@ -3,6 +3,7 @@
 #include <string.h>
 
 #include "tachyon/base/logging.h"
+#include "vendors/halo2/include/dlopen.h"
 
 namespace tachyon::halo2_api::bn254 {
 
@@ -43,8 +44,22 @@ rust::Vec<uint8_t> Blake2bWriter::state() const {
   return ret;
 }
 
+std::unique_ptr<Blake2bWriter> Blake2bWriter::clone() const {
+  rust::Vec<uint8_t> state = this->state();
+  return std::make_unique<Blake2bWriter>(
+      tachyon_halo2_bn254_transcript_writer_create_from_state(
+          TACHYON_HALO2_BLAKE2B_TRANSCRIPT, state.data(), state.size()));
+}
+
 std::unique_ptr<Blake2bWriter> new_blake2b_writer() {
   return std::make_unique<Blake2bWriter>();
 }
 
+std::unique_ptr<Blake2bWriter> new_blake2b_writer_from_state(
+    rust::Slice<const uint8_t> state) {
+  return std::make_unique<Blake2bWriter>(
+      tachyon_halo2_bn254_transcript_writer_create_from_state(
+          TACHYON_HALO2_BLAKE2B_TRANSCRIPT, state.data(), state.size()));
+}
+
 }  // namespace tachyon::halo2_api::bn254
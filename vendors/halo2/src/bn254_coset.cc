@@ -0,0 +1,56 @@
+#include "vendors/halo2/include/bn254_coset.h"
+
+#include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluation_domain.h"
+#include "vendors/halo2/include/bn254_evals.h"
+#include "vendors/halo2/include/bn254_poly.h"
+#include "vendors/halo2/include/dlopen.h"
+#include "vendors/halo2/src/bn254.rs.h"
+
+namespace tachyon::halo2_api::bn254 {
+
+namespace {
+
+class ScopedDomain {
+ public:
+  explicit ScopedDomain(uint32_t k)
+      : domain_(tachyon_bn254_univariate_evaluation_domain_create(size_t{1}
+                                                                  << k)) {}
+  ScopedDomain(const ScopedDomain& other) = delete;
+  ScopedDomain& operator=(const ScopedDomain& other) = delete;
+  ~ScopedDomain() { tachyon_bn254_univariate_evaluation_domain_destroy(domain_); }
+
+  const tachyon_bn254_univariate_evaluation_domain* get() const {
+    return domain_;
+  }
+
+ private:
+  tachyon_bn254_univariate_evaluation_domain* domain_;
+};
+
+}  // namespace
+
+std::unique_ptr<Evals> empty_coset_evals(uint32_t k) {
+  ScopedDomain domain(k);
+  return std::make_unique<Evals>(
+      tachyon_bn254_univariate_evaluation_domain_empty_evals(domain.get()));
+}
+
+std::unique_ptr<Evals> coset_fft(const Poly& poly, uint32_t k,
+                                 const Fr& offset) {
+  ScopedDomain domain(k);
+  return std::make_unique<Evals>(
+      tachyon_bn254_univariate_evaluation_domain_coset_fft(
+          domain.get(), poly.poly(),
+          reinterpret_cast<const tachyon_bn254_fr*>(&offset)));
+}
+
+std::unique_ptr<Poly> coset_ifft(const Evals& evals, uint32_t k,
+                                 const Fr& offset) {
+  ScopedDomain domain(k);
+  return std::make_unique<Poly>(
+      tachyon_bn254_univariate_evaluation_domain_coset_ifft(
+          domain.get(), evals.evals(),
+          reinterpret_cast<const tachyon_bn254_fr*>(&offset)));
+}
+
+}  // namespace tachyon::halo2_api::bn254
@@ -1,5 +1,7 @@
 #include "vendors/halo2/include/bn254_evals.h"
 
+#include "tachyon/base/logging.h"
+#include "vendors/halo2/include/dlopen.h"
 #include "vendors/halo2/src/bn254.rs.h"
 
 namespace tachyon::halo2_api::bn254 {
@@ -12,11 +14,31 @@ size_t Evals::len() const {
   return tachyon_bn254_univariate_evaluations_len(evals_);
 }
 
+rust::Box<Fr> Evals::get_value(size_t idx) const {
+  tachyon_bn254_fr* ret = new tachyon_bn254_fr;
+  *ret = tachyon_bn254_univariate_evaluations_get_value(evals_, idx);
+  return rust::Box<Fr>::from_raw(reinterpret_cast<Fr*>(ret));
+}
+
 void Evals::set_value(size_t idx, const Fr& fr) {
   tachyon_bn254_univariate_evaluations_set_value(
       evals_, idx, reinterpret_cast<const tachyon_bn254_fr*>(&fr));
 }
 
+void Evals::get_values(rust::Slice<Fr> values) const {
+  CHECK_EQ(values.length(), len());
+  tachyon_bn254_univariate_evaluations_get_values(
+      evals_, reinterpret_cast<tachyon_bn254_fr*>(values.data()));
+}
+
+void Evals::set_values(rust::Slice<const Fr> values) {
+  tachyon_bn254_univariate_evaluations_set_values(
+      evals_, reinterpret_cast<const tachyon_bn254_fr*>(values.data()),
+      values.length());
+}
+
+void Evals::reset() { tachyon_bn254_univariate_evaluations_reset(evals_); }
+
 std::unique_ptr<Evals> Evals::clone() const {
   return std::make_unique<Evals>(
       tachyon_bn254_univariate_evaluations_clone(evals_));
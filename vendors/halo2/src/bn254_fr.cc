@@ -0,0 +1,23 @@
+#include "vendors/halo2/include/bn254_fr.h"
+
+#include "tachyon/base/logging.h"
+#include "tachyon/c/math/elliptic_curves/bn/bn254/fr.h"
+#include "vendors/halo2/include/dlopen.h"
+#include "vendors/halo2/src/bn254.rs.h"
+
+namespace tachyon::halo2_api::bn254 {
+
+void batch_inverse(rust::Slice<const Fr> values, rust::Slice<Fr> inverses) {
+  CHECK_EQ(values.length(), inverses.length());
+  tachyon_bn254_fr_batch_inverse(
+      reinterpret_cast<const tachyon_bn254_fr*>(values.data()),
+      values.length(), reinterpret_cast<tachyon_bn254_fr*>(inverses.data()));
+}
+
+void batch_inverse_in_place(rust::Slice<Fr> values) {
+  tachyon_bn254_fr* c_values =
+      reinterpret_cast<tachyon_bn254_fr*>(values.data());
+  tachyon_bn254_fr_batch_inverse(c_values, values.length(), c_values);
+}
+
+}  // namespace tachyon::halo2_api::bn254
@@ -1,8 +1,12 @@
 #include "vendors/halo2/include/bn254_gwc_prover.h"
 
+#include <vector>
+
 #include "tachyon/base/buffer/buffer.h"
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluation_domain.h"
 #include "tachyon/rs/base/rust_vec.h"
+#include "vendors/halo2/include/bn254_transcript_callbacks.h"
+#include "vendors/halo2/include/dlopen.h"
 #include "vendors/halo2/src/bn254.rs.h"
 
 namespace tachyon::halo2_api::bn254 {
@@ -64,6 +68,14 @@ std::unique_ptr<Poly> GWCProver::ifft(const Evals& evals) const {
       tachyon_halo2_bn254_gwc_prover_get_domain(prover_), evals.evals()));
 }
 
+ColumnStats GWCProver::column_stats(const Evals& evals) const {
+  tachyon_bn254_univariate_column_stats stats;
+  tachyon_bn254_univariate_evaluation_domain_column_stats(
+      tachyon_halo2_bn254_gwc_prover_get_domain(prover_), evals.evals(),
+      &stats);
+  return {stats.num_nonzero_rows, stats.num_coeffs, stats.entropy};
+}
+
 void GWCProver::batch_evaluate(
     rust::Slice<const std::unique_ptr<RationalEvals>> rational_evals,
     rust::Slice<std::unique_ptr<Evals>> evals) const {
@@ -84,10 +96,44 @@ void GWCProver::set_transcript(rust::Slice<const uint8_t> state) {
                                                       state.size());
 }
 
+void GWCProver::set_transcript_callbacks(TranscriptCallbacks* callbacks) {
+  tachyon_halo2_bn254_transcript_callbacks c_callbacks =
+      to_c_transcript_callbacks(callbacks);
+  tachyon_halo2_bn254_gwc_prover_set_transcript_callbacks(prover_,
+                                                          &c_callbacks);
+}
+
 void GWCProver::set_extended_domain(const ProvingKey& pk) {
   tachyon_halo2_bn254_gwc_prover_set_extended_domain(prover_, pk.pk());
 }
 
+void GWCProver::set_intra_proof_parallelism(size_t intra_proof_parallelism) {
+  tachyon_halo2_bn254_gwc_prover_set_intra_proof_parallelism(
+      prover_, intra_proof_parallelism);
+}
+
+void GWCProver::set_gpu_stages(uint8_t gpu_stages) {
+  tachyon_halo2_bn254_gwc_prover_set_gpu_stages(prover_, gpu_stages);
+}
+
+void GWCProver::set_fft_chunk_log2(uint32_t fft_chunk_log2) {
+  tachyon_halo2_bn254_gwc_prover_set_fft_chunk_log2(prover_, fft_chunk_log2);
+}
+
+void GWCProver::set_num_threads(size_t num_threads) {
+  tachyon_halo2_bn254_gwc_prover_set_num_threads(prover_, num_threads);
+}
+
+bool GWCProver::set_lagrange_basis_policy(uint8_t policy) {
+  return tachyon_halo2_bn254_gwc_prover_set_lagrange_basis_policy(prover_,
+                                                                  policy);
+}
+
+void GWCProver::set_record_gate_costs(bool record_gate_costs) {
+  tachyon_halo2_bn254_gwc_prover_set_record_gate_costs(prover_,
+                                                       record_gate_costs);
+}
+
 void GWCProver::create_proof(ProvingKey& key,
                              rust::Slice<InstanceSingle> instance_singles,
                              rust::Slice<AdviceSingle> advice_singles,
@@ -187,6 +233,50 @@ rust::Vec<uint8_t> GWCProver::get_proof() const {
   return proof;
 }
 
+rust::Vec<uint8_t> GWCProver::get_transcript_state() const {
+  size_t state_len;
+  tachyon_halo2_bn254_gwc_prover_get_transcript_state(prover_, nullptr,
+                                                      &state_len);
+  rust::Vec<uint8_t> state;
+  // NOTE(chokobole): |rust::Vec<uint8_t>| doesn't have |resize()|.
+  state.reserve(state_len);
+  for (size_t i = 0; i < state_len; ++i) {
+    state.push_back(0);
+  }
+  tachyon_halo2_bn254_gwc_prover_get_transcript_state(prover_, state.data(),
+                                                      &state_len);
+  return state;
+}
+
+rust::Vec<uint8_t> GWCProver::pcs_params() const {
+  size_t params_len;
+  tachyon_halo2_bn254_gwc_prover_get_pcs_params(prover_, nullptr, &params_len);
+  rust::Vec<uint8_t> params;
+  // NOTE(chokobole): |rust::Vec<uint8_t>| doesn't have |resize()|.
+  params.reserve(params_len);
+  for (size_t i = 0; i < params_len; ++i) {
+    params.push_back(0);
+  }
+  tachyon_halo2_bn254_gwc_prover_get_pcs_params(prover_, params.data(),
+                                                &params_len);
+  return params;
+}
+
+rust::Vec<GateCost> GWCProver::gate_costs() const {
+  size_t gate_costs_len;
+  tachyon_halo2_bn254_gwc_prover_get_gate_costs(prover_, nullptr,
+                                                &gate_costs_len);
+  std::vector<tachyon_halo2_gate_cost> costs(gate_costs_len);
+  tachyon_halo2_bn254_gwc_prover_get_gate_costs(prover_, costs.data(),
+                                                &gate_costs_len);
+  rust::Vec<GateCost> ret;
+  ret.reserve(costs.size());
+  for (const tachyon_halo2_gate_cost& cost : costs) {
+    ret.push_back({cost.nanos, cost.num_ops});
+  }
+  return ret;
+}
+
 std::unique_ptr<GWCProver> new_gwc_prover(uint8_t transcript_type, uint32_t k,
                                           const Fr& s) {
   return std::make_unique<GWCProver>(transcript_type, k, s);
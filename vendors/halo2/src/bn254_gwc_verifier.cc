@@ -0,0 +1,52 @@
+#include "vendors/halo2/include/bn254_gwc_verifier.h"
+
+#include "tachyon/base/logging.h"
+#include "vendors/halo2/include/bn254_proving_key.h"
+#include "vendors/halo2/include/dlopen.h"
+#include "vendors/halo2/src/bn254.rs.h"
+
+namespace tachyon::halo2_api::bn254 {
+
+bool commit_gwc_instance_columns(uint8_t transcript_type, uint32_t k,
+                                 rust::Slice<const uint8_t> pcs_params,
+                                 const ProvingKey& pk,
+                                 rust::Slice<const size_t> column_lens,
+                                 rust::Slice<const Fr> values,
+                                 rust::Slice<G1Point2> commitments) {
+  CHECK_EQ(column_lens.length(), commitments.length());
+  // NOTE: The commitments don't depend on the proof, so the verifier is
+  // created without one.
+  tachyon_halo2_bn254_gwc_verifier* verifier =
+      tachyon_halo2_bn254_gwc_verifier_create_from_params(
+          transcript_type, k, pcs_params.data(), pcs_params.length(), nullptr,
+          0);
+  bool ret = tachyon_halo2_bn254_gwc_verifier_commit_instance_columns(
+      verifier, tachyon_bn254_plonk_proving_key_get_verifying_key(pk.pk()),
+      reinterpret_cast<const tachyon_bn254_fr*>(values.data()),
+      column_lens.data(), column_lens.length(),
+      reinterpret_cast<tachyon_bn254_g1_point2*>(commitments.data()));
+  tachyon_halo2_bn254_gwc_verifier_destroy(verifier);
+  return ret;
+}
+
+bool verify_gwc_proof_with_instance_commitments(
+    uint8_t transcript_type, uint32_t k,
+    rust::Slice<const uint8_t> pcs_params, rust::Slice<const uint8_t> proof,
+    const ProvingKey& pk, size_t num_circuits,
+    rust::Slice<const G1Point2> commitments) {
+  CHECK_GT(num_circuits, size_t{0});
+  CHECK_EQ(commitments.length() % num_circuits, size_t{0});
+  tachyon_halo2_bn254_gwc_verifier* verifier =
+      tachyon_halo2_bn254_gwc_verifier_create_from_params(
+          transcript_type, k, pcs_params.data(), pcs_params.length(),
+          proof.data(), proof.length());
+  bool ret =
+      tachyon_halo2_bn254_gwc_verifier_verify_proof_with_instance_commitments(
+          verifier, tachyon_bn254_plonk_proving_key_get_verifying_key(pk.pk()),
+          reinterpret_cast<const tachyon_bn254_g1_point2*>(commitments.data()),
+          num_circuits, commitments.length() / num_circuits);
+  tachyon_halo2_bn254_gwc_verifier_destroy(verifier);
+  return ret;
+}
+
+}  // namespace tachyon::halo2_api::bn254
@@ -1,6 +1,7 @@
 #include "vendors/halo2/include/bn254_msm.h"
 
 #include "tachyon/c/math/elliptic_curves/bn/bn254/msm.h"
+#include "vendors/halo2/include/dlopen.h"
 #include "vendors/halo2/src/bn254.rs.h"
 
 namespace tachyon::halo2_api::bn254 {
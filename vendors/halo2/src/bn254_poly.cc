@@ -1,9 +1,36 @@
 #include "vendors/halo2/include/bn254_poly.h"
 
+#include "tachyon/base/logging.h"
+#include "vendors/halo2/include/dlopen.h"
+#include "vendors/halo2/src/bn254.rs.h"
+
 namespace tachyon::halo2_api::bn254 {
 
 Poly::Poly() : poly_(tachyon_bn254_univariate_dense_polynomial_create()) {}
 
 Poly::~Poly() { tachyon_bn254_univariate_dense_polynomial_destroy(poly_); }
 
+size_t Poly::len() const {
+  return tachyon_bn254_univariate_dense_polynomial_len(poly_);
+}
+
+void Poly::get_coeffs(rust::Slice<Fr> coeffs) const {
+  CHECK_EQ(coeffs.length(), len());
+  tachyon_bn254_univariate_dense_polynomial_get_coeffs(
+      poly_, reinterpret_cast<tachyon_bn254_fr*>(coeffs.data()));
+}
+
+void Poly::set_coeffs(rust::Slice<const Fr> coeffs) {
+  tachyon_bn254_univariate_dense_polynomial_set_coeffs(
+      poly_, reinterpret_cast<const tachyon_bn254_fr*>(coeffs.data()),
+      coeffs.length());
+}
+
+std::unique_ptr<Poly> Poly::clone() const {
+  return std::make_unique<Poly>(
+      tachyon_bn254_univariate_dense_polynomial_clone(poly_));
+}
+
+std::unique_ptr<Poly> zero_poly() { return std::make_unique<Poly>(); }
+
 }  // namespace tachyon::halo2_api::bn254
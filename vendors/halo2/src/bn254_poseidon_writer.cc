@@ -1,5 +1,7 @@
 #include "vendors/halo2/include/bn254_poseidon_writer.h"
 
+#include "vendors/halo2/include/dlopen.h"
+
 namespace tachyon::halo2_api::bn254 {
 
 PoseidonWriter::PoseidonWriter()
@@ -36,8 +38,22 @@ rust::Vec<uint8_t> PoseidonWriter::state() const {
   return ret;
 }
 
+std::unique_ptr<PoseidonWriter> PoseidonWriter::clone() const {
+  rust::Vec<uint8_t> state = this->state();
+  return std::make_unique<PoseidonWriter>(
+      tachyon_halo2_bn254_transcript_writer_create_from_state(
+          TACHYON_HALO2_POSEIDON_TRANSCRIPT, state.data(), state.size()));
+}
+
 std::unique_ptr<PoseidonWriter> new_poseidon_writer() {
   return std::make_unique<PoseidonWriter>();
 }
 
+std::unique_ptr<PoseidonWriter> new_poseidon_writer_from_state(
+    rust::Slice<const uint8_t> state) {
+  return std::make_unique<PoseidonWriter>(
+      tachyon_halo2_bn254_transcript_writer_create_from_state(
+          TACHYON_HALO2_POSEIDON_TRANSCRIPT, state.data(), state.size()));
+}
+
 }  // namespace tachyon::halo2_api::bn254
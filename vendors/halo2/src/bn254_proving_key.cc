@@ -1,5 +1,8 @@
 #include "vendors/halo2/include/bn254_proving_key.h"
 
+#include <vector>
+
+#include "vendors/halo2/include/dlopen.h"
 #include "vendors/halo2/src/bn254.rs.h"
 
 namespace tachyon::halo2_api::bn254 {
@@ -11,6 +14,8 @@ using GetPhasesAPI = void (*)(const tachyon_bn254_plonk_constraint_system*,
 using GetFixedColumnsAPI =
     void (*)(const tachyon_bn254_plonk_constraint_system*,
              tachyon_fixed_column_key*, size_t*);
+using GetQueriesAPI = void (*)(const tachyon_bn254_plonk_constraint_system*,
+                               tachyon_plonk_column_query*, size_t*);
 
 rust::Vec<uint8_t> DoGetPhases(const tachyon_bn254_plonk_constraint_system* cs,
                                GetPhasesAPI api) {
@@ -41,6 +46,20 @@ rust::Vec<size_t> GetFixedColumns(
   return fixed_columns;
 }
 
+rust::Vec<ColumnQuery> GetQueries(
+    const tachyon_bn254_plonk_constraint_system* cs, GetQueriesAPI api) {
+  size_t queries_len;
+  api(cs, nullptr, &queries_len);
+  std::vector<tachyon_plonk_column_query> queries(queries_len);
+  api(cs, queries.data(), &queries_len);
+  rust::Vec<ColumnQuery> ret;
+  ret.reserve(queries_len);
+  for (const tachyon_plonk_column_query& query : queries) {
+    ret.push_back(ColumnQuery{query.index, query.rotation});
+  }
+  return ret;
+}
+
 }  // namespace
 
 ProvingKey::ProvingKey(rust::Slice<const uint8_t> pk_bytes)
@@ -52,7 +71,7 @@ ProvingKey::~ProvingKey() { tachyon_bn254_plonk_proving_key_destroy(pk_); }
 rust::Vec<uint8_t> ProvingKey::advice_column_phases() const {
   return DoGetPhases(
       GetConstraintSystem(),
-      &tachyon_bn254_plonk_constraint_system_get_advice_column_phases);
+      tachyon_bn254_plonk_constraint_system_get_advice_column_phases);
 }
 
 uint32_t ProvingKey::blinding_factors() const {
@@ -63,12 +82,22 @@ uint32_t ProvingKey::blinding_factors() const {
 rust::Vec<uint8_t> ProvingKey::challenge_phases() const {
   return DoGetPhases(
       GetConstraintSystem(),
-      &tachyon_bn254_plonk_constraint_system_get_challenge_phases);
+      tachyon_bn254_plonk_constraint_system_get_challenge_phases);
 }
 
 rust::Vec<size_t> ProvingKey::constants() const {
   return GetFixedColumns(GetConstraintSystem(),
-                         &tachyon_bn254_plonk_constraint_system_get_constants);
+                         tachyon_bn254_plonk_constraint_system_get_constants);
+}
+
+size_t ProvingKey::degree() const {
+  return tachyon_bn254_plonk_constraint_system_compute_degree(
+      GetConstraintSystem());
+}
+
+uint32_t ProvingKey::extended_k(uint32_t k) const {
+  return tachyon_bn254_plonk_constraint_system_compute_extended_k(
+      GetConstraintSystem(), k);
 }
 
 size_t ProvingKey::num_advice_columns() const {
@@ -81,14 +110,56 @@ size_t ProvingKey::num_challenges() const {
       GetConstraintSystem());
 }
 
+size_t ProvingKey::num_fixed_columns() const {
+  return tachyon_bn254_plonk_constraint_system_get_num_fixed_columns(
+      GetConstraintSystem());
+}
+
 size_t ProvingKey::num_instance_columns() const {
   return tachyon_bn254_plonk_constraint_system_get_num_instance_columns(
       GetConstraintSystem());
 }
 
+size_t ProvingKey::num_lookups() const {
+  return tachyon_bn254_plonk_constraint_system_get_num_lookups(
+      GetConstraintSystem());
+}
+
+size_t ProvingKey::num_permutation_columns() const {
+  return tachyon_bn254_plonk_constraint_system_get_num_permutation_columns(
+      GetConstraintSystem());
+}
+
 rust::Vec<uint8_t> ProvingKey::phases() const {
   return DoGetPhases(GetConstraintSystem(),
-                     &tachyon_bn254_plonk_constraint_system_get_phases);
+                     tachyon_bn254_plonk_constraint_system_get_phases);
+}
+
+rust::Vec<size_t> ProvingKey::selector_map() const {
+  return GetFixedColumns(
+      GetConstraintSystem(),
+      tachyon_bn254_plonk_constraint_system_get_selector_map);
+}
+
+rust::Vec<size_t> ProvingKey::fixed_query_columns() const {
+  return GetFixedColumns(
+      GetConstraintSystem(),
+      tachyon_bn254_plonk_constraint_system_get_fixed_query_columns);
+}
+
+rust::Vec<ColumnQuery> ProvingKey::advice_queries() const {
+  return GetQueries(GetConstraintSystem(),
+                    tachyon_bn254_plonk_constraint_system_get_advice_queries);
+}
+
+rust::Vec<ColumnQuery> ProvingKey::instance_queries() const {
+  return GetQueries(GetConstraintSystem(),
+                    tachyon_bn254_plonk_constraint_system_get_instance_queries);
+}
+
+rust::Vec<ColumnQuery> ProvingKey::fixed_queries() const {
+  return GetQueries(GetConstraintSystem(),
+                    tachyon_bn254_plonk_constraint_system_get_fixed_queries);
 }
 
 const tachyon_bn254_plonk_verifying_key* ProvingKey::GetVerifyingKey() const {
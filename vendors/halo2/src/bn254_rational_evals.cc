@@ -1,5 +1,6 @@
 #include "vendors/halo2/include/bn254_rational_evals.h"
 
+#include "vendors/halo2/include/dlopen.h"
 #include "vendors/halo2/src/bn254.rs.h"
 
 namespace tachyon::halo2_api::bn254 {
@@ -19,6 +20,10 @@ void RationalEvals::set_zero(size_t idx) {
   tachyon_bn254_univariate_rational_evaluations_set_zero(evals_, idx);
 }
 
+void RationalEvals::reset() {
+  tachyon_bn254_univariate_rational_evaluations_reset(evals_);
+}
+
 void RationalEvals::set_trivial(size_t idx, const Fr& numerator) {
   tachyon_bn254_univariate_rational_evaluations_set_trivial(
       evals_, idx, reinterpret_cast<const tachyon_bn254_fr*>(&numerator));
@@ -3,6 +3,7 @@
 #include <string.h>
 
 #include "tachyon/base/logging.h"
+#include "vendors/halo2/include/dlopen.h"
 
 namespace tachyon::halo2_api::bn254 {
 
@@ -42,8 +43,22 @@ rust::Vec<uint8_t> Sha256Writer::state() const {
   return ret;
 }
 
+std::unique_ptr<Sha256Writer> Sha256Writer::clone() const {
+  rust::Vec<uint8_t> state = this->state();
+  return std::make_unique<Sha256Writer>(
+      tachyon_halo2_bn254_transcript_writer_create_from_state(
+          TACHYON_HALO2_SHA256_TRANSCRIPT, state.data(), state.size()));
+}
+
 std::unique_ptr<Sha256Writer> new_sha256_writer() {
   return std::make_unique<Sha256Writer>();
 }
 
+std::unique_ptr<Sha256Writer> new_sha256_writer_from_state(
+    rust::Slice<const uint8_t> state) {
+  return std::make_unique<Sha256Writer>(
+      tachyon_halo2_bn254_transcript_writer_create_from_state(
+          TACHYON_HALO2_SHA256_TRANSCRIPT, state.data(), state.size()));
+}
+
 }  // namespace tachyon::halo2_api::bn254
@@ -1,8 +1,12 @@
 #include "vendors/halo2/include/bn254_shplonk_prover.h"
 
+#include <vector>
+
 #include "tachyon/base/buffer/buffer.h"
 #include "tachyon/c/math/polynomials/univariate/bn254_univariate_evaluation_domain.h"
 #include "tachyon/rs/base/rust_vec.h"
+#include "vendors/halo2/include/bn254_transcript_callbacks.h"
+#include "vendors/halo2/include/dlopen.h"
 #include "vendors/halo2/src/bn254.rs.h"
 
 namespace tachyon::halo2_api::bn254 {
@@ -67,6 +71,14 @@ std::unique_ptr<Poly> SHPlonkProver::ifft(const Evals& evals) const {
       tachyon_halo2_bn254_shplonk_prover_get_domain(prover_), evals.evals()));
 }
 
+ColumnStats SHPlonkProver::column_stats(const Evals& evals) const {
+  tachyon_bn254_univariate_column_stats stats;
+  tachyon_bn254_univariate_evaluation_domain_column_stats(
+      tachyon_halo2_bn254_shplonk_prover_get_domain(prover_), evals.evals(),
+      &stats);
+  return {stats.num_nonzero_rows, stats.num_coeffs, stats.entropy};
+}
+
 void SHPlonkProver::batch_evaluate(
     rust::Slice<const std::unique_ptr<RationalEvals>> rational_evals,
     rust::Slice<std::unique_ptr<Evals>> evals) const {
@@ -87,10 +99,44 @@ void SHPlonkProver::set_transcript(rust::Slice<const uint8_t> state) {
                                                           state.size());
 }
 
+void SHPlonkProver::set_transcript_callbacks(TranscriptCallbacks* callbacks) {
+  tachyon_halo2_bn254_transcript_callbacks c_callbacks =
+      to_c_transcript_callbacks(callbacks);
+  tachyon_halo2_bn254_shplonk_prover_set_transcript_callbacks(prover_,
+                                                              &c_callbacks);
+}
+
 void SHPlonkProver::set_extended_domain(const ProvingKey& pk) {
   tachyon_halo2_bn254_shplonk_prover_set_extended_domain(prover_, pk.pk());
 }
 
+void SHPlonkProver::set_intra_proof_parallelism(size_t intra_proof_parallelism) {
+  tachyon_halo2_bn254_shplonk_prover_set_intra_proof_parallelism(
+      prover_, intra_proof_parallelism);
+}
+
+void SHPlonkProver::set_gpu_stages(uint8_t gpu_stages) {
+  tachyon_halo2_bn254_shplonk_prover_set_gpu_stages(prover_, gpu_stages);
+}
+
+void SHPlonkProver::set_fft_chunk_log2(uint32_t fft_chunk_log2) {
+  tachyon_halo2_bn254_shplonk_prover_set_fft_chunk_log2(prover_, fft_chunk_log2);
+}
+
+void SHPlonkProver::set_num_threads(size_t num_threads) {
+  tachyon_halo2_bn254_shplonk_prover_set_num_threads(prover_, num_threads);
+}
+
+bool SHPlonkProver::set_lagrange_basis_policy(uint8_t policy) {
+  return tachyon_halo2_bn254_shplonk_prover_set_lagrange_basis_policy(prover_,
+                                                                      policy);
+}
+
+void SHPlonkProver::set_record_gate_costs(bool record_gate_costs) {
+  tachyon_halo2_bn254_shplonk_prover_set_record_gate_costs(prover_,
+                                                           record_gate_costs);
+}
+
 void SHPlonkProver::create_proof(ProvingKey& key,
                                  rust::Slice<InstanceSingle> instance_singles,
                                  rust::Slice<AdviceSingle> advice_singles,
@@ -177,6 +223,19 @@ void SHPlonkProver::create_proof(ProvingKey& key,
   tachyon_halo2_bn254_argument_data_destroy(data);
 }
 
+void SHPlonkProver::add_opening(const Poly& poly, const Fr& point) {
+  opening_polys_.push_back(poly.poly());
+  opening_points_.push_back(reinterpret_cast<const tachyon_bn254_fr&>(point));
+}
+
+void SHPlonkProver::create_opening_proof() {
+  tachyon_halo2_bn254_shplonk_prover_create_opening_proof(
+      prover_, opening_polys_.data(), opening_points_.data(),
+      opening_polys_.size());
+  opening_polys_.clear();
+  opening_points_.clear();
+}
+
 rust::Vec<uint8_t> SHPlonkProver::get_proof() const {
   size_t proof_len;
   tachyon_halo2_bn254_shplonk_prover_get_proof(prover_, nullptr, &proof_len);
@@ -191,6 +250,67 @@ rust::Vec<uint8_t> SHPlonkProver::get_proof() const {
   return proof;
 }
 
+rust::Vec<uint8_t> SHPlonkProver::get_transcript_state() const {
+  size_t state_len;
+  tachyon_halo2_bn254_shplonk_prover_get_transcript_state(prover_, nullptr,
+                                                          &state_len);
+  rust::Vec<uint8_t> state;
+  // NOTE(chokobole): |rust::Vec<uint8_t>| doesn't have |resize()|.
+  state.reserve(state_len);
+  for (size_t i = 0; i < state_len; ++i) {
+    state.push_back(0);
+  }
+  tachyon_halo2_bn254_shplonk_prover_get_transcript_state(prover_, state.data(),
+                                                          &state_len);
+  return state;
+}
+
+rust::Vec<uint8_t> SHPlonkProver::pcs_params() const {
+  size_t params_len;
+  tachyon_halo2_bn254_shplonk_prover_get_pcs_params(prover_, nullptr,
+                                                    &params_len);
+  rust::Vec<uint8_t> params;
+  // NOTE(chokobole): |rust::Vec<uint8_t>| doesn't have |resize()|.
+  params.reserve(params_len);
+  for (size_t i = 0; i < params_len; ++i) {
+    params.push_back(0);
+  }
+  tachyon_halo2_bn254_shplonk_prover_get_pcs_params(prover_, params.data(),
+                                                    &params_len);
+  return params;
+}
+
+rust::Vec<GateCost> SHPlonkProver::gate_costs() const {
+  size_t gate_costs_len;
+  tachyon_halo2_bn254_shplonk_prover_get_gate_costs(prover_, nullptr,
+                                                    &gate_costs_len);
+  std::vector<tachyon_halo2_gate_cost> costs(gate_costs_len);
+  tachyon_halo2_bn254_shplonk_prover_get_gate_costs(prover_, costs.data(),
+                                                    &gate_costs_len);
+  rust::Vec<GateCost> ret;
+  ret.reserve(costs.size());
+  for (const tachyon_halo2_gate_cost& cost : costs) {
+    ret.push_back({cost.nanos, cost.num_ops});
+  }
+  return ret;
+}
+
+AllocationStats SHPlonkProver::allocation_stats() const {
+  tachyon_halo2_bn254_shplonk_prover_allocation_stats stats;
+  tachyon_halo2_bn254_shplonk_prover_get_allocation_stats(prover_, &stats);
+  return {stats.srs_bytes, stats.transcript_bytes};
+}
+
+ScratchStats SHPlonkProver::scratch_stats() const {
+  tachyon_halo2_bn254_shplonk_prover_scratch_stats stats;
+  tachyon_halo2_bn254_shplonk_prover_get_scratch_stats(prover_, &stats);
+  return {stats.bytes, stats.num_allocations, stats.num_reuses};
+}
+
+void SHPlonkProver::release_scratch() {
+  tachyon_halo2_bn254_shplonk_prover_release_scratch(prover_);
+}
+
 std::unique_ptr<SHPlonkProver> new_shplonk_prover(uint8_t transcript_type,
                                                   uint32_t k, const Fr& s) {
   return std::make_unique<SHPlonkProver>(transcript_type, k, s);
@@ -0,0 +1,101 @@
+#include "vendors/halo2/include/bn254_shplonk_verifier.h"
+
+#include "tachyon/base/logging.h"
+#include "tachyon/c/zk/plonk/halo2/bn254_instance_columns_vec.h"
+#include "vendors/halo2/include/bn254_proving_key.h"
+#include "vendors/halo2/include/dlopen.h"
+#include "vendors/halo2/src/bn254.rs.h"
+
+namespace tachyon::halo2_api::bn254 {
+
+namespace {
+
+tachyon_halo2_bn254_instance_columns_vec* CreateInstanceColumnsVec(
+    size_t num_circuits, rust::Slice<const size_t> column_lens,
+    rust::Slice<const Fr> values) {
+  CHECK_GT(num_circuits, size_t{0});
+  CHECK_EQ(column_lens.length() % num_circuits, size_t{0});
+  size_t num_columns = column_lens.length() / num_circuits;
+
+  tachyon_halo2_bn254_instance_columns_vec* instance_columns_vec =
+      tachyon_halo2_bn254_instance_columns_vec_create(num_circuits);
+  const tachyon_bn254_fr* value =
+      reinterpret_cast<const tachyon_bn254_fr*>(values.data());
+  for (size_t i = 0; i < num_circuits; ++i) {
+    tachyon_halo2_bn254_instance_columns_vec_resize_columns(
+        instance_columns_vec, i, num_columns);
+    for (size_t j = 0; j < num_columns; ++j) {
+      size_t len = column_lens[i * num_columns + j];
+      tachyon_halo2_bn254_instance_columns_vec_reserve_values(
+          instance_columns_vec, i, j, len);
+      for (size_t l = 0; l < len; ++l) {
+        tachyon_halo2_bn254_instance_columns_vec_add_values(
+            instance_columns_vec, i, j, value++);
+      }
+    }
+  }
+  CHECK_EQ(value, reinterpret_cast<const tachyon_bn254_fr*>(values.data()) +
+                      values.length());
+  return instance_columns_vec;
+}
+
+}  // namespace
+
+bool verify_shplonk_proof(uint8_t transcript_type, uint32_t k,
+                          rust::Slice<const uint8_t> pcs_params,
+                          rust::Slice<const uint8_t> proof,
+                          const ProvingKey& pk, size_t num_circuits,
+                          rust::Slice<const size_t> column_lens,
+                          rust::Slice<const Fr> values) {
+  tachyon_halo2_bn254_instance_columns_vec* instance_columns_vec =
+      CreateInstanceColumnsVec(num_circuits, column_lens, values);
+
+  tachyon_halo2_bn254_shplonk_verifier* verifier =
+      tachyon_halo2_bn254_shplonk_verifier_create_from_params(
+          transcript_type, k, pcs_params.data(), pcs_params.length(),
+          proof.data(), proof.length());
+  // NOTE: |instance_columns_vec| is destroyed by the verifier.
+  bool ret = tachyon_halo2_bn254_shplonk_verifier_verify_proof(
+      verifier, tachyon_bn254_plonk_proving_key_get_verifying_key(pk.pk()),
+      instance_columns_vec);
+  tachyon_halo2_bn254_shplonk_verifier_destroy(verifier);
+  return ret;
+}
+
+bool verify_shplonk_proof_from_state(
+    uint8_t transcript_type, uint32_t k,
+    rust::Slice<const uint8_t> pcs_params, rust::Slice<const uint8_t> proof,
+    const ProvingKey& pk, size_t num_circuits,
+    rust::Slice<const size_t> column_lens, rust::Slice<const Fr> values,
+    rust::Slice<const uint8_t> state, rust::Vec<uint8_t>& next_state) {
+  tachyon_halo2_bn254_instance_columns_vec* instance_columns_vec =
+      CreateInstanceColumnsVec(num_circuits, column_lens, values);
+
+  tachyon_halo2_bn254_shplonk_verifier* verifier =
+      tachyon_halo2_bn254_shplonk_verifier_create_from_params(
+          transcript_type, k, pcs_params.data(), pcs_params.length(),
+          proof.data(), proof.length());
+  tachyon_halo2_bn254_shplonk_verifier_set_transcript_state(
+      verifier, state.data(), state.length());
+  // NOTE: |instance_columns_vec| is destroyed by the verifier.
+  bool ret = tachyon_halo2_bn254_shplonk_verifier_verify_proof(
+      verifier, tachyon_bn254_plonk_proving_key_get_verifying_key(pk.pk()),
+      instance_columns_vec);
+  if (ret) {
+    size_t state_len;
+    tachyon_halo2_bn254_shplonk_verifier_get_transcript_state(
+        verifier, nullptr, &state_len);
+    // NOTE(chokobole): |rust::Vec<uint8_t>| doesn't have |resize()|.
+    next_state.clear();
+    next_state.reserve(state_len);
+    for (size_t i = 0; i < state_len; ++i) {
+      next_state.push_back(0);
+    }
+    tachyon_halo2_bn254_shplonk_verifier_get_transcript_state(
+        verifier, next_state.data(), &state_len);
+  }
+  tachyon_halo2_bn254_shplonk_verifier_destroy(verifier);
+  return ret;
+}
+
+}  // namespace tachyon::halo2_api::bn254
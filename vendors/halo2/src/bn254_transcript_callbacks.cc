@@ -0,0 +1,31 @@
+#include "vendors/halo2/include/bn254_transcript_callbacks.h"
+
+#include "vendors/halo2/src/bn254.rs.h"
+
+namespace tachyon::halo2_api::bn254 {
+
+namespace {
+
+void AbsorbScalar(void* ctx, const tachyon_bn254_fr* scalar) {
+  reinterpret_cast<TranscriptCallbacks*>(ctx)->absorb_scalar(
+      reinterpret_cast<const Fr&>(*scalar));
+}
+
+void AbsorbPoint(void* ctx, const tachyon_bn254_g1_point2* point) {
+  reinterpret_cast<TranscriptCallbacks*>(ctx)->absorb_point(
+      reinterpret_cast<const G1Point2&>(*point));
+}
+
+void Squeeze(void* ctx, tachyon_bn254_fr* challenge) {
+  rust::Box<Fr> ret = reinterpret_cast<TranscriptCallbacks*>(ctx)->squeeze();
+  *challenge = reinterpret_cast<const tachyon_bn254_fr&>(*ret);
+}
+
+}  // namespace
+
+tachyon_halo2_bn254_transcript_callbacks to_c_transcript_callbacks(
+    TranscriptCallbacks* callbacks) {
+  return {callbacks, AbsorbScalar, AbsorbPoint, Squeeze};
+}
+
+}  // namespace tachyon::halo2_api::bn254
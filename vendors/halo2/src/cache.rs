@@ -0,0 +1,393 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt, fs, io,
+    path::PathBuf,
+};
+
+use ff::PrimeField;
+use halo2_proofs::{
+    plonk::Circuit,
+    poly::commitment::CommitmentScheme,
+    transcript::{EncodedChallenge, TranscriptWriterBuffer},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    bn254::{ProvingKey as TachyonProvingKey, TachyonProver, TranscriptWriteState},
+    error::TachyonError,
+    prover::create_proof,
+    version::check_backend,
+    xor_shift_rng::XORShiftRng as TachyonXORShiftRng,
+};
+
+/// Returns the SHA-256 of a serialized proving key, to be passed to
+/// [`ProofCacheKey::new()`]. Compute it once when the key is loaded.
+pub fn pk_checksum(pk_bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(pk_bytes).into()
+}
+
+/// Identifies a proof request. Two requests with the same key are expected to
+/// have interchangeable proofs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProofCacheKey(pub [u8; 32]);
+
+impl ProofCacheKey {
+    /// Hashes the proving key checksum, every instance value and, if the
+    /// circuit has private inputs, a digest of them. Leave `witness_digest`
+    /// out only if the instances determine the witness.
+    pub fn new<F: PrimeField>(
+        pk_checksum: &[u8; 32],
+        instances: &[&[&[F]]],
+        witness_digest: Option<&[u8; 32]>,
+    ) -> ProofCacheKey {
+        let mut hasher = Sha256::new();
+        hasher.update(b"tachyon_halo2 proof cache v1");
+        hasher.update(pk_checksum);
+        // The lengths are hashed too, so that moving a value to another column
+        // or circuit changes the key.
+        hasher.update((instances.len() as u64).to_le_bytes());
+        for circuit in instances {
+            hasher.update((circuit.len() as u64).to_le_bytes());
+            for column in *circuit {
+                hasher.update((column.len() as u64).to_le_bytes());
+                for value in *column {
+                    hasher.update(value.to_repr().as_ref());
+                }
+            }
+        }
+        match witness_digest {
+            Some(digest) => {
+                hasher.update([1]);
+                hasher.update(digest);
+            }
+            None => hasher.update([0]),
+        }
+        ProofCacheKey(hasher.finalize().into())
+    }
+
+    fn to_hex(self) -> String {
+        self.0.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+pub trait ProofCache {
+    /// Returns the proof stored for `key`, or `None` if there is none or it
+    /// can't be read back intact.
+    fn get(&mut self, key: &ProofCacheKey) -> Option<Vec<u8>>;
+
+    fn put(&mut self, key: ProofCacheKey, proof: Vec<u8>) -> io::Result<()>;
+}
+
+/// Keeps up to `capacity` proofs in memory and evicts the least recently used
+/// one first.
+pub struct MemoryProofCache {
+    capacity: usize,
+    proofs: HashMap<ProofCacheKey, Vec<u8>>,
+    /// Keys from the least to the most recently used.
+    order: VecDeque<ProofCacheKey>,
+}
+
+impl fmt::Debug for MemoryProofCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryProofCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.proofs.len())
+            .finish()
+    }
+}
+
+impl MemoryProofCache {
+    pub fn new(capacity: usize) -> MemoryProofCache {
+        MemoryProofCache {
+            capacity,
+            proofs: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.proofs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.proofs.is_empty()
+    }
+
+    fn touch(&mut self, key: &ProofCacheKey) {
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(*key);
+    }
+}
+
+impl ProofCache for MemoryProofCache {
+    fn get(&mut self, key: &ProofCacheKey) -> Option<Vec<u8>> {
+        let proof = self.proofs.get(key)?.clone();
+        self.touch(key);
+        Some(proof)
+    }
+
+    fn put(&mut self, key: ProofCacheKey, proof: Vec<u8>) -> io::Result<()> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+        self.touch(&key);
+        self.proofs.insert(key, proof);
+        while self.proofs.len() > self.capacity {
+            let oldest = self.order.pop_front().unwrap();
+            self.proofs.remove(&oldest);
+        }
+        Ok(())
+    }
+}
+
+/// Stores each proof in its own file under a directory. Every file carries the
+/// SHA-256 of the proof, and files that don't match it are treated as missing
+/// and removed.
+#[derive(Debug)]
+pub struct FsProofCache {
+    dir: PathBuf,
+}
+
+impl FsProofCache {
+    const MAGIC: &'static [u8; 4] = b"TPC1";
+    const HEADER_LEN: usize = 4 + 32;
+
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<FsProofCache> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(FsProofCache { dir })
+    }
+
+    fn path(&self, key: &ProofCacheKey) -> PathBuf {
+        self.dir.join(format!("{}.proof", key.to_hex()))
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Vec<u8>> {
+        if bytes.len() < Self::HEADER_LEN || &bytes[..4] != Self::MAGIC {
+            return None;
+        }
+        let proof = &bytes[Self::HEADER_LEN..];
+        if Sha256::digest(proof).as_slice() != &bytes[4..Self::HEADER_LEN] {
+            return None;
+        }
+        Some(proof.to_vec())
+    }
+}
+
+impl ProofCache for FsProofCache {
+    fn get(&mut self, key: &ProofCacheKey) -> Option<Vec<u8>> {
+        let path = self.path(key);
+        let bytes = fs::read(&path).ok()?;
+        let proof = Self::decode(&bytes);
+        if proof.is_none() {
+            let _ = fs::remove_file(&path);
+        }
+        proof
+    }
+
+    fn put(&mut self, key: ProofCacheKey, proof: Vec<u8>) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(Self::HEADER_LEN + proof.len());
+        bytes.extend_from_slice(Self::MAGIC);
+        bytes.extend_from_slice(&Sha256::digest(&proof));
+        bytes.extend_from_slice(&proof);
+        // Write to a temporary file first, so that a crash never leaves a
+        // partial file under the final name.
+        let path = self.path(&key);
+        let tmp_path = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(&tmp_path, &path)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CacheOptions {
+    /// Create a new proof even on a hit, and replace the stored one with it.
+    pub regenerate: bool,
+}
+
+/// Returns the proof stored for `key`, or creates one with [`create_proof()`]
+/// and stores it. Proofs are randomized, so a hit returns the stored bytes,
+/// which differ from what a new proof with `rng` would be.
+///
+/// Failing to store the proof doesn't fail the call, since the proof itself is
+/// good.
+#[allow(clippy::too_many_arguments)]
+pub fn prove_cached<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E> + TranscriptWriterBuffer<Vec<u8>, Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+    C: ProofCache,
+>(
+    prover: &mut P,
+    pk: &mut TachyonProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[Scheme::Scalar]]],
+    rng: TachyonXORShiftRng,
+    mut transcript: T,
+    cache: &mut C,
+    key: &ProofCacheKey,
+    options: &CacheOptions,
+) -> Result<Vec<u8>, TachyonError> {
+    check_backend()?;
+    if !options.regenerate {
+        if let Some(proof) = cache.get(key) {
+            return Ok(proof);
+        }
+    }
+    create_proof(prover, pk, circuits, instances, rng, &mut transcript)?;
+    let mut proof = transcript.finalize();
+    proof.extend_from_slice(&prover.get_proof());
+    let _ = cache.put(*key, proof.clone());
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::PathBuf};
+
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::TranscriptWriterBuffer,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+    use rand_core::SeedableRng;
+
+    use super::{
+        pk_checksum, prove_cached, CacheOptions, FsProofCache, MemoryProofCache, ProofCache,
+        ProofCacheKey,
+    };
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{TranscriptType, SEED},
+        xor_shift_rng::XORShiftRng,
+    };
+
+    fn key(value: u64) -> ProofCacheKey {
+        ProofCacheKey::new(&[0; 32], &[&[&[Fr::from(value)]]], None)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("tachyon_halo2_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_key() {
+        assert_eq!(key(1), key(1));
+        assert_ne!(key(1), key(2));
+        assert_ne!(
+            ProofCacheKey::new(&[0; 32], &[&[&[Fr::from(1)]]], None),
+            ProofCacheKey::new(&[1; 32], &[&[&[Fr::from(1)]]], None)
+        );
+        assert_ne!(
+            ProofCacheKey::new(&[0; 32], &[&[&[Fr::from(1)], &[]]], None),
+            ProofCacheKey::new(&[0; 32], &[&[&[], &[Fr::from(1)]]], None)
+        );
+        assert_ne!(
+            key(1),
+            ProofCacheKey::new(&[0; 32], &[&[&[Fr::from(1)]]], Some(&[0; 32]))
+        );
+    }
+
+    #[test]
+    fn test_memory_cache() {
+        let mut cache = MemoryProofCache::new(2);
+        assert_eq!(cache.get(&key(1)), None);
+
+        cache.put(key(1), vec![1]).unwrap();
+        cache.put(key(2), vec![2]).unwrap();
+        assert_eq!(cache.get(&key(1)), Some(vec![1]));
+
+        // `key(2)` is now the least recently used.
+        cache.put(key(3), vec![3]).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&key(2)), None);
+        assert_eq!(cache.get(&key(1)), Some(vec![1]));
+        assert_eq!(cache.get(&key(3)), Some(vec![3]));
+    }
+
+    #[test]
+    fn test_fs_cache() {
+        let dir = temp_dir("test_fs_cache");
+        let mut cache = FsProofCache::new(&dir).unwrap();
+        assert_eq!(cache.get(&key(1)), None);
+
+        cache.put(key(1), vec![1, 2, 3]).unwrap();
+        assert_eq!(cache.get(&key(1)), Some(vec![1, 2, 3]));
+        // Another instance over the same directory sees it too.
+        assert_eq!(
+            FsProofCache::new(&dir).unwrap().get(&key(1)),
+            Some(vec![1, 2, 3])
+        );
+
+        // Flip a byte of the proof, as a partial write or bit rot would.
+        let path = cache.path(&key(1));
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, bytes).unwrap();
+        assert_eq!(cache.get(&key(1)), None);
+        assert!(!path.exists());
+
+        fs::write(cache.path(&key(2)), b"TPC1").unwrap();
+        assert_eq!(cache.get(&key(2)), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_prove_cached() {
+        let k = 4;
+        let s = Fr::from(2);
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let instances = vec![Fr::from(7 * 2 * 2 * 3 * 3)];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+
+        let key = ProofCacheKey::new(&pk_checksum(&pk_bytes), &[&[&instances[..]]], None);
+        let mut cache = MemoryProofCache::new(1);
+        let mut prove = |seed: [u8; 16], options: &CacheOptions| {
+            prove_cached(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&[&instances[..]]],
+                XORShiftRng::from_seed(seed),
+                TachyonBlake2bWrite::init(vec![]),
+                &mut cache,
+                &key,
+                options,
+            )
+            .expect("proof generation should not fail")
+        };
+
+        let proof = prove(SEED, &CacheOptions::default());
+        // A hit returns the stored proof, whatever the randomness.
+        assert_eq!(prove([1; 16], &CacheOptions::default()), proof);
+
+        let regenerated = prove([1; 16], &CacheOptions { regenerate: true });
+        assert_ne!(regenerated, proof);
+        assert_eq!(prove(SEED, &CacheOptions::default()), regenerated);
+    }
+}
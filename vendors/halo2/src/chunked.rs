@@ -0,0 +1,449 @@
+use std::{fmt, marker::PhantomData};
+
+use ff::PrimeField;
+use halo2_proofs::{
+    plonk::Circuit,
+    poly::commitment::CommitmentScheme,
+    transcript::{EncodedChallenge, TranscriptWriterBuffer},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    audit::to_hex,
+    bn254::{ProvingKey as TachyonProvingKey, TachyonProver, TranscriptWriteState},
+    error::TachyonError,
+    prover::{create_proof_with_options, ProofOptions},
+    rng::proof_context,
+};
+
+/// Declares that instance column `output_column` of every chunk is carried
+/// over into instance column `input_column` of the next one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContinuationLink {
+    pub output_column: usize,
+    pub input_column: usize,
+}
+
+/// One circuit of a chain and its instance columns.
+#[derive(Clone, Debug)]
+pub struct Chunk<C, F> {
+    pub circuit: C,
+    pub instances: Vec<Vec<F>>,
+}
+
+#[derive(Debug)]
+pub enum ChainError {
+    /// Instance column `link.input_column` of `chunk` differs from
+    /// `link.output_column` of the chunk before it, starting at `row`.
+    Mismatch {
+        chunk: usize,
+        link: ContinuationLink,
+        row: usize,
+    },
+    /// A link names a column `chunk` doesn't have.
+    MissingColumn { chunk: usize, column: usize },
+    /// Proving `chunk` failed.
+    Prove { chunk: usize, error: TachyonError },
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::Mismatch { chunk, link, row } => write!(
+                f,
+                "chunk {} reads instance column {} row {}, which doesn't match column {} of chunk {}",
+                chunk,
+                link.input_column,
+                row,
+                link.output_column,
+                chunk - 1
+            ),
+            ChainError::MissingColumn { chunk, column } => {
+                write!(f, "chunk {} has no instance column {}", chunk, column)
+            }
+            ChainError::Prove { chunk, error } => {
+                write!(f, "failed to prove chunk {}: {}", chunk, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChainError {}
+
+/// Checks that every chunk continues from the one before it, see
+/// [`ContinuationLink`].
+pub fn check_links<C, F: PartialEq>(
+    links: &[ContinuationLink],
+    chunks: &[Chunk<C, F>],
+) -> Result<(), ChainError> {
+    let column = |chunk: usize, column: usize| {
+        chunks[chunk]
+            .instances
+            .get(column)
+            .ok_or(ChainError::MissingColumn { chunk, column })
+    };
+    for chunk in 1..chunks.len() {
+        for link in links {
+            let outputs = column(chunk - 1, link.output_column)?;
+            let inputs = column(chunk, link.input_column)?;
+            // Columns of different lengths mismatch in the first row only one
+            // of them has.
+            let row = outputs
+                .iter()
+                .zip(inputs)
+                .position(|(output, input)| output != input)
+                .or_else(|| {
+                    (outputs.len() != inputs.len()).then(|| outputs.len().min(inputs.len()))
+                });
+            if let Some(row) = row {
+                return Err(ChainError::Mismatch {
+                    chunk,
+                    link: *link,
+                    row,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What a verifier needs, besides the proofs, to check a chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainManifest {
+    pub chain_id: Vec<u8>,
+    pub links: Vec<ContinuationLink>,
+    pub chunks: Vec<ChunkEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkEntry {
+    /// SHA-256 over the little-endian repr of every instance value, column by
+    /// column, each column prefixed with its length.
+    pub instances_sha256: [u8; 32],
+    pub proof_sha256: [u8; 32],
+    pub proof_len: usize,
+}
+
+impl ChainManifest {
+    pub fn to_json(&self) -> String {
+        let links = self
+            .links
+            .iter()
+            .map(|link| {
+                format!(
+                    "{{\"output_column\":{},\"input_column\":{}}}",
+                    link.output_column, link.input_column
+                )
+            })
+            .collect::<Vec<_>>();
+        let chunks = self
+            .chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                format!(
+                    "{{\"index\":{},\"instances_sha256\":\"{}\",\"proof_sha256\":\"{}\",\"proof_len\":{}}}",
+                    index,
+                    to_hex(&chunk.instances_sha256),
+                    to_hex(&chunk.proof_sha256),
+                    chunk.proof_len
+                )
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "{{\"chain_id\":\"{}\",\"links\":[{}],\"chunks\":[{}]}}",
+            to_hex(&self.chain_id),
+            links.join(","),
+            chunks.join(",")
+        )
+    }
+}
+
+fn instances_sha256<F: PrimeField>(instances: &[Vec<F>]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update((instances.len() as u64).to_le_bytes());
+    for column in instances {
+        hasher.update((column.len() as u64).to_le_bytes());
+        for value in column {
+            hasher.update(value.to_repr().as_ref());
+        }
+    }
+    hasher.finalize().into()
+}
+
+/// Proves the chunks of a statement that doesn't fit in one circuit, one after
+/// another with the same prover and proving key.
+///
+/// Chunk `i` is proven with [`create_proof_with_options()`] and the RNG
+/// context `proof_context(chain_id, i)`, so the prover needs a master key.
+pub struct ChainProver<'a, Scheme: CommitmentScheme, P: TachyonProver<Scheme>> {
+    prover: &'a mut P,
+    pk: &'a mut TachyonProvingKey<Scheme::Curve>,
+    chain_id: Vec<u8>,
+    links: Vec<ContinuationLink>,
+    _marker: PhantomData<Scheme>,
+}
+
+impl<'a, Scheme: CommitmentScheme, P: TachyonProver<Scheme> + fmt::Debug> fmt::Debug
+    for ChainProver<'a, Scheme, P>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChainProver")
+            .field("prover", &self.prover)
+            .field("pk", &self.pk)
+            .field("chain_id", &to_hex(&self.chain_id))
+            .field("links", &self.links)
+            .finish()
+    }
+}
+
+impl<'a, Scheme: CommitmentScheme, P: TachyonProver<Scheme>> ChainProver<'a, Scheme, P> {
+    pub fn new(
+        prover: &'a mut P,
+        pk: &'a mut TachyonProvingKey<Scheme::Curve>,
+        chain_id: &[u8],
+        links: Vec<ContinuationLink>,
+    ) -> ChainProver<'a, Scheme, P> {
+        ChainProver {
+            prover,
+            pk,
+            chain_id: chain_id.to_vec(),
+            links,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Checks the links with [`check_links()`] before proving anything, then
+    /// returns a proof per chunk, each written to a transcript from
+    /// `new_transcript`.
+    pub fn prove<
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptWriteState<Scheme::Curve, E> + TranscriptWriterBuffer<Vec<u8>, Scheme::Curve, E>,
+        ConcreteCircuit: Circuit<Scheme::Scalar>,
+    >(
+        &mut self,
+        chunks: &[Chunk<ConcreteCircuit, Scheme::Scalar>],
+        mut new_transcript: impl FnMut() -> T,
+    ) -> Result<(Vec<Vec<u8>>, ChainManifest), ChainError> {
+        check_links(&self.links, chunks)?;
+
+        let mut proofs = Vec::with_capacity(chunks.len());
+        let mut entries = Vec::with_capacity(chunks.len());
+        for (index, chunk) in chunks.iter().enumerate() {
+            let instances = chunk
+                .instances
+                .iter()
+                .map(Vec::as_slice)
+                .collect::<Vec<_>>();
+            let options = ProofOptions {
+                rng_context: proof_context(&self.chain_id, index as u64),
+                ..Default::default()
+            };
+            let mut transcript = new_transcript();
+            create_proof_with_options(
+                self.prover,
+                self.pk,
+                std::slice::from_ref(&chunk.circuit),
+                &[&instances[..]],
+                &options,
+                &mut transcript,
+            )
+            .map_err(|error| ChainError::Prove {
+                chunk: index,
+                error,
+            })?;
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&self.prover.get_proof());
+
+            entries.push(ChunkEntry {
+                instances_sha256: instances_sha256(&chunk.instances),
+                proof_sha256: Sha256::digest(&proof).into(),
+                proof_len: proof.len(),
+            });
+            proofs.push(proof);
+        }
+        let manifest = ChainManifest {
+            chain_id: self.chain_id.clone(),
+            links: self.links.clone(),
+            chunks: entries,
+        };
+        Ok((proofs, manifest))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{
+            keygen_pk2, verify_proof, Advice, Circuit, Column, ConstraintSystem, Error, Instance,
+            Selector,
+        },
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::VerifierSHPLONK,
+                strategy::SingleStrategy,
+            },
+            Rotation,
+        },
+        transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+    use super::{check_links, ChainError, ChainProver, Chunk, ContinuationLink};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        consts::TranscriptType,
+    };
+
+    const LINK: ContinuationLink = ContinuationLink {
+        output_column: 1,
+        input_column: 0,
+    };
+
+    /// Constrains the second instance column to the first plus `delta` in the
+    /// first row, i.e, one step of a running sum.
+    #[derive(Clone, Default)]
+    struct StepCircuit {
+        delta: Fr,
+    }
+
+    impl Circuit<Fr> for StepCircuit {
+        type Config = (Column<Advice>, [Column<Instance>; 2], Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let delta = meta.advice_column();
+            let instances = [meta.instance_column(), meta.instance_column()];
+            let q = meta.selector();
+            meta.create_gate("step", |meta| {
+                let q = meta.query_selector(q);
+                let delta = meta.query_advice(delta, Rotation::cur());
+                let input = meta.query_instance(instances[0], Rotation::cur());
+                let output = meta.query_instance(instances[1], Rotation::cur());
+                vec![q * (output - input - delta)]
+            });
+            (delta, instances, q)
+        }
+
+        fn synthesize(
+            &self,
+            (delta, _, q): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "step",
+                |mut region| {
+                    q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "delta", delta, 0, || Value::known(self.delta))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn chunk(input: u64, delta: u64) -> Chunk<StepCircuit, Fr> {
+        Chunk {
+            circuit: StepCircuit {
+                delta: Fr::from(delta),
+            },
+            instances: vec![vec![Fr::from(input)], vec![Fr::from(input + delta)]],
+        }
+    }
+
+    #[test]
+    fn test_check_links() {
+        assert!(check_links(&[LINK], &[chunk(0, 3), chunk(3, 2), chunk(5, 7)]).is_ok());
+
+        match check_links(&[LINK], &[chunk(0, 3), chunk(3, 2), chunk(6, 7)]) {
+            Err(ChainError::Mismatch { chunk, link, row }) => {
+                assert_eq!((chunk, link, row), (2, LINK, 0));
+            }
+            _ => panic!("expected Mismatch"),
+        }
+
+        let mut longer = chunk(3, 2);
+        longer.instances[0].push(Fr::one());
+        assert!(matches!(
+            check_links(&[LINK], &[chunk(0, 3), longer]),
+            Err(ChainError::Mismatch { row: 1, .. })
+        ));
+
+        let link = ContinuationLink {
+            output_column: 2,
+            input_column: 0,
+        };
+        assert!(matches!(
+            check_links(&[link], &[chunk(0, 3), chunk(3, 2)]),
+            Err(ChainError::MissingColumn {
+                chunk: 0,
+                column: 2
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_chain_prover() {
+        let k = 4;
+        let s = Fr::from(2);
+        let chunks = vec![chunk(0, 3), chunk(3, 2), chunk(5, 7)];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &chunks[0].circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        prover.set_master_key([7; 32]);
+
+        let mut chain = ChainProver::new(&mut prover, &mut tachyon_pk, b"sum", vec![LINK]);
+        let (proofs, manifest) = chain
+            .prove(&chunks, || TachyonBlake2bWrite::init(vec![]))
+            .expect("proof generation should not fail");
+        assert_eq!(proofs.len(), 3);
+        assert_eq!(manifest.chunks.len(), 3);
+        assert!(manifest
+            .to_json()
+            .contains("\"links\":[{\"output_column\":1,\"input_column\":0}]"));
+
+        for (chunk, (proof, entry)) in chunks.iter().zip(proofs.iter().zip(&manifest.chunks)) {
+            assert_eq!(entry.proof_len, proof.len());
+            let instances = chunk
+                .instances
+                .iter()
+                .map(Vec::as_slice)
+                .collect::<Vec<_>>();
+            let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+            assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+                params.verifier_params(),
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                &[&instances[..]],
+                &mut transcript,
+            )
+            .is_ok());
+        }
+
+        // A broken chain is rejected before any chunk is proven.
+        let broken = vec![chunk(0, 3), chunk(4, 2), chunk(6, 7)];
+        match chain.prove(&broken, || TachyonBlake2bWrite::init(vec![])) {
+            Err(e @ ChainError::Mismatch { chunk: 1, .. }) => assert_eq!(
+                e.to_string(),
+                "chunk 1 reads instance column 0 row 0, which doesn't match column 1 of chunk 0"
+            ),
+            _ => panic!("expected Mismatch"),
+        }
+    }
+}
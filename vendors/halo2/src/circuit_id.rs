@@ -0,0 +1,310 @@
+// A circuit id identifies the structure of a constraint system, so that
+// proofs can be routed to the right verifier whatever SRS the keys were
+// generated with. It is the SHA-256 of the following, where integers are
+// little-endian, `u64` unless noted, and lists are prefixed with their length:
+//
+//   "tachyon_halo2 circuit_id v1"
+//   num_fixed_columns | num_advice_columns | num_instance_columns
+//   num_selectors | num_challenges
+//   advice_column_phase: list of u8
+//   challenge_phase: list of u8
+//   gates: list of (polynomials: list of expression)
+//   lookups: list of (inputs: list of expression, tables: list of expression)
+//   permutation columns: list of (kind: u8, index), kind is 0 for advice, 1
+//                        for fixed and 2 for instance
+//   constants: list of fixed column index
+//
+// and an expression is encoded as a tag byte followed by its fields:
+//
+//   0x00 constant   repr
+//   0x01 selector   index
+//   0x02 fixed      column_index | rotation (i32)
+//   0x03 advice     column_index | rotation (i32) | phase (u8)
+//   0x04 instance   column_index | rotation (i32)
+//   0x05 challenge  index | phase (u8)
+//   0x06 negated    expression
+//   0x07 sum        expression | expression
+//   0x08 product    expression | expression
+//   0x09 scaled     expression | repr
+//
+// Names of gates, lookups and columns are left out, and so are the params and
+// the values of fixed columns. Since keygen folds selectors into fixed columns,
+// the id is of the verifying key's constraint system, not the one returned by
+// `configure()`. This encoding is frozen: a change must bump the version in
+// the domain string, and `test_circuit_id_vector` guards it.
+
+use ff::PrimeField;
+use halo2_proofs::plonk::{Any, ConstraintSystem, Expression, VerifyingKey};
+use halo2curves::CurveAffine;
+use sha2::{Digest, Sha256};
+
+use crate::{audit::to_hex, proving_key::Metadata};
+
+/// The proving key metadata key the circuit id is stored under, as hex.
+pub const CIRCUIT_ID_METADATA_KEY: &str = "circuit_id";
+
+const DOMAIN: &[u8] = b"tachyon_halo2 circuit_id v1";
+
+fn encode_len(len: usize, out: &mut Vec<u8>) {
+    out.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+fn encode_expression<F: PrimeField>(expression: &Expression<F>) -> Vec<u8> {
+    let tagged = |tag: u8, fields: &[&[u8]]| {
+        let mut out = vec![tag];
+        for field in fields {
+            out.extend_from_slice(field);
+        }
+        out
+    };
+    expression.evaluate(
+        &|constant| tagged(0x00, &[constant.to_repr().as_ref()]),
+        &|selector| tagged(0x01, &[&(selector.index() as u64).to_le_bytes()]),
+        &|query| {
+            tagged(
+                0x02,
+                &[
+                    &(query.column_index() as u64).to_le_bytes(),
+                    &query.rotation().0.to_le_bytes(),
+                ],
+            )
+        },
+        &|query| {
+            tagged(
+                0x03,
+                &[
+                    &(query.column_index() as u64).to_le_bytes(),
+                    &query.rotation().0.to_le_bytes(),
+                    &[query.phase()],
+                ],
+            )
+        },
+        &|query| {
+            tagged(
+                0x04,
+                &[
+                    &(query.column_index() as u64).to_le_bytes(),
+                    &query.rotation().0.to_le_bytes(),
+                ],
+            )
+        },
+        &|challenge| {
+            tagged(
+                0x05,
+                &[
+                    &(challenge.index() as u64).to_le_bytes(),
+                    &[challenge.phase()],
+                ],
+            )
+        },
+        &|a| tagged(0x06, &[&a]),
+        &|a, b| tagged(0x07, &[&a, &b]),
+        &|a, b| tagged(0x08, &[&a, &b]),
+        &|a, scalar| tagged(0x09, &[&a, scalar.to_repr().as_ref()]),
+    )
+}
+
+pub(crate) fn encode_expressions<F: PrimeField>(expressions: &[Expression<F>], out: &mut Vec<u8>) {
+    encode_len(expressions.len(), out);
+    for expression in expressions {
+        out.extend_from_slice(&encode_expression(expression));
+    }
+}
+
+/// Encodes `cs` as described at the top of this file.
+fn encode_constraint_system<F: PrimeField>(cs: &ConstraintSystem<F>) -> Vec<u8> {
+    let mut out = DOMAIN.to_vec();
+    for n in [
+        cs.num_fixed_columns(),
+        cs.num_advice_columns(),
+        cs.num_instance_columns(),
+        cs.num_selectors(),
+        cs.num_challenges(),
+    ] {
+        encode_len(n, &mut out);
+    }
+    for phases in [&cs.advice_column_phase, &cs.challenge_phase] {
+        encode_len(phases.len(), &mut out);
+        out.extend(phases.iter().map(|phase| phase.0));
+    }
+
+    encode_len(cs.gates().len(), &mut out);
+    for gate in cs.gates() {
+        encode_expressions(gate.polynomials(), &mut out);
+    }
+    encode_len(cs.lookups().len(), &mut out);
+    for lookup in cs.lookups() {
+        encode_expressions(lookup.input_expressions(), &mut out);
+        encode_expressions(lookup.table_expressions(), &mut out);
+    }
+
+    let columns = cs.permutation().get_columns();
+    encode_len(columns.len(), &mut out);
+    for column in columns {
+        out.push(match column.column_type() {
+            Any::Advice(_) => 0,
+            Any::Fixed => 1,
+            Any::Instance => 2,
+        });
+        encode_len(column.index(), &mut out);
+    }
+    encode_len(cs.constants().len(), &mut out);
+    for column in cs.constants() {
+        encode_len(column.index(), &mut out);
+    }
+    out
+}
+
+/// Returns the circuit id of a constraint system. Pass the one of the
+/// verifying key, see [`circuit_id()`].
+pub fn constraint_system_id<F: PrimeField>(cs: &ConstraintSystem<F>) -> [u8; 32] {
+    Sha256::digest(encode_constraint_system(cs)).into()
+}
+
+/// Returns the circuit id of `vk`. It depends only on the structure of the
+/// circuit, so keys generated with different params share it. Use
+/// `pk.get_vk()` for a halo2 proving key, and
+/// [`crate::bn254::ProvingKey::circuit_id()`] for a loaded one.
+pub fn circuit_id<C: CurveAffine>(vk: &VerifyingKey<C>) -> [u8; 32] {
+    constraint_system_id(vk.cs())
+}
+
+/// Records the circuit id of `vk` in `metadata`, so that it is written in
+/// front of the proving key with [`crate::proving_key::write_with_metadata()`].
+pub fn circuit_id_to_metadata<C: CurveAffine>(vk: &VerifyingKey<C>, metadata: &mut Metadata) {
+    metadata.insert(CIRCUIT_ID_METADATA_KEY.to_string(), to_hex(&circuit_id(vk)));
+}
+
+/// Parses the circuit id stored by [`circuit_id_to_metadata()`].
+pub(crate) fn circuit_id_from_metadata(metadata: &Metadata) -> Option<[u8; 32]> {
+    let hex = metadata.get(CIRCUIT_ID_METADATA_KEY)?;
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut id = [0u8; 32];
+    for (byte, chunk) in id.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+    }
+    Some(id)
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_vk, Advice, Circuit, Column, ConstraintSystem, Fixed, Instance, Selector},
+        poly::{kzg::commitment::ParamsKZG, Rotation},
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{
+        circuit_id, circuit_id_from_metadata, circuit_id_to_metadata, constraint_system_id,
+    };
+    use crate::{
+        audit::to_hex,
+        circuits::{
+            rotation_circuit::RotationCircuit, simple_circuit::SimpleCircuit,
+            simple_lookup_circuit::SimpleLookupCircuit,
+        },
+        proving_key::Metadata,
+    };
+
+    /// Two advice columns, a fixed and an instance column, and the gate
+    /// `q * (a * b' - f)`, where `b'` is `b` in the next row.
+    fn mul_constraint_system() -> (
+        ConstraintSystem<Fr>,
+        [Column<Advice>; 2],
+        Column<Fixed>,
+        Selector,
+    ) {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        let a = cs.advice_column();
+        let b = cs.advice_column();
+        let f = cs.fixed_column();
+        let i: Column<Instance> = cs.instance_column();
+        cs.enable_equality(a);
+        cs.enable_equality(i);
+        let q = cs.selector();
+        cs.create_gate("mul", |meta| {
+            let q = meta.query_selector(q);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::next());
+            let f = meta.query_fixed(f, Rotation::cur());
+            vec![q * (a * b - f)]
+        });
+        (cs, [a, b], f, q)
+    }
+
+    #[test]
+    fn test_circuit_id_vector() {
+        let (cs, _, _, _) = mul_constraint_system();
+        assert_eq!(
+            to_hex(&constraint_system_id(&cs)),
+            "4305fd1ed2a7c7c6532891b0edb4b37a06e20727781e2b90987a498adc3b4a12"
+        );
+    }
+
+    // The ids of the fixture circuits as configured, before keygen folds their
+    // selectors, which is what the ids of their keys are computed from. A
+    // change here means keys of these circuits get new ids.
+    #[test]
+    fn test_fixture_circuit_id_vectors() {
+        fn configured_id<C: Circuit<Fr>>() -> String {
+            let mut cs = ConstraintSystem::<Fr>::default();
+            C::configure(&mut cs);
+            to_hex(&constraint_system_id(&cs))
+        }
+
+        assert_eq!(
+            configured_id::<SimpleCircuit<Fr>>(),
+            "0deff5c849e13345a35322b0fcc510a2d8330a6994ada1a106132ab0080d12f8"
+        );
+        assert_eq!(
+            configured_id::<SimpleLookupCircuit<Fr>>(),
+            "7e3c6beacc0d584a88c18045ec454644ba0385e383829bcfa25cd241b55393e3"
+        );
+        assert_eq!(
+            configured_id::<RotationCircuit<Fr>>(),
+            "b8a12f759d09ef278cdd85f3f7e344f1955a1707c19c9fcd1bab93a819cb61ae"
+        );
+    }
+
+    #[test]
+    fn test_circuit_id_changes_with_gates() {
+        let (cs, _, _, _) = mul_constraint_system();
+        let (mut cs_with_gate, [a, b], _, q) = mul_constraint_system();
+        cs_with_gate.create_gate("add", |meta| {
+            let q = meta.query_selector(q);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            vec![q * (a - b)]
+        });
+        assert_ne!(
+            constraint_system_id(&cs),
+            constraint_system_id(&cs_with_gate)
+        );
+    }
+
+    #[test]
+    fn test_circuit_id_srs_invariance() {
+        let k = 4;
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let vk = |s: u64| {
+            let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::from(s));
+            keygen_vk(&params, &circuit).expect("vk should not fail")
+        };
+        let vk2 = vk(2);
+        let vk3 = vk(3);
+        // The keys themselves differ.
+        assert_ne!(vk2.transcript_repr(), vk3.transcript_repr());
+        assert_eq!(circuit_id(&vk2), circuit_id(&vk3));
+
+        let mut metadata = Metadata::new();
+        circuit_id_to_metadata(&vk2, &mut metadata);
+        assert_eq!(circuit_id_from_metadata(&metadata), Some(circuit_id(&vk2)));
+    }
+}
@@ -0,0 +1,276 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+#[derive(Clone, Debug)]
+pub struct DynamicLookupConfig {
+    q_table: Selector,
+    table: Column<Advice>,
+    q_input: Selector,
+    input: Column<Advice>,
+}
+
+/// Looks up every value of `inputs` in `table`, where both are witnessed in
+/// advice columns, so the table differs from proof to proof.
+#[derive(Clone, Debug, Default)]
+pub struct DynamicLookupCircuit<F: FieldExt> {
+    pub table: Vec<Value<F>>,
+    pub inputs: Vec<Value<F>>,
+}
+
+impl<F: FieldExt> Circuit<F> for DynamicLookupCircuit<F> {
+    type Config = DynamicLookupConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            table: vec![Value::unknown(); self.table.len()],
+            inputs: vec![Value::unknown(); self.inputs.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let config = DynamicLookupConfig {
+            q_table: meta.complex_selector(),
+            table: meta.advice_column(),
+            q_input: meta.complex_selector(),
+            input: meta.advice_column(),
+        };
+
+        // Rows outside either region evaluate to 0 on their side, and 0 is in
+        // the table as long as it doesn't fill all the usable rows.
+        meta.lookup_any("dynamic", |meta| {
+            let q_table = meta.query_selector(config.q_table);
+            let table = meta.query_advice(config.table, Rotation::cur());
+            let q_input = meta.query_selector(config.q_input);
+            let input = meta.query_advice(config.input, Rotation::cur());
+            vec![(q_input * input, q_table * table)]
+        });
+
+        config
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "table",
+            |mut region| {
+                for (offset, value) in self.table.iter().enumerate() {
+                    config.q_table.enable(&mut region, offset)?;
+                    region.assign_advice(
+                        || format!("table[{}]", offset),
+                        config.table,
+                        offset,
+                        || *value,
+                    )?;
+                }
+                Ok(())
+            },
+        )?;
+
+        layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                for (offset, value) in self.inputs.iter().enumerate() {
+                    config.q_input.enable(&mut region, offset)?;
+                    region.assign_advice(
+                        || format!("input[{}]", offset),
+                        config.input,
+                        offset,
+                        || *value,
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, verify_proof},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::SeedableRng;
+
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::dynamic_lookup_circuit::DynamicLookupCircuit,
+        consts::{TranscriptType, SEED},
+        debug::{verify_constraints, DebugError},
+        error::TachyonError,
+        prover::{create_proof as tachyon_create_proof, create_proof_with_options, ProofOptions},
+        xor_shift_rng::XORShiftRng,
+    };
+
+    // The table has repeated values, which the permuted table must keep.
+    const TABLE: [u64; 8] = [3, 1, 4, 1, 5, 9, 2, 6];
+    const INPUTS: [u64; 7] = [9, 1, 1, 6, 2, 3, 5];
+    const MISSING_ROW: usize = 3;
+
+    fn circuit(inputs: &[u64]) -> DynamicLookupCircuit<Fr> {
+        let values = |values: &[u64]| {
+            values
+                .iter()
+                .map(|value| Value::known(Fr::from(*value)))
+                .collect()
+        };
+        DynamicLookupCircuit {
+            table: values(&TABLE),
+            inputs: values(inputs),
+        }
+    }
+
+    fn missing_value_circuit() -> DynamicLookupCircuit<Fr> {
+        let mut inputs = INPUTS;
+        inputs[MISSING_ROW] = 7;
+        circuit(&inputs)
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_create_proof() {
+        let k = 5;
+        let s = Fr::from(2);
+        let circuit = circuit(&INPUTS);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let rng = XORShiftRng::from_seed(SEED);
+
+        let halo2_proof = {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            halo2_proofs::plonk::create_proof::<
+                KZGCommitmentScheme<Bn256>,
+                ProverSHPLONK<_>,
+                _,
+                _,
+                _,
+                _,
+            >(
+                &params,
+                &pk,
+                &[circuit.clone()],
+                &[&[]],
+                rng.clone(),
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            transcript.finalize()
+        };
+
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        let tachyon_proof = {
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            tachyon_create_proof(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit],
+                &[&[]],
+                rng,
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+        assert_eq!(halo2_proof, tachyon_proof);
+
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&tachyon_proof[..]);
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[]],
+            &mut transcript,
+        )
+        .is_ok());
+
+        // The backend can't permute a table that lacks an input value, so the
+        // preflight check has to reject the witness first.
+        prover.set_master_key([7; 32]);
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let options = ProofOptions {
+            verify_constraints_first: true,
+            ..Default::default()
+        };
+        match create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[missing_value_circuit()],
+            &[&[]],
+            &options,
+            &mut TachyonBlake2bWrite::init(vec![]),
+        ) {
+            Err(TachyonError::Preflight(DebugError::Unsatisfied { gates, lookups, .. })) => {
+                assert!(gates.is_empty());
+                assert_eq!(lookups, vec![(0, MISSING_ROW)]);
+            }
+            _ => panic!("expected Preflight"),
+        }
+    }
+
+    #[test]
+    fn test_missing_value() {
+        let k = 5;
+        assert!(verify_constraints(k, &circuit(&INPUTS), &[]).is_ok());
+
+        let circuit = missing_value_circuit();
+        match verify_constraints(k, &circuit, &[]) {
+            Err(DebugError::Unsatisfied { gates, lookups, .. }) => {
+                assert!(gates.is_empty());
+                assert_eq!(lookups, vec![(0, MISSING_ROW)]);
+            }
+            _ => panic!("expected Unsatisfied"),
+        }
+
+        // Upstream halo2 refuses to prove it as well.
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::from(2));
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        assert!(halo2_proofs::plonk::create_proof::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<_>,
+            _,
+            _,
+            _,
+            _,
+        >(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[]],
+            rand_xorshift::XorShiftRng::from_seed(SEED),
+            &mut transcript,
+        )
+        .is_err());
+    }
+}
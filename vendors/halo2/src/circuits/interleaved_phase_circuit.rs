@@ -0,0 +1,100 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Challenge, Circuit, Column, ConstraintSystem, Error, FirstPhase, SecondPhase,
+        Selector,
+    },
+    poly::Rotation,
+};
+
+/// Advice columns 0 and 2 are in the first phase, and 1 and 3 in the second,
+/// so that the columns of neither phase are contiguous.
+#[derive(Clone, Debug)]
+pub struct InterleavedPhaseConfig {
+    q: Selector,
+    first: [Column<Advice>; 2],
+    second: [Column<Advice>; 2],
+    challenge: Challenge,
+}
+
+/// Constrains `second[i]` to `first[i]` times a challenge squeezed after the
+/// first phase, in every row of `values`.
+#[derive(Clone, Debug, Default)]
+pub struct InterleavedPhaseCircuit<F: FieldExt> {
+    pub values: Vec<[Value<F>; 2]>,
+}
+
+impl<F: FieldExt> Circuit<F> for InterleavedPhaseCircuit<F> {
+    type Config = InterleavedPhaseConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![[Value::unknown(); 2]; self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q = meta.selector();
+        let a0 = meta.advice_column_in(FirstPhase);
+        let b0 = meta.advice_column_in(SecondPhase);
+        let a1 = meta.advice_column_in(FirstPhase);
+        let b1 = meta.advice_column_in(SecondPhase);
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let (first, second) = ([a0, a1], [b0, b1]);
+
+        meta.create_gate("second = first * challenge", |meta| {
+            let q = meta.query_selector(q);
+            let challenge = meta.query_challenge(challenge);
+            first
+                .iter()
+                .zip(second.iter())
+                .map(|(first, second)| {
+                    let first = meta.query_advice(*first, Rotation::cur());
+                    let second = meta.query_advice(*second, Rotation::cur());
+                    q.clone() * (second - first * challenge.clone())
+                })
+                .collect::<Vec<_>>()
+        });
+
+        InterleavedPhaseConfig {
+            q,
+            first,
+            second,
+            challenge,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenge = layouter.get_challenge(config.challenge);
+
+        layouter.assign_region(
+            || "interleaved",
+            |mut region| {
+                for (offset, values) in self.values.iter().enumerate() {
+                    config.q.enable(&mut region, offset)?;
+                    for (i, value) in values.iter().enumerate() {
+                        region.assign_advice(
+                            || format!("first[{}][{}]", i, offset),
+                            config.first[i],
+                            offset,
+                            || *value,
+                        )?;
+                        region.assign_advice(
+                            || format!("second[{}][{}]", i, offset),
+                            config.second[i],
+                            offset,
+                            || *value * challenge,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
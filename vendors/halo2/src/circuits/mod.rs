@@ -1,3 +1,8 @@
+pub mod dynamic_lookup_circuit;
+pub mod interleaved_phase_circuit;
+pub mod nested_circuit;
+pub mod rotation_circuit;
 pub mod shuffle_circuit;
 pub mod simple_circuit;
 pub mod simple_lookup_circuit;
+pub mod wide_circuit;
@@ -0,0 +1,259 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error},
+    poly::kzg::commitment::KZGCommitmentScheme,
+    transcript::TranscriptWriterBuffer,
+};
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use rand_core::SeedableRng;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    bn254::{
+        Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+        TachyonProver,
+    },
+    circuits::simple_circuit::{FieldConfig, SimpleCircuit},
+    consts::{TranscriptType, SEED},
+    prover::create_proof as tachyon_create_proof,
+    redact::Redacted,
+    xor_shift_rng::XORShiftRng,
+};
+
+/// A circuit whose witness is derived from a [`SimpleCircuit`] proof that is
+/// created with a separate prover while this circuit is being synthesized.
+///
+/// It proves `c = constant * a² * b²` where `a` is the digest of the inner
+/// proof.
+#[derive(Clone)]
+pub struct NestedCircuit {
+    pub k: u32,
+    pub s: Fr,
+    /// The inner proving key, serialized with `SerdeFormat::RawBytesUnchecked`.
+    pub inner_pk: Rc<Vec<u8>>,
+    pub inner: SimpleCircuit<Fr>,
+    pub inner_instance: Fr,
+    pub constant: Fr,
+    pub b: Option<Fr>,
+    /// The inner proofs created so far, in synthesis order.
+    pub proofs: Rc<RefCell<Vec<Vec<u8>>>>,
+}
+
+// `s` is the toxic waste of the inner prover's SRS.
+impl fmt::Debug for NestedCircuit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NestedCircuit")
+            .field("k", &self.k)
+            .field("s", &Redacted(()))
+            .field("inner_pk_len", &self.inner_pk.len())
+            .field("inner", &self.inner)
+            .field("inner_instance", &self.inner_instance)
+            .field("constant", &self.constant)
+            .field("b", &self.b)
+            .field("num_proofs", &self.proofs.borrow().len())
+            .finish()
+    }
+}
+
+impl NestedCircuit {
+    /// Creates the inner proof with a prover of its own.
+    pub fn prove_inner(&self) -> Result<Vec<u8>, Error> {
+        let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            self.k,
+            &self.s,
+        );
+        let mut pk = TachyonProvingKey::<G1Affine>::try_from(self.inner_pk.as_slice())
+            .map_err(Error::Transcript)?;
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        let instance = vec![self.inner_instance];
+        let instances = vec![&instance[..]];
+
+        tachyon_create_proof::<_, _, _, _, _>(
+            &mut prover,
+            &mut pk,
+            &[self.inner.clone()],
+            &[&instances[..]],
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )?;
+
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+        Ok(proof)
+    }
+
+    /// Maps an inner proof to the value witnessed as `a`.
+    pub fn absorb(proof: &[u8]) -> Fr {
+        let mut buf = [0u8; 64];
+        buf[..32].copy_from_slice(&Sha256::digest(proof));
+        Fr::from_bytes_wide(&buf)
+    }
+}
+
+impl Circuit<Fr> for NestedCircuit {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            b: None,
+            proofs: Rc::new(RefCell::new(vec![])),
+            ..self.clone()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        SimpleCircuit::<Fr>::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        // Keygen synthesizes without witnesses, in which case there is
+        // nothing to prove yet.
+        let a = match self.b {
+            Some(_) => {
+                let proof = self.prove_inner()?;
+                let a = Self::absorb(&proof);
+                self.proofs.borrow_mut().push(proof);
+                Value::known(a)
+            }
+            None => Value::unknown(),
+        };
+
+        SimpleCircuit {
+            constant: self.constant,
+            a,
+            b: self.b.map(Value::known).unwrap_or_else(Value::unknown),
+        }
+        .synthesize(config, layouter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, verify_proof},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::VerifierSHPLONK,
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::SeedableRng;
+
+    use super::NestedCircuit;
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{TranscriptType, SEED},
+        prover::create_proof as tachyon_create_proof,
+        xor_shift_rng::XORShiftRng,
+    };
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_create_nested_proof() {
+        let k = 4;
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+
+        let inner = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let inner_pk = keygen_pk2(&params, &inner).expect("vk should not fail");
+        let mut inner_pk_bytes: Vec<u8> = vec![];
+        inner_pk
+            .write(
+                &mut inner_pk_bytes,
+                halo2_proofs::SerdeFormat::RawBytesUnchecked,
+            )
+            .unwrap();
+
+        let constant = Fr::from(5);
+        let b = Fr::from(11);
+        let circuit = NestedCircuit {
+            k,
+            s,
+            inner_pk: Rc::new(inner_pk_bytes),
+            inner,
+            inner_instance: Fr::from(7 * 2 * 2 * 3 * 3),
+            constant,
+            b: Some(b),
+            proofs: Rc::new(RefCell::new(vec![])),
+        };
+
+        // The inner proof is deterministic, so it can be computed up front to
+        // derive the public input of the outer circuit.
+        let inner_proof = circuit.prove_inner().unwrap();
+        let a = NestedCircuit::absorb(&inner_proof);
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let public_inputs2 = vec![&public_inputs[..]];
+        let public_inputs3 = vec![&public_inputs2[..]];
+
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        assert!(circuit.proofs.borrow().is_empty());
+
+        let proof = {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+
+            let mut tachyon_pk = {
+                let mut pk_bytes: Vec<u8> = vec![];
+                pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+                    .unwrap();
+                TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap()
+            };
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+
+            tachyon_create_proof::<_, _, _, _, _>(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                public_inputs3.as_slice(),
+                XORShiftRng::from_seed(SEED),
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+        assert_eq!(*circuit.proofs.borrow(), vec![inner_proof]);
+
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+        assert!(verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<_>,
+            _,
+            _,
+            SingleStrategy<_>,
+        >(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            public_inputs3.as_slice(),
+            &mut transcript,
+        )
+        .is_ok());
+    }
+}
@@ -0,0 +1,210 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+/// The rotations `RotationCircuit` queries `a` at, relative to the row `q`
+/// is enabled in.
+pub const ROTATIONS: [i32; 4] = [-5, 0, 7, 32];
+
+/// The rows `q` is enabled in.
+const ENABLED_ROWS: [usize; 2] = [5, 6];
+
+/// The number of rows assigned in `a`. This covers every rotated row.
+const ROWS: usize = 40;
+
+#[derive(Clone, Debug)]
+pub struct RotationConfig {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    q: Selector,
+}
+
+/// Constrains `b = a(-5) + a(0) + a(7) + a(32)` in every row `q` is enabled
+/// in.
+#[derive(Clone, Debug, Default)]
+pub struct RotationCircuit<F: FieldExt> {
+    pub _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> RotationCircuit<F> {
+    fn a(row: usize) -> F {
+        F::from(row as u64 + 1)
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for RotationCircuit<F> {
+    type Config = RotationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> RotationConfig {
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let q = meta.selector();
+
+        meta.create_gate("rotated sum", |meta| {
+            let q = meta.query_selector(q);
+            let b = meta.query_advice(b, Rotation::cur());
+            let sum = ROTATIONS
+                .iter()
+                .map(|rotation| meta.query_advice(a, Rotation(*rotation)))
+                .reduce(|acc, a| acc + a)
+                .unwrap();
+            vec![q * (sum - b)]
+        });
+
+        RotationConfig { a, b, q }
+    }
+
+    fn synthesize(
+        &self,
+        config: RotationConfig,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "rotated sum",
+            |mut region| {
+                for row in 0..ROWS {
+                    region.assign_advice(|| "a", config.a, row, || Value::known(Self::a(row)))?;
+                }
+                for row in ENABLED_ROWS {
+                    config.q.enable(&mut region, row)?;
+                    let b = ROTATIONS
+                        .iter()
+                        .map(|rotation| Self::a((row as i32 + rotation) as usize))
+                        .fold(F::zero(), |acc, a| acc + a);
+                    region.assign_advice(|| "b", config.b, row, || Value::known(b))?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        plonk::{create_proof, verify_proof},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+            Rotation,
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::SeedableRng;
+
+    use super::RotationCircuit;
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        consts::{TranscriptType, SEED},
+        keygen::{keygen_pk2, KeygenError},
+        prover::create_proof as tachyon_create_proof,
+        xor_shift_rng::XORShiftRng,
+    };
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_create_shplonk_proof() {
+        let k = 6;
+        let circuit = RotationCircuit::<Fr>::default();
+
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let rng = XORShiftRng::from_seed(SEED);
+
+        let halo2_proof = {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+
+            create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+                &params,
+                &pk,
+                &[circuit.clone()],
+                &[&[]],
+                rng.clone(),
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+
+            transcript.finalize()
+        };
+
+        let tachyon_proof = {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+
+            tachyon_create_proof::<_, _, _, _, _>(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit],
+                &[&[]],
+                rng,
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+        assert_eq!(halo2_proof, tachyon_proof);
+
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&tachyon_proof[..]);
+        assert!(verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<_>,
+            _,
+            _,
+            SingleStrategy<_>,
+        >(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[]],
+            &mut transcript,
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_rotation_out_of_range() {
+        // Rotation(32) wraps around all the way with 2⁵ rows.
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(5, Fr::from(2));
+        match keygen_pk2(&params, &RotationCircuit::<Fr>::default()) {
+            Err(KeygenError::RotationOutOfRange { rotation, n, .. }) => {
+                assert_eq!(rotation, Rotation(32));
+                assert_eq!(n, 32);
+            }
+            _ => panic!("expected RotationOutOfRange"),
+        }
+    }
+}
@@ -34,8 +34,8 @@ fn shuffled<F: FieldExt, R: RngCore, const W: usize, const H: usize>(
     shuffled
 }
 
-#[derive(Clone)]
-struct MyConfig<const W: usize> {
+#[derive(Clone, Debug)]
+pub struct MyConfig<const W: usize> {
     q_shuffle: Selector,
     q_first: Selector,
     q_last: Selector,
@@ -108,14 +108,14 @@ impl<const W: usize> MyConfig<W> {
     }
 }
 
-#[derive(Clone, Default)]
-struct MyCircuit<F: FieldExt, const W: usize, const H: usize> {
+#[derive(Clone, Debug, Default)]
+pub struct MyCircuit<F: FieldExt, const W: usize, const H: usize> {
     original: Value<[[F; H]; W]>,
     shuffled: Value<[[F; H]; W]>,
 }
 
 impl<F: FieldExt, const W: usize, const H: usize> MyCircuit<F, W, H> {
-    fn rand<R: RngCore>(rng: &mut R) -> Self {
+    pub fn rand<R: RngCore>(rng: &mut R) -> Self {
         let original = rand_2d_array::<F, _, W, H>(rng);
         let shuffled = shuffled(original, rng);
 
@@ -254,7 +254,7 @@ mod test {
         },
         transcript::{Blake2bWrite, Challenge255, PoseidonWrite, TranscriptWriterBuffer},
     };
-    use halo2curves::bn256::{Bn256, Fr};
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
     use rand_core::SeedableRng;
 
     use crate::{
@@ -264,6 +264,7 @@ mod test {
             TachyonProver,
         },
         consts::{TranscriptType, SEED},
+        phase::{ChallengeId, PhaseId},
         prover::create_proof as tachyon_create_proof,
         sha::ShaWrite,
         xor_shift_rng::XORShiftRng,
@@ -276,6 +277,44 @@ mod test {
     const K: u32 = 4;
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_iter_phases() {
+        let mut rng_for_table = XORShiftRng::from_seed(SEED);
+        let circuit = MyCircuit::<Fr, W, H>::rand(&mut rng_for_table);
+
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(K, s);
+        let pk = keygen_pk2(&params, &circuit).unwrap();
+        let cs = pk.get_vk().cs();
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let tachyon_pk = TachyonProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+
+        let phases = tachyon_pk.iter_phases().collect::<Vec<_>>();
+        assert_eq!(
+            phases,
+            vec![
+                (
+                    PhaseId(0),
+                    (0..2 * W).collect(),
+                    vec![ChallengeId(0), ChallengeId(1)]
+                ),
+                (PhaseId(1), vec![2 * W], vec![]),
+            ]
+        );
+        for (phase, columns, challenges) in phases {
+            for column in columns {
+                assert_eq!(PhaseId::from(cs.advice_column_phase[column]), phase);
+            }
+            for challenge in challenges {
+                assert_eq!(PhaseId::from(cs.challenge_phase[challenge.0]), phase);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_create_gwc_proof() {
         let mut rng_for_table = XORShiftRng::from_seed(SEED);
         let circuit = MyCircuit::<Fr, W, H>::rand(&mut rng_for_table);
@@ -311,7 +350,7 @@ mod test {
                 pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
                     .unwrap();
                 drop(pk);
-                TachyonProvingKey::from(pk_bytes.as_slice())
+                TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap()
             };
             let mut transcript = TachyonBlake2bWrite::init(vec![]);
 
@@ -324,6 +363,12 @@ mod test {
                 &mut transcript,
             )
             .expect("proof generation should not fail");
+            // Only the 2 * W first phase columns are allocated at once. Every
+            // other column reuses them from the pool.
+            assert_eq!(
+                prover.metrics().num_rational_evals_allocations,
+                2 * W as u64
+            );
 
             let mut proof = transcript.finalize();
             let proof_last = prover.get_proof();
@@ -334,6 +379,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_create_shplonk_proof_with_various_transcripts() {
         let mut rng_for_table = XORShiftRng::from_seed(SEED);
         let circuit = MyCircuit::<Fr, W, H>::rand(&mut rng_for_table);
@@ -377,7 +423,7 @@ mod test {
                 &s,
             );
 
-            let mut tachyon_pk = TachyonProvingKey::from(pk_bytes.as_slice());
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
             let mut transcript = TachyonBlake2bWrite::init(vec![]);
 
             tachyon_create_proof::<_, _, _, _, _>(
@@ -427,7 +473,7 @@ mod test {
                 &s,
             );
 
-            let mut tachyon_pk = TachyonProvingKey::from(pk_bytes.as_slice());
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
             let mut transcript = TachyonPoseidonWrite::init(vec![]);
 
             tachyon_create_proof::<_, _, _, _, _>(
@@ -477,7 +523,7 @@ mod test {
                 &s,
             );
 
-            let mut tachyon_pk = TachyonProvingKey::from(pk_bytes.as_slice());
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
             let mut transcript = TachyonSha256Write::init(vec![]);
 
             tachyon_create_proof::<_, _, _, _, _>(
@@ -237,7 +237,7 @@ impl<F: FieldExt> NumericInstructions<F> for FieldChip<F> {
 /// In this struct we store the private input variables. We use `Option<F>` because
 /// they won't have any value during key generation. During proving, if any of these
 /// were `None` we would get an error.
-#[derive(Clone, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct SimpleCircuit<F: FieldExt> {
     pub constant: F,
     pub a: Value<F>,
@@ -306,30 +306,38 @@ impl<F: FieldExt> Circuit<F> for SimpleCircuit<F> {
 mod test {
     use halo2_proofs::{
         circuit::Value,
-        plonk::keygen_pk2,
-        poly::kzg::{
-            commitment::{KZGCommitmentScheme, ParamsKZG},
-            multiopen::{ProverGWC, ProverSHPLONK},
+        plonk::{keygen_pk2, verify_proof},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, PoseidonWrite, TranscriptReadBuffer,
+            TranscriptWriterBuffer,
         },
-        transcript::{Blake2bWrite, Challenge255, PoseidonWrite, TranscriptWriterBuffer},
     };
     use halo2curves::bn256::{Bn256, Fr, G1Affine};
     use rand_core::SeedableRng;
 
     use crate::{
         bn254::{
-            Blake2bWrite as TachyonBlake2bWrite, GWCProver, PoseidonWrite as TachyonPoseidonWrite,
-            ProvingKey as TachyonProvingKey, SHPlonkProver, Sha256Write as TachyonSha256Write,
-            TachyonProver,
+            AnyProver, Blake2bWrite as TachyonBlake2bWrite, GWCProver,
+            PoseidonWrite as TachyonPoseidonWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            Sha256Write as TachyonSha256Write, TachyonProver,
         },
         circuits::simple_circuit::SimpleCircuit,
-        consts::{TranscriptType, SEED},
+        consts::{MultiOpenScheme, TranscriptType, SEED},
         prover::create_proof as tachyon_create_proof,
         sha::ShaWrite,
         xor_shift_rng::XORShiftRng,
     };
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_create_gwc_proof() {
         // ANCHOR: test-circuit
         // The number of rows in our circuit cannot exceed 2ᵏ. Since our example
@@ -394,7 +402,7 @@ mod test {
                 pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
                     .unwrap();
                 drop(pk);
-                TachyonProvingKey::from(pk_bytes.as_slice())
+                TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap()
             };
             let mut transcript = TachyonBlake2bWrite::init(vec![]);
 
@@ -418,6 +426,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_create_shplonk_proof_with_various_transcripts() {
         // ANCHOR: test-circuit
         // The number of rows in our circuit cannot exceed 2ᵏ. Since our example
@@ -483,7 +492,7 @@ mod test {
                 &s,
             );
 
-            let mut tachyon_pk = TachyonProvingKey::from(pk_bytes.as_slice());
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
             let mut transcript = TachyonBlake2bWrite::init(vec![]);
 
             tachyon_create_proof::<_, _, _, _, _>(
@@ -533,7 +542,7 @@ mod test {
                 &s,
             );
 
-            let mut tachyon_pk = TachyonProvingKey::from(pk_bytes.as_slice());
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
             let mut transcript = TachyonPoseidonWrite::init(vec![]);
 
             tachyon_create_proof::<_, _, _, _, _>(
@@ -584,7 +593,7 @@ mod test {
                 &s,
             );
 
-            let mut tachyon_pk = TachyonProvingKey::from(pk_bytes.as_slice());
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
             let mut transcript = TachyonSha256Write::init(vec![]);
 
             tachyon_create_proof::<_, _, _, _, _>(
@@ -605,4 +614,82 @@ mod test {
         assert_eq!(halo2_sha256_proof, tachyon_sha256_proof);
         // ANCHOR_END: test-circuit
     }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_create_proof_with_any_prover() {
+        let k = 4;
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        for scheme in [MultiOpenScheme::Gwc, MultiOpenScheme::SHPlonk] {
+            let mut prover = AnyProver::<KZGCommitmentScheme<Bn256>>::new(
+                scheme,
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            assert_eq!(prover.scheme(), scheme);
+
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            tachyon_create_proof::<_, _, _, _, _>(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&[&public_inputs[..]]],
+                XORShiftRng::from_seed(SEED),
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+
+            let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+            let strategy = SingleStrategy::new(&params);
+            let result = match scheme {
+                MultiOpenScheme::Gwc => verify_proof::<
+                    KZGCommitmentScheme<Bn256>,
+                    VerifierGWC<_>,
+                    _,
+                    _,
+                    SingleStrategy<_>,
+                >(
+                    params.verifier_params(),
+                    pk.get_vk(),
+                    strategy,
+                    &[&[&public_inputs[..]]],
+                    &mut transcript,
+                ),
+                MultiOpenScheme::SHPlonk => verify_proof::<
+                    KZGCommitmentScheme<Bn256>,
+                    VerifierSHPLONK<_>,
+                    _,
+                    _,
+                    SingleStrategy<_>,
+                >(
+                    params.verifier_params(),
+                    pk.get_vk(),
+                    strategy,
+                    &[&[&public_inputs[..]]],
+                    &mut transcript,
+                ),
+            };
+            assert!(result.is_ok(), "{:?} proof doesn't verify", scheme);
+        }
+    }
 }
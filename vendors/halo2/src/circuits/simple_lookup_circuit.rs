@@ -7,13 +7,13 @@ use halo2_proofs::{
     poly::Rotation,
 };
 
-#[derive(Clone, Default)]
-struct SimpleLookupCircuit<F: FieldExt> {
+#[derive(Clone, Debug, Default)]
+pub struct SimpleLookupCircuit<F: FieldExt> {
     _marker: PhantomData<F>,
 }
 
-#[derive(Clone)]
-struct SimpleLookupConfig {
+#[derive(Clone, Debug)]
+pub struct SimpleLookupConfig {
     selector: Selector,
     table: TableColumn,
     advice: Column<Advice>,
@@ -113,6 +113,7 @@ mod test {
     };
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_create_gwc_proof() {
         // ANCHOR: test-circuit
         // The number of rows in our circuit cannot exceed 2ᵏ. Since our example
@@ -167,7 +168,7 @@ mod test {
                 pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
                     .unwrap();
                 drop(pk);
-                TachyonProvingKey::from(pk_bytes.as_slice())
+                TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap()
             };
             let mut transcript = TachyonBlake2bWrite::init(vec![]);
 
@@ -191,6 +192,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_create_shplonk_proof_with_various_transcripts() {
         // ANCHOR: test-circuit
         // The number of rows in our circuit cannot exceed 2ᵏ. Since our example
@@ -246,7 +248,7 @@ mod test {
                 &s,
             );
 
-            let mut tachyon_pk = TachyonProvingKey::from(pk_bytes.as_slice());
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
             let mut transcript = TachyonBlake2bWrite::init(vec![]);
 
             tachyon_create_proof::<_, _, _, _, _>(
@@ -296,7 +298,7 @@ mod test {
                 &s,
             );
 
-            let mut tachyon_pk = TachyonProvingKey::from(pk_bytes.as_slice());
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
             let mut transcript = TachyonPoseidonWrite::init(vec![]);
 
             tachyon_create_proof::<_, _, _, _, _>(
@@ -347,7 +349,7 @@ mod test {
                 &s,
             );
 
-            let mut tachyon_pk = TachyonProvingKey::from(pk_bytes.as_slice());
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
             let mut transcript = TachyonSha256Write::init(vec![]);
 
             tachyon_create_proof::<_, _, _, _, _>(
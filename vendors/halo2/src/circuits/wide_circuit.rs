@@ -0,0 +1,105 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+/// The number of rows assigned in every advice column.
+const ROWS: usize = 4;
+
+#[derive(Clone, Debug)]
+pub struct WideConfig<const COLUMNS: usize> {
+    advice: [Column<Advice>; COLUMNS],
+    q_step: Selector,
+    q_instance: Selector,
+}
+
+/// Has `COLUMNS` advice columns, each counting up by its own step:
+/// `aᵢ(next) = aᵢ(cur) + i + 1`. `a₀` starts at the one instance value. It
+/// has little to prove per column, so that proving it is mostly the
+/// per-column work of the prover.
+#[derive(Clone, Debug, Default)]
+pub struct WideCircuit<F: FieldExt, const COLUMNS: usize> {
+    pub _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const COLUMNS: usize> WideCircuit<F, COLUMNS> {
+    fn value(column: usize, row: usize) -> F {
+        F::from(((column + 1) * (row + 1)) as u64)
+    }
+
+    /// The values of the instance column.
+    pub fn instances(&self) -> Vec<F> {
+        vec![Self::value(0, 0)]
+    }
+}
+
+impl<F: FieldExt, const COLUMNS: usize> Circuit<F> for WideCircuit<F, COLUMNS> {
+    type Config = WideConfig<COLUMNS>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> WideConfig<COLUMNS> {
+        let advice = [(); COLUMNS].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let q_step = meta.selector();
+        let q_instance = meta.selector();
+
+        meta.create_gate("step", |meta| {
+            let q_step = meta.query_selector(q_step);
+            advice
+                .iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    let cur = meta.query_advice(*column, Rotation::cur());
+                    let next = meta.query_advice(*column, Rotation::next());
+                    q_step.clone() * (next - cur - Expression::Constant(F::from(i as u64 + 1)))
+                })
+                .collect::<Vec<_>>()
+        });
+        meta.create_gate("instance", |meta| {
+            let q_instance = meta.query_selector(q_instance);
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let instance = meta.query_instance(instance, Rotation::cur());
+            vec![q_instance * (a - instance)]
+        });
+
+        WideConfig {
+            advice,
+            q_step,
+            q_instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: WideConfig<COLUMNS>,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "wide",
+            |mut region| {
+                for (column_index, column) in config.advice.iter().enumerate() {
+                    for row in 0..ROWS {
+                        region.assign_advice(
+                            || "a",
+                            *column,
+                            row,
+                            || Value::known(Self::value(column_index, row)),
+                        )?;
+                    }
+                }
+                for row in 0..ROWS - 1 {
+                    config.q_step.enable(&mut region, row)?;
+                }
+                config.q_instance.enable(&mut region, 0)
+            },
+        )
+    }
+}
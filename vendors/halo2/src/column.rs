@@ -0,0 +1,192 @@
+use std::fmt;
+
+use halo2_proofs::plonk::{Advice, Column, Fixed, Instance};
+
+use crate::debug::ColumnKind;
+
+macro_rules! column_index {
+    ($(#[$attr:meta])* $name:ident, $column:ty) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub(crate) usize);
+
+        impl $name {
+            pub fn index(&self) -> usize {
+                self.0
+            }
+        }
+
+        impl From<Column<$column>> for $name {
+            fn from(column: Column<$column>) -> Self {
+                $name(column.index())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+column_index!(
+    /// The index of an advice column, as in `Column::<Advice>::index()`.
+    /// Get one from a column of the circuit, or from a plain index with
+    /// [`crate::bn254::ProvingKey::advice_index()`], which checks it against
+    /// the key.
+    AdviceColumnIndex,
+    Advice
+);
+column_index!(
+    /// The index of a fixed column, see [`AdviceColumnIndex`] and
+    /// [`crate::bn254::ProvingKey::fixed_index()`].
+    FixedColumnIndex,
+    Fixed
+);
+column_index!(
+    /// The index of an instance column, see [`AdviceColumnIndex`] and
+    /// [`crate::bn254::ProvingKey::instance_index()`].
+    InstanceColumnIndex,
+    Instance
+);
+
+/// A column index a key doesn't have a column of its kind for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnIndexError {
+    pub kind: ColumnKind,
+    pub index: usize,
+    pub num_columns: usize,
+}
+
+impl fmt::Display for ColumnIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ColumnKind::Advice => "advice",
+            ColumnKind::Fixed => "fixed",
+            ColumnKind::Instance => "instance",
+        };
+        write!(
+            f,
+            "{} column {} is out of range, the key has {}",
+            kind, self.index, self.num_columns
+        )
+    }
+}
+
+impl std::error::Error for ColumnIndexError {}
+
+/// Checks `index` against the `num_columns` columns of `kind` of a key.
+pub(crate) fn check_index(
+    kind: ColumnKind,
+    index: usize,
+    num_columns: usize,
+) -> Result<usize, ColumnIndexError> {
+    if index < num_columns {
+        Ok(index)
+    } else {
+        Err(ColumnIndexError {
+            kind,
+            index,
+            num_columns,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, ConstraintSystem},
+        poly::kzg::commitment::ParamsKZG,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+    use static_assertions::assert_not_impl_any;
+
+    use super::{AdviceColumnIndex, ColumnIndexError, FixedColumnIndex, InstanceColumnIndex};
+    use crate::{
+        bn254::ProvingKey as TachyonProvingKey, circuits::simple_circuit::SimpleCircuit,
+        debug::ColumnKind,
+    };
+
+    // An index of one kind can't be passed where another kind is expected,
+    // and a plain index has to go through the key first.
+    assert_not_impl_any!(
+        AdviceColumnIndex: From<usize>,
+        From<FixedColumnIndex>,
+        From<InstanceColumnIndex>
+    );
+    assert_not_impl_any!(
+        FixedColumnIndex: From<usize>,
+        From<AdviceColumnIndex>,
+        From<InstanceColumnIndex>
+    );
+    assert_not_impl_any!(
+        InstanceColumnIndex: From<usize>,
+        From<AdviceColumnIndex>,
+        From<FixedColumnIndex>
+    );
+
+    #[test]
+    fn test_from_column() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let _ = meta.advice_column();
+        let advice = meta.advice_column();
+        let fixed = meta.fixed_column();
+        let instance = meta.instance_column();
+        assert_eq!(AdviceColumnIndex::from(advice).index(), 1);
+        assert_eq!(FixedColumnIndex::from(fixed).index(), 0);
+        assert_eq!(InstanceColumnIndex::from(instance).index(), 0);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_pk_index() {
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+
+        let num_advice_columns = tachyon_pk.num_advice_columns();
+        assert_eq!(
+            tachyon_pk
+                .advice_index(num_advice_columns - 1)
+                .unwrap()
+                .index(),
+            num_advice_columns - 1
+        );
+        assert_eq!(
+            tachyon_pk.advice_index(num_advice_columns),
+            Err(ColumnIndexError {
+                kind: ColumnKind::Advice,
+                index: num_advice_columns,
+                num_columns: num_advice_columns,
+            })
+        );
+
+        let num_fixed_columns = tachyon_pk.num_fixed_columns();
+        assert!(tachyon_pk.fixed_index(0).is_ok());
+        let error = tachyon_pk.fixed_index(num_fixed_columns + 3).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "fixed column {} is out of range, the key has {}",
+                num_fixed_columns + 3,
+                num_fixed_columns
+            )
+        );
+
+        assert!(tachyon_pk.instance_index(0).is_ok());
+        assert_eq!(
+            tachyon_pk.instance_index(1).unwrap_err().kind,
+            ColumnKind::Instance
+        );
+    }
+}
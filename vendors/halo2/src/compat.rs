@@ -0,0 +1,398 @@
+// Downstream crates frequently pin a `halo2curves` version that differs from
+// ours, which makes `bn256::Fr` from both sides distinct types. The functions
+// below only go through the canonical byte encodings so that values can cross
+// the `create_proof` boundary without transmuting.
+//
+// `CompatScalar` covers the scalars of every halo2curves built on ff 0.12,
+// i.e, the 0.3 releases and forks of them like ours, without a feature per
+// version. The releases from 0.4 on are built on ff 0.13, and aren't covered:
+// a second blanket impl over ff 0.13's `PrimeField` would conflict with this
+// one, so each of them would need an impl for its concrete `Fr` behind a
+// feature and an optional dependency on it. Their scalars can still cross
+// with `fr_from_bytes_le()` on their `to_repr()`.
+
+use std::fmt;
+
+use ff::PrimeField;
+use halo2curves::{
+    bn256::{Fq, Fr, G1Affine},
+    CurveAffine,
+};
+
+use crate::{
+    serde_fr::{fr_from_le_bytes, fr_to_le_bytes},
+    version::HALO2_VERSION,
+};
+
+/// Parses a canonical little-endian encoding of a scalar. Returns `None` if
+/// `bytes` is not smaller than the modulus.
+pub fn fr_from_bytes_le(bytes: &[u8; 32]) -> Option<Fr> {
+    fr_from_le_bytes(bytes).ok()
+}
+
+pub fn fr_to_bytes_le(fr: &Fr) -> [u8; 32] {
+    fr_to_le_bytes(fr)
+}
+
+/// Builds an affine point from canonical little-endian encodings of its
+/// coordinates. Returns `None` if either coordinate is not canonical or the
+/// point is not on the curve.
+pub fn g1_from_xy(x: &[u8; 32], y: &[u8; 32]) -> Option<G1Affine> {
+    let x = Option::from(Fq::from_repr(*x))?;
+    let y = Option::from(Fq::from_repr(*y))?;
+    Option::from(G1Affine::from_xy(x, y))
+}
+
+/// Returns the little-endian encodings of the coordinates of `point`, or
+/// `None` for the point at infinity.
+pub fn g1_to_xy(point: &G1Affine) -> Option<([u8; 32], [u8; 32])> {
+    let coords = Option::from(point.coordinates())?;
+    Some((coords.x().to_repr(), coords.y().to_repr()))
+}
+
+/// A scalar that can be converted to and from the `Fr` this crate links
+/// against by way of its canonical encoding.
+pub trait CompatScalar: Sized {
+    fn to_bytes_le(&self) -> [u8; 32];
+
+    fn from_bytes_le(bytes: &[u8; 32]) -> Option<Self>;
+
+    fn to_fr(&self) -> Fr {
+        // A value produced by `to_bytes_le()` is always canonical, so this
+        // never fails for a well-behaved implementation.
+        fr_from_bytes_le(&self.to_bytes_le()).expect("non-canonical scalar encoding")
+    }
+
+    fn from_fr(fr: &Fr) -> Option<Self> {
+        Self::from_bytes_le(&fr_to_bytes_le(fr))
+    }
+}
+
+impl<F: PrimeField<Repr = [u8; 32]>> CompatScalar for F {
+    fn to_bytes_le(&self) -> [u8; 32] {
+        self.to_repr()
+    }
+
+    fn from_bytes_le(bytes: &[u8; 32]) -> Option<Self> {
+        Option::from(F::from_repr(*bytes))
+    }
+}
+
+/// A halo2_proofs crate, as declared in `Cargo.toml` or as Cargo resolved it.
+/// A verifying key only matches between provers built against the same one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Halo2Version {
+    /// The name the crate is published under, "halo2_proofs" for every fork.
+    pub crate_name: String,
+    /// The GitHub repository of the fork, e.g, "kroma-network/halo2", or
+    /// "crates.io" or "path" for a crate that isn't from git.
+    pub fork: String,
+    pub version: Option<String>,
+    /// The git revision. It is the full hash for a linked crate and may be a
+    /// prefix of it for a declared one.
+    pub rev: Option<String>,
+}
+
+impl Halo2Version {
+    /// Parses an entry of `TACHYON_HALO2_EXPECTED` or `TACHYON_HALO2_LINKED`,
+    /// which build.rs captures from `Cargo.toml` and `Cargo.lock`, e.g,
+    /// "name=halo2_proofs;git=https://github.com/kroma-network/halo2.git;rev=be2e64f"
+    /// or "name=halo2_proofs;version=0.2.0;source=git+https://...?rev=be2e64f#be2e64f3...".
+    fn from_build_env(entry: &str) -> Option<Halo2Version> {
+        let field = |key: &str| {
+            entry.split(';').find_map(|field| {
+                let (k, v) = field.split_once('=')?;
+                (k == key).then(|| v.to_owned())
+            })
+        };
+        let crate_name = field("name")?;
+        let version = field("version");
+        let (fork, rev) = match (field("git"), field("source")) {
+            (Some(git), _) => (fork_of(&git), field("rev")),
+            (None, Some(source)) => match source.strip_prefix("git+") {
+                Some(git) => {
+                    let (url, rev) = match git.split_once('#') {
+                        Some((url, rev)) => (url, Some(rev.to_owned())),
+                        None => (git, None),
+                    };
+                    (fork_of(url.split('?').next().unwrap()), rev)
+                }
+                None if source.starts_with("registry+") => ("crates.io".to_owned(), None),
+                None => (source, None),
+            },
+            (None, None) if version.is_some() => ("crates.io".to_owned(), None),
+            (None, None) => ("path".to_owned(), None),
+        };
+        Some(Halo2Version {
+            crate_name,
+            fork,
+            version,
+            rev,
+        })
+    }
+
+    /// Parses [`HALO2_VERSION`], e.g, "kroma-network/halo2@be2e64f".
+    fn from_halo2_version(halo2_version: &str) -> Halo2Version {
+        let (fork, rev) = match halo2_version.split_once('@') {
+            Some((fork, rev)) => (fork, Some(rev.to_owned())),
+            None => (halo2_version, None),
+        };
+        Halo2Version {
+            crate_name: "halo2_proofs".to_owned(),
+            fork: fork.to_owned(),
+            version: None,
+            rev,
+        }
+    }
+
+    /// Whether the linked crate `linked` is this declared one. A field this
+    /// one leaves out matches anything, and a revision matches one it is a
+    /// prefix of.
+    pub fn accepts(&self, linked: &Halo2Version) -> bool {
+        let version_matches = match (&self.version, &linked.version) {
+            (None, _) => true,
+            (Some(expected), Some(found)) => {
+                found == expected || found.starts_with(&format!("{}.", expected))
+            }
+            (Some(_), None) => false,
+        };
+        let rev_matches = match (&self.rev, &linked.rev) {
+            (None, _) => true,
+            (Some(expected), Some(found)) => found.starts_with(expected.as_str()),
+            (Some(_), None) => false,
+        };
+        self.crate_name == linked.crate_name
+            && self.fork == linked.fork
+            && version_matches
+            && rev_matches
+    }
+}
+
+impl fmt::Display for Halo2Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.crate_name)?;
+        if let Some(version) = &self.version {
+            write!(f, " {}", version)?;
+        }
+        write!(f, " ({}", self.fork)?;
+        if let Some(rev) = &self.rev {
+            write!(f, "@{}", rev)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// "https://github.com/kroma-network/halo2.git" -> "kroma-network/halo2".
+fn fork_of(url: &str) -> String {
+    let url = url.trim_end_matches('/').trim_end_matches(".git");
+    url.strip_prefix("https://github.com/")
+        .unwrap_or(url)
+        .to_owned()
+}
+
+fn parse_build_env(value: &str) -> Vec<Halo2Version> {
+    value
+        .split('|')
+        .filter_map(Halo2Version::from_build_env)
+        .collect()
+}
+
+/// The halo2_proofs crates this crate is written against, one per
+/// dependency on it in `Cargo.toml`, e.g, one per feature-selected fork.
+/// Without build.rs, e.g, with Bazel, it is the one of [`HALO2_VERSION`].
+pub fn halo2_versions() -> Vec<Halo2Version> {
+    match option_env!("TACHYON_HALO2_EXPECTED") {
+        Some(expected) => parse_build_env(expected),
+        None => vec![Halo2Version::from_halo2_version(HALO2_VERSION)],
+    }
+}
+
+/// The halo2_proofs crates Cargo resolved the dependencies of
+/// [`halo2_versions()`] to, as recorded in `Cargo.lock`. It differs from them
+/// when halo2_proofs is patched, e.g, with `[patch]`. Empty if the crate was
+/// built without build.rs or `Cargo.lock`.
+pub fn linked_halo2() -> Vec<Halo2Version> {
+    option_env!("TACHYON_HALO2_LINKED")
+        .map(parse_build_env)
+        .unwrap_or_default()
+}
+
+/// The linked halo2_proofs isn't one this crate is written against, or it
+/// couldn't be told which one it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Halo2Mismatch {
+    pub expected: Vec<Halo2Version>,
+    pub linked: Vec<Halo2Version>,
+}
+
+impl fmt::Display for Halo2Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "the linked halo2_proofs isn't one this crate supports")?;
+        if self.linked.is_empty() {
+            writeln!(
+                f,
+                "  linked: unknown, the crate was built without Cargo.lock"
+            )?;
+        }
+        for linked in &self.linked {
+            writeln!(f, "  linked: {}", linked)?;
+        }
+        for expected in &self.expected {
+            writeln!(f, "  supported: {}", expected)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Halo2Mismatch {}
+
+/// Checks that every linked halo2_proofs is accepted by one of `expected`.
+pub fn check_halo2(
+    expected: &[Halo2Version],
+    linked: &[Halo2Version],
+) -> Result<(), Halo2Mismatch> {
+    if !linked.is_empty()
+        && linked
+            .iter()
+            .all(|linked| expected.iter().any(|expected| expected.accepts(linked)))
+    {
+        Ok(())
+    } else {
+        Err(Halo2Mismatch {
+            expected: expected.to_vec(),
+            linked: linked.to_vec(),
+        })
+    }
+}
+
+/// Checks that the linked halo2_proofs is one of [`halo2_versions()`]. Call
+/// this at startup to catch a patched halo2_proofs before it produces keys
+/// the verifier doesn't accept.
+pub fn assert_halo2() -> Result<(), Halo2Mismatch> {
+    check_halo2(&halo2_versions(), &linked_halo2())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        assert_halo2, check_halo2, fr_from_bytes_le, fr_to_bytes_le, g1_from_xy, g1_to_xy,
+        halo2_versions, linked_halo2, parse_build_env, CompatScalar, Halo2Mismatch, Halo2Version,
+    };
+    use ff::Field;
+    use halo2curves::{
+        bn256::{Fr, G1Affine},
+        group::prime::PrimeCurveAffine,
+    };
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_fr_bytes() {
+        let fr = Fr::random(OsRng);
+        assert_eq!(fr_from_bytes_le(&fr_to_bytes_le(&fr)), Some(fr));
+        assert_eq!(fr.to_fr(), fr);
+        assert_eq!(Fr::from_fr(&fr), Some(fr));
+
+        // Values above the modulus are not canonical encodings.
+        assert_eq!(fr_from_bytes_le(&[0xff; 32]), None);
+    }
+
+    #[test]
+    fn test_g1_xy() {
+        let point = G1Affine::random(OsRng);
+        let (x, y) = g1_to_xy(&point).unwrap();
+        assert_eq!(g1_from_xy(&x, &y), Some(point));
+
+        let mut bad_y = y;
+        bad_y[0] ^= 1;
+        assert_eq!(g1_from_xy(&x, &bad_y), None);
+        assert_eq!(g1_to_xy(&<G1Affine as PrimeCurveAffine>::identity()), None);
+    }
+
+    fn kroma(rev: &str) -> Halo2Version {
+        Halo2Version {
+            crate_name: "halo2_proofs".to_owned(),
+            fork: "kroma-network/halo2".to_owned(),
+            version: None,
+            rev: Some(rev.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_parse_build_env() {
+        let expected = parse_build_env(
+            "name=halo2_proofs;git=https://github.com/kroma-network/halo2.git;rev=be2e64f|\
+             name=halo2_proofs;version=0.3",
+        );
+        assert_eq!(
+            expected,
+            vec![
+                kroma("be2e64f"),
+                Halo2Version {
+                    crate_name: "halo2_proofs".to_owned(),
+                    fork: "crates.io".to_owned(),
+                    version: Some("0.3".to_owned()),
+                    rev: None,
+                },
+            ]
+        );
+
+        let linked = parse_build_env(
+            "name=halo2_proofs;version=0.2.0;source=git+https://github.com/kroma-network/halo2.git\
+             ?rev=be2e64f#be2e64f33363340df28ac671da6a7040bf9f8b93",
+        );
+        assert_eq!(
+            linked,
+            vec![Halo2Version {
+                version: Some("0.2.0".to_owned()),
+                ..kroma("be2e64f33363340df28ac671da6a7040bf9f8b93")
+            }]
+        );
+        assert!(expected[0].accepts(&linked[0]));
+        assert!(!expected[1].accepts(&linked[0]));
+        assert_eq!(
+            linked[0].to_string(),
+            "halo2_proofs 0.2.0 (kroma-network/halo2@be2e64f33363340df28ac671da6a7040bf9f8b93)"
+        );
+    }
+
+    #[test]
+    fn test_assert_halo2() {
+        assert!(!halo2_versions().is_empty());
+        // Only a build with Cargo.lock knows what is linked.
+        if !linked_halo2().is_empty() {
+            assert_eq!(assert_halo2(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_halo2_mismatch() {
+        let linked = vec![kroma("be2e64f33363340df28ac671da6a7040bf9f8b93")];
+        assert_eq!(check_halo2(&[kroma("be2e64f")], &linked), Ok(()));
+
+        // A build pinned to the PSE fork or another revision of the same one
+        // doesn't accept it, and the error names both sides.
+        let pse = Halo2Version {
+            fork: "privacy-scaling-explorations/halo2".to_owned(),
+            ..kroma("7a21656")
+        };
+        let expected = vec![pse, kroma("0123456")];
+        let err = check_halo2(&expected, &linked).unwrap_err();
+        assert_eq!(
+            err,
+            Halo2Mismatch {
+                expected: expected.clone(),
+                linked: linked.clone(),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "the linked halo2_proofs isn't one this crate supports\n  \
+             linked: halo2_proofs (kroma-network/halo2@be2e64f33363340df28ac671da6a7040bf9f8b93)\n  \
+             supported: halo2_proofs (privacy-scaling-explorations/halo2@7a21656)\n  \
+             supported: halo2_proofs (kroma-network/halo2@0123456)\n"
+        );
+
+        let err = check_halo2(&expected, &[]).unwrap_err();
+        assert!(err.to_string().contains("linked: unknown"));
+    }
+}
@@ -1,7 +1,219 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TranscriptType {
     Blake2b,
     Poseidon,
     Sha256,
+    /// A hasher registered with [`crate::transcript::register_custom()`].
+    Custom,
+}
+
+impl TranscriptType {
+    /// The name the transcript is stored under, e.g, in a
+    /// [`crate::repro::ReproJob`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            TranscriptType::Blake2b => "blake2b",
+            TranscriptType::Poseidon => "poseidon",
+            TranscriptType::Sha256 => "sha256",
+            TranscriptType::Custom => "custom",
+        }
+    }
+
+    /// Inverse of `transcript_type as u8`, which is how the backend takes it.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        [
+            TranscriptType::Blake2b,
+            TranscriptType::Poseidon,
+            TranscriptType::Sha256,
+            TranscriptType::Custom,
+        ]
+        .into_iter()
+        .find(|transcript_type| *transcript_type as u8 == value)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "blake2b" => Some(TranscriptType::Blake2b),
+            "poseidon" => Some(TranscriptType::Poseidon),
+            "sha256" => Some(TranscriptType::Sha256),
+            "custom" => Some(TranscriptType::Custom),
+            _ => None,
+        }
+    }
+}
+
+/// The multi-open argument a proof is created with. It has to match the
+/// verifier the proof is checked by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultiOpenScheme {
+    Gwc,
+    SHPlonk,
+}
+
+impl MultiOpenScheme {
+    /// The name the scheme is stored under in metadata.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MultiOpenScheme::Gwc => "gwc",
+            MultiOpenScheme::SHPlonk => "shplonk",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "gwc" => Some(MultiOpenScheme::Gwc),
+            "shplonk" => Some(MultiOpenScheme::SHPlonk),
+            _ => None,
+        }
+    }
+}
+
+/// How the instances are absorbed into the transcript when they aren't
+/// committed to. This changes the transcript, so it is recorded with the key,
+/// see [`crate::proving_key::instance_absorption_to_metadata()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstanceAbsorption {
+    /// Every value is absorbed on its own, like halo2 does.
+    PerValue,
+    /// Every column is absorbed as a single digest of its values, hashed
+    /// with Poseidon in a Poseidon transcript and with Blake2b otherwise, see
+    /// [`crate::instance_hash::column_digest()`].
+    HashedColumn,
+}
+
+impl InstanceAbsorption {
+    /// The name the absorption is stored under in metadata.
+    pub fn name(&self) -> &'static str {
+        match self {
+            InstanceAbsorption::PerValue => "per_value",
+            InstanceAbsorption::HashedColumn => "hashed_column",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "per_value" => Some(InstanceAbsorption::PerValue),
+            "hashed_column" => Some(InstanceAbsorption::HashedColumn),
+            _ => None,
+        }
+    }
+}
+
+/// The order the advice commitments of a phase are written to the transcript
+/// in. The columns are committed to in the same order either way, and a phase
+/// always comes after the challenges of the one before it. This changes the
+/// transcript, so it is recorded with the key, see
+/// [`crate::proving_key::advice_absorb_order_to_metadata()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbsorbOrder {
+    /// Circuit by circuit, and the columns of a circuit in ascending order,
+    /// like halo2.
+    PhaseThenColumn,
+    /// Column by column in ascending order, and the circuits of a column in
+    /// order, so that a column of every circuit is absorbed together.
+    GlobalColumn,
+}
+
+impl Default for AbsorbOrder {
+    fn default() -> Self {
+        AbsorbOrder::PhaseThenColumn
+    }
+}
+
+impl AbsorbOrder {
+    /// The name the order is stored under in metadata.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AbsorbOrder::PhaseThenColumn => "phase_then_column",
+            AbsorbOrder::GlobalColumn => "global_column",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "phase_then_column" => Some(AbsorbOrder::PhaseThenColumn),
+            "global_column" => Some(AbsorbOrder::GlobalColumn),
+            _ => None,
+        }
+    }
+}
+
+/// How the verifying key is hashed into the scalar that starts the
+/// transcript. Forks of halo2 differ in it, and a proof only verifies with the
+/// one it was created with. See [`crate::vk_hash::transcript_repr()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VkHashMode {
+    /// The Blake2b-512 hash reduced into the field, like halo2.
+    Blake2bField,
+    /// A Poseidon hash, for verifiers inside another circuit.
+    Poseidon,
+    /// The Keccak-256 hash truncated to 248 bits, like Scroll's fork.
+    Keccak256Truncated,
+}
+
+impl VkHashMode {
+    pub const ALL: [VkHashMode; 3] = [
+        VkHashMode::Blake2bField,
+        VkHashMode::Poseidon,
+        VkHashMode::Keccak256Truncated,
+    ];
+
+    /// The name the mode is stored under in metadata.
+    pub fn name(&self) -> &'static str {
+        match self {
+            VkHashMode::Blake2bField => "blake2b_field",
+            VkHashMode::Poseidon => "poseidon",
+            VkHashMode::Keccak256Truncated => "keccak256_truncated",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "blake2b_field" => Some(VkHashMode::Blake2bField),
+            "poseidon" => Some(VkHashMode::Poseidon),
+            "keccak256_truncated" => Some(VkHashMode::Keccak256Truncated),
+            _ => None,
+        }
+    }
+}
+
+/// How the prover gets the Lagrange bases it commits to evaluations with. The
+/// commitments are the same under every policy, which trade the memory of a
+/// second copy of the bases for time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LagrangeBasisPolicy {
+    /// Keeps the Lagrange bases next to the monomial ones.
+    Resident,
+    /// Computes the Lagrange bases from the monomial ones, an FFT over the
+    /// bases, for every batch of commitments and drops them after.
+    RecomputePerBatch,
+    /// Interpolates every column and commits to its coefficients with the
+    /// monomial bases.
+    ViaMonomial,
+}
+
+impl Default for LagrangeBasisPolicy {
+    fn default() -> Self {
+        LagrangeBasisPolicy::Resident
+    }
+}
+
+impl LagrangeBasisPolicy {
+    pub const ALL: [LagrangeBasisPolicy; 3] = [
+        LagrangeBasisPolicy::Resident,
+        LagrangeBasisPolicy::RecomputePerBatch,
+        LagrangeBasisPolicy::ViaMonomial,
+    ];
+
+    /// The policy as the backend takes it, one of
+    /// `TACHYON_HALO2_LAGRANGE_BASES_*`.
+    pub fn code(&self) -> u8 {
+        match self {
+            LagrangeBasisPolicy::Resident => 0,
+            LagrangeBasisPolicy::RecomputePerBatch => 1,
+            LagrangeBasisPolicy::ViaMonomial => 2,
+        }
+    }
 }
 
 pub const SEED: [u8; 16] = [
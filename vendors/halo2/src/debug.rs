@@ -0,0 +1,1332 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use ff::{Field, PrimeField};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Expression, Fixed, FloorPlanner, Instance, Selector,
+    },
+    poly::Rotation,
+};
+use halo2curves::bn256::Fr;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    analyze::usable_rows,
+    annotation::{describe_column, ColumnAnnotations, RowOverflow},
+    audit::to_hex,
+    column::{AdviceColumnIndex, FixedColumnIndex, InstanceColumnIndex},
+    repro::{ExportedWitness, WitnessColumn},
+    serde_fr::fr_to_le_bytes,
+};
+
+/// The number of violations that are reported with the cells they read.
+const MAX_DETAILED_VIOLATIONS: usize = 4;
+
+#[derive(Debug)]
+pub enum DebugError {
+    Plonk(Error),
+    /// There are only `num_gates` gates.
+    NoSuchGate {
+        index: usize,
+        num_gates: usize,
+    },
+    /// The circuit squeezes challenges, so its later phase witness depends on
+    /// a transcript that doesn't exist here.
+    UsesChallenges,
+    /// The rows in which a gate or a lookup isn't satisfied, as
+    /// `(gate name, row)` and `(lookup index, row)`, and the cells that are
+    /// copies of each other but hold different values, as pairs of
+    /// `(column, row)`, see [`check_copies()`].
+    Unsatisfied {
+        gates: Vec<(String, usize)>,
+        lookups: Vec<(usize, usize)>,
+        copies: Vec<((String, usize), (String, usize))>,
+    },
+    RowOverflow(RowOverflow),
+}
+
+impl fmt::Display for DebugError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DebugError::Plonk(e) => write!(f, "{}", e),
+            DebugError::NoSuchGate { index, num_gates } => {
+                write!(f, "gate {} doesn't exist, there are {}", index, num_gates)
+            }
+            DebugError::UsesChallenges => write!(f, "circuits with challenges aren't supported"),
+            DebugError::Unsatisfied {
+                gates,
+                lookups,
+                copies,
+            } => write!(
+                f,
+                "constraints aren't satisfied, gates: {:?}, lookups: {:?}, copies: {:?}",
+                gates, lookups, copies
+            ),
+            DebugError::RowOverflow(overflow) => write!(f, "{}", overflow),
+        }
+    }
+}
+
+impl std::error::Error for DebugError {}
+
+impl From<Error> for DebugError {
+    fn from(e: Error) -> Self {
+        DebugError::Plonk(e)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnKind {
+    Advice,
+    Fixed,
+    Instance,
+}
+
+/// A cell read by a gate, relative to the row the gate is evaluated at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cell<F> {
+    pub kind: ColumnKind,
+    pub column: usize,
+    pub rotation: Rotation,
+    pub row: usize,
+    pub value: F,
+}
+
+impl<F> Cell<F> {
+    /// The column of the cell if it is an advice cell.
+    pub fn advice_column(&self) -> Option<AdviceColumnIndex> {
+        (self.kind == ColumnKind::Advice).then(|| AdviceColumnIndex(self.column))
+    }
+
+    /// The column of the cell if it is a fixed cell.
+    pub fn fixed_column(&self) -> Option<FixedColumnIndex> {
+        (self.kind == ColumnKind::Fixed).then(|| FixedColumnIndex(self.column))
+    }
+
+    /// The column of the cell if it is an instance cell.
+    pub fn instance_column(&self) -> Option<InstanceColumnIndex> {
+        (self.kind == ColumnKind::Instance).then(|| InstanceColumnIndex(self.column))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Violation<F> {
+    pub row: usize,
+    /// The index of the polynomial within the gate.
+    pub constraint: usize,
+    pub value: F,
+    /// The cells the polynomial reads. Only filled for the first few
+    /// violations.
+    pub cells: Vec<Cell<F>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GateReport<F> {
+    pub gate: String,
+    pub violations: Vec<Violation<F>>,
+}
+
+impl<F> GateReport<F> {
+    /// Returns every row in which at least one polynomial of the gate is
+    /// nonzero.
+    pub fn rows(&self) -> Vec<usize> {
+        let mut rows: Vec<_> = self
+            .violations
+            .iter()
+            .map(|violation| violation.row)
+            .collect();
+        rows.dedup();
+        rows
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupViolation<F> {
+    pub lookup_index: usize,
+    pub row: usize,
+    /// The values of the input expressions in `row`, which aren't a row of the
+    /// table.
+    pub input_values: Vec<F>,
+    /// The columns the input expressions query, see
+    /// [`crate::annotation::describe_column()`].
+    pub input_columns: Vec<String>,
+}
+
+/// Two cells the permutation argument requires to be equal, but that hold
+/// different values. `left` is the first cell of their cycle, which every
+/// other cell of it is compared with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CopyViolation<F> {
+    pub left: (Column<Any>, usize),
+    pub right: (Column<Any>, usize),
+    pub left_value: F,
+    pub right_value: F,
+}
+
+/// Every cell a circuit assigns, collected by synthesizing it the way the
+/// prover does.
+pub struct Witness<F: Field> {
+    k: u32,
+    cs: ConstraintSystem<F>,
+    advice: Vec<Vec<F>>,
+    fixed: Vec<Vec<F>>,
+    instance: Vec<Vec<F>>,
+    selectors: Vec<Vec<bool>>,
+    /// The pairs of cells the circuit constrains to be equal, which keygen
+    /// builds the permutation of the key from.
+    copies: Vec<((Column<Any>, usize), (Column<Any>, usize))>,
+    annotations: ColumnAnnotations,
+    /// The first cell assigned past the usable rows, if synthesis failed
+    /// because of one.
+    overflow: Option<RowOverflow>,
+}
+
+// The values are the private witness, so only the shape is printed.
+impl<F: Field> fmt::Debug for Witness<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Witness")
+            .field("k", &self.k)
+            .field("num_advice_columns", &self.advice.len())
+            .field("num_fixed_columns", &self.fixed.len())
+            .field("num_instance_columns", &self.instance.len())
+            .field("num_copies", &self.copies.len())
+            .field("overflow", &self.overflow)
+            .finish()
+    }
+}
+
+impl<F: Field> Witness<F> {
+    /// Synthesizes `circuit` for `2ᵏ` rows. Cells that aren't assigned are
+    /// zero.
+    pub fn new<ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+        instances: &[&[F]],
+    ) -> Result<Self, DebugError> {
+        let mut cs = ConstraintSystem::default();
+        let config = ConcreteCircuit::configure(&mut cs);
+        if cs.num_challenges() > 0 {
+            return Err(DebugError::UsesChallenges);
+        }
+        if instances.len() != cs.num_instance_columns() {
+            return Err(Error::InvalidInstances.into());
+        }
+
+        let n = 1usize << k;
+        let mut instance = vec![vec![F::zero(); n]; cs.num_instance_columns()];
+        for (column, values) in instance.iter_mut().zip(instances) {
+            if values.len() > n {
+                return Err(Error::InstanceTooLarge.into());
+            }
+            column[..values.len()].copy_from_slice(values);
+        }
+
+        let mut witness = Witness {
+            k,
+            advice: vec![vec![F::zero(); n]; cs.num_advice_columns()],
+            fixed: vec![vec![F::zero(); n]; cs.num_fixed_columns()],
+            instance,
+            selectors: vec![vec![false; n]; cs.num_selectors()],
+            copies: vec![],
+            cs,
+            annotations: ColumnAnnotations::new(),
+            overflow: None,
+        };
+        let constants = witness.cs.constants().clone();
+        if let Err(e) =
+            ConcreteCircuit::FloorPlanner::synthesize(&mut witness, circuit, config, constants)
+        {
+            return Err(match witness.overflow.take() {
+                Some(overflow) => DebugError::RowOverflow(overflow),
+                None => e.into(),
+            });
+        }
+        Ok(witness)
+    }
+
+    /// Names `column` by its annotation, if the circuit gave it one.
+    pub fn describe_column(&self, column: &Column<Any>) -> String {
+        describe_column(column, self.annotations.get(column).map(String::as_str))
+    }
+
+    fn usable_rows(&self) -> usize {
+        usable_rows(self.k, self.cs.blinding_factors())
+    }
+
+    fn check_row(&self, row: usize) -> Result<(), Error> {
+        if row < self.usable_rows() {
+            Ok(())
+        } else {
+            Err(Error::not_enough_rows_available(self.k))
+        }
+    }
+
+    /// Same as `check_row()`, but remembers which column overflowed.
+    fn check_cell(&mut self, column: Column<Any>, row: usize) -> Result<(), Error> {
+        let result = self.check_row(row);
+        if result.is_err() && self.overflow.is_none() {
+            self.overflow = Some(RowOverflow {
+                column: self.describe_column(&column),
+                row,
+                usable_rows: self.usable_rows(),
+            });
+        }
+        result
+    }
+
+    /// Returns the columns `expressions` query, in the order they are first
+    /// queried.
+    fn queried_columns(&self, expressions: &[Expression<F>]) -> Vec<Column<Any>> {
+        let mut columns: Vec<Column<Any>> = vec![];
+        for expression in expressions {
+            for column in expression.evaluate(
+                &|_| vec![],
+                &|_| vec![],
+                &|query| {
+                    self.cs
+                        .fixed_queries()
+                        .iter()
+                        .filter(|(column, _)| column.index() == query.column_index())
+                        .map(|(column, _)| Column::<Any>::from(*column))
+                        .take(1)
+                        .collect()
+                },
+                &|query| {
+                    self.cs
+                        .advice_queries()
+                        .iter()
+                        .filter(|(column, _)| column.index() == query.column_index())
+                        .map(|(column, _)| Column::<Any>::from(*column))
+                        .take(1)
+                        .collect()
+                },
+                &|query| {
+                    self.cs
+                        .instance_queries()
+                        .iter()
+                        .filter(|(column, _)| column.index() == query.column_index())
+                        .map(|(column, _)| Column::<Any>::from(*column))
+                        .take(1)
+                        .collect()
+                },
+                &|_| vec![],
+                &|a| a,
+                &|mut a, b| {
+                    a.extend(b);
+                    a
+                },
+                &|mut a, b| {
+                    a.extend(b);
+                    a
+                },
+                &|a, _| a,
+            ) {
+                if !columns.contains(&column) {
+                    columns.push(column);
+                }
+            }
+        }
+        columns
+    }
+
+    fn cell(&self, kind: ColumnKind, column: usize, rotation: Rotation, row: usize) -> Cell<F> {
+        let n = 1i64 << self.k;
+        let row = (row as i64 + rotation.0 as i64).rem_euclid(n) as usize;
+        let values = match kind {
+            ColumnKind::Advice => &self.advice,
+            ColumnKind::Fixed => &self.fixed,
+            ColumnKind::Instance => &self.instance,
+        };
+        Cell {
+            kind,
+            column,
+            rotation,
+            row,
+            value: values[column][row],
+        }
+    }
+
+    fn value(&self, column: &Column<Any>, row: usize) -> F {
+        let values = match column.column_type() {
+            Any::Advice(_) => &self.advice,
+            Any::Fixed => &self.fixed,
+            Any::Instance => &self.instance,
+        };
+        values[column.index()][row]
+    }
+
+    fn evaluate(&self, polynomial: &Expression<F>, row: usize) -> F {
+        polynomial.evaluate(
+            &|constant| constant,
+            &|selector| {
+                if self.selectors[selector.index()][row] {
+                    F::one()
+                } else {
+                    F::zero()
+                }
+            },
+            &|query| {
+                self.cell(
+                    ColumnKind::Fixed,
+                    query.column_index(),
+                    query.rotation(),
+                    row,
+                )
+                .value
+            },
+            &|query| {
+                self.cell(
+                    ColumnKind::Advice,
+                    query.column_index(),
+                    query.rotation(),
+                    row,
+                )
+                .value
+            },
+            &|query| {
+                self.cell(
+                    ColumnKind::Instance,
+                    query.column_index(),
+                    query.rotation(),
+                    row,
+                )
+                .value
+            },
+            &|_| unreachable!("challenges are rejected in Witness::new()"),
+            &|a| -a,
+            &|a, b| a + b,
+            &|a, b| a * b,
+            &|a, scalar| a * scalar,
+        )
+    }
+
+    fn cells(&self, polynomial: &Expression<F>, row: usize) -> Vec<Cell<F>> {
+        let mut cells = polynomial.evaluate(
+            &|_| vec![],
+            &|_| vec![],
+            &|query| {
+                vec![self.cell(
+                    ColumnKind::Fixed,
+                    query.column_index(),
+                    query.rotation(),
+                    row,
+                )]
+            },
+            &|query| {
+                vec![self.cell(
+                    ColumnKind::Advice,
+                    query.column_index(),
+                    query.rotation(),
+                    row,
+                )]
+            },
+            &|query| {
+                vec![self.cell(
+                    ColumnKind::Instance,
+                    query.column_index(),
+                    query.rotation(),
+                    row,
+                )]
+            },
+            &|_| vec![],
+            &|a| a,
+            &|mut a, b| {
+                a.extend(b);
+                a
+            },
+            &|mut a, b| {
+                a.extend(b);
+                a
+            },
+            &|a, _| a,
+        );
+        cells.dedup();
+        cells
+    }
+}
+
+/// Evaluates every polynomial of the gate at `gate_index` over the usable
+/// rows of `witness` and reports the rows in which one is nonzero. The first
+/// few violations also carry the cells the polynomial read.
+pub fn evaluate_gate<F: Field>(
+    witness: &Witness<F>,
+    gate_index: usize,
+) -> Result<GateReport<F>, DebugError> {
+    let gates = witness.cs.gates();
+    let gate = gates.get(gate_index).ok_or(DebugError::NoSuchGate {
+        index: gate_index,
+        num_gates: gates.len(),
+    })?;
+
+    let mut violations = vec![];
+    for row in 0..witness.usable_rows() {
+        for (constraint, polynomial) in gate.polynomials().iter().enumerate() {
+            let value = witness.evaluate(polynomial, row);
+            if value == F::zero() {
+                continue;
+            }
+            let cells = if violations.len() < MAX_DETAILED_VIOLATIONS {
+                witness.cells(polynomial, row)
+            } else {
+                vec![]
+            };
+            violations.push(Violation {
+                row,
+                constraint,
+                value,
+                cells,
+            });
+        }
+    }
+    Ok(GateReport {
+        gate: gate.name().to_string(),
+        violations,
+    })
+}
+
+fn lookup_key<F: PrimeField>(values: &[F]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_repr().as_ref().to_vec())
+        .collect()
+}
+
+/// Evaluates the input expressions of every lookup over the usable rows of
+/// `witness` and reports the rows whose values aren't a row of the table. The
+/// table expressions are evaluated over the same witness, so tables in fixed
+/// and in advice columns are checked alike.
+pub fn check_lookups<F: PrimeField>(witness: &Witness<F>) -> Vec<LookupViolation<F>> {
+    let usable_rows = witness.usable_rows();
+    let evaluate = |expressions: &[Expression<F>], row: usize| {
+        expressions
+            .iter()
+            .map(|expression| witness.evaluate(expression, row))
+            .collect::<Vec<_>>()
+    };
+
+    let mut violations = vec![];
+    for (lookup_index, lookup) in witness.cs.lookups().iter().enumerate() {
+        let input_columns = witness
+            .queried_columns(lookup.input_expressions())
+            .iter()
+            .map(|column| witness.describe_column(column))
+            .collect::<Vec<_>>();
+        let table = (0..usable_rows)
+            .map(|row| lookup_key(&evaluate(lookup.table_expressions(), row)))
+            .collect::<HashSet<_>>();
+        for row in 0..usable_rows {
+            let input_values = evaluate(lookup.input_expressions(), row);
+            if !table.contains(&lookup_key(&input_values)) {
+                violations.push(LookupViolation {
+                    lookup_index,
+                    row,
+                    input_values,
+                    input_columns: input_columns.clone(),
+                });
+            }
+        }
+    }
+    violations
+}
+
+fn find(parents: &mut [usize], mut cell: usize) -> usize {
+    while parents[cell] != cell {
+        parents[cell] = parents[parents[cell]];
+        cell = parents[cell];
+    }
+    cell
+}
+
+/// Joins the copies `witness` collected into the cycles of the permutation
+/// argument and reports every cell whose value differs from the one of the
+/// first cell of its cycle. Cycles and their cells are in the order the
+/// circuit first copies them in.
+pub fn check_copies<F: Field>(witness: &Witness<F>) -> Vec<CopyViolation<F>> {
+    let mut cells: Vec<(Column<Any>, usize)> = vec![];
+    let mut indices = HashMap::new();
+    let mut parents: Vec<usize> = vec![];
+    for (left, right) in &witness.copies {
+        let [left, right] = [left, right].map(|cell| {
+            *indices.entry(*cell).or_insert_with(|| {
+                cells.push(*cell);
+                parents.push(parents.len());
+                parents.len() - 1
+            })
+        });
+        let (left, right) = (find(&mut parents, left), find(&mut parents, right));
+        // The root of a cycle is always its first cell.
+        if left < right {
+            parents[right] = left;
+        } else {
+            parents[left] = right;
+        }
+    }
+
+    let mut violations = vec![];
+    for index in 0..cells.len() {
+        let root = find(&mut parents, index);
+        if root == index {
+            continue;
+        }
+        let (left, right) = (cells[root], cells[index]);
+        let left_value = witness.value(&left.0, left.1);
+        let right_value = witness.value(&right.0, right.1);
+        if left_value != right_value {
+            violations.push(CopyViolation {
+                left,
+                right,
+                left_value,
+                right_value,
+            });
+        }
+    }
+    violations
+}
+
+/// Synthesizes `circuit` and checks every gate, lookup and copy over it, so
+/// that a proof isn't created for a witness it can't verify with. See
+/// [`crate::prover::ProofOptions::verify_constraints_first`].
+pub fn verify_constraints<F: PrimeField, ConcreteCircuit: Circuit<F>>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+    instances: &[&[F]],
+) -> Result<(), DebugError> {
+    let witness = Witness::new(k, circuit, instances)?;
+    let mut gates = vec![];
+    for gate_index in 0..witness.cs.gates().len() {
+        let report = evaluate_gate(&witness, gate_index)?;
+        gates.extend(
+            report
+                .rows()
+                .into_iter()
+                .map(|row| (report.gate.clone(), row)),
+        );
+    }
+    let lookups = check_lookups(&witness)
+        .into_iter()
+        .map(|violation| (violation.lookup_index, violation.row))
+        .collect::<Vec<_>>();
+    let copies = check_copies(&witness)
+        .into_iter()
+        .map(|violation| {
+            (
+                (witness.describe_column(&violation.left.0), violation.left.1),
+                (
+                    witness.describe_column(&violation.right.0),
+                    violation.right.1,
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+    if gates.is_empty() && lookups.is_empty() && copies.is_empty() {
+        Ok(())
+    } else {
+        Err(DebugError::Unsatisfied {
+            gates,
+            lookups,
+            copies,
+        })
+    }
+}
+
+/// How a column two witnesses both have differs between them. Rows past the
+/// values of a column are zero, as they are when the witness is replayed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnDiff {
+    pub circuit: usize,
+    pub column: usize,
+    /// The first row in which the columns differ, if they do.
+    pub first_divergent_row: Option<usize>,
+    /// The number of rows in which the columns differ.
+    pub differing_cells: usize,
+    /// The SHA-256 of the values of the column in each witness.
+    pub hashes: ([u8; 32], [u8; 32]),
+}
+
+/// How two witnesses differ, see [`diff_witness()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessDiff {
+    /// The number of rows of each witness, if they differ.
+    pub n_mismatch: Option<(usize, usize)>,
+    /// The columns both witnesses have, ordered by circuit and column.
+    pub columns: Vec<ColumnDiff>,
+    /// The columns, as `(circuit, column)`, that only the first witness has.
+    pub only_in_a: Vec<(usize, usize)>,
+    /// The columns, as `(circuit, column)`, that only the second witness has.
+    pub only_in_b: Vec<(usize, usize)>,
+}
+
+impl WitnessDiff {
+    /// Whether the witnesses are equal.
+    pub fn is_empty(&self) -> bool {
+        self.n_mismatch.is_none()
+            && self.only_in_a.is_empty()
+            && self.only_in_b.is_empty()
+            && self.columns.iter().all(|diff| diff.differing_cells == 0)
+    }
+}
+
+impl fmt::Display for WitnessDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(
+                f,
+                "the witnesses are equal ({} columns)",
+                self.columns.len()
+            );
+        }
+        if let Some((a, b)) = self.n_mismatch {
+            writeln!(f, "n differs: {} and {}", a, b)?;
+        }
+        for (columns, witness) in [(&self.only_in_a, "first"), (&self.only_in_b, "second")] {
+            for (circuit, column) in columns {
+                writeln!(
+                    f,
+                    "advice column {} of circuit {}: only in the {} witness",
+                    column, circuit, witness
+                )?;
+            }
+        }
+        for diff in &self.columns {
+            if let Some(row) = diff.first_divergent_row {
+                writeln!(
+                    f,
+                    "advice column {} of circuit {}: {} cells differ, the first in row {} \
+                     (sha256 {} and {})",
+                    diff.column,
+                    diff.circuit,
+                    diff.differing_cells,
+                    row,
+                    to_hex(&diff.hashes.0[..4]),
+                    to_hex(&diff.hashes.1[..4])
+                )?;
+            }
+        }
+        let equal = self
+            .columns
+            .iter()
+            .filter(|diff| diff.differing_cells == 0)
+            .count();
+        write!(
+            f,
+            "{} of {} common columns are equal",
+            equal,
+            self.columns.len()
+        )
+    }
+}
+
+fn column_hash(values: &[Fr]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for value in values {
+        hasher.update(fr_to_le_bytes(value));
+    }
+    hasher.finalize().into()
+}
+
+fn diff_column(a: &WitnessColumn, b: &WitnessColumn) -> ColumnDiff {
+    let zero = Fr::zero();
+    let mut first_divergent_row = None;
+    let mut differing_cells = 0;
+    for row in 0..a.values.len().max(b.values.len()) {
+        if a.values.get(row).unwrap_or(&zero) != b.values.get(row).unwrap_or(&zero) {
+            first_divergent_row.get_or_insert(row);
+            differing_cells += 1;
+        }
+    }
+    ColumnDiff {
+        circuit: a.circuit,
+        column: a.column,
+        first_divergent_row,
+        differing_cells,
+        hashes: (column_hash(&a.values), column_hash(&b.values)),
+    }
+}
+
+/// Compares two exported witnesses column by column, e.g, of a proof that
+/// regressed between two versions of the crate, to tell a changed witness
+/// from a change in how the backend processes it. Witnesses of another shape
+/// are compared in the columns they share.
+pub fn diff_witness(a: &ExportedWitness, b: &ExportedWitness) -> WitnessDiff {
+    fn by_column(witness: &ExportedWitness) -> BTreeMap<(usize, usize), &WitnessColumn> {
+        witness
+            .columns
+            .iter()
+            .map(|column| ((column.circuit, column.column), column))
+            .collect()
+    }
+    fn only_in<V, W>(
+        x: &BTreeMap<(usize, usize), V>,
+        y: &BTreeMap<(usize, usize), W>,
+    ) -> Vec<(usize, usize)> {
+        x.keys()
+            .filter(|key| !y.contains_key(*key))
+            .copied()
+            .collect()
+    }
+
+    let (a_columns, b_columns) = (by_column(a), by_column(b));
+    WitnessDiff {
+        n_mismatch: (a.n != b.n).then(|| (a.n, b.n)),
+        columns: a_columns
+            .iter()
+            .filter_map(|(key, a)| b_columns.get(key).map(|b| diff_column(a, b)))
+            .collect(),
+        only_in_a: only_in(&a_columns, &b_columns),
+        only_in_b: only_in(&b_columns, &a_columns),
+    }
+}
+
+impl<F: Field> Assignment<F> for Witness<F> {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.check_row(row)?;
+        self.selectors[selector.index()][row] = true;
+        Ok(())
+    }
+
+    fn annotate_column<A, AR>(&mut self, annotation: A, column: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.annotations.insert(column, annotation().into());
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        self.check_row(row)?;
+        self.instance
+            .get(column.index())
+            .map(|column| Value::known(column[row]))
+            .ok_or(Error::BoundsFailure)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.check_cell(column.into(), row)?;
+        let value = to().into_field().evaluate().assign()?;
+        *self
+            .advice
+            .get_mut(column.index())
+            .and_then(|column| column.get_mut(row))
+            .ok_or(Error::BoundsFailure)? = value;
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.check_cell(column.into(), row)?;
+        let value = to().into_field().evaluate().assign()?;
+        *self
+            .fixed
+            .get_mut(column.index())
+            .and_then(|column| column.get_mut(row))
+            .ok_or(Error::BoundsFailure)? = value;
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.check_row(left_row)?;
+        self.check_row(right_row)?;
+        self.copies
+            .push(((left_column, left_row), (right_column, right_row)));
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        from_row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.check_row(from_row)?;
+        let value = to.evaluate().assign()?;
+        let usable_rows = self.usable_rows();
+        let column = self
+            .fixed
+            .get_mut(column.index())
+            .ok_or(Error::BoundsFailure)?;
+        for cell in &mut column[from_row..usable_rows] {
+            *cell = value;
+        }
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Selector, TableColumn},
+        poly::Rotation,
+    };
+    use halo2curves::bn256::Fr;
+
+    use super::{
+        check_copies, check_lookups, diff_witness, evaluate_gate, verify_constraints, ColumnKind,
+        CopyViolation, DebugError, LookupViolation, Witness,
+    };
+    use crate::repro::{ExportedWitness, WitnessColumn};
+
+    /// Constrains `a * b = c` in the first `ROWS` rows, but assigns a wrong
+    /// `c` in `BROKEN_ROW`.
+    #[derive(Clone, Default)]
+    struct BrokenCircuit;
+
+    const ROWS: usize = 5;
+    const BROKEN_ROW: usize = 3;
+
+    impl Circuit<Fr> for BrokenCircuit {
+        type Config = ([Column<Advice>; 3], Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let columns = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let q = meta.selector();
+            meta.create_gate("mul", |meta| {
+                let q = meta.query_selector(q);
+                let [a, b, c] = columns.map(|column| meta.query_advice(column, Rotation::cur()));
+                vec![q * (a * b - c)]
+            });
+            (columns, q)
+        }
+
+        fn synthesize(
+            &self,
+            ([a, b, c], q): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    for row in 0..ROWS {
+                        let (x, y) = (Fr::from(row as u64), Fr::from(2));
+                        let z = if row == BROKEN_ROW {
+                            x * y + Fr::one()
+                        } else {
+                            x * y
+                        };
+                        q.enable(&mut region, row)?;
+                        region.assign_advice(|| "a", a, row, || Value::known(x))?;
+                        region.assign_advice(|| "b", b, row, || Value::known(y))?;
+                        region.assign_advice(|| "c", c, row, || Value::known(z))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// Looks `a` up in a table of `0..TABLE_SIZE`, but assigns a value out of
+    /// the range in `OUT_OF_RANGE_ROW`.
+    #[derive(Clone, Default)]
+    struct RangeCircuit;
+
+    const TABLE_SIZE: u64 = 8;
+    const VALUES: [u64; 4] = [1, 7, 9, 3];
+    const OUT_OF_RANGE_ROW: usize = 2;
+
+    impl Circuit<Fr> for RangeCircuit {
+        type Config = (Column<Advice>, Selector, TableColumn);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let q = meta.complex_selector();
+            let table = meta.lookup_table_column();
+            meta.lookup("range", |meta| {
+                let q = meta.query_selector(q);
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![(q * a, table)]
+            });
+            (a, q, table)
+        }
+
+        fn synthesize(
+            &self,
+            (a, q, table): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "range",
+                |mut table_layouter| {
+                    for value in 0..TABLE_SIZE {
+                        table_layouter.assign_cell(
+                            || "value",
+                            table,
+                            value as usize,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "values",
+                |mut region| {
+                    for (row, value) in VALUES.iter().enumerate() {
+                        q.enable(&mut region, row)?;
+                        region.assign_advice(|| "a", a, row, || Value::known(Fr::from(*value)))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_check_lookups() {
+        let witness = Witness::new(4, &RangeCircuit, &[]).unwrap();
+        assert_eq!(
+            check_lookups(&witness),
+            vec![LookupViolation {
+                lookup_index: 0,
+                row: OUT_OF_RANGE_ROW,
+                input_values: vec![Fr::from(VALUES[OUT_OF_RANGE_ROW])],
+                input_columns: vec!["advice 0".to_string()],
+            }]
+        );
+
+        match verify_constraints(4, &RangeCircuit, &[]) {
+            Err(DebugError::Unsatisfied { gates, lookups, .. }) => {
+                assert!(gates.is_empty());
+                assert_eq!(lookups, vec![(0, OUT_OF_RANGE_ROW)]);
+            }
+            _ => panic!("expected Unsatisfied"),
+        }
+        match verify_constraints(4, &BrokenCircuit, &[]) {
+            Err(DebugError::Unsatisfied { gates, lookups, .. }) => {
+                assert_eq!(gates, vec![("mul".to_string(), BROKEN_ROW)]);
+                assert!(lookups.is_empty());
+            }
+            _ => panic!("expected Unsatisfied"),
+        }
+    }
+
+    /// Names its only advice column, but assigns it in the last row, which
+    /// is reserved for blinding.
+    #[derive(Clone, Default)]
+    struct OverflowCircuit;
+
+    impl Circuit<Fr> for OverflowCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            meta.advice_column()
+        }
+
+        fn synthesize(
+            &self,
+            a: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "overflow",
+                |mut region| {
+                    region.name_column(|| "byte_lookup_input", a);
+                    region.assign_advice(|| "a", a, (1 << 4) - 1, || Value::known(Fr::one()))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_row_overflow() {
+        match Witness::new(4, &OverflowCircuit, &[]) {
+            Err(DebugError::RowOverflow(overflow)) => {
+                assert_eq!(overflow.column, "column 'byte_lookup_input' (advice 0)");
+                assert_eq!(overflow.row, 15);
+                assert!(overflow
+                    .to_string()
+                    .starts_with("column 'byte_lookup_input' (advice 0) is assigned in row 15"));
+            }
+            _ => panic!("expected RowOverflow"),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_gate() {
+        let witness = Witness::new(4, &BrokenCircuit, &[]).unwrap();
+        let report = evaluate_gate(&witness, 0).unwrap();
+        assert_eq!(report.gate, "mul");
+        assert_eq!(report.rows(), vec![BROKEN_ROW]);
+
+        let violation = &report.violations[0];
+        assert_eq!(violation.constraint, 0);
+        assert_eq!(violation.value, -Fr::one());
+        assert_eq!(
+            violation
+                .cells
+                .iter()
+                .map(|cell| (cell.kind, cell.column, cell.row, cell.value))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    ColumnKind::Advice,
+                    0,
+                    BROKEN_ROW,
+                    Fr::from(BROKEN_ROW as u64)
+                ),
+                (ColumnKind::Advice, 1, BROKEN_ROW, Fr::from(2)),
+                (
+                    ColumnKind::Advice,
+                    2,
+                    BROKEN_ROW,
+                    Fr::from(2 * BROKEN_ROW as u64 + 1)
+                ),
+            ]
+        );
+        let cell = &violation.cells[1];
+        assert_eq!(cell.advice_column().map(|column| column.index()), Some(1));
+        assert_eq!(cell.fixed_column(), None);
+        assert_eq!(cell.instance_column(), None);
+
+        assert!(matches!(
+            evaluate_gate(&witness, 1),
+            Err(DebugError::NoSuchGate {
+                index: 1,
+                num_gates: 1
+            })
+        ));
+    }
+
+    /// Copies `a` into `b` and `c` in the next rows, but assigns the copy in
+    /// `b` a different value.
+    #[derive(Clone, Default)]
+    struct CopyCircuit;
+
+    impl Circuit<Fr> for CopyCircuit {
+        type Config = [Column<Advice>; 3];
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let columns = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for column in columns {
+                meta.enable_equality(column);
+            }
+            columns
+        }
+
+        fn synthesize(
+            &self,
+            [a, b, c]: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "copies",
+                |mut region| {
+                    let x = region.assign_advice(|| "a", a, 0, || Value::known(Fr::from(5)))?;
+                    let y = region.assign_advice(|| "b", b, 1, || Value::known(Fr::from(6)))?;
+                    let z = region.assign_advice(|| "c", c, 2, || Value::known(Fr::from(5)))?;
+                    region.constrain_equal(x.cell(), y.cell())?;
+                    region.constrain_equal(y.cell(), z.cell())?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_check_copies() {
+        let witness = Witness::new(4, &CopyCircuit, &[]).unwrap();
+        let [a, b, _] = CopyCircuit::configure(&mut ConstraintSystem::default());
+        assert_eq!(
+            check_copies(&witness),
+            vec![CopyViolation {
+                left: (Column::<Any>::from(a), 0),
+                right: (Column::<Any>::from(b), 1),
+                left_value: Fr::from(5),
+                right_value: Fr::from(6),
+            }]
+        );
+
+        match verify_constraints(4, &CopyCircuit, &[]) {
+            Err(DebugError::Unsatisfied {
+                gates,
+                lookups,
+                copies,
+            }) => {
+                assert!(gates.is_empty());
+                assert!(lookups.is_empty());
+                assert_eq!(
+                    copies,
+                    vec![(("advice 0".to_string(), 0), ("advice 1".to_string(), 1))]
+                );
+            }
+            _ => panic!("expected Unsatisfied"),
+        }
+        assert!(check_copies(&Witness::new(4, &BrokenCircuit, &[]).unwrap()).is_empty());
+    }
+
+    fn exported_witness(n: usize, columns: &[(usize, usize, &[u64])]) -> ExportedWitness {
+        ExportedWitness {
+            n,
+            columns: columns
+                .iter()
+                .map(|(circuit, column, values)| WitnessColumn {
+                    circuit: *circuit,
+                    column: *column,
+                    values: values.iter().map(|value| Fr::from(*value)).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_identical_witnesses() {
+        let a = exported_witness(16, &[(0, 0, &[1, 2, 3]), (0, 1, &[4, 5])]);
+        let diff = diff_witness(&a, &a.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff.columns.len(), 2);
+        assert_eq!(diff.to_string(), "the witnesses are equal (2 columns)");
+        assert_eq!(a.hash(), a.clone().hash());
+    }
+
+    #[test]
+    fn test_diff_single_cell() {
+        let a = exported_witness(16, &[(0, 0, &[1, 2, 3]), (0, 1, &[4, 5])]);
+        let b = exported_witness(16, &[(0, 0, &[1, 2, 3]), (0, 1, &[4, 6])]);
+        let diff = diff_witness(&a, &b);
+        assert!(!diff.is_empty());
+        assert_ne!(a.hash(), b.hash());
+        assert_eq!(diff.columns[0].first_divergent_row, None);
+        assert_eq!(diff.columns[0].hashes.0, diff.columns[0].hashes.1);
+        assert_eq!(
+            (
+                diff.columns[1].column,
+                diff.columns[1].first_divergent_row,
+                diff.columns[1].differing_cells
+            ),
+            (1, Some(1), 1)
+        );
+        assert_ne!(diff.columns[1].hashes.0, diff.columns[1].hashes.1);
+        let summary = diff.to_string();
+        assert!(
+            summary.contains("advice column 1 of circuit 0: 1 cells differ, the first in row 1"),
+            "{}",
+            summary
+        );
+        assert!(
+            summary.ends_with("1 of 2 common columns are equal"),
+            "{}",
+            summary
+        );
+
+        // Trailing zeros are the rows that aren't given, so they don't differ.
+        let c = exported_witness(16, &[(0, 0, &[1, 2, 3, 0]), (0, 1, &[4, 5])]);
+        assert!(diff_witness(&a, &c).is_empty());
+    }
+
+    #[test]
+    fn test_diff_different_shapes() {
+        let a = exported_witness(16, &[(0, 0, &[1]), (0, 1, &[2]), (1, 0, &[3])]);
+        let b = exported_witness(32, &[(0, 0, &[1]), (1, 0, &[3]), (1, 1, &[4])]);
+        let diff = diff_witness(&a, &b);
+        assert_eq!(diff.n_mismatch, Some((16, 32)));
+        assert_eq!(diff.only_in_a, vec![(0, 1)]);
+        assert_eq!(diff.only_in_b, vec![(1, 1)]);
+        assert_eq!(
+            diff.columns
+                .iter()
+                .map(|diff| (diff.circuit, diff.column, diff.differing_cells))
+                .collect::<Vec<_>>(),
+            vec![(0, 0, 0), (1, 0, 0)]
+        );
+        let summary = diff.to_string();
+        assert!(summary.starts_with("n differs: 16 and 32\n"), "{}", summary);
+        assert!(
+            summary.contains("advice column 1 of circuit 0: only in the first witness"),
+            "{}",
+            summary
+        );
+        assert!(
+            summary.contains("advice column 1 of circuit 1: only in the second witness"),
+            "{}",
+            summary
+        );
+    }
+}
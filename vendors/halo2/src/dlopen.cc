@@ -0,0 +1,58 @@
+#include "vendors/halo2/include/dlopen.h"
+
+#include <atomic>
+#include <iterator>
+#include <string>
+
+#include "tachyon/base/logging.h"
+#include "vendors/halo2/src/dlopen.rs.h"
+
+namespace tachyon::halo2_api::dlopen {
+
+namespace {
+
+#define TACHYON_HALO2_SYMBOL_NAME(name) #name,
+const char* const kRequiredSymbolNames[] = {
+    TACHYON_HALO2_REQUIRED_SYMBOLS(TACHYON_HALO2_SYMBOL_NAME)};
+const char* const kSymbolNames[] = {
+    TACHYON_HALO2_SYMBOLS(TACHYON_HALO2_SYMBOL_NAME)};
+#undef TACHYON_HALO2_SYMBOL_NAME
+
+static_assert(std::size(kSymbolNames) == kNumSymbols);
+
+const void* g_symbols[kNumSymbols];
+std::atomic<bool> g_loaded{false};
+
+}  // namespace
+
+const void* GetSymbol(Symbol symbol) {
+  if (!g_loaded.load(std::memory_order_acquire)) {
+    rust::String error = load_from_env();
+    CHECK(error.empty()) << std::string(error);
+  }
+  const void* address = g_symbols[symbol];
+  CHECK(address) << "libtachyon has no " << kSymbolNames[symbol]
+                 << ", it was built without GPU support";
+  return address;
+}
+
+rust::Vec<rust::String> symbol_names() {
+  rust::Vec<rust::String> ret;
+  ret.reserve(kNumSymbols);
+  for (const char* name : kSymbolNames) {
+    ret.push_back(rust::String(name));
+  }
+  return ret;
+}
+
+size_t num_required_symbols() { return std::size(kRequiredSymbolNames); }
+
+void set_symbols(rust::Slice<const size_t> addresses) {
+  CHECK_EQ(addresses.length(), size_t{kNumSymbols});
+  for (size_t i = 0; i < kNumSymbols; ++i) {
+    g_symbols[i] = reinterpret_cast<const void*>(addresses[i]);
+  }
+  g_loaded.store(true, std::memory_order_release);
+}
+
+}  // namespace tachyon::halo2_api::dlopen
@@ -0,0 +1,202 @@
+// With the "dlopen" feature, the crate isn't linked against libtachyon. The C++
+// wrappers call it through a table of addresses, see `include/dlopen.h`, which
+// `load()` fills after opening the library at runtime. The library is loaded
+// once per process, from `InitOptions::library_path` if `init_with()` is called
+// first and from `TACHYON_LIB_PATH` otherwise, and is never unloaded. Its ABI
+// version is checked before anything else of it is called.
+
+use std::{
+    env,
+    ffi::c_void,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use libloading::Library;
+
+use crate::{error::TachyonError, version::check_abi_version};
+
+#[cxx::bridge(namespace = "tachyon::halo2_api::dlopen")]
+mod ffi {
+    extern "Rust" {
+        fn load_from_env() -> String;
+    }
+
+    unsafe extern "C++" {
+        include!("vendors/halo2/include/dlopen.h");
+
+        fn symbol_names() -> Vec<String>;
+        fn num_required_symbols() -> usize;
+        fn set_symbols(addresses: &[usize]);
+    }
+}
+
+/// The environment variable libtachyon is loaded from, unless
+/// `InitOptions::library_path` says otherwise.
+pub const LIBRARY_PATH_ENV: &str = "TACHYON_LIB_PATH";
+
+const ABI_VERSION_SYMBOL: &str = "tachyon_get_abi_version";
+
+/// The path libtachyon was loaded from, once it is.
+fn loaded() -> &'static Mutex<Option<PathBuf>> {
+    static LOADED: Mutex<Option<PathBuf>> = Mutex::new(None);
+    &LOADED
+}
+
+fn unavailable(reason: String) -> TachyonError {
+    TachyonError::BackendUnavailable { reason }
+}
+
+fn open(path: &Path) -> Result<Library, TachyonError> {
+    // Loading runs the initializers of libtachyon, which don't depend on
+    // anything of this process.
+    unsafe { Library::new(path) }
+        .map_err(|e| unavailable(format!("failed to load {}: {}", path.display(), e)))
+}
+
+/// Checks the ABI version of the library `resolve` looks symbols up in, then
+/// returns the address of every symbol of `names`. The first `num_required`
+/// of them must be there; the others are 0 when they aren't.
+fn resolve_symbols(
+    names: &[String],
+    num_required: usize,
+    resolve: impl Fn(&str) -> Option<usize>,
+) -> Result<Vec<usize>, TachyonError> {
+    let abi_version = resolve(ABI_VERSION_SYMBOL)
+        .ok_or_else(|| unavailable(format!("libtachyon has no {}", ABI_VERSION_SYMBOL)))?;
+    // Every libtachyon has exported `tachyon_get_abi_version()` with this
+    // signature, so it is safe to call before the version is known.
+    let abi_version: extern "C" fn() -> u32 = unsafe { std::mem::transmute(abi_version) };
+    check_abi_version(abi_version())?;
+    names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| match resolve(name) {
+            Some(address) => Ok(address),
+            None if index >= num_required => Ok(0),
+            None => Err(unavailable(format!("libtachyon has no {}", name))),
+        })
+        .collect()
+}
+
+/// Loads libtachyon from `path`, or from `TACHYON_LIB_PATH` if it is `None`,
+/// unless it is loaded already.
+pub(crate) fn load(path: Option<&Path>) -> Result<(), TachyonError> {
+    let mut loaded = loaded().lock().unwrap_or_else(|e| e.into_inner());
+    if loaded.is_some() {
+        return Ok(());
+    }
+    let path = match path {
+        Some(path) => path.to_owned(),
+        None => env::var_os(LIBRARY_PATH_ENV)
+            .map(PathBuf::from)
+            .ok_or_else(|| {
+                unavailable(format!(
+                    "libtachyon isn't loaded, set {} or InitOptions::library_path",
+                    LIBRARY_PATH_ENV
+                ))
+            })?,
+    };
+    let library = open(&path)?;
+    let addresses = resolve_symbols(&ffi::symbol_names(), ffi::num_required_symbols(), |name| {
+        unsafe { library.get::<*const c_void>(name.as_bytes()) }
+            .ok()
+            .map(|symbol| *symbol as usize)
+    })?;
+    ffi::set_symbols(&addresses);
+    // The wrappers keep calling the addresses for as long as the process runs.
+    std::mem::forget(library);
+    *loaded = Some(path);
+    Ok(())
+}
+
+/// Called by the wrappers when they are called before libtachyon is loaded.
+/// Returns why loading failed, or an empty string.
+fn load_from_env() -> String {
+    match load(None) {
+        Ok(()) => String::new(),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{open, resolve_symbols, ABI_VERSION_SYMBOL};
+    use crate::{error::TachyonError, version::EXPECTED_ABI_VERSION};
+
+    extern "C" fn expected_abi_version() -> u32 {
+        EXPECTED_ABI_VERSION
+    }
+
+    extern "C" fn wrong_abi_version() -> u32 {
+        EXPECTED_ABI_VERSION + 1
+    }
+
+    fn names() -> Vec<String> {
+        vec!["tachyon_required".to_owned(), "tachyon_optional".to_owned()]
+    }
+
+    #[test]
+    fn test_missing_library() {
+        match open(Path::new("/nonexistent/libtachyon.so")) {
+            Err(TachyonError::BackendUnavailable { reason }) => {
+                assert!(reason.contains("/nonexistent/libtachyon.so"), "{}", reason)
+            }
+            _ => panic!("expected BackendUnavailable"),
+        }
+    }
+
+    #[test]
+    fn test_wrong_abi_version() {
+        let resolved = resolve_symbols(&names(), 1, |name| match name {
+            ABI_VERSION_SYMBOL => Some(wrong_abi_version as usize),
+            _ => panic!("{} was resolved before the ABI version was checked", name),
+        });
+        match resolved {
+            Err(TachyonError::AbiMismatch { expected, found }) => {
+                assert_eq!(expected, EXPECTED_ABI_VERSION);
+                assert_eq!(found, EXPECTED_ABI_VERSION + 1);
+            }
+            _ => panic!("expected AbiMismatch"),
+        }
+    }
+
+    #[test]
+    fn test_missing_symbols() {
+        let resolve = |name: &str| match name {
+            ABI_VERSION_SYMBOL => Some(expected_abi_version as usize),
+            "tachyon_required" => Some(1),
+            _ => None,
+        };
+        assert_eq!(resolve_symbols(&names(), 1, resolve).unwrap(), vec![1, 0]);
+        assert!(matches!(
+            resolve_symbols(&names(), 2, resolve),
+            Err(TachyonError::BackendUnavailable { .. })
+        ));
+    }
+
+    #[cfg(feature = "halo2")]
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_prove_after_loading() {
+        use std::{env, path::PathBuf};
+
+        use super::LIBRARY_PATH_ENV;
+        use crate::{
+            healthcheck::{dry_run, DryRunConfig},
+            version::{init_with, InitOptions},
+        };
+
+        let options = InitOptions {
+            library_path: env::var_os(LIBRARY_PATH_ENV).map(PathBuf::from),
+            ..InitOptions::default()
+        };
+        assert_eq!(
+            init_with(&options).unwrap().abi_version,
+            EXPECTED_ABI_VERSION
+        );
+        assert!(dry_run(&DryRunConfig::default()).unwrap().proof_len > 0);
+    }
+}
@@ -0,0 +1,403 @@
+// Holds this crate's proofs against the ones halo2's `create_proof()` writes
+// for the same proving key, witness and randomness, and verifies each with the
+// other's verifier.
+//
+// Both provers draw from one XORShift RNG, seeded with the same 16 bytes, see
+// `crate::rng::derive_seed()`. The proofs are byte-equal only because they
+// draw in the same order, which is:
+//
+// | step                          | halo2 (kroma fork)                    | here                                         |
+// |-------------------------------|---------------------------------------|----------------------------------------------|
+// | instance columns              | nothing                               | nothing                                      |
+// | advice blinding rows          | nothing, the last row is set to one   | the same, in `ProofSteps`                    |
+// | advice commitment blinds      | one per column, in column order, per  | the same, in `ProofSteps`, skipping          |
+// |                               | circuit, per phase                    | precommitted columns                         |
+// | challenges                    | squeezed from the transcript          | the same                                     |
+// | lookup permuted columns       | blinding rows of the input, then of   | the same, in the backend, which takes over   |
+// |                               | the table, then a blind for each, per | the RNG with `TachyonProver::set_rng()`      |
+// |                               | lookup, per circuit                   |                                              |
+// | permutation products          | blinding rows, then a blind, per set, | the same, in the backend                     |
+// |                               | per circuit                           |                                              |
+// | lookup products               | blinding rows, then a blind, per      | the same, in the backend                     |
+// |                               | lookup, per circuit                   |                                              |
+// | vanishing random polynomial   | nothing, it is one with a zero blind  | the same, in the backend                     |
+// | quotient pieces               | a blind per piece                     | the same, in the backend                     |
+// | multi-open argument           | nothing                               | nothing                                      |
+//
+// A change to either column of this table shows up here as a proof that
+// differs from some value on, and `StructuralDiff` names that value.
+//
+// halo2 is also the reference for parity with kroma-network's upstream vendor
+// layer, which its own tests hold byte-for-byte equal to halo2. That crate
+// can't be driven side by side with this one: it keeps its modules private,
+// and its cxx bridge defines the same symbols as ours, so the two can't be
+// linked into one binary. Every intentional difference from upstream, like
+// the pools and the lazily allocated rational evals, leaves the proof alone,
+// so a proof that differs from halo2's here is a divergence from upstream too.
+
+use std::fmt;
+
+use crate::{
+    proof::{parse, ParseError, PointEncoding, ProofValue},
+    transcript::{Component, TranscriptItem},
+};
+
+/// A value two proofs of the same layout disagree on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValueDiff {
+    pub item: TranscriptItem,
+    pub ours: ProofValue,
+    pub reference: ProofValue,
+}
+
+/// Every value two proofs of the same layout disagree on, in the order they
+/// were written. Since every challenge hashes what came before it, the first
+/// one is where the provers went apart.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StructuralDiff {
+    pub values: Vec<ValueDiff>,
+}
+
+impl StructuralDiff {
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn first(&self) -> Option<&ValueDiff> {
+        self.values.first()
+    }
+
+    /// The number of differing values of every component that has any, in
+    /// the order of [`Component::ALL`].
+    pub fn by_component(&self) -> Vec<(Component, usize)> {
+        Component::ALL
+            .iter()
+            .map(|component| {
+                (
+                    *component,
+                    self.values
+                        .iter()
+                        .filter(|diff| diff.item.component == *component)
+                        .count(),
+                )
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+}
+
+impl fmt::Display for StructuralDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} value(s) differ", self.values.len())?;
+        if let Some(first) = self.first() {
+            writeln!(
+                f,
+                "  first: {} of circuit {:?} at {}",
+                first.item.component.name(),
+                first.item.circuit,
+                first.item.index
+            )?;
+        }
+        for (component, count) in self.by_component() {
+            writeln!(f, "  {}: {}", component.name(), count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses `ours` and `reference` with `layout`, see
+/// [`crate::transcript::transcript_layout()`], and returns the values they
+/// disagree on.
+pub fn structural_diff(
+    ours: &[u8],
+    reference: &[u8],
+    layout: &[TranscriptItem],
+    encoding: PointEncoding,
+) -> Result<StructuralDiff, ParseError> {
+    let ours = parse(ours, layout, encoding)?;
+    let reference = parse(reference, layout, encoding)?;
+    Ok(StructuralDiff {
+        values: ours
+            .values()
+            .iter()
+            .zip(reference.values())
+            .filter(|((_, a), (_, b))| a != b)
+            .map(|((item, a), (_, b))| ValueDiff {
+                item: *item,
+                ours: *a,
+                reference: *b,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{create_proof, keygen_pk2, verify_proof, Circuit, ProvingKey},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        group::{prime::PrimeCurveAffine, GroupEncoding},
+    };
+    use rand_core::SeedableRng;
+
+    use super::{structural_diff, StructuralDiff, ValueDiff};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::{
+            shuffle_circuit::MyCircuit, simple_circuit::SimpleCircuit,
+            simple_lookup_circuit::SimpleLookupCircuit,
+        },
+        consts::{MultiOpenScheme, TranscriptType, SEED},
+        proof::{PointEncoding, ProofValue},
+        prover::{create_proof_with_options, ProofOptions},
+        rng::{derive_seed, proof_context},
+        serde_fr::{fr_from_le_bytes, fr_to_le_bytes, FR_BYTES},
+        transcript::{transcript_layout, Component, TranscriptItem},
+        xor_shift_rng::XORShiftRng,
+    };
+
+    const MASTER_KEY: [u8; 32] = [7; 32];
+
+    #[test]
+    fn test_structural_diff() {
+        let layout = [
+            TranscriptItem::new(Component::AdviceCommitment, Some(0), 0),
+            TranscriptItem::new(Component::AdviceEval, None, 0),
+            TranscriptItem::new(Component::AdviceEval, None, 1),
+        ];
+        let proof = |point: G1Affine, evals: [u64; 2]| {
+            let mut bytes = point.to_bytes().as_ref().to_vec();
+            for eval in evals {
+                bytes.extend_from_slice(&fr_to_le_bytes(&Fr::from(eval)));
+            }
+            bytes
+        };
+        let generator = G1Affine::generator();
+        let reference = proof(generator, [1, 2]);
+        let encoding = PointEncoding::Compressed;
+
+        let diff = structural_diff(&reference, &reference, &layout, encoding).unwrap();
+        assert!(diff.is_empty());
+
+        let ours = proof(generator, [1, 3]);
+        let diff = structural_diff(&ours, &reference, &layout, encoding).unwrap();
+        assert_eq!(
+            diff,
+            StructuralDiff {
+                values: vec![ValueDiff {
+                    item: layout[2],
+                    ours: ProofValue::Scalar(Fr::from(3)),
+                    reference: ProofValue::Scalar(Fr::from(2)),
+                }],
+            }
+        );
+
+        let ours = proof(G1Affine::identity(), [0, 3]);
+        let diff = structural_diff(&ours, &reference, &layout, encoding).unwrap();
+        assert_eq!(diff.first().unwrap().item, layout[0]);
+        assert_eq!(
+            diff.by_component(),
+            vec![(Component::AdviceCommitment, 1), (Component::AdviceEval, 2)]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "3 value(s) differ\n  first: advice_commitment of circuit Some(0) at 0\n  \
+             advice_commitment: 1\n  advice_eval: 2\n"
+        );
+
+        assert!(structural_diff(&ours[1..], &reference, &layout, encoding).is_err());
+    }
+
+    struct Proofs {
+        ours: Vec<u8>,
+        reference: Vec<u8>,
+        diff: StructuralDiff,
+    }
+
+    // Proves `circuit` with halo2 and with this crate from the same seed, and
+    // checks that each proof passes both verifiers.
+    fn prove_both<C: Circuit<Fr> + Clone>(
+        fixture: &str,
+        k: u32,
+        circuit: &C,
+        instances: &[&[Fr]],
+    ) -> Proofs {
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let rng_context = proof_context(fixture.as_bytes(), 0);
+
+        let reference = prove_reference(&params, &pk, circuit, instances, &rng_context);
+
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        prover.set_master_key(MASTER_KEY);
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let options = ProofOptions {
+            rng_context,
+            ..Default::default()
+        };
+        let mut transcript = TachyonBlake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit.clone()],
+            &[instances],
+            &options,
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let mut ours = transcript.finalize();
+        ours.extend_from_slice(&prover.get_proof());
+
+        for (name, proof) in [("ours", &ours), ("reference", &reference)] {
+            let mut transcript =
+                Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+            assert!(
+                verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+                    params.verifier_params(),
+                    pk.get_vk(),
+                    SingleStrategy::new(&params),
+                    &[instances],
+                    &mut transcript,
+                )
+                .is_ok(),
+                "{}: halo2 rejects {} proof",
+                fixture,
+                name
+            );
+            assert!(
+                prover.verify_proof(&mut tachyon_pk, &[instances], proof),
+                "{}: tachyon rejects {} proof",
+                fixture,
+                name
+            );
+        }
+
+        let layout = transcript_layout(pk.get_vk(), 1, MultiOpenScheme::SHPlonk);
+        let diff = structural_diff(
+            &ours,
+            &reference,
+            &layout,
+            PointEncoding::for_transcript(&TranscriptType::Blake2b),
+        )
+        .expect("both proofs should follow the layout");
+        Proofs {
+            ours,
+            reference,
+            diff,
+        }
+    }
+
+    fn prove_reference<C: Circuit<Fr> + Clone>(
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        circuit: &C,
+        instances: &[&[Fr]],
+        rng_context: &[u8],
+    ) -> Vec<u8> {
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+            params,
+            pk,
+            &[circuit.clone()],
+            &[instances],
+            XORShiftRng::from_seed(derive_seed(&MASTER_KEY, rng_context)),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        transcript.finalize()
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_equivalence() {
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let simple = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let proofs = prove_both("simple", 4, &simple, &[&public_inputs[..]]);
+        assert!(proofs.diff.is_empty(), "simple: {}", proofs.diff);
+        assert_eq!(proofs.ours, proofs.reference);
+
+        let simple_lookup = SimpleLookupCircuit::<Fr>::default();
+        let proofs = prove_both("simple_lookup", 5, &simple_lookup, &[]);
+        assert!(proofs.diff.is_empty(), "simple_lookup: {}", proofs.diff);
+
+        let shuffle = MyCircuit::<Fr, 2, 8>::rand(&mut XORShiftRng::from_seed(SEED));
+        let proofs = prove_both("shuffle", 4, &shuffle, &[]);
+        assert!(proofs.diff.is_empty(), "shuffle: {}", proofs.diff);
+
+        // A proof that differs from the reference in one evaluation passes
+        // neither verifier, and the diff names the evaluation.
+        let proofs = prove_both("simple", 4, &simple, &[&public_inputs[..]]);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+        let pk = keygen_pk2(&params, &simple).expect("vk should not fail");
+        let layout = transcript_layout(pk.get_vk(), 1, MultiOpenScheme::SHPlonk);
+        let encoding = PointEncoding::for_transcript(&TranscriptType::Blake2b);
+        let offset = layout
+            .iter()
+            .take_while(|item| item.component != Component::AdviceEval)
+            .map(|item| {
+                if item.component.is_point() {
+                    encoding.point_len()
+                } else {
+                    FR_BYTES
+                }
+            })
+            .sum::<usize>();
+        let mut tampered = proofs.ours.clone();
+        let bytes = &mut tampered[offset..offset + FR_BYTES];
+        let eval = fr_from_le_bytes((&*bytes).try_into().unwrap()).unwrap();
+        bytes.copy_from_slice(&fr_to_le_bytes(&(eval + Fr::one())));
+        let diff = structural_diff(&tampered, &proofs.reference, &layout, encoding).unwrap();
+        assert_eq!(diff.values.len(), 1);
+        assert_eq!(diff.first().unwrap().item.component, Component::AdviceEval);
+
+        let prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            4,
+            &Fr::from(2),
+        );
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        assert!(!prover.verify_proof(&mut tachyon_pk, &[&[&public_inputs[..]]], &tampered));
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(tampered.as_slice());
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[&public_inputs[..]]],
+            &mut transcript,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,300 @@
+use std::{fmt, io};
+
+#[cfg(feature = "halo2")]
+use halo2_proofs::plonk;
+
+use crate::consts::{AbsorbOrder, InstanceAbsorption, MultiOpenScheme, TranscriptType, VkHashMode};
+#[cfg(feature = "halo2")]
+use crate::{
+    annotation::RowOverflow, assignments::AssignmentError, debug::DebugError, limits::Limit,
+    phase::ChallengeError, state::StateError,
+};
+
+#[derive(Debug)]
+pub enum TachyonError {
+    /// halo2 failed while keying or proving.
+    #[cfg(feature = "halo2")]
+    Plonk(plonk::Error),
+    /// A proof created by the backend doesn't verify.
+    #[cfg(feature = "halo2")]
+    Verification(plonk::Error),
+    /// The requested backend isn't compiled in or doesn't work.
+    BackendUnavailable { reason: String },
+    /// A seed was to be derived, but the prover has no master key.
+    MissingMasterKey,
+    /// The linked libtachyon has a different ABI than this crate was written
+    /// against. See [`crate::version::init()`].
+    AbiMismatch { expected: u32, found: u32 },
+    /// The linked libtachyon lays out scalars or points differently than
+    /// halo2curves, so `which` of the checks of
+    /// [`crate::self_test::self_test()`] came out wrong.
+    LayoutMismatch { which: &'static str },
+    /// The circuit failed the check requested by
+    /// [`crate::prover::ProofOptions::verify_constraints_first`].
+    #[cfg(feature = "halo2")]
+    Preflight(DebugError),
+    /// The log requested by [`crate::prover::ProofOptions::audit_log`]
+    /// couldn't be written.
+    AuditLog(io::Error),
+    /// The file requested by [`crate::prover::ProofOptions::capture_repro`]
+    /// couldn't be written.
+    CaptureRepro(io::Error),
+    /// The metadata section in front of a proving key is malformed, see
+    /// [`crate::bn254::ProvingKey::try_from()`].
+    MalformedKey(io::Error),
+    /// The circuit assigns a cell past the usable rows.
+    #[cfg(feature = "halo2")]
+    RowOverflow(RowOverflow),
+    /// The proving key, or the instances prepared with
+    /// [`crate::prover::prepare_instances()`], are meant for another
+    /// multi-open scheme than the prover's, so the proof wouldn't pass the
+    /// verifier it is meant for. Verifiers refuse such keys too, with their
+    /// own scheme as `prover`.
+    MultiOpenMismatch {
+        key: MultiOpenScheme,
+        prover: MultiOpenScheme,
+    },
+    /// The transcript writer is of another type than the prover was created
+    /// with, so the proof would hash the two halves of the transcript
+    /// differently and never verify.
+    TranscriptMismatch {
+        prover: TranscriptType,
+        writer: TranscriptType,
+    },
+    /// The key records another instance absorption than the verifier was
+    /// asked to use, so the transcripts of the prover and the verifier would
+    /// differ.
+    InstanceAbsorptionMismatch {
+        key: InstanceAbsorption,
+        verifier: InstanceAbsorption,
+    },
+    /// The key records another advice absorb order than the prover or the
+    /// verifier was configured with, so the transcripts of the two would
+    /// diverge at the first advice commitment.
+    AdviceAbsorbOrderMismatch {
+        key: AbsorbOrder,
+        configured: AbsorbOrder,
+    },
+    /// The key records another vk hash mode than the verifier was asked to
+    /// use.
+    VkHashModeMismatch {
+        key: VkHashMode,
+        verifier: VkHashMode,
+    },
+    /// The proof doesn't verify with the digest of the verifying key the
+    /// verifier computed, but does with the one of `likely`, so the prover
+    /// hashed the key differently.
+    VkReprMismatch {
+        verifier: VkHashMode,
+        likely: VkHashMode,
+    },
+    /// The header of a proof records another `field` than the verifier
+    /// expects, so the proof was created for another circuit or setup. See
+    /// [`crate::proof::ProofHeader::check()`].
+    ProofHeaderMismatch {
+        field: &'static str,
+        expected: String,
+        found: String,
+    },
+    /// Keygen folded `selectors` into other fixed columns than the circuit
+    /// expects, so its gates would read the wrong columns. See
+    /// [`crate::selector_map::check_selector_mapping()`].
+    SelectorCompressionMismatch {
+        selectors: Vec<usize>,
+        expected_fixed_columns: usize,
+        found_fixed_columns: usize,
+    },
+    /// The commitment to an advice column the backend computed differs from
+    /// the one recomputed on the CPU, see
+    /// [`crate::prover::ProofOptions::self_check`].
+    SelfCheckFailed { column: usize },
+    /// The self-check was requested without Lagrange bases for the prover's
+    /// SRS, or with ones of another size.
+    SelfCheckBasesMismatch { expected: usize, found: usize },
+    /// The phases of the key don't squeeze every challenge exactly once.
+    #[cfg(feature = "halo2")]
+    Challenge(ChallengeError),
+    /// The advice assignments a proof was to be created from don't fit the
+    /// key or the prover's domain, see
+    /// [`crate::assignments::create_proof_from_assignments()`].
+    #[cfg(feature = "halo2")]
+    Assignment(AssignmentError),
+    /// A column of [`crate::prover::ProofOptions::precommitted_advice`]
+    /// can't be used for the proof.
+    InvalidPrecommitment { column: usize, reason: &'static str },
+    /// The commitment to a precommitted column isn't the one to its evals.
+    PrecommitmentMismatch { column: usize },
+    /// An RNG or transcript state handed to the backend doesn't have the
+    /// current layout.
+    #[cfg(feature = "halo2")]
+    State(StateError),
+    /// A polynomial converted from or to halo2's has another number of
+    /// values than the prover's domain.
+    LengthMismatch { expected: usize, found: usize },
+    /// The proof was stopped through [`crate::prover::ProofOptions::cancel`].
+    Cancelled,
+    /// The proof would use, or has used, more than one of
+    /// [`crate::prover::ProofOptions::limits`] allows.
+    #[cfg(feature = "halo2")]
+    LimitExceeded {
+        which: Limit,
+        limit: u64,
+        observed: u64,
+    },
+}
+
+impl fmt::Display for TachyonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "halo2")]
+            TachyonError::Plonk(e) => write!(f, "{}", e),
+            #[cfg(feature = "halo2")]
+            TachyonError::Verification(e) => write!(f, "proof doesn't verify: {}", e),
+            TachyonError::BackendUnavailable { reason } => {
+                write!(f, "backend unavailable: {}", reason)
+            }
+            TachyonError::MissingMasterKey => write!(f, "prover has no master key"),
+            TachyonError::AbiMismatch { expected, found } => write!(
+                f,
+                "libtachyon has ABI version {} but {} is expected, rebuild libtachyon from the \
+                 same revision as tachyon_halo2",
+                found, expected
+            ),
+            TachyonError::LayoutMismatch { which } => write!(
+                f,
+                "the {} self-test failed: libtachyon lays out values differently than \
+                 halo2curves, rebuild it against the halo2curves of tachyon_halo2",
+                which
+            ),
+            #[cfg(feature = "halo2")]
+            TachyonError::Preflight(e) => write!(f, "pre-flight check failed: {}", e),
+            TachyonError::AuditLog(e) => write!(f, "failed to write the audit log: {}", e),
+            TachyonError::CaptureRepro(e) => write!(f, "failed to write the repro file: {}", e),
+            TachyonError::MalformedKey(e) => write!(f, "malformed proving key: {}", e),
+            #[cfg(feature = "halo2")]
+            TachyonError::RowOverflow(overflow) => write!(f, "{}", overflow),
+            TachyonError::MultiOpenMismatch { key, prover } => write!(
+                f,
+                "the proving key is for {} but the prover uses {}",
+                key.name(),
+                prover.name()
+            ),
+            TachyonError::TranscriptMismatch { prover, writer } => write!(
+                f,
+                "the prover was created for a {} transcript but the writer is {}",
+                prover.name(),
+                writer.name()
+            ),
+            TachyonError::InstanceAbsorptionMismatch { key, verifier } => write!(
+                f,
+                "the key records {} instance absorption but the verifier uses {}",
+                key.name(),
+                verifier.name()
+            ),
+            TachyonError::AdviceAbsorbOrderMismatch { key, configured } => write!(
+                f,
+                "the key records {} advice absorb order but {} is configured, so the \
+                 transcripts would diverge at the first advice commitment",
+                key.name(),
+                configured.name()
+            ),
+            TachyonError::VkHashModeMismatch { key, verifier } => write!(
+                f,
+                "the key records {} vk hashing but the verifier uses {}",
+                key.name(),
+                verifier.name()
+            ),
+            TachyonError::VkReprMismatch { verifier, likely } => write!(
+                f,
+                "the proof absorbed another verifying key digest than the {} one the verifier \
+                 computed; it verifies with {}, so the prover likely hashed the key with that",
+                verifier.name(),
+                likely.name()
+            ),
+            TachyonError::ProofHeaderMismatch {
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "the proof header records {} as its {} but the verifier expects {}",
+                found, field, expected
+            ),
+            TachyonError::SelectorCompressionMismatch {
+                selectors,
+                expected_fixed_columns,
+                found_fixed_columns,
+            } => write!(
+                f,
+                "selectors {:?} aren't folded into the fixed columns the circuit expects: it \
+                 expects {} fixed columns and the key has {}",
+                selectors, expected_fixed_columns, found_fixed_columns
+            ),
+            TachyonError::SelfCheckFailed { column } => write!(
+                f,
+                "the commitment to advice column {} differs from the one recomputed on the CPU",
+                column
+            ),
+            TachyonError::SelfCheckBasesMismatch { expected, found } => write!(
+                f,
+                "the self-check needs {} Lagrange bases, but {} were given",
+                expected, found
+            ),
+            #[cfg(feature = "halo2")]
+            TachyonError::Challenge(e) => write!(f, "{}", e),
+            #[cfg(feature = "halo2")]
+            TachyonError::Assignment(e) => write!(f, "{}", e),
+            TachyonError::InvalidPrecommitment { column, reason } => {
+                write!(f, "precommitted advice column {}: {}", column, reason)
+            }
+            #[cfg(feature = "halo2")]
+            TachyonError::State(e) => write!(f, "{}", e),
+            TachyonError::Cancelled => write!(f, "the proof was cancelled"),
+            TachyonError::LengthMismatch { expected, found } => write!(
+                f,
+                "the polynomial has {} values, but the prover's domain has {}",
+                found, expected
+            ),
+            #[cfg(feature = "halo2")]
+            TachyonError::LimitExceeded {
+                which,
+                limit,
+                observed,
+            } => write!(
+                f,
+                "{} is {}, over the limit of {}",
+                which.name(),
+                observed,
+                limit
+            ),
+            TachyonError::PrecommitmentMismatch { column } => write!(
+                f,
+                "the commitment to precommitted advice column {} isn't the one to its evals",
+                column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TachyonError {}
+
+#[cfg(feature = "halo2")]
+impl From<plonk::Error> for TachyonError {
+    fn from(e: plonk::Error) -> Self {
+        TachyonError::Plonk(e)
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl From<StateError> for TachyonError {
+    fn from(e: StateError) -> Self {
+        TachyonError::State(e)
+    }
+}
+
+#[cfg(feature = "halo2")]
+impl From<DebugError> for TachyonError {
+    fn from(e: DebugError) -> Self {
+        TachyonError::Preflight(e)
+    }
+}
@@ -0,0 +1,818 @@
+use std::fmt;
+
+use halo2_proofs::poly::commitment::CommitmentScheme;
+
+use crate::{
+    bn254::{ProvingKey, TachyonProver},
+    consts::LagrangeBasisPolicy,
+};
+
+const FR_BYTES: u64 = 32;
+const G1_AFFINE_BYTES: u64 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Fixed and permutation polynomials held by the proving key.
+    ProvingKey,
+    Instance,
+    Witness,
+    Permutation,
+    Lookup,
+    /// Evaluation of h(X) on the extended domain.
+    Quotient,
+    /// MSM bases and scalars on the device.
+    Commitment,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EstimateOptions {
+    /// The number of circuits proven together.
+    pub num_circuits: usize,
+    /// Whether commitments and FFTs run on the device.
+    pub gpu: bool,
+    /// How the prover gets the Lagrange bases, see
+    /// [`crate::prover::ProofOptions::lagrange_bases`].
+    pub lagrange_bases: LagrangeBasisPolicy,
+    /// The log₂ of the size of the IFFTs the IFFT of the extended domain is
+    /// split into, or 0 if it runs in one piece, see
+    /// [`crate::prover::ProofOptions::fft_chunk_log2`].
+    pub fft_chunk_log2: u32,
+}
+
+impl Default for EstimateOptions {
+    fn default() -> Self {
+        Self {
+            num_circuits: 1,
+            gpu: cfg!(feature = "gpu"),
+            lagrange_bases: LagrangeBasisPolicy::Resident,
+            fft_chunk_log2: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    pub host_bytes: u64,
+    pub device_bytes: u64,
+    pub breakdown: Vec<(Stage, u64)>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MemoryLimits {
+    pub host_bytes: Option<u64>,
+    pub device_bytes: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FitError {
+    /// The host doesn't have enough memory. `stage` is the largest contributor.
+    Host {
+        required: u64,
+        limit: u64,
+        stage: Stage,
+    },
+    Device {
+        required: u64,
+        limit: u64,
+        stage: Stage,
+    },
+}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FitError::Host {
+                required,
+                limit,
+                stage,
+            } => write!(
+                f,
+                "proof needs {} host bytes but only {} are available (largest stage: {:?})",
+                required, limit, stage
+            ),
+            FitError::Device {
+                required,
+                limit,
+                stage,
+            } => write!(
+                f,
+                "proof needs {} device bytes but only {} are available (largest stage: {:?})",
+                required, limit, stage
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FitError {}
+
+impl MemoryEstimate {
+    /// Returns an error naming the largest stage if the estimate exceeds
+    /// `limits`.
+    pub fn check_fits(&self, limits: &MemoryLimits) -> Result<(), FitError> {
+        if let Some(limit) = limits.host_bytes {
+            if self.host_bytes > limit {
+                return Err(FitError::Host {
+                    required: self.host_bytes,
+                    limit,
+                    stage: self.largest_stage(false),
+                });
+            }
+        }
+        if let Some(limit) = limits.device_bytes {
+            if self.device_bytes > limit {
+                return Err(FitError::Device {
+                    required: self.device_bytes,
+                    limit,
+                    stage: self.largest_stage(true),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn largest_stage(&self, device: bool) -> Stage {
+        self.breakdown
+            .iter()
+            .filter(|(stage, _)| (*stage == Stage::Commitment) == device)
+            .max_by_key(|(_, bytes)| *bytes)
+            .map(|(stage, _)| *stage)
+            .unwrap_or(Stage::Witness)
+    }
+}
+
+/// Estimates the memory needed to create a proof with `prover` and `pk`.
+///
+/// Every buffer created during proving lives until the proof is done, so the
+/// peak is the sum of all stages.
+pub fn memory_for_proof<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    pk: &ProvingKey<Scheme::Curve>,
+    prover: &P,
+    options: &EstimateOptions,
+) -> MemoryEstimate {
+    let n = prover.n();
+    let extended_n = 1u64 << pk.extended_k(prover.k());
+    let num_circuits = options.num_circuits as u64;
+    let degree = pk.degree() as u64;
+    // Each column is kept both as evaluations and as coefficients.
+    let column_bytes = 2 * n * FR_BYTES;
+
+    let num_permutation_columns = pk.num_permutation_columns() as u64;
+    let chunk_len = degree.saturating_sub(2).max(1);
+    let num_permutation_chunks = (num_permutation_columns + chunk_len - 1) / chunk_len;
+
+    let mut breakdown = vec![
+        (
+            Stage::ProvingKey,
+            (pk.num_fixed_columns() as u64 + num_permutation_columns) * column_bytes,
+        ),
+        (
+            Stage::Instance,
+            num_circuits * pk.num_instance_columns() as u64 * column_bytes,
+        ),
+        (
+            Stage::Witness,
+            // The advice columns of every circuit plus the rational values of
+            // the circuit currently being synthesized.
+            (num_circuits + 1) * pk.num_advice_columns() as u64 * column_bytes,
+        ),
+        (
+            Stage::Permutation,
+            num_circuits * num_permutation_chunks * column_bytes,
+        ),
+        (
+            // Permuted input, permuted table and grand product.
+            Stage::Lookup,
+            num_circuits * pk.num_lookups() as u64 * 3 * column_bytes,
+        ),
+        (
+            // The accumulator on the extended domain and the pieces of h(X).
+            Stage::Quotient,
+            2 * extended_n * FR_BYTES + degree.saturating_sub(1) * n * FR_BYTES,
+        ),
+    ];
+    let host_bytes = breakdown.iter().map(|(_, bytes)| *bytes).sum();
+
+    let device_bytes = if options.gpu {
+        // The monomial bases, the scalars of a commitment and the extended
+        // domain, plus the Lagrange bases for as long as they are kept.
+        let bases_bytes = n * G1_AFFINE_BYTES;
+        let scalars_bytes = n * FR_BYTES;
+        // Split into IFFTs of 2^c and 2^(extended_k - c) values, only the
+        // larger of which is on the device at once.
+        let extended_k = pk.extended_k(prover.k());
+        let extended_bytes = match options.fft_chunk_log2 {
+            c if c == 0 || c >= extended_k => extended_n,
+            c => (1u64 << c).max(1 << (extended_k - c)),
+        } * FR_BYTES;
+        match options.lagrange_bases {
+            LagrangeBasisPolicy::Resident => 2 * bases_bytes + scalars_bytes + extended_bytes,
+            // The Lagrange bases are dropped after every batch of
+            // commitments, before the extended domain is needed.
+            LagrangeBasisPolicy::RecomputePerBatch => {
+                bases_bytes + (bases_bytes + scalars_bytes).max(extended_bytes)
+            }
+            LagrangeBasisPolicy::ViaMonomial => bases_bytes + scalars_bytes + extended_bytes,
+        }
+    } else {
+        0
+    };
+    if device_bytes > 0 {
+        breakdown.push((Stage::Commitment, device_bytes));
+    }
+
+    MemoryEstimate {
+        host_bytes,
+        device_bytes,
+        breakdown,
+    }
+}
+
+/// Returns the largest [`EstimateOptions::fft_chunk_log2`] with which the
+/// proof is estimated to fit in `max_device_bytes`, 0 if it fits without
+/// splitting the IFFT or there is no limit. If nothing fits, this returns the
+/// chunk that needs the least memory, and the proof fails the limit.
+pub fn fft_chunk_log2_for_budget<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    pk: &ProvingKey<Scheme::Curve>,
+    prover: &P,
+    options: &EstimateOptions,
+    max_device_bytes: Option<u64>,
+) -> u32 {
+    let max_device_bytes = match max_device_bytes {
+        Some(max_device_bytes) => max_device_bytes,
+        None => return 0,
+    };
+    let fits = |fft_chunk_log2| {
+        let options = EstimateOptions {
+            fft_chunk_log2,
+            ..*options
+        };
+        memory_for_proof(pk, prover, &options).device_bytes <= max_device_bytes
+    };
+    if fits(0) {
+        return 0;
+    }
+    // Chunks below half of the extended domain only make the other half of
+    // the split larger.
+    let extended_k = pk.extended_k(prover.k());
+    let smallest = (extended_k + 1) / 2;
+    (smallest..extended_k)
+        .rev()
+        .find(|fft_chunk_log2| fits(*fft_chunk_log2))
+        .unwrap_or(smallest)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        fft_chunk_log2_for_budget, memory_for_proof, EstimateOptions, FitError, MemoryLimits, Stage,
+    };
+    use crate::{
+        bn254::{Blake2bWrite, ProvingKey, SHPlonkProver, TachyonProver},
+        circuits::{simple_circuit::SimpleCircuit, wide_circuit::WideCircuit},
+        consts::{LagrangeBasisPolicy, TranscriptType},
+        error::TachyonError,
+        metrics::device_bytes_in_use,
+        prover::{create_proof_with_options, ProofOptions},
+        rng::proof_context,
+    };
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, Circuit},
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::{Challenge255, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::OsRng;
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_memory_for_proof() {
+        let k = 4;
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+        let prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+
+        let options = EstimateOptions {
+            num_circuits: 1,
+            gpu: false,
+            ..Default::default()
+        };
+        let estimate = memory_for_proof(&tachyon_pk, &prover, &options);
+        assert_eq!(
+            estimate.host_bytes,
+            estimate
+                .breakdown
+                .iter()
+                .map(|(_, bytes)| *bytes)
+                .sum::<u64>()
+        );
+        assert_eq!(estimate.device_bytes, 0);
+
+        let options = EstimateOptions {
+            num_circuits: 2,
+            gpu: false,
+            ..Default::default()
+        };
+        assert!(memory_for_proof(&tachyon_pk, &prover, &options).host_bytes > estimate.host_bytes);
+
+        assert!(estimate
+            .check_fits(&MemoryLimits {
+                host_bytes: Some(estimate.host_bytes),
+                device_bytes: None,
+            })
+            .is_ok());
+        let largest = estimate
+            .breakdown
+            .iter()
+            .max_by_key(|(_, bytes)| *bytes)
+            .unwrap()
+            .0;
+        assert_eq!(
+            estimate.check_fits(&MemoryLimits {
+                host_bytes: Some(estimate.host_bytes - 1),
+                device_bytes: None,
+            }),
+            Err(FitError::Host {
+                required: estimate.host_bytes,
+                limit: estimate.host_bytes - 1,
+                stage: largest,
+            })
+        );
+        assert_ne!(largest, Stage::Commitment);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_lagrange_basis_policy() {
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = vec![&public_inputs[..]];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+
+        // Every policy the prover takes commits to the same points.
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        let mut evals = prover.empty_evals();
+        for row in 0..evals.len() {
+            evals.set_value(row, &Fr::random(OsRng));
+        }
+        let expected = prover.commit_lagrange(&evals);
+        for policy in LagrangeBasisPolicy::ALL {
+            let previous = prover.lagrange_basis_policy();
+            match prover.set_lagrange_basis_policy(policy) {
+                Ok(()) => {
+                    assert_eq!(prover.lagrange_basis_policy(), policy);
+                    assert_eq!(prover.commit_lagrange(&evals), expected, "{:?}", policy);
+                    assert_eq!(prover.commit_lagrange_columns(&[&evals]), vec![expected]);
+                }
+                Err(TachyonError::BackendUnavailable { .. }) => {
+                    assert_eq!(policy, LagrangeBasisPolicy::RecomputePerBatch);
+                    assert_eq!(prover.lagrange_basis_policy(), previous);
+                }
+                Err(e) => panic!("{:?}: {}", policy, e),
+            }
+        }
+
+        // So are the proofs.
+        let prove = |lagrange_bases| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            let mut tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+            let options = ProofOptions {
+                rng_context: proof_context(b"simple", 0),
+                lagrange_bases,
+                ..Default::default()
+            };
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&instances[..]],
+                &options,
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+        assert_eq!(
+            prove(LagrangeBasisPolicy::ViaMonomial),
+            prove(LagrangeBasisPolicy::Resident)
+        );
+
+        // Only the bases on the device depend on the policy.
+        let estimate = |lagrange_bases| {
+            memory_for_proof(
+                &tachyon_pk,
+                &prover,
+                &EstimateOptions {
+                    num_circuits: 1,
+                    gpu: true,
+                    lagrange_bases,
+                    ..Default::default()
+                },
+            )
+        };
+        let resident = estimate(LagrangeBasisPolicy::Resident);
+        let recompute = estimate(LagrangeBasisPolicy::RecomputePerBatch);
+        let via_monomial = estimate(LagrangeBasisPolicy::ViaMonomial);
+        assert_eq!(recompute.host_bytes, resident.host_bytes);
+        assert_eq!(via_monomial.host_bytes, resident.host_bytes);
+        assert!(recompute.device_bytes < resident.device_bytes);
+        assert!(via_monomial.device_bytes <= recompute.device_bytes);
+        assert_eq!(
+            resident.device_bytes - via_monomial.device_bytes,
+            prover.n() * 64
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_fft_chunk_log2() {
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = vec![&public_inputs[..]];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+        let prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        let extended_k = tachyon_pk.extended_k(k);
+
+        // Only the device holds less with a split IFFT, and the least with an
+        // even one.
+        let estimate = |fft_chunk_log2| {
+            memory_for_proof(
+                &tachyon_pk,
+                &prover,
+                &EstimateOptions {
+                    gpu: true,
+                    fft_chunk_log2,
+                    ..Default::default()
+                },
+            )
+        };
+        let monolithic = estimate(0);
+        let halved = estimate(extended_k - 1);
+        let even = estimate(extended_k / 2);
+        assert_eq!(halved.host_bytes, monolithic.host_bytes);
+        assert_eq!(
+            monolithic.device_bytes - halved.device_bytes,
+            (1 << (extended_k - 1)) * 32
+        );
+        assert!(even.device_bytes < halved.device_bytes);
+        assert_eq!(estimate(extended_k), monolithic);
+
+        let options = EstimateOptions {
+            gpu: true,
+            ..Default::default()
+        };
+        let budget = |max_device_bytes| {
+            fft_chunk_log2_for_budget(&tachyon_pk, &prover, &options, max_device_bytes)
+        };
+        assert_eq!(budget(None), 0);
+        assert_eq!(budget(Some(monolithic.device_bytes)), 0);
+        assert_eq!(budget(Some(halved.device_bytes)), extended_k - 1);
+        assert_eq!(budget(Some(even.device_bytes)), (extended_k + 1) / 2);
+        assert_eq!(budget(Some(0)), (extended_k + 1) / 2);
+
+        // The proof doesn't depend on the split.
+        let prove = |fft_chunk_log2| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            let mut tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+            let options = ProofOptions {
+                rng_context: proof_context(b"simple", 0),
+                fft_chunk_log2,
+                ..Default::default()
+            };
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&instances[..]],
+                &options,
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+        let expected = prove(Some(0));
+        assert_eq!(prove(None), expected);
+        for fft_chunk_log2 in 1..extended_k {
+            assert_eq!(prove(Some(fft_chunk_log2)), expected, "{}", fft_chunk_log2);
+        }
+    }
+
+    // A budget that the monolithic IFFT of the extended domain exceeds but a
+    // split one fits only proves if the split is chosen for it.
+    #[cfg(feature = "gpu")]
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_fft_chunk_log2_under_budget() {
+        use halo2_proofs::transcript::Blake2bRead;
+
+        use crate::{
+            consts::{AbsorbOrder, InstanceAbsorption},
+            instance_hash::verify_proof,
+            limits::{Limit, ResourceLimits},
+            proving_key::Metadata,
+        };
+
+        let k = 14;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = vec![&public_inputs[..]];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+        let prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        let extended_k = tachyon_pk.extended_k(k);
+        let estimate = |fft_chunk_log2| {
+            memory_for_proof(
+                &tachyon_pk,
+                &prover,
+                &EstimateOptions {
+                    fft_chunk_log2,
+                    ..Default::default()
+                },
+            )
+            .device_bytes
+        };
+        let max_device_bytes = estimate(extended_k / 2);
+        assert!(max_device_bytes < estimate(0));
+
+        let prove = |fft_chunk_log2| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            let mut tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+            let options = ProofOptions {
+                rng_context: proof_context(b"simple", 0),
+                fft_chunk_log2,
+                limits: ResourceLimits {
+                    max_device_bytes: Some(max_device_bytes),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&instances[..]],
+                &options,
+                &mut transcript,
+            )?;
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            Ok::<_, TachyonError>(proof)
+        };
+        assert!(matches!(
+            prove(Some(0)),
+            Err(TachyonError::LimitExceeded {
+                which: Limit::DeviceBytes,
+                ..
+            })
+        ));
+        let proof = prove(None).expect("proof generation should not fail");
+        assert!(verify_proof::<Blake2bRead<_, _, _>>(
+            &params,
+            pk.get_vk(),
+            &Metadata::new(),
+            InstanceAbsorption::PerValue,
+            AbsorbOrder::PhaseThenColumn,
+            &[&instances[..]],
+            &proof,
+        )
+        .is_ok());
+    }
+
+    // Times committing to 64 columns under every policy the prover takes,
+    // next to the device memory each is estimated to need. Run it with
+    //   cargo test --release --features gpu -- --ignored bench_lagrange_basis_policy --nocapture
+    #[cfg(feature = "gpu")]
+    #[test]
+    #[ignore]
+    fn bench_lagrange_basis_policy() {
+        use std::time::Instant;
+
+        let s = Fr::from(2);
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        println!(
+            "{:>2} {:>20} {:>12} {:>14}",
+            "k", "policy", "time", "device bytes"
+        );
+        for k in [12, 14, 16] {
+            let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+            let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+            let mut pk_bytes: Vec<u8> = vec![];
+            pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+                .unwrap();
+            let tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            let columns: Vec<_> = (0..64)
+                .map(|_| {
+                    let mut evals = prover.empty_evals();
+                    for row in 0..evals.len() {
+                        evals.set_value(row, &Fr::random(OsRng));
+                    }
+                    evals
+                })
+                .collect();
+            let columns: Vec<_> = columns.iter().collect();
+
+            for policy in LagrangeBasisPolicy::ALL {
+                if prover.set_lagrange_basis_policy(policy).is_err() {
+                    println!(
+                        "{:>2} {:>20} {:>12} {:>14}",
+                        k,
+                        format!("{:?}", policy),
+                        "-",
+                        "-"
+                    );
+                    continue;
+                }
+                let start = Instant::now();
+                prover.commit_lagrange_columns(&columns);
+                let elapsed = start.elapsed();
+                let estimate = memory_for_proof(
+                    &tachyon_pk,
+                    &prover,
+                    &EstimateOptions {
+                        num_circuits: 1,
+                        gpu: true,
+                        lagrange_bases: policy,
+                        ..Default::default()
+                    },
+                );
+                println!(
+                    "{:>2} {:>20} {:>12} {:>14}",
+                    k,
+                    format!("{:?}", policy),
+                    format!("{:?}", elapsed),
+                    estimate.device_bytes
+                );
+            }
+        }
+    }
+
+    /// Proves `circuit` at `k` and asserts the memory it took is within 25% of
+    /// [`memory_for_proof()`]. The key is already in memory when the proof
+    /// begins, so it is left out of the host estimate.
+    fn assert_estimate_matches_peaks<C: Circuit<Fr> + Clone>(k: u32, circuit: C, instances: &[Fr]) {
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        let options = EstimateOptions {
+            num_circuits: 1,
+            gpu: device_bytes_in_use().is_some(),
+            ..Default::default()
+        };
+        let estimate = memory_for_proof(&tachyon_pk, &prover, &options);
+
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &[&[instances]],
+            &ProofOptions::default(),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+
+        let within = |measured: u64, estimated: u64| {
+            (measured as f64 - estimated as f64).abs() <= 0.25 * measured as f64
+        };
+        let metrics = prover.metrics();
+        // Zero where the OS can't reset the peak.
+        if metrics.peak_host_bytes > 0 {
+            let estimated = estimate.host_bytes
+                - estimate
+                    .breakdown
+                    .iter()
+                    .find(|(stage, _)| *stage == Stage::ProvingKey)
+                    .map_or(0, |(_, bytes)| *bytes);
+            assert!(
+                within(metrics.peak_host_bytes, estimated),
+                "host: measured {}, estimated {}",
+                metrics.peak_host_bytes,
+                estimated
+            );
+        }
+        if options.gpu {
+            assert!(
+                within(metrics.peak_device_bytes, estimate.device_bytes),
+                "device: measured {}, estimated {}",
+                metrics.peak_device_bytes,
+                estimate.device_bytes
+            );
+        }
+    }
+
+    // The peaks are those of the whole process, so this is only meaningful
+    // when run alone: `cargo test --features native-tests
+    // test_estimate_matches_peaks -- --test-threads=1`. The circuits are
+    // proven at k = 16 so that the pages the allocator rounds up to don't
+    // dominate what is measured.
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_estimate_matches_peaks() {
+        let k = 16;
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        assert_estimate_matches_peaks(k, circuit, &[constant * a.square() * b.square()]);
+
+        let circuit = WideCircuit::<Fr, 32>::default();
+        let instances = circuit.instances();
+        assert_estimate_matches_peaks(k, circuit, &instances);
+    }
+}
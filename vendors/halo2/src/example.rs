@@ -0,0 +1,260 @@
+// The end-to-end flows of the binaries in `examples/`, which can only reach
+// what the crate exports. They are exported with the "examples" feature, the
+// way the fuzz target reaches `parse_all()`.
+
+use std::io;
+
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{keygen_pk2, verify_proof, Error, ProvingKey},
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer},
+};
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use rand_core::SeedableRng;
+
+use crate::{
+    bn254::{
+        Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+        Sha256Write as TachyonSha256Write, TachyonProver,
+    },
+    circuits::{simple_circuit::SimpleCircuit, simple_lookup_circuit::SimpleLookupCircuit},
+    consts::{TranscriptType, SEED},
+    preset::{ProverBuilder, ProverPreset},
+    prover::create_proof,
+    serde_fr::frs_to_be_bytes,
+    sha::ShaWrite,
+    xor_shift_rng::XORShiftRng,
+};
+
+/// Returns `SimpleCircuit` proving `constant * a² * b²` with its public input.
+fn simple_circuit(a: u64, b: u64) -> (SimpleCircuit<Fr>, Fr) {
+    let constant = Fr::from(7);
+    let (a, b) = (Fr::from(a), Fr::from(b));
+    let circuit = SimpleCircuit {
+        constant,
+        a: Value::known(a),
+        b: Value::known(b),
+    };
+    (circuit, constant * a.square() * b.square())
+}
+
+fn to_tachyon_pk(pk: &ProvingKey<G1Affine>) -> Result<TachyonProvingKey<G1Affine>, Error> {
+    let mut pk_bytes: Vec<u8> = vec![];
+    pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+        .map_err(Error::Transcript)?;
+    TachyonProvingKey::try_from(pk_bytes.as_slice()).map_err(Error::Transcript)
+}
+
+fn params_bytes(params: &ParamsKZG<Bn256>) -> Result<Vec<u8>, Error> {
+    let mut params_bytes: Vec<u8> = vec![];
+    params.write(&mut params_bytes).map_err(Error::Transcript)?;
+    Ok(params_bytes)
+}
+
+fn verify_blake2b(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    instances: &[&[Fr]],
+    proof: &[u8],
+) -> Result<(), Error> {
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+    verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+        params.verifier_params(),
+        pk.get_vk(),
+        SingleStrategy::new(params),
+        &[instances],
+        &mut transcript,
+    )
+    .map(|_| ())
+}
+
+/// Proves that `constant * a² * b²` is the public input of `SimpleCircuit`
+/// with the backend, checks the proof with halo2's verifier, and returns it
+/// with the public input. The key is generated from `params`, whose `k` has
+/// to be at least 4.
+pub fn prove_simple(params: &ParamsKZG<Bn256>, a: u64, b: u64) -> Result<(Vec<u8>, Fr), Error> {
+    let (circuit, public_input) = simple_circuit(a, b);
+    let pk = keygen_pk2(params, &circuit)?;
+    let mut tachyon_pk = to_tachyon_pk(&pk)?;
+    let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::from_params(
+        TranscriptType::Blake2b as u8,
+        params.k(),
+        params_bytes(params)?.as_slice(),
+    );
+
+    let mut transcript = TachyonBlake2bWrite::init(vec![]);
+    create_proof(
+        &mut prover,
+        &mut tachyon_pk,
+        &[circuit],
+        &[&[&[public_input]]],
+        XORShiftRng::from_entropy(),
+        &mut transcript,
+    )?;
+    let mut proof = transcript.finalize();
+    proof.extend_from_slice(&prover.get_proof());
+
+    verify_blake2b(params, &pk, &[&[public_input]], &proof)?;
+    Ok((proof, public_input))
+}
+
+/// Proves `SimpleLookupCircuit`, which looks 16 advice values up in a table
+/// of 8, with a prover built from [`ProverPreset::LocalBlake2b`], checks the
+/// proof with halo2's verifier, and returns it. The key is generated from
+/// `params`, whose `k` has to be at least 5.
+pub fn prove_lookup(params: &ParamsKZG<Bn256>) -> Result<Vec<u8>, Error> {
+    let circuit = SimpleLookupCircuit::<Fr>::default();
+    let pk = keygen_pk2(params, &circuit)?;
+    let mut tachyon_pk = to_tachyon_pk(&pk)?;
+    let mut prover = ProverBuilder::new(ProverPreset::LocalBlake2b)
+        .build_from_params::<KZGCommitmentScheme<Bn256>>(
+            params.k(),
+            params_bytes(params)?.as_slice(),
+        );
+
+    let mut transcript = TachyonBlake2bWrite::init(vec![]);
+    create_proof(
+        &mut prover,
+        &mut tachyon_pk,
+        &[circuit],
+        &[&[]],
+        XORShiftRng::from_entropy(),
+        &mut transcript,
+    )?;
+    let mut proof = transcript.finalize();
+    proof.extend_from_slice(&prover.get_proof());
+
+    verify_blake2b(params, &pk, &[], &proof)?;
+    Ok(proof)
+}
+
+/// Proves `SimpleCircuit` like [`prove_simple()`] with a prover built from
+/// [`ProverPreset::EvmSha256Shplonk`], and returns the proof with the
+/// calldata of an EVM verifier: the public input as a 32-byte big-endian
+/// word, followed by the proof. There is no native SHA-256 verifier, so the
+/// proof is checked against the one halo2 creates with the same randomness
+/// instead.
+pub fn prove_and_encode_evm(
+    params: &ParamsKZG<Bn256>,
+    a: u64,
+    b: u64,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let (circuit, public_input) = simple_circuit(a, b);
+    let pk = keygen_pk2(params, &circuit)?;
+    let mut tachyon_pk = to_tachyon_pk(&pk)?;
+    let mut prover = ProverBuilder::new(ProverPreset::EvmSha256Shplonk)
+        .build_from_params::<KZGCommitmentScheme<Bn256>>(
+            params.k(),
+            params_bytes(params)?.as_slice(),
+        );
+
+    let mut transcript = TachyonSha256Write::init(vec![]);
+    create_proof(
+        &mut prover,
+        &mut tachyon_pk,
+        &[circuit.clone()],
+        &[&[&[public_input]]],
+        XORShiftRng::from_seed(SEED),
+        &mut transcript,
+    )?;
+    let mut proof = transcript.finalize();
+    proof.extend_from_slice(&prover.get_proof());
+
+    let mut transcript = ShaWrite::<_, G1Affine, Challenge255<_>, sha2::Sha256>::init(vec![]);
+    halo2_proofs::plonk::create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+        params,
+        &pk,
+        &[circuit],
+        &[&[&[public_input]]],
+        XORShiftRng::from_seed(SEED),
+        &mut transcript,
+    )?;
+    if proof != transcript.finalize() {
+        return Err(Error::Transcript(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the proof differs from halo2's",
+        )));
+    }
+
+    let mut calldata = frs_to_be_bytes(&[public_input]);
+    calldata.extend_from_slice(&proof);
+    Ok((proof, calldata))
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{prove_and_encode_evm, prove_lookup, prove_simple};
+    use crate::serde_fr::fr_to_be_bytes;
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_prove_simple() {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(8, Fr::from(2));
+        let (proof, public_input) = prove_simple(&params, 2, 3).unwrap();
+        assert!(!proof.is_empty());
+        assert_eq!(public_input, Fr::from(7 * 4 * 9));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_prove_lookup() {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(8, Fr::from(2));
+        assert!(!prove_lookup(&params).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_prove_and_encode_evm() {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(8, Fr::from(2));
+        let (proof, calldata) = prove_and_encode_evm(&params, 2, 3).unwrap();
+        assert_eq!(calldata[..32], fr_to_be_bytes(&Fr::from(7 * 4 * 9)));
+        assert_eq!(calldata[32..], proof);
+    }
+
+    // Runs the binaries `cargo test --features examples` builds, from
+    // `target/<profile>/examples`, next to the `deps` directory this test runs
+    // from. Bazel doesn't build cargo examples.
+    #[cfg(feature = "examples")]
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_example_binaries() {
+        use std::{env, fs, process::Command};
+
+        let examples = env::current_exe()
+            .unwrap()
+            .parent()
+            .and_then(|deps| deps.parent())
+            .unwrap()
+            .join("examples");
+        let out_dir =
+            env::temp_dir().join(format!("tachyon_halo2_examples_{}", std::process::id()));
+        fs::create_dir_all(&out_dir).unwrap();
+        for example in ["prove_simple", "prove_lookup", "prove_and_encode_evm"] {
+            let out = out_dir.join(example);
+            let output = Command::new(examples.join(example))
+                .args(["--k", "8", "--out"])
+                .arg(&out)
+                .output()
+                .unwrap_or_else(|e| panic!("failed to run {}: {}", example, e));
+            assert!(
+                output.status.success(),
+                "{} failed: {}",
+                example,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            assert!(!fs::read(&out).unwrap().is_empty(), "{}", example);
+        }
+        fs::remove_dir_all(&out_dir).unwrap();
+    }
+}
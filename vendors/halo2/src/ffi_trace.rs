@@ -0,0 +1,282 @@
+// Keeps the last `TRACE_CAPACITY` FFI entries and exits in a ring buffer and
+// writes them out when the process crashes, so that a segfault in the backend
+// can be traced back to the call that was in flight. Every entry is formatted
+// when it is recorded, which leaves the crash handler with nothing to do but
+// `write(2)` the slots it already has.
+//
+// Without the "ffi_trace" feature, `ffi_trace!` expands to nothing.
+
+#[cfg(feature = "ffi_trace")]
+macro_rules! ffi_trace {
+    ($name:expr) => {
+        let _ffi_trace_guard = crate::ffi_trace::Guard::enter($name, format_args!(""));
+    };
+    ($name:expr, $($arg:tt)+) => {
+        let _ffi_trace_guard = crate::ffi_trace::Guard::enter($name, format_args!($($arg)+));
+    };
+}
+
+#[cfg(not(feature = "ffi_trace"))]
+macro_rules! ffi_trace {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(feature = "ffi_trace")]
+pub use self::imp::*;
+
+#[cfg(feature = "ffi_trace")]
+mod imp {
+    use std::{
+        cell::UnsafeCell,
+        fmt,
+        fs::File,
+        io::{self, Write},
+        os::unix::io::IntoRawFd,
+        path::Path,
+        sync::atomic::{AtomicI32, AtomicUsize, Ordering},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    /// The environment variable that `install_from_env()` reads the trace
+    /// path from.
+    pub const TRACE_PATH_ENV: &str = "TACHYON_FFI_TRACE_PATH";
+
+    /// The number of most recent entries that are kept.
+    pub const TRACE_CAPACITY: usize = 64;
+
+    const ENTRY_SIZE: usize = 192;
+
+    struct Slot {
+        // Zero while the slot is being written.
+        len: AtomicUsize,
+        buf: UnsafeCell<[u8; ENTRY_SIZE]>,
+    }
+
+    // Slots are only written by `record()` after claiming them with
+    // `NEXT.fetch_add()`. Two writers only meet in a slot when more than
+    // `TRACE_CAPACITY` calls are recorded concurrently, which garbles that
+    // entry but nothing else.
+    unsafe impl Sync for Slot {}
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY_SLOT: Slot = Slot {
+        len: AtomicUsize::new(0),
+        buf: UnsafeCell::new([0; ENTRY_SIZE]),
+    };
+
+    static SLOTS: [Slot; TRACE_CAPACITY] = [EMPTY_SLOT; TRACE_CAPACITY];
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    static FD: AtomicI32 = AtomicI32::new(-1);
+
+    const CRASH_SIGNALS: [libc::c_int; 5] = [
+        libc::SIGSEGV,
+        libc::SIGBUS,
+        libc::SIGILL,
+        libc::SIGFPE,
+        libc::SIGABRT,
+    ];
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Event {
+        Enter,
+        Exit,
+    }
+
+    impl fmt::Display for Event {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Event::Enter => write!(f, "enter"),
+                Event::Exit => write!(f, "exit"),
+            }
+        }
+    }
+
+    /// Records `event` for the FFI call `name`. Each entry is a line of the
+    /// form "<unix time in ns> <event> <name> <args>", truncated to fit a slot.
+    pub fn record(event: Event, name: &str, args: fmt::Arguments<'_>) {
+        let index = NEXT.fetch_add(1, Ordering::Relaxed);
+        let slot = &SLOTS[index % TRACE_CAPACITY];
+        slot.len.store(0, Ordering::Release);
+
+        let buf = unsafe { &mut *slot.buf.get() };
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+        let mut cursor = io::Cursor::new(&mut buf[..]);
+        let _ = writeln!(cursor, "{} {} {} {}", nanos, event, name, args);
+        let len = cursor.position() as usize;
+        // Keep one entry per line even if it was truncated.
+        buf[len - 1] = b'\n';
+        slot.len.store(len, Ordering::Release);
+    }
+
+    /// Records the entry to `name` and its exit when dropped.
+    #[derive(Debug)]
+    pub struct Guard {
+        name: &'static str,
+    }
+
+    impl Guard {
+        pub fn enter(name: &'static str, args: fmt::Arguments<'_>) -> Guard {
+            record(Event::Enter, name, args);
+            Guard { name }
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            record(Event::Exit, self.name, format_args!(""));
+        }
+    }
+
+    /// Returns the address of the C++ object owned by `ptr` for logging.
+    pub fn ptr<T: cxx::memory::UniquePtrTarget>(ptr: &cxx::UniquePtr<T>) -> *const T {
+        ptr.as_ref()
+            .map_or(std::ptr::null(), |value| value as *const T)
+    }
+
+    /// Writes the recorded entries, oldest first, to the trace file. This only
+    /// calls `write(2)`, so it is safe to call from a signal handler.
+    pub fn flush() {
+        let fd = FD.load(Ordering::Acquire);
+        if fd < 0 {
+            return;
+        }
+        unsafe {
+            libc::ftruncate(fd, 0);
+            libc::lseek(fd, 0, libc::SEEK_SET);
+        }
+        let next = NEXT.load(Ordering::Acquire);
+        for i in 0..TRACE_CAPACITY {
+            let slot = &SLOTS[(next + i) % TRACE_CAPACITY];
+            let len = slot.len.load(Ordering::Acquire);
+            if len == 0 {
+                continue;
+            }
+            unsafe {
+                libc::write(fd, slot.buf.get() as *const libc::c_void, len);
+            }
+        }
+    }
+
+    extern "C" fn handle_crash(signal: libc::c_int) {
+        flush();
+        // The handler was installed with `SA_RESETHAND`, so this terminates the
+        // process with the default action of `signal`.
+        unsafe {
+            libc::raise(signal);
+        }
+    }
+
+    extern "C" fn handle_exit() {
+        flush();
+    }
+
+    /// Starts writing the trace to `path` when the process crashes or exits.
+    pub fn install(path: impl AsRef<Path>) -> io::Result<()> {
+        let fd = File::create(path)?.into_raw_fd();
+        let prev = FD.swap(fd, Ordering::AcqRel);
+        if prev >= 0 {
+            unsafe {
+                libc::close(prev);
+            }
+            return Ok(());
+        }
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handle_crash as extern "C" fn(libc::c_int) as usize;
+            action.sa_flags = libc::SA_RESETHAND;
+            libc::sigemptyset(&mut action.sa_mask);
+            for signal in CRASH_SIGNALS {
+                if libc::sigaction(signal, &action, std::ptr::null_mut()) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            libc::atexit(handle_exit);
+        }
+        Ok(())
+    }
+
+    /// Calls `install()` with the path in `TACHYON_FFI_TRACE_PATH`. Returns
+    /// `Ok(false)` if it isn't set.
+    pub fn install_from_env() -> io::Result<bool> {
+        match std::env::var_os(TRACE_PATH_ENV) {
+            Some(path) => install(path).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    #[cfg(all(test, feature = "halo2"))]
+    mod test {
+        use std::{process::Command, time::Instant};
+
+        use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+        use halo2curves::bn256::{Bn256, Fr};
+
+        use super::{install_from_env, record, Event, TRACE_PATH_ENV};
+        use crate::{backend_log::abort_in_backend, bn254::SHPlonkProver, consts::TranscriptType};
+
+        const CHILD_ENV: &str = "TACHYON_FFI_TRACE_CHILD";
+
+        // Run by `test_trace_on_abort()` in a child process.
+        #[test]
+        #[ignore]
+        fn abort_in_ffi() {
+            if std::env::var_os(CHILD_ENV).is_none() {
+                return;
+            }
+            assert!(install_from_env().unwrap());
+            let _prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                4,
+                &Fr::from(2),
+            );
+            abort_in_backend("injected");
+        }
+
+        #[test]
+        #[cfg_attr(not(feature = "native-tests"), ignore)]
+        fn test_trace_on_abort() {
+            let path = std::env::temp_dir().join(format!("ffi_trace_{}.log", std::process::id()));
+            let status = Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "ffi_trace::imp::test::abort_in_ffi", "--ignored"])
+                .env(CHILD_ENV, "1")
+                .env(TRACE_PATH_ENV, &path)
+                .status()
+                .unwrap();
+            assert!(!status.success());
+
+            let trace = std::fs::read_to_string(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            let lines: Vec<&str> = trace.lines().collect();
+            assert!(lines
+                .iter()
+                .any(|line| line.contains(" exit new_shplonk_prover ")));
+            let last: Vec<&str> = lines.last().unwrap().splitn(4, ' ').collect();
+            assert_eq!(
+                &last[1..],
+                ["enter", "log_fatal_for_testing", "message=injected"]
+            );
+        }
+
+        // Times recording the entry and exit of a call, which is what the
+        // feature adds to every FFI call:
+        //   cargo test --release --features ffi_trace -- --ignored bench_record --nocapture
+        // Compare it with the time of the cheapest calls, e.g, `commit_lagrange()`
+        // at a small k, to get the overhead.
+        #[test]
+        #[ignore]
+        fn bench_record() {
+            let n = 1_000_000;
+            for _ in 0..3 {
+                let start = Instant::now();
+                for i in 0..n {
+                    record(Event::Enter, "bench_record", format_args!("i={}", i));
+                    record(Event::Exit, "bench_record", format_args!(""));
+                }
+                println!("per call: {:?}", start.elapsed() / n);
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use ff::BatchInvert;
+use halo2curves::bn256::Fr;
+
+use crate::{
+    bn254::{ffi, Fr as CppFr},
+    error::TachyonError,
+    version::check_backend,
+};
+
+/// Slices shorter than this are inverted on the CPU with `ff::BatchInvert`.
+/// The value is a starting point, not a measured crossover; run
+/// `test_batch_invert_timing` to compare the two paths on a machine.
+pub const BACKEND_BATCH_INVERT_THRESHOLD: usize = 1 << 10;
+
+/// Replaces every value of `values` with its inverse, leaving zeros as zero
+/// like `ff::BatchInvert`. Slices of at least
+/// [`BACKEND_BATCH_INVERT_THRESHOLD`] values are inverted by libtachyon, in
+/// parallel.
+pub fn batch_invert(values: &mut [Fr]) -> Result<(), TachyonError> {
+    if values.len() < BACKEND_BATCH_INVERT_THRESHOLD {
+        values.iter_mut().batch_invert();
+        return Ok(());
+    }
+    check_backend()?;
+    let cpp_values: &mut [CppFr] = unsafe { std::mem::transmute(values) };
+    ffi::batch_inverse_in_place(cpp_values);
+    Ok(())
+}
+
+/// Writes the inverses of `values` to `inverses`, like [`batch_invert()`]
+/// but leaving `values` as is.
+///
+/// # Panics
+///
+/// Panics if `values` and `inverses` differ in length.
+pub fn batch_invert_into(values: &[Fr], inverses: &mut [Fr]) -> Result<(), TachyonError> {
+    assert_eq!(values.len(), inverses.len());
+    if values.len() < BACKEND_BATCH_INVERT_THRESHOLD {
+        inverses.copy_from_slice(values);
+        inverses.iter_mut().batch_invert();
+        return Ok(());
+    }
+    check_backend()?;
+    let cpp_values: &[CppFr] = unsafe { std::mem::transmute(values) };
+    let cpp_inverses: &mut [CppFr] = unsafe { std::mem::transmute(inverses) };
+    ffi::batch_inverse(cpp_values, cpp_inverses);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use ff::{BatchInvert, Field};
+    use halo2curves::bn256::Fr;
+    use rand_core::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    use super::{batch_invert, batch_invert_into, BACKEND_BATCH_INVERT_THRESHOLD};
+    use crate::consts::SEED;
+
+    /// Random values, every seventh of which is zero.
+    fn values_with_zeros(n: usize) -> Vec<Fr> {
+        let mut rng = XorShiftRng::from_seed(SEED);
+        (0..n)
+            .map(|i| {
+                if i % 7 == 0 {
+                    Fr::zero()
+                } else {
+                    Fr::random(&mut rng)
+                }
+            })
+            .collect()
+    }
+
+    fn expected(values: &[Fr]) -> Vec<Fr> {
+        let mut expected = values.to_vec();
+        expected.iter_mut().batch_invert();
+        expected
+    }
+
+    #[test]
+    fn test_batch_invert_cpu() {
+        let values = values_with_zeros(BACKEND_BATCH_INVERT_THRESHOLD - 1);
+        let expected = expected(&values);
+        assert!(expected
+            .iter()
+            .step_by(7)
+            .all(|value| bool::from(value.is_zero())));
+
+        let mut inverses = vec![Fr::zero(); values.len()];
+        batch_invert_into(&values, &mut inverses).unwrap();
+        assert_eq!(inverses, expected);
+
+        let mut values = values;
+        batch_invert(&mut values).unwrap();
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_batch_invert_backend() {
+        let values = values_with_zeros(BACKEND_BATCH_INVERT_THRESHOLD + 1);
+        let expected = expected(&values);
+
+        let mut inverses = vec![Fr::zero(); values.len()];
+        batch_invert_into(&values, &mut inverses).unwrap();
+        assert_eq!(inverses, expected);
+
+        let mut values = values;
+        batch_invert(&mut values).unwrap();
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_batch_invert_timing() {
+        let values = values_with_zeros(1 << 20);
+
+        let now = Instant::now();
+        let expected = expected(&values);
+        println!("ff::BatchInvert, elapsed: {:?}", now.elapsed());
+
+        let mut inverses = vec![Fr::zero(); values.len()];
+        let now = Instant::now();
+        batch_invert_into(&values, &mut inverses).unwrap();
+        println!("batch_invert_into, elapsed: {:?}", now.elapsed());
+        assert_eq!(inverses, expected);
+    }
+}
@@ -0,0 +1,245 @@
+// Everything that parses bytes from outside the process has to fail with an
+// error rather than panic, abort or allocate whatever length the bytes claim.
+// `parse_all()` hands an input to the parser its first byte picks. It is the
+// body of the cargo-fuzz target in `fuzz/fuzz_targets/parse_all.rs`, and the
+// tests below run it over a bounded corpus for builds without libFuzzer.
+//
+// The bytes of a proving key behind its metadata aren't covered: the backend
+// reads them and aborts on a malformed key.
+
+use halo2curves::bn256::G1Affine;
+
+use crate::{
+    bn254::ProvingKey,
+    consts::TranscriptType,
+    instance_source::{InstanceReader, InstanceSource},
+    interop::{detect_config, VerifierArtifact},
+    proof::{parse, PointEncoding, Proof},
+    proving_key::{decode_metadata, read_metadata, split_metadata},
+    repro::{decode_sections, ReproJob},
+    serde_fr::{frs_from_be_bytes, frs_from_le_bytes},
+    srs::{IntegrityPolicy, SrsIntegrity},
+    state::{state_body, transcript_state_body, StateKind},
+    transcript::{custom_hasher_from_state, Component, TranscriptItem},
+};
+
+/// The number of parsers [`parse_all()`] picks from.
+pub const NUM_PARSERS: u8 = 9;
+
+/// A layout with an item of every component, to parse proofs with.
+fn layout() -> Vec<TranscriptItem> {
+    Component::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, component)| TranscriptItem::new(*component, None, index))
+        .collect()
+}
+
+/// Parses `data[1..]` with the parser `data[0]` picks, and drops the result.
+/// Panics only if the parser does.
+pub fn parse_all(data: &[u8]) {
+    let (parser, bytes) = match data.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    match parser % NUM_PARSERS {
+        0 => {
+            let _ = SrsIntegrity::verify(bytes, &IntegrityPolicy::Structural);
+        }
+        1 => {
+            let _ = read_metadata(&mut &bytes[..]);
+            let _ = decode_metadata(bytes);
+            // The metadata is checked before the key gets to the backend, so
+            // only a key that fails it is safe to load here.
+            if split_metadata(bytes).is_err() {
+                assert!(ProvingKey::<G1Affine>::try_from(bytes).is_err());
+            }
+        }
+        2 => {
+            let _ = Proof::from_bytes(bytes);
+        }
+        3 => {
+            let layout = layout();
+            for encoding in [PointEncoding::Compressed, PointEncoding::Uncompressed] {
+                let _ = parse(bytes, &layout, encoding);
+                let _ = parse(bytes, &layout[..bytes.len() % layout.len()], encoding);
+            }
+        }
+        4 => {
+            if let Ok(mut reader) = InstanceReader::new(bytes) {
+                for column in 0..reader.num_columns() {
+                    if reader.read_column(column, &mut |_, _| {}).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+        5 => {
+            let _ = frs_from_le_bytes(bytes);
+            let _ = frs_from_be_bytes(bytes);
+        }
+        6 => {
+            let _ = state_body(StateKind::Rng, bytes);
+            for transcript_type in 0..=TranscriptType::Custom as u8 + 1 {
+                let _ = transcript_state_body(transcript_type, bytes);
+            }
+            let _ = custom_hasher_from_state(bytes);
+        }
+        7 => {
+            if let Ok(sections) = decode_sections(bytes) {
+                let _ = ReproJob::from_sections(&sections);
+            }
+        }
+        _ => {
+            let _ = detect_config(&VerifierArtifact::SolidityBytecode(bytes.to_vec()));
+            let _ = detect_config(&VerifierArtifact::VkBytes(bytes.to_vec()));
+            let _ = detect_config(&VerifierArtifact::SnarkJson(
+                String::from_utf8_lossy(bytes).into_owned(),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::poly::{commitment::Params, kzg::commitment::ParamsKZG};
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    use super::{parse_all, NUM_PARSERS};
+    use crate::{
+        bn254::ProvingKey,
+        consts::SEED,
+        instance_source::write_instances,
+        proving_key::{read_metadata, write_with_metadata, Metadata},
+        repro::write_sections,
+        serde_fr::frs_to_le_bytes,
+        srs::{IntegrityError, IntegrityPolicy, SrsIntegrity},
+        state::versioned,
+    };
+
+    /// A valid input for every parser, for the corpus to mutate.
+    fn seeds() -> Vec<Vec<u8>> {
+        let mut params = vec![];
+        ParamsKZG::<Bn256>::unsafe_setup_with_s(2, Fr::from(2))
+            .write(&mut params)
+            .unwrap();
+
+        let mut pk = vec![];
+        let metadata = Metadata::from([("circuit".to_string(), "simple".to_string())]);
+        write_with_metadata(&mut pk, &metadata, b"key").unwrap();
+
+        let values = [Fr::from(1), -Fr::one()];
+        let mut instances = vec![];
+        write_instances(&mut instances, &[&values[..], &[]]).unwrap();
+
+        let mut proof = b"TPRF\x01\x00\x01\x04".to_vec();
+        proof.extend_from_slice(&[0; 32]);
+        proof.extend(frs_to_le_bytes(&values));
+
+        let path = std::env::temp_dir().join(format!("fuzz_seed_{}.repro", std::process::id()));
+        write_sections(
+            &path,
+            &[
+                ("VERSION".to_string(), b"1".to_vec()),
+                ("instances.v1".to_string(), instances.clone()),
+            ],
+        )
+        .unwrap();
+        let repro = std::fs::read(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        [
+            params,
+            pk,
+            proof.clone(),
+            proof[40..].to_vec(),
+            instances,
+            frs_to_le_bytes(&values),
+            versioned(vec![0; 216]),
+            repro,
+            b"\x60\x02\x5a\xfa\x60\x08\x5a\xfa{\"protocol\": \"groth16\"}".to_vec(),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(parser, bytes)| {
+            let mut input = vec![parser as u8];
+            input.extend(bytes);
+            input
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_random_inputs() {
+        let mut rng = XorShiftRng::from_seed(SEED);
+        for i in 0..4096 {
+            let mut input = vec![0; 1 + rng.next_u32() as usize % 256];
+            rng.fill_bytes(&mut input);
+            input[0] = i as u8 % NUM_PARSERS;
+            parse_all(&input);
+        }
+    }
+
+    #[test]
+    fn test_mutated_seeds() {
+        let mut rng = XorShiftRng::from_seed(SEED);
+        for seed in seeds() {
+            parse_all(&seed);
+            for _ in 0..64 {
+                let mut input = seed.clone();
+                for _ in 0..1 + rng.next_u32() % 4 {
+                    let index = 1 + rng.next_u32() as usize % (input.len() - 1);
+                    input[index] ^= 1 << (rng.next_u32() % 8);
+                }
+                input.truncate(1 + rng.next_u32() as usize % input.len());
+                parse_all(&input);
+            }
+        }
+    }
+
+    // The inputs below used to panic.
+
+    #[test]
+    fn test_metadata_length_is_not_allocated() {
+        // A metadata section that claims to be `u64::MAX` bytes long.
+        let mut pk = b"TPKMETA1".to_vec();
+        pk.extend_from_slice(&u64::MAX.to_le_bytes());
+        pk.extend_from_slice(b"key");
+        assert!(read_metadata(&mut pk.as_slice()).is_err());
+
+        let mut input = vec![1];
+        input.extend(pk);
+        parse_all(&input);
+    }
+
+    #[test]
+    fn test_malformed_metadata_fails_to_load() {
+        let mut pk = vec![];
+        write_with_metadata(&mut pk, &Metadata::new(), b"key").unwrap();
+        // The section is 4 bytes long, too short for a key.
+        pk[8] = 4;
+        pk.splice(16..16, [0xff; 4]);
+        assert!(ProvingKey::<G1Affine>::try_from(&pk).is_err());
+    }
+
+    #[test]
+    fn test_params_k_is_bounded() {
+        for k in [32u32, 64, 29] {
+            let mut params = k.to_le_bytes().to_vec();
+            params.extend_from_slice(&[0; 64]);
+            assert!(matches!(
+                SrsIntegrity::verify(&params, &IntegrityPolicy::Structural),
+                Err(IntegrityError::Malformed(_))
+            ));
+        }
+        // A valid `k` with fewer bytes than its points take.
+        let mut params = 20u32.to_le_bytes().to_vec();
+        params.extend_from_slice(&[0; 1024]);
+        assert!(matches!(
+            SrsIntegrity::verify(&params, &IntegrityPolicy::Structural),
+            Err(IntegrityError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,348 @@
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{keygen_pk2, verify_proof},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer},
+};
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use rand_core::SeedableRng;
+
+use crate::{
+    bn254::{
+        Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+        TachyonProver,
+    },
+    circuits::simple_circuit::SimpleCircuit,
+    consts::{TranscriptType, SEED},
+    error::TachyonError,
+    prover::create_proof as tachyon_create_proof,
+    version::check_backend,
+    xor_shift_rng::XORShiftRng,
+};
+
+// The dry run keys and proves `SimpleCircuit` with 2⁴ rows. The params come
+// from a fixed toxic waste rather than from a file: at this size deriving them
+// takes about as long as reading them would, and nothing leaves the process.
+const K: u32 = 4;
+const TOXIC_WASTE: u64 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Cpu,
+    /// Runs the CPU pipeline and additionally checks the GPU MSM against it.
+    /// Requires the "gpu" feature.
+    Gpu,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Backend::Cpu
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DryRunConfig {
+    pub backend: Backend,
+}
+
+#[derive(Clone, Debug)]
+pub struct HealthReport {
+    pub backend: Backend,
+    pub k: u32,
+    pub proof_len: usize,
+    /// Every stage in the order it ran, with how long it took.
+    pub stages: Vec<(&'static str, Duration)>,
+}
+
+impl HealthReport {
+    pub fn total(&self) -> Duration {
+        self.stages.iter().map(|(_, elapsed)| *elapsed).sum()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WarmupReport {
+    pub k_hint: u32,
+    /// Every workload in the order it ran, with how long it took. Empty
+    /// without the "gpu" feature, since the CPU backend has nothing to warm.
+    pub stages: Vec<(&'static str, Duration)>,
+}
+
+impl WarmupReport {
+    pub fn total(&self) -> Duration {
+        self.stages.iter().map(|(_, elapsed)| *elapsed).sum()
+    }
+}
+
+fn timed<T>(
+    stages: &mut Vec<(&'static str, Duration)>,
+    name: &'static str,
+    f: impl FnOnce() -> Result<T, TachyonError>,
+) -> Result<T, TachyonError> {
+    let start = Instant::now();
+    let result = f()?;
+    stages.push((name, start.elapsed()));
+    Ok(result)
+}
+
+/// Keys, proves and verifies a tiny built-in circuit to check that every FFI
+/// path the prover uses is wired up, without reading any file.
+pub fn dry_run(config: &DryRunConfig) -> Result<HealthReport, TachyonError> {
+    check_backend()?;
+    if config.backend == Backend::Gpu && !cfg!(feature = "gpu") {
+        return Err(TachyonError::BackendUnavailable {
+            reason: "built without the \"gpu\" feature".to_owned(),
+        });
+    }
+
+    let mut stages = vec![];
+    let s = Fr::from(TOXIC_WASTE);
+    let params = timed(&mut stages, "setup", || {
+        Ok(ParamsKZG::<Bn256>::unsafe_setup_with_s(K, s))
+    })?;
+
+    let constant = Fr::from(7);
+    let a = Fr::from(2);
+    let b = Fr::from(3);
+    let circuit = SimpleCircuit {
+        constant,
+        a: Value::known(a),
+        b: Value::known(b),
+    };
+    let public_inputs = vec![constant * a.square() * b.square()];
+    let public_inputs2 = vec![&public_inputs[..]];
+    let public_inputs3 = vec![&public_inputs2[..]];
+
+    let (pk, mut tachyon_pk) = timed(&mut stages, "keygen", || {
+        let pk = keygen_pk2(&params, &circuit)?;
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .expect("writing to a Vec should not fail");
+        let tachyon_pk = TachyonProvingKey::<G1Affine>::try_from(pk_bytes.as_slice())
+            .map_err(TachyonError::MalformedKey)?;
+        Ok((pk, tachyon_pk))
+    })?;
+
+    let proof = timed(&mut stages, "prove", || {
+        let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::try_new(
+            TranscriptType::Blake2b as u8,
+            K,
+            &s,
+        )?;
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        tachyon_create_proof::<_, _, _, _, _>(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            public_inputs3.as_slice(),
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )?;
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+        Ok(proof)
+    })?;
+
+    timed(&mut stages, "verify", || {
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, SingleStrategy<_>>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            public_inputs3.as_slice(),
+            &mut transcript,
+        )
+        .map_err(TachyonError::Verification)
+    })?;
+
+    #[cfg(feature = "gpu")]
+    if config.backend == Backend::Gpu {
+        timed(&mut stages, "msm_gpu", || {
+            check_msm_gpu(params.get_g(), K as u8)
+        })?;
+    }
+
+    Ok(HealthReport {
+        backend: config.backend,
+        k: K,
+        proof_len: proof.len(),
+        stages,
+    })
+}
+
+/// Runs the dry run on the GPU, an FFT of `2^k_hint` values and a GPU MSM
+/// sized for `2^k_hint` bases, so that the kernels are compiled and the device
+/// context is created before the first real proof. See
+/// [`crate::bn254::SHPlonkProver::warm_up()`].
+pub(crate) fn warm_up(k_hint: u32) -> Result<WarmupReport, TachyonError> {
+    check_backend()?;
+    #[allow(unused_mut)]
+    let mut stages = vec![];
+
+    #[cfg(feature = "gpu")]
+    {
+        use halo2curves::FieldExt;
+
+        use crate::bn254::{ffi, Fr as CppFr};
+
+        let report = dry_run(&DryRunConfig {
+            backend: Backend::Gpu,
+        })?;
+        stages.push(("dry_run", report.total()));
+
+        timed(&mut stages, "fft", || {
+            let zeta = Fr::ZETA;
+            let cpp_zeta = unsafe { std::mem::transmute::<_, &CppFr>(&zeta) };
+            let poly = ffi::coset_ifft(&ffi::empty_coset_evals(k_hint), k_hint, cpp_zeta);
+            ffi::coset_fft(&poly, k_hint, cpp_zeta);
+            Ok(())
+        })?;
+
+        timed(&mut stages, "msm_gpu", || {
+            let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(K, Fr::from(TOXIC_WASTE));
+            check_msm_gpu(params.get_g(), k_hint as u8)
+        })?;
+    }
+
+    Ok(WarmupReport { k_hint, stages })
+}
+
+#[cfg(feature = "gpu")]
+fn check_msm_gpu(bases: &[G1Affine], degree: u8) -> Result<(), TachyonError> {
+    use halo2_proofs::arithmetic::best_multiexp;
+    use halo2curves::bn256::G1;
+
+    use crate::bn254::{ffi, Fr as CppFr, G1Point2 as CppG1Point2};
+
+    let scalars: Vec<Fr> = (1..=bases.len() as u64).map(Fr::from).collect();
+    let expected = best_multiexp(&scalars, bases);
+    let actual = unsafe {
+        let cpp_bases: &[CppG1Point2] = std::mem::transmute(bases);
+        let cpp_scalars: &[CppFr] = std::mem::transmute(scalars.as_slice());
+        let mut msm = ffi::create_g1_msm_gpu(degree, 0);
+        let actual = ffi::g1_point2_msm_gpu(&mut *msm, cpp_bases, cpp_scalars);
+        ffi::destroy_g1_msm_gpu(msm);
+        *std::mem::transmute::<_, Box<G1>>(actual)
+    };
+    if actual != expected {
+        return Err(TachyonError::BackendUnavailable {
+            reason: "GPU MSM disagrees with the CPU".to_owned(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{dry_run, Backend, DryRunConfig};
+    #[cfg(not(feature = "gpu"))]
+    use crate::error::TachyonError;
+    use crate::{bn254::SHPlonkProver, consts::TranscriptType};
+
+    fn new_prover() -> SHPlonkProver<KZGCommitmentScheme<Bn256>> {
+        SHPlonkProver::new(TranscriptType::Blake2b as u8, 4, &Fr::from(2))
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_dry_run_cpu() {
+        let report = dry_run(&DryRunConfig::default()).unwrap();
+        assert_eq!(report.backend, Backend::Cpu);
+        assert!(report.proof_len > 0);
+        assert_eq!(
+            report
+                .stages
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+            vec!["setup", "keygen", "prove", "verify"]
+        );
+        assert!(report.total() < Duration::from_secs(1), "{:?}", report);
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_dry_run_gpu() {
+        let report = dry_run(&DryRunConfig {
+            backend: Backend::Gpu,
+        })
+        .unwrap();
+        assert_eq!(report.stages.last().unwrap().0, "msm_gpu");
+        assert!(report.total() < Duration::from_secs(1), "{:?}", report);
+    }
+
+    #[cfg(not(feature = "gpu"))]
+    #[test]
+    fn test_dry_run_gpu_unavailable() {
+        assert!(matches!(
+            dry_run(&DryRunConfig {
+                backend: Backend::Gpu,
+            }),
+            Err(TachyonError::BackendUnavailable { .. })
+        ));
+    }
+
+    #[cfg(not(feature = "gpu"))]
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_warm_up_cpu() {
+        let mut prover = new_prover();
+        let start = std::time::Instant::now();
+        let report = prover.warm_up(10).unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(report.k_hint, 10);
+        assert!(report.stages.is_empty());
+        assert!(elapsed < Duration::from_millis(10), "{:?}", elapsed);
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_warm_up_gpu() {
+        let mut prover = new_prover();
+        let report = prover.warm_up(10).unwrap();
+        assert_eq!(
+            report
+                .stages
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+            vec!["dry_run", "fft", "msm_gpu"]
+        );
+
+        let elapsed = || {
+            dry_run(&DryRunConfig {
+                backend: Backend::Gpu,
+            })
+            .unwrap()
+            .total()
+        };
+        let first = elapsed();
+        let mut rest = (0..5).map(|_| elapsed()).collect::<Vec<_>>();
+        rest.sort();
+        let median = rest[rest.len() / 2];
+        // Timings on a shared machine are noisy, so the first proof only has
+        // to be within half again of the median of the following ones.
+        assert!(
+            first <= median * 3 / 2 + Duration::from_millis(5),
+            "first: {:?}, median: {:?}",
+            first,
+            median
+        );
+    }
+}
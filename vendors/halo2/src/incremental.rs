@@ -0,0 +1,421 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use halo2_proofs::{
+    plonk::Circuit,
+    poly::commitment::CommitmentScheme,
+    transcript::{EncodedChallenge, TranscriptWriterBuffer},
+};
+use rand_core::SeedableRng;
+
+use crate::{
+    annotation::RowOverflow,
+    bn254::{ProvingKey as TachyonProvingKey, TachyonProver, TranscriptWriteState},
+    debug::verify_constraints,
+    error::TachyonError,
+    precommit::check_precommitted_advice,
+    prover::{
+        check_advice_absorb_order, check_multi_open_scheme, check_transcript_type, fft_chunk_log2,
+        prepare_instances, write_audit, PreparedInstances, ProofGuard, ProofOptions, ProofSteps,
+    },
+    repro::{capture_proof, WitnessColumn},
+    rng::derive_seed,
+    self_check::self_check_bases,
+    transcript::RecordingTranscript,
+    version::check_backend,
+    xor_shift_rng::XORShiftRng as TachyonXORShiftRng,
+};
+
+#[derive(Debug)]
+pub enum StepResult {
+    /// The fraction of the steps that have run, in `[0, 1)`.
+    InProgress(f32),
+    /// The finalized transcript followed by the backend's proof.
+    Done(Vec<u8>),
+    Err(TachyonError),
+}
+
+enum State<Scheme: CommitmentScheme, ConcreteCircuit: Circuit<Scheme::Scalar>> {
+    Start,
+    Proving {
+        prepared: PreparedInstances<Scheme::Curve>,
+        steps: ProofSteps<Scheme, ConcreteCircuit>,
+        /// The seed of the RNG, for [`ProofOptions::capture_repro`].
+        seed: [u8; 16],
+    },
+    Finished,
+}
+
+/// Creates the same proof as [`crate::prover::create_proof_with_options()`],
+/// but a bounded amount of work at a time, so that a single-threaded embedder
+/// can keep its event loop running in between.
+///
+/// The work is split into the checks and instance preparation, the synthesis
+/// and advice commitments of each circuit in each phase, the challenges of
+/// each phase, and the backend's part of the proof. The last one is a single
+/// call into libtachyon and can't be split, so it takes most of the time for
+/// larger circuits.
+pub struct IncrementalProof<
+    'a,
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E> + TranscriptWriterBuffer<Vec<u8>, Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+> {
+    prover: ProofGuard<'a, P>,
+    pk: &'a mut TachyonProvingKey<Scheme::Curve>,
+    circuit: &'a ConcreteCircuit,
+    instances: &'a [&'a [Scheme::Scalar]],
+    options: ProofOptions,
+    transcript: Option<RecordingTranscript<Scheme::Curve, T>>,
+    state: State<Scheme, ConcreteCircuit>,
+    _marker: PhantomData<E>,
+}
+
+impl<
+        'a,
+        Scheme: CommitmentScheme,
+        P: TachyonProver<Scheme> + fmt::Debug,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptWriteState<Scheme::Curve, E> + TranscriptWriterBuffer<Vec<u8>, Scheme::Curve, E>,
+        ConcreteCircuit: Circuit<Scheme::Scalar>,
+    > fmt::Debug for IncrementalProof<'a, Scheme, P, E, T, ConcreteCircuit>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.state {
+            State::Start => "Start",
+            State::Proving { .. } => "Proving",
+            State::Finished => "Finished",
+        };
+        f.debug_struct("IncrementalProof")
+            .field("prover", &*self.prover)
+            .field("pk", &self.pk)
+            .field("num_instance_columns", &self.instances.len())
+            .field("options", &self.options)
+            .field("state", &state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<
+        'a,
+        Scheme: CommitmentScheme,
+        P: TachyonProver<Scheme>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptWriteState<Scheme::Curve, E> + TranscriptWriterBuffer<Vec<u8>, Scheme::Curve, E>,
+        ConcreteCircuit: Circuit<Scheme::Scalar>,
+    > IncrementalProof<'a, Scheme, P, E, T, ConcreteCircuit>
+{
+    /// No work is done until the first [`IncrementalProof::step()`].
+    pub fn new(
+        prover: &'a mut P,
+        pk: &'a mut TachyonProvingKey<Scheme::Curve>,
+        circuit: &'a ConcreteCircuit,
+        instances: &'a [&'a [Scheme::Scalar]],
+        options: ProofOptions,
+        transcript: T,
+    ) -> Self {
+        IncrementalProof {
+            prover: ProofGuard::new(prover),
+            pk,
+            circuit,
+            instances,
+            options,
+            transcript: Some(RecordingTranscript::new(transcript)),
+            state: State::Start,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Runs steps until `budget` has passed, and at least one. A step isn't
+    /// interrupted, so a call can overrun `budget` by up to the longest step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called again after returning [`StepResult::Done`] or
+    /// [`StepResult::Err`].
+    pub fn step(&mut self, budget: Duration) -> StepResult {
+        let start = Instant::now();
+        loop {
+            match self.run_next() {
+                Ok(Some(proof)) => {
+                    self.state = State::Finished;
+                    return StepResult::Done(proof);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    self.state = State::Finished;
+                    return StepResult::Err(e);
+                }
+            }
+            if start.elapsed() >= budget {
+                return StepResult::InProgress(self.progress());
+            }
+        }
+    }
+
+    /// The prover, e.g, to read its metrics between steps. It can't be used
+    /// for anything else until the proof is dropped.
+    pub fn prover(&self) -> &P {
+        &self.prover
+    }
+
+    fn progress(&self) -> f32 {
+        match &self.state {
+            State::Start => 0.0,
+            // The first step prepares the instances.
+            State::Proving { steps, .. } => {
+                (1 + steps.num_finished()) as f32 / (1 + steps.num_steps()) as f32
+            }
+            State::Finished => 1.0,
+        }
+    }
+
+    fn run_next(&mut self) -> Result<Option<Vec<u8>>, TachyonError> {
+        match &mut self.state {
+            State::Start => {
+                self.state = self.start()?;
+                Ok(None)
+            }
+            State::Proving {
+                prepared,
+                steps,
+                seed,
+            } => {
+                let transcript = self.transcript.as_mut().unwrap();
+                let mut overflow: Option<RowOverflow> = None;
+                steps
+                    .run_next(
+                        self.prover.prover_mut(),
+                        self.pk,
+                        std::slice::from_ref(self.circuit),
+                        prepared,
+                        transcript,
+                        &mut overflow,
+                    )
+                    .map_err(|e| steps.error(e, overflow))?;
+                if !steps.is_finished() {
+                    return Ok(None);
+                }
+                let seed = *seed;
+                let witness = steps.take_captured_witness();
+                self.finish(seed, witness).map(Some)
+            }
+            State::Finished => panic!("the proof has already finished"),
+        }
+    }
+
+    fn start(&mut self) -> Result<State<Scheme, ConcreteCircuit>, TachyonError> {
+        let start = Instant::now();
+        check_backend()?;
+        check_transcript_type(&*self.prover, self.transcript.as_ref().unwrap())?;
+        check_multi_open_scheme(&*self.prover, self.pk)?;
+        check_advice_absorb_order(self.pk, &self.options)?;
+        let fft_chunk_log2 = fft_chunk_log2(&*self.prover, self.pk, 1, &self.options);
+        self.options
+            .limits
+            .check_before(&*self.prover, self.pk, 1, fft_chunk_log2)?;
+        let self_check = self_check_bases(&self.options, self.prover.n())?;
+        check_precommitted_advice(&*self.prover, self.pk, 1, &self.options)?;
+        if self.options.verify_constraints_first {
+            verify_constraints(self.prover.k(), self.circuit, self.instances)?;
+        }
+        let gpu_stages = self.options.stage_placement.gpu_stages(self.prover.k())?;
+        let master_key = self
+            .prover
+            .master_key()
+            .ok_or(TachyonError::MissingMasterKey)?;
+        let seed = derive_seed(master_key, &self.options.rng_context);
+        let rng = TachyonXORShiftRng::from_seed(seed);
+        let prover = self.prover.prover_mut();
+        prover.set_intra_proof_parallelism(self.options.intra_proof_parallelism.max(1));
+        prover.set_num_threads(self.options.num_threads);
+        prover.set_record_gate_costs(self.options.gate_costs);
+        prover.set_gpu_stages(gpu_stages);
+        prover.set_fft_chunk_log2(fft_chunk_log2);
+        prover.set_lagrange_basis_policy(self.options.lagrange_bases)?;
+        let prepared = prepare_instances(prover, self.pk, &[self.instances])?;
+        let mut steps = ProofSteps::new(self.pk, 1, rng, self.options.column_report);
+        if self.options.capture_repro.is_some() {
+            steps.capture_witness();
+        }
+        if self.options.region_layout {
+            steps.capture_layout();
+        }
+        if self.options.gate_costs {
+            steps.record_gate_costs();
+        }
+        if self.options.evaluations {
+            steps.read_evaluations();
+        }
+        if let Some(bases) = self_check {
+            steps.self_check(self.options.self_check, bases);
+        }
+        steps.precommit(&self.options.precommitted_advice);
+        // The wall time includes the time between the steps.
+        steps.limit(self.options.limits, start);
+        steps.cancel_on(self.options.cancel.clone());
+        Ok(State::Proving {
+            prepared,
+            steps,
+            seed,
+        })
+    }
+
+    fn finish(
+        &mut self,
+        seed: [u8; 16],
+        witness: Vec<WitnessColumn>,
+    ) -> Result<Vec<u8>, TachyonError> {
+        let (transcript, log) = self.transcript.take().unwrap().into_parts();
+        if let Some(path) = &self.options.audit_log {
+            write_audit(
+                &*self.prover,
+                self.pk,
+                &[self.instances],
+                &log,
+                path,
+                &self.options,
+            )?;
+        }
+        if let Some(capture) = &self.options.capture_repro {
+            capture_proof(
+                capture,
+                &*self.prover,
+                &[self.instances],
+                &self.options,
+                seed,
+                witness,
+                &log,
+            )?;
+        }
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&self.prover.get_proof());
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::TranscriptWriterBuffer,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{IncrementalProof, StepResult};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::TranscriptType,
+        error::TachyonError,
+        prover::{create_proof_with_options, ProofOptions},
+        rng::proof_context,
+    };
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_incremental_proof() {
+        let k = 8;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = vec![&public_inputs[..]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let options = ProofOptions {
+            rng_context: proof_context(b"simple", 0),
+            ..Default::default()
+        };
+        let new_prover = || {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            prover
+        };
+
+        let blocking_proof = {
+            let mut prover = new_prover();
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&instances[..]],
+                &options,
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+
+        let mut prover = new_prover();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut incremental = IncrementalProof::new(
+            &mut prover,
+            &mut tachyon_pk,
+            &circuit,
+            &instances,
+            options.clone(),
+            TachyonBlake2bWrite::init(vec![]),
+        );
+        // A zero budget runs exactly one step per call.
+        let mut progress = vec![];
+        let proof = loop {
+            match incremental.step(Duration::ZERO) {
+                StepResult::InProgress(fraction) => progress.push(fraction),
+                StepResult::Done(proof) => break proof,
+                StepResult::Err(e) => panic!("proof generation should not fail: {}", e),
+            }
+        };
+        assert!(progress.len() > 1);
+        assert!(progress.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(progress.iter().all(|fraction| *fraction < 1.0));
+        assert_eq!(proof, blocking_proof);
+
+        // Without a master key, the first step fails.
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut incremental = IncrementalProof::new(
+            &mut prover,
+            &mut tachyon_pk,
+            &circuit,
+            &instances,
+            options,
+            TachyonBlake2bWrite::init(vec![]),
+        );
+        assert!(matches!(
+            incremental.step(Duration::from_secs(60)),
+            StepResult::Err(TachyonError::MissingMasterKey)
+        ));
+    }
+}
@@ -0,0 +1,425 @@
+// Public inputs usually arrive from outside the process, and a value that
+// isn't a canonical scalar or a column that doesn't fit the circuit would only
+// surface as a failed verification downstream. The functions below check them
+// up front and report every problem at once, each with its column and row, so
+// that the caller can reject the request with all of them. Large inputs are
+// checked in chunks, in parallel with the "rayon" feature.
+
+use std::fmt;
+
+use halo2_proofs::arithmetic::CurveAffine;
+use halo2curves::bn256::Fr;
+
+use crate::{
+    analyze::usable_rows,
+    bn254::ProvingKey as TachyonProvingKey,
+    par,
+    serde_fr::{fr_from_be_bytes, fr_from_le_bytes, FrBytesError, FR_BYTES},
+};
+
+/// The number of values of a column checked by one job.
+const CHUNK_ROWS: usize = 1 << 12;
+
+/// The instance columns a circuit takes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstanceShape {
+    pub num_columns: usize,
+    /// The number of values a column can hold at most.
+    pub usable_rows: usize,
+}
+
+impl InstanceShape {
+    /// The instance columns of a circuit with `2ᵏ` rows that is keyed by `pk`.
+    pub fn of<C: CurveAffine>(k: u32, pk: &TachyonProvingKey<C>) -> Self {
+        InstanceShape {
+            num_columns: pk.num_instance_columns(),
+            usable_rows: usable_rows(k, pk.blinding_factors() as usize),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstanceErrorReason {
+    /// Another number of columns than the circuit takes is given. It is
+    /// reported at the first column past the shorter of both.
+    ColumnCount { expected: usize, found: usize },
+    /// The column holds more values than it has usable rows. It is reported
+    /// at the first row past them.
+    OutOfRange { usable_rows: usize },
+    /// The value is not smaller than the modulus.
+    NonCanonical,
+    /// The bytes of the column aren't a whole number of scalars.
+    Length { len: usize },
+}
+
+/// A problem with one column of public inputs, or one value of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstanceValidationError {
+    pub column: usize,
+    /// The row of the value, if the problem is with a value.
+    pub row: Option<usize>,
+    pub reason: InstanceErrorReason,
+}
+
+impl fmt::Display for InstanceValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.row {
+            Some(row) => write!(f, "instance column {} at row {}: ", self.column, row)?,
+            None => write!(f, "instance column {}: ", self.column)?,
+        }
+        match self.reason {
+            InstanceErrorReason::ColumnCount { expected, found } => write!(
+                f,
+                "{} columns are given, but the circuit takes {}",
+                found, expected
+            ),
+            InstanceErrorReason::OutOfRange { usable_rows } => {
+                write!(f, "the column has only {} usable rows", usable_rows)
+            }
+            InstanceErrorReason::NonCanonical => {
+                write!(f, "the value is not smaller than the modulus")
+            }
+            InstanceErrorReason::Length { len } => write!(
+                f,
+                "{} bytes is not a multiple of {} bytes per scalar",
+                len, FR_BYTES
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InstanceValidationError {}
+
+/// A public input as it is given to [`validate()`].
+pub trait InstanceValue: Sync {
+    /// Returns the scalar the value stands for, or `None` if it isn't
+    /// canonical.
+    fn to_fr(&self) -> Option<Fr>;
+}
+
+impl InstanceValue for Fr {
+    fn to_fr(&self) -> Option<Fr> {
+        Some(*self)
+    }
+}
+
+/// The little-endian encoding of a scalar, as everywhere in this crate.
+impl InstanceValue for [u8; FR_BYTES] {
+    fn to_fr(&self) -> Option<Fr> {
+        fr_from_le_bytes(self).ok()
+    }
+}
+
+/// Checks `inputs`, the instance columns of one circuit, against `shape`, and
+/// returns every problem found, ordered by column and row. The values are
+/// checked in chunks of every column, which run on the rayon thread pool with
+/// the "rayon" feature, see [`crate::par::map()`].
+pub fn validate<V: InstanceValue>(
+    shape: &InstanceShape,
+    inputs: &[&[V]],
+) -> Result<(), Vec<InstanceValidationError>> {
+    let column_lens = inputs.iter().map(|column| column.len()).collect::<Vec<_>>();
+    parse_columns(shape, &column_lens, vec![], |column, row| {
+        inputs[column][row].to_fr()
+    })
+    .map(|_| ())
+}
+
+/// Parses the instance columns of one circuit, each given as consecutive
+/// canonical little-endian encodings of its values, and checks them against
+/// `shape`. Returns every problem found, ordered by column and row.
+pub fn parse_le_bytes(
+    shape: &InstanceShape,
+    columns: &[&[u8]],
+) -> Result<Vec<Vec<Fr>>, Vec<InstanceValidationError>> {
+    parse_bytes(shape, columns, fr_from_le_bytes)
+}
+
+/// Like [`parse_le_bytes()`], but with big-endian encodings.
+pub fn parse_be_bytes(
+    shape: &InstanceShape,
+    columns: &[&[u8]],
+) -> Result<Vec<Vec<Fr>>, Vec<InstanceValidationError>> {
+    parse_bytes(shape, columns, fr_from_be_bytes)
+}
+
+/// Parses the values of the column at `column` from consecutive canonical
+/// little-endian encodings, returning every problem found. The number of
+/// values isn't checked.
+pub(crate) fn parse_le_column(
+    column: usize,
+    bytes: &[u8],
+) -> Result<Vec<Fr>, Vec<InstanceValidationError>> {
+    let shape = InstanceShape {
+        num_columns: 1,
+        usable_rows: usize::MAX,
+    };
+    parse_le_bytes(&shape, &[bytes])
+        .map(|mut columns| columns.pop().unwrap())
+        .map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|error| InstanceValidationError { column, ..error })
+                .collect()
+        })
+}
+
+fn parse_bytes(
+    shape: &InstanceShape,
+    columns: &[&[u8]],
+    from_bytes: fn(&[u8; FR_BYTES]) -> Result<Fr, FrBytesError>,
+) -> Result<Vec<Vec<Fr>>, Vec<InstanceValidationError>> {
+    let mut errors = vec![];
+    // A column that isn't a whole number of scalars has no values to check.
+    let column_lens = columns
+        .iter()
+        .enumerate()
+        .map(|(column, bytes)| {
+            if bytes.len() % FR_BYTES == 0 {
+                bytes.len() / FR_BYTES
+            } else {
+                errors.push(InstanceValidationError {
+                    column,
+                    row: None,
+                    reason: InstanceErrorReason::Length { len: bytes.len() },
+                });
+                0
+            }
+        })
+        .collect::<Vec<_>>();
+    parse_columns(shape, &column_lens, errors, |column, row| {
+        let bytes = &columns[column][row * FR_BYTES..(row + 1) * FR_BYTES];
+        from_bytes(bytes.try_into().unwrap()).ok()
+    })
+}
+
+/// Parses the values of every column with `value`, which returns `None` for a
+/// value that isn't canonical, and checks the columns against `shape`.
+/// `errors` are the problems found before, which are merged in.
+fn parse_columns(
+    shape: &InstanceShape,
+    column_lens: &[usize],
+    mut errors: Vec<InstanceValidationError>,
+    value: impl Fn(usize, usize) -> Option<Fr> + Sync + Send,
+) -> Result<Vec<Vec<Fr>>, Vec<InstanceValidationError>> {
+    if column_lens.len() != shape.num_columns {
+        errors.push(InstanceValidationError {
+            column: column_lens.len().min(shape.num_columns),
+            row: None,
+            reason: InstanceErrorReason::ColumnCount {
+                expected: shape.num_columns,
+                found: column_lens.len(),
+            },
+        });
+    }
+    for (column, len) in column_lens.iter().enumerate() {
+        if *len > shape.usable_rows {
+            errors.push(InstanceValidationError {
+                column,
+                row: Some(shape.usable_rows),
+                reason: InstanceErrorReason::OutOfRange {
+                    usable_rows: shape.usable_rows,
+                },
+            });
+        }
+    }
+
+    let chunks = column_lens
+        .iter()
+        .enumerate()
+        .flat_map(|(column, len)| {
+            (0..*len)
+                .step_by(CHUNK_ROWS)
+                .map(move |start| (column, start, (start + CHUNK_ROWS).min(*len)))
+        })
+        .collect::<Vec<_>>();
+    let parsed = par::map(&chunks, |(column, start, end)| {
+        let mut values = Vec::with_capacity(end - start);
+        let mut rows = vec![];
+        for row in *start..*end {
+            match value(*column, row) {
+                Some(value) => values.push(value),
+                None => rows.push(row),
+            }
+        }
+        (values, rows)
+    });
+
+    let mut columns = column_lens
+        .iter()
+        .map(|len| Vec::with_capacity(*len))
+        .collect::<Vec<_>>();
+    for ((column, _, _), (values, rows)) in chunks.iter().zip(parsed) {
+        columns[*column].extend(values);
+        errors.extend(rows.into_iter().map(|row| InstanceValidationError {
+            column: *column,
+            row: Some(row),
+            reason: InstanceErrorReason::NonCanonical,
+        }));
+    }
+    if errors.is_empty() {
+        Ok(columns)
+    } else {
+        errors.sort_by_key(|error| (error.column, error.row));
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use halo2curves::bn256::Fr;
+
+    use super::{
+        parse_be_bytes, parse_le_bytes, validate, InstanceErrorReason, InstanceShape,
+        InstanceValidationError, CHUNK_ROWS,
+    };
+    use crate::serde_fr::{fr_to_le_bytes, frs_to_be_bytes, frs_to_le_bytes, FR_BYTES};
+
+    const SHAPE: InstanceShape = InstanceShape {
+        num_columns: 2,
+        usable_rows: 4,
+    };
+
+    fn error(
+        column: usize,
+        row: Option<usize>,
+        reason: InstanceErrorReason,
+    ) -> InstanceValidationError {
+        InstanceValidationError {
+            column,
+            row,
+            reason,
+        }
+    }
+
+    #[test]
+    fn test_valid_inputs() {
+        let columns = vec![vec![Fr::from(1), -Fr::one()], vec![Fr::from(3)]];
+        let slices = columns.iter().map(|values| &values[..]).collect::<Vec<_>>();
+        assert_eq!(validate(&SHAPE, &slices), Ok(()));
+
+        let le = columns
+            .iter()
+            .map(|values| frs_to_le_bytes(values))
+            .collect::<Vec<_>>();
+        let le = le.iter().map(|bytes| &bytes[..]).collect::<Vec<_>>();
+        assert_eq!(parse_le_bytes(&SHAPE, &le).unwrap(), columns);
+
+        let be = columns
+            .iter()
+            .map(|values| frs_to_be_bytes(values))
+            .collect::<Vec<_>>();
+        let be = be.iter().map(|bytes| &bytes[..]).collect::<Vec<_>>();
+        assert_eq!(parse_be_bytes(&SHAPE, &be).unwrap(), columns);
+    }
+
+    #[test]
+    fn test_non_canonical_values() {
+        let values = [fr_to_le_bytes(&Fr::one()), [0xff; FR_BYTES], [0; FR_BYTES]];
+        assert_eq!(
+            validate(&SHAPE, &[&values[..], &[]]),
+            Err(vec![error(0, Some(1), InstanceErrorReason::NonCanonical)])
+        );
+
+        // -1 + 1, i.e, the modulus.
+        let mut modulus = frs_to_le_bytes(&[-Fr::one()]);
+        modulus[0] += 1;
+        assert_eq!(
+            parse_le_bytes(&SHAPE, &[&[], &modulus]),
+            Err(vec![error(1, Some(0), InstanceErrorReason::NonCanonical)])
+        );
+    }
+
+    #[test]
+    fn test_wrong_column_count() {
+        let values = [Fr::from(1)];
+        assert_eq!(
+            validate(&SHAPE, &[&values[..]]),
+            Err(vec![error(
+                1,
+                None,
+                InstanceErrorReason::ColumnCount {
+                    expected: 2,
+                    found: 1
+                }
+            )])
+        );
+        assert_eq!(
+            validate(&SHAPE, &[&values[..], &values[..], &values[..]]),
+            Err(vec![error(
+                2,
+                None,
+                InstanceErrorReason::ColumnCount {
+                    expected: 2,
+                    found: 3
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn test_all_errors_collected() {
+        let mut first = frs_to_le_bytes(&[Fr::from(1); 6]);
+        first[FR_BYTES * 2..FR_BYTES * 3].fill(0xff);
+        first[FR_BYTES * 5..].fill(0xff);
+        let second = [0; FR_BYTES + 1];
+        let third = [0xff; FR_BYTES];
+        assert_eq!(
+            parse_le_bytes(&SHAPE, &[&first, &second, &third]),
+            Err(vec![
+                error(0, Some(2), InstanceErrorReason::NonCanonical),
+                error(
+                    0,
+                    Some(4),
+                    InstanceErrorReason::OutOfRange { usable_rows: 4 }
+                ),
+                error(0, Some(5), InstanceErrorReason::NonCanonical),
+                error(1, None, InstanceErrorReason::Length { len: FR_BYTES + 1 }),
+                error(
+                    2,
+                    None,
+                    InstanceErrorReason::ColumnCount {
+                        expected: 2,
+                        found: 3
+                    }
+                ),
+                error(2, Some(0), InstanceErrorReason::NonCanonical),
+            ])
+        );
+    }
+
+    // Checks inputs of several chunks per column, which are parsed on the
+    // rayon thread pool with the "rayon" feature, and that the values and the
+    // errors come back in order either way.
+    #[test]
+    fn test_large_inputs() {
+        let shape = InstanceShape {
+            num_columns: 2,
+            usable_rows: 3 * CHUNK_ROWS,
+        };
+        let values = (0..3 * CHUNK_ROWS as u64).map(Fr::from).collect::<Vec<_>>();
+        let bytes = frs_to_le_bytes(&values);
+        assert_eq!(
+            parse_le_bytes(&shape, &[&bytes, &bytes[..CHUNK_ROWS * FR_BYTES]]),
+            Ok(vec![values.clone(), values[..CHUNK_ROWS].to_vec()])
+        );
+
+        let mut invalid = bytes;
+        let rows = [0, CHUNK_ROWS - 1, CHUNK_ROWS, 3 * CHUNK_ROWS - 1];
+        for row in rows {
+            invalid[row * FR_BYTES..(row + 1) * FR_BYTES].fill(0xff);
+        }
+        assert_eq!(
+            parse_le_bytes(&shape, &[&invalid, &invalid]),
+            Err([0, 1]
+                .iter()
+                .flat_map(|column| rows.iter().map(|row| error(
+                    *column,
+                    Some(*row),
+                    InstanceErrorReason::NonCanonical
+                )))
+                .collect())
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use std::{collections::VecDeque, fmt, io};
+
+#[cfg(not(feature = "stub-backend"))]
+use cxx::UniquePtr;
+use ff::PrimeField;
+use halo2_proofs::{
+    plonk::{verify_proof as halo2_verify_proof, VerifyingKey},
+    poly::kzg::{
+        commitment::{KZGCommitmentScheme, ParamsKZG},
+        multiopen::VerifierSHPLONK,
+        strategy::SingleStrategy,
+    },
+    transcript::{
+        Challenge255, EncodedChallenge, PoseidonWrite, Transcript, TranscriptRead,
+        TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use halo2curves::{
+    bn256::{Bn256, Fr, G1Affine},
+    FieldExt,
+};
+
+#[cfg(feature = "stub-backend")]
+use crate::stub::UniquePtr;
+use crate::{
+    advice_order::AdviceOrderRead,
+    aggregate::TranscriptReadType,
+    bn254::ffi,
+    consts::{AbsorbOrder, InstanceAbsorption, MultiOpenScheme, TranscriptType},
+    error::TachyonError,
+    proving_key::{
+        advice_absorb_order_from_metadata, instance_absorption_from_metadata,
+        multi_open_scheme_from_metadata, Metadata,
+    },
+};
+
+// The same personalization as halo2's Blake2b transcript, which the backend's
+// writer starts from.
+const BLAKE2B_PERSONALIZATION: &[u8; 16] = b"Halo2-Transcript";
+
+fn column_bytes(values: &[Fr]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(values.len() * 32);
+    for value in values {
+        bytes.extend_from_slice(value.to_repr().as_ref());
+    }
+    bytes
+}
+
+/// The digest an instance column is absorbed as with
+/// [`InstanceAbsorption::HashedColumn`] into a transcript of
+/// `transcript_type`. A Poseidon transcript absorbs the values into a fresh
+/// Poseidon sponge and squeezes it, so that a verifier inside a circuit
+/// hashes the column with the same sponge it reads the proof with. Every
+/// other transcript takes the Blake2b-512 hash of the little-endian values,
+/// reduced into `Fr`.
+pub fn column_digest(values: &[Fr], transcript_type: TranscriptType) -> Fr {
+    match transcript_type {
+        TranscriptType::Poseidon => {
+            let mut sponge = PoseidonWrite::<_, G1Affine, Challenge255<_>>::init(Vec::<u8>::new());
+            for value in values {
+                sponge
+                    .common_scalar(*value)
+                    .expect("absorbing into a Poseidon transcript can't fail");
+            }
+            sponge.squeeze_challenge().get_scalar()
+        }
+        TranscriptType::Blake2b | TranscriptType::Sha256 | TranscriptType::Custom => {
+            let hash = blake2b_simd::Params::new()
+                .hash_length(64)
+                .personal(BLAKE2B_PERSONALIZATION)
+                .hash(&column_bytes(values));
+            Fr::from_bytes_wide(hash.as_array())
+        }
+    }
+}
+
+/// Same as [`column_digest()`], but hashed by the backend with a single call.
+pub(crate) fn backend_column_digest(values: &[Fr], transcript_type: TranscriptType) -> Fr {
+    let mut digest = BackendColumnDigest::new(transcript_type);
+    digest.update(values);
+    digest.finalize()
+}
+
+enum BackendHasher {
+    Blake2b(UniquePtr<ffi::Blake2bWriter>),
+    Poseidon(UniquePtr<ffi::PoseidonWriter>),
+}
+
+/// Computes [`column_digest()`] with the backend a chunk of values at a time,
+/// for columns that aren't in memory at once.
+pub(crate) struct BackendColumnDigest {
+    hasher: BackendHasher,
+}
+
+impl BackendColumnDigest {
+    pub(crate) fn new(transcript_type: TranscriptType) -> Self {
+        let hasher = match transcript_type {
+            TranscriptType::Poseidon => BackendHasher::Poseidon(ffi::new_poseidon_writer()),
+            TranscriptType::Blake2b | TranscriptType::Sha256 | TranscriptType::Custom => {
+                BackendHasher::Blake2b(ffi::new_blake2b_writer())
+            }
+        };
+        BackendColumnDigest { hasher }
+    }
+
+    pub(crate) fn update(&mut self, values: &[Fr]) {
+        match &mut self.hasher {
+            BackendHasher::Blake2b(writer) => writer.pin_mut().update(&column_bytes(values)),
+            // The Poseidon writer reads the values as they are laid out in
+            // memory, like `bn254::PoseidonWrite` absorbs them.
+            BackendHasher::Poseidon(writer) => writer.pin_mut().update(unsafe {
+                std::slice::from_raw_parts(
+                    values.as_ptr() as *const u8,
+                    std::mem::size_of_val(values),
+                )
+            }),
+        }
+    }
+
+    pub(crate) fn finalize(self) -> Fr {
+        match self.hasher {
+            BackendHasher::Blake2b(mut writer) => {
+                let mut hash = [0; 64];
+                writer.pin_mut().finalize(&mut hash);
+                Fr::from_bytes_wide(&hash)
+            }
+            BackendHasher::Poseidon(mut writer) => {
+                *unsafe { std::mem::transmute::<_, Box<Fr>>(writer.pin_mut().squeeze()) }
+            }
+        }
+    }
+}
+
+/// Wraps the transcript of a verifier so that it absorbs the instances of a
+/// proof created with [`InstanceAbsorption::HashedColumn`]. halo2's verifier
+/// absorbs every value after the verifying key; this collects the values of
+/// each column and absorbs their [`column_digest()`] instead.
+pub struct HashedInstanceRead<T> {
+    inner: T,
+    /// The transcript the proof was created with, which picks the digest.
+    transcript_type: TranscriptType,
+    /// The lengths of the columns still to be absorbed, in order.
+    pending: VecDeque<usize>,
+    column: Vec<Fr>,
+    absorbed_vk: bool,
+}
+
+impl<T: fmt::Debug> fmt::Debug for HashedInstanceRead<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashedInstanceRead")
+            .field("inner", &self.inner)
+            .field("transcript_type", &self.transcript_type)
+            .field("pending_columns", &self.pending.len())
+            .field("absorbed_vk", &self.absorbed_vk)
+            .finish()
+    }
+}
+
+impl<T: TranscriptRead<G1Affine, Challenge255<G1Affine>>> HashedInstanceRead<T> {
+    pub fn new(inner: T, transcript_type: TranscriptType, instances: &[&[&[Fr]]]) -> Self {
+        HashedInstanceRead {
+            inner,
+            transcript_type,
+            pending: instances
+                .iter()
+                .flat_map(|instance| instance.iter().map(|values| values.len()))
+                .collect(),
+            column: vec![],
+            absorbed_vk: false,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Absorbs the columns that are complete, including empty ones, which
+    /// halo2 doesn't call the transcript for at all.
+    fn absorb_complete_columns(&mut self) -> io::Result<()> {
+        while self.pending.front() == Some(&self.column.len()) {
+            self.pending.pop_front();
+            self.inner
+                .common_scalar(column_digest(&self.column, self.transcript_type))?;
+            self.column.clear();
+        }
+        Ok(())
+    }
+}
+
+impl<T: TranscriptRead<G1Affine, Challenge255<G1Affine>>>
+    Transcript<G1Affine, Challenge255<G1Affine>> for HashedInstanceRead<T>
+{
+    fn squeeze_challenge(&mut self) -> Challenge255<G1Affine> {
+        self.inner.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: G1Affine) -> io::Result<()> {
+        self.inner.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        // The first scalar is the verifying key.
+        if !self.absorbed_vk {
+            self.absorbed_vk = true;
+            self.inner.common_scalar(scalar)?;
+            return self.absorb_complete_columns();
+        }
+        if self.pending.is_empty() {
+            return self.inner.common_scalar(scalar);
+        }
+        self.column.push(scalar);
+        self.absorb_complete_columns()
+    }
+}
+
+impl<T: TranscriptRead<G1Affine, Challenge255<G1Affine>>>
+    TranscriptRead<G1Affine, Challenge255<G1Affine>> for HashedInstanceRead<T>
+{
+    fn read_point(&mut self) -> io::Result<G1Affine> {
+        self.inner.read_point()
+    }
+
+    fn read_scalar(&mut self) -> io::Result<Fr> {
+        self.inner.read_scalar()
+    }
+}
+
+/// Verifies a SHPlonk proof, absorbing the instances with `absorption` and
+/// reading the advice commitments in `advice_order`. This fails with
+/// [`TachyonError::InstanceAbsorptionMismatch`] or
+/// [`TachyonError::AdviceAbsorbOrderMismatch`] if `key_metadata`, the metadata
+/// written in front of the key with
+/// [`crate::proving_key::write_with_metadata()`], records another one, and
+/// with [`TachyonError::MultiOpenMismatch`] if it records GWC. `T` is
+/// the transcript the proof was created with, e.g, `Blake2bRead<_, _, _>`,
+/// which also picks the [`column_digest()`] of hashed instances.
+pub fn verify_proof<
+    'a,
+    T: TranscriptReadBuffer<&'a [u8], G1Affine, Challenge255<G1Affine>> + TranscriptReadType,
+>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    key_metadata: &Metadata,
+    absorption: InstanceAbsorption,
+    advice_order: AbsorbOrder,
+    instances: &[&[&[Fr]]],
+    proof: &'a [u8],
+) -> Result<(), TachyonError> {
+    match multi_open_scheme_from_metadata(key_metadata) {
+        Some(key) if key != MultiOpenScheme::SHPlonk => {
+            return Err(TachyonError::MultiOpenMismatch {
+                key,
+                prover: MultiOpenScheme::SHPlonk,
+            })
+        }
+        _ => {}
+    }
+    let key = instance_absorption_from_metadata(key_metadata);
+    if key != absorption {
+        return Err(TachyonError::InstanceAbsorptionMismatch {
+            key,
+            verifier: absorption,
+        });
+    }
+    let key = advice_absorb_order_from_metadata(key_metadata);
+    if key != advice_order {
+        return Err(TachyonError::AdviceAbsorbOrderMismatch {
+            key,
+            configured: advice_order,
+        });
+    }
+    let mut transcript = AdviceOrderRead::new(T::init(proof), vk, instances.len(), advice_order);
+    let strategy = SingleStrategy::new(params);
+    let result = match absorption {
+        InstanceAbsorption::PerValue => halo2_verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<_>,
+            _,
+            _,
+            _,
+        >(params, vk, strategy, instances, &mut transcript),
+        InstanceAbsorption::HashedColumn => {
+            halo2_verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+                params,
+                vk,
+                strategy,
+                instances,
+                &mut HashedInstanceRead::new(transcript, T::TRANSCRIPT_TYPE, instances),
+            )
+        }
+    };
+    result.map(|_| ()).map_err(TachyonError::Verification)
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, keygen_vk},
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::{Blake2bRead, PoseidonRead, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+    use rand_core::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    use super::{backend_column_digest, column_digest, verify_proof};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, PoseidonWrite as TachyonPoseidonWrite,
+            ProvingKey as TachyonProvingKey, SHPlonkProver, TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{AbsorbOrder, InstanceAbsorption, MultiOpenScheme, TranscriptType, SEED},
+        error::TachyonError,
+        prover::create_proof,
+        proving_key::{instance_absorption_to_metadata, multi_open_scheme_to_metadata, Metadata},
+        xor_shift_rng::XORShiftRng,
+    };
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_backend_column_digest() {
+        let mut rng = XorShiftRng::from_seed(SEED);
+        for transcript_type in [TranscriptType::Blake2b, TranscriptType::Poseidon] {
+            for len in [0, 1, 1000] {
+                let values = (0..len).map(|_| Fr::random(&mut rng)).collect::<Vec<_>>();
+                assert_eq!(
+                    backend_column_digest(&values, transcript_type),
+                    column_digest(&values, transcript_type)
+                );
+            }
+            assert_ne!(
+                column_digest(&[Fr::zero()], transcript_type),
+                column_digest(&[], transcript_type)
+            );
+        }
+        assert_ne!(
+            column_digest(&[Fr::one()], TranscriptType::Blake2b),
+            column_digest(&[Fr::one()], TranscriptType::Poseidon)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_absorption() {
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = [&[&public_inputs[..]][..]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let prove = |transcript_type: TranscriptType, metadata: &Metadata| {
+            let mut prover =
+                SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(transcript_type as u8, k, &s);
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            tachyon_pk.set_metadata(metadata.clone());
+            let rng = XORShiftRng::from_seed(SEED);
+            let mut proof = match transcript_type {
+                TranscriptType::Blake2b => {
+                    let mut transcript = TachyonBlake2bWrite::init(vec![]);
+                    create_proof(
+                        &mut prover,
+                        &mut tachyon_pk,
+                        &[circuit.clone()],
+                        &instances,
+                        rng,
+                        &mut transcript,
+                    )
+                    .expect("proof generation should not fail");
+                    transcript.finalize()
+                }
+                TranscriptType::Poseidon => {
+                    let mut transcript = TachyonPoseidonWrite::init(vec![]);
+                    create_proof(
+                        &mut prover,
+                        &mut tachyon_pk,
+                        &[circuit.clone()],
+                        &instances,
+                        rng,
+                        &mut transcript,
+                    )
+                    .expect("proof generation should not fail");
+                    transcript.finalize()
+                }
+                _ => unreachable!(),
+            };
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+        let verify = |transcript_type: TranscriptType,
+                      metadata: &Metadata,
+                      absorption,
+                      proof: &[u8]| match transcript_type {
+            TranscriptType::Blake2b => verify_proof::<Blake2bRead<_, _, _>>(
+                &params,
+                pk.get_vk(),
+                metadata,
+                absorption,
+                AbsorbOrder::PhaseThenColumn,
+                &instances,
+                proof,
+            ),
+            TranscriptType::Poseidon => verify_proof::<PoseidonRead<_, _, _>>(
+                &params,
+                pk.get_vk(),
+                metadata,
+                absorption,
+                AbsorbOrder::PhaseThenColumn,
+                &instances,
+                proof,
+            ),
+            _ => unreachable!(),
+        };
+
+        let per_value = Metadata::new();
+        let mut hashed = Metadata::new();
+        instance_absorption_to_metadata(InstanceAbsorption::HashedColumn, &mut hashed);
+        for transcript_type in [TranscriptType::Blake2b, TranscriptType::Poseidon] {
+            let prove = |metadata: &Metadata| prove(transcript_type, metadata);
+            let verify = |metadata: &Metadata, absorption, proof: &[u8]| {
+                verify(transcript_type, metadata, absorption, proof)
+            };
+            let per_value_proof = prove(&per_value);
+            let hashed_proof = prove(&hashed);
+            assert_ne!(per_value_proof, hashed_proof);
+
+            assert!(verify(&per_value, InstanceAbsorption::PerValue, &per_value_proof).is_ok());
+            assert!(verify(&hashed, InstanceAbsorption::HashedColumn, &hashed_proof).is_ok());
+
+            // A verifier configured for the other absorption refuses the key
+            // instead of failing somewhere in the proof.
+            assert!(matches!(
+                verify(&hashed, InstanceAbsorption::PerValue, &hashed_proof),
+                Err(TachyonError::InstanceAbsorptionMismatch {
+                    key: InstanceAbsorption::HashedColumn,
+                    verifier: InstanceAbsorption::PerValue,
+                })
+            ));
+            assert!(matches!(
+                verify(
+                    &per_value,
+                    InstanceAbsorption::HashedColumn,
+                    &per_value_proof
+                ),
+                Err(TachyonError::InstanceAbsorptionMismatch {
+                    key: InstanceAbsorption::PerValue,
+                    verifier: InstanceAbsorption::HashedColumn,
+                })
+            ));
+            // A proof checked against a key that lies about its absorption
+            // still fails, just not as clearly.
+            assert!(matches!(
+                verify(&per_value, InstanceAbsorption::PerValue, &hashed_proof),
+                Err(TachyonError::Verification(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_multi_open_scheme_mismatch() {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+        let vk = keygen_vk(&params, &SimpleCircuit::<Fr>::default()).expect("vk should not fail");
+        let instances = [Fr::from(7)];
+        let verify = |metadata: &Metadata| {
+            verify_proof::<Blake2bRead<_, _, _>>(
+                &params,
+                &vk,
+                metadata,
+                InstanceAbsorption::PerValue,
+                AbsorbOrder::PhaseThenColumn,
+                &[&[&instances[..]]],
+                &[],
+            )
+        };
+
+        // A key for GWC is refused before the proof is read.
+        let mut gwc = Metadata::new();
+        multi_open_scheme_to_metadata(MultiOpenScheme::Gwc, &mut gwc);
+        assert!(matches!(
+            verify(&gwc),
+            Err(TachyonError::MultiOpenMismatch {
+                key: MultiOpenScheme::Gwc,
+                prover: MultiOpenScheme::SHPlonk,
+            })
+        ));
+        let mut shplonk = Metadata::new();
+        multi_open_scheme_to_metadata(MultiOpenScheme::SHPlonk, &mut shplonk);
+        assert!(matches!(
+            verify(&shplonk),
+            Err(TachyonError::Verification(_))
+        ));
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    fmt,
+    io::{self, Read},
+};
+
+use halo2curves::bn256::Fr;
+
+use crate::{
+    instance::parse_le_column,
+    serde_fr::{fr_to_le_bytes, FR_BYTES},
+};
+
+/// The instance columns of one circuit, handed over a column at a time so
+/// that they never have to be in memory at once. See
+/// [`crate::prover::create_proof_streaming_instances()`].
+pub trait InstanceSource {
+    fn num_columns(&self) -> usize;
+
+    /// The number of values of the column at `column`, known before it is
+    /// read.
+    fn column_len(&self, column: usize) -> usize;
+
+    /// Calls `sink` with `(row, value)` for every row of the column at
+    /// `column`, in ascending order. Columns are read in ascending order,
+    /// each once.
+    fn read_column(&mut self, column: usize, sink: &mut dyn FnMut(usize, Fr)) -> io::Result<()>;
+}
+
+impl<'a> InstanceSource for &'a [&'a [Fr]] {
+    fn num_columns(&self) -> usize {
+        self.len()
+    }
+
+    fn column_len(&self, column: usize) -> usize {
+        self[column].len()
+    }
+
+    fn read_column(&mut self, column: usize, sink: &mut dyn FnMut(usize, Fr)) -> io::Result<()> {
+        for (row, value) in self[column].iter().enumerate() {
+            sink(row, *value);
+        }
+        Ok(())
+    }
+}
+
+fn invalid_data(message: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes) as usize)
+}
+
+/// Streams the instance columns of one circuit from `R`. The input starts
+/// with the number of columns and then the length of each column, all as
+/// little-endian `u32`s, followed by the values of every column in order,
+/// each as the canonical little-endian encoding of a scalar. See
+/// [`write_instances()`].
+pub struct InstanceReader<R> {
+    reader: R,
+    column_lens: Vec<usize>,
+    /// The column [`InstanceSource::read_column()`] reads next.
+    next_column: usize,
+}
+
+impl<R> fmt::Debug for InstanceReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstanceReader")
+            .field("column_lens", &self.column_lens)
+            .field("next_column", &self.next_column)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: Read> InstanceReader<R> {
+    /// Reads the column lengths, leaving the values to be read column by
+    /// column.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let num_columns = read_u32(&mut reader)?;
+        let column_lens = (0..num_columns)
+            .map(|_| read_u32(&mut reader))
+            .collect::<io::Result<_>>()?;
+        Ok(InstanceReader {
+            reader,
+            column_lens,
+            next_column: 0,
+        })
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> InstanceSource for InstanceReader<R> {
+    fn num_columns(&self) -> usize {
+        self.column_lens.len()
+    }
+
+    fn column_len(&self, column: usize) -> usize {
+        self.column_lens[column]
+    }
+
+    fn read_column(&mut self, column: usize, sink: &mut dyn FnMut(usize, Fr)) -> io::Result<()> {
+        if column != self.next_column {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "column {} was read before column {}",
+                    column, self.next_column
+                ),
+            ));
+        }
+        // The column is parsed as a whole, so that every value that isn't
+        // canonical is reported rather than only the first. Its bytes are
+        // only allocated as they arrive, not for the length the input claims.
+        let len = (self.column_lens[column] * FR_BYTES) as u64;
+        let mut bytes = vec![];
+        (&mut self.reader).take(len).read_to_end(&mut bytes)?;
+        if (bytes.len() as u64) < len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let values = parse_le_column(column, &bytes).map_err(|errors| {
+            invalid_data(
+                errors
+                    .iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            )
+        })?;
+        for (row, value) in values.into_iter().enumerate() {
+            sink(row, value);
+        }
+        self.next_column += 1;
+        Ok(())
+    }
+}
+
+/// Writes `columns` in the format [`InstanceReader`] reads.
+pub fn write_instances<W: io::Write>(mut writer: W, columns: &[&[Fr]]) -> io::Result<()> {
+    writer.write_all(&(columns.len() as u32).to_le_bytes())?;
+    for column in columns {
+        writer.write_all(&(column.len() as u32).to_le_bytes())?;
+    }
+    for column in columns {
+        for value in column.iter() {
+            writer.write_all(&fr_to_le_bytes(value))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use ff::Field;
+    use halo2curves::bn256::Fr;
+
+    use super::{write_instances, InstanceReader, InstanceSource};
+
+    fn read_all<S: InstanceSource>(mut source: S) -> io::Result<Vec<Vec<Fr>>> {
+        (0..source.num_columns())
+            .map(|column| {
+                let mut values = vec![Fr::zero(); source.column_len(column)];
+                source.read_column(column, &mut |row, value| values[row] = value)?;
+                Ok(values)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reader_matches_slices() {
+        let columns = [
+            vec![Fr::from(1), Fr::from(2), Fr::from(3)],
+            vec![],
+            vec![-Fr::one()],
+        ];
+        let slices = columns.iter().map(|values| &values[..]).collect::<Vec<_>>();
+        let mut bytes = vec![];
+        write_instances(&mut bytes, &slices).unwrap();
+
+        let reader = InstanceReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.num_columns(), 3);
+        assert_eq!(
+            (0..3).map(|i| reader.column_len(i)).collect::<Vec<_>>(),
+            vec![3, 0, 1]
+        );
+        assert_eq!(read_all(reader).unwrap(), columns);
+        assert_eq!(read_all(&slices[..]).unwrap(), columns);
+    }
+
+    #[test]
+    fn test_reader_rejects_bad_input() {
+        let values = [Fr::from(5), Fr::from(6)];
+        let mut bytes = vec![];
+        write_instances(&mut bytes, &[&values[..], &values[..]]).unwrap();
+
+        // Columns have to be read in order.
+        let mut reader = InstanceReader::new(bytes.as_slice()).unwrap();
+        let e = reader.read_column(1, &mut |_, _| {}).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+
+        let truncated = InstanceReader::new(&bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(
+            read_all(truncated).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        // The last value is larger than the modulus.
+        let mut non_canonical = bytes.clone();
+        let last = non_canonical.len() - 32;
+        non_canonical[last..].fill(0xff);
+        let reader = InstanceReader::new(non_canonical.as_slice()).unwrap();
+        assert_eq!(
+            read_all(reader).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // Every value of the column that isn't canonical is reported.
+        non_canonical[last - 32..last].fill(0xff);
+        let e = read_all(InstanceReader::new(non_canonical.as_slice()).unwrap()).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        let message = e.to_string();
+        assert!(message.contains("column 1 at row 0"), "{}", message);
+        assert!(message.contains("column 1 at row 1"), "{}", message);
+    }
+}
@@ -0,0 +1,432 @@
+// Users targeting a verifier that is already deployed ask how to set up the
+// prover so that the verifier accepts its proofs. `detect_config()` looks for
+// what an artifact of the verifier says about that and how sure it can be of
+// it. What this crate writes itself, keys with metadata and proofs with a
+// header, records the settings and is classified for certain; everything else
+// is guessed from structural markers. Every proof of this crate is
+// zero-knowledge, so there is no mode to pick there.
+
+use std::fmt;
+
+use ff::PrimeField;
+use halo2curves::bn256::Fr;
+
+use crate::{
+    consts::{MultiOpenScheme, TranscriptType, VkHashMode},
+    preset::ProverPreset,
+    proof::Proof,
+    proving_key::{multi_open_scheme_from_metadata, split_metadata},
+    vk_hash::{vk_hash_mode_from_metadata, VK_HASH_MODE_METADATA_KEY},
+};
+
+/// Something a verifier was built from or accepts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifierArtifact {
+    /// The runtime bytecode of an EVM verifier.
+    SolidityBytecode(Vec<u8>),
+    /// A proving key as written by
+    /// [`crate::proving_key::write_with_metadata()`], with or without
+    /// metadata, or a halo2 verifying key, which a proving key starts with.
+    VkBytes(Vec<u8>),
+    /// A verifying key exported by snarkjs.
+    SnarkJson(String),
+    /// A proof the verifier accepts, as written by [`Proof::to_bytes()`].
+    Proof(Vec<u8>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    /// A default for verifiers of its kind; nothing in the artifact says
+    /// otherwise.
+    Low,
+    /// Follows from a marker that verifiers of other settings rarely have.
+    Medium,
+    /// Recorded in the artifact.
+    High,
+}
+
+/// A setting [`detect_config()`] recommends, with what it was found from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding<T> {
+    pub value: T,
+    pub confidence: Confidence,
+    pub marker: &'static str,
+}
+
+impl<T> Finding<T> {
+    fn new(value: T, confidence: Confidence, marker: &'static str) -> Self {
+        Finding {
+            value,
+            confidence,
+            marker,
+        }
+    }
+}
+
+/// How to set up the prover for a verifier, as far as its artifact tells.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProverConfigAdvice {
+    pub transcript_type: Option<Finding<TranscriptType>>,
+    pub multi_open_scheme: Option<Finding<MultiOpenScheme>>,
+    pub vk_hash_mode: Option<Finding<VkHashMode>>,
+    pub k: Option<u32>,
+    pub num_fixed_commitments: Option<usize>,
+}
+
+impl ProverConfigAdvice {
+    /// The preset that agrees with every setting found, if the transcript was
+    /// found and one does.
+    pub fn preset(&self) -> Option<ProverPreset> {
+        let transcript_type = self.transcript_type.as_ref()?.value;
+        [
+            ProverPreset::EvmSha256Shplonk,
+            ProverPreset::RecursionPoseidonShplonk,
+            ProverPreset::LocalBlake2b,
+        ]
+        .into_iter()
+        .find(|preset| {
+            preset.transcript_type() == transcript_type
+                && self
+                    .multi_open_scheme
+                    .as_ref()
+                    .map_or(true, |found| found.value == preset.multi_open_scheme())
+                && self
+                    .vk_hash_mode
+                    .as_ref()
+                    .map_or(true, |found| found.value == preset.vk_hash_mode())
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DetectError {
+    /// Nothing in the artifact marks it as a verifier this crate knows of.
+    Unrecognized(&'static str),
+    /// The artifact is for a proof system this crate doesn't prove for.
+    UnsupportedProtocol(String),
+    /// The verifier reads a transcript libtachyon doesn't have.
+    UnsupportedTranscript(&'static str),
+    /// The artifact has the marker of a format this crate writes, but the
+    /// rest of it doesn't parse.
+    Malformed(String),
+}
+
+impl fmt::Display for DetectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DetectError::Unrecognized(reason) => write!(f, "unrecognized artifact: {}", reason),
+            DetectError::UnsupportedProtocol(protocol) => {
+                write!(f, "{} verifiers are not supported", protocol)
+            }
+            DetectError::UnsupportedTranscript(transcript) => {
+                write!(f, "libtachyon has no {} transcript", transcript)
+            }
+            DetectError::Malformed(reason) => write!(f, "malformed artifact: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for DetectError {}
+
+/// Recommends the transcript, multi-open scheme and vk hash mode a prover
+/// needs for proofs that the verifier of `artifact` accepts, each with how
+/// sure it is. Settings the artifact says nothing about are left out.
+pub fn detect_config(artifact: &VerifierArtifact) -> Result<ProverConfigAdvice, DetectError> {
+    match artifact {
+        VerifierArtifact::SolidityBytecode(bytecode) => detect_bytecode(bytecode),
+        VerifierArtifact::VkBytes(bytes) => detect_key(bytes),
+        VerifierArtifact::SnarkJson(json) => Err(match json_string(json, "protocol") {
+            Some(protocol) => DetectError::UnsupportedProtocol(protocol.to_string()),
+            None => DetectError::Unrecognized("the JSON has no protocol"),
+        }),
+        VerifierArtifact::Proof(bytes) => {
+            let proof =
+                Proof::from_bytes(bytes).map_err(|e| DetectError::Malformed(e.to_string()))?;
+            let header = proof
+                .header()
+                .ok_or(DetectError::Unrecognized("the proof has no header"))?;
+            Ok(ProverConfigAdvice {
+                transcript_type: Some(Finding::new(
+                    header.transcript_type,
+                    Confidence::High,
+                    "the proof header",
+                )),
+                multi_open_scheme: Some(Finding::new(
+                    header.multi_open_scheme,
+                    Confidence::High,
+                    "the proof header",
+                )),
+                k: Some(header.k),
+                ..Default::default()
+            })
+        }
+    }
+}
+
+/// Reads the key of `bytes`. halo2 writes a verifying key, and so a proving
+/// key, starting with `k` and the number of fixed commitments, both as
+/// big-endian `u32`s, followed by the commitments.
+fn detect_key(bytes: &[u8]) -> Result<ProverConfigAdvice, DetectError> {
+    let (metadata, key) =
+        split_metadata(bytes).map_err(|e| DetectError::Malformed(e.to_string()))?;
+    let header = key.get(..8).and_then(|header| {
+        let k = u32::from_be_bytes(header[..4].try_into().unwrap());
+        let num_fixed = u32::from_be_bytes(header[4..].try_into().unwrap()) as usize;
+        // A commitment takes at least 32 bytes in every format.
+        let fits = num_fixed
+            .checked_mul(32)
+            .map_or(false, |len| len <= key.len() - 8);
+        (1..=Fr::S)
+            .contains(&k)
+            .then(|| (k, num_fixed))
+            .filter(|_| fits)
+    });
+    if metadata.is_empty() && header.is_none() {
+        return Err(DetectError::Unrecognized(
+            "neither key metadata nor a halo2 verifying key",
+        ));
+    }
+
+    let multi_open_scheme = multi_open_scheme_from_metadata(&metadata)
+        .map(|scheme| Finding::new(scheme, Confidence::High, "the key metadata"));
+    let vk_hash_mode = if metadata.contains_key(VK_HASH_MODE_METADATA_KEY) {
+        Finding::new(
+            vk_hash_mode_from_metadata(&metadata),
+            Confidence::High,
+            "the key metadata",
+        )
+    } else {
+        Finding::new(
+            VkHashMode::Blake2bField,
+            Confidence::Low,
+            "no vk hash mode in the key metadata, so hashed like halo2",
+        )
+    };
+    // Only verifiers inside a circuit hash the key with Poseidon, and they
+    // read a Poseidon transcript for the same reason.
+    let transcript_type = (vk_hash_mode.value == VkHashMode::Poseidon).then(|| {
+        Finding::new(
+            TranscriptType::Poseidon,
+            Confidence::Low,
+            "the key is hashed with Poseidon",
+        )
+    });
+    Ok(ProverConfigAdvice {
+        transcript_type,
+        multi_open_scheme,
+        vk_hash_mode: Some(vk_hash_mode),
+        k: header.map(|(k, _)| k),
+        num_fixed_commitments: header.map(|(_, num_fixed)| num_fixed),
+    })
+}
+
+const OP_KECCAK256: u8 = 0x20;
+const OP_GAS: u8 = 0x5a;
+const OP_PUSH1: u8 = 0x60;
+const OP_PUSH32: u8 = 0x7f;
+const OP_STATICCALL: u8 = 0xfa;
+
+const PRECOMPILE_SHA256: u8 = 0x02;
+const PRECOMPILE_PAIRING: u8 = 0x08;
+
+/// Scans `bytecode` for the precompiles it calls, as `PUSH1 address GAS
+/// STATICCALL`, and the hashes it computes.
+fn detect_bytecode(bytecode: &[u8]) -> Result<ProverConfigAdvice, DetectError> {
+    let mut precompiles = vec![];
+    let mut keccak = false;
+    let mut i = 0;
+    while i < bytecode.len() {
+        let op = bytecode[i];
+        if op == OP_PUSH1
+            && bytecode.get(i + 2) == Some(&OP_GAS)
+            && bytecode.get(i + 3) == Some(&OP_STATICCALL)
+        {
+            precompiles.push(bytecode[i + 1]);
+        }
+        keccak |= op == OP_KECCAK256;
+        i += match op {
+            OP_PUSH1..=OP_PUSH32 => 2 + (op - OP_PUSH1) as usize,
+            _ => 1,
+        };
+    }
+
+    if !precompiles.contains(&PRECOMPILE_PAIRING) {
+        return Err(DetectError::Unrecognized(
+            "the bytecode never checks a pairing",
+        ));
+    }
+    let transcript_type = if precompiles.contains(&PRECOMPILE_SHA256) {
+        Finding::new(
+            TranscriptType::Sha256,
+            Confidence::Medium,
+            "the bytecode calls the SHA-256 precompile",
+        )
+    } else if keccak {
+        return Err(DetectError::UnsupportedTranscript("Keccak-256"));
+    } else {
+        Finding::new(
+            TranscriptType::Poseidon,
+            Confidence::Low,
+            "the bytecode hashes without Keccak-256 or SHA-256",
+        )
+    };
+    Ok(ProverConfigAdvice {
+        transcript_type: Some(transcript_type),
+        // Both schemes end in a pairing of two pairs, but the EVM verifiers
+        // in use are generated for SHPlonk.
+        multi_open_scheme: Some(Finding::new(
+            MultiOpenScheme::SHPlonk,
+            Confidence::Low,
+            "EVM verifiers are generated for SHPlonk",
+        )),
+        vk_hash_mode: Some(Finding::new(
+            VkHashMode::Blake2bField,
+            Confidence::Low,
+            "EVM verifiers embed the key digest computed like halo2",
+        )),
+        ..Default::default()
+    })
+}
+
+/// The string value of the first `"key"` in `json`, without parsing the rest.
+fn json_string<'a>(json: &'a str, key: &str) -> Option<&'a str> {
+    let quoted = format!("\"{}\"", key);
+    let rest = &json[json.find(&quoted)? + quoted.len()..];
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+    let rest = rest.strip_prefix('"')?;
+    Some(&rest[..rest.find('"')?])
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{circuit::Value, plonk::keygen_pk2, poly::kzg::commitment::ParamsKZG};
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{detect_config, Confidence, DetectError, VerifierArtifact};
+    use crate::{
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{MultiOpenScheme, TranscriptType, VkHashMode},
+        preset::{ProverBuilder, ProverPreset},
+        proof::{Proof, ProofHeader},
+        proving_key::{write_with_metadata, Metadata},
+    };
+
+    #[test]
+    fn test_detect_own_artifacts() {
+        let k = 4;
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::from(2));
+        let pk = keygen_pk2(&params, &circuit).expect("pk should not fail");
+        let mut pk_bytes = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytes)
+            .unwrap();
+        let num_fixed = pk.get_vk().fixed_commitments().len();
+
+        for preset in [
+            ProverPreset::EvmSha256Shplonk,
+            ProverPreset::RecursionPoseidonShplonk,
+            ProverPreset::LocalBlake2b,
+        ] {
+            for scheme in [MultiOpenScheme::Gwc, MultiOpenScheme::SHPlonk] {
+                for vk_hash_mode in VkHashMode::ALL {
+                    let builder = ProverBuilder::new(preset)
+                        .multi_open_scheme(scheme)
+                        .vk_hash_mode(vk_hash_mode);
+                    let mut key = vec![];
+                    write_with_metadata(&mut key, &builder.key_metadata(pk.get_vk()), &pk_bytes)
+                        .unwrap();
+                    let advice = detect_config(&VerifierArtifact::VkBytes(key)).unwrap();
+                    let found_scheme = advice.multi_open_scheme.as_ref().unwrap();
+                    assert_eq!(found_scheme.value, scheme);
+                    assert_eq!(found_scheme.confidence, Confidence::High);
+                    let found_vk_hash_mode = advice.vk_hash_mode.as_ref().unwrap();
+                    assert_eq!(found_vk_hash_mode.value, vk_hash_mode);
+                    assert_eq!(found_vk_hash_mode.confidence, Confidence::High);
+                    assert_eq!(advice.k, Some(k));
+                    assert_eq!(advice.num_fixed_commitments, Some(num_fixed));
+                }
+
+                let transcript_type = preset.transcript_type();
+                let header = ProofHeader::new(pk.get_vk(), transcript_type, scheme, k);
+                let proof = Proof::new(header, vec![0; 64]).to_bytes();
+                let advice = detect_config(&VerifierArtifact::Proof(proof)).unwrap();
+                let found_transcript = advice.transcript_type.as_ref().unwrap();
+                assert_eq!(found_transcript.value, transcript_type);
+                assert_eq!(found_transcript.confidence, Confidence::High);
+                assert_eq!(advice.multi_open_scheme.as_ref().unwrap().value, scheme);
+                assert_eq!(advice.k, Some(k));
+                let expected_preset = (scheme == MultiOpenScheme::SHPlonk).then(|| preset);
+                assert_eq!(advice.preset(), expected_preset);
+            }
+        }
+
+        // A key without metadata only tells its shape.
+        let advice = detect_config(&VerifierArtifact::VkBytes(pk_bytes)).unwrap();
+        assert_eq!(advice.multi_open_scheme, None);
+        assert_eq!(advice.transcript_type, None);
+        assert_eq!(advice.vk_hash_mode.unwrap().confidence, Confidence::Low);
+        assert_eq!(advice.num_fixed_commitments, Some(num_fixed));
+        assert_eq!(advice.preset(), None);
+
+        let mut metadata = Metadata::new();
+        metadata.insert("circuit".to_string(), "simple".to_string());
+        let mut truncated = vec![];
+        write_with_metadata(&mut truncated, &metadata, &[]).unwrap();
+        truncated.truncate(truncated.len() - 1);
+        assert!(matches!(
+            detect_config(&VerifierArtifact::VkBytes(truncated)),
+            Err(DetectError::Malformed(_))
+        ));
+        assert!(matches!(
+            detect_config(&VerifierArtifact::VkBytes(vec![0xff; 16])),
+            Err(DetectError::Unrecognized(_))
+        ));
+        assert!(matches!(
+            detect_config(&VerifierArtifact::Proof(vec![0; 64])),
+            Err(DetectError::Unrecognized(_))
+        ));
+    }
+
+    #[test]
+    fn test_detect_foreign_artifacts() {
+        // `PUSH1 address GAS STATICCALL`, behind a `PUSH2` whose data looks
+        // like a call to the SHA-256 precompile.
+        let call = |address| vec![0x60, address, 0x5a, 0xfa];
+        let mut sha256 = vec![0x61, 0x60, 0x02];
+        sha256.extend(call(0x02));
+        sha256.extend(call(0x08));
+        let advice = detect_config(&VerifierArtifact::SolidityBytecode(sha256)).unwrap();
+        let found_transcript = advice.transcript_type.as_ref().unwrap();
+        assert_eq!(found_transcript.value, TranscriptType::Sha256);
+        assert_eq!(found_transcript.confidence, Confidence::Medium);
+        assert_eq!(advice.preset(), Some(ProverPreset::EvmSha256Shplonk));
+
+        let mut keccak = vec![0x20];
+        keccak.extend(call(0x08));
+        assert_eq!(
+            detect_config(&VerifierArtifact::SolidityBytecode(keccak)),
+            Err(DetectError::UnsupportedTranscript("Keccak-256"))
+        );
+
+        let mut no_pairing = vec![0x61, 0x60, 0x08];
+        no_pairing.extend(call(0x02));
+        assert!(matches!(
+            detect_config(&VerifierArtifact::SolidityBytecode(no_pairing)),
+            Err(DetectError::Unrecognized(_))
+        ));
+
+        let snarkjs = r#"{ "protocol" : "groth16", "curve": "bn128", "nPublic": 1 }"#;
+        assert_eq!(
+            detect_config(&VerifierArtifact::SnarkJson(snarkjs.to_string())),
+            Err(DetectError::UnsupportedProtocol("groth16".to_string()))
+        );
+        assert!(matches!(
+            detect_config(&VerifierArtifact::SnarkJson("{}".to_string())),
+            Err(DetectError::Unrecognized(_))
+        ));
+    }
+}
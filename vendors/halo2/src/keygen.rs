@@ -0,0 +1,141 @@
+use std::fmt;
+
+use halo2_proofs::{
+    plonk::{self, Any, Circuit, Column, ConstraintSystem, ProvingKey},
+    poly::{commitment::Params, Rotation},
+};
+use halo2curves::CurveAffine;
+
+use crate::{
+    analyze::{column_annotations, table_rows, usable_rows},
+    annotation::annotations_to_metadata,
+    proving_key::Metadata,
+};
+
+#[derive(Debug)]
+pub enum KeygenError {
+    Plonk(plonk::Error),
+    /// `column` is queried at `rotation`, which is `n` or more rows away.
+    RotationOutOfRange {
+        column: Column<Any>,
+        rotation: Rotation,
+        n: u64,
+    },
+    /// A lookup table has more rows than the circuit can use. See
+    /// [`crate::analyze::max_table_rows()`].
+    TableTooLarge {
+        table_rows: usize,
+        usable_rows: usize,
+    },
+}
+
+impl fmt::Display for KeygenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeygenError::Plonk(e) => write!(f, "{}", e),
+            KeygenError::RotationOutOfRange {
+                column,
+                rotation,
+                n,
+            } => write!(
+                f,
+                "{:?} is queried at rotation {} but there are only {} rows",
+                column, rotation.0, n
+            ),
+            KeygenError::TableTooLarge {
+                table_rows,
+                usable_rows,
+            } => write!(
+                f,
+                "lookup table has {} rows but only {} are usable",
+                table_rows, usable_rows
+            ),
+        }
+    }
+}
+
+impl std::error::Error for KeygenError {}
+
+impl From<plonk::Error> for KeygenError {
+    fn from(e: plonk::Error) -> Self {
+        KeygenError::Plonk(e)
+    }
+}
+
+/// Checks that every query in `cs` rotates by less than `2ᵏ` rows in either
+/// direction.
+pub fn check_rotations<F: ff::Field>(cs: &ConstraintSystem<F>, k: u32) -> Result<(), KeygenError> {
+    let n = 1u64 << k;
+    let queries = cs
+        .advice_queries()
+        .iter()
+        .map(|(column, rotation)| (Column::<Any>::from(*column), *rotation))
+        .chain(
+            cs.fixed_queries()
+                .iter()
+                .map(|(column, rotation)| (Column::<Any>::from(*column), *rotation)),
+        )
+        .chain(
+            cs.instance_queries()
+                .iter()
+                .map(|(column, rotation)| (Column::<Any>::from(*column), *rotation)),
+        );
+    for (column, rotation) in queries {
+        if rotation.0.unsigned_abs() as u64 >= n {
+            return Err(KeygenError::RotationOutOfRange {
+                column,
+                rotation,
+                n,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every lookup table of `circuit` fits in the usable rows of a
+/// circuit with `2ᵏ` rows.
+pub fn check_table_rows<F: ff::Field, ConcreteCircuit: Circuit<F>>(
+    cs: &ConstraintSystem<F>,
+    circuit: &ConcreteCircuit,
+    k: u32,
+) -> Result<(), KeygenError> {
+    let usable_rows = usable_rows(k, cs.blinding_factors());
+    for table_rows in table_rows(circuit)?.into_values() {
+        if table_rows > usable_rows {
+            return Err(KeygenError::TableTooLarge {
+                table_rows,
+                usable_rows,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Same as halo2's `keygen_pk2()`, but rejects circuits whose queries the
+/// backend can't evaluate or whose tables don't fit before doing any work.
+pub fn keygen_pk2<'params, C, P, ConcreteCircuit>(
+    params: &P,
+    circuit: &ConcreteCircuit,
+) -> Result<ProvingKey<C>, KeygenError>
+where
+    C: CurveAffine,
+    P: Params<'params, C> + Sync,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let mut cs = ConstraintSystem::default();
+    ConcreteCircuit::configure(&mut cs);
+    check_rotations(&cs, params.k())?;
+    check_table_rows(&cs, circuit, params.k())?;
+    Ok(plonk::keygen_pk2(params, circuit)?)
+}
+
+/// Returns the column annotations of `circuit` as proving key metadata. Write
+/// it in front of the key with [`crate::proving_key::write_with_metadata()`],
+/// so that errors name columns the way the circuit does.
+pub fn annotation_metadata<F: ff::Field, ConcreteCircuit: Circuit<F>>(
+    circuit: &ConcreteCircuit,
+) -> Result<Metadata, KeygenError> {
+    let mut metadata = Metadata::new();
+    annotations_to_metadata(&column_annotations(circuit)?, &mut metadata);
+    Ok(metadata)
+}
@@ -0,0 +1,390 @@
+use std::{collections::BTreeSet, fmt::Write, ops::Range};
+
+use crate::column::{AdviceColumnIndex, FixedColumnIndex};
+
+/// A column a region assigns to. Columns are drawn in this order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LayoutColumn {
+    Advice(AdviceColumnIndex),
+    Fixed(FixedColumnIndex),
+    /// A selector, as the circuit declared it, before selectors are combined
+    /// into fixed columns.
+    Selector(usize),
+}
+
+impl LayoutColumn {
+    fn kind(&self) -> &'static str {
+        match self {
+            LayoutColumn::Advice(_) => "advice",
+            LayoutColumn::Fixed(_) => "fixed",
+            LayoutColumn::Selector(_) => "selector",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            LayoutColumn::Advice(column) => column.index(),
+            LayoutColumn::Fixed(column) => column.index(),
+            LayoutColumn::Selector(index) => *index,
+        }
+    }
+}
+
+/// A region where the floor planner placed it for the proof.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegionShape {
+    /// The name the circuit gave the region, which needn't be unique.
+    pub name: String,
+    /// The first row the region assigns to, or `None` if it assigns nothing.
+    pub start_row: Option<usize>,
+    /// The number of rows from `start_row` to the last row the region assigns
+    /// to.
+    pub rows: usize,
+    /// Every column the region assigns to.
+    pub columns: BTreeSet<LayoutColumn>,
+}
+
+impl RegionShape {
+    pub fn row_range(&self) -> Range<usize> {
+        let start = self.start_row.unwrap_or(0);
+        start..start + self.rows
+    }
+
+    /// Whether the two regions span a row of a column both assign to. The
+    /// floor planner never places regions like that, since one of them could
+    /// overwrite the other.
+    pub fn overlaps(&self, other: &RegionShape) -> bool {
+        let (a, b) = (self.row_range(), other.row_range());
+        a.start < b.end && b.start < a.end && !self.columns.is_disjoint(&other.columns)
+    }
+}
+
+/// The regions of a circuit in the order it assigned them, recorded with
+/// [`crate::prover::ProofOptions::region_layout`]. Unlike halo2's dev tools,
+/// which lay the circuit out with `MockProver`, this is the layout of the
+/// proof. Cells assigned outside of regions, such as the constants the floor
+/// planner assigns last, aren't in any region.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegionLayout {
+    pub circuit: usize,
+    pub usable_rows: usize,
+    pub regions: Vec<RegionShape>,
+}
+
+impl RegionLayout {
+    /// Every pair of regions that [overlap](RegionShape::overlaps), by their
+    /// indices.
+    pub fn overlapping_regions(&self) -> Vec<(usize, usize)> {
+        let mut overlapping = vec![];
+        for (i, a) in self.regions.iter().enumerate() {
+            for (j, b) in self.regions.iter().enumerate().skip(i + 1) {
+                if a.overlaps(b) {
+                    overlapping.push((i, j));
+                }
+            }
+        }
+        overlapping
+    }
+
+    /// Every column any region assigns to.
+    pub fn columns(&self) -> BTreeSet<LayoutColumn> {
+        self.regions
+            .iter()
+            .flat_map(|region| region.columns.iter().copied())
+            .collect()
+    }
+
+    /// Writes the layout as a JSON object.
+    pub fn to_json(&self) -> String {
+        let regions = self
+            .regions
+            .iter()
+            .map(|region| {
+                let columns = region
+                    .columns
+                    .iter()
+                    .map(|column| {
+                        format!(
+                            "{{\"kind\":\"{}\",\"index\":{}}}",
+                            column.kind(),
+                            column.index()
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    "{{\"name\":{},\"start_row\":{},\"rows\":{},\"columns\":[{}]}}",
+                    json_string(&region.name),
+                    region
+                        .start_row
+                        .map_or_else(|| "null".to_owned(), |row| row.to_string()),
+                    region.rows,
+                    columns.join(",")
+                )
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "{{\"circuit\":{},\"usable_rows\":{},\"regions\":[{}]}}",
+            self.circuit,
+            self.usable_rows,
+            regions.join(",")
+        )
+    }
+
+    /// Draws the layout as an SVG image: a column of cells for every column
+    /// any region assigns to, and a rectangle over the cells of each region,
+    /// titled with its name. The unusable rows are left out.
+    pub fn to_svg(&self) -> String {
+        const COLUMN_WIDTH: usize = 64;
+        const ROW_HEIGHT: usize = 4;
+        const HEADER_HEIGHT: usize = 16;
+
+        let columns = self.columns().into_iter().collect::<Vec<_>>();
+        let width = columns.len().max(1) * COLUMN_WIDTH;
+        let height = HEADER_HEIGHT + self.usable_rows * ROW_HEIGHT;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"monospace\" font-size=\"10\">\n",
+            width, height
+        );
+        for (x, column) in columns.iter().enumerate() {
+            writeln!(
+                svg,
+                "<text x=\"{}\" y=\"12\" text-anchor=\"middle\">{} {}</text>",
+                x * COLUMN_WIDTH + COLUMN_WIDTH / 2,
+                column.kind(),
+                column.index()
+            )
+            .unwrap();
+        }
+        writeln!(
+            svg,
+            "<rect x=\"0\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#f0f0f0\"/>",
+            HEADER_HEIGHT,
+            width,
+            self.usable_rows * ROW_HEIGHT
+        )
+        .unwrap();
+        for (index, region) in self.regions.iter().enumerate() {
+            if region.start_row.is_none() {
+                continue;
+            }
+            let rows = region.row_range();
+            // Spread the hues of neighboring regions apart.
+            let hue = index * 137 % 360;
+            for column in &region.columns {
+                let x = columns.binary_search(column).unwrap();
+                writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"hsl({},60%,70%)\" stroke=\"#404040\" stroke-width=\"0.5\"><title>{} (region {}, rows {}..{})</title></rect>",
+                    x * COLUMN_WIDTH,
+                    HEADER_HEIGHT + rows.start * ROW_HEIGHT,
+                    COLUMN_WIDTH,
+                    region.rows * ROW_HEIGHT,
+                    hue,
+                    xml_escape(&region.name),
+                    index,
+                    rows.start,
+                    rows.end
+                )
+                .unwrap();
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Records the shape of every region while a circuit is synthesized.
+#[derive(Default)]
+pub(crate) struct RegionRecorder {
+    regions: Vec<RegionShape>,
+    /// The index of the region being assigned, if any.
+    current: Option<usize>,
+}
+
+impl RegionRecorder {
+    pub(crate) fn enter(&mut self, name: String) {
+        self.current = Some(self.regions.len());
+        self.regions.push(RegionShape {
+            name,
+            ..Default::default()
+        });
+    }
+
+    pub(crate) fn exit(&mut self) {
+        self.current = None;
+    }
+
+    pub(crate) fn assign(&mut self, column: LayoutColumn, row: usize) {
+        let region = match self.current {
+            Some(index) => &mut self.regions[index],
+            None => return,
+        };
+        let rows = match region.start_row {
+            Some(_) => {
+                let rows = region.row_range();
+                rows.start.min(row)..rows.end.max(row + 1)
+            }
+            None => row..row + 1,
+        };
+        region.start_row = Some(rows.start);
+        region.rows = rows.len();
+        region.columns.insert(column);
+    }
+
+    pub(crate) fn finish(self, circuit: usize, usable_rows: usize) -> RegionLayout {
+        RegionLayout {
+            circuit,
+            usable_rows,
+            regions: self.regions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{LayoutColumn, RegionRecorder};
+    use crate::{
+        bn254::{Blake2bWrite as TachyonBlake2bWrite, SHPlonkProver, TachyonProver},
+        circuits::simple_circuit::SimpleCircuit,
+        column::{AdviceColumnIndex, FixedColumnIndex},
+        consts::TranscriptType,
+        prover::{create_proof_with_options, ProofOptions},
+    };
+
+    #[test]
+    fn test_overlapping_regions() {
+        let mut recorder = RegionRecorder::default();
+        recorder.enter("a".to_owned());
+        recorder.assign(LayoutColumn::Advice(AdviceColumnIndex(0)), 2);
+        recorder.assign(LayoutColumn::Advice(AdviceColumnIndex(0)), 0);
+        recorder.exit();
+        // Outside of any region.
+        recorder.assign(LayoutColumn::Fixed(FixedColumnIndex(0)), 1);
+        // Beside "a", in another column.
+        recorder.enter("b".to_owned());
+        recorder.assign(LayoutColumn::Advice(AdviceColumnIndex(1)), 1);
+        recorder.exit();
+        // Over "a".
+        recorder.enter("\"c\"".to_owned());
+        recorder.assign(LayoutColumn::Advice(AdviceColumnIndex(0)), 1);
+        recorder.assign(LayoutColumn::Selector(0), 1);
+        recorder.exit();
+        let layout = recorder.finish(0, 4);
+
+        assert_eq!(layout.regions[0].row_range(), 0..3);
+        assert_eq!(layout.overlapping_regions(), vec![(0, 2)]);
+        assert_eq!(
+            layout.to_json(),
+            concat!(
+                "{\"circuit\":0,\"usable_rows\":4,\"regions\":[",
+                "{\"name\":\"a\",\"start_row\":0,\"rows\":3,\"columns\":[{\"kind\":\"advice\",\"index\":0}]},",
+                "{\"name\":\"b\",\"start_row\":1,\"rows\":1,\"columns\":[{\"kind\":\"advice\",\"index\":1}]},",
+                "{\"name\":\"\\\"c\\\"\",\"start_row\":1,\"rows\":1,\"columns\":[{\"kind\":\"advice\",\"index\":0},{\"kind\":\"selector\",\"index\":0}]}",
+                "]}"
+            )
+        );
+        let svg = layout.to_svg();
+        assert_eq!(svg.matches("<title>").count(), 4);
+        assert!(svg.contains("&quot;c&quot; (region 2, rows 1..2)"));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_region_layout() {
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let (a, b) = (Fr::from(2), Fr::from(3));
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = crate::bn254::ProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        prover.set_master_key([7; 32]);
+        let c = constant * a.square() * b.square();
+
+        create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit.clone(), circuit],
+            &[&[&[c]], &[&[c]]],
+            &ProofOptions {
+                region_layout: true,
+                ..Default::default()
+            },
+            &mut TachyonBlake2bWrite::init(vec![]),
+        )
+        .expect("proof generation should not fail");
+        let layouts = prover.metrics().region_layout;
+        assert_eq!(layouts.len(), 2);
+        assert_eq!(layouts[1].circuit, 1);
+        assert_eq!(layouts[0].regions, layouts[1].regions);
+
+        // The first three regions load the inputs, a row each, and the three
+        // multiplications follow, two rows each.
+        let load = "{\"kind\":\"advice\",\"index\":0}";
+        let mul = "{\"kind\":\"advice\",\"index\":0},{\"kind\":\"advice\",\"index\":1},{\"kind\":\"selector\",\"index\":0}";
+        let regions = [
+            ("load private", 0, 1, load),
+            ("load private", 1, 1, load),
+            ("load constant", 2, 1, load),
+            ("mul", 3, 2, mul),
+            ("mul", 5, 2, mul),
+            ("mul", 7, 2, mul),
+        ]
+        .iter()
+        .map(|(name, start_row, rows, columns)| {
+            format!(
+                "{{\"name\":\"{}\",\"start_row\":{},\"rows\":{},\"columns\":[{}]}}",
+                name, start_row, rows, columns
+            )
+        })
+        .collect::<Vec<_>>();
+        assert_eq!(
+            layouts[0].to_json(),
+            format!(
+                "{{\"circuit\":0,\"usable_rows\":{},\"regions\":[{}]}}",
+                (1 << k) - (pk.get_vk().cs().blinding_factors() + 1),
+                regions.join(",")
+            )
+        );
+        assert!(layouts[0].overlapping_regions().is_empty());
+    }
+}
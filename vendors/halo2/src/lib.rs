@@ -1,9 +1,146 @@
+#[cfg(all(feature = "stub-backend", feature = "native-tests"))]
+compile_error!(
+    "\"native-tests\" needs libtachyon, build with --no-default-features to use \"stub-backend\""
+);
+#[cfg(all(feature = "stub-backend", feature = "ffi_trace"))]
+compile_error!("\"ffi_trace\" traces calls into libtachyon, which \"stub-backend\" leaves out");
+#[cfg(all(feature = "stub-backend", feature = "dlopen"))]
+compile_error!("\"dlopen\" loads libtachyon, which \"stub-backend\" leaves out");
+#[cfg(all(feature = "math-only", feature = "halo2"))]
+compile_error!("\"math-only\" leaves out halo2_proofs, build it with --no-default-features");
+
+#[macro_use]
+mod ffi_trace;
+
+#[cfg(feature = "halo2")]
+mod advice_order;
+#[cfg(feature = "halo2")]
+mod aggregate;
+#[cfg(feature = "halo2")]
+mod analyze;
+#[cfg(feature = "halo2")]
+mod annotation;
+#[cfg(feature = "halo2")]
+mod artifact;
+#[cfg(feature = "halo2")]
+mod assignments;
+#[cfg(feature = "halo2")]
+mod audit;
+mod backend_log;
 mod bn254;
+#[cfg(feature = "halo2")]
+mod cache;
+#[cfg(feature = "halo2")]
+mod chunked;
+#[cfg(feature = "halo2")]
+mod circuit_id;
+#[cfg(feature = "halo2")]
 mod circuits;
+#[cfg(feature = "halo2")]
+mod column;
+#[cfg(feature = "halo2")]
+mod compat;
 mod consts;
+#[cfg(feature = "halo2")]
+mod debug;
+#[cfg(feature = "dlopen")]
+mod dlopen;
+#[cfg(feature = "halo2")]
+mod equivalence;
+mod error;
+#[cfg(feature = "halo2")]
+mod estimate;
+#[cfg(all(feature = "halo2", any(test, feature = "examples")))]
+mod example;
+mod field;
+#[cfg(all(feature = "halo2", any(test, feature = "fuzz")))]
+mod fuzz;
+#[cfg(feature = "halo2")]
+mod healthcheck;
+#[cfg(feature = "halo2")]
+mod incremental;
+#[cfg(feature = "halo2")]
+mod instance;
+#[cfg(feature = "halo2")]
+mod instance_hash;
+#[cfg(feature = "halo2")]
+mod instance_source;
+#[cfg(feature = "halo2")]
+mod interop;
+#[cfg(feature = "halo2")]
+mod keygen;
+#[cfg(feature = "halo2")]
+mod layout;
+#[cfg(feature = "halo2")]
+mod limits;
+#[cfg(feature = "halo2")]
+mod metrics;
 mod msm;
+#[cfg(feature = "halo2")]
+mod opening;
+#[cfg(feature = "halo2")]
+mod par;
+#[cfg(feature = "halo2")]
+mod phase;
+#[cfg(feature = "halo2")]
+mod placement;
+mod pool;
+#[cfg(feature = "halo2")]
+mod precommit;
+#[cfg(feature = "halo2")]
+mod preset;
+#[cfg(feature = "halo2")]
+mod proof;
+#[cfg(feature = "halo2")]
 mod prover;
+#[cfg(feature = "halo2")]
 mod proving_key;
+#[cfg(feature = "halo2")]
+mod race;
+mod redact;
+#[cfg(feature = "halo2")]
+mod registry;
+#[cfg(feature = "halo2")]
+mod repro;
+#[cfg(feature = "halo2")]
+mod rng;
+#[cfg(feature = "halo2")]
+mod selector_map;
+#[cfg(feature = "halo2")]
+mod self_check;
+mod self_test;
+mod serde_fr;
+#[cfg(feature = "halo2")]
 mod sha;
+#[cfg(feature = "halo2")]
+mod shared;
+#[cfg(feature = "halo2")]
+mod snark;
+#[cfg(feature = "halo2")]
+mod srs;
+#[cfg(all(feature = "halo2", feature = "shared-srs"))]
+mod srs_store;
+#[cfg(feature = "halo2")]
+mod state;
+#[cfg(all(feature = "halo2", feature = "stats"))]
+mod stats;
+#[cfg(all(feature = "halo2", feature = "stress"))]
+mod stress;
+#[cfg(feature = "stub-backend")]
+mod stub;
+#[cfg(feature = "halo2")]
 mod transcript;
+#[cfg(feature = "halo2")]
+mod verifier;
+#[cfg(feature = "halo2")]
+mod verifying_key;
+mod version;
+#[cfg(feature = "halo2")]
+mod vk_hash;
+#[cfg(feature = "halo2")]
 mod xor_shift_rng;
+
+#[cfg(all(feature = "halo2", feature = "examples"))]
+pub use example::{prove_and_encode_evm, prove_lookup, prove_simple};
+#[cfg(all(feature = "halo2", feature = "fuzz"))]
+pub use fuzz::{parse_all, NUM_PARSERS};
@@ -0,0 +1,251 @@
+use std::time::{Duration, Instant};
+
+use halo2_proofs::poly::commitment::CommitmentScheme;
+
+use crate::{
+    bn254::{ProvingKey as TachyonProvingKey, TachyonProver},
+    error::TachyonError,
+    estimate::{memory_for_proof, EstimateOptions, MemoryEstimate},
+};
+
+/// Ceilings on what a single proof may use, e.g, for a proving service that
+/// runs the proofs of several tenants. See [`crate::prover::ProofOptions::limits`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub max_k: Option<u32>,
+    pub max_advice_columns: Option<usize>,
+    /// Checked between the steps of the proof, so the backend's part, which
+    /// is a single call, can overrun it.
+    pub max_wall_time: Option<Duration>,
+    /// Checked against [`crate::estimate::memory_for_proof()`] before
+    /// proving only. The backend doesn't report the device memory it holds
+    /// while proving, so a proof the estimate undercounts isn't stopped.
+    pub max_device_bytes: Option<u64>,
+}
+
+/// Which of the [`ResourceLimits`] a proof exceeded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    K,
+    AdviceColumns,
+    /// In milliseconds.
+    WallTime,
+    DeviceBytes,
+}
+
+impl Limit {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Limit::K => "k",
+            Limit::AdviceColumns => "advice columns",
+            Limit::WallTime => "wall time (ms)",
+            Limit::DeviceBytes => "device bytes",
+        }
+    }
+}
+
+fn check(which: Limit, limit: Option<u64>, observed: u64) -> Result<(), TachyonError> {
+    match limit {
+        Some(limit) if observed > limit => Err(TachyonError::LimitExceeded {
+            which,
+            limit,
+            observed,
+        }),
+        _ => Ok(()),
+    }
+}
+
+impl ResourceLimits {
+    /// Checks `estimate`, the memory a proof is expected to need.
+    pub fn check_estimate(&self, estimate: &MemoryEstimate) -> Result<(), TachyonError> {
+        check(
+            Limit::DeviceBytes,
+            self.max_device_bytes,
+            estimate.device_bytes,
+        )
+    }
+
+    /// Checks what is known before a proof of `num_circuits` circuits with
+    /// `prover` and `pk` allocates anything, splitting the IFFT of the
+    /// extended domain as `fft_chunk_log2` says.
+    pub(crate) fn check_before<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+        &self,
+        prover: &P,
+        pk: &TachyonProvingKey<Scheme::Curve>,
+        num_circuits: usize,
+        fft_chunk_log2: u32,
+    ) -> Result<(), TachyonError> {
+        check(Limit::K, self.max_k.map(u64::from), u64::from(prover.k()))?;
+        check(
+            Limit::AdviceColumns,
+            self.max_advice_columns.map(|max| max as u64),
+            pk.num_advice_columns() as u64,
+        )?;
+        let estimate = memory_for_proof(
+            pk,
+            prover,
+            &EstimateOptions {
+                num_circuits,
+                fft_chunk_log2,
+                ..Default::default()
+            },
+        );
+        self.check_estimate(&estimate)
+    }
+
+    /// Checks the wall time of a proof that began at `start`.
+    pub(crate) fn check_during(&self, start: Instant) -> Result<(), TachyonError> {
+        let elapsed = start.elapsed();
+        match self.max_wall_time {
+            Some(max) if elapsed > max => Err(TachyonError::LimitExceeded {
+                which: Limit::WallTime,
+                limit: max.as_millis() as u64,
+                observed: elapsed.as_millis() as u64,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::TranscriptWriterBuffer,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{Limit, ResourceLimits};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::TranscriptType,
+        error::TachyonError,
+        estimate::{MemoryEstimate, Stage},
+        prover::{create_proof_with_options, ProofOptions},
+        rng::proof_context,
+    };
+
+    #[test]
+    fn test_check_estimate() {
+        let estimate = MemoryEstimate {
+            host_bytes: 1 << 20,
+            device_bytes: 1 << 10,
+            breakdown: vec![(Stage::Commitment, 1 << 10)],
+        };
+        let limits = |max_device_bytes| ResourceLimits {
+            max_device_bytes,
+            ..Default::default()
+        };
+        assert!(limits(None).check_estimate(&estimate).is_ok());
+        assert!(limits(Some(1 << 10)).check_estimate(&estimate).is_ok());
+        assert!(matches!(
+            limits(Some(1 << 9)).check_estimate(&estimate),
+            Err(TachyonError::LimitExceeded {
+                which: Limit::DeviceBytes,
+                limit: 512,
+                observed: 1024
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_limits() {
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = vec![&public_inputs[..]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let num_advice_columns = TachyonProvingKey::try_from(pk_bytes.as_slice())
+            .unwrap()
+            .num_advice_columns();
+
+        let prove = |limits| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let options = ProofOptions {
+                rng_context: proof_context(b"simple", 0),
+                limits,
+                ..Default::default()
+            };
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&instances[..]],
+                &options,
+                &mut transcript,
+            )
+            .map(|_| ())
+        };
+
+        let generous = ResourceLimits {
+            max_k: Some(k),
+            max_advice_columns: Some(num_advice_columns),
+            max_wall_time: Some(Duration::from_secs(600)),
+            max_device_bytes: Some(u64::MAX),
+        };
+        prove(generous).expect("proof generation should not fail");
+
+        assert!(matches!(
+            prove(ResourceLimits {
+                max_k: Some(k - 1),
+                ..generous
+            }),
+            Err(TachyonError::LimitExceeded {
+                which: Limit::K,
+                limit: 3,
+                observed: 4
+            })
+        ));
+        assert!(matches!(
+            prove(ResourceLimits {
+                max_advice_columns: Some(num_advice_columns - 1),
+                ..generous
+            }),
+            Err(TachyonError::LimitExceeded {
+                which: Limit::AdviceColumns,
+                limit,
+                observed
+            }) if limit + 1 == observed
+        ));
+        assert!(matches!(
+            prove(ResourceLimits {
+                max_wall_time: Some(Duration::ZERO),
+                ..generous
+            }),
+            Err(TachyonError::LimitExceeded {
+                which: Limit::WallTime,
+                limit: 0,
+                ..
+            })
+        ));
+    }
+}
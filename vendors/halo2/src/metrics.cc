@@ -0,0 +1,12 @@
+#include "vendors/halo2/include/metrics.h"
+
+#include "tachyon/c/device_memory.h"
+#include "vendors/halo2/include/dlopen.h"
+
+namespace tachyon::halo2_api {
+
+bool device_memory_in_use(size_t& bytes) {
+  return tachyon_get_device_memory_in_use(&bytes);
+}
+
+}  // namespace tachyon::halo2_api
@@ -0,0 +1,271 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::layout::RegionLayout;
+
+#[cfg(feature = "stub-backend")]
+use crate::stub::metrics as ffi;
+
+#[cfg(not(feature = "stub-backend"))]
+#[cxx::bridge(namespace = "tachyon::halo2_api")]
+mod ffi {
+    unsafe extern "C++" {
+        include!("vendors/halo2/include/metrics.h");
+
+        fn device_memory_in_use(bytes: &mut usize) -> bool;
+    }
+}
+
+/// How often [`PeakTracker`] samples the memory in use on the GPU.
+const DEVICE_SAMPLE_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Counters and high-water marks collected while proving.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProofMetrics {
+    /// How far the resident set size of the process rose above what it was
+    /// when the last proof began, at its highest, in bytes. It stays zero
+    /// where the OS can't reset its peak, see [`reset_peak_host_bytes()`].
+    pub peak_host_bytes: u64,
+    /// How far the memory in use on the GPU rose above what it was when the
+    /// last proof began, at its highest, in bytes. It is sampled every
+    /// millisecond, so a shorter spike can be missed, and counts other
+    /// processes on the GPU too. It stays zero without CUDA.
+    pub peak_device_bytes: u64,
+    /// The number of `Evals` allocated by the backend instead of being reused
+    /// from the pool.
+    pub num_evals_allocations: u64,
+    /// The number of `RationalEvals` allocated by the backend instead of being
+    /// reused from the pool.
+    pub num_rational_evals_allocations: u64,
+    /// The statistics of every advice column of the last proof created with
+    /// [`crate::prover::ProofOptions::column_report`], ordered by circuit and
+    /// column.
+    pub column_report: Vec<ColumnReport>,
+    /// Where the floor planner placed the regions of every circuit of the
+    /// last proof created with [`crate::prover::ProofOptions::region_layout`],
+    /// ordered by circuit.
+    pub region_layout: Vec<RegionLayout>,
+    /// What evaluating every custom gate for the quotient cost in the last
+    /// proof created with [`crate::prover::ProofOptions::gate_costs`],
+    /// ordered by gate.
+    pub gate_costs: Vec<GateCost>,
+}
+
+/// How much of the domain the values of a column use, to find columns that
+/// could be merged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColumnStats {
+    /// The number of rows whose value isn't zero.
+    pub num_nonzero_rows: usize,
+    /// The index of the highest nonzero coefficient of the column's
+    /// polynomial, or `None` if every value is zero.
+    pub highest_nonzero_coeff: Option<usize>,
+    /// The Shannon entropy of the values over all the `n` rows, in bits. It is
+    /// 0 for a constant column and `k` if every value is distinct.
+    pub entropy: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnReport {
+    pub circuit: usize,
+    pub column: usize,
+    pub stats: ColumnStats,
+}
+
+/// What the backend spent evaluating a custom gate over the extended domain,
+/// summed over the circuits of a proof.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GateCost {
+    /// The index of the gate in the constraint system.
+    pub gate: usize,
+    /// The name the circuit gave the gate, or its index if it gave none.
+    pub name: String,
+    /// The time spent in the calculations of the gate. Only every 64th row is
+    /// timed and the rest is extrapolated, so it is an estimate, which
+    /// includes the overhead of reading the clock.
+    pub duration: Duration,
+    /// The calculations of the gate, e.g, a product or a sum, times the rows
+    /// they were evaluated at. This is exact. A calculation that an earlier
+    /// gate shares is only counted for that one.
+    pub ops: u64,
+}
+
+/// The bytes a prover keeps allocated between proofs, to find what grows
+/// over many proofs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllocationStats {
+    /// Every category with its bytes, e.g, `("srs", 8192)`.
+    pub categories: Vec<(&'static str, u64)>,
+}
+
+impl AllocationStats {
+    pub fn get(&self, category: &str) -> Option<u64> {
+        self.categories
+            .iter()
+            .find(|(name, _)| *name == category)
+            .map(|(_, bytes)| *bytes)
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.categories.iter().map(|(_, bytes)| *bytes).sum()
+    }
+}
+
+/// How the commitments of a prover reuse the scratch buffers of their MSMs,
+/// which the backend keeps between them instead of allocating them for each.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScratchStats {
+    /// The bytes held by the scratch buffers between commitments.
+    pub bytes: u64,
+    /// The commitments that had to allocate scratch buffers.
+    pub num_allocations: u64,
+    /// The commitments that ran in scratch buffers kept from earlier ones.
+    pub num_reuses: u64,
+}
+
+/// Returns the peak resident set size of the current process in bytes, or
+/// `None` if the platform doesn't report it.
+pub fn peak_host_bytes() -> Option<u64> {
+    read_status_bytes("VmHWM:")
+}
+
+/// Returns the current resident set size of the current process in bytes, or
+/// `None` if the platform doesn't report it. Unlike [`peak_host_bytes()`], it
+/// goes down when memory is freed.
+pub fn current_host_bytes() -> Option<u64> {
+    read_status_bytes("VmRSS:")
+}
+
+/// Lowers the peak [`peak_host_bytes()`] reports to the current resident set
+/// size, for the whole process. Returns false if the platform can't, e.g,
+/// outside Linux or before Linux 4.0.
+pub fn reset_peak_host_bytes() -> bool {
+    std::fs::write("/proc/self/clear_refs", "5").is_ok()
+}
+
+/// Returns the bytes of memory in use on the GPU, by any process, or `None`
+/// if libtachyon was built without CUDA.
+pub fn device_bytes_in_use() -> Option<u64> {
+    let mut bytes = 0;
+    ffi::device_memory_in_use(&mut bytes).then_some(bytes as u64)
+}
+
+/// Measures how far the memory in use rises above what it was when a proof
+/// began, for [`ProofMetrics::peak_host_bytes`] and
+/// [`ProofMetrics::peak_device_bytes`]. The host peak is kept by the OS. The
+/// device one is sampled by a thread of its own, which only runs with CUDA,
+/// until the tracker is dropped.
+pub(crate) struct PeakTracker {
+    /// The resident set size when the proof began, `None` unless the OS
+    /// lowered its peak to it.
+    host_start: Option<u64>,
+    device_start: u64,
+    device_peak: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    sampler: Option<JoinHandle<()>>,
+}
+
+impl PeakTracker {
+    pub(crate) fn start() -> Self {
+        let host_start = if reset_peak_host_bytes() {
+            current_host_bytes()
+        } else {
+            None
+        };
+        let device_start = device_bytes_in_use();
+        let device_peak = Arc::new(AtomicU64::new(device_start.unwrap_or(0)));
+        let stop = Arc::new(AtomicBool::new(false));
+        let sampler = device_start.map(|_| {
+            let (device_peak, stop) = (device_peak.clone(), stop.clone());
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    if let Some(bytes) = device_bytes_in_use() {
+                        device_peak.fetch_max(bytes, Ordering::Relaxed);
+                    }
+                    thread::sleep(DEVICE_SAMPLE_INTERVAL);
+                }
+            })
+        });
+        PeakTracker {
+            host_start,
+            device_start: device_start.unwrap_or(0),
+            device_peak,
+            stop,
+            sampler,
+        }
+    }
+
+    /// Returns how far the resident set size rose since [`PeakTracker::start()`],
+    /// or 0 if the OS couldn't lower its peak then.
+    pub(crate) fn host_bytes(&self) -> u64 {
+        match (self.host_start, peak_host_bytes()) {
+            (Some(start), Some(peak)) => peak.saturating_sub(start),
+            _ => 0,
+        }
+    }
+
+    /// Stops sampling and returns how far the memory in use on the GPU rose
+    /// since [`PeakTracker::start()`].
+    pub(crate) fn device_bytes(&mut self) -> u64 {
+        self.stop_sampler();
+        self.device_peak
+            .load(Ordering::Relaxed)
+            .saturating_sub(self.device_start)
+    }
+
+    fn stop_sampler(&mut self) {
+        if let Some(sampler) = self.sampler.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            // The sampler only reads a counter; it can't panic.
+            let _ = sampler.join();
+        }
+    }
+}
+
+impl Drop for PeakTracker {
+    fn drop(&mut self) {
+        self.stop_sampler();
+    }
+}
+
+fn read_status_bytes(field: &str) -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status.lines().find_map(|line| {
+        // e.g, "VmHWM:     12345 kB"
+        let value = line.strip_prefix(field)?;
+        let kb = value
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+        Some(kb * 1024)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_peak_host_bytes() {
+        let before = super::peak_host_bytes().unwrap();
+        let buffer = vec![1u8; 64 << 20];
+        let after = super::peak_host_bytes().unwrap();
+        assert!(after >= before);
+        assert!(after >= buffer.len() as u64);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_current_host_bytes() {
+        let current = super::current_host_bytes().unwrap();
+        assert!(current > 0);
+        assert!(current <= super::peak_host_bytes().unwrap());
+    }
+}
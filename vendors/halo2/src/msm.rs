@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod test {
     use crate::bn254::{ffi, Fr as CppFr, G1Point2 as CppG1Point2};
+    #[cfg(feature = "halo2")]
     use halo2_proofs::arithmetic::best_multiexp;
     use halo2curves::{
         bn256::{Fr, G1Affine, G1},
@@ -8,6 +9,16 @@ mod test {
     };
     use std::{mem, time::Instant};
 
+    // The expected results come from halo2's MSM, which isn't there without
+    // the "halo2" feature.
+    #[cfg(not(feature = "halo2"))]
+    fn best_multiexp(scalars: &[Fr], bases: &[G1Affine]) -> G1 {
+        bases
+            .iter()
+            .zip(scalars)
+            .fold(G1::identity(), |acc, (base, scalar)| acc + base * scalar)
+    }
+
     struct Timer {
         now: Instant,
     }
@@ -52,6 +63,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_msm() {
         let degree = 10;
         let n = 1usize << degree;
@@ -83,6 +95,7 @@ mod test {
 
     #[cfg(feature = "gpu")]
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_msm_gpu() {
         let degree = 10;
         let n = 1usize << degree;
@@ -113,4 +126,37 @@ mod test {
 
         ffi::destroy_g1_msm_gpu(msm);
     }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_msm_gpu_concurrent() {
+        // Each instance owns its stream and memory pool, so creating the second
+        // one must not tear down the first while it's running.
+        let degree = 10;
+        let n = 1usize << degree;
+
+        let handles = (0..2)
+            .map(|_| {
+                let test_set = TestSet::create(n);
+                let msm = ffi::create_g1_msm_gpu(degree, 0);
+                std::thread::spawn(move || {
+                    let expected = best_multiexp(&test_set.scalars, &test_set.bases);
+                    let mut msm = msm;
+                    let actual = unsafe {
+                        let bases: Vec<CppG1Point2> = mem::transmute(test_set.bases);
+                        let scalars: Vec<CppFr> = mem::transmute(test_set.scalars);
+                        let actual = ffi::g1_point2_msm_gpu(&mut *msm, &bases, &scalars);
+                        let actual: Box<G1> = mem::transmute(actual);
+                        *actual
+                    };
+                    ffi::destroy_g1_msm_gpu(msm);
+                    assert_eq!(actual, expected);
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
 }
@@ -0,0 +1,652 @@
+use std::{collections::BTreeSet, io};
+
+use halo2_proofs::{
+    plonk::Error,
+    poly::{
+        commitment::Verifier,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::SingleStrategy,
+        },
+        Rotation, VerificationStrategy, VerifierQuery,
+    },
+    transcript::{Challenge255, TranscriptRead},
+};
+use halo2curves::{
+    bn256::{Bn256, Fr, G1Affine},
+    CurveAffine,
+};
+
+use crate::{
+    bn254::{Poly, ProvingKey, SHPlonkProver, TachyonProver, TranscriptWriteState},
+    column::{AdviceColumnIndex, FixedColumnIndex, InstanceColumnIndex},
+    consts::MultiOpenScheme,
+    proof::PointEncoding,
+    serde_fr::{frs_from_le_bytes, FR_BYTES},
+};
+
+/// A polynomial the multi-open argument of a proof opens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PolyRef {
+    /// Only opened with [`MultiOpenScheme::Gwc`].
+    Instance {
+        circuit: usize,
+        column: InstanceColumnIndex,
+    },
+    Advice {
+        circuit: usize,
+        column: AdviceColumnIndex,
+    },
+    Fixed {
+        column: FixedColumnIndex,
+    },
+    /// The grand product of the permutation set `set`.
+    PermutationProduct {
+        circuit: usize,
+        set: usize,
+    },
+    LookupProduct {
+        circuit: usize,
+        lookup: usize,
+    },
+    LookupPermutedInput {
+        circuit: usize,
+        lookup: usize,
+    },
+    LookupPermutedTable {
+        circuit: usize,
+        lookup: usize,
+    },
+    /// The permutation polynomial of the `column`-th column in the
+    /// permutation argument, shared by all circuits.
+    CommonPermutation {
+        column: usize,
+    },
+    /// The pieces of the quotient combined at `x`. Its evaluation isn't
+    /// written to the proof; the verifier recomputes it from the others.
+    Quotient,
+    VanishingRandom,
+}
+
+/// An opening of `poly` at `x·ω^rotation`, like halo2's `ProverQuery` without
+/// the polynomial itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpeningQuery {
+    pub poly: PolyRef,
+    pub rotation: Rotation,
+}
+
+impl OpeningQuery {
+    fn new(poly: PolyRef, rotation: i32) -> Self {
+        Self {
+            poly,
+            rotation: Rotation(rotation),
+        }
+    }
+}
+
+/// Returns every query the multi-open argument of a proof of `num_circuits`
+/// circuits with `pk` opens with `scheme`, in the order it opens them. This is
+/// the order of `plonk::prover::create_proof()`: the instances, advice,
+/// permutation products and lookups of each circuit, then the fixed columns,
+/// the common permutation polynomials and the vanishing argument.
+pub fn opening_queries<C: CurveAffine>(
+    pk: &ProvingKey<C>,
+    num_circuits: usize,
+    scheme: MultiOpenScheme,
+) -> Vec<OpeningQuery> {
+    let num_permutation_columns = pk.num_permutation_columns();
+    // Each permutation product covers as many columns as fit in the degree
+    // bound, see `permutation::Argument::commit()`.
+    let chunk_len = pk.degree() - 2;
+    let num_sets = (num_permutation_columns + chunk_len - 1) / chunk_len;
+    let last_rotation = -(pk.blinding_factors() as i32 + 1);
+    let instance_queries = pk.instance_queries();
+    let advice_queries = pk.advice_queries();
+
+    let mut queries = vec![];
+    for circuit in 0..num_circuits {
+        if scheme == MultiOpenScheme::Gwc {
+            queries.extend(instance_queries.iter().map(|(column, rotation)| {
+                OpeningQuery::new(
+                    PolyRef::Instance {
+                        circuit,
+                        column: *column,
+                    },
+                    rotation.0,
+                )
+            }));
+        }
+        queries.extend(advice_queries.iter().map(|(column, rotation)| {
+            OpeningQuery::new(
+                PolyRef::Advice {
+                    circuit,
+                    column: *column,
+                },
+                rotation.0,
+            )
+        }));
+        for set in 0..num_sets {
+            let poly = PolyRef::PermutationProduct { circuit, set };
+            queries.push(OpeningQuery::new(poly, 0));
+            queries.push(OpeningQuery::new(poly, 1));
+        }
+        // Every set but the last is also opened at the last usable row, in
+        // reverse order.
+        for set in (0..num_sets.saturating_sub(1)).rev() {
+            let poly = PolyRef::PermutationProduct { circuit, set };
+            queries.push(OpeningQuery::new(poly, last_rotation));
+        }
+        for lookup in 0..pk.num_lookups() {
+            let product = PolyRef::LookupProduct { circuit, lookup };
+            let input = PolyRef::LookupPermutedInput { circuit, lookup };
+            let table = PolyRef::LookupPermutedTable { circuit, lookup };
+            queries.extend([
+                OpeningQuery::new(product, 0),
+                OpeningQuery::new(input, 0),
+                OpeningQuery::new(table, 0),
+                OpeningQuery::new(input, -1),
+                OpeningQuery::new(product, 1),
+            ]);
+        }
+    }
+    queries.extend(pk.fixed_queries().iter().map(|(column, rotation)| {
+        OpeningQuery::new(PolyRef::Fixed { column: *column }, rotation.0)
+    }));
+    queries.extend(
+        (0..num_permutation_columns)
+            .map(|column| OpeningQuery::new(PolyRef::CommonPermutation { column }, 0)),
+    );
+    queries.push(OpeningQuery::new(PolyRef::Quotient, 0));
+    queries.push(OpeningQuery::new(PolyRef::VanishingRandom, 0));
+    queries
+}
+
+/// Where the evaluation of `query` is written among the evaluations of a
+/// proof, or `None` if it isn't. The proof writes them grouped by kind rather
+/// than in the order they are opened in, see
+/// [`crate::transcript::transcript_layout()`]. Queries of the same rank keep
+/// their relative order.
+fn written_rank(query: &OpeningQuery) -> Option<(u8, usize, usize)> {
+    let rotation = query.rotation.0;
+    Some(match query.poly {
+        PolyRef::Instance { circuit, .. } => (0, circuit, 0),
+        PolyRef::Advice { circuit, .. } => (1, circuit, 0),
+        PolyRef::Fixed { .. } => (2, 0, 0),
+        PolyRef::VanishingRandom => (3, 0, 0),
+        PolyRef::CommonPermutation { column } => (4, 0, column),
+        PolyRef::PermutationProduct { circuit, set } => {
+            let next = match rotation {
+                0 => 0,
+                1 => 1,
+                _ => 2,
+            };
+            (5, circuit, set * 3 + next)
+        }
+        PolyRef::LookupProduct { circuit, lookup } => {
+            (6, circuit, lookup * 5 + if rotation == 0 { 0 } else { 1 })
+        }
+        PolyRef::LookupPermutedInput { circuit, lookup } => {
+            (6, circuit, lookup * 5 + if rotation == 0 { 2 } else { 3 })
+        }
+        PolyRef::LookupPermutedTable { circuit, lookup } => (6, circuit, lookup * 5 + 4),
+        PolyRef::Quotient => return None,
+    })
+}
+
+/// The number of commitments the multi-open argument ends a proof that opens
+/// `queries` with.
+fn num_openings(queries: &[OpeningQuery], scheme: MultiOpenScheme) -> usize {
+    match scheme {
+        MultiOpenScheme::SHPlonk => 2,
+        // A witness per distinct rotation.
+        MultiOpenScheme::Gwc => queries
+            .iter()
+            .map(|query| query.rotation.0)
+            .collect::<BTreeSet<_>>()
+            .len(),
+    }
+}
+
+/// Reads the claimed evaluation of every query in `queries` but the quotient
+/// from `proof`, which ends with the evaluations followed by the commitments
+/// of the multi-open argument, as the bytes the backend writes do. The
+/// evaluations are returned in the order of `queries`.
+pub(crate) fn read_evaluations(
+    queries: &[OpeningQuery],
+    scheme: MultiOpenScheme,
+    encoding: PointEncoding,
+    proof: &[u8],
+) -> io::Result<Vec<(OpeningQuery, Fr)>> {
+    let mut written = (0..queries.len())
+        .filter(|i| written_rank(&queries[*i]).is_some())
+        .collect::<Vec<_>>();
+    written.sort_by_key(|i| written_rank(&queries[*i]));
+
+    let openings_len = num_openings(queries, scheme) * encoding.point_len();
+    let evaluations_len = written.len() * FR_BYTES;
+    let end = proof
+        .len()
+        .checked_sub(openings_len)
+        .filter(|end| *end >= evaluations_len)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "a proof of {} bytes is too short for {} evaluations",
+                    proof.len(),
+                    written.len()
+                ),
+            )
+        })?;
+    let values = frs_from_le_bytes(&proof[end - evaluations_len..end])
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+    let mut evaluations = vec![None; queries.len()];
+    for (i, value) in written.into_iter().zip(values) {
+        evaluations[i] = Some(value);
+    }
+    Ok(queries
+        .iter()
+        .zip(evaluations)
+        .filter_map(|(query, value)| value.map(|value| (*query, value)))
+        .collect())
+}
+
+/// Proves that every `(poly, point)` in `queries` opens to `poly(point)`,
+/// continuing from the current state of `transcript`. This is the same proof
+/// as `ProverSHPLONK::create_proof()` creates for the same queries.
+///
+/// Like [`crate::prover::create_proof()`], the proof bytes end up in the
+/// prover and must be appended to the finalized `transcript` with
+/// `prover.get_proof()`.
+pub fn create_multi_open<T: TranscriptWriteState<G1Affine, Challenge255<G1Affine>>>(
+    prover: &mut SHPlonkProver<KZGCommitmentScheme<Bn256>>,
+    queries: &[(&Poly, Fr)],
+    transcript: &mut T,
+) -> Result<(), Error> {
+    prover
+        .set_transcript(transcript.state().as_slice())
+        .map_err(|e| {
+            Error::Transcript(io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+        })?;
+    prover.create_opening_proof(queries);
+    Ok(())
+}
+
+/// Verifies a proof created by [`create_multi_open()`] or
+/// `ProverSHPLONK::create_proof()` for queries given as
+/// `(commitment, point, evaluation)`.
+pub fn verify_multi_open<'params, T: TranscriptRead<G1Affine, Challenge255<G1Affine>>>(
+    params: &'params ParamsKZG<Bn256>,
+    queries: &[(G1Affine, Fr, Fr)],
+    transcript: &mut T,
+) -> Result<(), Error> {
+    let verifier = VerifierSHPLONK::new(params);
+    let queries = queries
+        .iter()
+        .map(|(commitment, point, eval)| VerifierQuery::new_commitment(commitment, *point, *eval));
+    <SingleStrategy<'params, Bn256> as VerificationStrategy<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        VerifierSHPLONK<'params, Bn256>,
+    >>::process(SingleStrategy::new(params), |msm| {
+        verifier.verify_proof(transcript, queries, msm)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use halo2_proofs::{
+        arithmetic::eval_polynomial,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{
+            keygen_pk2, Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance,
+            Selector, TableColumn,
+        },
+        poly::{
+            commitment::{Blind, ParamsProver, Prover},
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::ProverSHPLONK,
+            },
+            EvaluationDomain, ProverQuery, Rotation,
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+        SerdeFormat,
+    };
+    use halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        group::Curve,
+    };
+    use rand_core::OsRng;
+
+    use super::{create_multi_open, verify_multi_open, OpeningQuery, PolyRef};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        consts::{MultiOpenScheme, TranscriptType},
+        proof::{parse, PointEncoding},
+        prover::{create_proof_with_options, ProofOptions},
+        transcript::{transcript_layout, Component, TranscriptItem},
+    };
+
+    #[derive(Clone, Debug)]
+    struct CopiedLookupConfig {
+        advice: [Column<Advice>; 6],
+        instance: Column<Instance>,
+        selector: Selector,
+        table: TableColumn,
+    }
+
+    /// Looks up the value of its first column and copies it through the
+    /// others to the instance, so that a proof opens lookups and more than one
+    /// permutation set.
+    #[derive(Clone, Debug, Default)]
+    struct CopiedLookupCircuit;
+
+    impl Circuit<Fr> for CopiedLookupCircuit {
+        type Config = CopiedLookupConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> CopiedLookupConfig {
+            let advice = [(); 6].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            for column in advice {
+                meta.enable_equality(column);
+            }
+            meta.enable_equality(instance);
+            let selector = meta.complex_selector();
+            let table = meta.lookup_table_column();
+            meta.lookup("lookup", |meta| {
+                let selector = meta.query_selector(selector);
+                let not_selector = Expression::Constant(Fr::one()) - selector.clone();
+                let value = meta.query_advice(advice[0], Rotation::cur());
+                vec![(selector * value + not_selector, table)]
+            });
+            CopiedLookupConfig {
+                advice,
+                instance,
+                selector,
+                table,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: CopiedLookupConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "table",
+                |mut table| {
+                    for row in 0..8 {
+                        table.assign_cell(
+                            || "row",
+                            config.table,
+                            row,
+                            || Value::known(Fr::from(row as u64 + 1)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+            let cell = layouter.assign_region(
+                || "copies",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    let mut cell = region.assign_advice(
+                        || "value",
+                        config.advice[0],
+                        0,
+                        || Value::known(Fr::from(3)),
+                    )?;
+                    for column in &config.advice[1..] {
+                        cell = cell.copy_advice(|| "copy", &mut region, *column, 0)?;
+                    }
+                    Ok(cell)
+                },
+            )?;
+            layouter.constrain_instance(cell.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_multi_open() {
+        let k = 4;
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        let domain = EvaluationDomain::<Fr>::new(1, k);
+
+        let values: Vec<Vec<Fr>> = (0..3)
+            .map(|_| (0..prover.n()).map(|_| Fr::random(OsRng)).collect())
+            .collect();
+        let halo2_polys: Vec<_> = values
+            .iter()
+            .map(|values| domain.lagrange_to_coeff(domain.lagrange_from_vec(values.clone())))
+            .collect();
+        let tachyon_polys: Vec<_> = values
+            .iter()
+            .map(|values| {
+                let mut evals = prover.empty_evals();
+                for (i, value) in values.iter().enumerate() {
+                    evals.set_value(i, value);
+                }
+                prover.ifft(&evals)
+            })
+            .collect();
+
+        // The first polynomial is opened at both points.
+        let x1 = Fr::random(OsRng);
+        let x2 = Fr::random(OsRng);
+        let opened = [(0, x1), (1, x1), (0, x2), (2, x2)];
+
+        let halo2_proof = {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            let queries = opened.iter().map(|(i, point)| ProverQuery {
+                point: *point,
+                poly: &halo2_polys[*i],
+                blind: Blind::default(),
+            });
+            ProverSHPLONK::new(&params)
+                .create_proof(OsRng, &mut transcript, queries)
+                .unwrap();
+            transcript.finalize()
+        };
+
+        let tachyon_proof = {
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            let queries: Vec<_> = opened
+                .iter()
+                .map(|(i, point)| (&tachyon_polys[*i], *point))
+                .collect();
+            create_multi_open(&mut prover, &queries, &mut transcript).unwrap();
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+        assert_eq!(halo2_proof, tachyon_proof);
+
+        let queries: Vec<_> = opened
+            .iter()
+            .map(|(i, point)| {
+                let poly = &halo2_polys[*i];
+                let commitment = params.commit(poly, Blind::default()).to_affine();
+                let eval = eval_polynomial(poly, *point);
+                (commitment, *point, eval)
+            })
+            .collect();
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&tachyon_proof[..]);
+        assert!(verify_multi_open(&params, &queries, &mut transcript).is_ok());
+
+        let mut wrong_queries = queries.clone();
+        wrong_queries[0].2 += Fr::one();
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&tachyon_proof[..]);
+        assert!(verify_multi_open(&params, &wrong_queries, &mut transcript).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_opening_queries_match_proof() {
+        let k = 5;
+        let s = Fr::from(2);
+        let circuits = [CopiedLookupCircuit, CopiedLookupCircuit];
+        let instance = [Fr::from(3)];
+        let instances: [&[&[Fr]]; 2] = [&[&instance[..]], &[&instance[..]]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuits[0]).expect("pk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
+
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        let artifacts = create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &circuits,
+            &instances,
+            &ProofOptions {
+                evaluations: true,
+                ..ProofOptions::default()
+            },
+            &mut transcript,
+        )
+        .unwrap();
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+
+        let queries = super::opening_queries(&tachyon_pk, 2, MultiOpenScheme::SHPlonk);
+        // The fixed columns, the common permutation polynomials and the
+        // vanishing argument are opened once for all circuits.
+        let shared = tachyon_pk.fixed_queries().len() + tachyon_pk.num_permutation_columns() + 2;
+        assert_eq!(
+            queries.len() - shared,
+            2 * (tachyon_pk.opening_queries(MultiOpenScheme::SHPlonk).len() - shared)
+        );
+        let last_rotation = Rotation(-(tachyon_pk.blinding_factors() as i32 + 1));
+        assert!(queries.iter().any(|query| query.rotation == last_rotation));
+        assert!(queries
+            .iter()
+            .all(|query| !matches!(query.poly, PolyRef::Instance { .. })));
+
+        // What each evaluation the layout lists is the evaluation of.
+        let query = |item: &TranscriptItem| {
+            let circuit = item.circuit.unwrap_or_default();
+            let (poly, rotation) = match item.component {
+                Component::AdviceEval => {
+                    let (column, rotation) = tachyon_pk.advice_queries()[item.index];
+                    (PolyRef::Advice { circuit, column }, rotation)
+                }
+                Component::FixedEval => {
+                    let (column, rotation) = tachyon_pk.fixed_queries()[item.index];
+                    (PolyRef::Fixed { column }, rotation)
+                }
+                Component::VanishingRandomEval => (PolyRef::VanishingRandom, Rotation::cur()),
+                Component::CommonPermutationEval => (
+                    PolyRef::CommonPermutation { column: item.index },
+                    Rotation::cur(),
+                ),
+                Component::PermutationProductEval
+                | Component::PermutationProductNextEval
+                | Component::PermutationProductLastEval => (
+                    PolyRef::PermutationProduct {
+                        circuit,
+                        set: item.index,
+                    },
+                    match item.component {
+                        Component::PermutationProductEval => Rotation::cur(),
+                        Component::PermutationProductNextEval => Rotation::next(),
+                        _ => last_rotation,
+                    },
+                ),
+                Component::LookupProductEval => (
+                    PolyRef::LookupProduct {
+                        circuit,
+                        lookup: item.index,
+                    },
+                    Rotation::cur(),
+                ),
+                Component::LookupProductNextEval => (
+                    PolyRef::LookupProduct {
+                        circuit,
+                        lookup: item.index,
+                    },
+                    Rotation::next(),
+                ),
+                Component::LookupPermutedInputEval => (
+                    PolyRef::LookupPermutedInput {
+                        circuit,
+                        lookup: item.index,
+                    },
+                    Rotation::cur(),
+                ),
+                Component::LookupPermutedInputPrevEval => (
+                    PolyRef::LookupPermutedInput {
+                        circuit,
+                        lookup: item.index,
+                    },
+                    Rotation::prev(),
+                ),
+                Component::LookupPermutedTableEval => (
+                    PolyRef::LookupPermutedTable {
+                        circuit,
+                        lookup: item.index,
+                    },
+                    Rotation::cur(),
+                ),
+                component => panic!("{:?} is not an evaluation", component),
+            };
+            OpeningQuery { poly, rotation }
+        };
+        let layout = transcript_layout(pk.get_vk(), 2, MultiOpenScheme::SHPlonk);
+        let written = parse(&proof, &layout, PointEncoding::Compressed)
+            .unwrap()
+            .evaluations()
+            .iter()
+            .map(|(item, value)| (query(item), *value))
+            .collect::<Vec<_>>();
+
+        // Every query but the quotient is written once, and the artifacts
+        // list them in the order they are opened in.
+        let opened = queries
+            .iter()
+            .filter(|query| query.poly != PolyRef::Quotient)
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(written.len(), opened.len());
+        assert_eq!(artifacts.evaluations.len(), opened.len());
+        for ((query, value), opened) in artifacts.evaluations.iter().zip(&opened) {
+            assert_eq!(query, opened);
+            let matching = written
+                .iter()
+                .filter(|(written, _)| written == query)
+                .collect::<Vec<_>>();
+            assert_eq!(matching, vec![&(*query, *value)]);
+            assert_eq!(artifacts.evaluation(query), Some(*value));
+        }
+        assert_eq!(
+            artifacts.evaluation(&OpeningQuery {
+                poly: PolyRef::Quotient,
+                rotation: Rotation::cur(),
+            }),
+            None
+        );
+    }
+}
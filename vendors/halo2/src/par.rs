@@ -0,0 +1,262 @@
+//! The per-column loops of the Rust side, run on the rayon thread pool with
+//! the "rayon" feature and in a plain loop without it. Either way the results
+//! come back in the order of the input, so whatever is written to the
+//! transcript from them is written in the same order.
+
+/// Whether the loops of this module run in parallel.
+pub const PARALLEL: bool = cfg!(feature = "rayon");
+
+/// Maps `f` over `items`, keeping their order.
+#[cfg(feature = "rayon")]
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    use rayon::prelude::*;
+
+    items.par_iter().map(f).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    items.iter().map(f).collect()
+}
+
+/// Calls `f` on every item of `items` with its index.
+#[cfg(feature = "rayon")]
+pub fn for_each_mut<T: Send>(items: &mut [T], f: impl Fn(usize, &mut T) + Sync + Send) {
+    use rayon::prelude::*;
+
+    items
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(index, item)| f(index, item));
+}
+
+#[cfg(not(feature = "rayon"))]
+pub fn for_each_mut<T: Send>(items: &mut [T], f: impl Fn(usize, &mut T) + Sync + Send) {
+    for (index, item) in items.iter_mut().enumerate() {
+        f(index, item);
+    }
+}
+
+/// Lets a closure over a value that isn't `Sync` be shared between the
+/// threads of [`map()`]. It is only for calls into the backend that are known
+/// to be safe from several threads at once, see the `unsafe impl` below.
+///
+/// The only user is `commit_lagrange_columns()` of the provers, which shares
+/// `commit_lagrange()` over a prover. That is sound as long as all of this
+/// holds, and a change to `commit_lagrange()` has to keep it so:
+///
+/// - It doesn't touch the cells of the Rust prover, i.e, its metrics and its
+///   pools. `commit_via_monomial` is only read, and
+///   the columns are committed one at a time when it is set, since `ifft()`
+///   and `commit()` allocate from the backend and weren't audited.
+/// - `ffi_trace!` records into slots claimed with an atomic counter, see
+///   `src/ffi_trace.rs`, so calls on several threads interleave entries but
+///   don't race on one, unless more than `TRACE_CAPACITY` are in flight.
+/// - `CommitLagrange()` of the backend only reads the Lagrange bases and runs
+///   its MSM in a scratch it takes from and returns to the prover under
+///   `scratch_mutex_`, see `tachyon/c/zk/plonk/halo2/kzg_family_prover_impl.h`.
+///   Two calls never share a scratch.
+/// - Nothing that needs `&mut` of the prover, like `release_scratch()` or
+///   `set_lagrange_basis_policy()`, can run meanwhile, since `map()` holds
+///   `&self` until every call returns.
+pub(crate) struct AssertSync<F>(F);
+
+impl<F> AssertSync<F> {
+    /// # Safety
+    ///
+    /// `f` must be safe to call from several threads at once.
+    pub(crate) unsafe fn new(f: F) -> Self {
+        Self(f)
+    }
+
+    pub(crate) fn call<A, R>(&self, arg: A) -> R
+    where
+        F: Fn(A) -> R,
+    {
+        (self.0)(arg)
+    }
+}
+
+// SAFETY: `AssertSync::new()` requires the closure to be safe to call from
+// several threads at once, see the invariants above.
+unsafe impl<F> Sync for AssertSync<F> {}
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use halo2_proofs::{
+        plonk::{create_proof, keygen_pk2},
+        poly::kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::ProverSHPLONK,
+        },
+        transcript::{Blake2bWrite, Challenge255, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::{OsRng, SeedableRng};
+
+    use super::{for_each_mut, map, PARALLEL};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::wide_circuit::WideCircuit,
+        consts::{TranscriptType, SEED},
+        prover::create_proof as tachyon_create_proof,
+        xor_shift_rng::XORShiftRng,
+    };
+
+    #[test]
+    fn test_order() {
+        let items: Vec<u64> = (0..1000).collect();
+        assert_eq!(
+            map(&items, |item| item * 2),
+            (0..2000).step_by(2).collect::<Vec<_>>()
+        );
+
+        let mut items = vec![0; 1000];
+        for_each_mut(&mut items, |index, item| *item = index);
+        assert_eq!(items, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_commit_lagrange_columns() {
+        let k = 5;
+        let prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &Fr::from(2),
+        );
+        let columns: Vec<_> = (0..32)
+            .map(|_| {
+                let mut evals = prover.empty_evals();
+                for row in 0..evals.len() {
+                    evals.set_value(row, &<Fr as ff::Field>::random(OsRng));
+                }
+                evals
+            })
+            .collect();
+        let expected: Vec<_> = columns
+            .iter()
+            .map(|evals| prover.commit_lagrange(evals))
+            .collect();
+        let columns: Vec<_> = columns.iter().collect();
+        assert_eq!(prover.commit_lagrange_columns(&columns), expected);
+    }
+
+    // Commits to many columns on every thread of the pool at once, over and
+    // over, to shake out races between the calls `AssertSync` shares. Every
+    // commitment runs in a scratch of its own, so there are never more of
+    // them than threads.
+    #[cfg(feature = "rayon")]
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_commit_lagrange_columns_stress() {
+        let k = 8;
+        let prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &Fr::from(2),
+        );
+        let columns: Vec<_> = (0..256)
+            .map(|_| {
+                let mut evals = prover.empty_evals();
+                for row in 0..evals.len() {
+                    evals.set_value(row, &<Fr as ff::Field>::random(OsRng));
+                }
+                evals
+            })
+            .collect();
+        let expected: Vec<_> = columns
+            .iter()
+            .map(|evals| prover.commit_lagrange(evals))
+            .collect();
+        let columns: Vec<_> = columns.iter().collect();
+        for _ in 0..50 {
+            assert_eq!(prover.commit_lagrange_columns(&columns), expected);
+        }
+
+        let stats = prover.scratch_stats();
+        assert!(stats.num_allocations <= rayon::current_num_threads() as u64);
+        assert_eq!(
+            stats.num_allocations + stats.num_reuses,
+            51 * columns.len() as u64
+        );
+    }
+
+    fn prove_wide(k: u32, s: &Fr, pk_bytes: &[u8], circuit: &WideCircuit<Fr, 300>) -> Vec<u8> {
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, s);
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes).unwrap();
+        let mut transcript = TachyonBlake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let instances = circuit.instances();
+        tachyon_create_proof::<_, _, _, _, _>(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit.clone()],
+            &[&[&instances[..]]],
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+        proof
+    }
+
+    // The proof with the "rayon" feature is the same as without it, since
+    // both are the same as halo2's.
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_wide_proof() {
+        let k = 4;
+        let s = Fr::from(2);
+        let circuit = WideCircuit::<Fr, 300>::default();
+        let instances = circuit.instances();
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit.clone()],
+            &[&[&instances[..]]],
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let halo2_proof = transcript.finalize();
+
+        assert_eq!(prove_wide(k, &s, &pk_bytes, &circuit), halo2_proof);
+    }
+
+    // Times proofs of a circuit with 300 advice columns. Run it with and
+    // without the "rayon" feature to compare:
+    //   cargo test --release -- --ignored bench_wide_circuit --nocapture
+    //   cargo test --release --features rayon -- --ignored bench_wide_circuit --nocapture
+    #[test]
+    #[ignore]
+    fn bench_wide_circuit() {
+        let s = Fr::from(2);
+        let circuit = WideCircuit::<Fr, 300>::default();
+        println!("parallel: {}", PARALLEL);
+        for k in [10, 12, 14] {
+            let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+            let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+            let mut pk_bytes: Vec<u8> = vec![];
+            pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+                .unwrap();
+
+            let start = Instant::now();
+            prove_wide(k, &s, &pk_bytes, &circuit);
+            println!("k = {:>2}: {:?}", k, start.elapsed());
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+
+use halo2_proofs::plonk::sealed;
+
+/// A phase of the constraint system. Unlike `sealed::Phase`, this can be
+/// constructed outside of halo2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PhaseId(pub u8);
+
+impl From<sealed::Phase> for PhaseId {
+    fn from(phase: sealed::Phase) -> Self {
+        PhaseId(phase.0)
+    }
+}
+
+impl From<PhaseId> for sealed::Phase {
+    fn from(phase: PhaseId) -> Self {
+        sealed::Phase(phase.0)
+    }
+}
+
+/// The index of a challenge, as in `Challenge::index()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChallengeId(pub usize);
+
+/// A challenge that the phases of a key don't squeeze exactly once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChallengeError {
+    /// The challenge's phase came up again after it was squeezed.
+    SqueezedTwice(ChallengeId),
+    /// The challenge's phase isn't one of the key's phases.
+    NotSqueezed(ChallengeId),
+}
+
+impl fmt::Display for ChallengeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChallengeError::SqueezedTwice(challenge) => {
+                write!(f, "challenge {} was squeezed twice", challenge.0)
+            }
+            ChallengeError::NotSqueezed(challenge) => {
+                write!(f, "challenge {} was never squeezed", challenge.0)
+            }
+        }
+    }
+}
+
+/// The index of an advice column, as in `Column::<Advice>::index()`.
+pub type ColumnIndex = usize;
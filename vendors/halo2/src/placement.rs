@@ -0,0 +1,344 @@
+use crate::error::TachyonError;
+
+/// Where a stage of a proof runs. See [`StagePlacement`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placement {
+    Cpu,
+    /// Fails with [`TachyonError::BackendUnavailable`] without the "gpu"
+    /// feature.
+    Gpu,
+    /// The GPU from the size in [`PlacementThresholds`] on, if the crate is
+    /// built with the "gpu" feature, and the CPU otherwise.
+    Auto,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Placement::Auto
+    }
+}
+
+/// The stages of a proof whose placement can be chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StageCategory {
+    Fft,
+    Msm,
+    LookupProducts,
+    Quotient,
+}
+
+impl StageCategory {
+    pub const ALL: [StageCategory; 4] = [
+        StageCategory::Fft,
+        StageCategory::Msm,
+        StageCategory::LookupProducts,
+        StageCategory::Quotient,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StageCategory::Fft => "fft",
+            StageCategory::Msm => "msm",
+            StageCategory::LookupProducts => "lookup_products",
+            StageCategory::Quotient => "quotient",
+        }
+    }
+
+    /// The bit of the stage in the mask the backend takes, one of
+    /// `TACHYON_HALO2_STAGE_*`.
+    pub fn bit(&self) -> u8 {
+        match self {
+            StageCategory::Fft => 1 << 0,
+            StageCategory::Msm => 1 << 1,
+            StageCategory::LookupProducts => 1 << 2,
+            StageCategory::Quotient => 1 << 3,
+        }
+    }
+}
+
+/// The smallest `k` at which [`Placement::Auto`] puts each stage on the GPU.
+/// Below it, launching the kernels costs more than they save.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlacementThresholds {
+    pub fft: u32,
+    pub msm: u32,
+    pub lookup_products: u32,
+    pub quotient: u32,
+}
+
+impl Default for PlacementThresholds {
+    /// FFTs stay on the CPU up to `k` = 14, while MSMs win on the GPU well
+    /// before. The lookup products and the quotient are made of FFTs.
+    fn default() -> Self {
+        Self {
+            fft: 15,
+            msm: 10,
+            lookup_products: 15,
+            quotient: 15,
+        }
+    }
+}
+
+/// Where every stage of a proof runs, see
+/// [`crate::prover::ProofOptions::stage_placement`]. Every stage computes the
+/// same values on either, so the proof doesn't depend on it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StagePlacement {
+    pub fft: Placement,
+    pub msm: Placement,
+    pub lookup_products: Placement,
+    pub quotient: Placement,
+    pub thresholds: PlacementThresholds,
+}
+
+impl StagePlacement {
+    /// Every stage on the CPU.
+    pub fn cpu() -> Self {
+        Self {
+            fft: Placement::Cpu,
+            msm: Placement::Cpu,
+            lookup_products: Placement::Cpu,
+            quotient: Placement::Cpu,
+            thresholds: PlacementThresholds::default(),
+        }
+    }
+
+    pub fn placement(&self, stage: StageCategory) -> Placement {
+        match stage {
+            StageCategory::Fft => self.fft,
+            StageCategory::Msm => self.msm,
+            StageCategory::LookupProducts => self.lookup_products,
+            StageCategory::Quotient => self.quotient,
+        }
+    }
+
+    pub fn threshold(&self, stage: StageCategory) -> u32 {
+        match stage {
+            StageCategory::Fft => self.thresholds.fft,
+            StageCategory::Msm => self.thresholds.msm,
+            StageCategory::LookupProducts => self.thresholds.lookup_products,
+            StageCategory::Quotient => self.thresholds.quotient,
+        }
+    }
+
+    /// Whether `stage` of a proof with `2ᵏ` rows runs on the GPU.
+    pub fn on_gpu(&self, stage: StageCategory, k: u32) -> Result<bool, TachyonError> {
+        match self.placement(stage) {
+            Placement::Cpu => Ok(false),
+            Placement::Gpu if cfg!(feature = "gpu") => Ok(true),
+            Placement::Gpu => Err(TachyonError::BackendUnavailable {
+                reason: format!(
+                    "the {} stage is placed on the GPU, but the crate is built without the \"gpu\" feature",
+                    stage.name()
+                ),
+            }),
+            Placement::Auto => Ok(cfg!(feature = "gpu") && k >= self.threshold(stage)),
+        }
+    }
+
+    /// The mask of the stages of a proof with `2ᵏ` rows that run on the GPU,
+    /// as the backend takes it.
+    pub fn gpu_stages(&self, k: u32) -> Result<u8, TachyonError> {
+        let mut mask = 0;
+        for stage in StageCategory::ALL {
+            if self.on_gpu(stage, k)? {
+                mask |= stage.bit();
+            }
+        }
+        Ok(mask)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{mem, time::Instant};
+
+    use halo2_proofs::{
+        arithmetic::best_multiexp,
+        circuit::Value,
+        plonk::{keygen_pk2, verify_proof},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::VerifierSHPLONK,
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer},
+    };
+    use halo2curves::{
+        bn256::{Bn256, Fr, G1Affine, G1},
+        group::{ff::Field, Curve, Group},
+    };
+    use rand_core::OsRng;
+
+    use super::{Placement, PlacementThresholds, StageCategory, StagePlacement};
+    use crate::{
+        bn254::{
+            ffi, Blake2bWrite as TachyonBlake2bWrite, Fr as CppFr, G1Point2 as CppG1Point2,
+            ProvingKey as TachyonProvingKey, SHPlonkProver, TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::TranscriptType,
+        error::TachyonError,
+        prover::{create_proof_with_options, ProofOptions},
+        rng::proof_context,
+    };
+
+    #[test]
+    fn test_gpu_stages() {
+        assert_eq!(StagePlacement::cpu().gpu_stages(20).unwrap(), 0);
+
+        let auto = StagePlacement::default();
+        assert_eq!(auto.gpu_stages(9).unwrap(), 0);
+        let msm = StageCategory::Msm.bit();
+        if cfg!(feature = "gpu") {
+            assert_eq!(auto.gpu_stages(14).unwrap(), msm);
+            assert_eq!(auto.gpu_stages(15).unwrap(), 0b1111);
+        } else {
+            assert_eq!(auto.gpu_stages(20).unwrap(), 0);
+        }
+
+        let gpu_msm = StagePlacement {
+            msm: Placement::Gpu,
+            ..StagePlacement::cpu()
+        };
+        if cfg!(feature = "gpu") {
+            assert_eq!(gpu_msm.gpu_stages(4).unwrap(), msm);
+        } else {
+            assert!(matches!(
+                gpu_msm.gpu_stages(4),
+                Err(TachyonError::BackendUnavailable { .. })
+            ));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_placement_invariance() {
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = vec![&public_inputs[..]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let prove = |stage_placement| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let options = ProofOptions {
+                rng_context: proof_context(b"simple", 0),
+                stage_placement,
+                ..Default::default()
+            };
+            let mut transcript = TachyonBlake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&instances[..]],
+                &options,
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+
+        // Every stage is on the GPU with the "gpu" feature, since the
+        // thresholds are 0, and on the CPU without it.
+        let eager = StagePlacement {
+            thresholds: PlacementThresholds {
+                fft: 0,
+                msm: 0,
+                lookup_products: 0,
+                quotient: 0,
+            },
+            ..Default::default()
+        };
+        let hybrid = StagePlacement {
+            fft: Placement::Cpu,
+            lookup_products: Placement::Cpu,
+            ..eager
+        };
+        let proof = prove(StagePlacement::cpu());
+        assert_eq!(prove(eager), proof);
+        assert_eq!(prove(hybrid), proof);
+
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&instances[..]],
+            &mut transcript,
+        )
+        .is_ok());
+    }
+
+    // Times an MSM of 2ᵏ points on the CPU and, with the "gpu" feature, on
+    // the GPU, to pick `PlacementThresholds::msm`. Run it with
+    //   cargo test --release --features gpu -- --ignored bench_msm_crossover --nocapture
+    #[test]
+    #[ignore]
+    fn bench_msm_crossover() {
+        println!("{:>2} {:>12} {:>12}", "k", "cpu", "gpu");
+        for k in 8..=16u8 {
+            let n = 1usize << k;
+            let mut base = G1::random(OsRng);
+            let bases: Vec<G1Affine> = (0..n)
+                .map(|_| {
+                    let ret = base.to_affine();
+                    base = base.double();
+                    ret
+                })
+                .collect();
+            let scalars: Vec<Fr> = (0..n).map(|_| Fr::random(OsRng)).collect();
+            let expected = best_multiexp(&scalars, &bases);
+            let bases: Vec<CppG1Point2> = unsafe { mem::transmute(bases) };
+            let scalars: Vec<CppFr> = unsafe { mem::transmute(scalars) };
+
+            let mut msm = ffi::create_g1_msm(k);
+            let start = Instant::now();
+            let actual = unsafe { ffi::g1_point2_msm(&mut *msm, &bases, &scalars) };
+            let cpu = start.elapsed();
+            ffi::destroy_g1_msm(msm);
+            let actual: Box<G1> = unsafe { mem::transmute(actual) };
+            assert_eq!(*actual, expected);
+
+            #[cfg(feature = "gpu")]
+            let gpu = {
+                let mut msm = ffi::create_g1_msm_gpu(k, 0);
+                let start = Instant::now();
+                let actual = unsafe { ffi::g1_point2_msm_gpu(&mut *msm, &bases, &scalars) };
+                let gpu = start.elapsed();
+                ffi::destroy_g1_msm_gpu(msm);
+                let actual: Box<G1> = unsafe { mem::transmute(actual) };
+                assert_eq!(*actual, expected);
+                format!("{:?}", gpu)
+            };
+            #[cfg(not(feature = "gpu"))]
+            let gpu = "-";
+            println!("{:>2} {:>12} {:>12}", k, format!("{:?}", cpu), gpu);
+        }
+    }
+}
@@ -0,0 +1,285 @@
+// The prover pools the buffers of its domain size, so that a service proving
+// many times with one prover doesn't allocate them from the backend for every
+// proof. A buffer only goes back to the pool when it is recycled or a
+// `Pooled` holding it is dropped. The proof paths do that for the rational
+// evals of every column they evaluate; the evals they hand to the backend are
+// owned by it from then on. Any other buffer, e.g, one a caller gets from
+// `empty_evals()`, is only reused if the caller passes it to
+// `TachyonProver::recycle_evals()` or `TachyonProver::recycle_rational_evals()`.
+//
+// `empty_evals()` returns a plain buffer rather than a `Pooled` one because
+// the backend takes ownership of the evals a proof hands it, and a `Pooled`
+// borrows its prover for as long as it lives, which the proof paths can't.
+// `pooled_evals()` and `pooled_rational_evals()` return the handle instead.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// The number of buffers a [`Pool`] keeps by default.
+pub const DEFAULT_POOL_CAPACITY: usize = 256;
+
+/// A buffer that a [`Pool`] can hand out again.
+pub trait Poolable {
+    fn len(&self) -> usize;
+
+    /// Sets every element back to zero while keeping the length, so that no
+    /// value from a previous proof leaks into the next one.
+    fn reset(&mut self);
+}
+
+/// A freelist of backend buffers keyed by their length.
+///
+/// Taking a buffer out of the pool always resets it first. Buffers returned
+/// while the pool already holds `capacity` entries are dropped instead.
+pub struct Pool<T: Poolable> {
+    free: RefCell<HashMap<usize, Vec<T>>>,
+    num_pooled: Cell<usize>,
+    capacity: Cell<usize>,
+}
+
+impl<T: Poolable> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("len", &self.len())
+            .field("num_elements", &self.num_elements())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T: Poolable> Pool<T> {
+    pub fn new(capacity: usize) -> Pool<T> {
+        Pool {
+            free: RefCell::new(HashMap::new()),
+            num_pooled: Cell::new(0),
+            capacity: Cell::new(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    /// Changes the capacity, dropping pooled buffers that no longer fit.
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.set(capacity);
+        let mut free = self.free.borrow_mut();
+        for buffers in free.values_mut() {
+            while self.num_pooled.get() > capacity && buffers.pop().is_some() {
+                self.num_pooled.set(self.num_pooled.get() - 1);
+            }
+        }
+        free.retain(|_, buffers| !buffers.is_empty());
+    }
+
+    /// Returns the number of buffers currently held by the pool.
+    pub fn len(&self) -> usize {
+        self.num_pooled.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the total length of the buffers currently held by the pool.
+    pub fn num_elements(&self) -> usize {
+        self.free
+            .borrow()
+            .iter()
+            .map(|(len, buffers)| len * buffers.len())
+            .sum()
+    }
+
+    /// Takes a zeroed buffer of length `len` out of the pool, if any.
+    pub fn take(&self, len: usize) -> Option<T> {
+        let mut free = self.free.borrow_mut();
+        let mut value = free.get_mut(&len).and_then(|buffers| buffers.pop())?;
+        self.num_pooled.set(self.num_pooled.get() - 1);
+        value.reset();
+        Some(value)
+    }
+
+    /// Returns `value` to the pool, or drops it if the pool is full.
+    pub fn put(&self, value: T) {
+        if self.num_pooled.get() >= self.capacity.get() {
+            return;
+        }
+        self.free
+            .borrow_mut()
+            .entry(value.len())
+            .or_default()
+            .push(value);
+        self.num_pooled.set(self.num_pooled.get() + 1);
+    }
+
+    /// Drops every pooled buffer.
+    pub fn trim(&self) {
+        self.free.borrow_mut().clear();
+        self.num_pooled.set(0);
+    }
+}
+
+impl<T: Poolable> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_POOL_CAPACITY)
+    }
+}
+
+/// A buffer that goes back to its [`Pool`] when dropped.
+pub struct Pooled<'a, T: Poolable> {
+    pool: &'a Pool<T>,
+    value: Option<T>,
+}
+
+impl<'a, T: Poolable + fmt::Debug> fmt::Debug for Pooled<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Pooled").field(&**self).finish()
+    }
+}
+
+impl<'a, T: Poolable> Pooled<'a, T> {
+    pub fn new(pool: &'a Pool<T>, value: T) -> Pooled<'a, T> {
+        Pooled {
+            pool,
+            value: Some(value),
+        }
+    }
+
+    /// Detaches the buffer from the pool so that it is not returned on drop.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+}
+
+impl<'a, T: Poolable> Deref for Pooled<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<'a, T: Poolable> DerefMut for Pooled<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+impl<'a, T: Poolable> Drop for Pooled<'a, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.pool.put(value);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "halo2"))]
+mod test {
+    use std::time::Instant;
+
+    use super::{Pool, Poolable};
+    use crate::{
+        bn254::{SHPlonkProver, TachyonProver},
+        consts::TranscriptType,
+    };
+    use ff::Field;
+    use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    impl Poolable for Vec<u64> {
+        fn len(&self) -> usize {
+            Vec::len(self)
+        }
+
+        fn reset(&mut self) {
+            self.iter_mut().for_each(|value| *value = 0);
+        }
+    }
+
+    #[test]
+    fn test_pool() {
+        let pool = Pool::<Vec<u64>>::new(2);
+        assert!(pool.take(4).is_none());
+
+        pool.put(vec![1; 4]);
+        pool.put(vec![2; 8]);
+        pool.put(vec![3; 4]);
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.num_elements(), 12);
+
+        assert!(pool.take(2).is_none());
+        assert_eq!(pool.take(4), Some(vec![0; 4]));
+        assert!(pool.take(4).is_none());
+
+        pool.set_capacity(0);
+        assert!(pool.is_empty());
+        pool.put(vec![4; 8]);
+        assert!(pool.is_empty());
+
+        pool.set_capacity(2);
+        pool.put(vec![5; 8]);
+        pool.trim();
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_prover_pool() {
+        let k = 4;
+        let s = Fr::from(2);
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+
+        let sentinel = Fr::random(rand_core::OsRng);
+        {
+            let mut evals = prover.pooled_evals();
+            for i in 0..evals.len() {
+                evals.set_value(i, &sentinel);
+            }
+        }
+        assert_eq!(prover.evals_pool().len(), 1);
+
+        // A reused buffer must not leak values from its previous owner.
+        let evals = prover.empty_evals();
+        assert!(prover.evals_pool().is_empty());
+        for i in 0..evals.len() {
+            assert_eq!(evals.get_value(i), Fr::zero());
+        }
+
+        prover.recycle_evals(evals);
+        prover.recycle_rational_evals(prover.empty_rational_evals());
+        prover.trim_pool();
+        assert!(prover.evals_pool().is_empty());
+        assert!(prover.rational_evals_pool().is_empty());
+    }
+
+    // Times taking buffers of a domain of 2^16 from the backend and from the
+    // pool:
+    //   cargo test --release -- --ignored bench_pool --nocapture
+    #[test]
+    #[ignore]
+    fn bench_pool() {
+        let k = 16;
+        let s = Fr::from(2);
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        for _ in 0..3 {
+            let start = Instant::now();
+            for _ in 0..100 {
+                drop(prover.empty_evals());
+            }
+            println!("allocated: {:?}", start.elapsed());
+
+            let start = Instant::now();
+            for _ in 0..100 {
+                drop(prover.pooled_evals());
+            }
+            println!("pooled:    {:?}", start.elapsed());
+            prover.trim_pool();
+        }
+    }
+}
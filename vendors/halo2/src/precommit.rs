@@ -0,0 +1,262 @@
+use std::{collections::HashSet, fmt};
+
+use halo2_proofs::poly::commitment::CommitmentScheme;
+use halo2curves::{
+    bn256::{Fr, G1Affine},
+    group::Curve,
+};
+
+use crate::{
+    bn254::{Evals, ProvingKey as TachyonProvingKey, TachyonProver},
+    column::AdviceColumnIndex,
+    error::TachyonError,
+    prover::ProofOptions,
+};
+
+/// An advice column committed to before the proof, e.g, by another protocol
+/// that the proof is linked to. See [`ProofOptions::precommitted_advice`].
+#[derive(Clone)]
+pub struct PrecommittedColumn {
+    pub column_index: AdviceColumnIndex,
+    /// Written to the transcript in place of the column's commitment.
+    pub commitment: G1Affine,
+    /// Used when the column is opened. KZG commitments aren't hiding, so the
+    /// commitment doesn't depend on it.
+    pub blind: Fr,
+    /// The whole column, including the rows past the usable ones, which are
+    /// committed to as they are instead of being blinded by the prover.
+    pub evals: Evals,
+}
+
+impl fmt::Debug for PrecommittedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrecommittedColumn")
+            .field("column_index", &self.column_index)
+            .field("commitment", &self.commitment)
+            .field("blind", &self.blind)
+            .field("evals", &format_args!("{} rows", self.evals.len()))
+            .finish()
+    }
+}
+
+/// Fails if `options.precommitted_advice` can't be used for a proof of
+/// `num_circuits` circuits with `pk`, and, if
+/// `options.check_precommitted_advice` is set, if a commitment isn't the one
+/// to its evals.
+pub(crate) fn check_precommitted_advice<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    prover: &P,
+    pk: &TachyonProvingKey<Scheme::Curve>,
+    num_circuits: usize,
+    options: &ProofOptions,
+) -> Result<(), TachyonError> {
+    let mut seen = HashSet::new();
+    for column in &options.precommitted_advice {
+        let invalid = |reason| {
+            Err(TachyonError::InvalidPrecommitment {
+                column: column.column_index.index(),
+                reason,
+            })
+        };
+        // The commitments aren't given per circuit.
+        if num_circuits != 1 {
+            return invalid("only a proof of a single circuit can have precommitted advice");
+        }
+        // The index may be of a column of another circuit.
+        if column.column_index.index() >= pk.num_advice_columns() {
+            return invalid("the circuit has no such advice column");
+        }
+        if !seen.insert(column.column_index) {
+            return invalid("the column is precommitted twice");
+        }
+        if column.evals.len() != prover.n() as usize {
+            return invalid("the number of rows differs from the prover's");
+        }
+        if options.check_precommitted_advice {
+            let commitment = prover.commit_lagrange(&column.evals).to_affine();
+            if unsafe { *std::mem::transmute::<_, &G1Affine>(&commitment) } != column.commitment {
+                return Err(TachyonError::PrecommitmentMismatch {
+                    column: column.column_index.index(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{
+            keygen_pk2, verify_proof, Advice, Circuit, Column, ConstraintSystem, Error, Instance,
+            Selector,
+        },
+        poly::{
+            commitment::{Blind, Params, ParamsProver},
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::VerifierSHPLONK,
+                strategy::SingleStrategy,
+            },
+            EvaluationDomain, Rotation,
+        },
+        transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer},
+    };
+    use halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        group::{prime::PrimeCurveAffine, Curve},
+    };
+    use rand_core::OsRng;
+
+    use super::PrecommittedColumn;
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        consts::TranscriptType,
+        error::TachyonError,
+        prover::{create_proof_with_options, ProofOptions},
+        rng::proof_context,
+        transcript::{RecordingTranscript, TranscriptEntry},
+    };
+
+    /// Constrains the first row of `a` to the instance, and leaves the other
+    /// rows free for whoever commits to it.
+    #[derive(Clone, Default)]
+    struct OpeningCircuit {
+        value: Fr,
+    }
+
+    impl Circuit<Fr> for OpeningCircuit {
+        type Config = (Column<Advice>, Column<Instance>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let instance = meta.instance_column();
+            let q = meta.selector();
+            meta.create_gate("opening", |meta| {
+                let q = meta.query_selector(q);
+                let a = meta.query_advice(a, Rotation::cur());
+                let instance = meta.query_instance(instance, Rotation::cur());
+                vec![q * (a - instance)]
+            });
+            (a, instance, q)
+        }
+
+        fn synthesize(
+            &self,
+            (a, _, q): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "opening",
+                |mut region| {
+                    q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", a, 0, || Value::known(self.value))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_precommitted_advice() {
+        let k = 4;
+        let n = 1 << k;
+        let s = Fr::from(2);
+        let value = Fr::from(9);
+        let circuit = OpeningCircuit { value };
+        let public_inputs = vec![value];
+        let instances = vec![&public_inputs[..]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        // The column is committed to with upstream halo2, as by another
+        // protocol.
+        let mut values = vec![value];
+        values.extend((1..n).map(|_| Fr::random(OsRng)));
+        let domain = EvaluationDomain::<Fr>::new(1, k);
+        let blind = Fr::random(OsRng);
+        let commitment = params
+            .commit_lagrange(&domain.lagrange_from_vec(values.clone()), Blind(blind))
+            .to_affine();
+
+        let prove = |commitment: G1Affine| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            let mut evals = prover.empty_evals();
+            for (row, value) in values.iter().enumerate() {
+                evals.set_value(row, value);
+            }
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let options = ProofOptions {
+                rng_context: proof_context(b"precommitted", 0),
+                precommitted_advice: vec![PrecommittedColumn {
+                    column_index: tachyon_pk.advice_index(0).unwrap(),
+                    commitment,
+                    blind,
+                    evals,
+                }],
+                check_precommitted_advice: true,
+                ..Default::default()
+            };
+            let mut transcript = RecordingTranscript::new(TachyonBlake2bWrite::init(vec![]));
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&instances[..]],
+                &options,
+                &mut transcript,
+            )?;
+            let (transcript, log) = transcript.into_parts();
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            Ok::<_, TachyonError>((proof, log))
+        };
+
+        let (proof, log) = prove(commitment).expect("proof generation should not fail");
+        let written = log
+            .iter()
+            .find_map(|entry| match entry {
+                TranscriptEntry::WritePoint(point) => Some(*point),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(written, commitment);
+
+        let verifier_params = params.verifier_params();
+        let strategy = SingleStrategy::new(&params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof.as_slice());
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            verifier_params,
+            pk.get_vk(),
+            strategy,
+            &[&instances[..]],
+            &mut transcript,
+        )
+        .is_ok());
+
+        let wrong = (commitment + G1Affine::generator()).to_affine();
+        assert!(matches!(
+            prove(wrong),
+            Err(TachyonError::PrecommitmentMismatch { column: 0 })
+        ));
+    }
+}
@@ -0,0 +1,462 @@
+use halo2_proofs::{plonk::VerifyingKey, poly::commitment::CommitmentScheme};
+use halo2curves::bn256::G1Affine;
+
+use crate::{
+    bn254::AnyProver,
+    consts::{MultiOpenScheme, TranscriptType, VkHashMode},
+    proof::PointEncoding,
+    proving_key::{multi_open_scheme_to_metadata, Metadata},
+    vk_hash::vk_hash_mode_to_metadata,
+};
+
+/// A consistent set of prover settings for a common verifier. The transcript
+/// and the multi-open scheme have to match the verifier, or the proof won't
+/// verify.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProverPreset {
+    /// For the SHA-256 EVM verifier of halo2-snark-aggregator, with
+    /// uncompressed points. There is no Keccak preset, since libtachyon has no
+    /// Keccak transcript.
+    EvmSha256Shplonk,
+    /// For a verifier inside another circuit, where Poseidon is cheap.
+    RecursionPoseidonShplonk,
+    /// For local testing against halo2's native verifier.
+    LocalBlake2b,
+}
+
+impl ProverPreset {
+    pub fn transcript_type(&self) -> TranscriptType {
+        match self {
+            ProverPreset::EvmSha256Shplonk => TranscriptType::Sha256,
+            ProverPreset::RecursionPoseidonShplonk => TranscriptType::Poseidon,
+            ProverPreset::LocalBlake2b => TranscriptType::Blake2b,
+        }
+    }
+
+    pub fn multi_open_scheme(&self) -> MultiOpenScheme {
+        MultiOpenScheme::SHPlonk
+    }
+
+    /// All the presets' verifiers hash the verifying key like halo2.
+    pub fn vk_hash_mode(&self) -> VkHashMode {
+        VkHashMode::Blake2bField
+    }
+}
+
+/// A setting of [`ProverBuilder`] that was set explicitly to something other
+/// than what its preset implies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Override {
+    pub setting: &'static str,
+    pub preset: String,
+    pub value: String,
+}
+
+/// Builds a prover from a [`ProverPreset`], with any setting overridden
+/// explicitly. An override wins over the preset, but is logged as a warning,
+/// since the proof may no longer verify with the preset's verifier.
+#[derive(Clone, Debug)]
+pub struct ProverBuilder {
+    preset: ProverPreset,
+    transcript_type: TranscriptType,
+    multi_open_scheme: MultiOpenScheme,
+    vk_hash_mode: VkHashMode,
+    overrides: Vec<Override>,
+}
+
+impl ProverBuilder {
+    pub fn new(preset: ProverPreset) -> Self {
+        ProverBuilder {
+            preset,
+            transcript_type: preset.transcript_type(),
+            multi_open_scheme: preset.multi_open_scheme(),
+            vk_hash_mode: preset.vk_hash_mode(),
+            overrides: vec![],
+        }
+    }
+
+    pub fn transcript_type(mut self, transcript_type: TranscriptType) -> Self {
+        self.record_override(
+            "transcript",
+            format!("{:?}", self.preset.transcript_type()),
+            format!("{:?}", transcript_type),
+        );
+        self.transcript_type = transcript_type;
+        self
+    }
+
+    pub fn multi_open_scheme(mut self, multi_open_scheme: MultiOpenScheme) -> Self {
+        self.record_override(
+            "multi-open scheme",
+            format!("{:?}", self.preset.multi_open_scheme()),
+            format!("{:?}", multi_open_scheme),
+        );
+        self.multi_open_scheme = multi_open_scheme;
+        self
+    }
+
+    /// Selects how the verifying key is hashed into the transcript. The
+    /// prover learns it from the key, so this only takes effect through
+    /// [`ProverBuilder::key_metadata()`].
+    pub fn vk_hash_mode(mut self, vk_hash_mode: VkHashMode) -> Self {
+        self.record_override(
+            "vk hash",
+            format!("{:?}", self.preset.vk_hash_mode()),
+            format!("{:?}", vk_hash_mode),
+        );
+        self.vk_hash_mode = vk_hash_mode;
+        self
+    }
+
+    fn record_override(&mut self, setting: &'static str, preset: String, value: String) {
+        self.overrides.retain(|o| o.setting != setting);
+        if preset == value {
+            return;
+        }
+        log::warn!(
+            "{} {} overrides {} of preset {:?}",
+            setting,
+            value,
+            preset,
+            self.preset
+        );
+        self.overrides.push(Override {
+            setting,
+            preset,
+            value,
+        });
+    }
+
+    pub fn preset(&self) -> ProverPreset {
+        self.preset
+    }
+
+    pub fn overrides(&self) -> &[Override] {
+        &self.overrides
+    }
+
+    pub fn get_transcript_type(&self) -> TranscriptType {
+        self.transcript_type
+    }
+
+    pub fn get_multi_open_scheme(&self) -> MultiOpenScheme {
+        self.multi_open_scheme
+    }
+
+    pub fn get_vk_hash_mode(&self) -> VkHashMode {
+        self.vk_hash_mode
+    }
+
+    /// How the points of the proof are encoded, which follows from the
+    /// transcript.
+    pub fn point_encoding(&self) -> PointEncoding {
+        PointEncoding::for_transcript(&self.transcript_type)
+    }
+
+    /// Whether instance columns are committed to and opened, which follows
+    /// from the multi-open scheme. See `TachyonProver::query_instance()`.
+    pub fn query_instance(&self) -> bool {
+        self.multi_open_scheme == MultiOpenScheme::Gwc
+    }
+
+    /// Lists the settings for logging, e.g,
+    /// `preset: LocalBlake2b, transcript: Blake2b, multi-open scheme: SHPlonk,
+    /// vk hash: Blake2bField, instances: absorbed, points: Compressed`. An overridden setting is
+    /// followed by the value of the preset.
+    pub fn describe(&self) -> String {
+        let setting =
+            |name: &str, value: String| match self.overrides.iter().find(|o| o.setting == name) {
+                Some(o) => format!("{}: {} (preset: {})", name, value, o.preset),
+                None => format!("{}: {}", name, value),
+            };
+        [
+            format!("preset: {:?}", self.preset),
+            setting("transcript", format!("{:?}", self.transcript_type)),
+            setting("multi-open scheme", format!("{:?}", self.multi_open_scheme)),
+            setting("vk hash", format!("{:?}", self.vk_hash_mode)),
+            format!(
+                "instances: {}",
+                if self.query_instance() {
+                    "committed"
+                } else {
+                    "absorbed"
+                }
+            ),
+            format!("points: {:?}", self.point_encoding()),
+        ]
+        .join(", ")
+    }
+
+    /// The metadata to write in front of a proving key for `vk` with
+    /// [`crate::proving_key::write_with_metadata()`], so that provers refuse
+    /// it for another multi-open scheme and hash it with the builder's mode.
+    pub fn key_metadata(&self, vk: &VerifyingKey<G1Affine>) -> Metadata {
+        let mut metadata = Metadata::new();
+        multi_open_scheme_to_metadata(self.multi_open_scheme, &mut metadata);
+        vk_hash_mode_to_metadata(self.vk_hash_mode, vk, &mut metadata);
+        metadata
+    }
+
+    /// Builds the prover with an unsafe SRS from `s`, like
+    /// [`AnyProver::new()`].
+    pub fn build<Scheme: CommitmentScheme>(
+        &self,
+        k: u32,
+        s: &halo2curves::bn256::Fr,
+    ) -> AnyProver<Scheme> {
+        AnyProver::new(self.multi_open_scheme, self.transcript_type as u8, k, s)
+    }
+
+    /// Builds the prover with the SRS of `params`, like
+    /// [`AnyProver::from_params()`].
+    pub fn build_from_params<Scheme: CommitmentScheme>(
+        &self,
+        k: u32,
+        params: &[u8],
+    ) -> AnyProver<Scheme> {
+        AnyProver::from_params(
+            self.multi_open_scheme,
+            self.transcript_type as u8,
+            k,
+            params,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, keygen_vk, verify_proof, Error, ProvingKey},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierGWC, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Challenge255, PoseidonRead, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::SeedableRng;
+
+    use super::{Override, ProverBuilder, ProverPreset};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, PoseidonWrite as TachyonPoseidonWrite,
+            ProvingKey as TachyonProvingKey, Sha256Write as TachyonSha256Write, TachyonProver,
+            TranscriptWriteState,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{MultiOpenScheme, TranscriptType, VkHashMode, SEED},
+        proof::PointEncoding,
+        prover::create_proof,
+        proving_key::multi_open_scheme_from_metadata,
+        sha::ShaWrite,
+        vk_hash::vk_hash_mode_from_metadata,
+        xor_shift_rng::XORShiftRng,
+    };
+
+    const K: u32 = 4;
+
+    fn circuit() -> (SimpleCircuit<Fr>, Vec<Fr>) {
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        (circuit, vec![constant * a.square() * b.square()])
+    }
+
+    fn prove<T: TranscriptWriteState<G1Affine, Challenge255<G1Affine>>>(
+        builder: &ProverBuilder,
+        pk_bytes: &[u8],
+        mut transcript: T,
+        finalize: impl FnOnce(T) -> Vec<u8>,
+    ) -> Vec<u8> {
+        let (circuit, public_inputs) = circuit();
+        let mut prover = builder.build::<KZGCommitmentScheme<Bn256>>(K, &Fr::from(2));
+        assert_eq!(prover.query_instance(), builder.query_instance());
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes).unwrap();
+        create_proof(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &[&[&public_inputs[..]]],
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let mut proof = finalize(transcript);
+        proof.extend_from_slice(&prover.get_proof());
+        proof
+    }
+
+    fn verify<'a, T: TranscriptReadBuffer<&'a [u8], G1Affine, Challenge255<G1Affine>>>(
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        scheme: MultiOpenScheme,
+        proof: &'a [u8],
+    ) -> Result<(), Error> {
+        let (_, public_inputs) = circuit();
+        let mut transcript = T::init(proof);
+        let strategy = SingleStrategy::new(params);
+        match scheme {
+            MultiOpenScheme::Gwc => {
+                verify_proof::<KZGCommitmentScheme<Bn256>, VerifierGWC<_>, _, _, _>(
+                    params.verifier_params(),
+                    pk.get_vk(),
+                    strategy,
+                    &[&[&public_inputs[..]]],
+                    &mut transcript,
+                )
+            }
+            MultiOpenScheme::SHPlonk => {
+                verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+                    params.verifier_params(),
+                    pk.get_vk(),
+                    strategy,
+                    &[&[&public_inputs[..]]],
+                    &mut transcript,
+                )
+            }
+        }
+        .map(|_| ())
+    }
+
+    #[test]
+    fn test_describe() {
+        let builder = ProverBuilder::new(ProverPreset::EvmSha256Shplonk);
+        assert_eq!(builder.get_transcript_type(), TranscriptType::Sha256);
+        assert_eq!(builder.point_encoding(), PointEncoding::Uncompressed);
+        assert!(!builder.query_instance());
+        assert_eq!(
+            builder.describe(),
+            "preset: EvmSha256Shplonk, transcript: Sha256, multi-open scheme: SHPlonk, \
+             vk hash: Blake2bField, instances: absorbed, points: Uncompressed"
+        );
+
+        // Setting what the preset implies isn't an override.
+        let builder = ProverBuilder::new(ProverPreset::LocalBlake2b)
+            .transcript_type(TranscriptType::Blake2b)
+            .multi_open_scheme(MultiOpenScheme::Gwc);
+        assert_eq!(
+            builder.overrides(),
+            &[Override {
+                setting: "multi-open scheme",
+                preset: "SHPlonk".to_string(),
+                value: "Gwc".to_string(),
+            }]
+        );
+        assert!(builder.query_instance());
+        assert_eq!(
+            builder.describe(),
+            "preset: LocalBlake2b, transcript: Blake2b, multi-open scheme: Gwc (preset: \
+             SHPlonk), vk hash: Blake2bField, instances: committed, points: Compressed"
+        );
+
+        // Overriding back to the preset's value drops the override.
+        let builder = builder.multi_open_scheme(MultiOpenScheme::SHPlonk);
+        assert!(builder.overrides().is_empty());
+
+        // The vk hash mode reaches provers through the metadata of the key.
+        let builder = builder.vk_hash_mode(VkHashMode::Keccak256Truncated);
+        assert_eq!(builder.overrides()[0].setting, "vk hash");
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(K, Fr::from(2));
+        let vk = keygen_vk(&params, &circuit().0).expect("vk should not fail");
+        let metadata = builder.key_metadata(&vk);
+        assert_eq!(
+            vk_hash_mode_from_metadata(&metadata),
+            VkHashMode::Keccak256Truncated
+        );
+        assert_eq!(
+            multi_open_scheme_from_metadata(&metadata),
+            Some(MultiOpenScheme::SHPlonk)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_presets() {
+        let (circuit, public_inputs) = circuit();
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(K, Fr::from(2));
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let builder = ProverBuilder::new(ProverPreset::LocalBlake2b);
+        let proof = prove(
+            &builder,
+            &pk_bytes,
+            TachyonBlake2bWrite::init(vec![]),
+            TachyonBlake2bWrite::finalize,
+        );
+        assert!(
+            verify::<Blake2bRead<_, _, _>>(&params, &pk, MultiOpenScheme::SHPlonk, &proof).is_ok()
+        );
+
+        let builder = ProverBuilder::new(ProverPreset::RecursionPoseidonShplonk);
+        let proof = prove(
+            &builder,
+            &pk_bytes,
+            TachyonPoseidonWrite::init(vec![]),
+            TachyonPoseidonWrite::finalize,
+        );
+        assert!(
+            verify::<PoseidonRead<_, _, _>>(&params, &pk, MultiOpenScheme::SHPlonk, &proof).is_ok()
+        );
+        // The same proof read with the wrong transcript doesn't verify.
+        assert!(
+            verify::<Blake2bRead<_, _, _>>(&params, &pk, MultiOpenScheme::SHPlonk, &proof).is_err()
+        );
+
+        // There is no native SHA-256 verifier, so the proof is compared with
+        // the one halo2 writes for the EVM verifier instead.
+        let builder = ProverBuilder::new(ProverPreset::EvmSha256Shplonk);
+        let proof = prove(
+            &builder,
+            &pk_bytes,
+            TachyonSha256Write::init(vec![]),
+            TachyonSha256Write::finalize,
+        );
+        let mut transcript = ShaWrite::<_, G1Affine, Challenge255<_>, sha2::Sha256>::init(vec![]);
+        halo2_proofs::plonk::create_proof::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<_>,
+            _,
+            _,
+            _,
+            _,
+        >(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[&public_inputs[..]]],
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        assert_eq!(proof, transcript.finalize());
+
+        // An override wins over the preset.
+        let builder =
+            ProverBuilder::new(ProverPreset::LocalBlake2b).multi_open_scheme(MultiOpenScheme::Gwc);
+        let proof = prove(
+            &builder,
+            &pk_bytes,
+            TachyonBlake2bWrite::init(vec![]),
+            TachyonBlake2bWrite::finalize,
+        );
+        assert!(verify::<Blake2bRead<_, _, _>>(&params, &pk, MultiOpenScheme::Gwc, &proof).is_ok());
+        assert!(
+            verify::<Blake2bRead<_, _, _>>(&params, &pk, MultiOpenScheme::SHPlonk, &proof).is_err()
+        );
+    }
+}
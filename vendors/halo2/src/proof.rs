@@ -0,0 +1,772 @@
+use std::{fmt, io};
+
+use halo2_proofs::{
+    plonk::VerifyingKey,
+    poly::{commitment::CommitmentScheme, kzg::commitment::ParamsKZG},
+    transcript::{Challenge255, TranscriptReadBuffer},
+};
+use halo2curves::{
+    bn256::{Bn256, Fr, G1Affine},
+    group::GroupEncoding,
+    CurveAffine,
+};
+
+use crate::{
+    aggregate::TranscriptReadType,
+    audit::to_hex,
+    bn254::TachyonProver,
+    circuit_id::circuit_id,
+    compat::{g1_from_xy, g1_to_xy},
+    consts::{AbsorbOrder, InstanceAbsorption, MultiOpenScheme, TranscriptType},
+    error::TachyonError,
+    instance_hash,
+    proving_key::Metadata,
+    serde_fr::{fr_from_le_bytes, fr_to_le_bytes, FR_BYTES},
+    state::StateError,
+    transcript::{Component, TranscriptItem},
+};
+
+/// How the points of a proof are encoded. This depends only on the transcript
+/// the proof was written with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointEncoding {
+    /// The 32 bytes of `GroupEncoding::to_bytes()`.
+    Compressed,
+    /// The little-endian x and y coordinates, 64 bytes in total. The identity
+    /// is written as `(0, 0)`.
+    Uncompressed,
+}
+
+impl PointEncoding {
+    pub fn for_transcript(transcript_type: &TranscriptType) -> Self {
+        match transcript_type {
+            TranscriptType::Blake2b | TranscriptType::Poseidon | TranscriptType::Custom => {
+                PointEncoding::Compressed
+            }
+            TranscriptType::Sha256 => PointEncoding::Uncompressed,
+        }
+    }
+
+    /// The size of an encoded point in bytes.
+    pub fn point_len(&self) -> usize {
+        match self {
+            PointEncoding::Compressed => 32,
+            PointEncoding::Uncompressed => 64,
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Option<G1Affine> {
+        match self {
+            PointEncoding::Compressed => {
+                let mut repr = <G1Affine as GroupEncoding>::Repr::default();
+                repr.as_mut().copy_from_slice(bytes);
+                Option::from(G1Affine::from_bytes(&repr))
+            }
+            PointEncoding::Uncompressed => {
+                if bytes.iter().all(|byte| *byte == 0) {
+                    return Some(G1Affine::default());
+                }
+                g1_from_xy(
+                    bytes[..32].try_into().unwrap(),
+                    bytes[32..].try_into().unwrap(),
+                )
+            }
+        }
+    }
+
+    pub(crate) fn encode(&self, point: &G1Affine, out: &mut Vec<u8>) {
+        match self {
+            PointEncoding::Compressed => out.extend_from_slice(point.to_bytes().as_ref()),
+            PointEncoding::Uncompressed => match g1_to_xy(point) {
+                Some((x, y)) => {
+                    out.extend_from_slice(&x);
+                    out.extend_from_slice(&y);
+                }
+                None => out.extend_from_slice(&[0; 64]),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofValue {
+    Point(G1Affine),
+    Scalar(Fr),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The proof ends in the middle of `item`, which starts at `offset`.
+    Truncated { offset: usize, item: TranscriptItem },
+    /// The bytes at `offset` are not a point on the curve.
+    NonCanonicalPoint { offset: usize, item: TranscriptItem },
+    /// The bytes at `offset` are not smaller than the scalar modulus.
+    NonCanonicalScalar { offset: usize, item: TranscriptItem },
+    /// The proof goes on after the last item of the layout.
+    TrailingBytes { offset: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Truncated { offset, item } => {
+                write!(f, "proof ends in {:?} at offset {}", item, offset)
+            }
+            ParseError::NonCanonicalPoint { offset, item } => {
+                write!(f, "non-canonical point {:?} at offset {}", item, offset)
+            }
+            ParseError::NonCanonicalScalar { offset, item } => {
+                write!(f, "non-canonical scalar {:?} at offset {}", item, offset)
+            }
+            ParseError::TrailingBytes { offset } => {
+                write!(f, "unexpected bytes after offset {}", offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A proof split into the values its layout says it has.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedProof {
+    encoding: PointEncoding,
+    values: Vec<(TranscriptItem, ProofValue)>,
+}
+
+impl ParsedProof {
+    pub fn encoding(&self) -> PointEncoding {
+        self.encoding
+    }
+
+    /// Every value in the order it was written.
+    pub fn values(&self) -> &[(TranscriptItem, ProofValue)] {
+        &self.values
+    }
+
+    fn points(&self, component: Component, circuit: Option<usize>) -> Vec<G1Affine> {
+        self.values
+            .iter()
+            .filter_map(|(item, value)| match value {
+                ProofValue::Point(point)
+                    if item.component == component && item.circuit == circuit =>
+                {
+                    Some(*point)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The advice commitments of `circuit` in the order they were written,
+    /// i.e, grouped by phase.
+    pub fn advice_commitments(&self, circuit: usize) -> Vec<G1Affine> {
+        self.points(Component::AdviceCommitment, Some(circuit))
+    }
+
+    /// Every evaluation in the order it was written.
+    pub fn evaluations(&self) -> Vec<(TranscriptItem, Fr)> {
+        self.values
+            .iter()
+            .filter_map(|(item, value)| match value {
+                ProofValue::Scalar(scalar) => Some((*item, *scalar)),
+                ProofValue::Point(_) => None,
+            })
+            .collect()
+    }
+
+    /// The commitments the multi-open argument ends the proof with.
+    pub fn final_opening(&self) -> Vec<G1Affine> {
+        self.points(Component::OpeningCommitment, None)
+    }
+
+    /// Writes the values back in the encoding they were parsed with.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        for (_, value) in &self.values {
+            match value {
+                ProofValue::Point(point) => self.encoding.encode(point, &mut bytes),
+                ProofValue::Scalar(scalar) => bytes.extend_from_slice(&fr_to_le_bytes(scalar)),
+            }
+        }
+        bytes
+    }
+}
+
+/// Splits `proof` into the values listed in `layout`, which is usually from
+/// [`crate::transcript::transcript_layout()`].
+pub fn parse(
+    proof: &[u8],
+    layout: &[TranscriptItem],
+    encoding: PointEncoding,
+) -> Result<ParsedProof, ParseError> {
+    let mut offset = 0;
+    let mut values = Vec::with_capacity(layout.len());
+    for item in layout {
+        let len = if item.component.is_point() {
+            encoding.point_len()
+        } else {
+            FR_BYTES
+        };
+        let bytes = proof
+            .get(offset..offset + len)
+            .ok_or(ParseError::Truncated {
+                offset,
+                item: *item,
+            })?;
+        let value = if item.component.is_point() {
+            ProofValue::Point(
+                encoding
+                    .decode(bytes)
+                    .ok_or(ParseError::NonCanonicalPoint {
+                        offset,
+                        item: *item,
+                    })?,
+            )
+        } else {
+            ProofValue::Scalar(fr_from_le_bytes(bytes.try_into().unwrap()).map_err(|_| {
+                ParseError::NonCanonicalScalar {
+                    offset,
+                    item: *item,
+                }
+            })?)
+        };
+        values.push((*item, value));
+        offset += len;
+    }
+    if offset != proof.len() {
+        return Err(ParseError::TrailingBytes { offset });
+    }
+    Ok(ParsedProof { encoding, values })
+}
+
+/// The version of the [`ProofHeader`] encoding [`Proof::to_bytes()`] writes.
+pub const PROOF_FORMAT_VERSION: u8 = 1;
+
+/// The bytes a [`Proof`] with a header starts with.
+const PROOF_MAGIC: [u8; 4] = *b"TPRF";
+
+/// The length of an encoded [`ProofHeader`]: the magic, the version, the
+/// transcript type, the multi-open scheme, k and the circuit id.
+const PROOF_HEADER_LEN: usize = PROOF_MAGIC.len() + 4 + 32;
+
+fn invalid_data(message: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn multi_open_scheme_to_u8(scheme: MultiOpenScheme) -> u8 {
+    match scheme {
+        MultiOpenScheme::Gwc => 0,
+        MultiOpenScheme::SHPlonk => 1,
+    }
+}
+
+fn multi_open_scheme_from_u8(value: u8) -> Option<MultiOpenScheme> {
+    match value {
+        0 => Some(MultiOpenScheme::Gwc),
+        1 => Some(MultiOpenScheme::SHPlonk),
+        _ => None,
+    }
+}
+
+/// What a [`Proof`] says about how it was created, so that a verifier can
+/// tell a proof for another circuit or setup from a forged one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofHeader {
+    pub version: u8,
+    /// See [`crate::circuit_id::circuit_id()`].
+    pub circuit_id: [u8; 32],
+    pub transcript_type: TranscriptType,
+    pub multi_open_scheme: MultiOpenScheme,
+    pub k: u32,
+}
+
+impl ProofHeader {
+    pub fn new<C: CurveAffine>(
+        vk: &VerifyingKey<C>,
+        transcript_type: TranscriptType,
+        multi_open_scheme: MultiOpenScheme,
+        k: u32,
+    ) -> Self {
+        ProofHeader {
+            version: PROOF_FORMAT_VERSION,
+            circuit_id: circuit_id(vk),
+            transcript_type,
+            multi_open_scheme,
+            k,
+        }
+    }
+
+    /// The header of the proofs `prover` creates with `vk`.
+    pub fn for_prover<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+        prover: &P,
+        vk: &VerifyingKey<Scheme::Curve>,
+    ) -> Result<Self, TachyonError> {
+        let transcript_type = prover.transcript_type();
+        let transcript_type = TranscriptType::from_u8(transcript_type)
+            .ok_or(StateError::UnknownTranscript(transcript_type))?;
+        Ok(Self::new(
+            vk,
+            transcript_type,
+            prover.multi_open_scheme(),
+            prover.k(),
+        ))
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&PROOF_MAGIC);
+        out.push(self.version);
+        out.push(self.transcript_type as u8);
+        out.push(multi_open_scheme_to_u8(self.multi_open_scheme));
+        out.push(self.k as u8);
+        out.extend_from_slice(&self.circuit_id);
+    }
+
+    /// Reads the header at the start of `bytes`, which has to start with the
+    /// magic.
+    fn read(bytes: &[u8]) -> io::Result<Self> {
+        let bytes = bytes.get(..PROOF_HEADER_LEN).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::UnexpectedEof, "the proof ends in its header")
+        })?;
+        let version = bytes[4];
+        if version != PROOF_FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported proof format version {}",
+                version
+            )));
+        }
+        let transcript_type = TranscriptType::from_u8(bytes[5])
+            .ok_or_else(|| invalid_data(format!("unknown transcript type {}", bytes[5])))?;
+        let multi_open_scheme = multi_open_scheme_from_u8(bytes[6])
+            .ok_or_else(|| invalid_data(format!("unknown multi-open scheme {}", bytes[6])))?;
+        Ok(ProofHeader {
+            version,
+            circuit_id: bytes[8..].try_into().unwrap(),
+            transcript_type,
+            multi_open_scheme,
+            k: bytes[7] as u32,
+        })
+    }
+
+    /// Checks that a proof with this header was created for `vk` with the
+    /// given setup.
+    pub fn check<C: CurveAffine>(
+        &self,
+        vk: &VerifyingKey<C>,
+        transcript_type: TranscriptType,
+        multi_open_scheme: MultiOpenScheme,
+        k: u32,
+    ) -> Result<(), TachyonError> {
+        let mismatch = |field, expected: String, found: String| {
+            Err(TachyonError::ProofHeaderMismatch {
+                field,
+                expected,
+                found,
+            })
+        };
+        let expected_id = circuit_id(vk);
+        if self.circuit_id != expected_id {
+            return mismatch("circuit id", to_hex(&expected_id), to_hex(&self.circuit_id));
+        }
+        if self.transcript_type != transcript_type {
+            return mismatch(
+                "transcript type",
+                transcript_type.name().to_string(),
+                self.transcript_type.name().to_string(),
+            );
+        }
+        if self.multi_open_scheme != multi_open_scheme {
+            return mismatch(
+                "multi-open scheme",
+                multi_open_scheme.name().to_string(),
+                self.multi_open_scheme.name().to_string(),
+            );
+        }
+        if self.k != k {
+            return mismatch("k", k.to_string(), self.k.to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The transcript bytes of a proof, behind a [`ProofHeader`] unless the proof
+/// predates it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Proof {
+    header: Option<ProofHeader>,
+    bytes: Vec<u8>,
+}
+
+impl fmt::Debug for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proof")
+            .field("header", &self.header)
+            .field("len", &self.bytes.len())
+            .finish()
+    }
+}
+
+impl Proof {
+    pub fn new(header: ProofHeader, bytes: Vec<u8>) -> Self {
+        Proof {
+            header: Some(header),
+            bytes,
+        }
+    }
+
+    /// A proof without a header, as written before there was one. Its header
+    /// can't be checked, so it is only as safe as the caller's bookkeeping.
+    pub fn from_raw(bytes: Vec<u8>) -> Self {
+        Proof {
+            header: None,
+            bytes,
+        }
+    }
+
+    pub fn header(&self) -> Option<&ProofHeader> {
+        self.header.as_ref()
+    }
+
+    /// The transcript bytes, as halo2 and the legacy APIs take them.
+    pub fn raw(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_raw(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// The header, if any, followed by the transcript bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(PROOF_HEADER_LEN + self.bytes.len());
+        if let Some(header) = &self.header {
+            header.write(&mut out);
+        }
+        out.extend_from_slice(&self.bytes);
+        out
+    }
+
+    /// Inverse of [`Proof::to_bytes()`]. Bytes that don't start with the
+    /// magic of a header are taken as a legacy proof, as with
+    /// [`Proof::from_raw()`].
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        if !bytes.starts_with(&PROOF_MAGIC) {
+            return Ok(Proof::from_raw(bytes.to_vec()));
+        }
+        let header = ProofHeader::read(bytes)?;
+        Ok(Proof::new(header, bytes[PROOF_HEADER_LEN..].to_vec()))
+    }
+
+    /// Checks the header against `vk` and the setup the verifier uses, see
+    /// [`ProofHeader::check()`]. A legacy proof has nothing to check.
+    pub fn check_header<C: CurveAffine>(
+        &self,
+        vk: &VerifyingKey<C>,
+        transcript_type: TranscriptType,
+        multi_open_scheme: MultiOpenScheme,
+        k: u32,
+    ) -> Result<(), TachyonError> {
+        match &self.header {
+            Some(header) => header.check(vk, transcript_type, multi_open_scheme, k),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Same as [`crate::instance_hash::verify_proof()`], but first checks the
+/// header of `proof` against `vk`, `transcript_type` and `params`, so that a
+/// proof for another circuit or setup fails with
+/// [`TachyonError::ProofHeaderMismatch`] instead of a bare verification
+/// error.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_proof<
+    'a,
+    T: TranscriptReadBuffer<&'a [u8], G1Affine, Challenge255<G1Affine>> + TranscriptReadType,
+>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    key_metadata: &Metadata,
+    absorption: InstanceAbsorption,
+    advice_order: AbsorbOrder,
+    transcript_type: TranscriptType,
+    instances: &[&[&[Fr]]],
+    proof: &'a Proof,
+) -> Result<(), TachyonError> {
+    proof.check_header(vk, transcript_type, MultiOpenScheme::SHPlonk, params.k())?;
+    instance_hash::verify_proof::<T>(
+        params,
+        vk,
+        key_metadata,
+        absorption,
+        advice_order,
+        instances,
+        proof.raw(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, Circuit, ProvingKey},
+        poly::{
+            commitment::Prover,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverGWC, ProverSHPLONK},
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptWrite, TranscriptWriterBuffer,
+        },
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::SeedableRng;
+
+    use super::{parse, verify_proof, ParseError, PointEncoding, Proof, ProofHeader};
+    use crate::{
+        circuits::{simple_circuit::SimpleCircuit, simple_lookup_circuit::SimpleLookupCircuit},
+        consts::{AbsorbOrder, InstanceAbsorption, MultiOpenScheme, TranscriptType, SEED},
+        error::TachyonError,
+        proving_key::Metadata,
+        sha::ShaWrite,
+        transcript::{transcript_layout, Component},
+        xor_shift_rng::XORShiftRng,
+    };
+
+    fn create_proof<'params, P, ConcreteCircuit, T>(
+        params: &'params ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        circuits: &[ConcreteCircuit],
+        instances: &[&[&[Fr]]],
+        mut transcript: T,
+        finalize: impl FnOnce(T) -> Vec<u8>,
+    ) -> Vec<u8>
+    where
+        P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+        ConcreteCircuit: Circuit<Fr>,
+        T: TranscriptWrite<G1Affine, Challenge255<G1Affine>>,
+    {
+        let rng = XORShiftRng::from_seed(SEED);
+        halo2_proofs::plonk::create_proof::<KZGCommitmentScheme<Bn256>, P, _, _, _, _>(
+            params,
+            pk,
+            circuits,
+            instances,
+            rng,
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        finalize(transcript)
+    }
+
+    #[test]
+    fn test_parse_shplonk_proof() {
+        let k = 4;
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let instances = vec![Fr::from(7 * 2 * 2 * 3 * 3)];
+        let instances: &[&[&[Fr]]] = &[&[&instances[..]]];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::from(2));
+        let pk = keygen_pk2(&params, &circuit).expect("pk should not fail");
+        let layout = transcript_layout(pk.get_vk(), 1, MultiOpenScheme::SHPlonk);
+
+        let proofs = [
+            (
+                TranscriptType::Blake2b,
+                create_proof::<ProverSHPLONK<_>, _, _>(
+                    &params,
+                    &pk,
+                    &[circuit.clone()],
+                    instances,
+                    Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]),
+                    Blake2bWrite::finalize,
+                ),
+            ),
+            (
+                TranscriptType::Sha256,
+                create_proof::<ProverSHPLONK<_>, _, _>(
+                    &params,
+                    &pk,
+                    &[circuit.clone()],
+                    instances,
+                    ShaWrite::<_, G1Affine, Challenge255<_>, sha2::Sha256>::init(vec![]),
+                    ShaWrite::finalize,
+                ),
+            ),
+        ];
+        for (transcript_type, proof) in proofs {
+            let encoding = PointEncoding::for_transcript(&transcript_type);
+            let parsed = parse(&proof, &layout, encoding).unwrap();
+            assert_eq!(parsed.to_bytes(), proof);
+            assert_eq!(
+                parsed.advice_commitments(0).len(),
+                pk.get_vk().cs().num_advice_columns
+            );
+            assert_eq!(parsed.final_opening().len(), 2);
+
+            let last = proof.len() - encoding.point_len();
+            assert_eq!(
+                parse(&proof[..proof.len() - 1], &layout, encoding),
+                Err(ParseError::Truncated {
+                    offset: last,
+                    item: *layout.last().unwrap(),
+                })
+            );
+
+            let mut trailing = proof.clone();
+            trailing.push(0);
+            assert_eq!(
+                parse(&trailing, &layout, encoding),
+                Err(ParseError::TrailingBytes {
+                    offset: proof.len()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_gwc_proof() {
+        let k = 5;
+        let circuit = SimpleLookupCircuit::<Fr>::default();
+        let instances = vec![];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::from(2));
+        let pk = keygen_pk2(&params, &circuit).expect("pk should not fail");
+        let layout = transcript_layout(pk.get_vk(), 2, MultiOpenScheme::Gwc);
+        let proof = create_proof::<ProverGWC<_>, _, _>(
+            &params,
+            &pk,
+            &[circuit.clone(), circuit],
+            &[&[&instances[..]], &[&instances[..]]],
+            Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]),
+            Blake2bWrite::finalize,
+        );
+
+        let parsed = parse(&proof, &layout, PointEncoding::Compressed).unwrap();
+        assert_eq!(parsed.to_bytes(), proof);
+        assert_eq!(
+            parsed.advice_commitments(1).len(),
+            pk.get_vk().cs().num_advice_columns
+        );
+
+        // Overwrite the first evaluation with a value above the modulus.
+        // Compressed points and scalars are both 32 bytes.
+        let index = layout
+            .iter()
+            .position(|item| !item.component.is_point())
+            .unwrap();
+        let (offset, item) = (index * 32, layout[index]);
+        assert_eq!(item.component, Component::AdviceEval);
+        let mut non_canonical = proof.clone();
+        non_canonical[offset..offset + 32].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            parse(&non_canonical, &layout, PointEncoding::Compressed),
+            Err(ParseError::NonCanonicalScalar { offset, item })
+        );
+    }
+
+    #[test]
+    fn test_proof_header() {
+        let k = 4;
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let instances = vec![Fr::from(7 * 2 * 2 * 3 * 3)];
+        let instances: &[&[&[Fr]]] = &[&[&instances[..]]];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::from(2));
+        let pk = keygen_pk2(&params, &circuit).expect("pk should not fail");
+        let raw = create_proof::<ProverSHPLONK<_>, _, _>(
+            &params,
+            &pk,
+            &[circuit.clone()],
+            instances,
+            Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]),
+            Blake2bWrite::finalize,
+        );
+        let header = ProofHeader::new(
+            pk.get_vk(),
+            TranscriptType::Blake2b,
+            MultiOpenScheme::SHPlonk,
+            k,
+        );
+        let verify = |proof: &Proof, transcript_type| {
+            verify_proof::<Blake2bRead<_, _, _>>(
+                &params,
+                pk.get_vk(),
+                &Metadata::new(),
+                InstanceAbsorption::PerValue,
+                AbsorbOrder::PhaseThenColumn,
+                transcript_type,
+                instances,
+                proof,
+            )
+        };
+
+        let proof = Proof::new(header, raw.clone());
+        let bytes = proof.to_bytes();
+        assert_eq!(&bytes[bytes.len() - raw.len()..], &raw[..]);
+        let read = Proof::from_bytes(&bytes).unwrap();
+        assert_eq!(read, proof);
+        assert_eq!(read.header(), Some(&header));
+        assert_eq!(read.raw(), &raw[..]);
+        assert!(verify(&read, TranscriptType::Blake2b).is_ok());
+
+        // A legacy proof has no header to check, and reads back as such.
+        let legacy = Proof::from_raw(raw.clone());
+        assert_eq!(legacy.to_bytes(), raw);
+        assert_eq!(Proof::from_bytes(&raw).unwrap(), legacy);
+        assert!(verify(&legacy, TranscriptType::Blake2b).is_ok());
+
+        // Every field of the header is checked before the proof is.
+        let e = verify(&proof, TranscriptType::Sha256).unwrap_err();
+        assert!(matches!(
+            &e,
+            TachyonError::ProofHeaderMismatch { field: "transcript type", expected, found }
+                if expected == "sha256" && found == "blake2b"
+        ));
+        assert!(e.to_string().contains("transcript type"));
+        let mismatched = [
+            (
+                ProofHeader {
+                    circuit_id: [0; 32],
+                    ..header
+                },
+                "circuit id",
+            ),
+            (
+                ProofHeader {
+                    multi_open_scheme: MultiOpenScheme::Gwc,
+                    ..header
+                },
+                "multi-open scheme",
+            ),
+            (ProofHeader { k: k + 1, ..header }, "k"),
+        ];
+        for (header, field) in mismatched {
+            let proof = Proof::new(header, raw.clone());
+            assert!(matches!(
+                verify(&proof, TranscriptType::Blake2b),
+                Err(TachyonError::ProofHeaderMismatch { field: found, .. }) if found == field
+            ));
+        }
+
+        // A header of an unknown version or cut short doesn't parse.
+        let mut unknown_version = bytes.clone();
+        unknown_version[4] = 2;
+        assert_eq!(
+            Proof::from_bytes(&unknown_version).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            Proof::from_bytes(&bytes[..10]).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
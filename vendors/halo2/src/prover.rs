@@ -2,17 +2,55 @@
 
 use std::{
     collections::{BTreeSet, HashMap},
-    ops::RangeTo,
+    fmt, io,
+    ops::{Deref, RangeTo},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
 use crate::bn254::{
     AdviceSingle, Evals, InstanceSingle, ProvingKey as TachyonProvingKey, RationalEvals,
     TachyonProver, TranscriptWriteState,
 };
-use crate::xor_shift_rng::XORShiftRng as TachyonXORShiftRng;
+#[cfg(feature = "stats")]
+use crate::stats::TimedStage;
+use crate::{
+    annotation::{describe_column, metadata_key, ColumnAnnotations, RowOverflow},
+    assignments::AssignedColumn,
+    audit::{backend_entries, rust_entries, write_audit_log, AuditLayout, Forward},
+    backend_log::VerbosityOverride,
+    column::InstanceColumnIndex,
+    consts::{
+        AbsorbOrder, InstanceAbsorption, LagrangeBasisPolicy, MultiOpenScheme, TranscriptType,
+    },
+    debug::verify_constraints,
+    error::TachyonError,
+    estimate::{fft_chunk_log2_for_budget, EstimateOptions},
+    instance_hash::{backend_column_digest, BackendColumnDigest},
+    instance_source::InstanceSource,
+    layout::{LayoutColumn, RegionLayout, RegionRecorder},
+    limits::ResourceLimits,
+    metrics::{ColumnReport, GateCost, PeakTracker},
+    opening::{opening_queries, read_evaluations, OpeningQuery},
+    par,
+    phase::{ChallengeError, ChallengeId, ColumnIndex},
+    placement::StagePlacement,
+    precommit::{check_precommitted_advice, PrecommittedColumn},
+    proof::PointEncoding,
+    proving_key::Metadata,
+    race::CancelToken,
+    redact::DebugFull,
+    repro::{capture_proof, ReproCapture, WitnessColumn},
+    rng::derive_seed,
+    self_check::{self_check_bases, LagrangeBases, SelfCheck},
+    state::StateError,
+    transcript::{RecordingTranscript, TranscriptLog},
+    version::check_backend,
+    xor_shift_rng::XORShiftRng as TachyonXORShiftRng,
+};
 use ff::Field;
 use halo2_proofs::{
-    circuit::Value,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{
         sealed, Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem,
         Error, Fixed, FloorPlanner, Instance, Selector,
@@ -21,15 +59,289 @@ use halo2_proofs::{
     transcript::EncodedChallenge,
 };
 use halo2curves::{
-    bn256::Fr,
-    group::{prime::PrimeCurveAffine, Curve},
+    bn256::{Fr, G1Affine},
+    group::{prime::PrimeCurveAffine, Curve, Group},
     CurveAffine,
 };
+use rand_core::SeedableRng;
+
+/// Instances whose evaluations and polynomials, and their commitments if the
+/// prover queries instances, are computed once so that they can be reused
+/// across proofs. See [`prepare_instances()`].
+pub struct PreparedInstances<C: CurveAffine> {
+    /// The values as given, per circuit and column. These are absorbed into
+    /// the transcript when instances aren't queried. Empty if `streamed`.
+    values: Vec<Vec<Vec<C::Scalar>>>,
+    /// The number of values given, per circuit and column.
+    column_lens: Vec<Vec<usize>>,
+    singles: Vec<InstanceSingle>,
+    /// Empty unless `query_instance` is set.
+    commitments: Vec<Vec<C>>,
+    query_instance: bool,
+    /// Set if the verifying key and the instances were absorbed into the
+    /// transcript as they were read, see
+    /// [`create_proof_streaming_instances()`].
+    streamed: bool,
+}
+
+impl<C: CurveAffine> fmt::Debug for PreparedInstances<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("PreparedInstances");
+        s.field("column_lens", &self.column_lens)
+            .field("query_instance", &self.query_instance)
+            .field("streamed", &self.streamed);
+        #[cfg(feature = "debug-values")]
+        s.field(
+            "values",
+            &self
+                .values
+                .iter()
+                .map(|instance| {
+                    instance
+                        .iter()
+                        .map(|values| {
+                            crate::redact::Values::summary(values.len(), move |i| values[i])
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>(),
+        );
+        s.finish()
+    }
+}
+
+impl<C: CurveAffine> DebugFull for PreparedInstances<C> {
+    fn fmt_full(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedInstances")
+            .field("column_lens", &self.column_lens)
+            .field("query_instance", &self.query_instance)
+            .field("streamed", &self.streamed)
+            .field("values", &self.values)
+            .field("commitments", &self.commitments)
+            .finish()
+    }
+}
+
+impl<C: CurveAffine> PreparedInstances<C> {
+    /// Sets the instance cells of the circuit at `circuit_index` in
+    /// `changes`, given as `(column, row, value)`, and recomputes the
+    /// polynomials and commitments of only the columns that changed. Returns
+    /// those columns, which are the ones whose absorption into the transcript
+    /// differs in the next proof.
+    pub fn update_cells<Scheme: CommitmentScheme<Curve = C>, P: TachyonProver<Scheme>>(
+        &mut self,
+        circuit_index: usize,
+        changes: &[(InstanceColumnIndex, usize, C::Scalar)],
+        prover: &P,
+        pk: &TachyonProvingKey<C>,
+    ) -> Result<Vec<InstanceColumnIndex>, Error> {
+        let changes: Vec<_> = changes
+            .iter()
+            .map(|(column, row, value)| (column.index(), *row, *value))
+            .collect();
+        let columns = self.update_raw::<Scheme, P>(circuit_index, &changes, prover, pk)?;
+        Ok(columns.into_iter().map(InstanceColumnIndex).collect())
+    }
+
+    /// Same as [`PreparedInstances::update_cells()`] with plain column
+    /// indices.
+    #[deprecated(note = "use `update_cells()`, which takes `InstanceColumnIndex`")]
+    pub fn update<Scheme: CommitmentScheme<Curve = C>, P: TachyonProver<Scheme>>(
+        &mut self,
+        circuit_index: usize,
+        changes: &[(usize, usize, C::Scalar)],
+        prover: &P,
+        pk: &TachyonProvingKey<C>,
+    ) -> Result<Vec<usize>, Error> {
+        self.update_raw::<Scheme, P>(circuit_index, changes, prover, pk)
+    }
+
+    fn update_raw<Scheme: CommitmentScheme<Curve = C>, P: TachyonProver<Scheme>>(
+        &mut self,
+        circuit_index: usize,
+        changes: &[(usize, usize, C::Scalar)],
+        prover: &P,
+        pk: &TachyonProvingKey<C>,
+    ) -> Result<Vec<usize>, Error> {
+        let single = self
+            .singles
+            .get_mut(circuit_index)
+            .ok_or(Error::InvalidInstances)?;
+        let fr_changes = changes
+            .iter()
+            .map(|(column, row, value)| {
+                (*column, *row, unsafe {
+                    *std::mem::transmute::<_, &halo2curves::bn256::Fr>(value)
+                })
+            })
+            .collect::<Vec<_>>();
+        let columns = single.update::<Scheme, P>(&fr_changes, prover, pk)?;
+
+        let values = &mut self.values[circuit_index];
+        let column_lens = &mut self.column_lens[circuit_index];
+        for (column, row, value) in changes {
+            let values = &mut values[*column];
+            if values.len() <= *row {
+                values.resize(*row + 1, C::Scalar::zero());
+                column_lens[*column] = *row + 1;
+            }
+            values[*row] = *value;
+        }
+        if self.query_instance {
+            let changed: Vec<_> = columns
+                .iter()
+                .map(|column| &single.instance_values[*column])
+                .collect();
+            let commitments_projective = prover.commit_lagrange_columns(&changed);
+            let mut commitments = vec![C::identity(); commitments_projective.len()];
+            C::CurveExt::batch_normalize(&commitments_projective, &mut commitments);
+            for (column, commitment) in columns.iter().zip(commitments) {
+                self.commitments[circuit_index][*column] = commitment;
+            }
+        }
+        Ok(columns)
+    }
+}
+
+/// Computes everything about `instances` that doesn't depend on the
+/// transcript, so that it can be passed to
+/// [`create_proof_from_prepared_instances()`] any number of times.
+pub fn prepare_instances<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    prover: &mut P,
+    pk: &TachyonProvingKey<Scheme::Curve>,
+    instances: &[&[&[Scheme::Scalar]]],
+) -> Result<PreparedInstances<Scheme::Curve>, Error> {
+    for instance in instances.iter() {
+        if instance.len() != pk.num_instance_columns() {
+            return Err(Error::InvalidInstances);
+        }
+    }
+
+    prover.set_extended_domain(pk);
+    let query_instance = prover.query_instance();
+    let mut singles = Vec::with_capacity(instances.len());
+    let mut commitments = Vec::with_capacity(instances.len());
+    for instance in instances.iter() {
+        let mut instance_values = instance
+            .iter()
+            .map(|values| {
+                let poly = prover.empty_evals();
+                assert_eq!(poly.len(), prover.n() as usize);
+                if values.len() > (poly.len() - ((pk.blinding_factors() as usize) + 1)) {
+                    return Err(Error::InstanceTooLarge);
+                }
+                Ok(poly)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        par::for_each_mut(&mut instance_values, |column, poly| {
+            for (i, value) in instance[column].iter().enumerate() {
+                poly.set_value(i, unsafe {
+                    std::mem::transmute::<_, &halo2curves::bn256::Fr>(value)
+                });
+            }
+        });
+
+        if query_instance {
+            let columns: Vec<_> = instance_values.iter().collect();
+            let instance_commitments_projective = prover.commit_lagrange_columns(&columns);
+            let mut instance_commitments =
+                vec![Scheme::Curve::identity(); instance_commitments_projective.len()];
+            <Scheme::Curve as CurveAffine>::CurveExt::batch_normalize(
+                &instance_commitments_projective,
+                &mut instance_commitments,
+            );
+            commitments.push(instance_commitments);
+        } else {
+            commitments.push(vec![]);
+        }
+
+        let instance_polys: Vec<_> = instance_values
+            .iter()
+            .map(|evals| prover.ifft(evals))
+            .collect();
+
+        singles.push(InstanceSingle {
+            instance_values,
+            instance_polys,
+        });
+    }
+
+    Ok(PreparedInstances {
+        values: instances
+            .iter()
+            .map(|instance| instance.iter().map(|values| values.to_vec()).collect())
+            .collect(),
+        column_lens: instances
+            .iter()
+            .map(|instance| instance.iter().map(|values| values.len()).collect())
+            .collect(),
+        singles,
+        commitments,
+        query_instance,
+        streamed: false,
+    })
+}
+
+/// Absorbs the verification key of `pk` and the instances of `prepared` into
+/// `transcript`, the way every proof starts.
+pub(crate) fn absorb_statement<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E>,
+>(
+    prover: &mut P,
+    pk: &mut TachyonProvingKey<Scheme::Curve>,
+    prepared: &PreparedInstances<Scheme::Curve>,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    prover.set_extended_domain(pk);
+    if prepared.streamed {
+        return Ok(());
+    }
+    // Hash verification key into transcript
+    transcript.common_scalar(prover.transcript_repr(pk))?;
+
+    for (values, commitments) in prepared.values.iter().zip(&prepared.commitments) {
+        if prepared.query_instance {
+            for commitment in commitments {
+                transcript.common_point(*commitment)?;
+            }
+        } else {
+            match pk.instance_absorption() {
+                InstanceAbsorption::PerValue => {
+                    for column in values {
+                        transcript.common_scalars(column)?;
+                    }
+                }
+                InstanceAbsorption::HashedColumn => {
+                    for column in values {
+                        let digest = backend_column_digest(
+                            unsafe { std::mem::transmute::<_, &[Fr]>(column.as_slice()) },
+                            T::TRANSCRIPT_TYPE,
+                        );
+                        transcript.common_scalar(unsafe {
+                            *std::mem::transmute::<_, &Scheme::Scalar>(&digest)
+                        })?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
 
 /// This creates a proof for the provided `circuit` when given the public
 /// parameters `params` and the proving key [`ProvingKey`] that was
 /// generated previously for the same circuit. The provided `instances`
-/// are zero-padded internally.
+/// are zero-padded internally. They are plain slices, so a framework that
+/// computes its instances as they are read should stream them with
+/// [`create_proof_streaming_instances()`], which reads every value once.
+///
+/// Every prover owns its backend state and the backend keeps no global state,
+/// so a circuit may create proofs with another prover from within
+/// `synthesize()`. `prover` itself is borrowed mutably for the whole call and
+/// can't be reused that way.
 pub fn create_proof<
     'params,
     Scheme: CommitmentScheme,
@@ -42,11 +354,87 @@ pub fn create_proof<
     pk: &mut TachyonProvingKey<Scheme::Curve>,
     circuits: &[ConcreteCircuit],
     instances: &[&[&[Scheme::Scalar]]],
-    mut rng: TachyonXORShiftRng,
+    rng: TachyonXORShiftRng,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    check_transcript_type(prover, transcript).map_err(invalid_transcript)?;
+    let prepared = prepare_instances(prover, pk, instances)?;
+    create_proof_from_prepared_instances(prover, pk, circuits, &prepared, rng, transcript)
+}
+
+/// Same as [`create_proof()`], but takes instances from
+/// [`prepare_instances()`]. `prepared` isn't consumed, so it can be updated
+/// with [`PreparedInstances::update()`] and proven again. Instances prepared
+/// with a prover of another multi-open scheme, or for another number of
+/// circuits, fail the proof with [`Error::InvalidInstances`].
+pub fn create_proof_from_prepared_instances<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+>(
+    prover: &mut P,
+    pk: &mut TachyonProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    prepared: &PreparedInstances<Scheme::Curve>,
+    rng: TachyonXORShiftRng,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    check_transcript_type(prover, transcript).map_err(invalid_transcript)?;
+    prove_prepared(prover, pk, circuits, prepared, rng, transcript)
+}
+
+/// The number of values an instance column is hashed by at a time when it is
+/// streamed with [`InstanceAbsorption::HashedColumn`].
+const INSTANCE_CHUNK_LEN: usize = 1 << 16;
+
+/// Same as [`create_proof()`], but reads the instances of each circuit from
+/// an [`InstanceSource`], e.g, an [`crate::instance_source::InstanceReader`],
+/// instead of taking them in memory. Each column is written into its evals
+/// and absorbed into the transcript as it is read, so the transcript is the
+/// same as with [`create_proof()`] while no copy of the instances is kept.
+/// Each column is read exactly once, and everything else, including
+/// `query_instance()` during synthesis, reads the evals it was written into,
+/// so a source that computes its values with side effects can't make them
+/// disagree. A source that fails to read fails the proof with
+/// [`Error::Transcript`].
+pub fn create_proof_streaming_instances<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+    S: InstanceSource,
+>(
+    prover: &mut P,
+    pk: &mut TachyonProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &mut [S],
+    rng: TachyonXORShiftRng,
     transcript: &mut T,
 ) -> Result<(), Error> {
+    check_transcript_type(prover, transcript).map_err(invalid_transcript)?;
+    let prepared = stream_instances(prover, pk, instances, transcript)?;
+    prove_prepared(prover, pk, circuits, &prepared, rng, transcript)
+}
+
+/// Does what [`prepare_instances()`] and then [`absorb_statement()`] do, a
+/// column at a time.
+fn stream_instances<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E>,
+    S: InstanceSource,
+>(
+    prover: &mut P,
+    pk: &TachyonProvingKey<Scheme::Curve>,
+    instances: &mut [S],
+    transcript: &mut T,
+) -> Result<PreparedInstances<Scheme::Curve>, Error> {
     for instance in instances.iter() {
-        if instance.len() != pk.num_instance_columns() {
+        if instance.num_columns() != pk.num_instance_columns() {
             return Err(Error::InvalidInstances);
         }
     }
@@ -55,455 +443,3353 @@ pub fn create_proof<
     // Hash verification key into transcript
     transcript.common_scalar(prover.transcript_repr(pk))?;
 
-    let mut meta = ConstraintSystem::default();
-    let config = ConcreteCircuit::configure(&mut meta);
-
-    // Selector optimizations cannot be applied here; use the ConstraintSystem
-    // from the verification key.
-
-    let mut instance: Vec<InstanceSingle> = instances
-        .iter()
-        .map(|instance| -> Result<InstanceSingle, Error> {
-            let instance_values = instance
-                .iter()
-                .map(|values| {
-                    let mut poly = prover.empty_evals();
-                    assert_eq!(poly.len(), prover.n() as usize);
-                    if values.len() > (poly.len() - ((pk.blinding_factors() as usize) + 1)) {
-                        return Err(Error::InstanceTooLarge);
+    let query_instance = prover.query_instance();
+    let absorb_values = !query_instance && pk.instance_absorption() == InstanceAbsorption::PerValue;
+    let hash_columns =
+        !query_instance && pk.instance_absorption() == InstanceAbsorption::HashedColumn;
+    let usable_rows = prover.n() as usize - ((pk.blinding_factors() as usize) + 1);
+    let mut singles = Vec::with_capacity(instances.len());
+    let mut column_lens = Vec::with_capacity(instances.len());
+    let mut commitments = Vec::with_capacity(instances.len());
+    for instance in instances.iter_mut() {
+        let lens: Vec<_> = (0..instance.num_columns())
+            .map(|column| instance.column_len(column))
+            .collect();
+        let mut instance_values = Vec::with_capacity(lens.len());
+        for (column, len) in lens.iter().enumerate() {
+            if *len > usable_rows {
+                return Err(Error::InstanceTooLarge);
+            }
+            let mut evals = prover.empty_evals();
+            let mut digest = hash_columns.then(|| BackendColumnDigest::new(T::TRANSCRIPT_TYPE));
+            let mut chunk = Vec::with_capacity(if hash_columns {
+                (*len).min(INSTANCE_CHUNK_LEN)
+            } else {
+                0
+            });
+            let mut next_row = 0;
+            let mut failure = None;
+            instance
+                .read_column(column, &mut |row, value| {
+                    if failure.is_some() {
+                        return;
                     }
-
-                    for i in 0..values.len() {
-                        if !P::QUERY_INSTANCE {
-                            transcript.common_scalar(values[i])?;
+                    if row != next_row || row >= *len {
+                        failure = Some(Error::InvalidInstances);
+                        return;
+                    }
+                    next_row += 1;
+                    evals.set_value(row, &value);
+                    if let Some(digest) = &mut digest {
+                        chunk.push(value);
+                        if chunk.len() == INSTANCE_CHUNK_LEN {
+                            digest.update(&chunk);
+                            chunk.clear();
+                        }
+                    } else if absorb_values {
+                        if let Err(e) = transcript.common_scalar(unsafe {
+                            *std::mem::transmute::<_, &Scheme::Scalar>(&value)
+                        }) {
+                            failure = Some(e.into());
                         }
-                        poly.set_value(i, unsafe {
-                            std::mem::transmute::<_, &halo2curves::bn256::Fr>(&values[i])
-                        });
                     }
-                    Ok(poly)
                 })
-                .collect::<Result<Vec<_>, _>>()?;
-
-            if P::QUERY_INSTANCE {
-                let instance_commitments_projective: Vec<_> = instance_values
-                    .iter()
-                    .map(|poly| prover.commit_lagrange(poly))
-                    .collect();
-                let mut instance_commitments =
-                    vec![Scheme::Curve::identity(); instance_commitments_projective.len()];
-                <Scheme::Curve as CurveAffine>::CurveExt::batch_normalize(
-                    &instance_commitments_projective,
-                    &mut instance_commitments,
-                );
-                let instance_commitments = instance_commitments;
-                drop(instance_commitments_projective);
+                .map_err(Error::Transcript)?;
+            if let Some(e) = failure {
+                return Err(e);
+            }
+            if next_row != *len {
+                return Err(Error::InvalidInstances);
+            }
+            if let Some(mut digest) = digest {
+                digest.update(&chunk);
+                transcript.common_scalar(unsafe {
+                    *std::mem::transmute::<_, &Scheme::Scalar>(&digest.finalize())
+                })?;
+            }
+            instance_values.push(evals);
+        }
 
-                for commitment in &instance_commitments {
-                    transcript.common_point(*commitment)?;
-                }
+        if query_instance {
+            let columns: Vec<_> = instance_values.iter().collect();
+            let instance_commitments_projective = prover.commit_lagrange_columns(&columns);
+            let mut instance_commitments =
+                vec![Scheme::Curve::identity(); instance_commitments_projective.len()];
+            <Scheme::Curve as CurveAffine>::CurveExt::batch_normalize(
+                &instance_commitments_projective,
+                &mut instance_commitments,
+            );
+            for commitment in &instance_commitments {
+                transcript.common_point(*commitment)?;
             }
+            commitments.push(instance_commitments);
+        } else {
+            commitments.push(vec![]);
+        }
 
-            let instance_polys: Vec<_> = instance_values
-                .iter()
-                .map(|evals| prover.ifft(evals))
-                .collect();
+        let instance_polys: Vec<_> = instance_values
+            .iter()
+            .map(|evals| prover.ifft(evals))
+            .collect();
 
-            Ok(InstanceSingle {
-                instance_values,
-                instance_polys,
-            })
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+        singles.push(InstanceSingle {
+            instance_values,
+            instance_polys,
+        });
+        column_lens.push(lens);
+    }
 
-    struct WitnessCollection<'a, F: Field> {
-        k: u32,
-        current_phase: sealed::Phase,
-        advice: Vec<RationalEvals>,
-        challenges: &'a HashMap<usize, F>,
-        instances: &'a [&'a [F]],
-        usable_rows: RangeTo<usize>,
-        _marker: std::marker::PhantomData<F>,
-    }
-
-    impl<'a, F: Field> Assignment<F> for WitnessCollection<'a, F> {
-        fn enter_region<NR, N>(&mut self, _: N)
-        where
-            NR: Into<String>,
-            N: FnOnce() -> NR,
-        {
-            // Do nothing; we don't care about regions in this context.
-        }
+    Ok(PreparedInstances {
+        values: vec![],
+        column_lens,
+        singles,
+        commitments,
+        query_instance,
+        streamed: true,
+    })
+}
 
-        fn exit_region(&mut self) {
-            // Do nothing; we don't care about regions in this context.
-        }
+// halo2's `Error` has no variant for a transcript of the wrong type, so the
+// functions that return it report one as a transcript error.
+fn invalid_transcript(e: TachyonError) -> Error {
+    Error::Transcript(io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+}
 
-        fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
-        where
-            A: FnOnce() -> AR,
-            AR: Into<String>,
-        {
-            // We only care about advice columns here
+struct WitnessCollection<'a, F: Field> {
+    k: u32,
+    current_phase: sealed::Phase,
+    // Only the columns of the current phase are allocated, on their first
+    // assignment.
+    advice: Vec<Option<RationalEvals>>,
+    empty_rational_evals: &'a dyn Fn() -> RationalEvals,
+    /// Indexed by challenge, `None` until squeezed.
+    challenges: &'a [Option<F>],
+    /// The instance columns as evaluated, of which only the first
+    /// `instance_lens` values were given.
+    instances: &'a [Evals],
+    instance_lens: &'a [usize],
+    usable_rows: RangeTo<usize>,
+    // Names columns the circuit doesn't annotate before it overflows.
+    metadata: &'a Metadata,
+    annotations: ColumnAnnotations,
+    overflow: Option<RowOverflow>,
+    /// `None` unless [`ProofOptions::region_layout`] is set, and in every
+    /// phase but the first, which assigns to the same regions.
+    layout: Option<RegionRecorder>,
+    _marker: std::marker::PhantomData<F>,
+}
 
-            Ok(())
+impl<'a, F: Field> WitnessCollection<'a, F> {
+    fn check_cell(&mut self, column: Column<Any>, row: usize) -> Result<(), Error> {
+        if self.usable_rows.contains(&row) {
+            return Ok(());
+        }
+        if self.overflow.is_none() {
+            let annotation = self
+                .annotations
+                .get(&column)
+                .or_else(|| self.metadata.get(&metadata_key(&column)));
+            self.overflow = Some(RowOverflow {
+                column: describe_column(&column, annotation.map(String::as_str)),
+                row,
+                usable_rows: self.usable_rows.end,
+            });
         }
+        Err(Error::not_enough_rows_available(self.k))
+    }
+}
 
-        fn annotate_column<A, AR>(&mut self, _annotation: A, _column: Column<Any>)
-        where
-            A: FnOnce() -> AR,
-            AR: Into<String>,
-        {
-            // Do nothing
+impl<'a, F: Field> Assignment<F> for WitnessCollection<'a, F> {
+    fn enter_region<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        if let Some(layout) = &mut self.layout {
+            layout.enter(name().into());
         }
+    }
 
-        fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
-            if !self.usable_rows.contains(&row) {
-                return Err(Error::not_enough_rows_available(self.k));
-            }
+    fn exit_region(&mut self) {
+        if let Some(layout) = &mut self.layout {
+            layout.exit();
+        }
+    }
 
-            self.instances
-                .get(column.index())
-                .and_then(|column| column.get(row))
-                .map(|v| Value::known(*v))
-                .ok_or(Error::BoundsFailure)
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We only care about advice columns here, besides the layout.
+        if let Some(layout) = &mut self.layout {
+            layout.assign(LayoutColumn::Selector(selector.index()), row);
         }
 
-        fn assign_advice<V, VR, A, AR>(
-            &mut self,
-            _: A,
-            column: Column<Advice>,
-            row: usize,
-            to: V,
-        ) -> Result<(), Error>
-        where
-            V: FnOnce() -> Value<VR>,
-            VR: Into<Assigned<F>>,
-            A: FnOnce() -> AR,
-            AR: Into<String>,
-        {
-            // Ignore assignment of advice column in different phase than current one.
-            if self.current_phase.0 < column.column_type().phase.0 {
-                return Ok(());
-            }
+        Ok(())
+    }
 
-            if !self.usable_rows.contains(&row) {
-                return Err(Error::not_enough_rows_available(self.k));
-            }
+    fn annotate_column<A, AR>(&mut self, annotation: A, column: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.annotations.insert(column, annotation().into());
+    }
 
-            let rational_evals = self
-                .advice
-                .get_mut(column.index())
-                .ok_or(Error::BoundsFailure)?;
-
-            let value = to().into_field().assign()?;
-            match &value {
-                Assigned::Zero => rational_evals.set_zero(row),
-                Assigned::Trivial(numerator) => {
-                    let numerator = unsafe { std::mem::transmute::<_, &Fr>(numerator) };
-                    rational_evals.set_trivial(row, numerator);
-                }
-                Assigned::Rational(numerator, denominator) => {
-                    let numerator = unsafe { std::mem::transmute::<_, &Fr>(numerator) };
-                    let denominator = unsafe { std::mem::transmute::<_, &Fr>(denominator) };
-                    rational_evals.set_rational(row, numerator, denominator)
-                }
-            }
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        if !self.usable_rows.contains(&row) {
+            return Err(Error::not_enough_rows_available(self.k));
+        }
 
-            Ok(())
+        match self.instance_lens.get(column.index()) {
+            Some(len) if row < *len => {
+                let value = self.instances[column.index()].get_value(row);
+                Ok(Value::known(unsafe {
+                    *std::mem::transmute::<_, &F>(&value)
+                }))
+            }
+            _ => Err(Error::BoundsFailure),
         }
+    }
 
-        fn assign_fixed<V, VR, A, AR>(
-            &mut self,
-            _: A,
-            _: Column<Fixed>,
-            _: usize,
-            _: V,
-        ) -> Result<(), Error>
-        where
-            V: FnOnce() -> Value<VR>,
-            VR: Into<Assigned<F>>,
-            A: FnOnce() -> AR,
-            AR: Into<String>,
-        {
-            // We only care about advice columns here
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if let Some(layout) = &mut self.layout {
+            layout.assign(LayoutColumn::Advice(column.into()), row);
+        }
 
-            Ok(())
+        // Ignore assignment of advice column in different phase than current one.
+        let phase = column.column_type().phase;
+        if self.current_phase.0 < phase.0 {
+            return Ok(());
         }
 
-        fn copy(
-            &mut self,
-            _: Column<Any>,
-            _: usize,
-            _: Column<Any>,
-            _: usize,
-        ) -> Result<(), Error> {
-            // We only care about advice columns here
+        self.check_cell(column.into(), row)?;
 
-            Ok(())
-        }
+        let rational_evals = self
+            .advice
+            .get_mut(column.index())
+            .ok_or(Error::BoundsFailure)?;
 
-        fn fill_from_row(
-            &mut self,
-            _: Column<Fixed>,
-            _: usize,
-            _: Value<Assigned<F>>,
-        ) -> Result<(), Error> {
-            Ok(())
+        let value = to().into_field().assign()?;
+        // Columns of earlier phases were committed already, so their values
+        // are dropped without allocating for them.
+        if self.current_phase != phase {
+            return Ok(());
         }
-
-        fn get_challenge(&self, challenge: Challenge) -> Value<F> {
-            self.challenges
-                .get(&challenge.index())
-                .cloned()
-                .map(Value::known)
-                .unwrap_or_else(Value::unknown)
+        let rational_evals = rational_evals.get_or_insert_with(|| (self.empty_rational_evals)());
+        match &value {
+            Assigned::Zero => rational_evals.set_zero(row),
+            Assigned::Trivial(numerator) => {
+                let numerator = unsafe { std::mem::transmute::<_, &Fr>(numerator) };
+                rational_evals.set_trivial(row, numerator);
+            }
+            // Upstream evaluates `n / 0` to zero, because `batch_invert()`
+            // leaves zero denominators as they are. Don't leave that to the
+            // backend's inversion.
+            Assigned::Rational(_, denominator) if bool::from(denominator.is_zero()) => {
+                rational_evals.set_zero(row)
+            }
+            Assigned::Rational(numerator, denominator) => {
+                let numerator = unsafe { std::mem::transmute::<_, &Fr>(numerator) };
+                let denominator = unsafe { std::mem::transmute::<_, &Fr>(denominator) };
+                rational_evals.set_rational(row, numerator, denominator)
+            }
         }
 
-        fn push_namespace<NR, N>(&mut self, _: N)
-        where
-            NR: Into<String>,
-            N: FnOnce() -> NR,
-        {
-            // Do nothing; we don't care about namespaces in this context.
-        }
+        Ok(())
+    }
 
-        fn pop_namespace(&mut self, _: Option<String>) {
-            // Do nothing; we don't care about namespaces in this context.
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We only care about advice columns here, besides the layout.
+        if let Some(layout) = &mut self.layout {
+            layout.assign(LayoutColumn::Fixed(column.into()), row);
         }
+
+        Ok(())
     }
 
-    let (mut advice, challenges) = {
-        let num_advice_columns = pk.num_advice_columns();
-        let num_challenges = pk.num_challenges();
-        let mut advice = vec![
-            AdviceSingle {
-                advice_polys: vec![prover.empty_evals(); num_advice_columns],
-                advice_blinds: vec![Blind::default(); num_advice_columns],
-            };
-            instances.len()
-        ];
-        #[cfg(feature = "phase-check")]
-        let mut advice_assignments =
-            vec![vec![prover.empty_rational_evals(); num_advice_columns]; instances.len()];
-        let mut challenges = HashMap::<usize, Scheme::Scalar>::with_capacity(num_challenges);
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        // We only care about advice columns here
 
-        let unusable_rows_start = prover.n() as usize - ((pk.blinding_factors() as usize) + 1);
-        for current_phase in pk.phases() {
-            let column_indices = meta
-                .advice_column_phase
-                .iter()
-                .enumerate()
-                .filter_map(|(column_index, phase)| {
-                    if current_phase == *phase {
-                        Some(column_index)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<BTreeSet<_>>();
+        Ok(())
+    }
 
-            for (_circuit_idx, ((circuit, advice), instances)) in circuits
-                .iter()
-                .zip(advice.iter_mut())
-                .zip(instances)
-                .enumerate()
-            {
-                let mut witness = WitnessCollection {
-                    k: prover.k(),
-                    current_phase,
-                    advice: vec![prover.empty_rational_evals(); num_advice_columns],
-                    instances,
-                    challenges: &challenges,
-                    // The prover will not be allowed to assign values to advice
-                    // cells that exist within inactive rows, which include some
-                    // number of blinding factors and an extra row for use in the
-                    // permutation argument.
-                    usable_rows: ..unusable_rows_start,
-                    _marker: std::marker::PhantomData,
-                };
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
 
-                // Synthesize the circuit to obtain the witness and other information.
-                ConcreteCircuit::FloorPlanner::synthesize(
-                    &mut witness,
-                    circuit,
-                    config.clone(),
-                    pk.constants(),
-                )?;
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.challenges
+            .get(challenge.index())
+            .cloned()
+            .flatten()
+            .map(Value::known)
+            .unwrap_or_else(Value::unknown)
+    }
 
-                #[cfg(feature = "phase-check")]
-                {
-                    let advice_column_phases = pk.advice_column_phases();
-                    for (idx, advice_col) in witness.advice.iter().enumerate() {
-                        if advice_column_phases[idx].0 < current_phase.0 {
-                            if advice_assignments[circuit_idx][idx].values != advice_col.values {
-                                log::error!(
-                                    "advice column {}(at {:?}) changed when {:?}",
-                                    idx,
-                                    advice_column_phases[idx],
-                                    current_phase
-                                );
-                            }
-                        }
-                    }
-                }
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
 
-                let advice_assigned_values = witness
-                    .advice
-                    .into_iter()
-                    .enumerate()
-                    .filter_map(|(column_index, advice)| {
-                        if column_indices.contains(&column_index) {
-                            #[cfg(feature = "phase-check")]
-                            {
-                                advice_assignments[circuit_idx][column_index] = advice.clone();
-                            }
-                            Some(advice)
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>();
-                let mut advice_values = vec![Evals::zero(); advice_assigned_values.len()];
-                prover.batch_evaluate(
-                    advice_assigned_values.as_slice(),
-                    advice_values.as_mut_slice(),
-                );
+    fn pop_namespace(&mut self, _: Option<String>) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+}
 
-                // Add blinding factors to advice columns
-                for advice_values in &mut advice_values {
-                    //for cell in &mut advice_values[unusable_rows_start..] {
-                    //*cell = C::Scalar::random(&mut rng);
-                    //*cell = C::Scalar::one();
-                    //}
-                    let idx = advice_values.len() - 1;
-                    advice_values.set_value(idx, &Fr::one());
-                }
+/// Does the work of [`create_proof_from_prepared_instances()`].
+fn prove_prepared<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+>(
+    prover: &mut P,
+    pk: &mut TachyonProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    prepared: &PreparedInstances<Scheme::Curve>,
+    rng: TachyonXORShiftRng,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    let mut steps = ProofSteps::<Scheme, ConcreteCircuit>::new(pk, circuits.len(), rng, false);
+    steps.run_all(prover, pk, circuits, prepared, transcript, &mut None)
+}
 
-                // Compute commitments to advice column polynomials
-                let blinds: Vec<_> = advice_values
-                    .iter()
-                    .map(|_| Blind(Fr::random(&mut rng)))
-                    .collect();
-                let advice_commitments_projective: Vec<_> = advice_values
-                    .iter()
-                    .zip(blinds.iter())
-                    .map(|(poly, _)| prover.commit_lagrange(poly))
-                    .collect();
-                let mut advice_commitments =
-                    vec![Scheme::Curve::identity(); advice_commitments_projective.len()];
-                <Scheme::Curve as CurveAffine>::CurveExt::batch_normalize(
-                    &advice_commitments_projective,
-                    &mut advice_commitments,
-                );
-                let advice_commitments = advice_commitments;
-                drop(advice_commitments_projective);
+/// Evaluates the assigned values of every column with one
+/// [`TachyonProver::batch_evaluate()`]. Each result stays paired with the index
+/// of its column, so that it can't end up in another one.
+fn batch_evaluate_columns<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    prover: &P,
+    columns: Vec<(ColumnIndex, RationalEvals)>,
+) -> Vec<(ColumnIndex, Evals)> {
+    let (column_indices, rational_evals): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+    let mut evals = vec![Evals::zero(); rational_evals.len()];
+    prover.batch_evaluate(rational_evals.as_slice(), evals.as_mut_slice());
+    for rational_evals in rational_evals {
+        prover.recycle_rational_evals(rational_evals);
+    }
+    column_indices.into_iter().zip(evals).collect()
+}
 
-                for commitment in &advice_commitments {
-                    transcript.write_point(unsafe {
-                        std::mem::transmute::<_, Scheme::Curve>(*commitment)
-                    })?;
+/// Fills the advice columns of `circuit` in `column_indices` from a replayed
+/// witness, see [`ProofSteps::replay_witness()`].
+fn replayed_columns<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    prover: &P,
+    witness: &mut HashMap<(usize, ColumnIndex), AssignedColumn>,
+    circuit: usize,
+    column_indices: &BTreeSet<ColumnIndex>,
+) -> Vec<(ColumnIndex, Evals)> {
+    column_indices
+        .iter()
+        .map(|column_index| {
+            let mut values = prover.empty_evals();
+            match witness.remove(&(circuit, *column_index)) {
+                Some(AssignedColumn::Dense(replayed)) => {
+                    for (row, value) in replayed.iter().enumerate() {
+                        values.set_value(row, value);
+                    }
                 }
-                for ((column_index, advice_values), blind) in
-                    column_indices.iter().zip(advice_values).zip(blinds)
-                {
-                    advice.advice_polys[*column_index] = advice_values;
-                    advice.advice_blinds[*column_index] = blind;
+                Some(AssignedColumn::Sparse(replayed)) => {
+                    for (row, value) in &replayed {
+                        values.set_value(*row, value);
+                    }
                 }
+                None => {}
             }
+            (*column_index, values)
+        })
+        .collect()
+}
 
-            for (index, phase) in pk.challenge_phases().iter().enumerate() {
-                if current_phase == *phase {
-                    let existing =
-                        challenges.insert(index, *transcript.squeeze_challenge_scalar::<()>());
-                    assert!(existing.is_none());
-                }
-            }
-        }
+/// The circuit a replayed witness is proven with. Its config is empty and it
+/// is never synthesized, see [`ProofSteps::replay_witness()`].
+#[derive(Clone, Default)]
+pub(crate) struct WitnessOnly;
 
-        assert_eq!(challenges.len(), num_challenges);
-        let challenges = (0..num_challenges)
-            .map(|index| challenges.remove(&index).unwrap())
-            .collect::<Vec<_>>();
+impl<F: Field> Circuit<F> for WitnessOnly {
+    type Config = ();
+    type FloorPlanner = SimpleFloorPlanner;
 
-        (advice, challenges)
-    };
+    fn without_witnesses(&self) -> Self {
+        WitnessOnly
+    }
 
-    prover.set_rng(rng.state().as_slice());
-    prover.set_transcript(transcript.state().as_slice());
+    fn configure(_: &mut ConstraintSystem<F>) -> Self::Config {}
 
-    let challenges = unsafe { std::mem::transmute::<_, Vec<crate::bn254::Fr>>(challenges) };
-    prover.create_proof(
-        pk,
-        instance.as_mut_slice(),
-        advice.as_mut_slice(),
-        challenges.as_slice(),
-    );
-    Ok(())
+    fn synthesize(&self, _: Self::Config, _: impl Layouter<F>) -> Result<(), Error> {
+        unreachable!("a replayed witness is committed to without synthesis")
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use crate::{
-        bn254::{SHPlonkProver as TachyonSHPlonkProver, TachyonProver},
-        consts::TranscriptType,
-    };
-    use ff::Field;
-    use halo2_proofs::poly::{
-        commitment::{Blind, Params, ParamsProver},
-        kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
-        EvaluationDomain,
-    };
-    use halo2curves::bn256::{Bn256, Fr};
-    use rand_core::OsRng;
+/// A unit of the work [`prove_prepared()`] does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ProofStep {
+    /// Absorbs the verifying key and the instances into the transcript.
+    Begin,
+    /// Synthesizes `circuit` in the phase at `phase`, and commits to the
+    /// advice columns of that phase.
+    Synthesize { phase: usize, circuit: usize },
+    /// Squeezes the challenges of the phase at `phase`.
+    Challenges { phase: usize },
+    /// Hands everything to the backend, which creates the rest of the proof
+    /// in one call.
+    Backend,
+}
 
-    #[test]
-    fn test_params() {
-        let k = 4;
-        const N: u64 = 16;
+/// The state of a proof between its [`ProofStep`]s, so that it can be created
+/// a step at a time, see [`crate::incremental::IncrementalProof`].
+pub(crate) struct ProofSteps<Scheme: CommitmentScheme, ConcreteCircuit: Circuit<Scheme::Scalar>> {
+    steps: Vec<ProofStep>,
+    next: usize,
+    config: ConcreteCircuit::Config,
+    advice_column_phase: Vec<sealed::Phase>,
+    phases: Vec<sealed::Phase>,
+    instance: Vec<InstanceSingle>,
+    advice: Vec<AdviceSingle>,
+    /// Indexed by challenge, filled a phase at a time.
+    challenges: Vec<Option<Scheme::Scalar>>,
+    rng: TachyonXORShiftRng,
+    /// `None` unless [`ProofOptions::column_report`] is set.
+    column_report: Option<Vec<ColumnReport>>,
+    /// `None` unless [`ProofOptions::region_layout`] is set.
+    region_layout: Option<Vec<RegionLayout>>,
+    /// The names of the gates, `None` unless [`ProofOptions::gate_costs`] is
+    /// set.
+    gate_names: Option<Vec<String>>,
+    /// See [`ProofOptions::evaluations`].
+    read_evaluations: bool,
+    /// The evaluated advice columns, kept for
+    /// [`ProofOptions::capture_repro`].
+    captured_witness: Option<Vec<WitnessColumn>>,
+    /// Columns committed to instead of synthesizing the circuits, see
+    /// [`ProofSteps::replay_witness()`] and [`ProofSteps::assign()`].
+    replayed_witness: Option<HashMap<(usize, ColumnIndex), AssignedColumn>>,
+    /// `None` unless [`ProofOptions::self_check`] is set.
+    self_check: Option<(SelfCheck, LagrangeBases)>,
+    /// See [`ProofOptions::precommitted_advice`].
+    precommitted: HashMap<ColumnIndex, PrecommittedColumn>,
+    /// The limits checked before each step and when the proof began.
+    limits: Option<(ResourceLimits, Instant)>,
+    /// See [`ProofOptions::cancel`].
+    cancel: Option<CancelToken>,
+    /// Why the last step failed, if [`Error`] can't hold it.
+    failure: Option<TachyonError>,
+    artifacts: ProofArtifacts,
+    /// The order the key records, see [`ProofOptions::advice_absorb_order`].
+    advice_absorb_order: AbsorbOrder,
+    /// The advice commitments of the current phase with their column and
+    /// circuit, held back with [`AbsorbOrder::GlobalColumn`] until every
+    /// circuit is committed to.
+    pending_commitments: Vec<(ColumnIndex, usize, Scheme::Curve)>,
+    #[cfg(feature = "phase-check")]
+    advice_assignments: Vec<Vec<RationalEvals>>,
+    /// When the first step began, for [`TimedStage::Total`].
+    #[cfg(feature = "stats")]
+    started: Option<Instant>,
+    /// Started when the proof begins, for
+    /// [`crate::metrics::ProofMetrics::peak_host_bytes`] and
+    /// [`crate::metrics::ProofMetrics::peak_device_bytes`].
+    peaks: Option<PeakTracker>,
+}
+
+impl<Scheme: CommitmentScheme, ConcreteCircuit: Circuit<Scheme::Scalar>>
+    ProofSteps<Scheme, ConcreteCircuit>
+{
+    pub(crate) fn new(
+        pk: &TachyonProvingKey<Scheme::Curve>,
+        num_circuits: usize,
+        rng: TachyonXORShiftRng,
+        column_report: bool,
+    ) -> Self {
+        let mut meta = ConstraintSystem::default();
+        let config = ConcreteCircuit::configure(&mut meta);
+
+        // Selector optimizations cannot be applied here; use the ConstraintSystem
+        // from the verification key. The phases are taken from the key too,
+        // since a replayed witness comes without its circuit.
+
+        let phases = pk.phases();
+        let mut steps = vec![ProofStep::Begin];
+        for phase in 0..phases.len() {
+            steps.extend((0..num_circuits).map(|circuit| ProofStep::Synthesize { phase, circuit }));
+            steps.push(ProofStep::Challenges { phase });
+        }
+        steps.push(ProofStep::Backend);
+
+        ProofSteps {
+            steps,
+            next: 0,
+            config,
+            advice_column_phase: pk.advice_column_phases(),
+            phases,
+            instance: vec![],
+            advice: vec![],
+            challenges: vec![None; pk.num_challenges()],
+            rng,
+            column_report: column_report.then(Vec::new),
+            region_layout: None,
+            gate_names: None,
+            read_evaluations: false,
+            captured_witness: None,
+            replayed_witness: None,
+            self_check: None,
+            precommitted: HashMap::new(),
+            limits: None,
+            cancel: None,
+            failure: None,
+            artifacts: ProofArtifacts::default(),
+            advice_absorb_order: pk.advice_absorb_order(),
+            pending_commitments: vec![],
+            #[cfg(feature = "phase-check")]
+            advice_assignments: vec![],
+            #[cfg(feature = "stats")]
+            started: None,
+            peaks: None,
+        }
+    }
+
+    /// Keeps the values of every advice column as evaluated, before the last
+    /// row is blinded, for [`ProofSteps::take_captured_witness()`].
+    pub(crate) fn capture_witness(&mut self) {
+        self.captured_witness = Some(vec![]);
+    }
+
+    pub(crate) fn take_captured_witness(&mut self) -> Vec<WitnessColumn> {
+        self.captured_witness.take().unwrap_or_default()
+    }
+
+    /// Records the regions of every circuit as it is synthesized, see
+    /// [`ProofOptions::region_layout`].
+    pub(crate) fn capture_layout(&mut self) {
+        self.region_layout = Some(vec![]);
+    }
+
+    /// Names the costs of the gates the backend records, see
+    /// [`ProofOptions::gate_costs`].
+    pub(crate) fn record_gate_costs(&mut self) {
+        let mut meta = ConstraintSystem::default();
+        ConcreteCircuit::configure(&mut meta);
+        self.gate_names = Some(
+            meta.gates()
+                .iter()
+                .map(|gate| gate.name().to_string())
+                .collect(),
+        );
+    }
+
+    /// Reads the evaluations the proof claims back into its artifacts, see
+    /// [`ProofOptions::evaluations`].
+    pub(crate) fn read_evaluations(&mut self) {
+        self.read_evaluations = true;
+    }
+
+    /// Recomputes the advice commitments `check` selects with `bases` before
+    /// they are written to the transcript, see [`ProofOptions::self_check`].
+    pub(crate) fn self_check(&mut self, check: SelfCheck, bases: LagrangeBases) {
+        self.self_check = Some((check, bases));
+    }
+
+    /// Commits to `columns` of the first circuit with their given commitments
+    /// and blinds, see [`ProofOptions::precommitted_advice`].
+    pub(crate) fn precommit(&mut self, columns: &[PrecommittedColumn]) {
+        self.precommitted = columns
+            .iter()
+            .map(|column| (column.column_index.index(), column.clone()))
+            .collect();
+    }
+
+    /// Checks `limits` for a proof that began at `start` before each step,
+    /// see [`ProofOptions::limits`].
+    pub(crate) fn limit(&mut self, limits: ResourceLimits, start: Instant) {
+        self.limits = Some((limits, start));
+    }
+
+    /// Stops before the next step once `cancel` is cancelled, see
+    /// [`ProofOptions::cancel`].
+    pub(crate) fn cancel_on(&mut self, cancel: Option<CancelToken>) {
+        self.cancel = cancel;
+    }
+
+    /// Converts an error of a step, which is what the step left in
+    /// `failure` if anything, or else an overflow if `overflow` holds one.
+    pub(crate) fn error(&mut self, e: Error, overflow: Option<RowOverflow>) -> TachyonError {
+        match self.failure.take() {
+            Some(failure) => failure,
+            None => with_overflow(e, overflow),
+        }
+    }
+
+    /// What the finished proof was created from.
+    pub(crate) fn take_artifacts(&mut self) -> ProofArtifacts {
+        std::mem::take(&mut self.artifacts)
+    }
+
+    /// Commits to `columns` instead of synthesizing the circuits, which are
+    /// then never called. A column that isn't given is all zeros. Together
+    /// with the RNG and the instances of the original proof, this recreates
+    /// it, since later phases depend on the transcript only through the
+    /// challenges, which come out the same.
+    pub(crate) fn replay_witness(&mut self, columns: Vec<WitnessColumn>) {
+        self.replayed_witness = Some(
+            columns
+                .into_iter()
+                .map(|column| {
+                    (
+                        (column.circuit, column.column),
+                        AssignedColumn::Dense(column.values),
+                    )
+                })
+                .collect(),
+        );
+    }
+
+    /// Adds `columns`, by circuit and column, to the ones committed to
+    /// instead of synthesizing the circuits. Given before each phase, they
+    /// can depend on the challenges squeezed so far, see
+    /// [`crate::assignments::create_proof_from_assignments()`].
+    pub(crate) fn assign(&mut self, columns: HashMap<(usize, ColumnIndex), AssignedColumn>) {
+        self.replayed_witness
+            .get_or_insert_with(HashMap::new)
+            .extend(columns);
+    }
+
+    /// Returns the phase and the circuit the next step synthesizes, if it is
+    /// a synthesis.
+    pub(crate) fn next_synthesis(&self) -> Option<(usize, usize)> {
+        match self.steps.get(self.next) {
+            Some(ProofStep::Synthesize { phase, circuit }) => Some((*phase, *circuit)),
+            _ => None,
+        }
+    }
+
+    /// Returns the challenges, by index, with zero for the ones that aren't
+    /// squeezed yet.
+    pub(crate) fn challenges(&self) -> Vec<Fr> {
+        self.challenges
+            .iter()
+            .map(|challenge| match challenge {
+                Some(challenge) => unsafe { *std::mem::transmute::<_, &Fr>(challenge) },
+                None => Fr::zero(),
+            })
+            .collect()
+    }
+
+    /// Returns the number of steps in total.
+    pub(crate) fn num_steps(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns the number of steps run so far.
+    pub(crate) fn num_finished(&self) -> usize {
+        self.next
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.next == self.steps.len()
+    }
+
+    /// Runs the remaining steps.
+    pub(crate) fn run_all<
+        P: TachyonProver<Scheme>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptWriteState<Scheme::Curve, E>,
+    >(
+        &mut self,
+        prover: &mut P,
+        pk: &mut TachyonProvingKey<Scheme::Curve>,
+        circuits: &[ConcreteCircuit],
+        prepared: &PreparedInstances<Scheme::Curve>,
+        transcript: &mut T,
+        overflow: &mut Option<RowOverflow>,
+    ) -> Result<(), Error> {
+        while !self.is_finished() {
+            self.run_next(prover, pk, circuits, prepared, transcript, overflow)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the next step. It must be given the same arguments every time.
+    pub(crate) fn run_next<
+        P: TachyonProver<Scheme>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptWriteState<Scheme::Curve, E>,
+    >(
+        &mut self,
+        prover: &mut P,
+        pk: &mut TachyonProvingKey<Scheme::Curve>,
+        circuits: &[ConcreteCircuit],
+        prepared: &PreparedInstances<Scheme::Curve>,
+        transcript: &mut T,
+        overflow: &mut Option<RowOverflow>,
+    ) -> Result<(), Error> {
+        if let Some((limits, start)) = &self.limits {
+            if let Err(e) = limits.check_during(*start) {
+                self.failure = Some(e);
+                return Err(Error::Synthesis);
+            }
+        }
+        if self
+            .cancel
+            .as_ref()
+            .map_or(false, CancelToken::is_cancelled)
+        {
+            self.failure = Some(TachyonError::Cancelled);
+            return Err(Error::Synthesis);
+        }
+        let step = self.steps[self.next];
+        self.next += 1;
+        #[cfg(feature = "stats")]
+        let step_start = Instant::now();
+        let result = match step {
+            ProofStep::Begin => self.begin(prover, pk, circuits.len(), prepared, transcript),
+            ProofStep::Synthesize { phase, circuit } => self.synthesize(
+                prover,
+                pk,
+                phase,
+                circuit,
+                &circuits[circuit],
+                prepared,
+                transcript,
+                overflow,
+            ),
+            ProofStep::Challenges { phase } => self.squeeze_challenges(pk, phase, transcript),
+            ProofStep::Backend => self.finish(prover, pk, transcript),
+        };
+        #[cfg(feature = "stats")]
+        if result.is_ok() {
+            self.record_stats(prover, step, step_start);
+        }
+        result
+    }
+
+    /// Records the duration of `step`, which began at `start`, and once the
+    /// backend is done, the duration and the size of the whole proof.
+    #[cfg(feature = "stats")]
+    fn record_stats<P: TachyonProver<Scheme>>(
+        &mut self,
+        prover: &P,
+        step: ProofStep,
+        start: Instant,
+    ) {
+        let stats = prover.stats();
+        let started = *self.started.get_or_insert(start);
+        let stage = match step {
+            ProofStep::Begin => TimedStage::Begin,
+            ProofStep::Synthesize { .. } => TimedStage::Synthesize,
+            ProofStep::Challenges { .. } => TimedStage::Challenges,
+            ProofStep::Backend => TimedStage::Backend,
+        };
+        stats.record_stage(stage, start.elapsed());
+        if stage == TimedStage::Backend {
+            stats.record_stage(TimedStage::Total, started.elapsed());
+            stats.record_proof_bytes(prover.get_proof().len());
+        }
+    }
+
+    fn begin<
+        P: TachyonProver<Scheme>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptWriteState<Scheme::Curve, E>,
+    >(
+        &mut self,
+        prover: &mut P,
+        pk: &mut TachyonProvingKey<Scheme::Curve>,
+        num_circuits: usize,
+        prepared: &PreparedInstances<Scheme::Curve>,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        // Every circuit is synthesized with the instances at its index.
+        if prepared.singles.len() != num_circuits {
+            return Err(Error::InvalidInstances);
+        }
+        if prepared.query_instance != prover.query_instance() {
+            // Only GWC queries the instance columns.
+            let prepared_for = if prepared.query_instance {
+                MultiOpenScheme::Gwc
+            } else {
+                MultiOpenScheme::SHPlonk
+            };
+            self.failure = Some(TachyonError::MultiOpenMismatch {
+                key: prepared_for,
+                prover: prover.multi_open_scheme(),
+            });
+            return Err(Error::InvalidInstances);
+        }
+
+        self.peaks = Some(PeakTracker::start());
+        absorb_statement(prover, pk, prepared, transcript)?;
+        // The backend takes ownership of the buffers, so the prepared ones are
+        // copied.
+        self.instance = prepared.singles.clone();
+
+        let num_advice_columns = pk.num_advice_columns();
+        self.advice = vec![
+            AdviceSingle {
+                advice_polys: vec![prover.empty_evals(); num_advice_columns],
+                advice_blinds: vec![Blind::default(); num_advice_columns],
+            };
+            num_circuits
+        ];
+        #[cfg(feature = "phase-check")]
+        {
+            self.advice_assignments =
+                vec![vec![prover.empty_rational_evals(); num_advice_columns]; num_circuits];
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn synthesize<
+        P: TachyonProver<Scheme>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptWriteState<Scheme::Curve, E>,
+    >(
+        &mut self,
+        prover: &mut P,
+        pk: &TachyonProvingKey<Scheme::Curve>,
+        phase_index: usize,
+        circuit_idx: usize,
+        circuit: &ConcreteCircuit,
+        prepared: &PreparedInstances<Scheme::Curve>,
+        transcript: &mut T,
+        overflow: &mut Option<RowOverflow>,
+    ) -> Result<(), Error> {
+        let current_phase = self.phases[phase_index];
+        let column_indices = self
+            .advice_column_phase
+            .iter()
+            .enumerate()
+            .filter_map(|(column_index, phase)| {
+                if current_phase == *phase {
+                    Some(column_index)
+                } else {
+                    None
+                }
+            })
+            .collect::<BTreeSet<_>>();
+        let mut advice_values = match self.replayed_witness.as_mut() {
+            Some(witness) => replayed_columns(prover, witness, circuit_idx, &column_indices),
+            None => self.synthesize_circuit(
+                prover,
+                pk,
+                current_phase,
+                circuit_idx,
+                circuit,
+                prepared,
+                &column_indices,
+                overflow,
+            )?,
+        };
+        for (column_index, values) in &mut advice_values {
+            if let Some(column) = self.precommitted.get(column_index) {
+                prover.recycle_evals(std::mem::replace(values, column.evals.clone()));
+            }
+        }
+        if let Some(captured) = &mut self.captured_witness {
+            captured.extend(par::map(&advice_values, |(column_index, values)| {
+                WitnessColumn {
+                    circuit: circuit_idx,
+                    column: *column_index,
+                    values: (0..values.len()).map(|row| values.get_value(row)).collect(),
+                }
+            }));
+        }
+        if let Some(report) = &mut self.column_report {
+            report.extend(
+                advice_values
+                    .iter()
+                    .map(|(column_index, values)| ColumnReport {
+                        circuit: circuit_idx,
+                        column: *column_index,
+                        stats: prover.column_stats(values),
+                    }),
+            );
+        }
+
+        // Add blinding factors to advice columns
+        for (column_index, advice_values) in &mut advice_values {
+            if self.precommitted.contains_key(column_index) {
+                continue;
+            }
+            //for cell in &mut advice_values[unusable_rows_start..] {
+            //*cell = C::Scalar::random(&mut rng);
+            //*cell = C::Scalar::one();
+            //}
+            let idx = advice_values.len() - 1;
+            advice_values.set_value(idx, &Fr::one());
+        }
+
+        // Compute commitments to advice column polynomials
+        let precommitted = &self.precommitted;
+        let advice_values: Vec<_> = advice_values
+            .into_iter()
+            .map(|(column_index, values)| {
+                let blind = match precommitted.get(&column_index) {
+                    Some(column) => column.blind,
+                    None => Fr::random(&mut self.rng),
+                };
+                (column_index, values, Blind(blind))
+            })
+            .collect();
+        // Precommitted columns are left at the identity and filled in after.
+        let committed: Vec<_> = advice_values
+            .iter()
+            .filter(|(column_index, _, _)| !precommitted.contains_key(column_index))
+            .map(|(_, poly, _)| poly)
+            .collect();
+        let mut commitments = prover.commit_lagrange_columns(&committed).into_iter();
+        let advice_commitments_projective: Vec<_> = advice_values
+            .iter()
+            .map(|(column_index, _, _)| {
+                if precommitted.contains_key(column_index) {
+                    <Scheme::Curve as CurveAffine>::CurveExt::identity()
+                } else {
+                    commitments.next().unwrap()
+                }
+            })
+            .collect();
+        let mut advice_commitments =
+            vec![Scheme::Curve::identity(); advice_commitments_projective.len()];
+        <Scheme::Curve as CurveAffine>::CurveExt::batch_normalize(
+            &advice_commitments_projective,
+            &mut advice_commitments,
+        );
+        drop(advice_commitments_projective);
+        for ((column_index, _, _), commitment) in advice_values.iter().zip(&mut advice_commitments)
+        {
+            if let Some(column) = precommitted.get(column_index) {
+                *commitment =
+                    unsafe { *std::mem::transmute::<_, &Scheme::Curve>(&column.commitment) };
+            }
+        }
+        #[cfg(test)]
+        crate::self_check::inject_corruption(
+            &advice_values
+                .iter()
+                .map(|(column_index, _, _)| *column_index)
+                .collect::<Vec<_>>(),
+            unsafe { std::mem::transmute::<_, &mut [G1Affine]>(advice_commitments.as_mut_slice()) },
+        );
+        let advice_commitments = advice_commitments;
+
+        if let Some((check, bases)) = &self.self_check {
+            for position in check.select(advice_values.len()) {
+                let (column_index, values, _) = &advice_values[position];
+                let commitment =
+                    unsafe { std::mem::transmute::<_, &G1Affine>(&advice_commitments[position]) };
+                if bases.commit(values) != *commitment {
+                    log::error!(
+                        "the commitment to advice column {} of circuit {} differs from the CPU's",
+                        column_index,
+                        circuit_idx
+                    );
+                    self.failure = Some(TachyonError::SelfCheckFailed {
+                        column: *column_index,
+                    });
+                    return Err(Error::Synthesis);
+                }
+            }
+        }
+
+        match self.advice_absorb_order {
+            AbsorbOrder::PhaseThenColumn => transcript.write_points(&advice_commitments)?,
+            AbsorbOrder::GlobalColumn => {
+                self.pending_commitments
+                    .extend(advice_values.iter().zip(&advice_commitments).map(
+                        |((column_index, _, _), commitment)| {
+                            (*column_index, circuit_idx, *commitment)
+                        },
+                    ))
+            }
+        }
+        let advice = &mut self.advice[circuit_idx];
+        for (column_index, values, blind) in advice_values {
+            advice.advice_polys[column_index] = values;
+            advice.advice_blinds[column_index] = blind;
+        }
+        Ok(())
+    }
+
+    /// Synthesizes `circuit` and evaluates its advice columns of
+    /// `current_phase`, whose indices are `column_indices`.
+    #[allow(clippy::too_many_arguments)]
+    fn synthesize_circuit<P: TachyonProver<Scheme>>(
+        &mut self,
+        prover: &mut P,
+        pk: &TachyonProvingKey<Scheme::Curve>,
+        current_phase: sealed::Phase,
+        circuit_idx: usize,
+        circuit: &ConcreteCircuit,
+        prepared: &PreparedInstances<Scheme::Curve>,
+        column_indices: &BTreeSet<ColumnIndex>,
+        overflow: &mut Option<RowOverflow>,
+    ) -> Result<Vec<(ColumnIndex, Evals)>, Error> {
+        let num_advice_columns = pk.num_advice_columns();
+        let unusable_rows_start = prover.n() as usize - ((pk.blinding_factors() as usize) + 1);
+        let empty_rational_evals = || prover.empty_rational_evals();
+        let mut witness = WitnessCollection {
+            k: prover.k(),
+            current_phase,
+            advice: (0..num_advice_columns).map(|_| None).collect(),
+            empty_rational_evals: &empty_rational_evals,
+            instances: &prepared.singles[circuit_idx].instance_values,
+            instance_lens: &prepared.column_lens[circuit_idx],
+            challenges: &self.challenges,
+            // The prover will not be allowed to assign values to advice
+            // cells that exist within inactive rows, which include some
+            // number of blinding factors and an extra row for use in the
+            // permutation argument.
+            usable_rows: ..unusable_rows_start,
+            metadata: pk.metadata(),
+            annotations: ColumnAnnotations::new(),
+            overflow: None,
+            layout: (self.region_layout.is_some() && self.phases.first() == Some(&current_phase))
+                .then(RegionRecorder::default),
+            _marker: std::marker::PhantomData,
+        };
+
+        // Synthesize the circuit to obtain the witness and other information.
+        if let Err(e) = ConcreteCircuit::FloorPlanner::synthesize(
+            &mut witness,
+            circuit,
+            self.config.clone(),
+            pk.constants(),
+        ) {
+            *overflow = witness.overflow.take();
+            return Err(e);
+        }
+        if let (Some(layouts), Some(layout)) = (&mut self.region_layout, witness.layout.take()) {
+            layouts.push(layout.finish(circuit_idx, witness.usable_rows.end));
+        }
+
+        #[cfg(feature = "phase-check")]
+        {
+            let advice_column_phases = pk.advice_column_phases();
+            for (idx, advice_col) in witness.advice.iter().enumerate() {
+                if advice_column_phases[idx].0 < current_phase.0 {
+                    if self.advice_assignments[circuit_idx][idx].values != advice_col.values {
+                        log::error!(
+                            "advice column {}(at {:?}) changed when {:?}",
+                            idx,
+                            advice_column_phases[idx],
+                            current_phase
+                        );
+                    }
+                }
+            }
+        }
+
+        // `column_indices` is ascending, which is the order the verifier reads
+        // the commitments of a phase in.
+        let advice_assigned_values = column_indices
+            .iter()
+            .map(|column_index| {
+                let advice = witness.advice[*column_index]
+                    .take()
+                    .unwrap_or_else(|| prover.empty_rational_evals());
+                #[cfg(feature = "phase-check")]
+                {
+                    self.advice_assignments[circuit_idx][*column_index] = advice.clone();
+                }
+                (*column_index, advice)
+            })
+            .collect::<Vec<_>>();
+        Ok(batch_evaluate_columns(prover, advice_assigned_values))
+    }
+
+    fn squeeze_challenges<
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptWriteState<Scheme::Curve, E>,
+    >(
+        &mut self,
+        pk: &TachyonProvingKey<Scheme::Curve>,
+        phase_index: usize,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        // Every circuit is committed to by now, so the commitments held back
+        // can be written column by column.
+        self.pending_commitments
+            .sort_by_key(|(column_index, circuit_idx, _)| (*column_index, *circuit_idx));
+        let commitments: Vec<_> = self
+            .pending_commitments
+            .drain(..)
+            .map(|(_, _, commitment)| commitment)
+            .collect();
+        transcript.write_points(&commitments)?;
+        let current_phase = self.phases[phase_index];
+        for (index, phase) in pk.challenge_phases().iter().enumerate() {
+            if current_phase == *phase {
+                let challenge = *transcript.squeeze_challenge_scalar::<()>();
+                if self.challenges[index].replace(challenge).is_some() {
+                    self.failure = Some(TachyonError::Challenge(ChallengeError::SqueezedTwice(
+                        ChallengeId(index),
+                    )));
+                    return Err(Error::Synthesis);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish<
+        P: TachyonProver<Scheme>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptWriteState<Scheme::Curve, E>,
+    >(
+        &mut self,
+        prover: &mut P,
+        pk: &mut TachyonProvingKey<Scheme::Curve>,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        let mut challenges = Vec::with_capacity(self.challenges.len());
+        for (index, challenge) in self.challenges.iter().enumerate() {
+            match challenge {
+                Some(challenge) => challenges.push(*challenge),
+                None => {
+                    self.failure = Some(TachyonError::Challenge(ChallengeError::NotSqueezed(
+                        ChallengeId(index),
+                    )));
+                    return Err(Error::Synthesis);
+                }
+            }
+        }
+        self.artifacts.challenges =
+            unsafe { std::mem::transmute::<_, &[Fr]>(challenges.as_slice()) }.to_vec();
+
+        if let Err(e) = prover
+            .set_rng(self.rng.state().as_slice())
+            .and_then(|()| prover.set_transcript(transcript.state().as_slice()))
+        {
+            self.failure = Some(e);
+            return Err(Error::Synthesis);
+        }
+
+        let challenges = unsafe { std::mem::transmute::<_, Vec<crate::bn254::Fr>>(challenges) };
+        prover.create_proof(
+            pk,
+            self.instance.as_mut_slice(),
+            self.advice.as_mut_slice(),
+            challenges.as_slice(),
+        );
+        if self.read_evaluations {
+            // The proof is already created; failing to read it back only
+            // leaves the evaluations out.
+            let queries = opening_queries(pk, self.advice.len(), prover.multi_open_scheme());
+            self.artifacts.evaluations = read_evaluations(
+                &queries,
+                prover.multi_open_scheme(),
+                PointEncoding::for_transcript(&T::TRANSCRIPT_TYPE),
+                &prover.get_proof(),
+            )
+            .unwrap_or_default();
+        }
+        if let Some(mut peaks) = self.peaks.take() {
+            let metrics = prover.metrics_mut();
+            metrics.peak_host_bytes = peaks.host_bytes();
+            metrics.peak_device_bytes = peaks.device_bytes();
+        }
+        if let Some(mut report) = self.column_report.take() {
+            // The columns were reported a phase at a time.
+            report.sort_by_key(|column| (column.circuit, column.column));
+            prover.metrics_mut().column_report = report;
+        }
+        if let Some(layouts) = self.region_layout.take() {
+            prover.metrics_mut().region_layout = layouts;
+        }
+        if let Some(names) = self.gate_names.take() {
+            prover.metrics_mut().gate_costs = gate_costs(names, prover.gate_costs());
+        }
+        Ok(())
+    }
+}
+
+// Names the costs the backend reports by gate index. Selector compression
+// keeps the gates of the key in the order the circuit configured them.
+fn gate_costs(names: Vec<String>, costs: Vec<(Duration, u64)>) -> Vec<GateCost> {
+    names
+        .into_iter()
+        .zip(costs)
+        .enumerate()
+        .map(|(gate, (name, (duration, ops)))| GateCost {
+            gate,
+            name: if name.is_empty() {
+                gate.to_string()
+            } else {
+                name
+            },
+            duration,
+            ops,
+        })
+        .collect()
+}
+
+/// The prover of a proof in progress, such as an
+/// [`crate::incremental::IncrementalProof`]. It borrows the prover exclusively
+/// until the proof is finished or dropped, so the prover can't start another
+/// proof in between or be reached from another thread, and only hands out
+/// shared references to anyone but the proof itself.
+///
+/// Provers are `Send` but not `Sync`, so the guard, and the proof holding it,
+/// can move to another thread between steps, but not be shared.
+pub struct ProofGuard<'a, P> {
+    prover: &'a mut P,
+}
+
+impl<'a, P: fmt::Debug> fmt::Debug for ProofGuard<'a, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ProofGuard").field(&self.prover).finish()
+    }
+}
+
+impl<'a, P> ProofGuard<'a, P> {
+    pub(crate) fn new(prover: &'a mut P) -> Self {
+        ProofGuard { prover }
+    }
+
+    pub(crate) fn prover_mut(&mut self) -> &mut P {
+        self.prover
+    }
+}
+
+impl<'a, P> Deref for ProofGuard<'a, P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        self.prover
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProofOptions {
+    /// Identifies the proof, e.g, with [`crate::rng::proof_context()`]. The
+    /// RNG is seeded with [`crate::rng::derive_seed()`] from the prover's master
+    /// key and this.
+    pub rng_context: Vec<u8>,
+    /// Check every gate and lookup with [`crate::debug::verify_constraints()`]
+    /// before proving, and fail with [`TachyonError::Preflight`] instead of
+    /// creating a proof that doesn't verify. This synthesizes each circuit
+    /// once more.
+    pub verify_constraints_first: bool,
+    /// Write every transcript operation of the proof to this file as JSONL,
+    /// see [`crate::audit::AuditEntry::to_json_line()`]. The backend squeezes
+    /// its challenges internally, so only the values it writes are logged for
+    /// it.
+    pub audit_log: Option<PathBuf>,
+    /// Splits and labels the backend's values in the audit log.
+    pub audit_layout: Option<AuditLayout>,
+    /// How many of the backend's lookup and permutation polynomials are
+    /// computed at once, on as many threads. Their blinding factors are
+    /// still drawn in order, so the proof is the same for any value. 0 and 1
+    /// compute them one by one.
+    pub intra_proof_parallelism: usize,
+    /// How many threads the backend's parallel loops run on, or 0 to leave
+    /// it to OpenMP, e.g, to `OMP_NUM_THREADS`. The proof is the same for any
+    /// value.
+    pub num_threads: usize,
+    /// Compute the [`crate::metrics::ColumnStats`] of every advice column
+    /// into [`crate::metrics::ProofMetrics::column_report`]. The backend
+    /// interpolates each column once more for it, but only the statistics
+    /// are copied back.
+    pub column_report: bool,
+    /// Record where the floor planner placed every region of every circuit
+    /// into [`crate::metrics::ProofMetrics::region_layout`], to draw it with
+    /// [`RegionLayout::to_svg()`]. Nothing is recorded otherwise.
+    pub region_layout: bool,
+    /// Record what the backend spends evaluating every custom gate for the
+    /// quotient into [`crate::metrics::ProofMetrics::gate_costs`], to find
+    /// the gates worth optimizing. The backend times every 64th row, which
+    /// costs a few percent of the quotient. Nothing is timed otherwise.
+    pub gate_costs: bool,
+    /// Write everything needed to create the proof again without the circuit
+    /// to a file, see [`crate::repro`].
+    pub capture_repro: Option<ReproCapture>,
+    /// Recompute advice commitments on the CPU and fail with
+    /// [`TachyonError::SelfCheckFailed`] if one differs from the backend's,
+    /// before it is written to the transcript. Needs `self_check_bases`.
+    pub self_check: SelfCheck,
+    /// The Lagrange bases of the prover's SRS for `self_check`.
+    pub self_check_bases: Option<LagrangeBases>,
+    /// Advice columns committed to before the proof, whose commitments are
+    /// written to the transcript as given instead of being computed. The
+    /// circuit's assignments to them are replaced with their evals. Only a
+    /// proof of a single circuit can have any.
+    pub precommitted_advice: Vec<PrecommittedColumn>,
+    /// Recommit to every precommitted column before proving, and fail with
+    /// [`TachyonError::PrecommitmentMismatch`] if a commitment isn't the one to
+    /// its evals. Otherwise such a proof just doesn't verify.
+    pub check_precommitted_advice: bool,
+    /// Fail with [`TachyonError::LimitExceeded`] instead of exceeding these.
+    /// No limit is set by default.
+    pub limits: ResourceLimits,
+    /// Fail with [`TachyonError::Cancelled`] once this is cancelled. It is
+    /// checked between the steps of the proof, so the backend's part, which
+    /// is a single call, still runs to the end.
+    pub cancel: Option<CancelToken>,
+    /// Which of the backend's stages run on the GPU. The proof is the same
+    /// for any placement. Placing a stage on the GPU without the "gpu"
+    /// feature fails with [`TachyonError::BackendUnavailable`].
+    pub stage_placement: StagePlacement,
+    /// How the prover gets the Lagrange bases it commits with, see
+    /// [`TachyonProver::set_lagrange_basis_policy()`]. The proof is the same
+    /// for any policy.
+    pub lagrange_bases: LagrangeBasisPolicy,
+    /// The order the advice commitments are written to the transcript in,
+    /// which has to be the one `pk` records, see
+    /// [`crate::proving_key::advice_absorb_order_to_metadata()`]. Otherwise
+    /// the proof fails with [`TachyonError::AdviceAbsorbOrderMismatch`]. The
+    /// columns are committed to in the same order either way.
+    pub advice_absorb_order: AbsorbOrder,
+    /// The log₂ of the size of the IFFTs the backend splits the IFFT of the
+    /// extended domain into, so that only one of them has to be on the device
+    /// at once. `Some(0)` runs it in one piece. By default, it is split only
+    /// if that is what it takes to fit in
+    /// [`ResourceLimits::max_device_bytes`], see
+    /// [`crate::estimate::fft_chunk_log2_for_budget()`]. The proof is the same
+    /// for any value.
+    pub fft_chunk_log2: Option<u32>,
+    /// When the caller queued the proof, e.g, when its request came in. With
+    /// the "stats" feature, the time from then until the proof starts is
+    /// recorded as its queue wait, see
+    /// [`crate::bn254::TachyonProver::stats_snapshot()`].
+    pub enqueued_at: Option<Instant>,
+    /// Log the backend's verbose messages up to this level while the proof
+    /// runs, e.g, to trace a single slow proof, and the previous level again
+    /// after. The level is process-wide, so it also applies to proofs
+    /// running at the same time. The messages go where
+    /// [`crate::version::InitOptions::backend_log`] sends them.
+    pub backend_log_verbosity: Option<i32>,
+    /// Read the evaluations the proof claims back from it into
+    /// [`ProofArtifacts::evaluations`]. They are left empty otherwise, and
+    /// also if the proof can't be read; the proof itself is the same either
+    /// way.
+    pub evaluations: bool,
+}
+
+/// What a proof was created from besides its transcript, returned by
+/// [`create_proof_with_options()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofArtifacts {
+    /// The challenges in the order of `Challenge::index()`, which is the
+    /// order the circuit declared them in, whatever their phases.
+    pub challenges: Vec<Fr>,
+    /// The evaluation the proof claims for every query the multi-open
+    /// argument opens, in the order of [`crate::opening::opening_queries()`].
+    /// The quotient is left out; its evaluation isn't written to the proof.
+    /// Empty unless [`ProofOptions::evaluations`] is set.
+    pub evaluations: Vec<(OpeningQuery, Fr)>,
+}
+
+impl ProofArtifacts {
+    /// The evaluation the proof claims for `query`, if it writes one.
+    pub fn evaluation(&self, query: &OpeningQuery) -> Option<Fr> {
+        self.evaluations
+            .iter()
+            .find(|(opened, _)| opened == query)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// Resolves [`ProofOptions::fft_chunk_log2`] for a proof of `num_circuits`
+/// circuits with `prover` and `pk`.
+pub(crate) fn fft_chunk_log2<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    prover: &P,
+    pk: &TachyonProvingKey<Scheme::Curve>,
+    num_circuits: usize,
+    options: &ProofOptions,
+) -> u32 {
+    options.fft_chunk_log2.unwrap_or_else(|| {
+        fft_chunk_log2_for_budget(
+            pk,
+            prover,
+            &EstimateOptions {
+                num_circuits,
+                ..Default::default()
+            },
+            options.limits.max_device_bytes,
+        )
+    })
+}
+
+pub(crate) fn with_overflow(e: Error, overflow: Option<RowOverflow>) -> TachyonError {
+    overflow.map_or_else(|| e.into(), TachyonError::RowOverflow)
+}
+
+/// Fails if `pk` records a multi-open scheme other than the one of `prover`.
+/// A key without one is accepted.
+pub(crate) fn check_multi_open_scheme<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    prover: &P,
+    pk: &TachyonProvingKey<Scheme::Curve>,
+) -> Result<(), TachyonError> {
+    match pk.multi_open_scheme() {
+        Some(key) if key != prover.multi_open_scheme() => Err(TachyonError::MultiOpenMismatch {
+            key,
+            prover: prover.multi_open_scheme(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Fails with [`TachyonError::AdviceAbsorbOrderMismatch`] if `pk` records
+/// another order than [`ProofOptions::advice_absorb_order`].
+pub(crate) fn check_advice_absorb_order<C: CurveAffine>(
+    pk: &TachyonProvingKey<C>,
+    options: &ProofOptions,
+) -> Result<(), TachyonError> {
+    let key = pk.advice_absorb_order();
+    if key != options.advice_absorb_order {
+        return Err(TachyonError::AdviceAbsorbOrderMismatch {
+            key,
+            configured: options.advice_absorb_order,
+        });
+    }
+    Ok(())
+}
+
+/// Fails with [`TachyonError::TranscriptMismatch`] if `transcript` is of
+/// another type than `prover` was created with.
+pub(crate) fn check_transcript_type<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E>,
+>(
+    prover: &P,
+    _transcript: &T,
+) -> Result<(), TachyonError> {
+    let transcript_type = prover.transcript_type();
+    let prover = TranscriptType::from_u8(transcript_type)
+        .ok_or(StateError::UnknownTranscript(transcript_type))?;
+    if prover != T::TRANSCRIPT_TYPE {
+        return Err(TachyonError::TranscriptMismatch {
+            prover,
+            writer: T::TRANSCRIPT_TYPE,
+        });
+    }
+    Ok(())
+}
+
+/// Same as [`create_proof()`], but seeds the RNG from `prover.master_key()` and
+/// `options.rng_context`, so that the same context always reproduces the same
+/// proof. If the circuit assigns a cell past the usable rows, this fails with
+/// [`TachyonError::RowOverflow`], which names the column, and if `pk` was
+/// written for another multi-open scheme, with
+/// [`TachyonError::MultiOpenMismatch`]. A transcript of another type than the
+/// prover's fails with [`TachyonError::TranscriptMismatch`] before anything is
+/// absorbed. A key whose challenges aren't each squeezed in exactly one phase
+/// fails with [`TachyonError::Challenge`].
+///
+/// The proof is meant to be a function of `pk`, the witness, the instances,
+/// the master key and `options.rng_context` alone: every value is computed
+/// exactly, the blinding factors are drawn in a fixed order, and the
+/// transcript is written in the order of the columns. What is tested is that
+/// it is the same byte for byte on x86-64 for any
+/// [`ProofOptions::num_threads`] and [`ProofOptions::intra_proof_parallelism`],
+/// with CPU [`ProofOptions::stage_placement`], with or without the "rayon"
+/// feature. Other platforms, GPU placement on a device,
+/// [`ProofOptions::fft_chunk_log2`] and [`ProofOptions::lagrange_bases`]
+/// aren't covered by a test.
+pub fn create_proof_with_options<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+>(
+    prover: &mut P,
+    pk: &mut TachyonProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[Scheme::Scalar]]],
+    options: &ProofOptions,
+    transcript: &mut T,
+) -> Result<ProofArtifacts, TachyonError> {
+    let start = Instant::now();
+    #[cfg(feature = "stats")]
+    if let Some(enqueued_at) = options.enqueued_at {
+        prover.stats().record_queue_wait(
+            start
+                .checked_duration_since(enqueued_at)
+                .unwrap_or_default(),
+        );
+    }
+    check_backend()?;
+    let _verbosity = options.backend_log_verbosity.map(VerbosityOverride::new);
+    check_transcript_type(prover, transcript)?;
+    check_multi_open_scheme(prover, pk)?;
+    check_advice_absorb_order(pk, options)?;
+    let fft_chunk_log2 = fft_chunk_log2(prover, pk, circuits.len(), options);
+    options
+        .limits
+        .check_before(prover, pk, circuits.len(), fft_chunk_log2)?;
+    if options.verify_constraints_first {
+        for (circuit, instances) in circuits.iter().zip(instances) {
+            verify_constraints(prover.k(), circuit, instances)?;
+        }
+    }
+    let self_check = self_check_bases(options, prover.n())?;
+    check_precommitted_advice(prover, pk, circuits.len(), options)?;
+    let gpu_stages = options.stage_placement.gpu_stages(prover.k())?;
+    let master_key = prover.master_key().ok_or(TachyonError::MissingMasterKey)?;
+    let seed = derive_seed(master_key, &options.rng_context);
+    let rng = TachyonXORShiftRng::from_seed(seed);
+    prover.set_intra_proof_parallelism(options.intra_proof_parallelism.max(1));
+    prover.set_num_threads(options.num_threads);
+    prover.set_record_gate_costs(options.gate_costs);
+    prover.set_gpu_stages(gpu_stages);
+    prover.set_fft_chunk_log2(fft_chunk_log2);
+    prover.set_lagrange_basis_policy(options.lagrange_bases)?;
+    let prepared = prepare_instances(prover, pk, instances)?;
+    let mut overflow = None;
+    let mut steps =
+        ProofSteps::<Scheme, ConcreteCircuit>::new(pk, circuits.len(), rng, options.column_report);
+    if let Some(bases) = self_check {
+        steps.self_check(options.self_check, bases);
+    }
+    if options.region_layout {
+        steps.capture_layout();
+    }
+    if options.gate_costs {
+        steps.record_gate_costs();
+    }
+    if options.evaluations {
+        steps.read_evaluations();
+    }
+    steps.precommit(&options.precommitted_advice);
+    steps.limit(options.limits, start);
+    steps.cancel_on(options.cancel.clone());
+    if options.audit_log.is_none() && options.capture_repro.is_none() {
+        steps
+            .run_all(prover, pk, circuits, &prepared, transcript, &mut overflow)
+            .map_err(|e| steps.error(e, overflow))?;
+        return Ok(steps.take_artifacts());
+    }
+
+    if options.capture_repro.is_some() {
+        steps.capture_witness();
+    }
+    let mut recording = RecordingTranscript::new(Forward(transcript));
+    steps
+        .run_all(
+            prover,
+            pk,
+            circuits,
+            &prepared,
+            &mut recording,
+            &mut overflow,
+        )
+        .map_err(|e| steps.error(e, overflow))?;
+    let (_, log) = recording.into_parts();
+    if let Some(path) = &options.audit_log {
+        write_audit(prover, pk, instances, &log, path, options)?;
+    }
+    if let Some(capture) = &options.capture_repro {
+        capture_proof(
+            capture,
+            prover,
+            instances,
+            options,
+            seed,
+            pk.circuit_id(),
+            steps.take_captured_witness(),
+            &log,
+        )?;
+    }
+    Ok(steps.take_artifacts())
+}
+
+/// Writes the audit log of a finished proof, whose Rust side was recorded in
+/// `log`.
+pub(crate) fn write_audit<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    prover: &P,
+    pk: &TachyonProvingKey<Scheme::Curve>,
+    instances: &[&[&[Scheme::Scalar]]],
+    log: &TranscriptLog<Scheme::Curve>,
+    path: &Path,
+    options: &ProofOptions,
+) -> Result<(), TachyonError> {
+    let mut entries = rust_entries(pk, instances, prover.query_instance(), log);
+    entries.extend(
+        backend_entries(&prover.get_proof(), options.audit_layout.as_ref())
+            .map_err(TachyonError::AuditLog)?,
+    );
+    write_audit_log(path, &entries).map_err(TachyonError::AuditLog)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io, time::Instant};
+
+    use super::{
+        create_proof, create_proof_from_prepared_instances, create_proof_streaming_instances,
+        create_proof_with_options, prepare_instances, ProofOptions, ProofStep, ProofSteps,
+    };
+    use crate::{
+        audit::AuditLayout,
+        bn254::{
+            AnyProver, Blake2bWrite as TachyonBlake2bWrite, Evals, Poly,
+            ProvingKey as TachyonProvingKey, SHPlonkProver as TachyonSHPlonkProver,
+            Sha256Write as TachyonSha256Write, TachyonProver, TranscriptWriteState,
+        },
+        circuits::{
+            interleaved_phase_circuit::InterleavedPhaseCircuit,
+            simple_lookup_circuit::SimpleLookupCircuit,
+        },
+        consts::{InstanceAbsorption, MultiOpenScheme, TranscriptType, SEED},
+        debug::DebugError,
+        error::TachyonError,
+        instance_source::{write_instances, InstanceReader, InstanceSource},
+        proving_key::{
+            instance_absorption_to_metadata, multi_open_scheme_to_metadata, write_with_metadata,
+            Metadata,
+        },
+        rng::proof_context,
+        transcript::{RecordingTranscript, TranscriptEntry},
+        xor_shift_rng::XORShiftRng,
+    };
+    use ff::{Field, PrimeField};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{
+            keygen_pk2, verify_proof, Advice, Assigned, Challenge, Circuit, Column,
+            ConstraintSystem, Error, FirstPhase, Instance, SecondPhase, Selector,
+        },
+        poly::{
+            commitment::{Blind, Params, ParamsProver},
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+            EvaluationDomain, Rotation,
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        group::Curve,
+    };
+    use rand_core::{OsRng, SeedableRng};
+
+    const NUM_INSTANCE_COLUMNS: usize = 10;
+
+    /// Constrains `a` to the sum of the instance columns in the first row.
+    #[derive(Clone, Default)]
+    struct InstanceCircuit {
+        sum: Fr,
+    }
+
+    impl Circuit<Fr> for InstanceCircuit {
+        type Config = (Column<Advice>, Vec<Column<Instance>>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let instances = (0..NUM_INSTANCE_COLUMNS)
+                .map(|_| meta.instance_column())
+                .collect::<Vec<_>>();
+            let q = meta.selector();
+            meta.create_gate("sum", |meta| {
+                let q = meta.query_selector(q);
+                let a = meta.query_advice(a, Rotation::cur());
+                let sum = instances
+                    .iter()
+                    .map(|instance| meta.query_instance(*instance, Rotation::cur()))
+                    .reduce(|acc, instance| acc + instance)
+                    .unwrap();
+                vec![q * (a - sum)]
+            });
+            (a, instances, q)
+        }
+
+        fn synthesize(
+            &self,
+            (a, _, q): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "sum",
+                |mut region| {
+                    q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", a, 0, || Value::known(self.sum))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// Assigns `x / 0` to `a` and constrains it to zero.
+    #[derive(Clone, Default)]
+    struct ZeroDenominatorCircuit {
+        x: Fr,
+    }
+
+    impl Circuit<Fr> for ZeroDenominatorCircuit {
+        type Config = (Column<Advice>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let q = meta.selector();
+            meta.create_gate("zero", |meta| {
+                let q = meta.query_selector(q);
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![q * a]
+            });
+            (a, q)
+        }
+
+        fn synthesize(
+            &self,
+            (a, q): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "zero",
+                |mut region| {
+                    q.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "a",
+                        a,
+                        0,
+                        || Value::known(Assigned::Rational(self.x, Fr::zero())),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_params() {
+        let k = 4;
+        const N: u64 = 16;
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s.clone());
+        let prover_from_s = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        let prover_from_params = {
+            let mut params_bytes: Vec<u8> = vec![];
+            params.write(&mut params_bytes).unwrap();
+            TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::from_params(
+                TranscriptType::Blake2b as u8,
+                k,
+                params_bytes.as_slice(),
+            )
+        };
+
+        assert_eq!(prover_from_s.n(), N);
+        assert_eq!(prover_from_params.n(), N);
+
+        let expected_s_g2 = params.s_g2();
+        assert_eq!(prover_from_s.s_g2(), &expected_s_g2);
+        assert_eq!(prover_from_params.s_g2(), &expected_s_g2);
+
+        let domain = EvaluationDomain::new(1, k);
+        let scalars = (0..N).map(|_| Fr::random(OsRng)).collect::<Vec<_>>();
+        let mut evals = prover_from_s.empty_evals();
+        for i in 0..scalars.len() {
+            evals.set_value(i, &scalars[i]);
+        }
+        let lagrange = domain.lagrange_from_vec(scalars.clone());
+        let expected_commitment = params.commit_lagrange(&lagrange, Blind::default());
+        assert_eq!(prover_from_s.commit_lagrange(&evals), expected_commitment);
+        assert_eq!(
+            prover_from_params.commit_lagrange(&evals),
+            expected_commitment
+        );
+
+        let cpp_poly = prover_from_s.ifft(&evals);
+        let poly = domain.lagrange_to_coeff(lagrange);
+
+        let expected_commitment = params.commit(&poly, Blind::default());
+        assert_eq!(prover_from_s.commit(&cpp_poly), expected_commitment);
+        assert_eq!(prover_from_params.commit(&cpp_poly), expected_commitment);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_update_prepared_instances() {
+        let k = 4;
+        let s = Fr::from(2);
+        let columns = (0..NUM_INSTANCE_COLUMNS)
+            .map(|column| {
+                (0..3)
+                    .map(|row| Fr::from((column * 3 + row) as u64))
+                    .collect()
+            })
+            .collect::<Vec<Vec<Fr>>>();
+        let circuit = |columns: &[Vec<Fr>]| InstanceCircuit {
+            sum: columns
+                .iter()
+                .fold(Fr::zero(), |acc, values| acc + values[0]),
+        };
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit(&columns)).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        for scheme in [MultiOpenScheme::Gwc, MultiOpenScheme::SHPlonk] {
+            let new_prover = || {
+                AnyProver::<KZGCommitmentScheme<Bn256>>::new(
+                    scheme,
+                    TranscriptType::Blake2b as u8,
+                    k,
+                    &s,
+                )
+            };
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+
+            let mut prover = new_prover();
+            let instances = columns.iter().map(|values| &values[..]).collect::<Vec<_>>();
+            let mut prepared = prepare_instances(&mut prover, &tachyon_pk, &[&instances[..]])
+                .expect("instances should be valid");
+
+            let value = Fr::from(100);
+            let column = |index| tachyon_pk.instance_index(index).unwrap();
+            let changed = prepared
+                .update_cells(
+                    0,
+                    &[(column(3), 0, value), (column(5), 1, columns[5][1])],
+                    &prover,
+                    &tachyon_pk,
+                )
+                .unwrap();
+            assert_eq!(changed, vec![column(3)]);
+
+            let updated_columns = {
+                let mut columns = columns.clone();
+                columns[3][0] = value;
+                columns
+            };
+            let incremental_proof = {
+                let mut transcript = TachyonBlake2bWrite::init(vec![]);
+                create_proof_from_prepared_instances(
+                    &mut prover,
+                    &mut tachyon_pk,
+                    &[circuit(&updated_columns)],
+                    &prepared,
+                    XORShiftRng::from_seed(SEED),
+                    &mut transcript,
+                )
+                .expect("proof generation should not fail");
+                let mut proof = transcript.finalize();
+                proof.extend_from_slice(&prover.get_proof());
+                proof
+            };
+
+            let from_scratch_proof = {
+                let mut prover = new_prover();
+                let instances = updated_columns
+                    .iter()
+                    .map(|values| &values[..])
+                    .collect::<Vec<_>>();
+                let mut transcript = TachyonBlake2bWrite::init(vec![]);
+                create_proof(
+                    &mut prover,
+                    &mut tachyon_pk,
+                    &[circuit(&updated_columns)],
+                    &[&instances[..]],
+                    XORShiftRng::from_seed(SEED),
+                    &mut transcript,
+                )
+                .expect("proof generation should not fail");
+                let mut proof = transcript.finalize();
+                proof.extend_from_slice(&prover.get_proof());
+                proof
+            };
+            assert_eq!(incremental_proof, from_scratch_proof, "{:?}", scheme);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_prepared_instances_of_another_scheme() {
+        let k = 4;
+        let s = Fr::from(2);
+        let columns = (0..NUM_INSTANCE_COLUMNS)
+            .map(|column| vec![Fr::from(column as u64)])
+            .collect::<Vec<Vec<Fr>>>();
+        let circuit = InstanceCircuit {
+            sum: columns
+                .iter()
+                .fold(Fr::zero(), |acc, values| acc + values[0]),
+        };
+        let instances = columns.iter().map(|values| &values[..]).collect::<Vec<_>>();
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let new_prover = |scheme| {
+            AnyProver::<KZGCommitmentScheme<Bn256>>::new(
+                scheme,
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            )
+        };
+
+        // Instances prepared for GWC are queried, which SHPlonk doesn't expect.
+        let prepared = prepare_instances(
+            &mut new_prover(MultiOpenScheme::Gwc),
+            &tachyon_pk,
+            &[&instances[..]],
+        )
+        .expect("instances should be valid");
+        let result = create_proof_from_prepared_instances(
+            &mut new_prover(MultiOpenScheme::SHPlonk),
+            &mut tachyon_pk,
+            &[circuit],
+            &prepared,
+            XORShiftRng::from_seed(SEED),
+            &mut TachyonBlake2bWrite::init(vec![]),
+        );
+        assert!(matches!(result, Err(Error::InvalidInstances)));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_more_circuits_than_instances() {
+        let k = 4;
+        let s = Fr::from(2);
+        let columns = (0..NUM_INSTANCE_COLUMNS)
+            .map(|column| vec![Fr::from(column as u64)])
+            .collect::<Vec<Vec<Fr>>>();
+        let circuit = InstanceCircuit {
+            sum: columns
+                .iter()
+                .fold(Fr::zero(), |acc, values| acc + values[0]),
+        };
+        let instances = columns.iter().map(|values| &values[..]).collect::<Vec<_>>();
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+
+        let result = create_proof(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit.clone(), circuit],
+            &[&instances[..]],
+            XORShiftRng::from_seed(SEED),
+            &mut TachyonBlake2bWrite::init(vec![]),
+        );
+        assert!(matches!(result, Err(Error::InvalidInstances)));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_streaming_instances() {
+        let k = 4;
+        let s = Fr::from(2);
+        let columns = (0..NUM_INSTANCE_COLUMNS)
+            .map(|column| {
+                (0..column % 4)
+                    .map(|row| Fr::from((column * 3 + row + 1) as u64))
+                    .collect()
+            })
+            .collect::<Vec<Vec<Fr>>>();
+        let circuit = InstanceCircuit {
+            sum: columns
+                .iter()
+                .filter_map(|values| values.first())
+                .fold(Fr::zero(), |acc, value| acc + value),
+        };
+        let instances = columns.iter().map(|values| &values[..]).collect::<Vec<_>>();
+        let mut bytes = vec![];
+        write_instances(&mut bytes, &instances).unwrap();
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        for scheme in [MultiOpenScheme::Gwc, MultiOpenScheme::SHPlonk] {
+            for absorption in [
+                InstanceAbsorption::PerValue,
+                InstanceAbsorption::HashedColumn,
+            ] {
+                let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+                let mut metadata = Metadata::new();
+                instance_absorption_to_metadata(absorption, &mut metadata);
+                tachyon_pk.set_metadata(metadata);
+                let new_prover = || {
+                    AnyProver::<KZGCommitmentScheme<Bn256>>::new(
+                        scheme,
+                        TranscriptType::Blake2b as u8,
+                        k,
+                        &s,
+                    )
+                };
+                let mut prove = |source: &mut dyn FnMut(
+                    &mut AnyProver<KZGCommitmentScheme<Bn256>>,
+                    &mut TachyonProvingKey<G1Affine>,
+                    &mut TachyonBlake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+                ) -> Result<(), Error>| {
+                    let mut prover = new_prover();
+                    let mut transcript = TachyonBlake2bWrite::init(vec![]);
+                    source(&mut prover, &mut tachyon_pk, &mut transcript)
+                        .expect("proof generation should not fail");
+                    let mut proof = transcript.finalize();
+                    proof.extend_from_slice(&prover.get_proof());
+                    proof
+                };
+
+                let from_slices = prove(&mut |prover, pk, transcript| {
+                    create_proof(
+                        prover,
+                        pk,
+                        &[circuit.clone()],
+                        &[&instances[..]],
+                        XORShiftRng::from_seed(SEED),
+                        transcript,
+                    )
+                });
+                let streamed_slices = prove(&mut |prover, pk, transcript| {
+                    create_proof_streaming_instances(
+                        prover,
+                        pk,
+                        &[circuit.clone()],
+                        &mut [&instances[..]],
+                        XORShiftRng::from_seed(SEED),
+                        transcript,
+                    )
+                });
+                let streamed_reader = prove(&mut |prover, pk, transcript| {
+                    let reader = InstanceReader::new(bytes.as_slice())?;
+                    create_proof_streaming_instances(
+                        prover,
+                        pk,
+                        &[circuit.clone()],
+                        &mut [reader],
+                        XORShiftRng::from_seed(SEED),
+                        transcript,
+                    )
+                });
+                assert_eq!(
+                    streamed_slices, from_slices,
+                    "{:?} {:?}",
+                    scheme, absorption
+                );
+                assert_eq!(
+                    streamed_reader, from_slices,
+                    "{:?} {:?}",
+                    scheme, absorption
+                );
+            }
+        }
+
+        // A truncated reader fails the proof instead of proving zeros.
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        let reader = InstanceReader::new(&bytes[..bytes.len() - 1]).unwrap();
+        let result = create_proof_streaming_instances(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &mut [reader],
+            XORShiftRng::from_seed(SEED),
+            &mut TachyonBlake2bWrite::init(vec![]),
+        );
+        assert!(matches!(result, Err(Error::Transcript(_))));
+    }
+
+    /// Computes every value as it is read, like a framework that evaluates
+    /// its instances lazily with side effects, and counts the reads. A value
+    /// that is read again comes out different.
+    struct CountingSource {
+        columns: Vec<Vec<Fr>>,
+        reads: Vec<Vec<u64>>,
+    }
+
+    impl InstanceSource for CountingSource {
+        fn num_columns(&self) -> usize {
+            self.columns.len()
+        }
+
+        fn column_len(&self, column: usize) -> usize {
+            self.columns[column].len()
+        }
+
+        fn read_column(
+            &mut self,
+            column: usize,
+            sink: &mut dyn FnMut(usize, Fr),
+        ) -> io::Result<()> {
+            for (row, value) in self.columns[column].iter().enumerate() {
+                let reads = &mut self.reads[column][row];
+                sink(row, *value + Fr::from(*reads));
+                *reads += 1;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_instances_read_once() {
+        let k = 4;
+        let s = Fr::from(2);
+        let columns = (0..NUM_INSTANCE_COLUMNS)
+            .map(|column| {
+                (0..2)
+                    .map(|row| Fr::from((column * 2 + row + 1) as u64))
+                    .collect()
+            })
+            .collect::<Vec<Vec<Fr>>>();
+        let circuit = InstanceCircuit {
+            sum: columns
+                .iter()
+                .fold(Fr::zero(), |acc, values| acc + values[0]),
+        };
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        // The values are absorbed into the transcript and committed to by
+        // the prover as they were first read.
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        let mut source = CountingSource {
+            reads: columns.iter().map(|values| vec![0; values.len()]).collect(),
+            columns: columns.clone(),
+        };
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        create_proof_streaming_instances(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            std::slice::from_mut(&mut source),
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        assert!(source.reads.iter().flatten().all(|reads| *reads == 1));
+
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+        let instances = columns.iter().map(|values| &values[..]).collect::<Vec<_>>();
+        assert!(prover.verify_proof(&mut tachyon_pk, &[&instances[..]], &proof));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_zero_denominator() {
+        let k = 4;
+        let s = Fr::from(2);
+        let circuit = ZeroDenominatorCircuit { x: Fr::from(3) };
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+
+        let halo2_proof = {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            halo2_proofs::plonk::create_proof::<
+                KZGCommitmentScheme<Bn256>,
+                ProverSHPLONK<_>,
+                _,
+                _,
+                _,
+                _,
+            >(
+                &params,
+                &pk,
+                &[circuit.clone()],
+                &[&[]],
+                XORShiftRng::from_seed(SEED),
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            transcript.finalize()
+        };
+
+        let tachyon_proof = {
+            let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            let mut tachyon_pk = {
+                let mut pk_bytes: Vec<u8> = vec![];
+                pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+                    .unwrap();
+                TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap()
+            };
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            create_proof(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit],
+                &[&[]],
+                XORShiftRng::from_seed(SEED),
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+        // Same witness, same randomness, so the proofs match byte for byte.
+        assert_eq!(halo2_proof, tachyon_proof);
+
+        let verifier_params = params.verifier_params();
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&tachyon_proof[..]);
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            verifier_params,
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[]],
+            &mut transcript,
+        )
+        .is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_verify_constraints_first() {
+        let k = 4;
+        let s = Fr::from(2);
+        let values = vec![Fr::one(); NUM_INSTANCE_COLUMNS];
+        let instances = values.iter().map(std::slice::from_ref).collect::<Vec<_>>();
+        // The sum of the instances is `NUM_INSTANCE_COLUMNS`, not zero.
+        let circuit = InstanceCircuit { sum: Fr::zero() };
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        prover.set_master_key([7; 32]);
+
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        let result = create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &[&instances[..]],
+            &ProofOptions {
+                verify_constraints_first: true,
+                ..Default::default()
+            },
+            &mut transcript,
+        );
+        match result {
+            Err(TachyonError::Preflight(DebugError::Unsatisfied { gates, lookups, .. })) => {
+                assert_eq!(gates, vec![("sum".to_string(), 0)]);
+                assert!(lookups.is_empty());
+            }
+            _ => panic!("expected a pre-flight failure"),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_multi_open_mismatch() {
+        let k = 4;
+        let s = Fr::from(2);
+        let values = vec![Fr::one(); NUM_INSTANCE_COLUMNS];
+        let instances = values.iter().map(std::slice::from_ref).collect::<Vec<_>>();
+        let circuit = InstanceCircuit {
+            sum: Fr::from(NUM_INSTANCE_COLUMNS as u64),
+        };
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut metadata = Metadata::new();
+        multi_open_scheme_to_metadata(MultiOpenScheme::Gwc, &mut metadata);
+        let mut gwc_pk_bytes = vec![];
+        write_with_metadata(&mut gwc_pk_bytes, &metadata, &pk_bytes).unwrap();
+
+        let prove = |scheme: MultiOpenScheme| {
+            let mut tachyon_pk = TachyonProvingKey::try_from(gwc_pk_bytes.as_slice()).unwrap();
+            assert_eq!(tachyon_pk.multi_open_scheme(), Some(MultiOpenScheme::Gwc));
+            let mut prover = AnyProver::<KZGCommitmentScheme<Bn256>>::new(
+                scheme,
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            assert_eq!(prover.multi_open_scheme(), scheme);
+            prover.set_master_key([7; 32]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&instances[..]],
+                &ProofOptions::default(),
+                &mut TachyonBlake2bWrite::init(vec![]),
+            )
+        };
+
+        match prove(MultiOpenScheme::SHPlonk) {
+            Err(TachyonError::MultiOpenMismatch { key, prover }) => {
+                assert_eq!(key, MultiOpenScheme::Gwc);
+                assert_eq!(prover, MultiOpenScheme::SHPlonk);
+            }
+            _ => panic!("expected MultiOpenMismatch"),
+        }
+        assert!(prove(MultiOpenScheme::Gwc).is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_transcript_mismatch() {
+        let k = 4;
         let s = Fr::from(2);
-        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s.clone());
-        let prover_from_s = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+        let values = vec![Fr::one(); NUM_INSTANCE_COLUMNS];
+        let instances = values.iter().map(std::slice::from_ref).collect::<Vec<_>>();
+        let circuit = InstanceCircuit {
+            sum: Fr::from(NUM_INSTANCE_COLUMNS as u64),
+        };
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
             TranscriptType::Blake2b as u8,
             k,
             &s,
         );
-        let prover_from_params = {
-            let mut params_bytes: Vec<u8> = vec![];
-            params.write(&mut params_bytes).unwrap();
-            TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::from_params(
+        prover.set_master_key([7; 32]);
+        let fresh_state = TachyonSha256Write::<_, G1Affine, Challenge255<_>>::init(vec![]).state();
+
+        let mut transcript =
+            RecordingTranscript::new(TachyonSha256Write::<_, G1Affine, Challenge255<_>>::init(
+                vec![],
+            ));
+        match create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit.clone()],
+            &[&instances[..]],
+            &ProofOptions::default(),
+            &mut transcript,
+        ) {
+            Err(TachyonError::TranscriptMismatch { prover, writer }) => {
+                assert_eq!(prover, TranscriptType::Blake2b);
+                assert_eq!(writer, TranscriptType::Sha256);
+            }
+            _ => panic!("expected TranscriptMismatch"),
+        }
+        // Nothing was absorbed, not even the verifying key.
+        assert!(transcript.log().is_empty());
+        assert_eq!(transcript.state(), fresh_state);
+
+        let mut transcript = TachyonSha256Write::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        assert!(matches!(
+            create_proof(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit],
+                &[&instances[..]],
+                XORShiftRng::from_seed(SEED),
+                &mut transcript,
+            ),
+            Err(Error::Transcript(_))
+        ));
+        assert_eq!(transcript.state(), fresh_state);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_audit_log() {
+        let k = 4;
+        let s = Fr::from(2);
+        let values = vec![Fr::one(); NUM_INSTANCE_COLUMNS];
+        let instances = values.iter().map(std::slice::from_ref).collect::<Vec<_>>();
+        let circuit = InstanceCircuit {
+            sum: Fr::from(NUM_INSTANCE_COLUMNS as u64),
+        };
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        prover.set_master_key([7; 32]);
+        let layout = AuditLayout::new(
+            pk.get_vk(),
+            1,
+            MultiOpenScheme::SHPlonk,
+            &TranscriptType::Blake2b,
+        );
+        let path = std::env::temp_dir().join(format!("audit_log_{}.jsonl", std::process::id()));
+
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &[&instances[..]],
+            &ProofOptions {
+                audit_log: Some(path.clone()),
+                audit_layout: Some(layout.clone()),
+                ..Default::default()
+            },
+            &mut transcript,
+        )
+        .expect("proof should not fail");
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines = log.lines().collect::<Vec<_>>();
+        // The vk repr and one scalar per instance value, then every value in
+        // the proof. `InstanceCircuit` has no challenges.
+        assert_eq!(lines.len(), 1 + NUM_INSTANCE_COLUMNS + layout.items.len());
+
+        let hex = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        };
+        let vk_repr = prover.transcript_repr(&mut tachyon_pk);
+        assert_eq!(
+            lines[0],
+            format!(
+                "{{\"seq\":0,\"source\":\"rust\",\"op\":\"common_scalar\",\"label\":\"vk_repr\",\"value\":\"{}\"}}",
+                hex(vk_repr.to_repr().as_ref())
+            )
+        );
+        assert_eq!(
+            lines[lines.len() - 1],
+            format!(
+                "{{\"seq\":{},\"source\":\"backend\",\"op\":\"write_point\",\"label\":\"opening_commitment[1]\",\"value\":\"{}\"}}",
+                lines.len() - 1,
+                hex(&proof[proof.len() - 32..])
+            )
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_coset_evaluate() {
+        let k = 10;
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &InstanceCircuit::default()).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        let domain = pk.get_vk().get_domain();
+
+        let random_poly = || {
+            let scalars = (0..1 << k).map(|_| Fr::random(OsRng)).collect::<Vec<_>>();
+            let mut evals = prover.empty_evals();
+            for (i, scalar) in scalars.iter().enumerate() {
+                evals.set_value(i, scalar);
+            }
+            (
+                prover.ifft(&evals),
+                domain.lagrange_to_coeff(domain.lagrange_from_vec(scalars)),
+            )
+        };
+        let (cpp_a, a) = random_poly();
+        let (cpp_b, b) = random_poly();
+
+        let extended_a = prover.coset_evaluate(&tachyon_pk, &cpp_a);
+        let extended_b = prover.coset_evaluate(&tachyon_pk, &cpp_b);
+        let expected_a = domain.coeff_to_extended(a);
+        let expected_b = domain.coeff_to_extended(b);
+        assert_eq!(extended_a.extended_k(), domain.extended_k());
+        assert_eq!(extended_a.values(), &expected_a[..]);
+        assert_eq!(extended_b.values(), &expected_b[..]);
+
+        let mut sum = extended_a.clone();
+        sum += &extended_b;
+        assert_eq!(sum.values(), &(expected_a.clone() + &expected_b)[..]);
+
+        let mut product = extended_a.clone();
+        product *= &extended_b;
+        let expected_product = expected_a
+            .iter()
+            .zip(expected_b.iter())
+            .map(|(a, b)| a * b)
+            .collect::<Vec<_>>();
+        assert_eq!(product.values(), &expected_product[..]);
+
+        let scalar = Fr::random(OsRng);
+        let mut scaled = extended_a.clone();
+        scaled *= scalar;
+        assert_eq!(scaled.values(), &(expected_a.clone() * scalar)[..]);
+
+        let mut quotient = extended_a.clone();
+        quotient.divide_by_vanishing_poly();
+        assert_eq!(
+            quotient.values(),
+            &domain.divide_by_vanishing_poly(expected_a)[..]
+        );
+
+        let cpp_poly = prover.coset_ifft(&extended_b);
+        assert_eq!(prover.coset_evaluate(&tachyon_pk, &cpp_poly), extended_b);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_polynomial_conversion() {
+        let k = 4;
+        let n = 1 << k;
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        let domain = EvaluationDomain::new(1, k);
+
+        let lagrange =
+            domain.lagrange_from_vec((0..n).map(|_| Fr::random(OsRng)).collect::<Vec<_>>());
+        let evals = Evals::from_lagrange(&prover, &lagrange).unwrap();
+        assert_eq!(&evals.to_lagrange_polynomial()[..], &lagrange[..]);
+        assert_eq!(
+            prover.commit_lagrange(&evals),
+            params.commit_lagrange(&lagrange, Blind::default())
+        );
+
+        let coeff = domain.lagrange_to_coeff(lagrange);
+        let poly = Poly::from_coeff(&prover, &coeff).unwrap();
+        assert_eq!(&poly.to_coeff_polynomial(&prover).unwrap()[..], &coeff[..]);
+        assert_eq!(
+            prover.commit(&poly),
+            params.commit(&coeff, Blind::default())
+        );
+        assert_eq!(
+            &prover.ifft(&evals).to_coeff_polynomial(&prover).unwrap()[..],
+            &coeff[..]
+        );
+
+        // The backend drops the zero coefficients of a constant, and they
+        // come back.
+        let constant = Fr::random(OsRng);
+        let evals =
+            Evals::from_lagrange(&prover, &domain.lagrange_from_vec(vec![constant; n])).unwrap();
+        let mut expected = vec![Fr::zero(); n];
+        expected[0] = constant;
+        assert_eq!(
+            &prover.ifft(&evals).to_coeff_polynomial(&prover).unwrap()[..],
+            &expected[..]
+        );
+
+        let small_domain = EvaluationDomain::new(1, k - 1);
+        assert!(matches!(
+            Evals::from_lagrange(
+                &prover,
+                &small_domain.lagrange_from_vec(vec![Fr::one(); n / 2])
+            ),
+            Err(TachyonError::LengthMismatch {
+                expected: 16,
+                found: 8
+            })
+        ));
+        assert!(matches!(
+            Poly::from_coeff(
+                &prover,
+                &small_domain.coeff_from_vec(vec![Fr::one(); n / 2])
+            ),
+            Err(TachyonError::LengthMismatch {
+                expected: 16,
+                found: 8
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_interleaved_phase_commitments() {
+        let k = 4;
+        let s = Fr::from(2);
+        let circuit = InterleavedPhaseCircuit {
+            values: (1..4)
+                .map(|i| [Value::known(Fr::from(i)), Value::known(Fr::from(i + 10))])
+                .collect(),
+        };
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+
+        let circuits = [circuit];
+        let prepared = prepare_instances(&mut prover, &tachyon_pk, &[&[]]).unwrap();
+        let mut transcript = RecordingTranscript::new(TachyonBlake2bWrite::init(vec![]));
+        let mut steps = ProofSteps::<KZGCommitmentScheme<Bn256>, _>::new(
+            &tachyon_pk,
+            circuits.len(),
+            XORShiftRng::from_seed(SEED),
+            false,
+        );
+        // Stop in front of the backend, which consumes the advice columns.
+        while steps.steps[steps.next] != ProofStep::Backend {
+            steps
+                .run_next(
+                    &mut prover,
+                    &mut tachyon_pk,
+                    &circuits,
+                    &prepared,
+                    &mut transcript,
+                    &mut None,
+                )
+                .expect("proof generation should not fail");
+        }
+
+        // Each phase writes the commitments of its columns in ascending order,
+        // and every column must hold the values its commitment was made from.
+        let advice = &steps.advice[0];
+        let commitments = transcript
+            .log()
+            .iter()
+            .filter_map(|entry| match entry {
+                TranscriptEntry::WritePoint(point) => Some(*point),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let expected = [0, 2, 1, 3].map(|column| {
+            prover
+                .commit_lagrange(&advice.advice_polys[column])
+                .to_affine()
+        });
+        assert_eq!(commitments, expected);
+        for blind in &advice.advice_blinds {
+            assert_ne!(blind.0, Fr::zero());
+        }
+
+        steps
+            .run_next(
+                &mut prover,
+                &mut tachyon_pk,
+                &circuits,
+                &prepared,
+                &mut transcript,
+                &mut None,
+            )
+            .expect("proof generation should not fail");
+        assert!(steps.is_finished());
+        let (transcript, _) = transcript.into_parts();
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[]],
+            &mut transcript,
+        )
+        .is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_intra_proof_parallelism() {
+        let k = 5;
+        let s = Fr::from(2);
+        let circuit = SimpleLookupCircuit::<Fr>::default();
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        // Two circuits, so that there are lookups of more than one circuit to
+        // compute at once.
+        let prove = |intra_proof_parallelism: usize| {
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
                 TranscriptType::Blake2b as u8,
                 k,
-                params_bytes.as_slice(),
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone(), circuit.clone()],
+                &[&[], &[]],
+                &ProofOptions {
+                    rng_context: proof_context(b"lookup", 0),
+                    intra_proof_parallelism,
+                    ..Default::default()
+                },
+                &mut transcript,
             )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
         };
 
-        assert_eq!(prover_from_s.n(), N);
-        assert_eq!(prover_from_params.n(), N);
+        let proof = prove(4);
+        assert_eq!(proof, prove(1));
 
-        let expected_s_g2 = params.s_g2();
-        assert_eq!(prover_from_s.s_g2(), &expected_s_g2);
-        assert_eq!(prover_from_params.s_g2(), &expected_s_g2);
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[], &[]],
+            &mut transcript,
+        )
+        .is_ok());
+    }
 
-        let domain = EvaluationDomain::new(1, k);
-        let scalars = (0..N).map(|_| Fr::random(OsRng)).collect::<Vec<_>>();
-        let mut evals = prover_from_s.empty_evals();
-        for i in 0..scalars.len() {
-            evals.set_value(i, &scalars[i]);
+    // The proof is the same for any number of threads, on the CPU or the
+    // GPU, and the same as halo2's, which doesn't depend on the platform.
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_proof_is_deterministic() {
+        let k = 5;
+        let s = Fr::from(2);
+        let master_key = [7; 32];
+        let rng_context = proof_context(b"lookup", 0);
+        let circuit = SimpleLookupCircuit::<Fr>::default();
+        let circuits = [circuit.clone(), circuit.clone()];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        halo2_proofs::plonk::create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &circuits,
+            &[&[], &[]],
+            XORShiftRng::from_seed(crate::rng::derive_seed(&master_key, &rng_context)),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let halo2_proof = transcript.finalize();
+
+        let prove = |options: ProofOptions| {
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key(master_key);
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &circuits,
+                &[&[], &[]],
+                &ProofOptions {
+                    rng_context: rng_context.clone(),
+                    ..options
+                },
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+
+        // Every stage is on the GPU with the "gpu" feature, since the
+        // thresholds are 0, and on the CPU without it.
+        let gpu = crate::placement::StagePlacement {
+            thresholds: crate::placement::PlacementThresholds {
+                fft: 0,
+                msm: 0,
+                lookup_products: 0,
+                quotient: 0,
+            },
+            ..Default::default()
+        };
+        for stage_placement in [crate::placement::StagePlacement::cpu(), gpu] {
+            for num_threads in [1, 2, 8] {
+                for intra_proof_parallelism in [1, 4] {
+                    let proof = prove(ProofOptions {
+                        num_threads,
+                        intra_proof_parallelism,
+                        stage_placement,
+                        ..Default::default()
+                    });
+                    assert_eq!(
+                        proof, halo2_proof,
+                        "{:?}, {} threads, {} at once",
+                        stage_placement, num_threads, intra_proof_parallelism
+                    );
+                }
+            }
         }
-        let lagrange = domain.lagrange_from_vec(scalars.clone());
-        let expected_commitment = params.commit_lagrange(&lagrange, Blind::default());
-        assert_eq!(prover_from_s.commit_lagrange(&evals), expected_commitment);
+        // The Rust side's loops on a single thread.
+        #[cfg(feature = "rayon")]
+        {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(1)
+                .build()
+                .unwrap();
+            assert_eq!(pool.install(|| prove(ProofOptions::default())), halo2_proof);
+        }
+    }
+
+    // The commitments of a prover run in the scratch buffers of the ones
+    // before them, which must not change the proof.
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_scratch_reuse() {
+        let k = 5;
+        let s = Fr::from(2);
+        let circuit = SimpleLookupCircuit::<Fr>::default();
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        prover.set_master_key([7; 32]);
+        let mut prove = |prover: &mut TachyonSHPlonkProver<KZGCommitmentScheme<Bn256>>| {
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            create_proof_with_options(
+                prover,
+                &mut tachyon_pk,
+                &[circuit.clone(), circuit.clone()],
+                &[&[], &[]],
+                &ProofOptions {
+                    rng_context: proof_context(b"lookup", 0),
+                    ..Default::default()
+                },
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+
+        assert_eq!(prover.scratch_stats(), Default::default());
+        let proof = prove(&mut prover);
+        let stats = prover.scratch_stats();
+        assert!(stats.bytes > 0);
+        assert!(stats.num_allocations > 0);
+
+        assert_eq!(prove(&mut prover), proof);
+        let reused = prover.scratch_stats();
+        assert!(reused.num_reuses > stats.num_reuses);
+        assert!(reused.bytes >= stats.bytes);
+
+        prover.release_scratch();
+        let released = prover.scratch_stats();
+        assert_eq!(released.bytes, 0);
+        assert_eq!(released.num_reuses, reused.num_reuses);
+        assert_eq!(prover.debug_allocation_stats().get("msm_scratch"), Some(0));
+        assert_eq!(prove(&mut prover), proof);
+        assert!(prover.scratch_stats().num_allocations > released.num_allocations);
+    }
+
+    /// Leaves `empty` unassigned and assigns a distinct nonzero value to every
+    /// usable row of `full`.
+    #[derive(Clone, Default)]
+    struct ColumnUsageCircuit {
+        usable_rows: usize,
+    }
+
+    impl Circuit<Fr> for ColumnUsageCircuit {
+        type Config = [Column<Advice>; 2];
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            [meta.advice_column(), meta.advice_column()]
+        }
+
+        fn synthesize(
+            &self,
+            [_empty, full]: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "full",
+                |mut region| {
+                    for row in 0..self.usable_rows {
+                        region.assign_advice(
+                            || "full",
+                            full,
+                            row,
+                            || Value::known(Fr::from(row as u64 + 1)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_column_report() {
+        let k = 4;
+        let s = Fr::from(2);
+        let n = 1usize << k;
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let usable_rows = {
+            let pk =
+                keygen_pk2(&params, &ColumnUsageCircuit::default()).expect("vk should not fail");
+            n - (pk.get_vk().cs().blinding_factors() + 1)
+        };
+        let circuit = ColumnUsageCircuit { usable_rows };
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        prover.set_master_key([7; 32]);
+
+        create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &[&[]],
+            &ProofOptions {
+                column_report: true,
+                ..Default::default()
+            },
+            &mut TachyonBlake2bWrite::init(vec![]),
+        )
+        .expect("proof generation should not fail");
+        let report = prover.metrics().column_report;
         assert_eq!(
-            prover_from_params.commit_lagrange(&evals),
-            expected_commitment
+            report
+                .iter()
+                .map(|column| (column.circuit, column.column))
+                .collect::<Vec<_>>(),
+            vec![(0, 0), (0, 1)]
         );
 
-        let cpp_poly = prover_from_s.ifft(&evals);
-        let poly = domain.lagrange_to_coeff(lagrange);
+        let empty = report[0].stats;
+        assert_eq!(empty.num_nonzero_rows, 0);
+        assert_eq!(empty.highest_nonzero_coeff, None);
+        assert_eq!(empty.entropy, 0.0);
 
-        let expected_commitment = params.commit(&poly, Blind::default());
-        assert_eq!(prover_from_s.commit(&cpp_poly), expected_commitment);
-        assert_eq!(prover_from_params.commit(&cpp_poly), expected_commitment);
+        let full = report[1].stats;
+        assert_eq!(full.num_nonzero_rows, usable_rows);
+        let domain = pk.get_vk().get_domain();
+        let mut values = vec![Fr::zero(); n];
+        for (row, value) in values.iter_mut().take(usable_rows).enumerate() {
+            *value = Fr::from(row as u64 + 1);
+        }
+        let coeffs = domain.lagrange_to_coeff(domain.lagrange_from_vec(values));
+        assert_eq!(
+            full.highest_nonzero_coeff,
+            coeffs
+                .iter()
+                .rposition(|coeff| !bool::from(coeff.is_zero()))
+        );
+        // Each usable row holds a value of its own, and the rest hold zeros.
+        let p_zero = (n - usable_rows) as f64 / n as f64;
+        let entropy = usable_rows as f64 / n as f64 * k as f64 - p_zero * p_zero.log2();
+        assert!((full.entropy - entropy).abs() < 1e-9);
+
+        // Without the option, the report of the last proof is left alone.
+        create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[ColumnUsageCircuit { usable_rows }],
+            &[&[]],
+            &ProofOptions::default(),
+            &mut TachyonBlake2bWrite::init(vec![]),
+        )
+        .expect("proof generation should not fail");
+        assert_eq!(prover.metrics().column_report, report);
+    }
+
+    /// The rows both gates of `GateCostCircuit` are enabled in.
+    const GATE_COST_ROWS: usize = 8;
+
+    /// Has a cheap gate, `a = b`, and a deliberately expensive one of degree
+    /// 7, `y = x⁶ + 3x⁵ + 5x⁴ + 7x³ + x² + x`, over columns of their own.
+    #[derive(Clone, Default)]
+    struct GateCostCircuit;
+
+    impl GateCostCircuit {
+        fn y(x: Fr) -> Fr {
+            let x2 = x.square();
+            let x3 = x2 * x;
+            let x4 = x3 * x;
+            let x5 = x4 * x;
+            x5 * x + x5 * Fr::from(3) + x4 * Fr::from(5) + x3 * Fr::from(7) + x2 + x
+        }
+    }
+
+    impl Circuit<Fr> for GateCostCircuit {
+        type Config = ([Column<Advice>; 4], [Selector; 2]);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let [a, b, x, y] = [(); 4].map(|_| meta.advice_column());
+            let [q_cheap, q_expensive] = [meta.selector(), meta.selector()];
+            meta.create_gate("cheap", |meta| {
+                let q = meta.query_selector(q_cheap);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                vec![q * (a - b)]
+            });
+            meta.create_gate("expensive", |meta| {
+                let q = meta.query_selector(q_expensive);
+                let x = meta.query_advice(x, Rotation::cur());
+                let y = meta.query_advice(y, Rotation::cur());
+                let x2 = x.clone() * x.clone();
+                let x3 = x2.clone() * x.clone();
+                let x4 = x3.clone() * x.clone();
+                let x5 = x4.clone() * x.clone();
+                let x6 = x5.clone() * x.clone();
+                vec![q * (x6 + x5 * Fr::from(3) + x4 * Fr::from(5) + x3 * Fr::from(7) + x2 + x - y)]
+            });
+            ([a, b, x, y], [q_cheap, q_expensive])
+        }
+
+        fn synthesize(
+            &self,
+            ([a, b, x, y], selectors): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "gates",
+                |mut region| {
+                    for row in 0..GATE_COST_ROWS {
+                        for selector in selectors {
+                            selector.enable(&mut region, row)?;
+                        }
+                        let value = Fr::from(row as u64 + 2);
+                        region.assign_advice(|| "a", a, row, || Value::known(value))?;
+                        region.assign_advice(|| "b", b, row, || Value::known(value))?;
+                        region.assign_advice(|| "x", x, row, || Value::known(value))?;
+                        region.assign_advice(
+                            || "y",
+                            y,
+                            row,
+                            || Value::known(GateCostCircuit::y(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn gate_cost_prover(
+        k: u32,
+    ) -> (
+        TachyonSHPlonkProver<KZGCommitmentScheme<Bn256>>,
+        TachyonProvingKey<G1Affine>,
+    ) {
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &GateCostCircuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        prover.set_master_key([7; 32]);
+        (
+            prover,
+            TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap(),
+        )
+    }
+
+    fn prove_gate_cost_circuit(
+        prover: &mut TachyonSHPlonkProver<KZGCommitmentScheme<Bn256>>,
+        pk: &mut TachyonProvingKey<G1Affine>,
+        gate_costs: bool,
+    ) {
+        create_proof_with_options(
+            prover,
+            pk,
+            &[GateCostCircuit],
+            &[&[]],
+            &ProofOptions {
+                gate_costs,
+                ..Default::default()
+            },
+            &mut TachyonBlake2bWrite::init(vec![]),
+        )
+        .expect("proof generation should not fail");
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_gate_costs() {
+        // Enough rows for the sampled ones to time each gate many times.
+        let (mut prover, mut pk) = gate_cost_prover(9);
+        prove_gate_cost_circuit(&mut prover, &mut pk, true);
+        let costs = prover.metrics().gate_costs;
+        assert_eq!(
+            costs
+                .iter()
+                .map(|cost| (cost.gate, cost.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(0, "cheap"), (1, "expensive")]
+        );
+        let (cheap, expensive) = (&costs[0], &costs[1]);
+        assert!(cheap.ops > 0);
+        assert!(expensive.ops > 2 * cheap.ops);
+        assert!(expensive.duration > cheap.duration);
+
+        // Without the option, the backend times nothing and the costs of the
+        // last proof are left alone.
+        prove_gate_cost_circuit(&mut prover, &mut pk, false);
+        assert!(prover.gate_costs().is_empty());
+        assert_eq!(prover.metrics().gate_costs, costs);
+    }
+
+    // Compares proving with and without recording the gate costs. Run with:
+    //   cargo test --release -- --ignored bench_gate_costs --nocapture
+    #[test]
+    #[ignore]
+    fn bench_gate_costs() {
+        let (mut prover, mut pk) = gate_cost_prover(16);
+        for gate_costs in [false, true, false, true] {
+            let start = Instant::now();
+            prove_gate_cost_circuit(&mut prover, &mut pk, gate_costs);
+            println!("gate_costs={:<5} {:?}", gate_costs, start.elapsed());
+        }
+    }
+
+    /// Declares a challenge of the second phase before two of the first, so
+    /// that the challenges are squeezed in another order than their indices.
+    /// Constrains `b` to `a * theta + gamma` with the latter two.
+    #[derive(Clone, Default)]
+    struct ThreeChallengeCircuit {
+        a: Fr,
+    }
+
+    impl Circuit<Fr> for ThreeChallengeCircuit {
+        type Config = (Selector, Column<Advice>, Column<Advice>, [Challenge; 3]);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let q = meta.selector();
+            let a = meta.advice_column_in(FirstPhase);
+            let b = meta.advice_column_in(SecondPhase);
+            let challenges = [
+                meta.challenge_usable_after(SecondPhase),
+                meta.challenge_usable_after(FirstPhase),
+                meta.challenge_usable_after(FirstPhase),
+            ];
+            meta.create_gate("b = a * theta + gamma", |meta| {
+                let q = meta.query_selector(q);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let theta = meta.query_challenge(challenges[1]);
+                let gamma = meta.query_challenge(challenges[2]);
+                vec![q * (b - (a * theta + gamma))]
+            });
+            (q, a, b, challenges)
+        }
+
+        fn synthesize(
+            &self,
+            (q, a, b, challenges): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let theta = layouter.get_challenge(challenges[1]);
+            let gamma = layouter.get_challenge(challenges[2]);
+            layouter.assign_region(
+                || "b",
+                |mut region| {
+                    q.enable(&mut region, 0)?;
+                    let a = Value::known(self.a);
+                    region.assign_advice(|| "a", a, 0, || a)?;
+                    region.assign_advice(|| "b", b, 0, || a * theta + gamma)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_challenge_order() {
+        let k = 4;
+        let s = Fr::from(2);
+        let circuit = ThreeChallengeCircuit { a: Fr::from(5) };
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        assert_eq!(pk.get_vk().cs().num_challenges(), 3);
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover = TachyonSHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        prover.set_master_key([7; 32]);
+
+        let mut transcript = RecordingTranscript::new(TachyonBlake2bWrite::init(vec![]));
+        let artifacts = create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &[&[]],
+            &ProofOptions::default(),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let (transcript, log) = transcript.into_parts();
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+
+        // The first phase squeezes challenges 1 and 2, the second challenge 0,
+        // but they are handed over by index, like halo2 does.
+        let squeezed = log
+            .iter()
+            .filter_map(|entry| match entry {
+                TranscriptEntry::Challenge(challenge) => Some(*challenge),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            artifacts.challenges,
+            vec![squeezed[2], squeezed[0], squeezed[1]]
+        );
+
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        assert!(
+            verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+                params.verifier_params(),
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                &[&[]],
+                &mut transcript,
+            )
+            .is_ok()
+        );
     }
 }
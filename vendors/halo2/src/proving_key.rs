@@ -1,11 +1,332 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+};
+
+use crate::consts::{AbsorbOrder, InstanceAbsorption, MultiOpenScheme};
+
+// A proving key file may start with a metadata section in front of the bytes
+// written by halo2's `ProvingKey::write()`:
+//
+//   magic (8 bytes) | section length (u64 LE) | entries...
+//
+// where each entry is a length-prefixed (u32 LE) UTF-8 key followed by a
+// length-prefixed value. The section is skipped before the key is handed to
+// the backend, so it affects neither `transcript_repr` nor proofs.
+//
+// The section is in front of the key rather than after its header, because
+// the header is part of halo2's `ProvingKey::write()` format, which the
+// backend parses itself. Putting the section inside would need the backend to
+// skip it, and would make the file unreadable by halo2's `ProvingKey::read()`
+// without stripping it. In front, it is still read without touching the
+// polynomials, and removing it gives back halo2's bytes unchanged.
+const METADATA_MAGIC: [u8; 8] = *b"TPKMETA1";
+
+pub type Metadata = BTreeMap<String, String>;
+
+/// The metadata key the multi-open scheme a key is meant for is stored under,
+/// see [`MultiOpenScheme::name()`].
+pub const MULTI_OPEN_SCHEME_METADATA_KEY: &str = "multi_open_scheme";
+
+/// The metadata key the instance absorption of a key is stored under, see
+/// [`InstanceAbsorption::name()`].
+pub const INSTANCE_ABSORPTION_METADATA_KEY: &str = "instance_absorption";
+
+/// The metadata key the advice absorb order of a key is stored under, see
+/// [`AbsorbOrder::name()`].
+pub const ADVICE_ABSORB_ORDER_METADATA_KEY: &str = "advice_absorb_order";
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+pub(crate) fn encode_metadata(metadata: &Metadata) -> Vec<u8> {
+    let mut section = vec![];
+    for (key, value) in metadata {
+        for s in [key, value] {
+            section.extend_from_slice(&(s.len() as u32).to_le_bytes());
+            section.extend_from_slice(s.as_bytes());
+        }
+    }
+    section
+}
+
+pub(crate) fn decode_metadata(mut section: &[u8]) -> io::Result<Metadata> {
+    fn read_str(section: &mut &[u8]) -> io::Result<String> {
+        let mut len = [0u8; 4];
+        section.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        if section.len() < len {
+            return Err(invalid_data("truncated proving key metadata"));
+        }
+        let (s, rest) = section.split_at(len);
+        *section = rest;
+        String::from_utf8(s.to_vec()).map_err(|_| invalid_data("metadata is not UTF-8"))
+    }
+
+    let mut metadata = Metadata::new();
+    while !section.is_empty() {
+        let key = read_str(&mut section)?;
+        let value = read_str(&mut section)?;
+        metadata.insert(key, value);
+    }
+    Ok(metadata)
+}
+
+/// Writes `metadata` followed by `pk`, the bytes of a halo2 proving key.
+pub fn write_with_metadata<W: Write>(
+    writer: &mut W,
+    metadata: &Metadata,
+    pk: &[u8],
+) -> io::Result<()> {
+    let section = encode_metadata(metadata);
+    writer.write_all(&METADATA_MAGIC)?;
+    writer.write_all(&(section.len() as u64).to_le_bytes())?;
+    writer.write_all(&section)?;
+    writer.write_all(pk)
+}
+
+/// Reads only the metadata section from the start of a proving key file.
+/// Returns an empty map for a key written without metadata.
+pub fn read_metadata<R: Read>(reader: &mut R) -> io::Result<Metadata> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if magic != METADATA_MAGIC {
+        return Ok(Metadata::new());
+    }
+    let mut len = [0u8; 8];
+    reader.read_exact(&mut len)?;
+    // The length isn't trusted with an allocation up front; a section that
+    // claims more than the reader holds is truncated.
+    let len = u64::from_le_bytes(len);
+    let mut section = vec![];
+    reader.take(len).read_to_end(&mut section)?;
+    if (section.len() as u64) < len {
+        return Err(invalid_data("truncated proving key metadata"));
+    }
+    decode_metadata(&section)
+}
+
+/// Splits `data` into its metadata and the halo2 proving key bytes that
+/// follow it.
+pub fn split_metadata(data: &[u8]) -> io::Result<(Metadata, &[u8])> {
+    if !data.starts_with(&METADATA_MAGIC) {
+        return Ok((Metadata::new(), data));
+    }
+    let data = &data[METADATA_MAGIC.len()..];
+    if data.len() < 8 {
+        return Err(invalid_data("truncated proving key metadata"));
+    }
+    let (len, data) = data.split_at(8);
+    let len = u64::from_le_bytes(len.try_into().unwrap()) as usize;
+    if data.len() < len {
+        return Err(invalid_data("truncated proving key metadata"));
+    }
+    let (section, pk) = data.split_at(len);
+    Ok((decode_metadata(section)?, pk))
+}
+
+/// Records that proofs with the key are to be created and verified with
+/// `scheme`. Provers of another scheme then refuse the key, see
+/// [`crate::error::TachyonError::MultiOpenMismatch`].
+pub fn multi_open_scheme_to_metadata(scheme: MultiOpenScheme, metadata: &mut Metadata) {
+    metadata.insert(
+        MULTI_OPEN_SCHEME_METADATA_KEY.to_string(),
+        scheme.name().to_string(),
+    );
+}
+
+/// Parses the scheme stored by [`multi_open_scheme_to_metadata()`].
+pub(crate) fn multi_open_scheme_from_metadata(metadata: &Metadata) -> Option<MultiOpenScheme> {
+    MultiOpenScheme::from_name(metadata.get(MULTI_OPEN_SCHEME_METADATA_KEY)?)
+}
+
+/// Records that the instances are absorbed into the transcript with
+/// `absorption` by provers and verifiers using the key. A key written without
+/// it absorbs every value, like halo2.
+pub fn instance_absorption_to_metadata(absorption: InstanceAbsorption, metadata: &mut Metadata) {
+    metadata.insert(
+        INSTANCE_ABSORPTION_METADATA_KEY.to_string(),
+        absorption.name().to_string(),
+    );
+}
+
+/// Parses the absorption stored by [`instance_absorption_to_metadata()`],
+/// defaulting to [`InstanceAbsorption::PerValue`].
+pub fn instance_absorption_from_metadata(metadata: &Metadata) -> InstanceAbsorption {
+    metadata
+        .get(INSTANCE_ABSORPTION_METADATA_KEY)
+        .and_then(|name| InstanceAbsorption::from_name(name))
+        .unwrap_or(InstanceAbsorption::PerValue)
+}
+
+/// Records that the advice commitments are written to the transcript in
+/// `order` by provers and read in it by verifiers using the key. A key
+/// written without it absorbs them like halo2.
+pub fn advice_absorb_order_to_metadata(order: AbsorbOrder, metadata: &mut Metadata) {
+    metadata.insert(
+        ADVICE_ABSORB_ORDER_METADATA_KEY.to_string(),
+        order.name().to_string(),
+    );
+}
+
+/// Parses the order stored by [`advice_absorb_order_to_metadata()`],
+/// defaulting to [`AbsorbOrder::PhaseThenColumn`].
+pub fn advice_absorb_order_from_metadata(metadata: &Metadata) -> AbsorbOrder {
+    metadata
+        .get(ADVICE_ABSORB_ORDER_METADATA_KEY)
+        .and_then(|name| AbsorbOrder::from_name(name))
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod test {
-    use crate::bn254::ProvingKey;
-    use crate::circuits::simple_circuit::SimpleCircuit;
-    use halo2_proofs::{circuit::Value, plonk::keygen_pk2, poly::kzg::commitment::ParamsKZG};
+    use std::io::{Read, Seek, SeekFrom};
+
+    use super::{
+        multi_open_scheme_from_metadata, multi_open_scheme_to_metadata, read_metadata,
+        split_metadata, write_with_metadata, Metadata, MULTI_OPEN_SCHEME_METADATA_KEY,
+    };
+    use crate::{
+        bn254::{Blake2bWrite as TachyonBlake2bWrite, ProvingKey, SHPlonkProver, TachyonProver},
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{MultiOpenScheme, TranscriptType, SEED},
+        prover::create_proof as tachyon_create_proof,
+        xor_shift_rng::XORShiftRng,
+    };
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::TranscriptWriterBuffer,
+    };
     use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::SeedableRng;
+
+    struct CountingReader<R> {
+        inner: R,
+        num_read: usize,
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.num_read += n;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_metadata() {
+        let k = 4;
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let public_inputs2 = vec![&public_inputs[..]];
+        let public_inputs3 = vec![&public_inputs2[..]];
+
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let metadata = Metadata::from([
+            ("circuit".to_string(), "simple".to_string()),
+            ("version".to_string(), "1.2.0".to_string()),
+            ("commit".to_string(), "0ec00be".to_string()),
+        ]);
+        let mut file = tempfile();
+        write_with_metadata(&mut file, &metadata, &pk_bytes).unwrap();
+        // Pretend the key is a few gigabytes large. The file stays sparse.
+        file.set_len(4 << 30).unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CountingReader {
+            inner: &mut file,
+            num_read: 0,
+        };
+        assert_eq!(read_metadata(&mut reader).unwrap(), metadata);
+        assert!(reader.num_read < 1024);
+        assert!(read_metadata(&mut pk_bytes.as_slice()).unwrap().is_empty());
+
+        let mut pk_with_metadata_bytes = vec![];
+        write_with_metadata(&mut pk_with_metadata_bytes, &metadata, &pk_bytes).unwrap();
+
+        let prove = |pk_bytes: &[u8]| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            let mut tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes).unwrap();
+            let transcript_repr = prover.transcript_repr(&mut tachyon_pk);
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            tachyon_create_proof::<_, _, _, _, _>(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                public_inputs3.as_slice(),
+                XORShiftRng::from_seed(SEED),
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            (tachyon_pk.metadata().clone(), transcript_repr, proof)
+        };
+        let (empty, expected_repr, expected_proof) = prove(&pk_bytes);
+        let (actual_metadata, repr, proof) = prove(&pk_with_metadata_bytes);
+        assert!(empty.is_empty());
+        assert_eq!(actual_metadata, metadata);
+        assert_eq!(repr, expected_repr);
+        assert_eq!(proof, expected_proof);
+    }
 
     #[test]
+    fn test_multi_open_scheme_metadata() {
+        for scheme in [MultiOpenScheme::Gwc, MultiOpenScheme::SHPlonk] {
+            let mut metadata = Metadata::new();
+            multi_open_scheme_to_metadata(scheme, &mut metadata);
+            let mut bytes = vec![];
+            write_with_metadata(&mut bytes, &metadata, b"pk").unwrap();
+
+            let (read, pk) = split_metadata(&bytes).unwrap();
+            assert_eq!(pk, b"pk");
+            assert_eq!(multi_open_scheme_from_metadata(&read), Some(scheme));
+        }
+
+        assert_eq!(multi_open_scheme_from_metadata(&Metadata::new()), None);
+        let unknown = Metadata::from([(
+            MULTI_OPEN_SCHEME_METADATA_KEY.to_string(),
+            "ipa".to_string(),
+        )]);
+        assert_eq!(multi_open_scheme_from_metadata(&unknown), None);
+    }
+
+    fn tempfile() -> std::fs::File {
+        let path = std::env::temp_dir().join(format!("pk_metadata_{}", std::process::id()));
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        file
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_proving_key() {
         // ANCHOR: test-circuit
         // The number of rows in our circuit cannot exceed 2ᵏ. Since our example
@@ -31,7 +352,7 @@ mod test {
         let mut pk_bytes: Vec<u8> = vec![];
         pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
             .unwrap();
-        let tachyon_pk = ProvingKey::<G1Affine>::from(pk_bytes.as_slice());
+        let tachyon_pk = ProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap();
 
         assert_eq!(
             pk.get_vk().cs().advice_column_phase,
@@ -46,6 +367,11 @@ mod test {
             tachyon_pk.challenge_phases()
         );
         assert_eq!(*pk.get_vk().cs().constants(), tachyon_pk.constants());
+        assert_eq!(pk.get_vk().cs().degree(), tachyon_pk.degree());
+        assert_eq!(
+            pk.get_vk().get_domain().extended_k(),
+            tachyon_pk.extended_k(k)
+        );
         assert_eq!(
             pk.get_vk().cs().num_advice_columns,
             tachyon_pk.num_advice_columns()
@@ -54,10 +380,19 @@ mod test {
             pk.get_vk().cs().num_challenges(),
             tachyon_pk.num_challenges()
         );
+        assert_eq!(
+            pk.get_vk().cs().num_fixed_columns,
+            tachyon_pk.num_fixed_columns()
+        );
         assert_eq!(
             pk.get_vk().cs().num_instance_columns,
             tachyon_pk.num_instance_columns()
         );
+        assert_eq!(pk.get_vk().cs().lookups().len(), tachyon_pk.num_lookups());
+        assert_eq!(
+            pk.get_vk().cs().permutation().get_columns().len(),
+            tachyon_pk.num_permutation_columns()
+        );
         let phases = pk.get_vk().cs().phases().collect::<Vec<_>>();
         assert_eq!(phases, tachyon_pk.phases());
     }
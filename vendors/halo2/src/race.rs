@@ -0,0 +1,341 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+use halo2_proofs::{
+    plonk::Circuit, poly::commitment::CommitmentScheme, transcript::EncodedChallenge,
+};
+use halo2curves::CurveAffine;
+
+use crate::{
+    bn254::{ProvingKey as TachyonProvingKey, TachyonProver, TranscriptWriteState},
+    error::TachyonError,
+    prover::{create_proof_with_options, ProofArtifacts, ProofOptions},
+};
+
+/// Stops the proofs it is given to, see [`ProofOptions::cancel`]. Clones
+/// share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// One of the two proofs [`race_prove()`] runs, with its own prover, key and
+/// transcript, e.g, a fork of the other's with
+/// [`crate::bn254::TranscriptFork::fork()`].
+pub struct Attempt<'a, P, C: CurveAffine, T> {
+    pub prover: &'a mut P,
+    pub pk: &'a mut TachyonProvingKey<C>,
+    /// `cancel` is replaced with the token `race_prove()` cancels the attempt
+    /// with.
+    pub options: ProofOptions,
+    pub transcript: T,
+}
+
+impl<'a, P: fmt::Debug, C: CurveAffine, T: fmt::Debug> fmt::Debug for Attempt<'a, P, C, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Attempt")
+            .field("prover", &self.prover)
+            .field("pk", &self.pk)
+            .field("options", &self.options)
+            .field("transcript", &self.transcript)
+            .finish()
+    }
+}
+
+/// What both attempts of [`race_prove()`] prove.
+pub struct ProofJob<'a, F, ConcreteCircuit> {
+    pub circuits: &'a [ConcreteCircuit],
+    pub instances: &'a [&'a [&'a [F]]],
+}
+
+impl<'a, F, ConcreteCircuit> fmt::Debug for ProofJob<'a, F, ConcreteCircuit> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProofJob")
+            .field("num_circuits", &self.circuits.len())
+            .field("num_instances", &self.instances.len())
+            .finish()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Racer {
+    A,
+    B,
+}
+
+impl Racer {
+    fn index(&self) -> usize {
+        match self {
+            Racer::A => 0,
+            Racer::B => 1,
+        }
+    }
+}
+
+/// How the attempt that didn't win ended.
+#[derive(Debug)]
+pub enum LoserOutcome {
+    /// It was stopped between two steps.
+    Cancelled,
+    /// It was already in the backend's part and finished anyway.
+    Finished,
+    Failed(TachyonError),
+}
+
+#[derive(Debug)]
+pub struct RaceOutcome<T> {
+    pub winner: Racer,
+    /// The transcript of the winner. The rest of the proof is `get_proof()`
+    /// of its prover.
+    pub transcript: T,
+    pub artifacts: ProofArtifacts,
+    pub loser: LoserOutcome,
+}
+
+type AttemptResult<T> = Result<(ProofArtifacts, T), TachyonError>;
+
+fn run_attempt<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+>(
+    attempt: Attempt<'_, P, Scheme::Curve, T>,
+    cancel: CancelToken,
+    job: &ProofJob<'_, Scheme::Scalar, ConcreteCircuit>,
+) -> AttemptResult<T> {
+    let Attempt {
+        prover,
+        pk,
+        mut options,
+        mut transcript,
+    } = attempt;
+    options.cancel = Some(cancel);
+    let artifacts = create_proof_with_options(
+        prover,
+        pk,
+        job.circuits,
+        job.instances,
+        &options,
+        &mut transcript,
+    )?;
+    Ok((artifacts, transcript))
+}
+
+/// Runs `a` and `b` on a thread each and keeps whichever finishes first,
+/// cancelling the other. If one fails, the other still gets to finish, and
+/// the error of the first failure is returned only if both do.
+pub fn race_prove<
+    Scheme: CommitmentScheme,
+    PA: TachyonProver<Scheme> + Send,
+    PB: TachyonProver<Scheme> + Send,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWriteState<Scheme::Curve, E> + Send,
+    ConcreteCircuit: Circuit<Scheme::Scalar> + Sync,
+>(
+    a: Attempt<'_, PA, Scheme::Curve, T>,
+    b: Attempt<'_, PB, Scheme::Curve, T>,
+    job: &ProofJob<'_, Scheme::Scalar, ConcreteCircuit>,
+) -> Result<RaceOutcome<T>, TachyonError>
+where
+    TachyonProvingKey<Scheme::Curve>: Send,
+    Scheme::Scalar: Sync,
+{
+    let cancels = [CancelToken::new(), CancelToken::new()];
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        {
+            let sender = sender.clone();
+            let cancel = cancels[0].clone();
+            scope.spawn(move || {
+                let _ = sender.send((Racer::A, run_attempt(a, cancel, job)));
+            });
+        }
+        {
+            let cancel = cancels[1].clone();
+            scope.spawn(move || {
+                let _ = sender.send((Racer::B, run_attempt(b, cancel, job)));
+            });
+        }
+
+        let (first, first_result) = receiver.recv().expect("an attempt panicked");
+        if first_result.is_ok() {
+            cancels[1 - first.index()].cancel();
+        }
+        let (second, second_result) = receiver.recv().expect("an attempt panicked");
+        match (first_result, second_result) {
+            (Ok((artifacts, transcript)), loser) => Ok(RaceOutcome {
+                winner: first,
+                transcript,
+                artifacts,
+                loser: match loser {
+                    Ok(_) => LoserOutcome::Finished,
+                    Err(TachyonError::Cancelled) => LoserOutcome::Cancelled,
+                    Err(e) => LoserOutcome::Failed(e),
+                },
+            }),
+            (Err(e), Ok((artifacts, transcript))) => Ok(RaceOutcome {
+                winner: second,
+                transcript,
+                artifacts,
+                loser: LoserOutcome::Failed(e),
+            }),
+            (Err(e), Err(_)) => Err(e),
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, verify_proof},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::VerifierSHPLONK,
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+    use super::{race_prove, Attempt, CancelToken, LoserOutcome, ProofJob, Racer};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver, TranscriptFork,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::TranscriptType,
+        error::TachyonError,
+        prover::{create_proof_with_options, ProofOptions},
+        rng::proof_context,
+    };
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_race_prove() {
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = vec![&public_inputs[..]];
+        let all_instances = vec![&instances[..]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let new_prover = || {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            prover
+        };
+        let options = |intra_proof_parallelism| ProofOptions {
+            rng_context: proof_context(b"simple", 0),
+            intra_proof_parallelism,
+            ..Default::default()
+        };
+
+        // A proof that is cancelled before it starts stops at the first step.
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let mut prover = new_prover();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut transcript = TachyonBlake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        assert!(matches!(
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &all_instances,
+                &ProofOptions {
+                    cancel: Some(cancel),
+                    ..options(1)
+                },
+                &mut transcript,
+            ),
+            Err(TachyonError::Cancelled)
+        ));
+
+        let mut prover_a = new_prover();
+        let mut prover_b = new_prover();
+        let mut pk_a = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut pk_b = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let transcript = TachyonBlake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let circuits = [circuit];
+        let outcome = race_prove(
+            Attempt {
+                prover: &mut prover_a,
+                pk: &mut pk_a,
+                options: options(1),
+                transcript: transcript.fork(),
+            },
+            Attempt {
+                prover: &mut prover_b,
+                pk: &mut pk_b,
+                options: options(4),
+                transcript,
+            },
+            &ProofJob {
+                circuits: &circuits,
+                instances: &all_instances,
+            },
+        )
+        .expect("proof generation should not fail");
+        assert!(matches!(
+            outcome.loser,
+            LoserOutcome::Cancelled | LoserOutcome::Finished
+        ));
+
+        let mut proof = outcome.transcript.finalize();
+        proof.extend_from_slice(&match outcome.winner {
+            Racer::A => prover_a.get_proof(),
+            Racer::B => prover_b.get_proof(),
+        });
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &all_instances,
+            &mut transcript,
+        )
+        .is_ok());
+    }
+}
@@ -0,0 +1,135 @@
+// `Debug` of the types in this crate summarizes instead of dumping: a column
+// prints its length, and its first and last few values only with the
+// "debug-values" feature. Anything secret, like a master key or a blind,
+// prints as `<redacted>` whatever the features. `DebugFull` is the explicit
+// way to get everything.
+
+use std::fmt;
+
+/// How many values at each end of a column `Debug` shows with the
+/// "debug-values" feature.
+pub const SHOWN_VALUES: usize = 3;
+
+/// Wraps a value so that `Debug` prints `<redacted>` instead of it.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Redacted<T>(pub T);
+
+impl<T> Redacted<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+/// A `Debug` that prints everything, including the values and secrets the
+/// type's `Debug` leaves out. Only use it where the output can't leak, e.g,
+/// to debug a test.
+pub trait DebugFull {
+    fn fmt_full(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    fn debug_full(&self) -> Full<'_, Self> {
+        Full(self)
+    }
+}
+
+/// See [`DebugFull::debug_full()`].
+pub struct Full<'a, T: ?Sized>(&'a T);
+
+impl<'a, T: DebugFull + ?Sized> fmt::Debug for Full<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_full(f)
+    }
+}
+
+/// Prints the values of a column of `len` values, which `get` returns by
+/// index. Unless `full`, only the first and last [`SHOWN_VALUES`] are
+/// printed, with `..` in between.
+pub(crate) struct Values<G> {
+    len: usize,
+    get: G,
+    full: bool,
+}
+
+impl<G> Values<G> {
+    pub(crate) fn summary(len: usize, get: G) -> Self {
+        Values {
+            len,
+            get,
+            full: false,
+        }
+    }
+
+    pub(crate) fn full(len: usize, get: G) -> Self {
+        Values {
+            len,
+            get,
+            full: true,
+        }
+    }
+}
+
+impl<G: Fn(usize) -> T, T: fmt::Debug> fmt::Debug for Values<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        if self.full || self.len <= 2 * SHOWN_VALUES {
+            list.entries((0..self.len).map(&self.get));
+        } else {
+            list.entries((0..SHOWN_VALUES).map(&self.get));
+            list.entry(&format_args!(".."));
+            list.entries((self.len - SHOWN_VALUES..self.len).map(&self.get));
+        }
+        list.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DebugFull, Redacted, Values};
+    use std::fmt;
+
+    struct Secret([u8; 4]);
+
+    impl fmt::Debug for Secret {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Secret")
+                .field("key", &Redacted(self.0))
+                .finish()
+        }
+    }
+
+    impl DebugFull for Secret {
+        fn fmt_full(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Secret").field("key", &self.0).finish()
+        }
+    }
+
+    #[test]
+    fn test_redacted() {
+        let secret = Secret([1, 2, 3, 4]);
+        assert_eq!(format!("{:?}", secret), "Secret { key: <redacted> }");
+        assert_eq!(
+            format!("{:?}", secret.debug_full()),
+            "Secret { key: [1, 2, 3, 4] }"
+        );
+        assert_eq!(format!("{:?}", Some(Redacted(7))), "Some(<redacted>)");
+    }
+
+    #[test]
+    fn test_values() {
+        let square = |i: usize| i * i;
+        assert_eq!(
+            format!("{:?}", Values::summary(10, square)),
+            "[0, 1, 4, .., 49, 64, 81]"
+        );
+        assert_eq!(format!("{:?}", Values::summary(3, square)), "[0, 1, 4]");
+        assert_eq!(
+            format!("{:?}", Values::full(7, square)),
+            "[0, 1, 4, 9, 16, 25, 36]"
+        );
+    }
+}
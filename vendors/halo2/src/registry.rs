@@ -0,0 +1,302 @@
+// A service that proves for many circuits loads their keys once and keeps
+// them for its whole lifetime. `PkRegistry` lets it replace the key of a
+// circuit while it runs: a proof takes the key it starts with out of the
+// registry, so a swap only changes what later proofs get, and the old key is
+// dropped, and its memory freed by the backend, once the last proof with it
+// finishes. The backend keeps a key's polynomials and cosets in the key
+// itself and frees them when it is destroyed, so nothing else has to be told
+// about a swap. The buffers a prover pools are sized by its domain rather
+// than by a key and are reused across keys; `TachyonProver::trim_pool()`
+// releases them.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use halo2curves::CurveAffine;
+
+use crate::bn254::ProvingKey as TachyonProvingKey;
+
+/// A proving key shared by the proofs running with it. The backend updates a
+/// key while proving with it, which is why proofs take `&mut`, so proofs with
+/// the same key take turns through the mutex.
+pub type SharedProvingKey<C> = Arc<Mutex<TachyonProvingKey<C>>>;
+
+struct Entry<C: CurveAffine> {
+    pk: SharedProvingKey<C>,
+    /// When [`PkRegistry::get()`] last handed the key out, or when it was
+    /// registered.
+    last_used: Instant,
+}
+
+impl<C: CurveAffine> Entry<C> {
+    fn new(pk: TachyonProvingKey<C>) -> Self {
+        Entry {
+            pk: Arc::new(Mutex::new(pk)),
+            last_used: Instant::now(),
+        }
+    }
+}
+
+/// The proving keys of a service, by circuit id, e.g, as computed by
+/// [`crate::circuit_id::circuit_id()`] or any other id the service names its
+/// circuits by. It can be shared between threads.
+pub struct PkRegistry<C: CurveAffine> {
+    entries: Mutex<HashMap<[u8; 32], Entry<C>>>,
+}
+
+impl<C: CurveAffine> fmt::Debug for PkRegistry<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PkRegistry")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<C: CurveAffine> Default for PkRegistry<C> {
+    fn default() -> Self {
+        PkRegistry {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<C: CurveAffine> PkRegistry<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<[u8; 32], Entry<C>>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Registers `pk` under `circuit_id`. If a key is registered under it
+    /// already, `pk` is handed back; replace that one with
+    /// [`PkRegistry::swap()`].
+    pub fn insert(
+        &self,
+        circuit_id: [u8; 32],
+        pk: TachyonProvingKey<C>,
+    ) -> Result<SharedProvingKey<C>, TachyonProvingKey<C>> {
+        let mut entries = self.entries();
+        if entries.contains_key(&circuit_id) {
+            return Err(pk);
+        }
+        let entry = Entry::new(pk);
+        let pk = entry.pk.clone();
+        entries.insert(circuit_id, entry);
+        Ok(pk)
+    }
+
+    /// Returns the key registered under `circuit_id`. Hold on to it for the
+    /// whole proof, so that the proof finishes with it whatever is swapped in
+    /// meanwhile.
+    pub fn get(&self, circuit_id: &[u8; 32]) -> Option<SharedProvingKey<C>> {
+        let mut entries = self.entries();
+        let entry = entries.get_mut(circuit_id)?;
+        entry.last_used = Instant::now();
+        Some(entry.pk.clone())
+    }
+
+    /// Replaces the key registered under `circuit_id` with `pk`, and returns
+    /// the key it replaces. Proofs that got the old key before finish with
+    /// it, and it is freed once the last of them and the caller drop it.
+    /// If no key is registered under `circuit_id`, `pk` is handed back.
+    pub fn swap(
+        &self,
+        circuit_id: &[u8; 32],
+        pk: TachyonProvingKey<C>,
+    ) -> Result<SharedProvingKey<C>, TachyonProvingKey<C>> {
+        match self.entries().get_mut(circuit_id) {
+            Some(entry) => Ok(std::mem::replace(entry, Entry::new(pk)).pk),
+            None => Err(pk),
+        }
+    }
+
+    /// Unregisters the key of `circuit_id` and returns it. Proofs holding it
+    /// still finish with it.
+    pub fn remove(&self, circuit_id: &[u8; 32]) -> Option<SharedProvingKey<C>> {
+        self.entries().remove(circuit_id).map(|entry| entry.pk)
+    }
+
+    /// Unregisters every key that [`PkRegistry::get()`] hasn't handed out for
+    /// `idle` and that no proof holds, which frees it, and returns their
+    /// circuit ids. Register a key again to prove with it.
+    pub fn evict_idle(&self, idle: Duration) -> Vec<[u8; 32]> {
+        let mut evicted = vec![];
+        self.entries().retain(|circuit_id, entry| {
+            let unused = entry.last_used.elapsed() >= idle && Arc::strong_count(&entry.pk) == 1;
+            if unused {
+                evicted.push(*circuit_id);
+            }
+            !unused
+        });
+        evicted.sort_unstable();
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{mpsc, Arc},
+        thread,
+        time::Duration,
+    };
+
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::{Blake2bRead, TranscriptWriterBuffer},
+        SerdeFormat,
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+    use super::{PkRegistry, SharedProvingKey};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuit_id::circuit_id,
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{AbsorbOrder, InstanceAbsorption, TranscriptType},
+        instance_hash::verify_proof,
+        prover::{create_proof_with_options, ProofOptions},
+        proving_key::Metadata,
+    };
+
+    #[test]
+    fn test_registry_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<PkRegistry<G1Affine>>();
+        assert_send_sync::<SharedProvingKey<G1Affine>>();
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_swap_during_proofs() {
+        let k = 4;
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        // The upgrade changes the constant, and so the fixed column and the
+        // verifying key.
+        let circuit = |constant: u64| SimpleCircuit {
+            constant: Fr::from(constant),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let instance = |constant: u64| vec![Fr::from(constant * 2 * 2 * 3 * 3)];
+        let keygen = |constant| {
+            let pk = keygen_pk2(&params, &circuit(constant)).expect("pk should not fail");
+            let mut pk_bytes: Vec<u8> = vec![];
+            pk.write(&mut pk_bytes, SerdeFormat::RawBytesUnchecked)
+                .unwrap();
+            (
+                pk,
+                TachyonProvingKey::<G1Affine>::try_from(pk_bytes.as_slice()).unwrap(),
+            )
+        };
+        let (old_pk, old_tachyon_pk) = keygen(7);
+        let (new_pk, new_tachyon_pk) = keygen(8);
+        assert_ne!(circuit_id(old_pk.get_vk()), circuit_id(new_pk.get_vk()));
+
+        let prove = |pk: &SharedProvingKey<G1Affine>, constant: u64| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            let instance = instance(constant);
+            create_proof_with_options(
+                &mut prover,
+                &mut pk.lock().unwrap(),
+                &[circuit(constant)],
+                &[&[&instance[..]]],
+                &ProofOptions::default(),
+                &mut transcript,
+            )
+            .unwrap();
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+
+        let id = [1; 32];
+        let registry = PkRegistry::new();
+        registry.insert(id, old_tachyon_pk).unwrap();
+        let (held_tx, held_rx) = mpsc::channel();
+        let (swapped_tx, swapped_rx) = mpsc::channel();
+        let mut proofs = thread::scope(|scope| {
+            // A proof on another thread takes the key, and only proves with it
+            // once the key is swapped.
+            let (registry, prove) = (&registry, &prove);
+            let in_flight = scope.spawn(move || {
+                let pk = registry.get(&id).unwrap();
+                held_tx.send(()).unwrap();
+                swapped_rx.recv().unwrap();
+                prove(&pk, 7)
+            });
+            held_rx.recv().unwrap();
+            // A held key isn't evicted, however long it has been idle.
+            assert!(registry.evict_idle(Duration::ZERO).is_empty());
+            assert_eq!(registry.len(), 1);
+
+            let replaced = registry.swap(&id, new_tachyon_pk).unwrap();
+            swapped_tx.send(()).unwrap();
+            // New requests get the new key, while the proof in flight
+            // finishes with the old one.
+            let new_proof = prove(&registry.get(&id).unwrap(), 8);
+            let old_proof = in_flight.join().unwrap();
+            // The proof in flight dropped the old key, which is freed with the
+            // last handle to it.
+            assert_eq!(Arc::strong_count(&replaced), 1);
+            vec![(7, old_proof), (8, new_proof)]
+        });
+        proofs.push((8, prove(&registry.get(&id).unwrap(), 8)));
+
+        for (constant, proof) in &proofs {
+            let (vk, other) = match constant {
+                7 => (old_pk.get_vk(), new_pk.get_vk()),
+                _ => (new_pk.get_vk(), old_pk.get_vk()),
+            };
+            let instance = instance(*constant);
+            let verify = |vk| {
+                verify_proof::<Blake2bRead<_, _, _>>(
+                    &params,
+                    vk,
+                    &Metadata::new(),
+                    InstanceAbsorption::PerValue,
+                    AbsorbOrder::PhaseThenColumn,
+                    &[&[&instance[..]]],
+                    proof,
+                )
+            };
+            assert!(verify(vk).is_ok());
+            assert!(verify(other).is_err());
+        }
+
+        // Only the new key is left, and it is evicted once it goes unused.
+        assert_eq!(registry.len(), 1);
+        let held = registry.get(&id).unwrap();
+        assert!(registry.evict_idle(Duration::ZERO).is_empty());
+        drop(held);
+        assert_eq!(registry.evict_idle(Duration::from_secs(3600)), vec![]);
+        assert_eq!(registry.evict_idle(Duration::ZERO), vec![id]);
+        assert!(registry.is_empty());
+        assert!(registry.get(&id).is_none());
+    }
+}
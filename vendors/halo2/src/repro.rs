@@ -0,0 +1,1105 @@
+// Captures a proof job into a single file that recreates the proof without
+// the circuit, so that a proof that fails to verify can be replayed from a
+// bug report. The file is a gzipped tar archive of sections, each named
+// `<name>.v<version>` so that one can change its encoding without the others:
+//
+//   VERSION        the version of the archive as a whole, "1"
+//   job.v1         k, transcript, multi-open scheme, RNG seed and the SHA-256
+//                  of the key and of the proof, encoded like the metadata of a
+//                  proving key
+//   instances.v1   u32 circuits, then for each circuit u32 columns, then for
+//                  each column u32 values and the values
+//   witness.v1     u32 columns, then for each column u32 circuit, u32 column,
+//                  u32 values and the values
+//   proof.v1       the expected proof
+//   pk.v1          the proving key, if embedded
+//   layout.v1      the point encoding and the transcript layout of the proof,
+//                  one item per line, if known
+//
+// Integers are little-endian and values are 32 bytes little-endian. The
+// witness is every advice column as evaluated, before the last row is
+// blinded.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use halo2_proofs::{
+    poly::{commitment::CommitmentScheme, kzg::commitment::KZGCommitmentScheme},
+    transcript::{Challenge255, TranscriptWriterBuffer},
+};
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use rand_core::SeedableRng;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    audit::{from_hex, item_label, to_hex, AuditLayout},
+    bn254::{
+        AnyProver, Blake2bWrite as TachyonBlake2bWrite, PoseidonWrite as TachyonPoseidonWrite,
+        ProvingKey as TachyonProvingKey, Sha256Write as TachyonSha256Write, TachyonProver,
+        TranscriptWriteState,
+    },
+    consts::{MultiOpenScheme, TranscriptType},
+    error::TachyonError,
+    proof::PointEncoding,
+    prover::{prepare_instances, ProofOptions, ProofSteps, WitnessOnly},
+    proving_key::{decode_metadata, encode_metadata, Metadata},
+    serde_fr::{fr_from_le_bytes, fr_to_le_bytes, FR_BYTES},
+    transcript::{Component, TranscriptEntry, TranscriptItem, TranscriptLog},
+    version::check_backend,
+    xor_shift_rng::XORShiftRng as TachyonXORShiftRng,
+};
+
+const FORMAT_VERSION: &str = "1";
+const VERSION_SECTION: &str = "VERSION";
+const SECTION_VERSION: u32 = 1;
+
+/// What [`crate::prover::ProofOptions::capture_repro`] writes, and where.
+#[derive(Clone, Debug)]
+pub struct ReproCapture {
+    pub path: PathBuf,
+    /// The transcript the proof is written with, which the prover doesn't
+    /// know.
+    pub transcript_type: TranscriptType,
+    /// The bytes the proving key was loaded from. Only their SHA-256 is
+    /// recorded, unless `embed_pk` is set.
+    pub pk_bytes: Vec<u8>,
+    pub embed_pk: bool,
+    /// Lets [`replay()`] name the value a replayed proof diverges in, rather
+    /// than only the offset.
+    pub layout: Option<AuditLayout>,
+}
+
+/// The values of an advice column of a circuit, as committed to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessColumn {
+    pub circuit: usize,
+    pub column: usize,
+    pub values: Vec<Fr>,
+}
+
+/// The witness of a proof as a repro file records it, with the number of
+/// rows of the circuit, so that two runs can be compared, see
+/// [`crate::debug::diff_witness()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportedWitness {
+    pub n: usize,
+    pub columns: Vec<WitnessColumn>,
+}
+
+impl ExportedWitness {
+    pub fn from_job(job: &ReproJob) -> Self {
+        ExportedWitness {
+            n: 1 << job.k,
+            columns: job.witness.clone(),
+        }
+    }
+
+    /// Reads the witness of the repro file at `path`.
+    pub fn read(path: &Path) -> Result<Self, ReproError> {
+        Ok(Self::from_job(&read(path)?))
+    }
+
+    /// The SHA-256 of `n` and of the columns in the encoding of the witness
+    /// section. Two witnesses are equal iff their hashes are.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut bytes = vec![];
+        put_u32(&mut bytes, self.n);
+        bytes.extend(encode_witness(&self.columns));
+        sha256(&bytes)
+    }
+}
+
+/// Everything in a repro file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReproJob {
+    pub k: u32,
+    pub transcript_type: TranscriptType,
+    pub multi_open_scheme: MultiOpenScheme,
+    /// See [`crate::prover::ProofOptions::rng_context`]. Only the seed is
+    /// needed to replay the proof, but the context says which proof it was.
+    pub rng_context: Vec<u8>,
+    pub seed: [u8; 16],
+    pub intra_proof_parallelism: usize,
+    pub pk_sha256: [u8; 32],
+    /// The circuit id the proving key records, see
+    /// [`crate::bn254::ProvingKey::circuit_id()`].
+    pub circuit_id: Option<[u8; 32]>,
+    pub pk: Option<Vec<u8>>,
+    /// The values of each instance column of each circuit.
+    pub instances: Vec<Vec<Vec<Fr>>>,
+    pub witness: Vec<WitnessColumn>,
+    /// The finalized transcript followed by the backend's proof.
+    pub proof: Vec<u8>,
+    pub layout: Option<AuditLayout>,
+}
+
+#[derive(Debug)]
+pub enum ReproError {
+    Io(io::Error),
+    /// The archive, or one of its sections, has a version this crate can't
+    /// read.
+    UnsupportedVersion {
+        section: String,
+        version: String,
+    },
+    MissingSection(&'static str),
+    Malformed {
+        section: &'static str,
+        reason: String,
+    },
+    /// The file doesn't embed the proving key, so it has to be passed to
+    /// [`replay_with_pk()`].
+    MissingPk,
+    /// The proving key isn't the one the proof was captured with.
+    PkMismatch {
+        expected: [u8; 32],
+        found: [u8; 32],
+    },
+    /// The prover passed to [`replay()`] has another `setting` than the
+    /// captured proof.
+    ProverMismatch {
+        setting: &'static str,
+        job: String,
+        prover: String,
+    },
+    /// Replaying the proof failed.
+    Proof(TachyonError),
+}
+
+impl fmt::Display for ReproError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReproError::Io(e) => write!(f, "failed to read the repro file: {}", e),
+            ReproError::UnsupportedVersion { section, version } => {
+                write!(f, "unsupported version {} of {}", version, section)
+            }
+            ReproError::MissingSection(section) => write!(f, "missing section {}", section),
+            ReproError::Malformed { section, reason } => {
+                write!(f, "malformed section {}: {}", section, reason)
+            }
+            ReproError::MissingPk => write!(f, "the repro file doesn't embed the proving key"),
+            ReproError::PkMismatch { expected, found } => write!(
+                f,
+                "the proving key has SHA-256 {} but the proof was captured with {}",
+                to_hex(found),
+                to_hex(expected)
+            ),
+            ReproError::ProverMismatch {
+                setting,
+                job,
+                prover,
+            } => write!(
+                f,
+                "the proof was captured with {} {} but the prover has {}",
+                setting, job, prover
+            ),
+            ReproError::Proof(e) => write!(f, "failed to replay the proof: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReproError {}
+
+impl From<io::Error> for ReproError {
+    fn from(e: io::Error) -> Self {
+        ReproError::Io(e)
+    }
+}
+
+fn malformed(section: &'static str, reason: impl Into<String>) -> ReproError {
+    ReproError::Malformed {
+        section,
+        reason: reason.into(),
+    }
+}
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+fn encoding_name(encoding: PointEncoding) -> &'static str {
+    match encoding {
+        PointEncoding::Compressed => "compressed",
+        PointEncoding::Uncompressed => "uncompressed",
+    }
+}
+
+fn put_u32(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend_from_slice(&(value as u32).to_le_bytes());
+}
+
+fn put_values(bytes: &mut Vec<u8>, values: &[Fr]) {
+    put_u32(bytes, values.len());
+    for value in values {
+        bytes.extend_from_slice(&fr_to_le_bytes(value));
+    }
+}
+
+fn encode_witness(columns: &[WitnessColumn]) -> Vec<u8> {
+    let mut bytes = vec![];
+    put_u32(&mut bytes, columns.len());
+    for column in columns {
+        put_u32(&mut bytes, column.circuit);
+        put_u32(&mut bytes, column.column);
+        put_values(&mut bytes, &column.values);
+    }
+    bytes
+}
+
+/// Reads the integers and values of a binary section.
+struct SectionReader<'a> {
+    section: &'static str,
+    bytes: &'a [u8],
+}
+
+impl<'a> SectionReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ReproError> {
+        if self.bytes.len() < len {
+            return Err(malformed(self.section, "truncated"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<usize, ReproError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    fn values(&mut self) -> Result<Vec<Fr>, ReproError> {
+        let len = self.u32()?;
+        let mut values = Vec::with_capacity(len.min(self.bytes.len() / FR_BYTES));
+        for _ in 0..len {
+            let bytes = self.take(FR_BYTES)?.try_into().unwrap();
+            values
+                .push(fr_from_le_bytes(bytes).map_err(|e| malformed(self.section, e.to_string()))?);
+        }
+        Ok(values)
+    }
+
+    fn finish(self) -> Result<(), ReproError> {
+        if !self.bytes.is_empty() {
+            return Err(malformed(self.section, "trailing bytes"));
+        }
+        Ok(())
+    }
+}
+
+impl ReproJob {
+    fn job_section(&self) -> Vec<u8> {
+        let mut job = Metadata::new();
+        for (key, value) in [
+            ("k", self.k.to_string()),
+            ("transcript", self.transcript_type.name().to_string()),
+            (
+                "multi_open_scheme",
+                self.multi_open_scheme.name().to_string(),
+            ),
+            ("rng_context", to_hex(&self.rng_context)),
+            ("seed", to_hex(&self.seed)),
+            (
+                "intra_proof_parallelism",
+                self.intra_proof_parallelism.to_string(),
+            ),
+            ("pk_sha256", to_hex(&self.pk_sha256)),
+            // Redundant with the proof, but lets the expectation be checked
+            // without unpacking it.
+            ("proof_sha256", to_hex(&sha256(&self.proof))),
+        ] {
+            job.insert(key.to_string(), value);
+        }
+        if let Some(circuit_id) = &self.circuit_id {
+            job.insert("circuit_id".to_string(), to_hex(circuit_id));
+        }
+        encode_metadata(&job)
+    }
+
+    fn instances_section(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        put_u32(&mut bytes, self.instances.len());
+        for columns in &self.instances {
+            put_u32(&mut bytes, columns.len());
+            for values in columns {
+                put_values(&mut bytes, values);
+            }
+        }
+        bytes
+    }
+
+    fn sections(&self) -> Vec<(String, Vec<u8>)> {
+        let section = |name: &str| format!("{}.v{}", name, SECTION_VERSION);
+        let mut sections = vec![
+            (
+                VERSION_SECTION.to_string(),
+                FORMAT_VERSION.as_bytes().to_vec(),
+            ),
+            (section("job"), self.job_section()),
+            (section("instances"), self.instances_section()),
+            (section("witness"), encode_witness(&self.witness)),
+            (section("proof"), self.proof.clone()),
+        ];
+        if let Some(pk) = &self.pk {
+            sections.push((section("pk"), pk.clone()));
+        }
+        if let Some(layout) = &self.layout {
+            let mut lines = vec![encoding_name(layout.encoding).to_string()];
+            lines.extend(layout.items.iter().map(|item| {
+                format!(
+                    "{} {} {}",
+                    item.component.name(),
+                    item.circuit
+                        .map_or_else(|| "-".to_string(), |circuit| circuit.to_string()),
+                    item.index
+                )
+            }));
+            sections.push((section("layout"), lines.join("\n").into_bytes()));
+        }
+        sections
+    }
+
+    pub(crate) fn from_sections(sections: &BTreeMap<String, Vec<u8>>) -> Result<Self, ReproError> {
+        match sections.get(VERSION_SECTION) {
+            Some(version) if version.as_slice() == FORMAT_VERSION.as_bytes() => {}
+            Some(version) => {
+                return Err(ReproError::UnsupportedVersion {
+                    section: VERSION_SECTION.to_string(),
+                    version: String::from_utf8_lossy(version).into_owned(),
+                })
+            }
+            None => return Err(ReproError::MissingSection(VERSION_SECTION)),
+        }
+        let required =
+            |name: &'static str| section(sections, name)?.ok_or(ReproError::MissingSection(name));
+
+        let job = decode_metadata(required("job")?).map_err(|e| malformed("job", e.to_string()))?;
+        let field = |key: &str| {
+            job.get(key)
+                .map(String::as_str)
+                .ok_or_else(|| malformed("job", format!("missing {}", key)))
+        };
+        let invalid = |key: &str| malformed("job", format!("invalid {}", key));
+        let hex_field = |key: &str| from_hex(field(key)?).ok_or_else(|| invalid(key));
+
+        let mut reader = SectionReader {
+            section: "instances",
+            bytes: required("instances")?,
+        };
+        let mut instances = vec![];
+        for _ in 0..reader.u32()? {
+            let mut columns = vec![];
+            for _ in 0..reader.u32()? {
+                columns.push(reader.values()?);
+            }
+            instances.push(columns);
+        }
+        reader.finish()?;
+
+        let mut reader = SectionReader {
+            section: "witness",
+            bytes: required("witness")?,
+        };
+        let mut witness = vec![];
+        for _ in 0..reader.u32()? {
+            witness.push(WitnessColumn {
+                circuit: reader.u32()?,
+                column: reader.u32()?,
+                values: reader.values()?,
+            });
+        }
+        reader.finish()?;
+
+        let proof = required("proof")?.to_vec();
+        if hex_field("proof_sha256")? != sha256(&proof) {
+            return Err(malformed("proof", "doesn't match proof_sha256 of the job"));
+        }
+
+        let layout = section(sections, "layout")?
+            .map(|layout| {
+                let layout =
+                    std::str::from_utf8(layout).map_err(|_| malformed("layout", "not UTF-8"))?;
+                let mut lines = layout.lines();
+                let encoding = match lines.next() {
+                    Some("compressed") => PointEncoding::Compressed,
+                    Some("uncompressed") => PointEncoding::Uncompressed,
+                    _ => return Err(malformed("layout", "unknown point encoding")),
+                };
+                let items = lines
+                    .map(|line| {
+                        let invalid = || malformed("layout", format!("invalid item {:?}", line));
+                        let (component, circuit, index) =
+                            match line.split(' ').collect::<Vec<_>>()[..] {
+                                [component, circuit, index] => (component, circuit, index),
+                                _ => return Err(invalid()),
+                            };
+                        Ok(TranscriptItem::new(
+                            Component::from_name(component).ok_or_else(invalid)?,
+                            match circuit {
+                                "-" => None,
+                                circuit => Some(circuit.parse().map_err(|_| invalid())?),
+                            },
+                            index.parse().map_err(|_| invalid())?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(AuditLayout { items, encoding })
+            })
+            .transpose()?;
+
+        Ok(ReproJob {
+            k: field("k")?.parse().map_err(|_| invalid("k"))?,
+            transcript_type: TranscriptType::from_name(field("transcript")?)
+                .ok_or_else(|| invalid("transcript"))?,
+            multi_open_scheme: MultiOpenScheme::from_name(field("multi_open_scheme")?)
+                .ok_or_else(|| invalid("multi_open_scheme"))?,
+            rng_context: hex_field("rng_context")?,
+            seed: hex_field("seed")?.try_into().map_err(|_| invalid("seed"))?,
+            intra_proof_parallelism: field("intra_proof_parallelism")?
+                .parse()
+                .map_err(|_| invalid("intra_proof_parallelism"))?,
+            pk_sha256: hex_field("pk_sha256")?
+                .try_into()
+                .map_err(|_| invalid("pk_sha256"))?,
+            circuit_id: job
+                .get("circuit_id")
+                .map(|hex| {
+                    from_hex(hex)
+                        .and_then(|id| id.try_into().ok())
+                        .ok_or_else(|| invalid("circuit_id"))
+                })
+                .transpose()?,
+            pk: section(sections, "pk")?.map(<[u8]>::to_vec),
+            instances,
+            witness,
+            proof,
+            layout,
+        })
+    }
+}
+
+/// Returns the section `name` of any version, or fails if its version isn't
+/// the one this crate writes.
+fn section<'a>(
+    sections: &'a BTreeMap<String, Vec<u8>>,
+    name: &'static str,
+) -> Result<Option<&'a [u8]>, ReproError> {
+    for (key, bytes) in sections {
+        let version = match key
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix(".v"))
+        {
+            Some(version) => version,
+            None => continue,
+        };
+        if version != SECTION_VERSION.to_string() {
+            return Err(ReproError::UnsupportedVersion {
+                section: name.to_string(),
+                version: version.to_string(),
+            });
+        }
+        return Ok(Some(bytes));
+    }
+    Ok(None)
+}
+
+pub(crate) fn write_sections(path: &Path, sections: &[(String, Vec<u8>)]) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for (name, bytes) in sections {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, name, bytes.as_slice())?;
+    }
+    builder.into_inner()?.finish()?.flush()
+}
+
+pub(crate) fn read_sections(path: &Path) -> io::Result<BTreeMap<String, Vec<u8>>> {
+    decode_sections(BufReader::new(File::open(path)?))
+}
+
+/// Reads the sections of a repro file from `reader`.
+pub(crate) fn decode_sections<R: Read>(reader: R) -> io::Result<BTreeMap<String, Vec<u8>>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(reader));
+    let mut sections = BTreeMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut bytes = vec![];
+        entry.read_to_end(&mut bytes)?;
+        sections.insert(name, bytes);
+    }
+    Ok(sections)
+}
+
+/// Writes `job` to a repro file at `path`.
+pub fn capture(path: &Path, job: &ReproJob) -> io::Result<()> {
+    write_sections(path, &job.sections())
+}
+
+/// Reads the repro file at `path`.
+pub fn read(path: &Path) -> Result<ReproJob, ReproError> {
+    ReproJob::from_sections(&read_sections(path)?)
+}
+
+/// Captures a finished proof for [`ProofOptions::capture_repro`]. `witness`
+/// is from [`ProofSteps::take_captured_witness()`] and `log` records the
+/// values the Rust side wrote to the transcript, which are the start of the
+/// proof.
+pub(crate) fn capture_proof<Scheme: CommitmentScheme, P: TachyonProver<Scheme>>(
+    capture_repro: &ReproCapture,
+    prover: &P,
+    instances: &[&[&[Scheme::Scalar]]],
+    options: &ProofOptions,
+    seed: [u8; 16],
+    circuit_id: Option<[u8; 32]>,
+    witness: Vec<WitnessColumn>,
+    log: &TranscriptLog<Scheme::Curve>,
+) -> Result<(), TachyonError> {
+    let encoding = PointEncoding::for_transcript(&capture_repro.transcript_type);
+    let mut proof = vec![];
+    for entry in log {
+        match entry {
+            TranscriptEntry::WritePoint(point) => {
+                let point = unsafe { std::mem::transmute::<_, &G1Affine>(point) };
+                encoding.encode(point, &mut proof);
+            }
+            TranscriptEntry::WriteScalar(scalar) => {
+                let scalar = unsafe { std::mem::transmute::<_, &Fr>(scalar) };
+                proof.extend_from_slice(&fr_to_le_bytes(scalar));
+            }
+            _ => {}
+        }
+    }
+    proof.extend_from_slice(&prover.get_proof());
+
+    let instances = instances
+        .iter()
+        .map(|columns| {
+            columns
+                .iter()
+                .map(|values| unsafe { std::mem::transmute::<_, &[Fr]>(*values) }.to_vec())
+                .collect()
+        })
+        .collect();
+    let job = ReproJob {
+        k: prover.k(),
+        transcript_type: capture_repro.transcript_type,
+        multi_open_scheme: prover.multi_open_scheme(),
+        rng_context: options.rng_context.clone(),
+        seed,
+        intra_proof_parallelism: options.intra_proof_parallelism,
+        pk_sha256: sha256(&capture_repro.pk_bytes),
+        circuit_id,
+        pk: capture_repro
+            .embed_pk
+            .then(|| capture_repro.pk_bytes.clone()),
+        instances,
+        witness,
+        proof,
+        layout: capture_repro.layout.clone(),
+    };
+    capture(&capture_repro.path, &job).map_err(TachyonError::CaptureRepro)
+}
+
+/// The prover [`replay()`] recreates a proof with.
+pub enum ReplayProver<'a> {
+    /// A prover with the k and the multi-open scheme of the captured proof.
+    /// Its transcript can't be checked, so a prover for another one makes
+    /// the proof diverge after the Rust side.
+    Prover(&'a mut AnyProver<KZGCommitmentScheme<Bn256>>),
+    /// Params written with `ParamsKZG::write()`, which a prover for the
+    /// captured settings is created from.
+    Params(&'a [u8]),
+}
+
+impl<'a> fmt::Debug for ReplayProver<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayProver::Prover(prover) => f.debug_tuple("Prover").field(prover).finish(),
+            ReplayProver::Params(params) => f
+                .debug_struct("Params")
+                .field("len", &params.len())
+                .finish(),
+        }
+    }
+}
+
+impl<'a> From<&'a mut AnyProver<KZGCommitmentScheme<Bn256>>> for ReplayProver<'a> {
+    fn from(prover: &'a mut AnyProver<KZGCommitmentScheme<Bn256>>) -> Self {
+        ReplayProver::Prover(prover)
+    }
+}
+
+impl<'a> From<&'a [u8]> for ReplayProver<'a> {
+    fn from(params: &'a [u8]) -> Self {
+        ReplayProver::Params(params)
+    }
+}
+
+/// Where a replayed proof first differs from the captured one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The offset of the first byte that differs, or the length of the
+    /// shorter proof if it is a prefix of the other.
+    pub offset: usize,
+    /// The value that byte belongs to, if the file has a layout.
+    pub item: Option<TranscriptItem>,
+    /// The bytes of that value in the captured proof, or the 32 bytes from
+    /// `offset` if it isn't known.
+    pub expected: Vec<u8>,
+    /// The same bytes of the replayed proof.
+    pub actual: Vec<u8>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the replayed proof diverges at offset {}", self.offset)?;
+        if let Some(item) = &self.item {
+            write!(f, " in {}", item_label(item))?;
+        }
+        write!(
+            f,
+            ": expected {}, got {}",
+            to_hex(&self.expected),
+            to_hex(&self.actual)
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayOutcome {
+    /// The replayed proof is the captured one.
+    Match,
+    Diverged(Divergence),
+}
+
+/// Recreates the proof in the repro file at `path` with the key embedded in
+/// it, and compares it with the captured one. A divergence is also logged as
+/// a warning.
+pub fn replay<'a>(
+    path: &Path,
+    prover: impl Into<ReplayProver<'a>>,
+) -> Result<ReplayOutcome, ReproError> {
+    let job = read(path)?;
+    let pk = job.pk.as_deref().ok_or(ReproError::MissingPk)?;
+    replay_job(&job, prover.into(), pk)
+}
+
+/// Same as [`replay()`], but with the proving key in `pk_bytes`, which has to
+/// be the one the proof was captured with.
+pub fn replay_with_pk<'a>(
+    path: &Path,
+    prover: impl Into<ReplayProver<'a>>,
+    pk_bytes: &[u8],
+) -> Result<ReplayOutcome, ReproError> {
+    replay_job(&read(path)?, prover.into(), pk_bytes)
+}
+
+fn replay_job(
+    job: &ReproJob,
+    prover: ReplayProver<'_>,
+    pk_bytes: &[u8],
+) -> Result<ReplayOutcome, ReproError> {
+    let found = sha256(pk_bytes);
+    if found != job.pk_sha256 {
+        return Err(ReproError::PkMismatch {
+            expected: job.pk_sha256,
+            found,
+        });
+    }
+    check_backend().map_err(ReproError::Proof)?;
+
+    let mut created;
+    let prover = match prover {
+        ReplayProver::Prover(prover) => {
+            if prover.k() != job.k {
+                return Err(ReproError::ProverMismatch {
+                    setting: "k",
+                    job: job.k.to_string(),
+                    prover: prover.k().to_string(),
+                });
+            }
+            if prover.multi_open_scheme() != job.multi_open_scheme {
+                return Err(ReproError::ProverMismatch {
+                    setting: "multi-open scheme",
+                    job: job.multi_open_scheme.name().to_string(),
+                    prover: prover.multi_open_scheme().name().to_string(),
+                });
+            }
+            prover
+        }
+        ReplayProver::Params(params) => {
+            created = AnyProver::try_from_params(
+                job.multi_open_scheme,
+                job.transcript_type as u8,
+                job.k,
+                params,
+            )
+            .map_err(ReproError::Proof)?;
+            &mut created
+        }
+    };
+    let n = prover.n() as usize;
+    for column in &job.witness {
+        if column.circuit >= job.instances.len() || column.values.len() > n {
+            return Err(malformed(
+                "witness",
+                format!(
+                    "column {} of circuit {} doesn't fit {} circuits of {} rows",
+                    column.column,
+                    column.circuit,
+                    job.instances.len(),
+                    n
+                ),
+            ));
+        }
+    }
+
+    let mut pk = TachyonProvingKey::<G1Affine>::try_from(pk_bytes)
+        .map_err(|e| malformed("proving key", e.to_string()))?;
+    prover.set_intra_proof_parallelism(job.intra_proof_parallelism.max(1));
+    let proof = match job.transcript_type {
+        TranscriptType::Blake2b => prove_job(
+            prover,
+            &mut pk,
+            job,
+            TachyonBlake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]),
+            TachyonBlake2bWrite::finalize,
+        ),
+        TranscriptType::Poseidon => prove_job(
+            prover,
+            &mut pk,
+            job,
+            TachyonPoseidonWrite::<_, G1Affine, Challenge255<_>>::init(vec![]),
+            TachyonPoseidonWrite::finalize,
+        ),
+        TranscriptType::Sha256 => prove_job(
+            prover,
+            &mut pk,
+            job,
+            TachyonSha256Write::<_, G1Affine, Challenge255<_>>::init(vec![]),
+            TachyonSha256Write::finalize,
+        ),
+        // The job doesn't record which registered hasher the proof was
+        // created with.
+        TranscriptType::Custom => {
+            return Err(malformed(
+                "job",
+                "a proof with a custom transcript can't be replayed",
+            ))
+        }
+    }
+    .map_err(ReproError::Proof)?;
+
+    let outcome = compare(&job.proof, &proof, job.layout.as_ref());
+    if let ReplayOutcome::Diverged(divergence) = &outcome {
+        log::warn!("{}", divergence);
+    }
+    Ok(outcome)
+}
+
+fn prove_job<T: TranscriptWriteState<G1Affine, Challenge255<G1Affine>>>(
+    prover: &mut AnyProver<KZGCommitmentScheme<Bn256>>,
+    pk: &mut TachyonProvingKey<G1Affine>,
+    job: &ReproJob,
+    mut transcript: T,
+    finalize: impl FnOnce(T) -> Vec<u8>,
+) -> Result<Vec<u8>, TachyonError> {
+    let instances = job
+        .instances
+        .iter()
+        .map(|columns| columns.iter().map(Vec::as_slice).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let prepared = prepare_instances(prover, pk, &instances)?;
+
+    let circuits = vec![WitnessOnly; job.instances.len()];
+    let mut steps = ProofSteps::<KZGCommitmentScheme<Bn256>, WitnessOnly>::new(
+        pk,
+        circuits.len(),
+        TachyonXORShiftRng::from_seed(job.seed),
+        false,
+    );
+    steps.replay_witness(job.witness.clone());
+    let mut overflow = None;
+    steps
+        .run_all(
+            prover,
+            pk,
+            &circuits,
+            &prepared,
+            &mut transcript,
+            &mut overflow,
+        )
+        .map_err(|e| steps.error(e, overflow))?;
+    let mut proof = finalize(transcript);
+    proof.extend_from_slice(&prover.get_proof());
+    Ok(proof)
+}
+
+/// Returns the layout item `offset` falls in and its byte range.
+fn locate(offset: usize, layout: Option<&AuditLayout>) -> (Option<TranscriptItem>, Range<usize>) {
+    if let Some(layout) = layout {
+        let mut start = 0;
+        for item in &layout.items {
+            let len = if item.component.is_point() {
+                layout.encoding.point_len()
+            } else {
+                FR_BYTES
+            };
+            if offset < start + len {
+                return (Some(*item), start..start + len);
+            }
+            start += len;
+        }
+    }
+    (None, offset..offset + FR_BYTES)
+}
+
+fn compare(expected: &[u8], actual: &[u8], layout: Option<&AuditLayout>) -> ReplayOutcome {
+    let offset = match expected.iter().zip(actual).position(|(e, a)| e != a) {
+        Some(offset) => offset,
+        None if expected.len() == actual.len() => return ReplayOutcome::Match,
+        None => expected.len().min(actual.len()),
+    };
+    let (item, range) = locate(offset, layout);
+    let clip =
+        |bytes: &[u8]| bytes[range.start.min(bytes.len())..range.end.min(bytes.len())].to_vec();
+    ReplayOutcome::Diverged(Divergence {
+        offset,
+        item,
+        expected: clip(expected),
+        actual: clip(actual),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::{
+            commitment::Params,
+            kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        },
+        transcript::TranscriptWriterBuffer,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{
+        capture, compare, read, read_sections, replay, write_sections, Divergence, ReplayOutcome,
+        ReproCapture, ReproError, ReproJob, WitnessColumn,
+    };
+    use crate::{
+        audit::AuditLayout,
+        bn254::{
+            AnyProver, Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey,
+            SHPlonkProver, TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{MultiOpenScheme, TranscriptType},
+        proof::PointEncoding,
+        prover::{create_proof_with_options, ProofOptions},
+        rng::proof_context,
+        transcript::{Component, TranscriptItem},
+    };
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("{}_{}.tar.gz", name, std::process::id()))
+    }
+
+    fn job() -> ReproJob {
+        ReproJob {
+            k: 4,
+            transcript_type: TranscriptType::Poseidon,
+            multi_open_scheme: MultiOpenScheme::Gwc,
+            rng_context: b"context".to_vec(),
+            seed: [3; 16],
+            intra_proof_parallelism: 2,
+            pk_sha256: [5; 32],
+            circuit_id: Some([6; 32]),
+            pk: None,
+            instances: vec![vec![vec![Fr::from(1), Fr::from(2)], vec![]]],
+            witness: vec![WitnessColumn {
+                circuit: 0,
+                column: 1,
+                values: (0..16).map(Fr::from).collect(),
+            }],
+            proof: vec![7; 100],
+            layout: Some(AuditLayout {
+                items: vec![
+                    TranscriptItem::new(Component::AdviceCommitment, Some(0), 1),
+                    TranscriptItem::new(Component::QuotientCommitment, None, 0),
+                    TranscriptItem::new(Component::AdviceEval, Some(0), 0),
+                ],
+                encoding: PointEncoding::Compressed,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_sections() {
+        let path = temp_path("repro_sections");
+        let job = job();
+        capture(&path, &job).unwrap();
+        assert_eq!(read(&path).unwrap(), job);
+        // The key isn't embedded.
+        assert!(matches!(replay(&path, &[][..]), Err(ReproError::MissingPk)));
+
+        // A section from a newer version is refused rather than misread.
+        let mut sections = read_sections(&path).unwrap();
+        let witness = sections.remove("witness.v1").unwrap();
+        sections.insert("witness.v2".to_string(), witness);
+        write_sections(&path, &sections.into_iter().collect::<Vec<_>>()).unwrap();
+        assert!(matches!(
+            read(&path),
+            Err(ReproError::UnsupportedVersion { section, version })
+                if section == "witness" && version == "2"
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compare() {
+        let layout = job().layout;
+        let expected = vec![0; 96];
+        assert_eq!(
+            compare(&expected, &expected, layout.as_ref()),
+            ReplayOutcome::Match
+        );
+
+        let mut actual = expected.clone();
+        actual[40] = 1;
+        assert_eq!(
+            compare(&expected, &actual, layout.as_ref()),
+            ReplayOutcome::Diverged(Divergence {
+                offset: 40,
+                item: Some(TranscriptItem::new(Component::QuotientCommitment, None, 0)),
+                expected: vec![0; 32],
+                actual: actual[32..64].to_vec(),
+            })
+        );
+        // Without a layout, only the offset is known.
+        let truncated = &expected[..90];
+        assert_eq!(
+            compare(&expected, truncated, None),
+            ReplayOutcome::Diverged(Divergence {
+                offset: 90,
+                item: None,
+                expected: vec![0; 6],
+                actual: vec![],
+            })
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_capture_and_replay() {
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let mut params_bytes = vec![];
+        params.write(&mut params_bytes).unwrap();
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let layout = AuditLayout::new(
+            pk.get_vk(),
+            1,
+            MultiOpenScheme::SHPlonk,
+            &TranscriptType::Blake2b,
+        );
+        let path = temp_path("repro");
+
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        prover.set_master_key([7; 32]);
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        create_proof_with_options(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &[&[&public_inputs[..]]],
+            &ProofOptions {
+                rng_context: proof_context(b"simple", 0),
+                capture_repro: Some(ReproCapture {
+                    path: path.clone(),
+                    transcript_type: TranscriptType::Blake2b,
+                    pk_bytes: pk_bytes.clone(),
+                    embed_pk: true,
+                    layout: Some(layout.clone()),
+                }),
+                ..Default::default()
+            },
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+
+        let job = read(&path).unwrap();
+        assert_eq!(job.proof, proof);
+        assert_eq!(job.k, k);
+        assert_eq!(job.pk.as_deref(), Some(pk_bytes.as_slice()));
+        assert_eq!(job.instances, vec![vec![public_inputs.clone()]]);
+        assert!(job
+            .witness
+            .iter()
+            .all(|column| column.values.len() == 1 << k));
+
+        assert_eq!(
+            replay(&path, params_bytes.as_slice()).unwrap(),
+            ReplayOutcome::Match
+        );
+
+        // Changing one byte of the first column of the witness changes its
+        // commitment, which is the first value of the proof.
+        let mut sections = read_sections(&path).unwrap();
+        // The number of columns, then the circuit, column and number of values
+        // of the first one.
+        sections.get_mut("witness.v1").unwrap()[16] ^= 1;
+        write_sections(&path, &sections.into_iter().collect::<Vec<_>>()).unwrap();
+        let mut replay_prover = AnyProver::<KZGCommitmentScheme<Bn256>>::new(
+            MultiOpenScheme::SHPlonk,
+            TranscriptType::Blake2b as u8,
+            k,
+            &s,
+        );
+        let divergence = match replay(&path, &mut replay_prover).unwrap() {
+            ReplayOutcome::Diverged(divergence) => divergence,
+            ReplayOutcome::Match => panic!("the corrupted witness should diverge"),
+        };
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(divergence.offset, 0);
+        assert_eq!(
+            divergence.item,
+            Some(TranscriptItem::new(
+                Component::AdviceCommitment,
+                Some(0),
+                job.witness[0].column
+            ))
+        );
+        assert_eq!(divergence.item, Some(layout.items[0]));
+        assert_eq!(divergence.expected, proof[..32]);
+        assert_ne!(divergence.actual, divergence.expected);
+    }
+}
@@ -0,0 +1,144 @@
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+fn expand<const N: usize>(master: &[u8; 32], context: &[u8]) -> [u8; N] {
+    let mut seed = [0u8; N];
+    Hkdf::<Sha256>::new(None, master)
+        .expand(context, &mut seed)
+        .expect("seed should be shorter than 255 hashes");
+    seed
+}
+
+/// Derives the seed of an `XORShiftRng` as HKDF-SHA256(`master`, `context`).
+/// Proofs with the same context get the same randomness, and proofs with
+/// different contexts never share it.
+pub fn derive_seed(master: &[u8; 32], context: &[u8]) -> [u8; 16] {
+    expand(master, context)
+}
+
+/// Same as [`derive_seed()`], but sized for RNGs with 32-byte seeds such as
+/// ChaCha.
+pub fn derive_seed32(master: &[u8; 32], context: &[u8]) -> [u8; 32] {
+    expand(master, context)
+}
+
+/// Returns `circuit_id || proof_index` as a context for [`derive_seed()`].
+/// `circuit_id` is length-prefixed so that no two pairs map to the same
+/// context.
+pub fn proof_context(circuit_id: &[u8], proof_index: u64) -> Vec<u8> {
+    let mut context = Vec::with_capacity(8 + circuit_id.len() + 8);
+    context.extend_from_slice(&(circuit_id.len() as u64).to_le_bytes());
+    context.extend_from_slice(circuit_id);
+    context.extend_from_slice(&proof_index.to_le_bytes());
+    context
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::TranscriptWriterBuffer,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{derive_seed, derive_seed32, proof_context};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::TranscriptType,
+        error::TachyonError,
+        prover::{create_proof_with_options, ProofOptions},
+    };
+
+    const MASTER: [u8; 32] = [7; 32];
+
+    #[test]
+    fn test_derive_seed() {
+        let context = proof_context(b"simple", 0);
+        assert_eq!(
+            derive_seed(&MASTER, &context),
+            derive_seed(&MASTER, &context)
+        );
+        assert_ne!(
+            derive_seed(&MASTER, &context),
+            derive_seed(&MASTER, &proof_context(b"simple", 1))
+        );
+        assert_ne!(
+            derive_seed(&MASTER, &context),
+            derive_seed(&[8; 32], &context)
+        );
+        // The shorter seed is a prefix of the longer one, as HKDF expands
+        // sequentially.
+        assert_eq!(
+            derive_seed(&MASTER, &context),
+            derive_seed32(&MASTER, &context)[..16]
+        );
+        assert_ne!(proof_context(b"ab", 0), proof_context(b"a", 0));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_create_proof_with_options() {
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let public_inputs2 = vec![&public_inputs[..]];
+        let public_inputs3 = vec![&public_inputs2[..]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let prove = |master_key: Option<[u8; 32]>, proof_index: u64| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            if let Some(master_key) = master_key {
+                prover.set_master_key(master_key);
+            }
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            let options = ProofOptions {
+                rng_context: proof_context(b"simple", proof_index),
+                ..Default::default()
+            };
+            create_proof_with_options::<_, _, _, _, _>(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                public_inputs3.as_slice(),
+                &options,
+                &mut transcript,
+            )?;
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            Ok::<_, TachyonError>(proof)
+        };
+
+        let proof = prove(Some(MASTER), 0).unwrap();
+        assert_eq!(proof, prove(Some(MASTER), 0).unwrap());
+        assert_ne!(proof, prove(Some(MASTER), 1).unwrap());
+        assert_ne!(proof, prove(Some([8; 32]), 0).unwrap());
+        assert!(matches!(
+            prove(None, 0),
+            Err(TachyonError::MissingMasterKey)
+        ));
+    }
+}
@@ -0,0 +1,243 @@
+// Keygen replaces every selector of `configure()` with a fixed column that is
+// appended after the declared ones. With compression, simple selectors that
+// are never enabled on the same row share a column, and the gates query it
+// through a polynomial that picks the selector out; without it, selector `i`
+// gets the `i`-th new column. A key converted for a circuit that expects the
+// other mapping evaluates its gates on the wrong columns, which only shows up
+// as proofs that don't verify, so the mapping the backend read from the key is
+// checked against what `configure()` declares while the key is loaded.
+
+use std::collections::BTreeSet;
+
+use ff::Field;
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Expression};
+use halo2curves::CurveAffine;
+
+use crate::{bn254::ProvingKey, error::TachyonError};
+
+/// How keygen folded the selectors of a circuit into fixed columns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectorCompression {
+    /// Simple selectors enabled on disjoint rows share a column, as halo2's
+    /// `keygen_vk()` does.
+    Compressed,
+    /// Every selector has a column of its own, in the order the selectors
+    /// were declared.
+    Direct,
+}
+
+/// Returns the index of every selector queried in `expression`.
+fn queried_selectors<F: Field>(expression: &Expression<F>) -> Vec<usize> {
+    let merge = |mut a: Vec<usize>, b: Vec<usize>| {
+        a.extend(b);
+        a
+    };
+    expression.evaluate(
+        &|_| vec![],
+        &|selector| vec![selector.index()],
+        &|_| vec![],
+        &|_| vec![],
+        &|_| vec![],
+        &|_| vec![],
+        &|a| a,
+        &merge,
+        &merge,
+        &|a, _| a,
+    )
+}
+
+/// Checks that the selectors of `ConcreteCircuit` were folded into the fixed
+/// columns of `pk` as `compression` says. The number of fixed columns has to
+/// match, every selector has to be mapped to one of the appended columns, and
+/// the column of a selector the gates query has to be queried by the key.
+/// Otherwise this fails with [`TachyonError::SelectorCompressionMismatch`],
+/// listing the selectors whose columns are suspect, or all of them if only the
+/// number of columns is off.
+pub fn check_selector_mapping<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
+    pk: &ProvingKey<C>,
+    compression: SelectorCompression,
+) -> Result<(), TachyonError> {
+    let mut cs = ConstraintSystem::default();
+    ConcreteCircuit::configure(&mut cs);
+    let declared = cs.num_fixed_columns();
+    let num_selectors = cs.num_selectors();
+    let in_gates = cs
+        .gates()
+        .iter()
+        .flat_map(|gate| gate.polynomials())
+        .flat_map(queried_selectors)
+        .collect::<BTreeSet<_>>();
+
+    let mapping = pk
+        .selector_mapping()
+        .iter()
+        .map(|column| column.index())
+        .collect::<Vec<_>>();
+    let queried = pk
+        .fixed_query_columns()
+        .iter()
+        .map(|column| column.index())
+        .collect::<BTreeSet<_>>();
+    let found_fixed_columns = pk.num_fixed_columns();
+    let expected_fixed_columns = declared
+        + match compression {
+            SelectorCompression::Compressed => mapping.iter().collect::<BTreeSet<_>>().len(),
+            SelectorCompression::Direct => num_selectors,
+        };
+
+    let mut selectors = (0..num_selectors.max(mapping.len()))
+        .filter(|selector| match mapping.get(*selector) {
+            None => true,
+            Some(column) => {
+                *selector >= num_selectors
+                    || *column < declared
+                    || *column >= found_fixed_columns
+                    || (in_gates.contains(selector) && !queried.contains(column))
+                    || (compression == SelectorCompression::Direct
+                        && *column != declared + selector)
+            }
+        })
+        .collect::<Vec<_>>();
+    if selectors.is_empty() && expected_fixed_columns == found_fixed_columns {
+        return Ok(());
+    }
+    if selectors.is_empty() {
+        selectors = (0..num_selectors).collect();
+    }
+    Err(TachyonError::SelectorCompressionMismatch {
+        selectors,
+        expected_fixed_columns,
+        found_fixed_columns,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{keygen_pk2, Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::{kzg::commitment::ParamsKZG, Rotation},
+        SerdeFormat,
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+    use super::SelectorCompression;
+    use crate::{bn254::ProvingKey as TachyonProvingKey, error::TachyonError};
+
+    #[derive(Clone, Debug)]
+    struct TwoGateConfig {
+        advice: [Column<Advice>; 3],
+        q_add: Selector,
+        q_mul: Selector,
+        q_pow: Selector,
+    }
+
+    /// Adds on row 0 and multiplies on row 1, so that halo2 folds `q_add`
+    /// and `q_mul` into one column. The degree 5 gate of the complex `q_pow`
+    /// leaves room for the polynomial that tells them apart.
+    #[derive(Clone, Debug, Default)]
+    struct TwoGateCircuit;
+
+    impl<F: FieldExt> Circuit<F> for TwoGateCircuit {
+        type Config = TwoGateConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> TwoGateConfig {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let q_add = meta.selector();
+            let q_mul = meta.selector();
+            let q_pow = meta.complex_selector();
+            meta.create_gate("add", |meta| {
+                let q = meta.query_selector(q_add);
+                let [a, b, c] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+                vec![q * (a + b - c)]
+            });
+            meta.create_gate("mul", |meta| {
+                let q = meta.query_selector(q_mul);
+                let [a, b, c] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+                vec![q * (a * b - c)]
+            });
+            meta.create_gate("pow", |meta| {
+                let q = meta.query_selector(q_pow);
+                let a = meta.query_advice(advice[0], Rotation::cur());
+                let b = meta.query_advice(advice[1], Rotation::cur());
+                vec![q * (a.clone() * a.clone() * a.clone() * a - b)]
+            });
+            TwoGateConfig {
+                advice,
+                q_add,
+                q_mul,
+                q_pow,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: TwoGateConfig,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "two gates",
+                |mut region| {
+                    for (row, values) in [[1, 2, 3], [2, 3, 6], [2, 16, 0]].iter().enumerate() {
+                        for (column, value) in config.advice.iter().zip(values) {
+                            region.assign_advice(
+                                || "value",
+                                *column,
+                                row,
+                                || Value::known(F::from(*value)),
+                            )?;
+                        }
+                    }
+                    config.q_add.enable(&mut region, 0)?;
+                    config.q_mul.enable(&mut region, 1)?;
+                    config.q_pow.enable(&mut region, 2)
+                },
+            )
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_selector_compression_mismatch() {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+        let pk = keygen_pk2(&params, &TwoGateCircuit).expect("pk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        // halo2 compresses the selectors, so the key loads as such.
+        let tachyon_pk = TachyonProvingKey::<G1Affine>::from_checked::<TwoGateCircuit>(
+            &pk_bytes,
+            SelectorCompression::Compressed,
+        )
+        .unwrap();
+        let mapping = tachyon_pk.selector_mapping();
+        assert_eq!(mapping.len(), 3);
+        assert_eq!(mapping[0], mapping[1]);
+        assert_ne!(mapping[0], mapping[2]);
+        assert_eq!(tachyon_pk.num_fixed_columns(), 2);
+
+        // A circuit expecting a column per selector is refused when the key is
+        // loaded rather than when its proofs fail.
+        let e = TachyonProvingKey::<G1Affine>::from_checked::<TwoGateCircuit>(
+            &pk_bytes,
+            SelectorCompression::Direct,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            &e,
+            TachyonError::SelectorCompressionMismatch {
+                selectors,
+                expected_fixed_columns: 3,
+                found_fixed_columns: 2,
+            } if !selectors.is_empty()
+        ));
+        assert!(e.to_string().contains("the key has 2"));
+    }
+}
@@ -0,0 +1,238 @@
+use std::{fmt, sync::Arc};
+
+use halo2_proofs::{
+    arithmetic::{best_multiexp, g_to_lagrange},
+    poly::kzg::commitment::ParamsKZG,
+};
+use halo2curves::{
+    bn256::{Bn256, G1Affine},
+    group::{prime::PrimeCurveAffine, Curve},
+};
+use rand_core::{OsRng, RngCore};
+
+use crate::{bn254::Evals, error::TachyonError, phase::ColumnIndex, prover::ProofOptions};
+
+/// Which advice commitments are recomputed on the CPU and compared with the
+/// backend's before they are written to the transcript, to catch a corrupted
+/// device. See [`ProofOptions::self_check`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SelfCheck {
+    Off,
+    /// A random fraction of the columns of each phase, at least one if the
+    /// fraction is positive.
+    Sample(f32),
+    Full,
+}
+
+impl Default for SelfCheck {
+    fn default() -> Self {
+        SelfCheck::Off
+    }
+}
+
+impl SelfCheck {
+    /// Picks the positions, among `num_columns`, to check, in ascending order.
+    /// The proof's RNG isn't used, so the proof doesn't depend on the pick.
+    pub(crate) fn select(&self, num_columns: usize) -> Vec<usize> {
+        let count = match self {
+            SelfCheck::Off => 0,
+            SelfCheck::Sample(fraction) if *fraction > 0.0 => {
+                ((num_columns as f32 * fraction).ceil() as usize).min(num_columns)
+            }
+            SelfCheck::Sample(_) => 0,
+            SelfCheck::Full => num_columns,
+        };
+        let mut positions = (0..num_columns).collect::<Vec<_>>();
+        for i in 0..count {
+            let j = i + (OsRng.next_u64() % (num_columns - i) as u64) as usize;
+            positions.swap(i, j);
+        }
+        positions.truncate(count);
+        positions.sort_unstable();
+        positions
+    }
+}
+
+/// The Lagrange bases of the prover's SRS, which the self-check commits with.
+#[derive(Clone)]
+pub struct LagrangeBases(Arc<Vec<G1Affine>>);
+
+impl LagrangeBases {
+    pub fn new(params: &ParamsKZG<Bn256>) -> Self {
+        let g = params.get_g().iter().map(|g| g.to_curve()).collect();
+        LagrangeBases(Arc::new(g_to_lagrange(g, params.k())))
+    }
+
+    /// Commits to `evals` with a CPU MSM.
+    pub(crate) fn commit(&self, evals: &Evals) -> G1Affine {
+        let scalars = (0..evals.len())
+            .map(|row| evals.get_value(row))
+            .collect::<Vec<_>>();
+        best_multiexp(&scalars, &self.0[..scalars.len()]).to_affine()
+    }
+}
+
+impl fmt::Debug for LagrangeBases {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LagrangeBases({} bases)", self.0.len())
+    }
+}
+
+/// Returns the bases to check with, or `None` if `options` don't ask for the
+/// self-check. Fails if they don't fit a prover of `n` rows.
+pub(crate) fn self_check_bases(
+    options: &ProofOptions,
+    n: u64,
+) -> Result<Option<LagrangeBases>, TachyonError> {
+    if options.self_check == SelfCheck::Off {
+        return Ok(None);
+    }
+    let found = options
+        .self_check_bases
+        .as_ref()
+        .map_or(0, |bases| bases.0.len());
+    if found != n as usize {
+        return Err(TachyonError::SelfCheckBasesMismatch {
+            expected: n as usize,
+            found,
+        });
+    }
+    Ok(options.self_check_bases.clone())
+}
+
+#[cfg(test)]
+thread_local! {
+    static CORRUPTED_COLUMN: std::cell::Cell<Option<ColumnIndex>> = std::cell::Cell::new(None);
+}
+
+/// Makes the commitment to `column` of every later proof on this thread come
+/// out wrong, as if the device computing it were corrupted.
+#[cfg(test)]
+pub(crate) fn corrupt_commitments(column: Option<ColumnIndex>) {
+    CORRUPTED_COLUMN.with(|corrupted| corrupted.set(column));
+}
+
+#[cfg(test)]
+pub(crate) fn inject_corruption(columns: &[ColumnIndex], commitments: &mut [G1Affine]) {
+    let corrupted = match CORRUPTED_COLUMN.with(|corrupted| corrupted.get()) {
+        Some(corrupted) => corrupted,
+        None => return,
+    };
+    if let Some(position) = columns.iter().position(|column| *column == corrupted) {
+        commitments[position] = (commitments[position] + G1Affine::generator()).to_affine();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::TranscriptWriterBuffer,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{corrupt_commitments, LagrangeBases, SelfCheck};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::TranscriptType,
+        error::TachyonError,
+        prover::{create_proof_with_options, ProofOptions},
+        rng::proof_context,
+    };
+
+    #[test]
+    fn test_select() {
+        assert!(SelfCheck::Off.select(10).is_empty());
+        assert!(SelfCheck::Sample(0.0).select(10).is_empty());
+        assert_eq!(SelfCheck::Full.select(3), vec![0, 1, 2]);
+        assert_eq!(SelfCheck::Sample(0.05).select(10).len(), 1);
+        assert_eq!(SelfCheck::Sample(0.5).select(10).len(), 5);
+        assert_eq!(SelfCheck::Sample(2.0).select(3), vec![0, 1, 2]);
+        assert!(SelfCheck::Sample(0.5).select(0).is_empty());
+        let positions = SelfCheck::Sample(0.3).select(100);
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(positions.iter().all(|position| *position < 100));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_self_check() {
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = vec![&public_inputs[..]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let bases = LagrangeBases::new(&params);
+
+        let prove = |self_check, self_check_bases| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            prover.set_master_key([7; 32]);
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let options = ProofOptions {
+                rng_context: proof_context(b"simple", 0),
+                self_check,
+                self_check_bases,
+                ..Default::default()
+            };
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[&instances[..]],
+                &options,
+                &mut transcript,
+            )?;
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            Ok::<_, TachyonError>(proof)
+        };
+
+        let proof = prove(SelfCheck::Off, None).expect("proof generation should not fail");
+        for self_check in [SelfCheck::Sample(0.05), SelfCheck::Full] {
+            assert_eq!(
+                prove(self_check, Some(bases.clone())).expect("proof generation should not fail"),
+                proof
+            );
+        }
+
+        corrupt_commitments(Some(1));
+        let result = prove(SelfCheck::Full, Some(bases.clone()));
+        corrupt_commitments(None);
+        assert!(matches!(
+            result,
+            Err(TachyonError::SelfCheckFailed { column: 1 })
+        ));
+
+        assert!(matches!(
+            prove(SelfCheck::Full, None),
+            Err(TachyonError::SelfCheckBasesMismatch {
+                expected: 16,
+                found: 0
+            })
+        ));
+    }
+}
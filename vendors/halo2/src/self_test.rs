@@ -0,0 +1,203 @@
+// The FFI passes halo2curves values to libtachyon by transmuting them, so it
+// assumes that both sides lay out a scalar as the same four little-endian
+// limbs in Montgomery form, and a point as the same coordinates. Nothing
+// checks that at compile time: after a bump of either side that changes it,
+// proofs come out as garbage instead of failing. The self-test pushes known
+// values across the FFI once per process and compares what comes back with
+// what halo2curves computes.
+
+use std::{mem, sync::OnceLock};
+
+use ff::{Field, PrimeField};
+use halo2curves::{
+    bn256::{Fr, G1Affine, G1},
+    group::{Curve, Group},
+};
+
+use crate::{
+    bn254::{ffi, Fr as CppFr, G1Point2 as CppG1Point2},
+    error::TachyonError,
+    version::check_backend,
+};
+
+/// What each check of [`self_test()`] is reported as in
+/// [`TachyonError::LayoutMismatch`].
+pub const CHECKS: [&str; 3] = ["scalar", "msm", "ifft"];
+
+// `Fr::one()` in Montgomery form, i.e, 2²⁵⁶ mod r, as libtachyon expects it.
+const MONTGOMERY_ONE: [u64; 4] = [
+    0xac96341c4ffffffb,
+    0x36fc76959f60cd29,
+    0x666ea36f7879462e,
+    0x0e0a77c19a07df2f,
+];
+
+/// Checks that libtachyon reads and writes scalars and points the way
+/// halo2curves lays them out, by passing a few scalars through evals, an MSM
+/// of 4 points and an IFFT of 4 values across the FFI. Fails with
+/// [`TachyonError::LayoutMismatch`] naming the first check that differs.
+///
+/// It runs once per process before the first prover is created, unless
+/// [`crate::version::InitOptions::skip_self_test`] is set.
+pub fn self_test() -> Result<(), TachyonError> {
+    check_backend()?;
+    layout_checks().map_err(|which| TachyonError::LayoutMismatch { which })
+}
+
+/// The result of the layout checks of the process, by the first check that
+/// failed. It is `Ok` without running them if they were skipped.
+static SELF_TEST: OnceLock<Result<(), &'static str>> = OnceLock::new();
+
+/// Runs [`self_test()`] if its layout checks haven't run in this process yet,
+/// and returns its result. Only the layout checks are remembered; a backend
+/// that is unavailable fails every call, not only the first.
+pub(crate) fn ensure_self_test() -> Result<(), TachyonError> {
+    check_backend()?;
+    (*SELF_TEST.get_or_init(layout_checks)).map_err(|which| TachyonError::LayoutMismatch { which })
+}
+
+/// Records the layout checks as passed without running them, unless they
+/// already ran, in which case their result stands. Only
+/// [`crate::version::init_with()`] calls this, for
+/// [`crate::version::InitOptions::skip_self_test`].
+pub(crate) fn skip_self_test() {
+    let _ = SELF_TEST.set(Ok(()));
+}
+
+// Runs the checks of `self_test()` across the FFI, and returns the first that
+// differs.
+fn layout_checks() -> Result<(), &'static str> {
+    check("scalar", scalars_match())?;
+    check("msm", msm_matches())?;
+    check("ifft", ifft_matches())
+}
+
+fn check(which: &'static str, matches: bool) -> Result<(), &'static str> {
+    #[cfg(test)]
+    let matches = matches && CORRUPTED_CHECK.with(|corrupted| corrupted.get()) != Some(which);
+    if matches {
+        Ok(())
+    } else {
+        Err(which)
+    }
+}
+
+fn scalars_match() -> bool {
+    let one: [u64; 4] = unsafe { mem::transmute(Fr::one()) };
+    if one != MONTGOMERY_ONE {
+        return false;
+    }
+
+    let values = [Fr::zero(), Fr::one(), -Fr::one(), Fr::from(7)];
+    let mut evals = ffi::empty_coset_evals(2);
+    for (i, value) in values.iter().enumerate() {
+        evals
+            .pin_mut()
+            .set_value(i, unsafe { mem::transmute::<_, &CppFr>(value) });
+    }
+    let round_trip = (0..values.len())
+        .all(|i| *unsafe { mem::transmute::<_, Box<Fr>>(evals.get_value(i)) } == values[i]);
+
+    // The backend only computes the right inverse if it reads the limbs as
+    // halo2curves writes them.
+    let mut inverses = [Fr::zero(); 1];
+    ffi::batch_inverse(
+        unsafe { mem::transmute::<_, &[CppFr]>(&values[3..]) },
+        unsafe { mem::transmute::<_, &mut [CppFr]>(&mut inverses[..]) },
+    );
+    round_trip && inverses[0] * values[3] == Fr::one()
+}
+
+// 1·G + 2·(2G) + 3·(3G) + 4·(4G) = 30·G.
+fn msm_matches() -> bool {
+    let generator = G1::generator();
+    let bases: Vec<G1Affine> = (1..=4u64)
+        .map(|i| (generator * Fr::from(i)).to_affine())
+        .collect();
+    let scalars: Vec<Fr> = (1..=4u64).map(Fr::from).collect();
+    let actual = unsafe {
+        let mut msm = ffi::create_g1_msm(2);
+        let actual = ffi::g1_point2_msm(
+            &mut *msm,
+            mem::transmute::<_, &[CppG1Point2]>(bases.as_slice()),
+            mem::transmute::<_, &[CppFr]>(scalars.as_slice()),
+        );
+        ffi::destroy_g1_msm(msm);
+        *mem::transmute::<_, Box<G1>>(actual)
+    };
+    actual == generator * Fr::from(30)
+}
+
+// The IFFT of 1, 2, 3, 4 over the domain of size 4, against the inverse DFT
+// computed from its definition.
+fn ifft_matches() -> bool {
+    let k = 2;
+    let n = 1 << k;
+    let values: Vec<Fr> = (1..=n as u64).map(Fr::from).collect();
+    let mut evals = ffi::empty_coset_evals(k);
+    evals
+        .pin_mut()
+        .set_values(unsafe { mem::transmute::<_, &[CppFr]>(values.as_slice()) });
+    let poly = ffi::coset_ifft(&evals, k, unsafe {
+        mem::transmute::<_, &CppFr>(&Fr::one())
+    });
+    // The backend drops the zero coefficients of the highest degrees.
+    if poly.len() > n {
+        return false;
+    }
+    let mut coeffs = vec![Fr::zero(); n];
+    poly.get_coeffs(unsafe { mem::transmute::<_, &mut [CppFr]>(&mut coeffs[..poly.len()]) });
+
+    let omega_inv = Fr::root_of_unity()
+        .pow_vartime([1u64 << (Fr::S - k)])
+        .invert()
+        .unwrap();
+    let n_inv = Fr::from(n as u64).invert().unwrap();
+    (0..n).all(|j| {
+        let step = omega_inv.pow_vartime([j as u64]);
+        let (sum, _) = values
+            .iter()
+            .fold((Fr::zero(), Fr::one()), |(sum, power), value| {
+                (sum + *value * power, power * step)
+            });
+        coeffs[j] == sum * n_inv
+    })
+}
+
+#[cfg(test)]
+thread_local! {
+    static CORRUPTED_CHECK: std::cell::Cell<Option<&'static str>> = std::cell::Cell::new(None);
+}
+
+/// Makes `which` of every later [`self_test()`] on this thread fail, as if
+/// libtachyon laid out its values differently.
+#[cfg(test)]
+pub(crate) fn corrupt_check(which: Option<&'static str>) {
+    CORRUPTED_CHECK.with(|corrupted| corrupted.set(which));
+}
+
+#[cfg(test)]
+mod test {
+    use super::{corrupt_check, self_test, CHECKS};
+    use crate::error::TachyonError;
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_self_test() {
+        self_test().unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_layout_mismatch() {
+        for which in CHECKS {
+            corrupt_check(Some(which));
+            match self_test() {
+                Err(TachyonError::LayoutMismatch { which: found }) => assert_eq!(found, which),
+                result => panic!("expected a mismatch of {}, got {:?}", which, result),
+            }
+        }
+        corrupt_check(None);
+        self_test().unwrap();
+    }
+}
@@ -0,0 +1,161 @@
+// Every place that turns bytes from outside the crate into scalars goes
+// through here, so that the byte order is always spelled out and non-canonical
+// encodings are rejected instead of being reduced.
+
+use std::fmt;
+
+use ff::PrimeField;
+use halo2curves::bn256::Fr;
+
+/// The size of an encoded scalar in bytes.
+pub const FR_BYTES: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrBytesError {
+    /// The input is not a whole number of scalars.
+    Length { len: usize },
+    /// The scalar at `index` is not smaller than the modulus.
+    NonCanonical { index: usize },
+}
+
+impl fmt::Display for FrBytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrBytesError::Length { len } => write!(
+                f,
+                "{} bytes is not a multiple of {} bytes per scalar",
+                len, FR_BYTES
+            ),
+            FrBytesError::NonCanonical { index } => {
+                write!(f, "scalar {} is not smaller than the modulus", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FrBytesError {}
+
+/// Parses a canonical little-endian encoding of a scalar.
+pub fn fr_from_le_bytes(bytes: &[u8; FR_BYTES]) -> Result<Fr, FrBytesError> {
+    Option::from(Fr::from_repr(*bytes)).ok_or(FrBytesError::NonCanonical { index: 0 })
+}
+
+/// Parses a canonical big-endian encoding of a scalar.
+pub fn fr_from_be_bytes(bytes: &[u8; FR_BYTES]) -> Result<Fr, FrBytesError> {
+    let mut le = *bytes;
+    le.reverse();
+    fr_from_le_bytes(&le)
+}
+
+pub fn fr_to_le_bytes(fr: &Fr) -> [u8; FR_BYTES] {
+    fr.to_repr()
+}
+
+pub fn fr_to_be_bytes(fr: &Fr) -> [u8; FR_BYTES] {
+    let mut bytes = fr.to_repr();
+    bytes.reverse();
+    bytes
+}
+
+fn frs_from_bytes(
+    bytes: &[u8],
+    from_bytes: fn(&[u8; FR_BYTES]) -> Result<Fr, FrBytesError>,
+) -> Result<Vec<Fr>, FrBytesError> {
+    if bytes.len() % FR_BYTES != 0 {
+        return Err(FrBytesError::Length { len: bytes.len() });
+    }
+    bytes
+        .chunks_exact(FR_BYTES)
+        .enumerate()
+        .map(|(index, chunk)| {
+            from_bytes(chunk.try_into().unwrap()).map_err(|_| FrBytesError::NonCanonical { index })
+        })
+        .collect()
+}
+
+/// Parses consecutive canonical little-endian encodings of scalars.
+pub fn frs_from_le_bytes(bytes: &[u8]) -> Result<Vec<Fr>, FrBytesError> {
+    frs_from_bytes(bytes, fr_from_le_bytes)
+}
+
+/// Parses consecutive canonical big-endian encodings of scalars.
+pub fn frs_from_be_bytes(bytes: &[u8]) -> Result<Vec<Fr>, FrBytesError> {
+    frs_from_bytes(bytes, fr_from_be_bytes)
+}
+
+pub fn frs_to_le_bytes(frs: &[Fr]) -> Vec<u8> {
+    frs.iter().flat_map(fr_to_le_bytes).collect()
+}
+
+pub fn frs_to_be_bytes(frs: &[Fr]) -> Vec<u8> {
+    frs.iter().flat_map(fr_to_be_bytes).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use halo2curves::bn256::Fr;
+    use rand_core::OsRng;
+
+    use super::{
+        fr_from_be_bytes, fr_from_le_bytes, fr_to_be_bytes, fr_to_le_bytes, frs_from_be_bytes,
+        frs_from_le_bytes, frs_to_be_bytes, frs_to_le_bytes, FrBytesError,
+    };
+
+    /// The modulus of `Fr` in big-endian order.
+    const MODULUS_BE: [u8; 32] = [
+        0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58,
+        0x5d, 0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00,
+        0x00, 0x01,
+    ];
+
+    fn reversed(mut bytes: [u8; 32]) -> [u8; 32] {
+        bytes.reverse();
+        bytes
+    }
+
+    #[test]
+    fn test_vectors() {
+        let mut modulus_minus_one = MODULUS_BE;
+        modulus_minus_one[31] = 0x00;
+        assert_eq!(fr_from_be_bytes(&modulus_minus_one), Ok(-Fr::one()));
+        assert_eq!(
+            fr_from_le_bytes(&reversed(modulus_minus_one)),
+            Ok(-Fr::one())
+        );
+        assert_eq!(fr_to_be_bytes(&-Fr::one()), modulus_minus_one);
+
+        let non_canonical = Err(FrBytesError::NonCanonical { index: 0 });
+        assert_eq!(fr_from_be_bytes(&MODULUS_BE), non_canonical);
+        assert_eq!(fr_from_le_bytes(&reversed(MODULUS_BE)), non_canonical);
+
+        // Only the top byte is set, which is above the modulus.
+        let mut high_bit = [0u8; 32];
+        high_bit[0] = 0x80;
+        assert_eq!(fr_from_be_bytes(&high_bit), non_canonical);
+        assert_eq!(fr_from_le_bytes(&[0xff; 32]), non_canonical);
+
+        // The same bytes read in the other order are a small value.
+        assert_eq!(fr_from_le_bytes(&high_bit), Ok(Fr::from(0x80)));
+        assert_eq!(fr_to_le_bytes(&Fr::from(0x80)), high_bit);
+    }
+
+    #[test]
+    fn test_batch() {
+        let frs = (0..3).map(|_| Fr::random(OsRng)).collect::<Vec<_>>();
+        assert_eq!(frs_from_le_bytes(&frs_to_le_bytes(&frs)), Ok(frs.clone()));
+        assert_eq!(frs_from_be_bytes(&frs_to_be_bytes(&frs)), Ok(frs.clone()));
+
+        let mut bytes = frs_to_be_bytes(&frs);
+        bytes[32..64].copy_from_slice(&MODULUS_BE);
+        assert_eq!(
+            frs_from_be_bytes(&bytes),
+            Err(FrBytesError::NonCanonical { index: 1 })
+        );
+        assert_eq!(
+            frs_from_le_bytes(&bytes[1..]),
+            Err(FrBytesError::Length { len: 95 })
+        );
+        assert_eq!(frs_from_le_bytes(&[]), Ok(vec![]));
+    }
+}
@@ -0,0 +1,411 @@
+//! Proves several statements under one transcript, so that every proof of a
+//! batch depends on every statement in it, e.g, to aggregate them later as
+//! one.
+//!
+//! The backend runs every phase after the advice commitments in one call, and
+//! its verifier reads a whole proof in one call, so the proofs can't be
+//! interleaved phase by phase. Instead the batch is laid out as:
+//!
+//! 1. The verification key repr and the instances of every job, in job order,
+//!    absorbed into the shared transcript the way every proof starts. Nothing
+//!    is written for them.
+//! 2. The proof of every job, in job order, each an ordinary proof whose
+//!    transcript starts where the one before it ended, and so absorbs its own
+//!    statement again. The first one starts after the absorptions of step 1.
+//!
+//! The verifier absorbs the same statements and verifies the proofs in the
+//! same order, each from the state the one before it ended in, see
+//! [`verify_shared_proof()`].
+
+use std::fmt;
+
+use halo2_proofs::{
+    plonk::Circuit, poly::commitment::CommitmentScheme, transcript::EncodedChallenge,
+};
+
+use crate::{
+    bn254::{ProvingKey as TachyonProvingKey, SHPlonkProver, TachyonProver, TranscriptResume},
+    error::TachyonError,
+    prover::{absorb_statement, create_proof_with_options, prepare_instances, ProofOptions},
+};
+
+/// One proof of a batch, see [`create_proofs_shared_transcript()`]. Jobs may
+/// have different circuits of the same type and different `k`, with a prover
+/// each, but every prover has to use the transcript type of the batch.
+pub struct SharedProofJob<'a, Scheme: CommitmentScheme, P: TachyonProver<Scheme>, C> {
+    pub prover: &'a mut P,
+    pub pk: &'a mut TachyonProvingKey<Scheme::Curve>,
+    pub circuits: &'a [C],
+    pub instances: &'a [&'a [&'a [Scheme::Scalar]]],
+    pub options: ProofOptions,
+}
+
+impl<'a, Scheme: CommitmentScheme, P: TachyonProver<Scheme> + fmt::Debug, C> fmt::Debug
+    for SharedProofJob<'a, Scheme, P, C>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedProofJob")
+            .field("prover", &self.prover)
+            .field("pk", &self.pk)
+            .field("num_circuits", &self.circuits.len())
+            .field("num_instances", &self.instances.len())
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+/// What a verifier needs, besides the proof, to check one proof of a batch.
+pub struct SharedStatement<'a, Scheme: CommitmentScheme> {
+    pub verifier: &'a mut SHPlonkProver<Scheme>,
+    pub pk: &'a mut TachyonProvingKey<Scheme::Curve>,
+    pub instances: &'a [&'a [&'a [Scheme::Scalar]]],
+}
+
+impl<'a, Scheme: CommitmentScheme> fmt::Debug for SharedStatement<'a, Scheme> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedStatement")
+            .field("verifier", &self.verifier)
+            .field("pk", &self.pk)
+            .field("num_instances", &self.instances.len())
+            .finish()
+    }
+}
+
+/// The proofs of a batch, one after another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedProof {
+    /// What the transcript had written before the batch, followed by the
+    /// proofs.
+    pub bytes: Vec<u8>,
+    /// Where the proof of every job starts in `bytes`, followed by the length
+    /// of `bytes`.
+    pub offsets: Vec<usize>,
+    /// The state the shared transcript ended in, to continue it after the
+    /// batch.
+    pub state: Vec<u8>,
+}
+
+impl SharedProof {
+    pub fn num_proofs(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    /// The proof of `job`, which verifies only from the state the proof
+    /// before it ended in.
+    pub fn sub_proof(&self, job: usize) -> Option<&[u8]> {
+        let start = *self.offsets.get(job)?;
+        let end = *self.offsets.get(job + 1)?;
+        self.bytes.get(start..end)
+    }
+}
+
+#[derive(Debug)]
+pub enum SharedProofError {
+    /// Absorbing the statement of `job` failed.
+    Absorb { job: usize, error: TachyonError },
+    /// Proving `job` failed.
+    Prove { job: usize, error: TachyonError },
+}
+
+impl fmt::Display for SharedProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SharedProofError::Absorb { job, error } => {
+                write!(
+                    f,
+                    "failed to absorb the statement of job {}: {}",
+                    job, error
+                )
+            }
+            SharedProofError::Prove { job, error } => {
+                write!(f, "failed to prove job {}: {}", job, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SharedProofError {}
+
+/// Proves every job of `jobs` under `transcript`, laid out as described in
+/// the module docs. Every job is proven with [`create_proof_with_options()`]
+/// and its own options, so its prover needs a master key.
+pub fn create_proofs_shared_transcript<
+    Scheme: CommitmentScheme,
+    P: TachyonProver<Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptResume<Vec<u8>, Scheme::Curve, E>,
+    C: Circuit<Scheme::Scalar>,
+>(
+    jobs: &mut [SharedProofJob<'_, Scheme, P, C>],
+    mut transcript: T,
+) -> Result<SharedProof, SharedProofError> {
+    for (index, job) in jobs.iter_mut().enumerate() {
+        prepare_instances(job.prover, job.pk, job.instances)
+            .and_then(|prepared| absorb_statement(job.prover, job.pk, &prepared, &mut transcript))
+            .map_err(|e| SharedProofError::Absorb {
+                job: index,
+                error: e.into(),
+            })?;
+    }
+
+    let mut state = transcript.state();
+    let mut bytes = transcript.into_writer();
+    let mut offsets = Vec::with_capacity(jobs.len() + 1);
+    for (index, job) in jobs.iter_mut().enumerate() {
+        offsets.push(bytes.len());
+        let prove = |job: &mut SharedProofJob<'_, Scheme, P, C>| {
+            let mut transcript = T::resume(vec![], &state)?;
+            create_proof_with_options(
+                job.prover,
+                job.pk,
+                job.circuits,
+                job.instances,
+                &job.options,
+                &mut transcript,
+            )?;
+            let mut proof = transcript.into_writer();
+            proof.extend_from_slice(&job.prover.get_proof());
+            Ok::<_, TachyonError>(proof)
+        };
+        let proof = prove(job).map_err(|error| SharedProofError::Prove { job: index, error })?;
+        bytes.extend_from_slice(&proof);
+        state = job.prover.transcript_state();
+    }
+    offsets.push(bytes.len());
+    Ok(SharedProof {
+        bytes,
+        offsets,
+        state,
+    })
+}
+
+/// Verifies `proof`, made by [`create_proofs_shared_transcript()`] with a
+/// transcript in the state `transcript` is in, against `statements` in job
+/// order. Fails if any proof fails.
+pub fn verify_shared_proof<
+    Scheme: CommitmentScheme,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptResume<Vec<u8>, Scheme::Curve, E>,
+>(
+    statements: &mut [SharedStatement<'_, Scheme>],
+    proof: &SharedProof,
+    mut transcript: T,
+) -> bool {
+    if proof.num_proofs() != statements.len() {
+        return false;
+    }
+    for statement in statements.iter_mut() {
+        let absorbed = prepare_instances(statement.verifier, statement.pk, statement.instances)
+            .and_then(|prepared| {
+                absorb_statement(statement.verifier, statement.pk, &prepared, &mut transcript)
+            });
+        if absorbed.is_err() {
+            return false;
+        }
+    }
+
+    let mut state = transcript.state();
+    for (index, statement) in statements.iter_mut().enumerate() {
+        let sub_proof = match proof.sub_proof(index) {
+            Some(sub_proof) => sub_proof,
+            None => return false,
+        };
+        state = match statement.verifier.verify_proof_from_state(
+            statement.pk,
+            statement.instances,
+            sub_proof,
+            &state,
+        ) {
+            Some(state) => state,
+            None => return false,
+        };
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, ProvingKey},
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::{Challenge255, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+    use super::{
+        create_proofs_shared_transcript, verify_shared_proof, SharedProof, SharedProofJob,
+        SharedStatement,
+    };
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{MultiOpenScheme, TranscriptType},
+        proof::PointEncoding,
+        prover::ProofOptions,
+        rng::proof_context,
+        serde_fr::{fr_from_le_bytes, fr_to_le_bytes, FR_BYTES},
+        transcript::{transcript_layout, Component},
+    };
+
+    type Transcript = TachyonBlake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>;
+
+    struct Fixture {
+        k: u32,
+        circuit: SimpleCircuit<Fr>,
+        public_inputs: Vec<Fr>,
+        pk: ProvingKey<G1Affine>,
+        pk_bytes: Vec<u8>,
+    }
+
+    fn fixture(k: u32, constant: u64) -> Fixture {
+        let constant = Fr::from(constant);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::from(2));
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        Fixture {
+            k,
+            circuit,
+            public_inputs: vec![constant * a.square() * b.square()],
+            pk,
+            pk_bytes,
+        }
+    }
+
+    fn new_prover(k: u32) -> SHPlonkProver<KZGCommitmentScheme<Bn256>> {
+        let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+            TranscriptType::Blake2b as u8,
+            k,
+            &Fr::from(2),
+        );
+        prover.set_master_key([7; 32]);
+        prover
+    }
+
+    fn verify(fixtures: &[Fixture], proof: &SharedProof) -> bool {
+        let mut verifiers: Vec<_> = fixtures.iter().map(|f| new_prover(f.k)).collect();
+        let mut pks: Vec<_> = fixtures
+            .iter()
+            .map(|f| TachyonProvingKey::try_from(f.pk_bytes.as_slice()).unwrap())
+            .collect();
+        let instances: Vec<_> = fixtures
+            .iter()
+            .map(|f| vec![&f.public_inputs[..]])
+            .collect();
+        let instances: Vec<_> = instances.iter().map(|columns| [&columns[..]]).collect();
+        let mut statements: Vec<_> = verifiers
+            .iter_mut()
+            .zip(pks.iter_mut())
+            .zip(&instances)
+            .map(|((verifier, pk), instances)| SharedStatement {
+                verifier,
+                pk,
+                instances: &instances[..],
+            })
+            .collect();
+        verify_shared_proof(&mut statements, proof, Transcript::init(vec![]))
+    }
+
+    // Adds one to the first advice evaluation of `proof`, a proof of
+    // `fixture`.
+    fn tamper(fixture: &Fixture, proof: &mut [u8]) {
+        let layout = transcript_layout(fixture.pk.get_vk(), 1, MultiOpenScheme::SHPlonk);
+        let encoding = PointEncoding::for_transcript(&TranscriptType::Blake2b);
+        let offset = layout
+            .iter()
+            .take_while(|item| item.component != Component::AdviceEval)
+            .map(|item| {
+                if item.component.is_point() {
+                    encoding.point_len()
+                } else {
+                    FR_BYTES
+                }
+            })
+            .sum::<usize>();
+        let bytes = &mut proof[offset..offset + FR_BYTES];
+        let eval = fr_from_le_bytes((&*bytes).try_into().unwrap()).unwrap();
+        bytes.copy_from_slice(&fr_to_le_bytes(&(eval + Fr::one())));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_shared_transcript() {
+        let fixtures = [fixture(4, 7), fixture(5, 5)];
+        let mut provers: Vec<_> = fixtures.iter().map(|f| new_prover(f.k)).collect();
+        let mut pks: Vec<_> = fixtures
+            .iter()
+            .map(|f| TachyonProvingKey::try_from(f.pk_bytes.as_slice()).unwrap())
+            .collect();
+        let circuits: Vec<_> = fixtures.iter().map(|f| [f.circuit.clone()]).collect();
+        let instances: Vec<_> = fixtures
+            .iter()
+            .map(|f| vec![&f.public_inputs[..]])
+            .collect();
+        let instances: Vec<_> = instances.iter().map(|columns| [&columns[..]]).collect();
+        let mut jobs: Vec<_> = provers
+            .iter_mut()
+            .zip(pks.iter_mut())
+            .zip(circuits.iter().zip(&instances))
+            .enumerate()
+            .map(
+                |(index, ((prover, pk), (circuits, instances)))| SharedProofJob {
+                    prover,
+                    pk,
+                    circuits: &circuits[..],
+                    instances: &instances[..],
+                    options: ProofOptions {
+                        rng_context: proof_context(b"shared", index as u64),
+                        ..Default::default()
+                    },
+                },
+            )
+            .collect();
+        let proof = create_proofs_shared_transcript(&mut jobs, Transcript::init(vec![]))
+            .expect("proof generation should not fail");
+        assert_eq!(proof.num_proofs(), 2);
+        assert_eq!(proof.offsets[0], 0);
+        assert!(verify(&fixtures, &proof));
+
+        for (job, fixture) in fixtures.iter().enumerate() {
+            let mut tampered = proof.clone();
+            let offset = tampered.offsets[job];
+            tamper(fixture, &mut tampered.bytes[offset..]);
+            assert!(
+                !verify(&fixtures, &tampered),
+                "job {} was tampered with",
+                job
+            );
+        }
+
+        // A proof of the batch doesn't verify on its own.
+        let verifier = new_prover(fixtures[1].k);
+        let mut pk = TachyonProvingKey::try_from(fixtures[1].pk_bytes.as_slice()).unwrap();
+        assert!(!verifier.verify_proof(
+            &mut pk,
+            &[&[&fixtures[1].public_inputs[..]]],
+            proof.sub_proof(1).unwrap(),
+        ));
+
+        // Nor does the batch against statements in another order.
+        let swapped = SharedProof {
+            bytes: [proof.sub_proof(1).unwrap(), proof.sub_proof(0).unwrap()].concat(),
+            offsets: vec![0, proof.offsets[2] - proof.offsets[1], proof.offsets[2]],
+            state: proof.state.clone(),
+        };
+        let [first, second] = fixtures;
+        assert!(!verify(&[second, first], &swapped));
+    }
+}
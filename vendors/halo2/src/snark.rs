@@ -0,0 +1,392 @@
+// A snark is a proof with its header and instances, written as JSON so that
+// services which route proofs by circuit can pass it around without the
+// circuit:
+//
+//   {
+//     "version": 1,
+//     "circuit_id": "<64 hex digits>",
+//     "transcript": "blake2b",
+//     "multi_open_scheme": "shplonk",
+//     "k": 4,
+//     "instances": [[["<64 hex digits>", ...], ...], ...],
+//     "proof": "<hex>"
+//   }
+//
+// The fields before "instances" are those of `crate::proof::ProofHeader`,
+// "instances" has the columns of each circuit, and "proof" the transcript
+// bytes without the header. Scalars are their 32-byte little-endian repr in
+// hex. Reading takes the fields in any order and ignores whitespace, but
+// nothing beyond this layout, e.g, escapes in strings.
+
+use std::{fmt::Write, io};
+
+use halo2curves::bn256::Fr;
+
+use crate::{
+    audit::{from_hex, to_hex},
+    consts::{MultiOpenScheme, TranscriptType},
+    proof::{Proof, ProofHeader, PROOF_FORMAT_VERSION},
+    serde_fr::{fr_from_le_bytes, fr_to_le_bytes},
+};
+
+fn invalid_data(message: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snark {
+    pub header: ProofHeader,
+    /// The values of each instance column of each circuit.
+    pub instances: Vec<Vec<Vec<Fr>>>,
+    /// The transcript bytes, see [`Proof::raw()`].
+    pub proof: Vec<u8>,
+}
+
+impl Snark {
+    /// Fails for a proof without a header, which doesn't say what circuit it
+    /// is for.
+    pub fn new(proof: Proof, instances: &[&[&[Fr]]]) -> io::Result<Self> {
+        let header = *proof
+            .header()
+            .ok_or_else(|| invalid_data("a snark needs a proof with a header"))?;
+        Ok(Snark {
+            header,
+            instances: instances
+                .iter()
+                .map(|columns| columns.iter().map(|values| values.to_vec()).collect())
+                .collect(),
+            proof: proof.into_raw(),
+        })
+    }
+
+    pub fn proof(&self) -> Proof {
+        Proof::new(self.header, self.proof.clone())
+    }
+
+    /// The instances as [`crate::proof::verify_proof()`] takes them, once
+    /// each circuit is borrowed as a slice, e.g, with
+    /// `columns.iter().map(Vec::as_slice)`.
+    pub fn instances(&self) -> &[Vec<Vec<Fr>>] {
+        &self.instances
+    }
+
+    pub fn to_json(&self) -> String {
+        let instances = self
+            .instances
+            .iter()
+            .map(|columns| {
+                let columns = columns
+                    .iter()
+                    .map(|values| {
+                        let values = values
+                            .iter()
+                            .map(|value| format!("\"{}\"", to_hex(&fr_to_le_bytes(value))))
+                            .collect::<Vec<_>>();
+                        format!("[{}]", values.join(","))
+                    })
+                    .collect::<Vec<_>>();
+                format!("[{}]", columns.join(","))
+            })
+            .collect::<Vec<_>>();
+        let mut json = String::new();
+        write!(
+            json,
+            "{{\"version\":{},\"circuit_id\":\"{}\",\"transcript\":\"{}\",\
+             \"multi_open_scheme\":\"{}\",\"k\":{},\"instances\":[{}],\"proof\":\"{}\"}}",
+            self.header.version,
+            to_hex(&self.header.circuit_id),
+            self.header.transcript_type.name(),
+            self.header.multi_open_scheme.name(),
+            self.header.k,
+            instances.join(","),
+            to_hex(&self.proof)
+        )
+        .unwrap();
+        json
+    }
+
+    /// Inverse of [`Snark::to_json()`].
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let mut parser = Parser {
+            bytes: json.as_bytes(),
+            pos: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != parser.bytes.len() {
+            return Err(invalid_data("unexpected characters after the snark"));
+        }
+        let fields = match value {
+            Json::Object(fields) => fields,
+            _ => return Err(invalid_data("a snark is a JSON object")),
+        };
+        let field = |key: &str| {
+            fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value)
+                .ok_or_else(|| invalid_data(format!("the snark has no {:?}", key)))
+        };
+        let string = |key: &str| match field(key)? {
+            Json::String(s) => Ok(s.as_str()),
+            _ => Err(invalid_data(format!("{:?} is not a string", key))),
+        };
+        let number = |key: &str| match field(key)? {
+            Json::Number(n) => Ok(*n),
+            _ => Err(invalid_data(format!("{:?} is not a number", key))),
+        };
+        let hex = |key: &str| {
+            from_hex(string(key)?).ok_or_else(|| invalid_data(format!("{:?} is not hex", key)))
+        };
+
+        let version = number("version")?;
+        if version != PROOF_FORMAT_VERSION as u64 {
+            return Err(invalid_data(format!(
+                "unsupported snark version {}",
+                version
+            )));
+        }
+        let transcript = string("transcript")?;
+        let transcript_type = TranscriptType::from_name(transcript)
+            .ok_or_else(|| invalid_data(format!("unknown transcript {:?}", transcript)))?;
+        let scheme = string("multi_open_scheme")?;
+        let multi_open_scheme = MultiOpenScheme::from_name(scheme)
+            .ok_or_else(|| invalid_data(format!("unknown multi-open scheme {:?}", scheme)))?;
+        let header = ProofHeader {
+            version: PROOF_FORMAT_VERSION,
+            circuit_id: hex("circuit_id")?
+                .try_into()
+                .map_err(|_| invalid_data("\"circuit_id\" is not 32 bytes"))?,
+            transcript_type,
+            multi_open_scheme,
+            k: u32::try_from(number("k")?).map_err(|_| invalid_data("\"k\" is too large"))?,
+        };
+
+        let instances = field("instances")?
+            .instances()?
+            .iter()
+            .map(|columns| -> io::Result<Vec<Vec<Fr>>> {
+                columns.instances()?.iter().map(Json::column).collect()
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(Snark {
+            header,
+            instances,
+            proof: hex("proof")?,
+        })
+    }
+}
+
+/// The JSON a snark is made of. Numbers are unsigned integers.
+enum Json {
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// The items of an array in "instances".
+    fn instances(&self) -> io::Result<&[Json]> {
+        match self {
+            Json::Array(items) => Ok(items),
+            _ => Err(invalid_data("\"instances\" is not an array of arrays")),
+        }
+    }
+
+    fn column(&self) -> io::Result<Vec<Fr>> {
+        self.instances()?.iter().map(Json::scalar).collect()
+    }
+
+    fn scalar(&self) -> io::Result<Fr> {
+        let bytes = match self {
+            Json::String(s) => from_hex(s),
+            _ => None,
+        }
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| invalid_data("an instance is not 32 bytes of hex"))?;
+        fr_from_le_bytes(&bytes).map_err(|_| invalid_data("an instance is not canonical"))
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> io::Result<()> {
+        if self.peek() != Some(byte) {
+            return Err(invalid_data(format!(
+                "expected {:?} at offset {}",
+                byte as char, self.pos
+            )));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> io::Result<Json> {
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields = vec![];
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((key, self.value()?));
+                    if self.peek() == Some(b',') {
+                        self.pos += 1;
+                    } else {
+                        self.expect(b'}')?;
+                        return Ok(Json::Object(fields));
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = vec![];
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    if self.peek() == Some(b',') {
+                        self.pos += 1;
+                    } else {
+                        self.expect(b']')?;
+                        return Ok(Json::Array(items));
+                    }
+                }
+            }
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'0'..=b'9') => {
+                let start = self.pos;
+                while matches!(self.bytes.get(self.pos), Some(b'0'..=b'9')) {
+                    self.pos += 1;
+                }
+                std::str::from_utf8(&self.bytes[start..self.pos])
+                    .unwrap()
+                    .parse()
+                    .map(Json::Number)
+                    .map_err(|_| invalid_data(format!("number too large at offset {}", start)))
+            }
+            _ => Err(invalid_data(format!(
+                "expected a value at offset {}",
+                self.pos
+            ))),
+        }
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    return Err(invalid_data(format!(
+                        "unsupported escape at offset {}",
+                        self.pos
+                    )))
+                }
+                Some(_) => self.pos += 1,
+                None => return Err(invalid_data("unterminated string")),
+            }
+        }
+        let s = std::str::from_utf8(&self.bytes[start..self.pos])
+            .map_err(|_| invalid_data("a string is not UTF-8"))?;
+        self.pos += 1;
+        Ok(s.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2curves::bn256::Fr;
+
+    use super::Snark;
+    use crate::{
+        consts::{MultiOpenScheme, TranscriptType},
+        proof::{Proof, ProofHeader, PROOF_FORMAT_VERSION},
+    };
+
+    fn snark() -> Snark {
+        let header = ProofHeader {
+            version: PROOF_FORMAT_VERSION,
+            circuit_id: [0xab; 32],
+            transcript_type: TranscriptType::Poseidon,
+            multi_open_scheme: MultiOpenScheme::Gwc,
+            k: 9,
+        };
+        let instances: &[&[&[Fr]]] = &[&[&[Fr::from(1), -Fr::from(1)], &[]], &[]];
+        Snark::new(Proof::new(header, vec![1, 2, 3]), instances).unwrap()
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let snark = snark();
+        let json = snark.to_json();
+        assert_eq!(Snark::from_json(&json).unwrap(), snark);
+        assert_eq!(snark.proof().header(), Some(&snark.header));
+        assert_eq!(snark.proof().raw(), &[1, 2, 3]);
+
+        // Whitespace and the order of the fields don't matter.
+        let reordered = format!(
+            "{{\n  \"proof\": \"010203\",\n  {}\n}}",
+            &json[1..json.find(",\"proof\"").unwrap()]
+        );
+        assert_eq!(Snark::from_json(&reordered).unwrap(), snark);
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let json = snark().to_json();
+        for (from, to) in [
+            ("\"version\":1", "\"version\":2"),
+            ("\"transcript\":\"poseidon\"", "\"transcript\":\"md5\""),
+            (
+                "\"multi_open_scheme\":\"gwc\"",
+                "\"multi_open_scheme\":\"kzg\"",
+            ),
+            ("\"k\":9", "\"k\":\"9\""),
+            ("\"proof\":\"010203\"", "\"proof\":\"0102030\""),
+            ("\"circuit_id\":\"abab", "\"circuit_id\":\""),
+            // The modulus isn't canonical.
+            (
+                "\"0100000000000000000000000000000000000000000000000000000000000000\"",
+                "\"010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430\"",
+            ),
+        ] {
+            assert!(json.contains(from), "{}", from);
+            assert!(
+                Snark::from_json(&json.replacen(from, to, 1)).is_err(),
+                "{}",
+                to
+            );
+        }
+        assert!(Snark::from_json(&json[..json.len() - 1]).is_err());
+        assert!(Snark::from_json(&format!("{} {{}}", json)).is_err());
+        assert!(Snark::from_json("[]").is_err());
+
+        let legacy = Proof::from_raw(vec![1, 2, 3]);
+        assert!(Snark::new(legacy, &[]).is_err());
+    }
+}
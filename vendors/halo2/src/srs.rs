@@ -0,0 +1,295 @@
+use std::{fmt, io};
+
+use ff::PrimeField;
+use halo2_proofs::poly::{commitment::Params, kzg::commitment::ParamsKZG};
+use halo2curves::{
+    bn256::{Bn256, Fr, G1Affine, G2Affine},
+    pairing::Engine,
+};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// The number of powers [`SrsIntegrity::check_powers()`] checks at random
+/// indices, besides the first and the last.
+pub const NUM_SAMPLED_POWERS: usize = 8;
+
+/// SHA-256 digest of serialized params, as published by a setup ceremony.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CeremonyDigest(pub [u8; 32]);
+
+impl CeremonyDigest {
+    pub fn of(params: &[u8]) -> CeremonyDigest {
+        CeremonyDigest(Sha256::digest(params).into())
+    }
+}
+
+/// How much to trust params loaded from outside.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegrityPolicy {
+    /// Load params as they are.
+    Off,
+    /// Check that sampled powers are consistent with `s_g2`.
+    Structural,
+    /// Check that the params hash to the given digest.
+    Digest(CeremonyDigest),
+}
+
+#[derive(Debug)]
+pub enum IntegrityError {
+    Malformed(io::Error),
+    DigestMismatch {
+        expected: CeremonyDigest,
+        actual: CeremonyDigest,
+    },
+    /// `e(g[index + 1], g2) != e(g[index], s_g2)`.
+    InconsistentPower {
+        index: usize,
+    },
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityError::Malformed(e) => write!(f, "malformed params: {}", e),
+            IntegrityError::DigestMismatch { expected, actual } => write!(
+                f,
+                "params digest mismatch: expected {:?}, actual {:?}",
+                expected.0, actual.0
+            ),
+            IntegrityError::InconsistentPower { index } => {
+                write!(f, "params power at {} is inconsistent with s_g2", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {}
+
+/// Checks the `k` at the start of `params` before halo2 reads them. halo2
+/// shifts by it and allocates for `2ᵏ` points without looking at how many
+/// bytes follow, so a bad `k` would panic or exhaust memory. Every point takes
+/// at least 32 bytes, whatever its encoding.
+fn check_header(params: &[u8]) -> io::Result<()> {
+    let k = params
+        .get(..4)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "the params are too short"))?;
+    let k = u32::from_le_bytes(k.try_into().unwrap());
+    if k > Fr::S {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("k = {} is larger than the field supports", k),
+        ));
+    }
+    if ((params.len() - 4) >> 5) < (1 << k) {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("the params are too short for k = {}", k),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct SrsIntegrity;
+
+impl SrsIntegrity {
+    /// Verifies `params` (serialized with `ParamsKZG::write`) against `policy`.
+    pub fn verify(params: &[u8], policy: &IntegrityPolicy) -> Result<(), IntegrityError> {
+        match policy {
+            IntegrityPolicy::Off => Ok(()),
+            IntegrityPolicy::Structural => {
+                check_header(params).map_err(IntegrityError::Malformed)?;
+                let params = ParamsKZG::<Bn256>::read(&mut &params[..])
+                    .map_err(IntegrityError::Malformed)?;
+                Self::check_powers(params.get_g(), &params.g2(), &params.s_g2())
+            }
+            IntegrityPolicy::Digest(expected) => {
+                let actual = CeremonyDigest::of(params);
+                if actual != *expected {
+                    return Err(IntegrityError::DigestMismatch {
+                        expected: *expected,
+                        actual,
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks `e(g[i + 1], g2) == e(g[i], s_g2)` at the first and last `i`
+    /// and at [`NUM_SAMPLED_POWERS`] random ones, which holds for every `i`
+    /// when `g[i] = sⁱ·G`. The indices are drawn anew on every call, so
+    /// params tampered with away from the ends can't be made to pass by
+    /// knowing which powers are checked.
+    pub fn check_powers(
+        g: &[G1Affine],
+        g2: &G2Affine,
+        s_g2: &G2Affine,
+    ) -> Result<(), IntegrityError> {
+        Self::check_powers_with_rng(g, g2, s_g2, OsRng)
+    }
+
+    /// Like [`SrsIntegrity::check_powers()`], drawing the indices from `rng`.
+    pub fn check_powers_with_rng(
+        g: &[G1Affine],
+        g2: &G2Affine,
+        s_g2: &G2Affine,
+        mut rng: impl RngCore,
+    ) -> Result<(), IntegrityError> {
+        if g.len() < 2 {
+            return Ok(());
+        }
+        let last = g.len() - 2;
+        let mut indices = vec![0, last];
+        indices
+            .extend((0..NUM_SAMPLED_POWERS).map(|_| (rng.next_u64() % (last as u64 + 1)) as usize));
+        indices.sort_unstable();
+        indices.dedup();
+        for index in indices {
+            if Bn256::pairing(&g[index + 1], g2) != Bn256::pairing(&g[index], s_g2) {
+                return Err(IntegrityError::InconsistentPower { index });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CeremonyDigest, IntegrityError, IntegrityPolicy, SrsIntegrity};
+    use crate::{bn254::SHPlonkProver, consts::TranscriptType};
+    use halo2_proofs::poly::{
+        commitment::Params,
+        kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    /// Returns params of `2^k` powers written with `ParamsKZG::write()`, with
+    /// the bytes of `g[index]` and `g[index + 1]` swapped if `swap` is set.
+    /// Both are still points on the curve, so only the structural check can
+    /// tell.
+    fn params_bytes(k: u32, swap: Option<usize>) -> Vec<u8> {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::from(2));
+        let mut bytes = vec![];
+        params.write(&mut bytes).unwrap();
+        if let Some(index) = swap {
+            // `k`, then `g` and `g_lagrange` of `2^k` G1 points each, then `g2`
+            // and `s_g2`, which are twice as large.
+            let point_len = (bytes.len() - 4) / ((2 << k) + 4);
+            let start = 4 + index * point_len;
+            let (first, second) = bytes[start..start + 2 * point_len].split_at_mut(point_len);
+            first.swap_with_slice(second);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_structural() {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+        let mut params_bytes = vec![];
+        params.write(&mut params_bytes).unwrap();
+        assert!(SrsIntegrity::verify(&params_bytes, &IntegrityPolicy::Structural).is_ok());
+
+        let mut g = params.get_g().to_vec();
+        g[1] = g[2];
+        assert!(matches!(
+            SrsIntegrity::check_powers(&g, &params.g2(), &params.s_g2()),
+            Err(IntegrityError::InconsistentPower { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_random_sampling() {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+        let mut g = params.get_g().to_vec();
+        // Only `i = 8` and `i = 9` see the tampered power, and neither is an
+        // end, so only a random index can find it. Each call misses it with a
+        // chance of (13/15)^8 < 1/3, so all of them do with less than 3^-64.
+        g[9] = g[10];
+        let found: Vec<_> = (0..64)
+            .filter_map(
+                |_| match SrsIntegrity::check_powers(&g, &params.g2(), &params.s_g2()) {
+                    Err(IntegrityError::InconsistentPower { index }) => Some(index),
+                    Err(e) => panic!("{}", e),
+                    Ok(()) => None,
+                },
+            )
+            .collect();
+        assert!(!found.is_empty());
+        assert!(found.iter().all(|index| *index == 8 || *index == 9));
+    }
+
+    #[test]
+    fn test_from_params_with_integrity_rejects() {
+        let k = 4;
+        let tampered = params_bytes(k, Some(1));
+        assert!(matches!(
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::from_params_with_integrity(
+                TranscriptType::Blake2b as u8,
+                k,
+                &tampered,
+                IntegrityPolicy::Structural,
+            ),
+            Err(IntegrityError::InconsistentPower { index: 0 })
+        ));
+
+        let digest = CeremonyDigest::of(&params_bytes(k, None));
+        assert!(matches!(
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::from_params_with_integrity(
+                TranscriptType::Blake2b as u8,
+                k,
+                &tampered,
+                IntegrityPolicy::Digest(digest),
+            ),
+            Err(IntegrityError::DigestMismatch { expected, .. }) if expected == digest
+        ));
+
+        assert!(matches!(
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::from_params_with_integrity(
+                TranscriptType::Blake2b as u8,
+                k,
+                &tampered[..tampered.len() / 2],
+                IntegrityPolicy::Structural,
+            ),
+            Err(IntegrityError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_from_params_with_integrity() {
+        let k = 4;
+        let params = params_bytes(k, None);
+        for policy in [
+            IntegrityPolicy::Off,
+            IntegrityPolicy::Structural,
+            IntegrityPolicy::Digest(CeremonyDigest::of(&params)),
+        ] {
+            let prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::from_params_with_integrity(
+                TranscriptType::Blake2b as u8,
+                k,
+                &params,
+                policy,
+            )
+            .unwrap();
+            assert_eq!(prover.k(), k);
+        }
+    }
+
+    #[test]
+    fn test_digest() {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+        let mut params_bytes = vec![];
+        params.write(&mut params_bytes).unwrap();
+
+        let digest = CeremonyDigest::of(&params_bytes);
+        assert!(SrsIntegrity::verify(&params_bytes, &IntegrityPolicy::Digest(digest)).is_ok());
+
+        let last = params_bytes.len() - 1;
+        params_bytes[last] ^= 1;
+        assert!(matches!(
+            SrsIntegrity::verify(&params_bytes, &IntegrityPolicy::Digest(digest)),
+            Err(IntegrityError::DigestMismatch { .. })
+        ));
+    }
+}
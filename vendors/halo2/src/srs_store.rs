@@ -0,0 +1,572 @@
+// Shares the G1 bases of one SRS between the prover processes of a machine
+// through a POSIX shared memory segment, so that each process doesn't keep a
+// copy of its own.
+
+use std::{
+    ffi::CString,
+    fmt,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use halo2_proofs::poly::{commitment::Params, kzg::commitment::ParamsKZG};
+use halo2curves::bn256::{Bn256, Fr, G1Affine, G1};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    artifact::ArtifactStore,
+    bn254::{ffi, Fr as CppFr, G1Point2 as CppG1Point2},
+    error::TachyonError,
+    srs::CeremonyDigest,
+    version::check_backend,
+};
+
+const MAGIC: [u8; 8] = *b"TCHNSRS1";
+
+/// The layout of the start of a segment. The bases follow at
+/// [`BASES_OFFSET`].
+#[repr(C)]
+struct Header {
+    magic: [u8; 8],
+    /// Set to 1 by the creator once the rest of the segment is written.
+    ready: AtomicU32,
+    k: u32,
+    /// The [`CeremonyDigest`] of the params file the bases were read from.
+    digest: [u8; 32],
+    num_bases: u64,
+}
+
+const BASES_OFFSET: usize = 64;
+
+#[derive(Debug)]
+pub enum SharedSrsError {
+    /// The params file couldn't be read or parsed.
+    Params(io::Error),
+    /// A call to create, map or remove the segment failed.
+    Segment(io::Error),
+    /// `name` isn't a valid segment name, which is `/` followed by at most
+    /// 254 characters other than `/`.
+    InvalidName(String),
+    /// The segment isn't one of ours, or its creator hasn't finished writing
+    /// it, e.g, because it crashed.
+    Incomplete,
+    /// The segment was created from other params than the file. It is left
+    /// as is, since other processes may still use it;
+    /// [`SrsStore::recreate_shared()`] replaces it.
+    Stale {
+        segment_k: u32,
+        segment_digest: CeremonyDigest,
+        params_k: u32,
+        params_digest: CeremonyDigest,
+    },
+}
+
+impl fmt::Display for SharedSrsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SharedSrsError::Params(e) => write!(f, "failed to read params: {}", e),
+            SharedSrsError::Segment(e) => write!(f, "shared memory segment: {}", e),
+            SharedSrsError::InvalidName(name) => write!(f, "invalid segment name {:?}", name),
+            SharedSrsError::Incomplete => write!(f, "the shared memory segment is incomplete"),
+            SharedSrsError::Stale {
+                segment_k,
+                segment_digest,
+                params_k,
+                params_digest,
+            } => write!(
+                f,
+                "the shared memory segment holds k = {} with digest {:?}, but the params file \
+                 has k = {} with digest {:?}",
+                segment_k, segment_digest.0, params_k, params_digest.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SharedSrsError {}
+
+/// The G1 bases of an SRS, mapped read-only from a shared memory segment.
+pub struct SharedSrs {
+    ptr: *mut libc::c_void,
+    len: usize,
+    created: bool,
+}
+
+impl fmt::Debug for SharedSrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedSrs")
+            .field("len", &self.len)
+            .field("created", &self.created)
+            .finish_non_exhaustive()
+    }
+}
+
+// The mapping is read-only and stays valid until dropped.
+unsafe impl Send for SharedSrs {}
+unsafe impl Sync for SharedSrs {}
+
+impl SharedSrs {
+    fn header(&self) -> &Header {
+        unsafe { &*(self.ptr as *const Header) }
+    }
+
+    pub fn k(&self) -> u32 {
+        self.header().k
+    }
+
+    pub fn digest(&self) -> CeremonyDigest {
+        CeremonyDigest(self.header().digest)
+    }
+
+    /// Whether this process created the segment rather than mapping an
+    /// existing one.
+    pub fn created(&self) -> bool {
+        self.created
+    }
+
+    /// The powers of tau in G1, in the monomial basis.
+    pub fn g(&self) -> &[G1Affine] {
+        unsafe {
+            std::slice::from_raw_parts(
+                (self.ptr as *const u8).add(BASES_OFFSET) as *const G1Affine,
+                self.header().num_bases as usize,
+            )
+        }
+    }
+
+    /// Commits to the polynomial with coefficients `coeffs` with libtachyon's
+    /// MSM, which reads the bases from the segment directly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more coefficients than bases.
+    pub fn commit(&self, coeffs: &[Fr]) -> Result<G1, TachyonError> {
+        check_backend()?;
+        let bases = &self.g()[..coeffs.len()];
+        let degree = (usize::BITS - coeffs.len().saturating_sub(1).leading_zeros()) as u8;
+        unsafe {
+            let cpp_bases: &[CppG1Point2] = std::mem::transmute(bases);
+            let cpp_scalars: &[CppFr] = std::mem::transmute(coeffs);
+            let mut msm = ffi::create_g1_msm(degree);
+            let commitment = ffi::g1_point2_msm(&mut *msm, cpp_bases, cpp_scalars);
+            ffi::destroy_g1_msm(msm);
+            Ok(*std::mem::transmute::<_, Box<G1>>(commitment))
+        }
+    }
+}
+
+impl Drop for SharedSrs {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SrsStore;
+
+impl SrsStore {
+    /// Maps the segment `name` read-only, creating it from the params at
+    /// `params_path` (written with `ParamsKZG::write`) if it doesn't exist.
+    /// An existing segment is only used if it was created from the same
+    /// params, which is checked by hashing the file, but not parsing it.
+    ///
+    /// The segment outlives the processes that map it, until
+    /// [`SrsStore::unlink_shared()`].
+    pub fn open_shared(name: &str, params_path: &Path) -> Result<SharedSrs, SharedSrsError> {
+        open_shared_with(name, || File::open(params_path))
+    }
+
+    /// Same as [`SrsStore::open_shared()`] with the params stored as `key` in
+    /// `store`. They are streamed twice, to hash and then to parse them,
+    /// instead of being kept in memory.
+    pub fn open_shared_from_store(
+        name: &str,
+        store: &dyn ArtifactStore,
+        key: &str,
+    ) -> Result<SharedSrs, SharedSrsError> {
+        open_shared_with(name, || store.get(key))
+    }
+
+    /// Same as [`SrsStore::open_shared()`], but first removes the segment
+    /// `name` if it is stale or incomplete. Processes that mapped the old
+    /// segment keep using it. An incomplete segment may still be being
+    /// written by another process, so don't call this while one may be.
+    pub fn recreate_shared(name: &str, params_path: &Path) -> Result<SharedSrs, SharedSrsError> {
+        recreate_shared_with(name, || File::open(params_path))
+    }
+
+    /// Same as [`SrsStore::recreate_shared()`] with the params stored as `key`
+    /// in `store`.
+    pub fn recreate_shared_from_store(
+        name: &str,
+        store: &dyn ArtifactStore,
+        key: &str,
+    ) -> Result<SharedSrs, SharedSrsError> {
+        recreate_shared_with(name, || store.get(key))
+    }
+
+    /// Removes the segment `name`. It is freed once every process has
+    /// dropped its [`SharedSrs`].
+    pub fn unlink_shared(name: &str) -> Result<(), SharedSrsError> {
+        let c_name = segment_name(name)?;
+        if unsafe { libc::shm_unlink(c_name.as_ptr()) } != 0 {
+            return Err(SharedSrsError::Segment(io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+}
+
+// `open` is called once for every time the params are read.
+fn open_shared_with<R: Read>(
+    name: &str,
+    open: impl Fn() -> io::Result<R>,
+) -> Result<SharedSrs, SharedSrsError> {
+    let c_name = segment_name(name)?;
+    let (params_k, params_digest) = hash_params(open().map_err(SharedSrsError::Params)?)?;
+    let fd = unsafe {
+        libc::shm_open(
+            c_name.as_ptr(),
+            libc::O_CREAT | libc::O_EXCL | libc::O_RDWR,
+            0o644,
+        )
+    };
+    if fd >= 0 {
+        let reader = match open() {
+            Ok(reader) => reader,
+            Err(e) => {
+                unsafe {
+                    libc::close(fd);
+                    libc::shm_unlink(c_name.as_ptr());
+                }
+                return Err(SharedSrsError::Params(e));
+            }
+        };
+        return create(fd, reader, params_digest).map_err(|e| {
+            unsafe {
+                libc::shm_unlink(c_name.as_ptr());
+            }
+            e
+        });
+    }
+    let error = io::Error::last_os_error();
+    if error.raw_os_error() != Some(libc::EEXIST) {
+        return Err(SharedSrsError::Segment(error));
+    }
+
+    let srs = map_existing(&c_name)?;
+    if srs.k() != params_k || srs.digest() != params_digest {
+        return Err(SharedSrsError::Stale {
+            segment_k: srs.k(),
+            segment_digest: srs.digest(),
+            params_k,
+            params_digest,
+        });
+    }
+    Ok(srs)
+}
+
+fn recreate_shared_with<R: Read>(
+    name: &str,
+    open: impl Fn() -> io::Result<R>,
+) -> Result<SharedSrs, SharedSrsError> {
+    match open_shared_with(name, &open) {
+        Err(SharedSrsError::Stale { .. }) | Err(SharedSrsError::Incomplete) => {
+            SrsStore::unlink_shared(name)?;
+            open_shared_with(name, open)
+        }
+        result => result,
+    }
+}
+
+fn segment_name(name: &str) -> Result<CString, SharedSrsError> {
+    let valid =
+        name.len() > 1 && name.len() <= 255 && name.starts_with('/') && !name[1..].contains('/');
+    match CString::new(name) {
+        Ok(c_name) if valid => Ok(c_name),
+        _ => Err(SharedSrsError::InvalidName(name.to_owned())),
+    }
+}
+
+/// Returns the `k` and the [`CeremonyDigest`] of the params `reader` reads
+/// without keeping them in memory.
+fn hash_params<R: Read>(mut reader: R) -> Result<(u32, CeremonyDigest), SharedSrsError> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 1 << 20];
+    let mut k_bytes = vec![];
+    loop {
+        let len = reader.read(&mut buf).map_err(SharedSrsError::Params)?;
+        if len == 0 {
+            break;
+        }
+        if k_bytes.len() < 4 {
+            let needed = (4 - k_bytes.len()).min(len);
+            k_bytes.extend_from_slice(&buf[..needed]);
+        }
+        hasher.update(&buf[..len]);
+    }
+    // `ParamsKZG::write` starts with k as a little-endian u32.
+    let k_bytes: [u8; 4] = k_bytes.try_into().map_err(|_| {
+        SharedSrsError::Params(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "the params file is too short",
+        ))
+    })?;
+    Ok((
+        u32::from_le_bytes(k_bytes),
+        CeremonyDigest(hasher.finalize().into()),
+    ))
+}
+
+/// Writes the bases of the params `reader` reads to the new segment `fd` and
+/// maps it read-only.
+fn create<R: Read>(
+    fd: libc::c_int,
+    reader: R,
+    digest: CeremonyDigest,
+) -> Result<SharedSrs, SharedSrsError> {
+    let result = (|| {
+        let mut reader = BufReader::new(reader);
+        let params = ParamsKZG::<Bn256>::read(&mut reader).map_err(SharedSrsError::Params)?;
+        let bases = params.get_g();
+        let len = BASES_OFFSET + bases.len() * std::mem::size_of::<G1Affine>();
+        if unsafe { libc::ftruncate(fd, len as libc::off_t) } != 0 {
+            return Err(SharedSrsError::Segment(io::Error::last_os_error()));
+        }
+        let ptr = map(fd, len, libc::PROT_READ | libc::PROT_WRITE)?;
+        let srs = SharedSrs {
+            ptr,
+            len,
+            created: true,
+        };
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                bases.as_ptr() as *const u8,
+                (ptr as *mut u8).add(BASES_OFFSET),
+                bases.len() * std::mem::size_of::<G1Affine>(),
+            );
+            let header = ptr as *mut Header;
+            (*header).magic = MAGIC;
+            (*header).k = params.k();
+            (*header).digest = digest.0;
+            (*header).num_bases = bases.len() as u64;
+            (*header).ready.store(1, Ordering::Release);
+            if libc::mprotect(ptr, len, libc::PROT_READ) != 0 {
+                return Err(SharedSrsError::Segment(io::Error::last_os_error()));
+            }
+        }
+        Ok(srs)
+    })();
+    unsafe {
+        libc::close(fd);
+    }
+    result
+}
+
+fn map_existing(c_name: &CString) -> Result<SharedSrs, SharedSrsError> {
+    let fd = unsafe { libc::shm_open(c_name.as_ptr(), libc::O_RDONLY, 0) };
+    if fd < 0 {
+        return Err(SharedSrsError::Segment(io::Error::last_os_error()));
+    }
+    let result = (|| {
+        let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+        if unsafe { libc::fstat(fd, &mut stat) } != 0 {
+            return Err(SharedSrsError::Segment(io::Error::last_os_error()));
+        }
+        let len = stat.st_size as usize;
+        if len < BASES_OFFSET {
+            return Err(SharedSrsError::Incomplete);
+        }
+        let srs = SharedSrs {
+            ptr: map(fd, len, libc::PROT_READ)?,
+            len,
+            created: false,
+        };
+        let header = srs.header();
+        if header.magic != MAGIC
+            || header.ready.load(Ordering::Acquire) != 1
+            || (len - BASES_OFFSET) as u64
+                != header.num_bases * std::mem::size_of::<G1Affine>() as u64
+        {
+            return Err(SharedSrsError::Incomplete);
+        }
+        Ok(srs)
+    })();
+    unsafe {
+        libc::close(fd);
+    }
+    result
+}
+
+fn map(
+    fd: libc::c_int,
+    len: usize,
+    prot: libc::c_int,
+) -> Result<*mut libc::c_void, SharedSrsError> {
+    let ptr = unsafe { libc::mmap(std::ptr::null_mut(), len, prot, libc::MAP_SHARED, fd, 0) };
+    if ptr == libc::MAP_FAILED {
+        return Err(SharedSrsError::Segment(io::Error::last_os_error()));
+    }
+    Ok(ptr)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::PathBuf, process::Command};
+
+    use halo2_proofs::{
+        arithmetic::best_multiexp,
+        poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    };
+    use halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        group::{Curve, GroupEncoding},
+    };
+
+    use super::{SharedSrsError, SrsStore};
+    use crate::artifact::{ArtifactStore, MemoryArtifactStore};
+
+    const NAME_VAR: &str = "TACHYON_SHARED_SRS_NAME";
+    const PARAMS_VAR: &str = "TACHYON_SHARED_SRS_PARAMS";
+
+    fn write_params(name: &str, s: u64) -> PathBuf {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(s));
+        let path = std::env::temp_dir().join(format!("{}_{}.params", name, std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        params.write(&mut file).unwrap();
+        path
+    }
+
+    fn coeffs() -> Vec<Fr> {
+        (1..=16).map(Fr::from).collect()
+    }
+
+    #[test]
+    fn test_open_shared() {
+        let name = format!("/tachyon_halo2_srs_{}", std::process::id());
+        let path = write_params("shared_srs", 2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+
+        let first = SrsStore::open_shared(&name, &path).unwrap();
+        assert!(first.created());
+        assert_eq!(first.k(), 4);
+        assert_eq!(first.g(), params.get_g());
+        let second = SrsStore::open_shared(&name, &path).unwrap();
+        assert!(!second.created());
+        assert_eq!(second.g(), first.g());
+
+        // Params with another s are rejected, and replace the segment only on
+        // request.
+        let other_path = write_params("shared_srs_other", 3);
+        assert!(matches!(
+            SrsStore::open_shared(&name, &other_path),
+            Err(SharedSrsError::Stale {
+                segment_k: 4,
+                params_k: 4,
+                ..
+            })
+        ));
+        let recreated = SrsStore::recreate_shared(&name, &other_path).unwrap();
+        assert!(recreated.created());
+        assert_ne!(recreated.digest(), first.digest());
+        assert_eq!(
+            recreated.g(),
+            ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(3)).get_g()
+        );
+        // The old mapping stays valid.
+        assert_eq!(first.g(), params.get_g());
+
+        assert!(matches!(
+            SrsStore::open_shared("no_slash", &path),
+            Err(SharedSrsError::InvalidName(_))
+        ));
+
+        SrsStore::unlink_shared(&name).unwrap();
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(other_path).unwrap();
+    }
+
+    #[test]
+    fn test_open_shared_from_store() {
+        let name = format!("/tachyon_halo2_srs_store_{}", std::process::id());
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+        let mut bytes = vec![];
+        params.write(&mut bytes).unwrap();
+        let store = MemoryArtifactStore::new();
+        store.put("srs/k4.params", &bytes).unwrap();
+
+        let srs = SrsStore::open_shared_from_store(&name, &store, "srs/k4.params").unwrap();
+        assert!(srs.created());
+        assert_eq!(srs.g(), params.get_g());
+        // The same params from a file map the same segment.
+        let path = write_params("shared_srs_store", 2);
+        let from_file = SrsStore::open_shared(&name, &path).unwrap();
+        assert!(!from_file.created());
+        assert_eq!(from_file.digest(), srs.digest());
+
+        assert!(matches!(
+            SrsStore::open_shared_from_store(&name, &store, "srs/missing.params"),
+            Err(SharedSrsError::Params(e)) if e.kind() == std::io::ErrorKind::NotFound
+        ));
+
+        SrsStore::unlink_shared(&name).unwrap();
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_commit_in_child_process() {
+        let name = format!("/tachyon_halo2_srs_child_{}", std::process::id());
+        let path = write_params("shared_srs_child", 2);
+        let srs = SrsStore::open_shared(&name, &path).unwrap();
+        let commitment = srs.commit(&coeffs()).unwrap();
+        assert_eq!(commitment, best_multiexp(&coeffs(), &srs.g()[..16]));
+
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "srs_store::test::commit_in_child",
+                "--exact",
+                "--ignored",
+                "--nocapture",
+            ])
+            .env(NAME_VAR, &name)
+            .env(PARAMS_VAR, &path)
+            .output()
+            .unwrap();
+        SrsStore::unlink_shared(&name).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{}", stdout);
+        let child_commitment = stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("commitment: "))
+            .expect("the child should print its commitment");
+        assert_eq!(
+            child_commitment,
+            hex(commitment.to_affine().to_bytes().as_ref())
+        );
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Run by `test_commit_in_child_process` in a child process, which maps
+    /// the segment its parent created.
+    #[test]
+    #[ignore]
+    fn commit_in_child() {
+        let (name, path) = match (std::env::var(NAME_VAR), std::env::var(PARAMS_VAR)) {
+            (Ok(name), Ok(path)) => (name, path),
+            _ => return,
+        };
+        let srs = SrsStore::open_shared(&name, path.as_ref()).unwrap();
+        assert!(!srs.created());
+        let commitment: G1Affine = srs.commit(&coeffs()).unwrap().to_affine();
+        println!("commitment: {}", hex(commitment.to_bytes().as_ref()));
+    }
+}
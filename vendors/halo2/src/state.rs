@@ -0,0 +1,321 @@
+use std::fmt;
+
+use crate::consts::TranscriptType;
+
+/// The version of the state layouts the backend reads with
+/// `TachyonProver::set_rng()` and `TachyonProver::set_transcript()`. A state
+/// is this byte followed by the backend's bytes, whose length is fixed by the
+/// kind of state, see [`StateKind::body_len()`]. Bump it whenever the backend
+/// changes one of the layouts. A custom transcript is the exception: its state
+/// is the id of its hasher followed by whatever the hasher writes.
+pub const STATE_VERSION: u8 = 1;
+
+/// What a state is the state of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateKind {
+    Rng,
+    Transcript(TranscriptType),
+}
+
+impl StateKind {
+    /// The length of a state of this kind, without the version byte, or
+    /// `None` if the hasher of a custom transcript decides.
+    pub fn body_len(&self) -> Option<usize> {
+        match self {
+            StateKind::Rng => Some(16),
+            // `blake2b_state_st` of BoringSSL.
+            StateKind::Transcript(TranscriptType::Blake2b) => Some(216),
+            StateKind::Transcript(TranscriptType::Poseidon) => Some(324),
+            // `SHA256_CTX` of BoringSSL.
+            StateKind::Transcript(TranscriptType::Sha256) => Some(112),
+            StateKind::Transcript(TranscriptType::Custom) => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            StateKind::Rng => "rng",
+            StateKind::Transcript(transcript_type) => transcript_type.name(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateError {
+    /// The state has no version byte.
+    Empty(StateKind),
+    /// The state was written for another layout than [`STATE_VERSION`].
+    UnknownVersion { kind: StateKind, found: u8 },
+    /// The state is longer or shorter than its layout.
+    Length {
+        kind: StateKind,
+        expected: usize,
+        found: usize,
+    },
+    /// The prover was created with a transcript type that has no state
+    /// layout.
+    UnknownTranscript(u8),
+    /// No hasher is registered under the id of a custom transcript.
+    UnregisteredHasher(u8),
+    /// The hasher of a custom transcript refused its state.
+    Hasher { id: u8, reason: String },
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::Empty(kind) => write!(f, "the {} state is empty", kind.name()),
+            StateError::UnknownVersion { kind, found } => write!(
+                f,
+                "the {} state has version {}, expected {}",
+                kind.name(),
+                found,
+                STATE_VERSION
+            ),
+            StateError::Length {
+                kind,
+                expected,
+                found,
+            } => write!(
+                f,
+                "the {} state is {} bytes long, expected {}",
+                kind.name(),
+                found,
+                expected
+            ),
+            StateError::UnknownTranscript(transcript_type) => {
+                write!(f, "transcript type {} has no state layout", transcript_type)
+            }
+            StateError::UnregisteredHasher(id) => {
+                write!(f, "no transcript hasher is registered under id {}", id)
+            }
+            StateError::Hasher { id, reason } => write!(
+                f,
+                "the transcript hasher registered under id {} refused its state: {}",
+                id, reason
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+/// Puts the version byte in front of `body`, a state as the backend writes
+/// it.
+pub(crate) fn versioned(body: Vec<u8>) -> Vec<u8> {
+    let mut state = Vec::with_capacity(1 + body.len());
+    state.push(STATE_VERSION);
+    state.extend(body);
+    state
+}
+
+/// Checks the version and the length of `state`, and returns the part the
+/// backend reads.
+pub(crate) fn state_body(kind: StateKind, state: &[u8]) -> Result<&[u8], StateError> {
+    let (version, body) = state.split_first().ok_or(StateError::Empty(kind))?;
+    if *version != STATE_VERSION {
+        return Err(StateError::UnknownVersion {
+            kind,
+            found: *version,
+        });
+    }
+    match kind.body_len() {
+        Some(expected) if body.len() != expected => Err(StateError::Length {
+            kind,
+            expected,
+            found: body.len(),
+        }),
+        _ => Ok(body),
+    }
+}
+
+/// Like [`state_body()`] for the transcript of a prover created with
+/// `transcript_type`.
+pub(crate) fn transcript_state_body(
+    transcript_type: u8,
+    state: &[u8],
+) -> Result<&[u8], StateError> {
+    let transcript_type = TranscriptType::from_u8(transcript_type)
+        .ok_or(StateError::UnknownTranscript(transcript_type))?;
+    state_body(StateKind::Transcript(transcript_type), state)
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, verify_proof},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::VerifierSHPLONK,
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::SeedableRng;
+
+    use super::{state_body, versioned, StateError, StateKind, STATE_VERSION};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, PoseidonWrite as TachyonPoseidonWrite,
+            ProvingKey as TachyonProvingKey, SHPlonkProver, Sha256Write as TachyonSha256Write,
+            TachyonProver, TranscriptWriteState,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{TranscriptType, SEED},
+        error::TachyonError,
+        prover::create_proof,
+        xor_shift_rng::XORShiftRng,
+    };
+
+    #[test]
+    fn test_state_body() {
+        let kind = StateKind::Rng;
+        let state = versioned(vec![7; 16]);
+        assert_eq!(state[0], STATE_VERSION);
+        assert_eq!(state_body(kind, &state), Ok(&[7; 16][..]));
+
+        assert_eq!(state_body(kind, &[]), Err(StateError::Empty(kind)));
+        assert_eq!(
+            state_body(kind, &state[..16]),
+            Err(StateError::Length {
+                kind,
+                expected: 16,
+                found: 15
+            })
+        );
+        let mut long = state.clone();
+        long.push(0);
+        assert_eq!(
+            state_body(kind, &long),
+            Err(StateError::Length {
+                kind,
+                expected: 16,
+                found: 17
+            })
+        );
+        let mut old = state;
+        old[0] = STATE_VERSION.wrapping_add(1);
+        assert_eq!(
+            state_body(kind, &old),
+            Err(StateError::UnknownVersion {
+                kind,
+                found: STATE_VERSION.wrapping_add(1)
+            })
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_state_lengths() {
+        let rng = XORShiftRng::from_seed(SEED);
+        assert_eq!(rng.state().len(), 1 + StateKind::Rng.body_len().unwrap());
+        let lengths = [
+            (
+                TranscriptType::Blake2b,
+                TachyonBlake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]).state(),
+            ),
+            (
+                TranscriptType::Poseidon,
+                TachyonPoseidonWrite::<_, G1Affine, Challenge255<_>>::init(vec![]).state(),
+            ),
+            (
+                TranscriptType::Sha256,
+                TachyonSha256Write::<_, G1Affine, Challenge255<_>>::init(vec![]).state(),
+            ),
+        ];
+        for (transcript_type, state) in lengths {
+            assert_eq!(
+                state.len(),
+                1 + StateKind::Transcript(transcript_type).body_len().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_set_state() {
+        let k = 4;
+        let s = Fr::from(2);
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        let rng = XORShiftRng::from_seed(SEED).state();
+        let transcript = TachyonBlake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]).state();
+        let blake2b = StateKind::Transcript(TranscriptType::Blake2b);
+
+        assert!(matches!(
+            prover.set_rng(&rng[..rng.len() - 1]),
+            Err(TachyonError::State(StateError::Length {
+                kind: StateKind::Rng,
+                expected: 16,
+                found: 15
+            }))
+        ));
+        let mut long = transcript.clone();
+        long.push(0);
+        assert!(matches!(
+            prover.set_transcript(&long),
+            Err(TachyonError::State(StateError::Length { kind, expected: 216, found: 217 }))
+                if kind == blake2b
+        ));
+        let mut old = transcript.clone();
+        old[0] = 0;
+        assert!(matches!(
+            prover.set_transcript(&old),
+            Err(TachyonError::State(StateError::UnknownVersion { kind, found: 0 }))
+                if kind == blake2b
+        ));
+        // A SHA-256 state doesn't fit a Blake2b prover.
+        let sha256 = TachyonSha256Write::<_, G1Affine, Challenge255<_>>::init(vec![]).state();
+        assert!(matches!(
+            prover.set_transcript(&sha256),
+            Err(TachyonError::State(StateError::Length { .. }))
+        ));
+        prover.set_rng(&rng).unwrap();
+        prover.set_transcript(&transcript).unwrap();
+
+        // Proofs go through the setters and still verify.
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = vec![&public_inputs[..]];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        create_proof(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &[&instances[..]],
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&instances[..]],
+            &mut transcript,
+        )
+        .is_ok());
+    }
+}
@@ -0,0 +1,395 @@
+// Rolling statistics of the proofs a prover creates, for the dashboards of a
+// proving service. Every value goes into a histogram of fixed size with
+// log-linear buckets, like HdrHistogram: values below `SUB_BUCKETS` have a
+// bucket each, and every power of two above is split into `SUB_BUCKETS`
+// buckets, so a percentile is off by less than 1/`SUB_BUCKETS` of its value.
+// The buckets are atomics, so a snapshot can be taken from another thread
+// while a proof runs, without locking the prover.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+const SUB_BUCKET_BITS: u32 = 4;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+/// Enough buckets for any `u64`.
+const NUM_BUCKETS: usize = (64 - SUB_BUCKET_BITS as usize + 1) * SUB_BUCKETS;
+
+fn bucket_of(value: u64) -> usize {
+    if value < SUB_BUCKETS as u64 {
+        return value as usize;
+    }
+    let exponent = 63 - value.leading_zeros() - SUB_BUCKET_BITS;
+    let sub_bucket = (value >> exponent) as usize - SUB_BUCKETS;
+    (exponent as usize + 1) * SUB_BUCKETS + sub_bucket
+}
+
+/// The largest value that falls into `bucket`.
+fn bucket_max(bucket: usize) -> u64 {
+    if bucket < SUB_BUCKETS {
+        return bucket as u64;
+    }
+    let exponent = (bucket / SUB_BUCKETS - 1) as u32;
+    let start = ((SUB_BUCKETS + bucket % SUB_BUCKETS) as u64) << exponent;
+    start + ((1u64 << exponent) - 1)
+}
+
+/// A histogram of `u64` values in fixed memory, which can be recorded to and
+/// read from several threads at once.
+pub struct Histogram {
+    buckets: Vec<AtomicU64>,
+    sum: AtomicU64,
+    min: AtomicU64,
+    max: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram {
+            buckets: (0..NUM_BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicU64::new(0),
+            min: AtomicU64::new(u64::MAX),
+            max: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Histogram {
+    pub fn record(&self, value: u64) {
+        self.buckets[bucket_of(value)].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+        self.min.fetch_min(value, Ordering::Relaxed);
+        self.max.fetch_max(value, Ordering::Relaxed);
+    }
+
+    /// Copies the current counts. A value recorded meanwhile may be missing
+    /// from some of them.
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let buckets = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect::<Vec<_>>();
+        HistogramSnapshot {
+            count: buckets.iter().sum(),
+            sum: self.sum.load(Ordering::Relaxed),
+            min: self.min.load(Ordering::Relaxed),
+            max: self.max.load(Ordering::Relaxed),
+            buckets,
+        }
+    }
+
+    /// Forgets every value. A value recorded meanwhile may or may not be
+    /// forgotten.
+    pub fn reset(&self) {
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.sum.store(0, Ordering::Relaxed);
+        self.min.store(u64::MAX, Ordering::Relaxed);
+        self.max.store(0, Ordering::Relaxed);
+    }
+}
+
+/// The values of a [`Histogram`] at one point in time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistogramSnapshot {
+    pub count: u64,
+    pub sum: u64,
+    /// `u64::MAX` while there are no values.
+    pub min: u64,
+    pub max: u64,
+    buckets: Vec<u64>,
+}
+
+impl HistogramSnapshot {
+    pub fn mean(&self) -> Option<u64> {
+        self.sum.checked_div(self.count)
+    }
+
+    /// The smallest value that `quantile`, between 0 and 1, of the values are
+    /// at most, up to the precision of the buckets, or `None` while there are
+    /// no values.
+    pub fn percentile(&self, quantile: f64) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((quantile.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                // `min` and `max` may lag behind the buckets they were copied
+                // with.
+                let value = bucket_max(bucket);
+                return Some(if self.min <= self.max {
+                    value.clamp(self.min, self.max)
+                } else {
+                    value
+                });
+            }
+        }
+        Some(self.max)
+    }
+
+    pub fn p50(&self) -> Option<u64> {
+        self.percentile(0.5)
+    }
+
+    pub fn p95(&self) -> Option<u64> {
+        self.percentile(0.95)
+    }
+
+    pub fn p99(&self) -> Option<u64> {
+        self.percentile(0.99)
+    }
+}
+
+/// What the duration of a proof is broken down into. The stages other than
+/// [`TimedStage::Total`] are the steps of the proof, so a proof of several
+/// circuits or phases records [`TimedStage::Synthesize`] several times.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimedStage {
+    /// Absorbing the verifying key and the instances.
+    Begin,
+    /// Synthesizing a circuit in a phase and committing to its advice.
+    Synthesize,
+    /// Squeezing the challenges of a phase.
+    Challenges,
+    /// The backend's part of the proof.
+    Backend,
+    /// From the first step to the end of the backend's part.
+    Total,
+}
+
+impl TimedStage {
+    pub const ALL: [TimedStage; 5] = [
+        TimedStage::Begin,
+        TimedStage::Synthesize,
+        TimedStage::Challenges,
+        TimedStage::Backend,
+        TimedStage::Total,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimedStage::Begin => "begin",
+            TimedStage::Synthesize => "synthesize",
+            TimedStage::Challenges => "challenges",
+            TimedStage::Backend => "backend",
+            TimedStage::Total => "total",
+        }
+    }
+}
+
+/// The histograms a prover records its proofs into. Durations are in
+/// microseconds and sizes in bytes.
+#[derive(Default)]
+pub struct ProverStats {
+    stages: [Histogram; 5],
+    proof_bytes: Histogram,
+    queue_wait: Histogram,
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros().min(u64::MAX as u128) as u64
+}
+
+impl ProverStats {
+    pub(crate) fn record_stage(&self, stage: TimedStage, duration: Duration) {
+        self.stages[stage as usize].record(micros(duration));
+    }
+
+    pub(crate) fn record_proof_bytes(&self, len: usize) {
+        self.proof_bytes.record(len as u64);
+    }
+
+    pub(crate) fn record_queue_wait(&self, duration: Duration) {
+        self.queue_wait.record(micros(duration));
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            stages: TimedStage::ALL.map(|stage| (stage, self.stages[stage as usize].snapshot())),
+            proof_bytes: self.proof_bytes.snapshot(),
+            queue_wait: self.queue_wait.snapshot(),
+        }
+    }
+
+    pub fn reset(&self) {
+        for histogram in self
+            .stages
+            .iter()
+            .chain([&self.proof_bytes, &self.queue_wait])
+        {
+            histogram.reset();
+        }
+    }
+}
+
+/// The statistics of a prover at one point in time, see
+/// [`crate::bn254::TachyonProver::stats_snapshot()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    /// The durations of every stage in microseconds.
+    pub stages: [(TimedStage, HistogramSnapshot); 5],
+    /// The size of the backend's part of every proof.
+    pub proof_bytes: HistogramSnapshot,
+    /// How long every proof waited between
+    /// [`crate::prover::ProofOptions::enqueued_at`] and its start, in
+    /// microseconds.
+    pub queue_wait: HistogramSnapshot,
+}
+
+impl StatsSnapshot {
+    pub fn stage(&self, stage: TimedStage) -> &HistogramSnapshot {
+        &self.stages[stage as usize].1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::Arc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::TranscriptWriterBuffer,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::{bucket_max, bucket_of, Histogram, TimedStage, SUB_BUCKETS};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+            TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::TranscriptType,
+        prover::{create_proof_with_options, ProofOptions},
+        rng::proof_context,
+    };
+
+    #[test]
+    fn test_buckets() {
+        let mut previous = None;
+        for value in (0..4096).chain([u64::MAX / 3, u64::MAX - 1, u64::MAX]) {
+            let bucket = bucket_of(value);
+            assert!(value <= bucket_max(bucket));
+            if bucket > 0 {
+                assert!(value > bucket_max(bucket - 1));
+            }
+            // Within 1/SUB_BUCKETS of the value.
+            assert!(bucket_max(bucket) - value <= value / SUB_BUCKETS as u64);
+            if let Some(previous) = previous {
+                assert!(bucket >= previous);
+            }
+            previous = Some(bucket);
+        }
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let histogram = Histogram::default();
+        assert_eq!(histogram.snapshot().p50(), None);
+        for value in 1..=1000 {
+            histogram.record(value);
+        }
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count, 1000);
+        assert_eq!((snapshot.min, snapshot.max), (1, 1000));
+        assert_eq!(snapshot.mean(), Some(500));
+        for (percentile, expected) in [(snapshot.p50(), 500), (snapshot.p99(), 990)] {
+            let percentile = percentile.unwrap();
+            assert!(percentile >= expected && percentile <= expected + expected / 16);
+        }
+        assert_eq!(snapshot.percentile(1.0), Some(1000));
+
+        histogram.reset();
+        assert_eq!(histogram.snapshot().count, 0);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_stats_snapshot() {
+        let k = 4;
+        let s = Fr::from(2);
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let instances = vec![Fr::from(7 * 4 * 9)];
+        let instances: &[&[Fr]] = &[&instances];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+        prover.set_master_key([3; 32]);
+        let stats = prover.stats();
+        // Snapshots are taken while the proofs run.
+        let watcher = thread::spawn({
+            let stats = Arc::clone(&stats);
+            move || {
+                (0..100)
+                    .map(|_| {
+                        thread::sleep(Duration::from_millis(1));
+                        stats.snapshot().stage(TimedStage::Total).count
+                    })
+                    .collect::<Vec<_>>()
+            }
+        });
+        for i in 0..20 {
+            let options = ProofOptions {
+                rng_context: proof_context(b"stats", i),
+                enqueued_at: Some(Instant::now()),
+                ..Default::default()
+            };
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            create_proof_with_options(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &[instances],
+                &options,
+                &mut transcript,
+            )
+            .unwrap();
+        }
+        let counts = watcher.join().unwrap();
+        assert!(counts.windows(2).all(|counts| counts[0] <= counts[1]));
+
+        let snapshot = prover.stats_snapshot();
+        for stage in [
+            TimedStage::Begin,
+            TimedStage::Synthesize,
+            TimedStage::Backend,
+            TimedStage::Total,
+        ] {
+            let histogram = snapshot.stage(stage);
+            assert_eq!(histogram.count, 20, "{}", stage.name());
+            let (p50, p95, p99) = (histogram.p50(), histogram.p95(), histogram.p99());
+            assert!(p50 <= p95 && p95 <= p99 && p99 <= Some(histogram.max));
+        }
+        assert_eq!(snapshot.stage(TimedStage::Challenges).count, 20);
+        assert!(snapshot.stage(TimedStage::Backend).min <= snapshot.stage(TimedStage::Total).max);
+        assert_eq!(snapshot.proof_bytes.count, 20);
+        assert_eq!(snapshot.proof_bytes.min, snapshot.proof_bytes.max);
+        assert_eq!(snapshot.queue_wait.count, 20);
+
+        prover.reset_stats();
+        assert_eq!(stats.snapshot().stage(TimedStage::Total).count, 0);
+    }
+}
@@ -0,0 +1,253 @@
+// Runs many proofs through one prover and fails if memory grows from proof to
+// proof, to catch leaks in the pools or fragmentation in the backend. The
+// full run takes a while, so its test is ignored; run it with
+//
+//   cargo test --features stress,native-tests -- --ignored stress
+
+use std::fmt;
+
+use halo2_proofs::{
+    circuit::Value,
+    plonk::keygen_pk2,
+    poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+    transcript::TranscriptWriterBuffer,
+};
+use halo2curves::bn256::{Bn256, Fr};
+use rand_core::SeedableRng;
+
+use crate::{
+    bn254::{
+        Blake2bWrite as TachyonBlake2bWrite, ProvingKey as TachyonProvingKey, SHPlonkProver,
+        TachyonProver,
+    },
+    circuits::simple_circuit::SimpleCircuit,
+    consts::{TranscriptType, SEED},
+    error::TachyonError,
+    metrics::{current_host_bytes, device_bytes_in_use, AllocationStats},
+    prover::create_proof,
+    version::check_backend,
+    xor_shift_rng::XORShiftRng,
+};
+
+#[derive(Clone, Debug)]
+pub struct StressConfig {
+    pub k: u32,
+    pub num_proofs: usize,
+    /// Memory is sampled after every `sample_every` proofs.
+    pub sample_every: usize,
+    /// The steepest growth of the host or device memory, in bytes per proof,
+    /// that isn't reported as a leak.
+    pub max_bytes_per_proof: f64,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        StressConfig {
+            k: 10,
+            num_proofs: 200,
+            sample_every: 10,
+            max_bytes_per_proof: 4096.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MemorySample {
+    /// The number of proofs created before the sample was taken.
+    pub num_proofs: usize,
+    /// The resident set size of the process.
+    pub host_bytes: u64,
+    /// The memory in use on the GPU, by any process. It stays zero without
+    /// CUDA.
+    pub device_bytes: u64,
+    pub allocations: AllocationStats,
+}
+
+#[derive(Clone, Debug)]
+pub struct StressReport {
+    pub samples: Vec<MemorySample>,
+    /// The slope of the least-squares line through the host bytes of the
+    /// samples, in bytes per proof.
+    pub host_bytes_per_proof: f64,
+    pub device_bytes_per_proof: f64,
+}
+
+#[derive(Debug)]
+pub enum StressError {
+    /// Keying the fixture circuit failed.
+    Setup(TachyonError),
+    /// Proof `proof` failed.
+    Proof { proof: usize, error: TachyonError },
+    /// The platform doesn't report the resident set size.
+    NoHostBytes,
+    /// `kind` memory, which is "host" or "device", grew faster than
+    /// [`StressConfig::max_bytes_per_proof`].
+    Leak {
+        kind: &'static str,
+        bytes_per_proof: f64,
+        report: StressReport,
+    },
+}
+
+impl fmt::Display for StressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StressError::Setup(e) => write!(f, "failed to set up the stress test: {}", e),
+            StressError::Proof { proof, error } => write!(f, "proof {} failed: {}", proof, error),
+            StressError::NoHostBytes => write!(f, "the platform doesn't report the RSS"),
+            StressError::Leak {
+                kind,
+                bytes_per_proof,
+                ..
+            } => write!(
+                f,
+                "{} memory grows by {:.0} bytes per proof",
+                kind, bytes_per_proof
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StressError {}
+
+/// Returns the slope of the least-squares line through `points`, or 0 if
+/// there are fewer than two distinct `x`.
+pub(crate) fn slope(points: &[(f64, f64)]) -> f64 {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        (
+            cov + (x - mean_x) * (y - mean_y),
+            var + (x - mean_x) * (x - mean_x),
+        )
+    });
+    if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    }
+}
+
+/// Proves `SimpleCircuit` `config.num_proofs` times with one prover and one
+/// proving key, sampling memory after every `config.sample_every` proofs.
+/// The first sample is left out of the trend line, since the pools and the
+/// transcript buffer only reach their steady size during the first proofs.
+pub fn run(config: &StressConfig) -> Result<StressReport, StressError> {
+    check_backend().map_err(StressError::Setup)?;
+
+    let s = Fr::from(2);
+    let constant = Fr::from(7);
+    let a = Fr::from(2);
+    let b = Fr::from(3);
+    let circuit = SimpleCircuit {
+        constant,
+        a: Value::known(a),
+        b: Value::known(b),
+    };
+    let public_inputs = vec![constant * a.square() * b.square()];
+
+    let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(config.k, s);
+    let pk = keygen_pk2(&params, &circuit).map_err(|e| StressError::Setup(e.into()))?;
+    let mut pk_bytes: Vec<u8> = vec![];
+    pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+        .expect("writing to a Vec should not fail");
+    let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice())
+        .map_err(|e| StressError::Setup(TachyonError::MalformedKey(e)))?;
+    let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::try_new(
+        TranscriptType::Blake2b as u8,
+        config.k,
+        &s,
+    )
+    .map_err(StressError::Setup)?;
+
+    let mut samples = vec![];
+    for i in 0..config.num_proofs {
+        let mut transcript = TachyonBlake2bWrite::init(vec![]);
+        create_proof(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit.clone()],
+            &[&[&public_inputs[..]]],
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )
+        .map_err(|e| StressError::Proof {
+            proof: i,
+            error: e.into(),
+        })?;
+        transcript.finalize();
+        prover.get_proof();
+
+        if (i + 1) % config.sample_every == 0 {
+            samples.push(MemorySample {
+                num_proofs: i + 1,
+                host_bytes: current_host_bytes().ok_or(StressError::NoHostBytes)?,
+                device_bytes: device_bytes_in_use().unwrap_or(0),
+                allocations: prover.debug_allocation_stats(),
+            });
+        }
+    }
+
+    let trend = |bytes: fn(&MemorySample) -> u64| {
+        let points = samples
+            .iter()
+            .skip(1)
+            .map(|sample| (sample.num_proofs as f64, bytes(sample) as f64))
+            .collect::<Vec<_>>();
+        slope(&points)
+    };
+    let report = StressReport {
+        host_bytes_per_proof: trend(|sample| sample.host_bytes),
+        device_bytes_per_proof: trend(|sample| sample.device_bytes),
+        samples,
+    };
+    for (kind, bytes_per_proof) in [
+        ("host", report.host_bytes_per_proof),
+        ("device", report.device_bytes_per_proof),
+    ] {
+        if bytes_per_proof > config.max_bytes_per_proof {
+            return Err(StressError::Leak {
+                kind,
+                bytes_per_proof,
+                report,
+            });
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{run, slope, StressConfig};
+
+    #[test]
+    fn test_slope() {
+        let line = (0..10)
+            .map(|x| (x as f64, 3.0 * x as f64 + 5.0))
+            .collect::<Vec<_>>();
+        assert!((slope(&line) - 3.0).abs() < 1e-9);
+        assert_eq!(slope(&[(1.0, 2.0), (1.0, 4.0)]), 0.0);
+        assert_eq!(slope(&[(1.0, 2.0)]), 0.0);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_stress() {
+        let config = StressConfig::default();
+        let report = run(&config).unwrap_or_else(|e| panic!("{}", e));
+        println!(
+            "host: {:.0} bytes per proof, device: {:.0} bytes per proof",
+            report.host_bytes_per_proof, report.device_bytes_per_proof
+        );
+        assert_eq!(
+            report.samples.len(),
+            config.num_proofs / config.sample_every
+        );
+
+        let allocations = &report.samples.last().unwrap().allocations;
+        println!("{:?}", allocations);
+        assert!(allocations.get("srs").unwrap() > 0);
+        assert!(allocations.get("transcript").unwrap() > 0);
+    }
+}
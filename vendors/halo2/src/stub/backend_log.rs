@@ -0,0 +1,17 @@
+use super::unavailable;
+
+pub fn set_backend_log(_destination: u8, _callback: usize) {
+    unavailable()
+}
+
+pub fn backend_log_verbosity() -> i32 {
+    unavailable()
+}
+
+pub fn set_backend_log_verbosity(_verbosity: i32) {
+    unavailable()
+}
+
+pub fn log_fatal_for_testing(_message: &str) {
+    unavailable()
+}
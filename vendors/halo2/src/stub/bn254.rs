@@ -0,0 +1,693 @@
+use std::pin::Pin;
+
+use super::{unavailable, UniquePtr};
+pub use crate::bn254::{
+    AdviceSingle, Fr, G1JacobianPoint, G1MSMGpu, G1Point2, G2AffinePoint, InstanceSingle,
+    TranscriptCallbacks, G1MSM,
+};
+
+pub fn create_g1_msm(_degree: u8) -> Box<G1MSM> {
+    unavailable()
+}
+
+pub fn destroy_g1_msm(_msm: Box<G1MSM>) {}
+
+/// # Safety
+///
+/// Never dereferences `msm`.
+pub unsafe fn g1_point2_msm(
+    _msm: *mut G1MSM,
+    _bases: &[G1Point2],
+    _scalars: &[Fr],
+) -> Box<G1JacobianPoint> {
+    unavailable()
+}
+
+#[cfg(feature = "gpu")]
+pub fn create_g1_msm_gpu(_degree: u8, _algorithm: i32) -> Box<G1MSMGpu> {
+    unavailable()
+}
+
+#[cfg(feature = "gpu")]
+pub fn destroy_g1_msm_gpu(_msm: Box<G1MSMGpu>) {}
+
+/// # Safety
+///
+/// Never dereferences `msm`.
+#[cfg(feature = "gpu")]
+pub unsafe fn g1_point2_msm_gpu(
+    _msm: *mut G1MSMGpu,
+    _bases: &[G1Point2],
+    _scalars: &[Fr],
+) -> Box<G1JacobianPoint> {
+    unavailable()
+}
+
+pub fn batch_inverse(_values: &[Fr], _inverses: &mut [Fr]) {
+    unavailable()
+}
+
+pub fn batch_inverse_in_place(_values: &mut [Fr]) {
+    unavailable()
+}
+
+pub struct ColumnStats {
+    pub num_nonzero_rows: usize,
+    pub num_coeffs: usize,
+    pub entropy: f64,
+}
+
+pub struct ColumnQuery {
+    pub index: usize,
+    pub rotation: i32,
+}
+
+pub struct AllocationStats {
+    pub srs_bytes: usize,
+    pub transcript_bytes: usize,
+}
+
+pub struct ScratchStats {
+    pub bytes: usize,
+    pub num_allocations: usize,
+    pub num_reuses: usize,
+}
+
+pub struct GateCost {
+    pub nanos: u64,
+    pub num_ops: u64,
+}
+
+pub enum Blake2bWriter {}
+
+pub fn new_blake2b_writer() -> UniquePtr<Blake2bWriter> {
+    unavailable()
+}
+
+pub fn new_blake2b_writer_from_state(_state: &[u8]) -> UniquePtr<Blake2bWriter> {
+    unavailable()
+}
+
+impl Blake2bWriter {
+    pub fn update(self: Pin<&mut Self>, _data: &[u8]) {
+        match *self {}
+    }
+
+    pub fn finalize(self: Pin<&mut Self>, _result: &mut [u8; 64]) {
+        match *self {}
+    }
+
+    pub fn state(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn clone(&self) -> UniquePtr<Blake2bWriter> {
+        match *self {}
+    }
+}
+
+pub enum PoseidonWriter {}
+
+pub fn new_poseidon_writer() -> UniquePtr<PoseidonWriter> {
+    unavailable()
+}
+
+pub fn new_poseidon_writer_from_state(_state: &[u8]) -> UniquePtr<PoseidonWriter> {
+    unavailable()
+}
+
+impl PoseidonWriter {
+    pub fn update(self: Pin<&mut Self>, _data: &[u8]) {
+        match *self {}
+    }
+
+    pub fn squeeze(self: Pin<&mut Self>) -> Box<Fr> {
+        match *self {}
+    }
+
+    pub fn state(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn clone(&self) -> UniquePtr<PoseidonWriter> {
+        match *self {}
+    }
+}
+
+pub enum Sha256Writer {}
+
+pub fn new_sha256_writer() -> UniquePtr<Sha256Writer> {
+    unavailable()
+}
+
+pub fn new_sha256_writer_from_state(_state: &[u8]) -> UniquePtr<Sha256Writer> {
+    unavailable()
+}
+
+impl Sha256Writer {
+    pub fn update(self: Pin<&mut Self>, _data: &[u8]) {
+        match *self {}
+    }
+
+    pub fn finalize(self: Pin<&mut Self>, _result: &mut [u8; 32]) {
+        match *self {}
+    }
+
+    pub fn state(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn clone(&self) -> UniquePtr<Sha256Writer> {
+        match *self {}
+    }
+}
+
+pub enum ProvingKey {}
+
+pub fn new_proving_key(_data: &[u8]) -> UniquePtr<ProvingKey> {
+    unavailable()
+}
+
+impl ProvingKey {
+    pub fn advice_column_phases(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn blinding_factors(&self) -> u32 {
+        match *self {}
+    }
+
+    pub fn challenge_phases(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn constants(&self) -> Vec<usize> {
+        match *self {}
+    }
+
+    pub fn degree(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn extended_k(&self, _k: u32) -> u32 {
+        match *self {}
+    }
+
+    pub fn num_advice_columns(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn num_challenges(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn num_fixed_columns(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn num_instance_columns(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn num_lookups(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn num_permutation_columns(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn phases(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn selector_map(&self) -> Vec<usize> {
+        match *self {}
+    }
+
+    pub fn fixed_query_columns(&self) -> Vec<usize> {
+        match *self {}
+    }
+
+    pub fn advice_queries(&self) -> Vec<ColumnQuery> {
+        match *self {}
+    }
+
+    pub fn instance_queries(&self) -> Vec<ColumnQuery> {
+        match *self {}
+    }
+
+    pub fn fixed_queries(&self) -> Vec<ColumnQuery> {
+        match *self {}
+    }
+
+    pub fn transcript_repr_gwc(self: Pin<&mut Self>, _prover: &GWCProver) -> Box<Fr> {
+        match *self {}
+    }
+
+    pub fn transcript_repr_shplonk(self: Pin<&mut Self>, _prover: &SHPlonkProver) -> Box<Fr> {
+        match *self {}
+    }
+}
+
+pub enum Evals {}
+
+pub fn zero_evals() -> UniquePtr<Evals> {
+    unavailable()
+}
+
+// `len()` mirrors the C++ method, which has no `is_empty()` counterpart.
+#[allow(clippy::len_without_is_empty)]
+impl Evals {
+    pub fn len(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn get_value(&self, _idx: usize) -> Box<Fr> {
+        match *self {}
+    }
+
+    pub fn set_value(self: Pin<&mut Self>, _idx: usize, _value: &Fr) {
+        match *self {}
+    }
+
+    pub fn get_values(&self, _values: &mut [Fr]) {
+        match *self {}
+    }
+
+    pub fn set_values(self: Pin<&mut Self>, _values: &[Fr]) {
+        match *self {}
+    }
+
+    pub fn reset(self: Pin<&mut Self>) {
+        match *self {}
+    }
+
+    pub fn clone(&self) -> UniquePtr<Evals> {
+        match *self {}
+    }
+}
+
+pub enum RationalEvals {}
+
+#[allow(clippy::len_without_is_empty)]
+impl RationalEvals {
+    pub fn len(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn set_zero(self: Pin<&mut Self>, _idx: usize) {
+        match *self {}
+    }
+
+    pub fn set_trivial(self: Pin<&mut Self>, _idx: usize, _numerator: &Fr) {
+        match *self {}
+    }
+
+    pub fn set_rational(self: Pin<&mut Self>, _idx: usize, _numerator: &Fr, _denominator: &Fr) {
+        match *self {}
+    }
+
+    pub fn reset(self: Pin<&mut Self>) {
+        match *self {}
+    }
+
+    pub fn clone(&self) -> UniquePtr<RationalEvals> {
+        match *self {}
+    }
+}
+
+pub enum Poly {}
+
+pub fn zero_poly() -> UniquePtr<Poly> {
+    unavailable()
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Poly {
+    pub fn len(&self) -> usize {
+        match *self {}
+    }
+
+    pub fn get_coeffs(&self, _coeffs: &mut [Fr]) {
+        match *self {}
+    }
+
+    pub fn set_coeffs(self: Pin<&mut Self>, _coeffs: &[Fr]) {
+        match *self {}
+    }
+
+    pub fn clone(&self) -> UniquePtr<Poly> {
+        match *self {}
+    }
+}
+
+pub fn empty_coset_evals(_k: u32) -> UniquePtr<Evals> {
+    unavailable()
+}
+
+pub fn coset_fft(poly: &Poly, _k: u32, _offset: &Fr) -> UniquePtr<Evals> {
+    match *poly {}
+}
+
+pub fn coset_ifft(evals: &Evals, _k: u32, _offset: &Fr) -> UniquePtr<Poly> {
+    match *evals {}
+}
+
+pub enum GWCProver {}
+
+pub fn new_gwc_prover(_transcript_type: u8, _k: u32, _s: &Fr) -> UniquePtr<GWCProver> {
+    unavailable()
+}
+
+pub fn new_gwc_prover_from_params(
+    _transcript_type: u8,
+    _k: u32,
+    _params: &[u8],
+) -> UniquePtr<GWCProver> {
+    unavailable()
+}
+
+impl GWCProver {
+    pub fn k(&self) -> u32 {
+        match *self {}
+    }
+
+    pub fn n(&self) -> u64 {
+        match *self {}
+    }
+
+    pub fn s_g2(&self) -> &G2AffinePoint {
+        match *self {}
+    }
+
+    pub fn commit(&self, _poly: &Poly) -> Box<G1JacobianPoint> {
+        match *self {}
+    }
+
+    pub fn commit_lagrange(&self, _evals: &Evals) -> Box<G1JacobianPoint> {
+        match *self {}
+    }
+
+    pub fn empty_evals(&self) -> UniquePtr<Evals> {
+        match *self {}
+    }
+
+    pub fn empty_rational_evals(&self) -> UniquePtr<RationalEvals> {
+        match *self {}
+    }
+
+    pub fn ifft(&self, _evals: &Evals) -> UniquePtr<Poly> {
+        match *self {}
+    }
+
+    pub fn column_stats(&self, _evals: &Evals) -> ColumnStats {
+        match *self {}
+    }
+
+    pub fn batch_evaluate(
+        &self,
+        _rational_evals: &[UniquePtr<RationalEvals>],
+        _evals: &mut [UniquePtr<Evals>],
+    ) {
+        match *self {}
+    }
+
+    pub fn set_rng(self: Pin<&mut Self>, _state: &[u8]) {
+        match *self {}
+    }
+
+    pub fn set_transcript(self: Pin<&mut Self>, _state: &[u8]) {
+        match *self {}
+    }
+
+    /// # Safety
+    ///
+    /// Never dereferences `callbacks`.
+    pub unsafe fn set_transcript_callbacks(
+        self: Pin<&mut Self>,
+        _callbacks: *mut TranscriptCallbacks,
+    ) {
+        match *self {}
+    }
+
+    pub fn set_extended_domain(self: Pin<&mut Self>, _pk: &ProvingKey) {
+        match *self {}
+    }
+
+    pub fn set_intra_proof_parallelism(self: Pin<&mut Self>, _intra_proof_parallelism: usize) {
+        match *self {}
+    }
+
+    pub fn set_gpu_stages(self: Pin<&mut Self>, _gpu_stages: u8) {
+        match *self {}
+    }
+
+    pub fn set_fft_chunk_log2(self: Pin<&mut Self>, _fft_chunk_log2: u32) {
+        match *self {}
+    }
+
+    pub fn set_num_threads(self: Pin<&mut Self>, _num_threads: usize) {
+        match *self {}
+    }
+
+    pub fn set_lagrange_basis_policy(self: Pin<&mut Self>, _policy: u8) -> bool {
+        match *self {}
+    }
+
+    pub fn set_record_gate_costs(self: Pin<&mut Self>, _record_gate_costs: bool) {
+        match *self {}
+    }
+
+    pub fn create_proof(
+        self: Pin<&mut Self>,
+        _key: Pin<&mut ProvingKey>,
+        _instance_singles: &mut [InstanceSingle],
+        _advice_singles: &mut [AdviceSingle],
+        _challenges: &[Fr],
+    ) {
+        match *self {}
+    }
+
+    pub fn get_proof(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn get_transcript_state(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn pcs_params(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn gate_costs(&self) -> Vec<GateCost> {
+        match *self {}
+    }
+}
+
+pub fn commit_gwc_instance_columns(
+    _transcript_type: u8,
+    _k: u32,
+    _pcs_params: &[u8],
+    pk: &ProvingKey,
+    _column_lens: &[usize],
+    _values: &[Fr],
+    _commitments: &mut [G1Point2],
+) -> bool {
+    match *pk {}
+}
+
+pub fn verify_gwc_proof_with_instance_commitments(
+    _transcript_type: u8,
+    _k: u32,
+    _pcs_params: &[u8],
+    _proof: &[u8],
+    pk: &ProvingKey,
+    _num_circuits: usize,
+    _commitments: &[G1Point2],
+) -> bool {
+    match *pk {}
+}
+
+pub enum SHPlonkProver {}
+
+pub fn new_shplonk_prover(_transcript_type: u8, _k: u32, _s: &Fr) -> UniquePtr<SHPlonkProver> {
+    unavailable()
+}
+
+pub fn new_shplonk_prover_from_params(
+    _transcript_type: u8,
+    _k: u32,
+    _params: &[u8],
+) -> UniquePtr<SHPlonkProver> {
+    unavailable()
+}
+
+impl SHPlonkProver {
+    pub fn k(&self) -> u32 {
+        match *self {}
+    }
+
+    pub fn n(&self) -> u64 {
+        match *self {}
+    }
+
+    pub fn s_g2(&self) -> &G2AffinePoint {
+        match *self {}
+    }
+
+    pub fn commit(&self, _poly: &Poly) -> Box<G1JacobianPoint> {
+        match *self {}
+    }
+
+    pub fn commit_lagrange(&self, _evals: &Evals) -> Box<G1JacobianPoint> {
+        match *self {}
+    }
+
+    pub fn empty_evals(&self) -> UniquePtr<Evals> {
+        match *self {}
+    }
+
+    pub fn empty_rational_evals(&self) -> UniquePtr<RationalEvals> {
+        match *self {}
+    }
+
+    pub fn ifft(&self, _evals: &Evals) -> UniquePtr<Poly> {
+        match *self {}
+    }
+
+    pub fn column_stats(&self, _evals: &Evals) -> ColumnStats {
+        match *self {}
+    }
+
+    pub fn batch_evaluate(
+        &self,
+        _rational_evals: &[UniquePtr<RationalEvals>],
+        _evals: &mut [UniquePtr<Evals>],
+    ) {
+        match *self {}
+    }
+
+    pub fn set_rng(self: Pin<&mut Self>, _state: &[u8]) {
+        match *self {}
+    }
+
+    pub fn set_transcript(self: Pin<&mut Self>, _state: &[u8]) {
+        match *self {}
+    }
+
+    /// # Safety
+    ///
+    /// Never dereferences `callbacks`.
+    pub unsafe fn set_transcript_callbacks(
+        self: Pin<&mut Self>,
+        _callbacks: *mut TranscriptCallbacks,
+    ) {
+        match *self {}
+    }
+
+    pub fn set_extended_domain(self: Pin<&mut Self>, _pk: &ProvingKey) {
+        match *self {}
+    }
+
+    pub fn set_intra_proof_parallelism(self: Pin<&mut Self>, _intra_proof_parallelism: usize) {
+        match *self {}
+    }
+
+    pub fn set_gpu_stages(self: Pin<&mut Self>, _gpu_stages: u8) {
+        match *self {}
+    }
+
+    pub fn set_fft_chunk_log2(self: Pin<&mut Self>, _fft_chunk_log2: u32) {
+        match *self {}
+    }
+
+    pub fn set_num_threads(self: Pin<&mut Self>, _num_threads: usize) {
+        match *self {}
+    }
+
+    pub fn set_lagrange_basis_policy(self: Pin<&mut Self>, _policy: u8) -> bool {
+        match *self {}
+    }
+
+    pub fn set_record_gate_costs(self: Pin<&mut Self>, _record_gate_costs: bool) {
+        match *self {}
+    }
+
+    pub fn create_proof(
+        self: Pin<&mut Self>,
+        _key: Pin<&mut ProvingKey>,
+        _instance_singles: &mut [InstanceSingle],
+        _advice_singles: &mut [AdviceSingle],
+        _challenges: &[Fr],
+    ) {
+        match *self {}
+    }
+
+    pub fn add_opening(self: Pin<&mut Self>, _poly: &Poly, _point: &Fr) {
+        match *self {}
+    }
+
+    pub fn create_opening_proof(self: Pin<&mut Self>) {
+        match *self {}
+    }
+
+    pub fn get_proof(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn get_transcript_state(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn pcs_params(&self) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn gate_costs(&self) -> Vec<GateCost> {
+        match *self {}
+    }
+
+    pub fn allocation_stats(&self) -> AllocationStats {
+        match *self {}
+    }
+
+    pub fn scratch_stats(&self) -> ScratchStats {
+        match *self {}
+    }
+
+    pub fn release_scratch(self: Pin<&mut Self>) {
+        match *self {}
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn verify_shplonk_proof(
+    _transcript_type: u8,
+    _k: u32,
+    _pcs_params: &[u8],
+    _proof: &[u8],
+    pk: &ProvingKey,
+    _num_circuits: usize,
+    _column_lens: &[usize],
+    _values: &[Fr],
+) -> bool {
+    match *pk {}
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn verify_shplonk_proof_from_state(
+    _transcript_type: u8,
+    _k: u32,
+    _pcs_params: &[u8],
+    _proof: &[u8],
+    pk: &ProvingKey,
+    _num_circuits: usize,
+    _column_lens: &[usize],
+    _values: &[Fr],
+    _state: &[u8],
+    _next_state: &mut Vec<u8>,
+) -> bool {
+    match *pk {}
+}
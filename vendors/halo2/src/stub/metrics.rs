@@ -0,0 +1,4 @@
+// Without libtachyon there is no device to report on.
+pub fn device_memory_in_use(_bytes: &mut usize) -> bool {
+    false
+}
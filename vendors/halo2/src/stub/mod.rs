@@ -0,0 +1,43 @@
+// Stand-ins for the cxx bridges, used with the "stub-backend" feature. They
+// have the same signatures as the bridges, so the crate builds without
+// libtachyon. Every function that would create a C++ object panics with
+// [`TachyonError::BackendUnavailable`] instead, and since the C++ types are
+// uninhabited, no method on them can ever be called.
+
+pub mod backend_log;
+pub mod bn254;
+pub mod metrics;
+pub mod version;
+pub mod xor_shift_rng;
+
+use std::{ops::Deref, pin::Pin};
+
+use crate::{error::TachyonError, version::STUB_BACKEND_REASON};
+
+/// Mirrors the parts of `cxx::UniquePtr` the crate uses. It is uninhabited
+/// whenever `T` is.
+pub struct UniquePtr<T>(T);
+
+impl<T> UniquePtr<T> {
+    pub fn pin_mut(&mut self) -> Pin<&mut T> {
+        // SAFETY: The value is never moved out of `self`.
+        unsafe { Pin::new_unchecked(&mut self.0) }
+    }
+}
+
+impl<T> Deref for UniquePtr<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+pub(crate) fn unavailable() -> ! {
+    panic!(
+        "{}",
+        TachyonError::BackendUnavailable {
+            reason: STUB_BACKEND_REASON.to_owned(),
+        }
+    )
+}
@@ -0,0 +1,9 @@
+use super::unavailable;
+
+pub fn abi_version() -> u32 {
+    unavailable()
+}
+
+pub fn runtime_version() -> String {
+    unavailable()
+}
@@ -0,0 +1,23 @@
+use std::pin::Pin;
+
+use super::{unavailable, UniquePtr};
+
+pub enum XORShiftRng {}
+
+pub fn new_xor_shift_rng(_seed: [u8; 16]) -> UniquePtr<XORShiftRng> {
+    unavailable()
+}
+
+impl XORShiftRng {
+    pub fn next_u32(self: Pin<&mut Self>) -> u32 {
+        match *self {}
+    }
+
+    pub fn clone(&self) -> UniquePtr<XORShiftRng> {
+        match *self {}
+    }
+
+    pub fn state(&self) -> Vec<u8> {
+        match *self {}
+    }
+}
@@ -1,24 +1,741 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    io::{self, Read, Write},
+    sync::Mutex,
+};
+
+use ff::PrimeField;
+use halo2_proofs::{
+    plonk::VerifyingKey,
+    transcript::{Challenge255, EncodedChallenge, Transcript, TranscriptRead, TranscriptWrite},
+};
+use halo2curves::{
+    bn256::{Fr, G1Affine, G2Affine},
+    group::GroupEncoding,
+    CurveAffine,
+};
+
+use crate::{
+    bn254::{TranscriptFork, TranscriptResume, TranscriptWriteState},
+    consts::{MultiOpenScheme, TranscriptType},
+    state::{state_body, versioned, StateError, StateKind},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptEntry<C: CurveAffine> {
+    /// A point absorbed without being written to the proof.
+    CommonPoint(C),
+    /// A scalar absorbed without being written to the proof.
+    CommonScalar(C::Scalar),
+    /// A G2 point absorbed with [`TranscriptWriteState::common_g2()`].
+    CommonG2(G2Affine),
+    WritePoint(C),
+    WriteScalar(C::Scalar),
+    /// The scalar of a squeezed challenge.
+    Challenge(C::Scalar),
+}
+
+/// Every operation on a transcript in the order it happened.
+pub type TranscriptLog<C> = Vec<TranscriptEntry<C>>;
+
+/// Delegates to `T` and records every operation that succeeded in a
+/// [`TranscriptLog`]. Since `state()` is forwarded too, this can be passed to
+/// [`crate::prover::create_proof()`] wherever `T` can.
+pub struct RecordingTranscript<C: CurveAffine, T> {
+    inner: T,
+    log: TranscriptLog<C>,
+}
+
+impl<C: CurveAffine, T: fmt::Debug> fmt::Debug for RecordingTranscript<C, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingTranscript")
+            .field("inner", &self.inner)
+            .field("log_len", &self.log.len())
+            .finish()
+    }
+}
+
+impl<C: CurveAffine, T> RecordingTranscript<C, T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner, log: vec![] }
+    }
+
+    pub fn log(&self) -> &TranscriptLog<C> {
+        &self.log
+    }
+
+    pub fn into_parts(self) -> (T, TranscriptLog<C>) {
+        (self.inner, self.log)
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E>
+    for RecordingTranscript<C, T>
+{
+    fn squeeze_challenge(&mut self) -> E {
+        let challenge = self.inner.squeeze_challenge();
+        self.log
+            .push(TranscriptEntry::Challenge(challenge.get_scalar()));
+        challenge
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.common_point(point)?;
+        self.log.push(TranscriptEntry::CommonPoint(point));
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.common_scalar(scalar)?;
+        self.log.push(TranscriptEntry::CommonScalar(scalar));
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWrite<C, E>> TranscriptWrite<C, E>
+    for RecordingTranscript<C, T>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.write_point(point)?;
+        self.log.push(TranscriptEntry::WritePoint(point));
+        Ok(())
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.write_scalar(scalar)?;
+        self.log.push(TranscriptEntry::WriteScalar(scalar));
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWriteState<C, E>>
+    TranscriptWriteState<C, E> for RecordingTranscript<C, T>
+{
+    const TRANSCRIPT_TYPE: TranscriptType = T::TRANSCRIPT_TYPE;
+
+    fn state(&self) -> Vec<u8> {
+        self.inner.state()
+    }
+
+    fn common_g2(&mut self, point: G2Affine) -> io::Result<()> {
+        self.inner.common_g2(point)?;
+        self.log.push(TranscriptEntry::CommonG2(point));
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptFork<C, E>> TranscriptFork<C, E>
+    for RecordingTranscript<C, T>
+{
+    fn fork(&self) -> Self {
+        Self {
+            inner: self.inner.fork(),
+            log: self.log.clone(),
+        }
+    }
+}
+
+/// A hasher for transcripts of type [`TranscriptType::Custom`], registered
+/// under an id with [`register_custom()`]. [`CustomWrite`] and [`CustomRead`]
+/// drive it directly, and the backend calls back into it for everything it
+/// absorbs and squeezes, see `TachyonProver::set_transcript()`.
+pub trait TranscriptHasher: Send {
+    fn absorb_scalar(&mut self, scalar: &Fr);
+
+    /// Never called with the point at infinity, which can't be written to a
+    /// transcript.
+    fn absorb_point(&mut self, point: &G1Affine);
+
+    fn squeeze(&mut self) -> Fr;
+
+    /// The bytes [`TranscriptHasher::set_state()`] continues from.
+    fn state(&self) -> Vec<u8>;
+
+    /// Fails if `state` wasn't written by [`TranscriptHasher::state()`] of a
+    /// hasher of this kind.
+    fn set_state(&mut self, state: &[u8]) -> io::Result<()>;
+
+    /// A hasher in the same state, so that a transcript can be forked.
+    fn boxed_clone(&self) -> Box<dyn TranscriptHasher>;
+}
+
+type Registry = Mutex<BTreeMap<u8, Box<dyn TranscriptHasher>>>;
+
+fn registry() -> &'static Registry {
+    static REGISTRY: Registry = Mutex::new(BTreeMap::new());
+    &REGISTRY
+}
+
+/// Registers `hasher` under `id`, replacing the hasher registered under it
+/// before. Every transcript with this id starts from a clone of `hasher`, so
+/// it should be in its initial state.
+pub fn register_custom(id: u8, hasher: Box<dyn TranscriptHasher>) {
+    registry()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id, hasher);
+}
+
+/// A clone of the hasher registered under `id`.
+pub(crate) fn custom_hasher(id: u8) -> Result<Box<dyn TranscriptHasher>, StateError> {
+    registry()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&id)
+        .map(|hasher| hasher.boxed_clone())
+        .ok_or(StateError::UnregisteredHasher(id))
+}
+
+/// Reads a state as written by [`custom_state()`], and returns the id it was
+/// written with and the registered hasher continued from it.
+pub(crate) fn custom_hasher_from_state(
+    state: &[u8],
+) -> Result<(u8, Box<dyn TranscriptHasher>), StateError> {
+    let body = state_body(StateKind::Transcript(TranscriptType::Custom), state)?;
+    custom_hasher_from_body(body)
+}
+
+/// Like [`custom_hasher_from_state()`] for a state without its version byte.
+pub(crate) fn custom_hasher_from_body(
+    body: &[u8],
+) -> Result<(u8, Box<dyn TranscriptHasher>), StateError> {
+    let (id, hasher_state) = body.split_first().ok_or(StateError::Length {
+        kind: StateKind::Transcript(TranscriptType::Custom),
+        expected: 1,
+        found: 0,
+    })?;
+    let mut hasher = custom_hasher(*id)?;
+    hasher
+        .set_state(hasher_state)
+        .map_err(|e| StateError::Hasher {
+            id: *id,
+            reason: e.to_string(),
+        })?;
+    Ok((*id, hasher))
+}
+
+/// The state of a custom transcript is the id of its hasher followed by the
+/// hasher's own state.
+pub(crate) fn custom_state(id: u8, hasher: &dyn TranscriptHasher) -> Vec<u8> {
+    let mut body = vec![id];
+    body.extend(hasher.state());
+    versioned(body)
+}
+
+// Like the Poseidon transcript, a squeezed scalar is the challenge itself.
+fn custom_challenge(scalar: Fr) -> Challenge255<G1Affine> {
+    let mut bytes = [0; 64];
+    bytes[..32].copy_from_slice(scalar.to_repr().as_ref());
+    Challenge255::new(&bytes)
+}
+
+fn absorb_custom_point(hasher: &mut dyn TranscriptHasher, point: G1Affine) -> io::Result<()> {
+    if bool::from(point.coordinates().is_none()) {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "cannot write points at infinity to the transcript",
+        ));
+    }
+    hasher.absorb_point(&point);
+    Ok(())
+}
+
+/// Writes a proof with the hasher registered under an id, see
+/// [`register_custom()`]. Points are compressed, like with Blake2b.
+pub struct CustomWrite<W: Write> {
+    id: u8,
+    hasher: Box<dyn TranscriptHasher>,
+    writer: W,
+}
+
+impl<W: Write> fmt::Debug for CustomWrite<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomWrite")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: Write> CustomWrite<W> {
+    /// Starts a transcript with the hasher registered under `id`. Fails if
+    /// there is none.
+    pub fn init(id: u8, writer: W) -> Result<Self, StateError> {
+        Ok(CustomWrite {
+            id,
+            hasher: custom_hasher(id)?,
+            writer,
+        })
+    }
+
+    pub fn finalize(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Transcript<G1Affine, Challenge255<G1Affine>> for CustomWrite<W> {
+    fn squeeze_challenge(&mut self) -> Challenge255<G1Affine> {
+        custom_challenge(self.hasher.squeeze())
+    }
+
+    fn common_point(&mut self, point: G1Affine) -> io::Result<()> {
+        absorb_custom_point(self.hasher.as_mut(), point)
+    }
+
+    fn common_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.hasher.absorb_scalar(&scalar);
+        Ok(())
+    }
+}
+
+impl<W: Write> TranscriptWrite<G1Affine, Challenge255<G1Affine>> for CustomWrite<W> {
+    fn write_point(&mut self, point: G1Affine) -> io::Result<()> {
+        self.common_point(point)?;
+        self.writer
+            .write_all(GroupEncoding::to_bytes(&point).as_ref())
+    }
+
+    fn write_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        self.writer.write_all(scalar.to_repr().as_ref())
+    }
+}
+
+impl<W: Write> TranscriptWriteState<G1Affine, Challenge255<G1Affine>> for CustomWrite<W> {
+    const TRANSCRIPT_TYPE: TranscriptType = TranscriptType::Custom;
+
+    fn state(&self) -> Vec<u8> {
+        custom_state(self.id, self.hasher.as_ref())
+    }
+}
+
+impl<W: Write + Clone> TranscriptFork<G1Affine, Challenge255<G1Affine>> for CustomWrite<W> {
+    fn fork(&self) -> Self {
+        CustomWrite {
+            id: self.id,
+            hasher: self.hasher.boxed_clone(),
+            writer: self.writer.clone(),
+        }
+    }
+}
+
+impl<W: Write> TranscriptResume<W, G1Affine, Challenge255<G1Affine>> for CustomWrite<W> {
+    fn resume(writer: W, state: &[u8]) -> Result<Self, StateError> {
+        let (id, hasher) = custom_hasher_from_state(state)?;
+        Ok(CustomWrite { id, hasher, writer })
+    }
+
+    fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads a proof written by [`CustomWrite`] with the same hasher.
+pub struct CustomRead<R: Read> {
+    id: u8,
+    hasher: Box<dyn TranscriptHasher>,
+    reader: R,
+}
+
+impl<R: Read> fmt::Debug for CustomRead<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomRead")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: Read> CustomRead<R> {
+    /// Starts a transcript with the hasher registered under `id`. Fails if
+    /// there is none.
+    pub fn init(id: u8, reader: R) -> Result<Self, StateError> {
+        Ok(CustomRead {
+            id,
+            hasher: custom_hasher(id)?,
+            reader,
+        })
+    }
+}
+
+impl<R: Read> Transcript<G1Affine, Challenge255<G1Affine>> for CustomRead<R> {
+    fn squeeze_challenge(&mut self) -> Challenge255<G1Affine> {
+        custom_challenge(self.hasher.squeeze())
+    }
+
+    fn common_point(&mut self, point: G1Affine) -> io::Result<()> {
+        absorb_custom_point(self.hasher.as_mut(), point)
+    }
+
+    fn common_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.hasher.absorb_scalar(&scalar);
+        Ok(())
+    }
+}
+
+impl<R: Read> TranscriptRead<G1Affine, Challenge255<G1Affine>> for CustomRead<R> {
+    fn read_point(&mut self) -> io::Result<G1Affine> {
+        let mut compressed = <G1Affine as GroupEncoding>::Repr::default();
+        self.reader.read_exact(compressed.as_mut())?;
+        let point: G1Affine = Option::from(<G1Affine as GroupEncoding>::from_bytes(&compressed))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+            })?;
+        self.common_point(point)?;
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<Fr> {
+        let mut data = <Fr as PrimeField>::Repr::default();
+        self.reader.read_exact(data.as_mut())?;
+        let scalar: Fr = Option::from(Fr::from_repr(data)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid field element encoding in proof",
+            )
+        })?;
+        self.common_scalar(scalar)?;
+        Ok(scalar)
+    }
+}
+
+/// What a value written to a proof stands for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Component {
+    AdviceCommitment,
+    LookupPermutedInputCommitment,
+    LookupPermutedTableCommitment,
+    PermutationProductCommitment,
+    LookupProductCommitment,
+    VanishingRandomCommitment,
+    QuotientCommitment,
+    InstanceEval,
+    AdviceEval,
+    FixedEval,
+    VanishingRandomEval,
+    CommonPermutationEval,
+    PermutationProductEval,
+    PermutationProductNextEval,
+    PermutationProductLastEval,
+    LookupProductEval,
+    LookupProductNextEval,
+    LookupPermutedInputEval,
+    LookupPermutedInputPrevEval,
+    LookupPermutedTableEval,
+    /// A commitment of the multi-open argument at the end of the proof.
+    OpeningCommitment,
+}
+
+impl Component {
+    pub const ALL: [Component; 21] = [
+        Component::AdviceCommitment,
+        Component::LookupPermutedInputCommitment,
+        Component::LookupPermutedTableCommitment,
+        Component::PermutationProductCommitment,
+        Component::LookupProductCommitment,
+        Component::VanishingRandomCommitment,
+        Component::QuotientCommitment,
+        Component::InstanceEval,
+        Component::AdviceEval,
+        Component::FixedEval,
+        Component::VanishingRandomEval,
+        Component::CommonPermutationEval,
+        Component::PermutationProductEval,
+        Component::PermutationProductNextEval,
+        Component::PermutationProductLastEval,
+        Component::LookupProductEval,
+        Component::LookupProductNextEval,
+        Component::LookupPermutedInputEval,
+        Component::LookupPermutedInputPrevEval,
+        Component::LookupPermutedTableEval,
+        Component::OpeningCommitment,
+    ];
+
+    /// The name the component is labeled with in audit logs and stored under
+    /// in repro files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Component::AdviceCommitment => "advice_commitment",
+            Component::LookupPermutedInputCommitment => "lookup_permuted_input_commitment",
+            Component::LookupPermutedTableCommitment => "lookup_permuted_table_commitment",
+            Component::PermutationProductCommitment => "permutation_product_commitment",
+            Component::LookupProductCommitment => "lookup_product_commitment",
+            Component::VanishingRandomCommitment => "vanishing_random_commitment",
+            Component::QuotientCommitment => "quotient_commitment",
+            Component::InstanceEval => "instance_eval",
+            Component::AdviceEval => "advice_eval",
+            Component::FixedEval => "fixed_eval",
+            Component::VanishingRandomEval => "vanishing_random_eval",
+            Component::CommonPermutationEval => "common_permutation_eval",
+            Component::PermutationProductEval => "permutation_product_eval",
+            Component::PermutationProductNextEval => "permutation_product_next_eval",
+            Component::PermutationProductLastEval => "permutation_product_last_eval",
+            Component::LookupProductEval => "lookup_product_eval",
+            Component::LookupProductNextEval => "lookup_product_next_eval",
+            Component::LookupPermutedInputEval => "lookup_permuted_input_eval",
+            Component::LookupPermutedInputPrevEval => "lookup_permuted_input_prev_eval",
+            Component::LookupPermutedTableEval => "lookup_permuted_table_eval",
+            Component::OpeningCommitment => "opening_commitment",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|component| component.name() == name)
+            .copied()
+    }
+
+    /// Whether the value is a point. Every other value is a scalar.
+    pub fn is_point(&self) -> bool {
+        matches!(
+            self,
+            Component::AdviceCommitment
+                | Component::LookupPermutedInputCommitment
+                | Component::LookupPermutedTableCommitment
+                | Component::PermutationProductCommitment
+                | Component::LookupProductCommitment
+                | Component::VanishingRandomCommitment
+                | Component::QuotientCommitment
+                | Component::OpeningCommitment
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TranscriptItem {
+    pub component: Component,
+    /// The circuit the value belongs to, or `None` if it is shared by all of
+    /// them.
+    pub circuit: Option<usize>,
+    /// The column, query, lookup, permutation set or piece the value is for.
+    pub index: usize,
+}
+
+impl TranscriptItem {
+    pub(crate) fn new(component: Component, circuit: Option<usize>, index: usize) -> Self {
+        Self {
+            component,
+            circuit,
+            index,
+        }
+    }
+}
+
+/// Returns every value a proof of `num_circuits` circuits over `vk` writes,
+/// in the order they are written. This mirrors the order the verifier and
+/// tachyon's `ProofReader` read them in.
+pub fn transcript_layout<C: CurveAffine>(
+    vk: &VerifyingKey<C>,
+    num_circuits: usize,
+    scheme: MultiOpenScheme,
+) -> Vec<TranscriptItem> {
+    let cs = vk.cs();
+    let query_instance = scheme == MultiOpenScheme::Gwc;
+    let num_lookups = cs.lookups().len();
+    let num_permutation_columns = cs.permutation().get_columns().len();
+    // Each permutation product covers as many columns as fit in the degree
+    // bound, see `permutation::Argument::commit()`.
+    let chunk_len = cs.degree() - 2;
+    let num_permutation_products = (num_permutation_columns + chunk_len - 1) / chunk_len;
+    let circuits = || (0..num_circuits).map(Some);
+
+    let mut layout = vec![];
+    for phase in cs.phases() {
+        for circuit in circuits() {
+            for (column, column_phase) in cs.advice_column_phase.iter().enumerate() {
+                if *column_phase == phase {
+                    layout.push(TranscriptItem::new(
+                        Component::AdviceCommitment,
+                        circuit,
+                        column,
+                    ));
+                }
+            }
+        }
+    }
+    for circuit in circuits() {
+        for lookup in 0..num_lookups {
+            layout.push(TranscriptItem::new(
+                Component::LookupPermutedInputCommitment,
+                circuit,
+                lookup,
+            ));
+            layout.push(TranscriptItem::new(
+                Component::LookupPermutedTableCommitment,
+                circuit,
+                lookup,
+            ));
+        }
+    }
+    for circuit in circuits() {
+        for set in 0..num_permutation_products {
+            layout.push(TranscriptItem::new(
+                Component::PermutationProductCommitment,
+                circuit,
+                set,
+            ));
+        }
+    }
+    for circuit in circuits() {
+        for lookup in 0..num_lookups {
+            layout.push(TranscriptItem::new(
+                Component::LookupProductCommitment,
+                circuit,
+                lookup,
+            ));
+        }
+    }
+    layout.push(TranscriptItem::new(
+        Component::VanishingRandomCommitment,
+        None,
+        0,
+    ));
+    for piece in 0..cs.degree() - 1 {
+        layout.push(TranscriptItem::new(
+            Component::QuotientCommitment,
+            None,
+            piece,
+        ));
+    }
+
+    if query_instance {
+        for circuit in circuits() {
+            for query in 0..cs.instance_queries().len() {
+                layout.push(TranscriptItem::new(Component::InstanceEval, circuit, query));
+            }
+        }
+    }
+    for circuit in circuits() {
+        for query in 0..cs.advice_queries().len() {
+            layout.push(TranscriptItem::new(Component::AdviceEval, circuit, query));
+        }
+    }
+    for query in 0..cs.fixed_queries().len() {
+        layout.push(TranscriptItem::new(Component::FixedEval, None, query));
+    }
+    layout.push(TranscriptItem::new(Component::VanishingRandomEval, None, 0));
+    for column in 0..num_permutation_columns {
+        layout.push(TranscriptItem::new(
+            Component::CommonPermutationEval,
+            None,
+            column,
+        ));
+    }
+    for circuit in circuits() {
+        for set in 0..num_permutation_products {
+            layout.push(TranscriptItem::new(
+                Component::PermutationProductEval,
+                circuit,
+                set,
+            ));
+            layout.push(TranscriptItem::new(
+                Component::PermutationProductNextEval,
+                circuit,
+                set,
+            ));
+            if set + 1 != num_permutation_products {
+                layout.push(TranscriptItem::new(
+                    Component::PermutationProductLastEval,
+                    circuit,
+                    set,
+                ));
+            }
+        }
+    }
+    for circuit in circuits() {
+        for lookup in 0..num_lookups {
+            for component in [
+                Component::LookupProductEval,
+                Component::LookupProductNextEval,
+                Component::LookupPermutedInputEval,
+                Component::LookupPermutedInputPrevEval,
+                Component::LookupPermutedTableEval,
+            ] {
+                layout.push(TranscriptItem::new(component, circuit, lookup));
+            }
+        }
+    }
+
+    let num_openings = match scheme {
+        // SHPLONK always writes `h` and the final `W`.
+        MultiOpenScheme::SHPlonk => 2,
+        // GWC writes one witness per distinct rotation that is opened.
+        MultiOpenScheme::Gwc => {
+            let mut rotations = BTreeSet::from([0]);
+            if query_instance {
+                rotations.extend(cs.instance_queries().iter().map(|(_, rotation)| rotation.0));
+            }
+            rotations.extend(cs.advice_queries().iter().map(|(_, rotation)| rotation.0));
+            rotations.extend(cs.fixed_queries().iter().map(|(_, rotation)| rotation.0));
+            if num_permutation_products > 0 {
+                rotations.insert(1);
+            }
+            if num_permutation_products > 1 {
+                rotations.insert(-(cs.blinding_factors() as i32 + 1));
+            }
+            if num_lookups > 0 {
+                rotations.extend([1, -1]);
+            }
+            rotations.len()
+        }
+    };
+    for opening in 0..num_openings {
+        layout.push(TranscriptItem::new(
+            Component::OpeningCommitment,
+            None,
+            opening,
+        ));
+    }
+    layout
+}
+
 #[cfg(test)]
 mod test {
-    use std::io;
+    use std::{io, time::Instant};
 
+    use super::{
+        register_custom, CustomRead, CustomWrite, RecordingTranscript, TranscriptEntry,
+        TranscriptHasher,
+    };
     use crate::{
         bn254::{
             Blake2bWrite as TachyonBlake2bWrite, PoseidonWrite as TachyonPoseidonWrite,
-            Sha256Write as TachyonSha256Write, TranscriptWriteState,
+            ProvingKey as TachyonProvingKey, SHPlonkProver, Sha256Write as TachyonSha256Write,
+            TachyonProver, TranscriptResume, TranscriptWriteState,
         },
+        circuits::{shuffle_circuit::MyCircuit, simple_circuit::SimpleCircuit},
+        consts::{TranscriptType, SEED},
+        prover::create_proof as tachyon_create_proof,
         sha::ShaWrite,
+        state::StateError,
+        xor_shift_rng::XORShiftRng,
     };
-    use ff::Field;
-    use halo2_proofs::transcript::{
-        Blake2bWrite, Challenge255, ChallengeScalar, EncodedChallenge, PoseidonWrite,
-        TranscriptWrite, TranscriptWriterBuffer,
+    use ff::{Field, PrimeField};
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::{keygen_pk2, verify_proof},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::VerifierSHPLONK,
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, ChallengeScalar, EncodedChallenge,
+            PoseidonWrite, Transcript, TranscriptReadBuffer, TranscriptWrite,
+            TranscriptWriterBuffer,
+        },
     };
     use halo2curves::{
-        bn256::{Bn256, Fr, G1Affine},
+        bn256::{Bn256, Fr, G1Affine, G2Affine},
+        group::{prime::PrimeCurveAffine, GroupEncoding},
         pairing::Engine,
+        FieldExt,
     };
-    use rand_core::OsRng;
+    use rand_core::{OsRng, SeedableRng};
+    use sha2::{Digest, Sha256};
 
     #[derive(Clone, Copy, Debug)]
     struct Theta;
@@ -57,6 +774,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_blake2b_write_scalar_to_proof() {
         let fr = Fr::random(OsRng);
         let proof = {
@@ -73,6 +791,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_blake2b_write_point_to_proof() {
         let point = G1Affine::random(OsRng);
         let proof = {
@@ -89,6 +808,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_blake2b_squeeze_challenge() {
         let point = G1Affine::random(OsRng);
         let theta = {
@@ -107,12 +827,13 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_blake2b_state() {
         let transcript = TachyonBlake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
         assert_eq!(
             transcript.state(),
             vec![
-                72, 201, 189, 242, 103, 230, 9, 106, 59, 167, 202, 132, 133, 174, 103, 187, 43,
+                1, 72, 201, 189, 242, 103, 230, 9, 106, 59, 167, 202, 132, 133, 174, 103, 187, 43,
                 248, 148, 254, 114, 243, 110, 60, 241, 54, 29, 95, 58, 245, 79, 165, 209, 130, 230,
                 173, 127, 82, 14, 81, 31, 108, 62, 43, 140, 104, 5, 155, 35, 220, 45, 148, 153,
                 244, 215, 109, 24, 79, 13, 112, 107, 164, 144, 47, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
@@ -127,6 +848,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_poseidon_write_scalar_to_proof() {
         let fr = Fr::random(OsRng);
         let proof = {
@@ -143,6 +865,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_poseidon_write_point_to_proof() {
         let point = G1Affine::random(OsRng);
         let proof = {
@@ -159,6 +882,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_poseidon_squeeze_challenge() {
         let point = G1Affine::random(OsRng);
         let theta = {
@@ -177,12 +901,13 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_poseidon_state() {
         let transcript = TachyonPoseidonWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
         assert_eq!(
             transcript.state(),
             vec![
-                9, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0,
+                1, 9, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0,
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
@@ -193,12 +918,13 @@ mod test {
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
             ]
         );
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_sha256_write_scalar_to_proof() {
         let fr = Fr::random(OsRng);
         let proof = {
@@ -216,6 +942,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_sha256_write_point_to_proof() {
         let point = G1Affine::random(OsRng);
         let proof = {
@@ -233,6 +960,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_sha256_squeeze_challenge() {
         let point = G1Affine::random(OsRng);
         let theta = {
@@ -252,17 +980,389 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_sha256_state() {
         let transcript = TachyonSha256Write::<_, G1Affine, Challenge255<_>>::init(vec![]);
         assert_eq!(
             transcript.state(),
             vec![
-                103, 230, 9, 106, 133, 174, 103, 187, 114, 243, 110, 60, 58, 245, 79, 165, 127, 82,
-                14, 81, 140, 104, 5, 155, 171, 217, 131, 31, 25, 205, 224, 91, 0, 0, 0, 0, 0, 0, 0,
+                1, 103, 230, 9, 106, 133, 174, 103, 187, 114, 243, 110, 60, 58, 245, 79, 165, 127,
+                82, 14, 81, 140, 104, 5, 155, 171, 217, 131, 31, 25, 205, 224, 91, 0, 0, 0, 0, 0,
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0, 0
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0, 0
             ]
         );
     }
+
+    /// Writes the same random points and scalars to two transcripts, one at a
+    /// time and in batches, and checks that they squeeze the same challenges
+    /// and write the same proof.
+    fn assert_batched_writes_match<T: TranscriptWriteState<G1Affine, Challenge255<G1Affine>>>(
+        init: impl Fn() -> T,
+        finalize: impl Fn(T) -> Vec<u8>,
+    ) {
+        for len in [0, 1, 2, 17] {
+            let points: Vec<_> = (0..len).map(|_| G1Affine::random(OsRng)).collect();
+            let scalars: Vec<_> = (0..len).map(|_| Fr::random(OsRng)).collect();
+
+            let mut transcript = init();
+            let mut challenges = vec![];
+            for point in &points {
+                transcript.write_point(*point).unwrap();
+            }
+            challenges.push(*squeeze_challenge::<Bn256, _, _>(&mut transcript));
+            for scalar in &scalars {
+                transcript.common_scalar(*scalar).unwrap();
+            }
+            challenges.push(*squeeze_challenge::<Bn256, _, _>(&mut transcript));
+            let proof = finalize(transcript);
+
+            let mut transcript = init();
+            let mut batched_challenges = vec![];
+            transcript.write_points(&points).unwrap();
+            batched_challenges.push(*squeeze_challenge::<Bn256, _, _>(&mut transcript));
+            transcript.common_scalars(&scalars).unwrap();
+            batched_challenges.push(*squeeze_challenge::<Bn256, _, _>(&mut transcript));
+
+            assert_eq!(challenges, batched_challenges);
+            assert_eq!(proof, finalize(transcript));
+        }
+
+        let mut transcript = init();
+        assert!(transcript
+            .write_points(&[G1Affine::random(OsRng), G1Affine::default()])
+            .is_err());
+        assert!(finalize(transcript).is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_batched_writes() {
+        assert_batched_writes_match(
+            || TachyonBlake2bWrite::init(vec![]),
+            TachyonBlake2bWrite::finalize,
+        );
+        assert_batched_writes_match(
+            || TachyonPoseidonWrite::init(vec![]),
+            TachyonPoseidonWrite::finalize,
+        );
+        assert_batched_writes_match(
+            || TachyonSha256Write::init(vec![]),
+            TachyonSha256Write::finalize,
+        );
+    }
+
+    fn challenge_after_g2<T: TranscriptWriteState<G1Affine, Challenge255<G1Affine>>>(
+        mut transcript: T,
+    ) -> Fr {
+        transcript.common_g2(G2Affine::generator()).unwrap();
+        *squeeze_challenge::<Bn256, _, _>(&mut transcript)
+    }
+
+    // The challenges squeezed right after absorbing the generator of G2, for
+    // other implementations to check their encoding against. There is no
+    // Keccak transcript to pin one for.
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_common_g2() {
+        let blake2b = Fr::from_str_vartime(
+            "8485877884495084690242349810746896686302039545414909911328729374085441630446",
+        )
+        .unwrap();
+        assert_eq!(
+            challenge_after_g2(TachyonBlake2bWrite::init(vec![])),
+            blake2b
+        );
+        // halo2's transcript over G2 absorbs any point the same way.
+        let mut reference = Blake2bWrite::<_, G2Affine, Challenge255<_>>::init(vec![]);
+        reference.common_point(G2Affine::generator()).unwrap();
+        assert_eq!(reference.squeeze_challenge().get_scalar(), blake2b);
+
+        assert_eq!(
+            challenge_after_g2(TachyonSha256Write::init(vec![])),
+            Fr::from_str_vartime(
+                "6683693215198896867939232779388432772416967351438955951787506284633385763852"
+            )
+            .unwrap()
+        );
+    }
+
+    /// Checks that a G2 point absorbed before the state is handed off, e.g,
+    /// to the backend, is in the state, and that the point at infinity fails.
+    fn assert_g2_in_state<T: TranscriptResume<Vec<u8>, G1Affine, Challenge255<G1Affine>>>(
+        init: impl Fn() -> T,
+    ) {
+        let mut transcript = init();
+        transcript.common_g2(G2Affine::generator()).unwrap();
+        let mut resumed = T::resume(vec![], &transcript.state()).unwrap();
+        let challenge = *squeeze_challenge::<Bn256, _, _>(&mut transcript);
+        assert_eq!(*squeeze_challenge::<Bn256, _, _>(&mut resumed), challenge);
+        assert_ne!(*squeeze_challenge::<Bn256, _, _>(&mut init()), challenge);
+
+        let mut transcript = init();
+        assert!(transcript.common_g2(G2Affine::default()).is_err());
+        assert_eq!(transcript.state(), init().state());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_common_g2_state() {
+        assert_g2_in_state(|| TachyonBlake2bWrite::init(vec![]));
+        assert_g2_in_state(|| TachyonPoseidonWrite::init(vec![]));
+        assert_g2_in_state(|| TachyonSha256Write::init(vec![]));
+    }
+
+    // Times writing the advice commitments of a wide circuit one at a time
+    // and in a batch:
+    //   cargo test --release -- --ignored bench_write_points --nocapture
+    #[test]
+    #[ignore]
+    fn bench_write_points() {
+        let points: Vec<_> = (0..600).map(|_| G1Affine::random(OsRng)).collect();
+        for _ in 0..3 {
+            let start = Instant::now();
+            let mut transcript = TachyonBlake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            for point in &points {
+                transcript.write_point(*point).unwrap();
+            }
+            println!("per point: {:?}", start.elapsed());
+
+            let start = Instant::now();
+            let mut transcript = TachyonBlake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            transcript.write_points(&points).unwrap();
+            println!("batched:   {:?}", start.elapsed());
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_recording_transcript() {
+        const W: usize = 2;
+        const H: usize = 8;
+        let k = 4;
+        let circuit = MyCircuit::<Fr, W, H>::rand(&mut XORShiftRng::from_seed(SEED));
+
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).unwrap();
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let prove = |record: bool| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            let rng = XORShiftRng::from_seed(SEED);
+            let (transcript, log) = if record {
+                let mut transcript = RecordingTranscript::new(TachyonBlake2bWrite::init(vec![]));
+                tachyon_create_proof::<_, _, _, _, _>(
+                    &mut prover,
+                    &mut tachyon_pk,
+                    &[circuit.clone()],
+                    &[&[]],
+                    rng,
+                    &mut transcript,
+                )
+                .unwrap();
+                transcript.into_parts()
+            } else {
+                let mut transcript = TachyonBlake2bWrite::init(vec![]);
+                tachyon_create_proof::<_, _, _, _, _>(
+                    &mut prover,
+                    &mut tachyon_pk,
+                    &[circuit.clone()],
+                    &[&[]],
+                    rng,
+                    &mut transcript,
+                )
+                .unwrap();
+                (transcript, vec![])
+            };
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            (proof, log)
+        };
+        let (proof, _) = prove(false);
+        let (recorded_proof, log) = prove(true);
+        assert_eq!(proof, recorded_proof);
+
+        // The vk repr, the first phase advice commitments and both challenges,
+        // then the second phase advice commitment. The rest of the proof is
+        // written by the backend.
+        assert_eq!(log.len(), 1 + 2 * W + 2 + 1);
+        assert_eq!(
+            log[0],
+            TranscriptEntry::CommonScalar(pk.get_vk().transcript_repr())
+        );
+        assert!(log[1..1 + 2 * W]
+            .iter()
+            .all(|entry| matches!(entry, TranscriptEntry::WritePoint(_))));
+        assert!(log[1 + 2 * W..3 + 2 * W]
+            .iter()
+            .all(|entry| matches!(entry, TranscriptEntry::Challenge(_))));
+        assert!(matches!(log[3 + 2 * W], TranscriptEntry::WritePoint(_)));
+
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&recorded_proof[..]);
+        assert!(verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<_>,
+            _,
+            _,
+            SingleStrategy<_>,
+        >(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[]],
+            &mut transcript,
+        )
+        .is_ok());
+    }
+
+    /// Chains SHA-256 over everything absorbed.
+    #[derive(Clone, Default)]
+    struct ChainHasher {
+        digest: [u8; 32],
+    }
+
+    impl ChainHasher {
+        fn absorb(&mut self, bytes: &[u8]) {
+            self.digest = Sha256::new()
+                .chain_update(self.digest)
+                .chain_update(bytes)
+                .finalize()
+                .into();
+        }
+    }
+
+    impl TranscriptHasher for ChainHasher {
+        fn absorb_scalar(&mut self, scalar: &Fr) {
+            self.absorb(scalar.to_repr().as_ref());
+        }
+
+        fn absorb_point(&mut self, point: &G1Affine) {
+            self.absorb(GroupEncoding::to_bytes(point).as_ref());
+        }
+
+        fn squeeze(&mut self) -> Fr {
+            self.absorb(&[]);
+            let mut bytes = [0; 64];
+            bytes[..32].copy_from_slice(&self.digest);
+            Fr::from_bytes_wide(&bytes)
+        }
+
+        fn state(&self) -> Vec<u8> {
+            self.digest.to_vec()
+        }
+
+        fn set_state(&mut self, state: &[u8]) -> io::Result<()> {
+            self.digest = state.try_into().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "expected a 32 byte digest")
+            })?;
+            Ok(())
+        }
+
+        fn boxed_clone(&self) -> Box<dyn TranscriptHasher> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn test_custom_state() {
+        register_custom(200, Box::new(ChainHasher::default()));
+        let mut transcript = CustomWrite::init(200, vec![]).unwrap();
+        transcript.write_scalar(Fr::from(3)).unwrap();
+        let state = transcript.state();
+        assert_eq!(state[1], 200);
+        assert_eq!(state.len(), 1 + 1 + 32);
+
+        let mut resumed = CustomWrite::resume(vec![], &state).unwrap();
+        assert_eq!(
+            *squeeze_challenge::<Bn256, _, _>(&mut transcript),
+            *squeeze_challenge::<Bn256, _, _>(&mut resumed)
+        );
+        assert!(transcript.common_point(G1Affine::default()).is_err());
+
+        // The default `write_points()` checks every point before it writes
+        // any of them.
+        let before = transcript.state();
+        assert!(transcript
+            .write_points(&[G1Affine::random(OsRng), G1Affine::default()])
+            .is_err());
+        assert_eq!(transcript.state(), before);
+        assert_eq!(transcript.into_writer().len(), 32);
+
+        assert!(matches!(
+            CustomWrite::init(201, vec![]),
+            Err(StateError::UnregisteredHasher(201))
+        ));
+        assert!(matches!(
+            CustomWrite::resume(vec![], &state[..state.len() - 1]),
+            Err(StateError::Hasher { id: 200, .. })
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_custom_transcript() {
+        register_custom(100, Box::new(ChainHasher::default()));
+        // The same hasher starting from another digest.
+        register_custom(101, Box::new(ChainHasher { digest: [1; 32] }));
+
+        let k = 4;
+        let s = Fr::from(2);
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let public_inputs = vec![constant * a.square() * b.square()];
+        let instances = vec![&public_inputs[..]];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let mut prover =
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Custom as u8, k, &s);
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut transcript = CustomWrite::init(100, vec![]).unwrap();
+        tachyon_create_proof(
+            &mut prover,
+            &mut tachyon_pk,
+            &[circuit],
+            &[&instances[..]],
+            XORShiftRng::from_seed(SEED),
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let mut proof = transcript.finalize();
+        proof.extend_from_slice(&prover.get_proof());
+        // The backend absorbed through the hasher, which the state is read
+        // back from.
+        assert_eq!(prover.transcript_state()[1], 100);
+        assert!(!prover.verify_proof(&mut tachyon_pk, &[&instances[..]], &proof));
+
+        let verify = |id| {
+            let mut transcript = CustomRead::init(id, proof.as_slice()).unwrap();
+            verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+                params.verifier_params(),
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                &[&instances[..]],
+                &mut transcript,
+            )
+        };
+        assert!(verify(100).is_ok());
+        assert!(verify(101).is_err());
+    }
 }
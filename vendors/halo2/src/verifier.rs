@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use ff::PrimeField;
+use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    bn254::{GWCProver, ProvingKey as TachyonProvingKey},
+    error::TachyonError,
+    prover::check_multi_open_scheme,
+};
+
+/// Verifies GWC proofs of a circuit with the backend's verifier. GWC opens
+/// the instance columns, so the verifier reads no more of them than their
+/// commitments, which can be computed once for instances shared by many
+/// proofs, see [`VerifierInstanceCache`].
+pub struct VerifyingKey {
+    /// Only its params and transcript are used.
+    prover: GWCProver<KZGCommitmentScheme<Bn256>>,
+    pk: TachyonProvingKey<G1Affine>,
+}
+
+impl VerifyingKey {
+    /// `prover` has the params and the transcript of the proofs to verify.
+    /// Fails with [`TachyonError::MultiOpenMismatch`] if `pk` records that
+    /// its proofs are SHPlonk ones.
+    pub fn new(
+        prover: GWCProver<KZGCommitmentScheme<Bn256>>,
+        pk: TachyonProvingKey<G1Affine>,
+    ) -> Result<VerifyingKey, TachyonError> {
+        check_multi_open_scheme(&prover, &pk)?;
+        Ok(VerifyingKey { prover, pk })
+    }
+
+    /// See [`TachyonProvingKey::circuit_id()`].
+    pub fn circuit_id(&self) -> Option<[u8; 32]> {
+        self.pk.circuit_id()
+    }
+
+    pub fn num_instance_columns(&self) -> usize {
+        self.pk.num_instance_columns()
+    }
+
+    /// Commits to the instance columns of a circuit, as
+    /// [`VerifyingKey::verify_proof_with_precommitted_instances()`] takes them.
+    ///
+    /// # Panics
+    ///
+    /// If `instances` doesn't have every instance column of the circuit, or a
+    /// column has more values than usable rows.
+    pub fn commit_instances(&self, instances: &[&[Fr]]) -> Vec<G1Affine> {
+        assert_eq!(
+            instances.len(),
+            self.num_instance_columns(),
+            "every instance column should be given"
+        );
+        self.commit_columns(instances)
+    }
+
+    /// Verifies `proof`, a proof of `instances` with one set of instance
+    /// columns per circuit.
+    pub fn verify_proof(&mut self, instances: &[&[&[Fr]]], proof: &[u8]) -> bool {
+        self.prover.verify_proof(&mut self.pk, instances, proof)
+    }
+
+    /// Like [`VerifyingKey::verify_proof()`] with the commitments to the
+    /// instance columns of every circuit, e.g, from
+    /// [`VerifyingKey::commit_instances()`]. The proof fails if they commit to
+    /// other values than the ones it was created with.
+    pub fn verify_proof_with_precommitted_instances(
+        &mut self,
+        commitments: &[&[G1Affine]],
+        proof: &[u8],
+    ) -> bool {
+        self.prover
+            .verify_proof_with_instance_commitments(&mut self.pk, commitments, proof)
+    }
+
+    fn commit_columns(&self, columns: &[&[Fr]]) -> Vec<G1Affine> {
+        self.prover
+            .commit_instance_columns(&self.pk, columns)
+            .expect("an instance column has more values than usable rows")
+    }
+}
+
+/// The commitments to instance columns seen before, keyed by a hash of their
+/// values, for a verifier that sees the same instances in many proofs. They
+/// depend on the params, so keep a cache per [`VerifyingKey`].
+#[derive(Debug, Default)]
+pub struct VerifierInstanceCache {
+    commitments: HashMap<[u8; 32], G1Affine>,
+    hits: u64,
+    misses: u64,
+}
+
+impl VerifierInstanceCache {
+    pub fn new() -> VerifierInstanceCache {
+        VerifierInstanceCache::default()
+    }
+
+    /// Same as [`VerifyingKey::commit_instances()`], but only commits to the
+    /// columns that aren't in the cache and adds them.
+    pub fn commitments(&mut self, vk: &VerifyingKey, instances: &[&[Fr]]) -> Vec<G1Affine> {
+        assert_eq!(
+            instances.len(),
+            vk.num_instance_columns(),
+            "every instance column should be given"
+        );
+        let keys: Vec<[u8; 32]> = instances.iter().map(|column| column_key(column)).collect();
+        let missing: Vec<usize> = (0..instances.len())
+            .filter(|i| !self.commitments.contains_key(&keys[*i]))
+            .collect();
+        self.hits += (instances.len() - missing.len()) as u64;
+        self.misses += missing.len() as u64;
+        if !missing.is_empty() {
+            let columns: Vec<&[Fr]> = missing.iter().map(|i| instances[*i]).collect();
+            for (i, commitment) in missing.iter().zip(vk.commit_columns(&columns)) {
+                self.commitments.insert(keys[*i], commitment);
+            }
+        }
+        keys.iter().map(|key| self.commitments[key]).collect()
+    }
+
+    /// The columns that were found in the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The columns that had to be committed to.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn len(&self) -> usize {
+        self.commitments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commitments.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.commitments.clear();
+    }
+}
+
+// The length is hashed too, so that trailing zeros change the key even though
+// they don't change the commitment; a column with more values than usable
+// rows shouldn't hit one that fits.
+fn column_key(column: &[Fr]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"tachyon_halo2 instance commitment v1");
+    hasher.update((column.len() as u64).to_le_bytes());
+    for value in column {
+        hasher.update(value.to_repr().as_ref());
+    }
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::{Challenge255, TranscriptWriterBuffer},
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+    use rand_core::SeedableRng;
+
+    use super::{VerifierInstanceCache, VerifyingKey as TachyonVerifyingKey};
+    use crate::{
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, GWCProver, ProvingKey as TachyonProvingKey,
+            TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{MultiOpenScheme, TranscriptType, SEED},
+        error::TachyonError,
+        prover::create_proof,
+        proving_key::{multi_open_scheme_to_metadata, Metadata},
+        xor_shift_rng::XORShiftRng,
+    };
+
+    // Returns the key of `SimpleCircuit` and `num_proofs` proofs of it that
+    // share its instance, each with other randomness.
+    fn prove(num_proofs: usize) -> (TachyonVerifyingKey, Vec<Fr>, Vec<Vec<u8>>) {
+        let k = 4;
+        let s = Fr::from(2);
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let instances = vec![Fr::from(7 * 2 * 2 * 3 * 3)];
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut prover =
+            GWCProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+
+        let proofs = (0..num_proofs)
+            .map(|i| {
+                let mut seed = SEED;
+                seed[0] ^= i as u8;
+                seed[1] ^= (i >> 8) as u8;
+                let mut transcript = TachyonBlake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+                create_proof(
+                    &mut prover,
+                    &mut tachyon_pk,
+                    &[circuit.clone()],
+                    &[&[&instances[..]]],
+                    XORShiftRng::from_seed(seed),
+                    &mut transcript,
+                )
+                .expect("proof generation should not fail");
+                let mut proof = transcript.finalize();
+                proof.extend_from_slice(&prover.get_proof());
+                proof
+            })
+            .collect();
+        (
+            TachyonVerifyingKey::new(prover, tachyon_pk).unwrap(),
+            instances,
+            proofs,
+        )
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_verify_proof_with_precommitted_instances() {
+        let (mut vk, instances, proofs) = prove(1);
+        let proof = &proofs[0];
+        assert!(vk.verify_proof(&[&[&instances[..]]], proof));
+
+        let commitments = vk.commit_instances(&[&instances[..]]);
+        assert!(vk.verify_proof_with_precommitted_instances(&[&commitments[..]], proof));
+
+        // A commitment to another public input fails, as the values would.
+        let other = vec![instances[0] + Fr::one()];
+        assert!(!vk.verify_proof(&[&[&other[..]]], proof));
+        let wrong = vk.commit_instances(&[&other[..]]);
+        assert_ne!(wrong, commitments);
+        assert!(!vk.verify_proof_with_precommitted_instances(&[&wrong[..]], proof));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_instance_cache() {
+        let (mut vk, instances, proofs) = prove(2);
+        let mut cache = VerifierInstanceCache::new();
+        for proof in &proofs {
+            let commitments = cache.commitments(&vk, &[&instances[..]]);
+            assert_eq!(commitments, vk.commit_instances(&[&instances[..]]));
+            assert!(vk.verify_proof_with_precommitted_instances(&[&commitments[..]], proof));
+        }
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 1, 1));
+
+        let other = vec![instances[0] + Fr::one()];
+        cache.commitments(&vk, &[&other[..]]);
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 2, 2));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_multi_open_scheme_mismatch() {
+        let k = 4;
+        let s = Fr::from(2);
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &SimpleCircuit::<Fr>::default()).expect("pk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+        let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+        let mut metadata = Metadata::new();
+        multi_open_scheme_to_metadata(MultiOpenScheme::SHPlonk, &mut metadata);
+        tachyon_pk.set_metadata(metadata);
+        let prover =
+            GWCProver::<KZGCommitmentScheme<Bn256>>::new(TranscriptType::Blake2b as u8, k, &s);
+
+        assert!(matches!(
+            TachyonVerifyingKey::new(prover, tachyon_pk),
+            Err(TachyonError::MultiOpenMismatch {
+                key: MultiOpenScheme::SHPlonk,
+                prover: MultiOpenScheme::Gwc,
+            })
+        ));
+    }
+
+    // Compares verifying proofs that share their instances with and without
+    // the cache. Run with:
+    //   cargo test --release -- --ignored bench_shared_instances --nocapture
+    #[test]
+    #[ignore]
+    fn bench_shared_instances() {
+        let (mut vk, instances, proofs) = prove(100);
+
+        let start = Instant::now();
+        for proof in &proofs {
+            assert!(vk.verify_proof(&[&[&instances[..]]], proof));
+        }
+        println!("values:       {:?}", start.elapsed());
+
+        let mut cache = VerifierInstanceCache::new();
+        let start = Instant::now();
+        for proof in &proofs {
+            let commitments = cache.commitments(&vk, &[&instances[..]]);
+            assert!(vk.verify_proof_with_precommitted_instances(&[&commitments[..]], proof));
+        }
+        println!("precommitted: {:?}", start.elapsed());
+        assert_eq!(cache.misses(), 1);
+    }
+}
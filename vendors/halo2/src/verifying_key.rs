@@ -0,0 +1,153 @@
+use std::io::{self, Write};
+
+use halo2_proofs::{
+    plonk::{Circuit, VerifyingKey},
+    SerdeFormat,
+};
+use halo2curves::bn256::{Fr, G1Affine};
+
+use crate::{
+    circuit_id::{circuit_id, circuit_id_from_metadata, circuit_id_to_metadata},
+    consts::MultiOpenScheme,
+    proving_key::{multi_open_scheme_from_metadata, split_metadata, write_with_metadata, Metadata},
+};
+
+// A verifying key file is laid out like a proving key file: the metadata
+// section of `crate::proving_key` in front of the bytes of halo2's
+// `VerifyingKey::write()`. The metadata is the one of the proving key, so
+// what the key records for its provers, e.g, the circuit id, travels to its
+// verifiers with it.
+
+/// A halo2 verifying key with the metadata of its proving key.
+#[derive(Debug)]
+pub struct TachyonVerifyingKey {
+    vk: VerifyingKey<G1Affine>,
+    metadata: Metadata,
+}
+
+impl TachyonVerifyingKey {
+    /// Wraps `vk`, recording its circuit id in `metadata`.
+    pub fn new(vk: VerifyingKey<G1Affine>, mut metadata: Metadata) -> Self {
+        circuit_id_to_metadata(&vk, &mut metadata);
+        TachyonVerifyingKey { vk, metadata }
+    }
+
+    pub fn vk(&self) -> &VerifyingKey<G1Affine> {
+        &self.vk
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// See [`crate::circuit_id::circuit_id()`].
+    pub fn circuit_id(&self) -> [u8; 32] {
+        circuit_id(&self.vk)
+    }
+
+    /// Returns the multi-open scheme of the proofs, if the proving key recorded
+    /// it with [`crate::proving_key::multi_open_scheme_to_metadata()`].
+    pub fn multi_open_scheme(&self) -> Option<MultiOpenScheme> {
+        multi_open_scheme_from_metadata(&self.metadata)
+    }
+
+    /// Writes the metadata followed by the key in `format`.
+    pub fn write<W: Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
+        let mut vk = vec![];
+        self.vk.write(&mut vk, format)?;
+        write_with_metadata(writer, &self.metadata, &vk)
+    }
+
+    /// Inverse of [`TachyonVerifyingKey::write()`] for a key of
+    /// `ConcreteCircuit`. A halo2 verifying key without metadata is read as
+    /// well. Fails if the metadata records another circuit id than the key
+    /// has, i.e, if it was put in front of the key of another circuit.
+    pub fn read<ConcreteCircuit: Circuit<Fr>>(
+        bytes: &[u8],
+        format: SerdeFormat,
+    ) -> io::Result<Self> {
+        let (metadata, mut vk) = split_metadata(bytes)?;
+        let vk = VerifyingKey::<G1Affine>::read::<_, ConcreteCircuit>(&mut vk, format)?;
+        if let Some(recorded) = circuit_id_from_metadata(&metadata) {
+            if recorded != circuit_id(&vk) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the metadata is for another circuit than the key",
+                ));
+            }
+        }
+        Ok(TachyonVerifyingKey::new(vk, metadata))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value, plonk::keygen_vk, poly::kzg::commitment::ParamsKZG, SerdeFormat,
+    };
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::TachyonVerifyingKey;
+    use crate::{
+        audit::to_hex,
+        circuit_id::{circuit_id, circuit_id_from_metadata, CIRCUIT_ID_METADATA_KEY},
+        circuits::{simple_circuit::SimpleCircuit, simple_lookup_circuit::SimpleLookupCircuit},
+        consts::MultiOpenScheme,
+        proving_key::{
+            multi_open_scheme_to_metadata, split_metadata, write_with_metadata, Metadata,
+        },
+    };
+
+    #[test]
+    fn test_write_and_read() {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(5, Fr::from(2));
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(2)),
+            b: Value::known(Fr::from(3)),
+        };
+        let vk = keygen_vk(&params, &circuit).expect("vk should not fail");
+        let mut metadata = Metadata::new();
+        metadata.insert("name".to_string(), "simple".to_string());
+        multi_open_scheme_to_metadata(MultiOpenScheme::Gwc, &mut metadata);
+        let vk = TachyonVerifyingKey::new(vk, metadata);
+        assert_eq!(
+            circuit_id_from_metadata(vk.metadata()),
+            Some(vk.circuit_id())
+        );
+
+        let mut bytes = vec![];
+        vk.write(&mut bytes, SerdeFormat::RawBytes).unwrap();
+        let read =
+            TachyonVerifyingKey::read::<SimpleCircuit<Fr>>(&bytes, SerdeFormat::RawBytes).unwrap();
+        assert_eq!(read.metadata(), vk.metadata());
+        assert_eq!(read.multi_open_scheme(), Some(MultiOpenScheme::Gwc));
+        assert_eq!(read.circuit_id(), vk.circuit_id());
+        assert_eq!(read.vk().transcript_repr(), vk.vk().transcript_repr());
+
+        // Without metadata, the circuit id is recorded on reading.
+        let (_, halo2_bytes) = split_metadata(&bytes).unwrap();
+        let read =
+            TachyonVerifyingKey::read::<SimpleCircuit<Fr>>(halo2_bytes, SerdeFormat::RawBytes)
+                .unwrap();
+        assert_eq!(read.multi_open_scheme(), None);
+        assert_eq!(
+            circuit_id_from_metadata(read.metadata()),
+            Some(vk.circuit_id())
+        );
+
+        // Metadata of another circuit is refused.
+        let lookup_vk =
+            keygen_vk(&params, &SimpleLookupCircuit::<Fr>::default()).expect("vk should not fail");
+        let mut metadata = vk.metadata().clone();
+        metadata.insert(
+            CIRCUIT_ID_METADATA_KEY.to_string(),
+            to_hex(&circuit_id(&lookup_vk)),
+        );
+        let mut bytes = vec![];
+        write_with_metadata(&mut bytes, &metadata, halo2_bytes).unwrap();
+        assert!(
+            TachyonVerifyingKey::read::<SimpleCircuit<Fr>>(&bytes, SerdeFormat::RawBytes).is_err()
+        );
+    }
+}
@@ -0,0 +1,14 @@
+#include "vendors/halo2/include/version.h"
+
+#include "tachyon/c/version.h"
+#include "vendors/halo2/include/dlopen.h"
+
+namespace tachyon::halo2_api {
+
+uint32_t abi_version() { return tachyon_get_abi_version(); }
+
+rust::String runtime_version() {
+  return rust::String(tachyon_get_runtime_full_version_str());
+}
+
+}  // namespace tachyon::halo2_api
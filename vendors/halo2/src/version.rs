@@ -0,0 +1,171 @@
+#[cfg(feature = "dlopen")]
+use std::path::PathBuf;
+
+use crate::{
+    backend_log::{set_backend_log, BackendLog},
+    error::TachyonError,
+    self_test::{ensure_self_test, skip_self_test},
+};
+
+#[cfg(feature = "stub-backend")]
+use crate::stub::version as ffi;
+
+#[cfg(not(feature = "stub-backend"))]
+#[cxx::bridge(namespace = "tachyon::halo2_api")]
+pub mod ffi {
+    unsafe extern "C++" {
+        include!("vendors/halo2/include/version.h");
+
+        fn abi_version() -> u32;
+        fn runtime_version() -> String;
+    }
+}
+
+/// The `TACHYON_C_ABI_VERSION` this crate was written against.
+pub const EXPECTED_ABI_VERSION: u32 = 1;
+
+/// The halo2 fork and revision the vendor layer mirrors. Keep this in sync
+/// with `Cargo.toml`.
+pub const HALO2_VERSION: &str = "kroma-network/halo2@be2e64f";
+
+pub(crate) const STUB_BACKEND_REASON: &str = "built with the \"stub-backend\" feature";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackendInfo {
+    pub abi_version: u32,
+    /// The full version of the linked libtachyon, e.g, "0.2.0-<commit>".
+    pub tachyon_version: String,
+    pub halo2_version: &'static str,
+}
+
+/// How [`init_with()`] finds libtachyon.
+#[derive(Clone, Debug, Default)]
+pub struct InitOptions {
+    /// The path libtachyon is loaded from, instead of `TACHYON_LIB_PATH`.
+    #[cfg(feature = "dlopen")]
+    pub library_path: Option<PathBuf>,
+    /// Doesn't run [`crate::self_test::self_test()`], here or before the
+    /// first prover is created, for the rest of the process. It only takes
+    /// effect if neither ran it yet; a result it already has stands. Nothing
+    /// else skips it.
+    pub skip_self_test: bool,
+    /// Where the messages libtachyon logs go from now on.
+    pub backend_log: BackendLog,
+}
+
+pub fn backend_info() -> BackendInfo {
+    BackendInfo {
+        abi_version: ffi::abi_version(),
+        tachyon_version: ffi::runtime_version(),
+        halo2_version: HALO2_VERSION,
+    }
+}
+
+pub(crate) fn check_abi_version(found: u32) -> Result<(), TachyonError> {
+    if found == EXPECTED_ABI_VERSION {
+        Ok(())
+    } else {
+        Err(TachyonError::AbiMismatch {
+            expected: EXPECTED_ABI_VERSION,
+            found,
+        })
+    }
+}
+
+/// Fails with [`TachyonError::BackendUnavailable`] if the crate was built with
+/// the "stub-backend" feature, which has no libtachyon to prove with, or with
+/// the "dlopen" feature and libtachyon can't be loaded.
+pub(crate) fn check_backend() -> Result<(), TachyonError> {
+    if cfg!(feature = "stub-backend") {
+        return Err(TachyonError::BackendUnavailable {
+            reason: STUB_BACKEND_REASON.to_owned(),
+        });
+    }
+    #[cfg(feature = "dlopen")]
+    crate::dlopen::load(None)?;
+    Ok(())
+}
+
+/// Checks that the linked libtachyon has the ABI and the layout of values this
+/// crate expects. Call this once at startup to get a typed error; the prover
+/// constructors check it too, and `new()` and `from_params()` panic where
+/// `try_new()` and `try_from_params()` fail.
+pub fn init() -> Result<BackendInfo, TachyonError> {
+    init_with(&InitOptions::default())
+}
+
+/// Like [`init()`], but with the "dlopen" feature, loads libtachyon from
+/// `options.library_path` if it isn't loaded yet.
+pub fn init_with(options: &InitOptions) -> Result<BackendInfo, TachyonError> {
+    #[cfg(feature = "dlopen")]
+    crate::dlopen::load(options.library_path.as_deref())?;
+    check_backend()?;
+    check_abi_version(ffi::abi_version())?;
+    set_backend_log(&options.backend_log);
+    if options.skip_self_test {
+        skip_self_test();
+    }
+    ensure_self_test()?;
+    Ok(backend_info())
+}
+
+/// Fails unless libtachyon is there and has the ABI and the layout of values
+/// this crate expects. The prover constructors call this.
+pub(crate) fn ensure_abi_version() -> Result<(), TachyonError> {
+    check_backend()?;
+    check_abi_version(ffi::abi_version())?;
+    ensure_self_test()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_abi_version, init, EXPECTED_ABI_VERSION, HALO2_VERSION};
+    use crate::error::TachyonError;
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_init() {
+        let info = init().unwrap();
+        assert_eq!(info.abi_version, EXPECTED_ABI_VERSION);
+        assert_eq!(info.halo2_version, HALO2_VERSION);
+        assert!(!info.tachyon_version.is_empty());
+    }
+
+    #[test]
+    fn test_abi_mismatch() {
+        match check_abi_version(EXPECTED_ABI_VERSION + 1) {
+            Err(TachyonError::AbiMismatch { expected, found }) => {
+                assert_eq!(expected, EXPECTED_ABI_VERSION);
+                assert_eq!(found, EXPECTED_ABI_VERSION + 1);
+            }
+            _ => panic!("expected AbiMismatch"),
+        }
+    }
+
+    #[cfg(feature = "stub-backend")]
+    #[test]
+    fn test_init_stub_backend() {
+        assert!(matches!(
+            init(),
+            Err(TachyonError::BackendUnavailable { .. })
+        ));
+    }
+
+    #[cfg(all(feature = "stub-backend", feature = "halo2"))]
+    #[test]
+    fn test_try_new_stub_backend() {
+        use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+        use halo2curves::bn256::{Bn256, Fr};
+
+        use crate::{bn254::SHPlonkProver, consts::TranscriptType};
+
+        assert!(matches!(
+            SHPlonkProver::<KZGCommitmentScheme<Bn256>>::try_new(
+                TranscriptType::Blake2b as u8,
+                4,
+                &Fr::from(2)
+            ),
+            Err(TachyonError::BackendUnavailable { .. })
+        ));
+    }
+}
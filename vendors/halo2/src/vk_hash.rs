@@ -0,0 +1,473 @@
+// The transcript of a proof starts with a scalar digest of the verifying key,
+// `transcript_repr`. Every mode hashes the same bytes as halo2, the `Debug`
+// output of `vk.pinned()` prefixed with its length (u64 LE):
+//
+//   blake2b_field        Blake2b-512 with the personalization
+//                        "Halo2-Verify-Key", reduced into Fr. This is halo2's.
+//   poseidon             the bytes in 31-byte chunks, each read as a
+//                        little-endian Fr, absorbed into a Poseidon transcript
+//                        and squeezed once.
+//   keccak256_truncated  the first 31 bytes of the Keccak-256 hash, read as a
+//                        little-endian Fr.
+//
+// Keys hashed like halo2 leave the digest to the backend. For the other modes,
+// `vk_hash_mode_to_metadata()` stores it in front of the proving key, and the
+// prover absorbs it instead, see `ProvingKey::transcript_repr_shplonk()`.
+
+use std::io;
+
+use ff::PrimeField;
+use halo2_proofs::{
+    plonk::{verify_proof as halo2_verify_proof, VerifyingKey},
+    poly::kzg::{
+        commitment::{KZGCommitmentScheme, ParamsKZG},
+        multiopen::VerifierSHPLONK,
+        strategy::SingleStrategy,
+    },
+    transcript::{
+        Challenge255, EncodedChallenge, PoseidonWrite, Transcript, TranscriptRead,
+        TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use halo2curves::{
+    bn256::{Bn256, Fr, G1Affine},
+    FieldExt,
+};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    audit::{from_hex, to_hex},
+    consts::VkHashMode,
+    error::TachyonError,
+    proving_key::Metadata,
+};
+
+/// The metadata key the vk hash mode of a key is stored under, see
+/// [`VkHashMode::name()`].
+pub const VK_HASH_MODE_METADATA_KEY: &str = "vk_hash_mode";
+
+/// The metadata key the digest of a key hashed with another mode than
+/// [`VkHashMode::Blake2bField`] is stored under, as its little-endian repr in
+/// hex.
+pub const VK_TRANSCRIPT_REPR_METADATA_KEY: &str = "vk_transcript_repr";
+
+const VK_PERSONALIZATION: &[u8; 16] = b"Halo2-Verify-Key";
+
+fn pinned_bytes(vk: &VerifyingKey<G1Affine>) -> Vec<u8> {
+    framed_bytes(&format!("{:?}", vk.pinned()))
+}
+
+fn framed_bytes(pinned: &str) -> Vec<u8> {
+    let mut bytes = (pinned.len() as u64).to_le_bytes().to_vec();
+    bytes.extend_from_slice(pinned.as_bytes());
+    bytes
+}
+
+/// Reads `bytes` as little-endian field elements of 31 bytes each, so that
+/// every chunk is canonical.
+fn field_chunks(bytes: &[u8]) -> Vec<Fr> {
+    bytes
+        .chunks(31)
+        .map(|chunk| {
+            let mut repr = [0u8; 32];
+            repr[..chunk.len()].copy_from_slice(chunk);
+            Fr::from_repr(repr).unwrap()
+        })
+        .collect()
+}
+
+fn hash_bytes(bytes: &[u8], mode: VkHashMode) -> Fr {
+    match mode {
+        VkHashMode::Blake2bField => {
+            let hash = blake2b_simd::Params::new()
+                .hash_length(64)
+                .personal(VK_PERSONALIZATION)
+                .hash(bytes);
+            Fr::from_bytes_wide(hash.as_array())
+        }
+        VkHashMode::Poseidon => {
+            let mut transcript =
+                PoseidonWrite::<_, G1Affine, Challenge255<_>>::init(Vec::<u8>::new());
+            for chunk in field_chunks(bytes) {
+                transcript
+                    .common_scalar(chunk)
+                    .expect("absorbing into a Poseidon transcript can't fail");
+            }
+            transcript.squeeze_challenge().get_scalar()
+        }
+        VkHashMode::Keccak256Truncated => {
+            let hash = Keccak256::digest(bytes);
+            let mut repr = [0u8; 32];
+            repr[..31].copy_from_slice(&hash[..31]);
+            Fr::from_repr(repr).unwrap()
+        }
+    }
+}
+
+/// Returns the scalar `vk` is absorbed as when hashed with `mode`. With
+/// [`VkHashMode::Blake2bField`], this is `vk.transcript_repr()`.
+pub fn transcript_repr(vk: &VerifyingKey<G1Affine>, mode: VkHashMode) -> Fr {
+    hash_bytes(&pinned_bytes(vk), mode)
+}
+
+/// Records that `vk` is hashed with `mode`, along with the resulting digest
+/// unless it is halo2's, so that provers using the key absorb it.
+pub fn vk_hash_mode_to_metadata(
+    mode: VkHashMode,
+    vk: &VerifyingKey<G1Affine>,
+    metadata: &mut Metadata,
+) {
+    metadata.insert(
+        VK_HASH_MODE_METADATA_KEY.to_string(),
+        mode.name().to_string(),
+    );
+    if mode == VkHashMode::Blake2bField {
+        metadata.remove(VK_TRANSCRIPT_REPR_METADATA_KEY);
+    } else {
+        metadata.insert(
+            VK_TRANSCRIPT_REPR_METADATA_KEY.to_string(),
+            to_hex(transcript_repr(vk, mode).to_repr().as_ref()),
+        );
+    }
+}
+
+/// Parses the mode stored by [`vk_hash_mode_to_metadata()`], defaulting to
+/// [`VkHashMode::Blake2bField`].
+pub fn vk_hash_mode_from_metadata(metadata: &Metadata) -> VkHashMode {
+    metadata
+        .get(VK_HASH_MODE_METADATA_KEY)
+        .and_then(|name| VkHashMode::from_name(name))
+        .unwrap_or(VkHashMode::Blake2bField)
+}
+
+/// Parses the digest stored by [`vk_hash_mode_to_metadata()`], if the key
+/// isn't hashed like halo2.
+pub(crate) fn vk_transcript_repr_from_metadata<F: PrimeField>(metadata: &Metadata) -> Option<F> {
+    if vk_hash_mode_from_metadata(metadata) == VkHashMode::Blake2bField {
+        return None;
+    }
+    let bytes = from_hex(metadata.get(VK_TRANSCRIPT_REPR_METADATA_KEY)?)?;
+    let mut repr = F::Repr::default();
+    if repr.as_ref().len() != bytes.len() {
+        return None;
+    }
+    repr.as_mut().copy_from_slice(&bytes);
+    Option::from(F::from_repr(repr))
+}
+
+/// Wraps the transcript of a verifier so that it absorbs `repr` in place of
+/// the digest of the verifying key halo2's verifier computes.
+#[derive(Debug)]
+pub struct VkReprRead<T> {
+    inner: T,
+    repr: Fr,
+    absorbed_vk: bool,
+}
+
+impl<T: TranscriptRead<G1Affine, Challenge255<G1Affine>>> VkReprRead<T> {
+    pub fn new(inner: T, repr: Fr) -> Self {
+        VkReprRead {
+            inner,
+            repr,
+            absorbed_vk: false,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: TranscriptRead<G1Affine, Challenge255<G1Affine>>>
+    Transcript<G1Affine, Challenge255<G1Affine>> for VkReprRead<T>
+{
+    fn squeeze_challenge(&mut self) -> Challenge255<G1Affine> {
+        self.inner.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: G1Affine) -> io::Result<()> {
+        self.inner.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        // The first scalar is the verifying key.
+        if !self.absorbed_vk {
+            self.absorbed_vk = true;
+            return self.inner.common_scalar(self.repr);
+        }
+        self.inner.common_scalar(scalar)
+    }
+}
+
+impl<T: TranscriptRead<G1Affine, Challenge255<G1Affine>>>
+    TranscriptRead<G1Affine, Challenge255<G1Affine>> for VkReprRead<T>
+{
+    fn read_point(&mut self) -> io::Result<G1Affine> {
+        self.inner.read_point()
+    }
+
+    fn read_scalar(&mut self) -> io::Result<Fr> {
+        self.inner.read_scalar()
+    }
+}
+
+fn verify_with_repr<'a, T: TranscriptReadBuffer<&'a [u8], G1Affine, Challenge255<G1Affine>>>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    repr: Fr,
+    instances: &[&[&[Fr]]],
+    proof: &'a [u8],
+) -> Result<(), halo2_proofs::plonk::Error> {
+    halo2_verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+        params,
+        vk,
+        SingleStrategy::new(params),
+        instances,
+        &mut VkReprRead::new(T::init(proof), repr),
+    )
+    .map(|_| ())
+}
+
+/// Verifies a SHPlonk proof, hashing the verifying key with `mode`. This
+/// fails with [`TachyonError::VkHashModeMismatch`] if `key_metadata` records
+/// another mode. The digest isn't part of the proof, so a prover that hashed
+/// the key differently only shows as a proof that doesn't verify. In that
+/// case the other modes are tried, and if one of them verifies, this fails
+/// with [`TachyonError::VkReprMismatch`] naming it. `T` is the transcript the
+/// proof was created with, e.g, `Blake2bRead<_, _, _>`.
+pub fn verify_proof<'a, T: TranscriptReadBuffer<&'a [u8], G1Affine, Challenge255<G1Affine>>>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    key_metadata: &Metadata,
+    mode: VkHashMode,
+    instances: &[&[&[Fr]]],
+    proof: &'a [u8],
+) -> Result<(), TachyonError> {
+    let key = vk_hash_mode_from_metadata(key_metadata);
+    if key != mode {
+        return Err(TachyonError::VkHashModeMismatch {
+            key,
+            verifier: mode,
+        });
+    }
+    let pinned = pinned_bytes(vk);
+    let verify = |candidate| {
+        verify_with_repr::<T>(params, vk, hash_bytes(&pinned, candidate), instances, proof)
+    };
+    let e = match verify(mode) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+    match VkHashMode::ALL
+        .iter()
+        .find(|other| **other != mode && verify(**other).is_ok())
+    {
+        Some(likely) => Err(TachyonError::VkReprMismatch {
+            verifier: mode,
+            likely: *likely,
+        }),
+        None => Err(TachyonError::Verification(e)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ff::PrimeField;
+    use halo2_proofs::{
+        circuit::Value,
+        plonk::keygen_pk2,
+        poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG},
+        transcript::{
+            Blake2bRead, Challenge255, EncodedChallenge, Transcript, TranscriptWriterBuffer,
+        },
+    };
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::SeedableRng;
+
+    use super::{
+        field_chunks, framed_bytes, hash_bytes, transcript_repr, verify_proof,
+        vk_hash_mode_to_metadata, vk_transcript_repr_from_metadata,
+    };
+    use crate::{
+        audit::to_hex,
+        bn254::{
+            Blake2bWrite as TachyonBlake2bWrite, PoseidonWrite as TachyonPoseidonWrite,
+            ProvingKey as TachyonProvingKey, SHPlonkProver, TachyonProver,
+        },
+        circuits::simple_circuit::SimpleCircuit,
+        consts::{TranscriptType, VkHashMode, SEED},
+        error::TachyonError,
+        prover::create_proof,
+        proving_key::Metadata,
+        xor_shift_rng::XORShiftRng,
+    };
+
+    fn circuit() -> (SimpleCircuit<Fr>, Vec<Fr>) {
+        let constant = Fr::from(7);
+        let a = Fr::from(2);
+        let b = Fr::from(3);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        (circuit, vec![constant * a.square() * b.square()])
+    }
+
+    // The vectors are hashes of fixed bytes, since the `Debug` output of a
+    // pinned key isn't something to freeze in a test.
+    #[test]
+    fn test_vectors() {
+        let bytes = framed_bytes("tachyon_halo2 vk hash vector");
+        assert_eq!(
+            to_hex(
+                hash_bytes(&bytes, VkHashMode::Blake2bField)
+                    .to_repr()
+                    .as_ref()
+            ),
+            "33861b3804b06c793e7fa35a628dd69fc52fc64a81d3f548d325b8e927f9d91b"
+        );
+        assert_eq!(
+            to_hex(
+                hash_bytes(&bytes, VkHashMode::Keccak256Truncated)
+                    .to_repr()
+                    .as_ref()
+            ),
+            "ae57a416d12f54272719bcd2651cea1800c625b452c83c7c0a098659578a7a00"
+        );
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(4, Fr::from(2));
+        let pk = keygen_pk2(&params, &circuit().0).expect("vk should not fail");
+        let vk = pk.get_vk();
+        assert_eq!(
+            transcript_repr(vk, VkHashMode::Blake2bField),
+            vk.transcript_repr()
+        );
+        let reprs = VkHashMode::ALL
+            .iter()
+            .map(|mode| transcript_repr(vk, *mode))
+            .collect::<Vec<_>>();
+        assert_ne!(reprs[0], reprs[1]);
+        assert_ne!(reprs[0], reprs[2]);
+        assert_ne!(reprs[1], reprs[2]);
+
+        let mut metadata = Metadata::new();
+        vk_hash_mode_to_metadata(VkHashMode::Keccak256Truncated, vk, &mut metadata);
+        assert_eq!(
+            vk_transcript_repr_from_metadata::<Fr>(&metadata),
+            Some(reprs[2])
+        );
+        // A key hashed like halo2 leaves the digest to the backend.
+        vk_hash_mode_to_metadata(VkHashMode::Blake2bField, vk, &mut metadata);
+        assert_eq!(vk_transcript_repr_from_metadata::<Fr>(&metadata), None);
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_poseidon_matches_backend() {
+        let bytes = framed_bytes("tachyon_halo2 vk hash vector");
+        let mut transcript = TachyonPoseidonWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        for chunk in field_chunks(&bytes) {
+            transcript.common_scalar(chunk).unwrap();
+        }
+        assert_eq!(
+            transcript.squeeze_challenge().get_scalar(),
+            hash_bytes(&bytes, VkHashMode::Poseidon)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_vk_hash_modes() {
+        let k = 4;
+        let s = Fr::from(2);
+        let (circuit, public_inputs) = circuit();
+        let instances = [&[&public_inputs[..]][..]];
+
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let pk = keygen_pk2(&params, &circuit).expect("vk should not fail");
+        let mut pk_bytes: Vec<u8> = vec![];
+        pk.write(&mut pk_bytes, halo2_proofs::SerdeFormat::RawBytesUnchecked)
+            .unwrap();
+
+        let metadata = |mode| {
+            let mut metadata = Metadata::new();
+            vk_hash_mode_to_metadata(mode, pk.get_vk(), &mut metadata);
+            metadata
+        };
+        let prove = |metadata: &Metadata| {
+            let mut prover = SHPlonkProver::<KZGCommitmentScheme<Bn256>>::new(
+                TranscriptType::Blake2b as u8,
+                k,
+                &s,
+            );
+            let mut tachyon_pk = TachyonProvingKey::try_from(pk_bytes.as_slice()).unwrap();
+            tachyon_pk.set_metadata(metadata.clone());
+            assert_eq!(
+                prover.transcript_repr(&mut tachyon_pk),
+                transcript_repr(pk.get_vk(), tachyon_pk.vk_hash_mode())
+            );
+            let mut transcript = TachyonBlake2bWrite::init(vec![]);
+            create_proof(
+                &mut prover,
+                &mut tachyon_pk,
+                &[circuit.clone()],
+                &instances,
+                XORShiftRng::from_seed(SEED),
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            let mut proof = transcript.finalize();
+            proof.extend_from_slice(&prover.get_proof());
+            proof
+        };
+        let verify = |metadata: &Metadata, mode, proof: &[u8]| {
+            verify_proof::<Blake2bRead<_, _, _>>(
+                &params,
+                pk.get_vk(),
+                metadata,
+                mode,
+                &instances,
+                proof,
+            )
+        };
+
+        for mode in VkHashMode::ALL {
+            let metadata = metadata(mode);
+            let proof = prove(&metadata);
+            assert!(verify(&metadata, mode, &proof).is_ok());
+        }
+
+        // A verifier configured for another mode refuses the key.
+        let keccak = metadata(VkHashMode::Keccak256Truncated);
+        let keccak_proof = prove(&keccak);
+        assert!(matches!(
+            verify(&keccak, VkHashMode::Blake2bField, &keccak_proof),
+            Err(TachyonError::VkHashModeMismatch {
+                key: VkHashMode::Keccak256Truncated,
+                verifier: VkHashMode::Blake2bField,
+            })
+        ));
+        // A key that lies about its mode is caught by the proof, which names
+        // the mode it was created with.
+        assert!(matches!(
+            verify(
+                &metadata(VkHashMode::Blake2bField),
+                VkHashMode::Blake2bField,
+                &keccak_proof
+            ),
+            Err(TachyonError::VkReprMismatch {
+                verifier: VkHashMode::Blake2bField,
+                likely: VkHashMode::Keccak256Truncated,
+            })
+        ));
+        // A proof that doesn't verify with any mode is just that.
+        let mut broken = keccak_proof;
+        let last = broken.len() - 1;
+        broken[last] ^= 1;
+        assert!(matches!(
+            verify(&keccak, VkHashMode::Keccak256Truncated, &broken),
+            Err(TachyonError::Verification(_))
+        ));
+    }
+}
@@ -3,6 +3,7 @@
 #include <string.h>
 
 #include "tachyon/base/logging.h"
+#include "vendors/halo2/include/dlopen.h"
 
 namespace tachyon::halo2_api {
 
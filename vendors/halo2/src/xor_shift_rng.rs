@@ -1,3 +1,16 @@
+use std::fmt;
+
+use hkdf::Hkdf;
+use rand_core::SeedableRng;
+use sha2::Sha256;
+
+#[cfg(feature = "stub-backend")]
+use crate::stub::{xor_shift_rng as ffi, UniquePtr};
+use crate::{redact::Redacted, state::versioned};
+#[cfg(not(feature = "stub-backend"))]
+use cxx::UniquePtr;
+
+#[cfg(not(feature = "stub-backend"))]
 #[cxx::bridge(namespace = "tachyon::halo2_api")]
 pub mod ffi {
     unsafe extern "C++" {
@@ -13,12 +26,33 @@ pub mod ffi {
 }
 
 pub struct XORShiftRng {
-    inner: cxx::UniquePtr<ffi::XORShiftRng>,
+    inner: UniquePtr<ffi::XORShiftRng>,
+}
+
+// The state determines the blinds of a proof, so it is redacted.
+impl fmt::Debug for XORShiftRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("XORShiftRng").field(&Redacted(())).finish()
+    }
 }
 
 impl XORShiftRng {
+    /// The state to continue from with `TachyonProver::set_rng()`, versioned
+    /// like [`crate::state::STATE_VERSION`] says.
     pub fn state(&self) -> Vec<u8> {
-        self.inner.state()
+        versioned(self.inner.state())
+    }
+
+    /// Returns an RNG seeded with HKDF-SHA256 of the state of this one and
+    /// `label`, leaving this one as is. The same label always gives the same
+    /// fork, and forks with different labels don't share randomness, so two
+    /// attempts at a proof can each take one.
+    pub fn fork_with_label(&self, label: &[u8]) -> Self {
+        let mut seed = [0u8; 16];
+        Hkdf::<Sha256>::new(None, &self.inner.state())
+            .expand(label, &mut seed)
+            .expect("seed should be shorter than 255 hashes");
+        Self::from_seed(seed)
     }
 }
 
@@ -68,6 +102,7 @@ mod test {
     use crate::consts::SEED;
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_rng() {
         let mut rng = rand_xorshift::XorShiftRng::from_seed(SEED);
         let mut rng_tachyon = crate::xor_shift_rng::XORShiftRng::from_seed(SEED);
@@ -79,6 +114,7 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_clone() {
         let mut rng = crate::xor_shift_rng::XORShiftRng::from_seed(SEED);
         let mut rng_clone = rng.clone();
@@ -90,11 +126,39 @@ mod test {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
+    fn test_fork_with_label() {
+        let mut rng = crate::xor_shift_rng::XORShiftRng::from_seed(SEED);
+        let state = rng.state();
+        let mut a = rng.fork_with_label(b"a");
+        let mut a_again = rng.fork_with_label(b"a");
+        let mut b = rng.fork_with_label(b"b");
+        // Forking doesn't advance the parent.
+        assert_eq!(rng.state(), state);
+
+        const LEN: i32 = 100;
+        let random_u64s_a = (0..LEN).map(|_| a.next_u64()).collect::<Vec<_>>();
+        assert_eq!(
+            random_u64s_a,
+            (0..LEN).map(|_| a_again.next_u64()).collect::<Vec<_>>()
+        );
+        assert_ne!(
+            random_u64s_a,
+            (0..LEN).map(|_| b.next_u64()).collect::<Vec<_>>()
+        );
+        assert_ne!(
+            random_u64s_a,
+            (0..LEN).map(|_| rng.next_u64()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "native-tests"), ignore)]
     fn test_state() {
         let rng = crate::xor_shift_rng::XORShiftRng::from_seed(SEED);
         assert_eq!(
             rng.state(),
-            vec![89, 98, 190, 93, 118, 61, 49, 141, 23, 219, 55, 50, 84, 6, 188, 229]
+            vec![1, 89, 98, 190, 93, 118, 61, 49, 141, 23, 219, 55, 50, 84, 6, 188, 229]
         );
     }
 }